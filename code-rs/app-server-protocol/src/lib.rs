@@ -19,8 +19,12 @@ pub use code_protocol::mcp_protocol::ConversationId;
 pub use code_protocol::mcp_protocol::EXEC_COMMAND_APPROVAL_METHOD;
 pub use code_protocol::mcp_protocol::ExecCommandApprovalParams;
 pub use code_protocol::mcp_protocol::ExecCommandApprovalResponse;
+pub use code_protocol::mcp_protocol::ForkConversationParams;
+pub use code_protocol::mcp_protocol::ForkConversationResponse;
 pub use code_protocol::mcp_protocol::GetAuthStatusParams;
 pub use code_protocol::mcp_protocol::GetAuthStatusResponse;
+pub use code_protocol::mcp_protocol::GetConversationItemsParams;
+pub use code_protocol::mcp_protocol::GetConversationItemsResponse;
 pub use code_protocol::mcp_protocol::GetUserAgentResponse;
 pub use code_protocol::mcp_protocol::GetUserSavedConfigResponse;
 pub use code_protocol::mcp_protocol::GitDiffToRemoteParams;
@@ -37,6 +41,13 @@ pub use code_protocol::mcp_protocol::LoginChatGptCompleteNotification;
 pub use code_protocol::mcp_protocol::LoginChatGptResponse;
 pub use code_protocol::mcp_protocol::LogoutChatGptParams;
 pub use code_protocol::mcp_protocol::LogoutChatGptResponse;
+pub use code_protocol::mcp_protocol::MCP_ELICITATION_METHOD;
+pub use code_protocol::mcp_protocol::MCP_SAMPLING_APPROVAL_METHOD;
+pub use code_protocol::mcp_protocol::McpElicitationAction;
+pub use code_protocol::mcp_protocol::McpElicitationParams;
+pub use code_protocol::mcp_protocol::McpElicitationResponse;
+pub use code_protocol::mcp_protocol::McpSamplingApprovalParams;
+pub use code_protocol::mcp_protocol::McpSamplingApprovalResponse;
 pub use code_protocol::mcp_protocol::NewConversationParams;
 pub use code_protocol::mcp_protocol::NewConversationResponse;
 pub use code_protocol::mcp_protocol::Profile;
@@ -44,6 +55,8 @@ pub use code_protocol::mcp_protocol::RemoveConversationListenerParams;
 pub use code_protocol::mcp_protocol::RemoveConversationSubscriptionResponse;
 pub use code_protocol::mcp_protocol::ResumeConversationParams;
 pub use code_protocol::mcp_protocol::ResumeConversationResponse;
+pub use code_protocol::mcp_protocol::RollbackConversationParams;
+pub use code_protocol::mcp_protocol::RollbackConversationResponse;
 pub use code_protocol::mcp_protocol::SandboxSettings;
 pub use code_protocol::mcp_protocol::SendUserMessageParams;
 pub use code_protocol::mcp_protocol::SendUserMessageResponse;
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use code_common::CliConfigOverrides;
+
+use crate::transport::ListenAddress;
+
+/// Environment variable holding the token socket clients must present.
+pub const AUTH_TOKEN_ENV_VAR: &str = "CODE_APP_SERVER_TOKEN";
+
+#[derive(Debug, Parser)]
+pub struct AppServerCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Where to accept clients: `stdio`, `ws://HOST:PORT`, or `unix:PATH`.
+    #[arg(long = "listen", value_name = "ADDRESS", default_value = "stdio")]
+    pub listen: ListenAddress,
+
+    /// Read the client auth token from this file instead of
+    /// `$CODE_APP_SERVER_TOKEN`. Required for `ws://` listeners.
+    #[arg(long = "auth-token-file", value_name = "FILE")]
+    pub auth_token_file: Option<PathBuf>,
+}
+
+impl AppServerCli {
+    /// Resolves the token socket clients must present, if any.
+    pub(crate) fn auth_token(&self) -> std::io::Result<Option<String>> {
+        let token = match &self.auth_token_file {
+            Some(path) => Some(std::fs::read_to_string(path)?),
+            None => std::env::var(AUTH_TOKEN_ENV_VAR).ok(),
+        };
+        Ok(token
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty()))
+    }
+}
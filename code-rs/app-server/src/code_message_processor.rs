@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -9,8 +10,14 @@ use code_core::AuthManager;
 use code_core::BeaconConversation;
 use code_core::ConversationManager;
 use code_core::NewConversation;
+use code_core::RolloutRecorder;
+use code_core::RolloutSettings;
+use code_core::SESSIONS_SUBDIR;
+use code_core::archive_rollout;
 use code_core::config::Config;
 use code_core::config::ConfigOverrides;
+use code_core::config::load_config_as_toml_with_cli_overrides;
+use code_core::find_conversation_path_by_id_str;
 use code_core::git_info::git_diff_to_remote;
 use code_core::protocol::ApplyPatchApprovalRequestEvent;
 use code_core::protocol::Event;
@@ -18,16 +25,23 @@ use code_core::protocol::EventMsg;
 use code_core::protocol::ExecApprovalRequestEvent;
 use code_protocol::mcp_protocol::FuzzyFileSearchParams;
 use code_protocol::mcp_protocol::FuzzyFileSearchResponse;
+use code_protocol::models::ResponseItem;
 use code_protocol::protocol::ReviewDecision;
 use mcp_types::JSONRPCErrorError;
 use mcp_types::RequestId;
 use tokio::sync::Mutex;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use tracing::error;
 use tracing::warn;
 use uuid::Uuid;
 
+use crate::conversation_hub::ApprovalSink;
+use crate::conversation_hub::ClientRequestEvent;
+use crate::conversation_hub::ConversationHub;
 use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::fuzzy_file_search::run_fuzzy_file_search;
@@ -40,13 +54,25 @@ use code_protocol::mcp_protocol::APPLY_PATCH_APPROVAL_METHOD;
 use code_protocol::mcp_protocol::AddConversationListenerParams;
 use code_protocol::mcp_protocol::AddConversationSubscriptionResponse;
 use code_protocol::mcp_protocol::ApplyPatchApprovalParams;
+use code_protocol::mcp_protocol::ArchiveConversationParams;
+use code_protocol::mcp_protocol::ArchiveConversationResponse;
 use code_protocol::mcp_protocol::ClientRequest;
 use code_protocol::mcp_protocol::ConversationId;
 use code_protocol::mcp_protocol::EXEC_COMMAND_APPROVAL_METHOD;
 use code_protocol::mcp_protocol::ExecCommandApprovalParams;
+use code_protocol::mcp_protocol::ForkConversationParams;
+use code_protocol::mcp_protocol::ForkConversationResponse;
+use code_protocol::mcp_protocol::GetConversationItemsParams;
+use code_protocol::mcp_protocol::GetConversationItemsResponse;
 use code_protocol::mcp_protocol::InputItem as WireInputItem;
 use code_protocol::mcp_protocol::InterruptConversationParams;
 use code_protocol::mcp_protocol::InterruptConversationResponse;
+use code_protocol::mcp_protocol::MCP_ELICITATION_METHOD;
+use code_protocol::mcp_protocol::MCP_SAMPLING_APPROVAL_METHOD;
+use code_protocol::mcp_protocol::McpElicitationAction as WireElicitationAction;
+use code_protocol::mcp_protocol::McpElicitationParams;
+use code_protocol::mcp_protocol::McpElicitationResponse;
+use code_protocol::mcp_protocol::McpSamplingApprovalParams;
 use code_protocol::protocol::TurnAbortReason;
 use code_utils_json_to_toml::json_to_toml;
 // Unused login-related and diff param imports removed
@@ -55,6 +81,8 @@ use code_protocol::mcp_protocol::NewConversationParams;
 use code_protocol::mcp_protocol::NewConversationResponse;
 use code_protocol::mcp_protocol::RemoveConversationListenerParams;
 use code_protocol::mcp_protocol::RemoveConversationSubscriptionResponse;
+use code_protocol::mcp_protocol::RollbackConversationParams;
+use code_protocol::mcp_protocol::RollbackConversationResponse;
 use code_protocol::mcp_protocol::SendUserMessageParams;
 use code_protocol::mcp_protocol::SendUserMessageResponse;
 use code_protocol::mcp_protocol::SendUserTurnParams;
@@ -62,6 +90,16 @@ use code_protocol::mcp_protocol::SendUserTurnResponse;
 
 const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
 
+/// Notification sent when the server ends a conversation subscription, e.g.
+/// because the client could not keep up with the event stream.
+const SUBSCRIPTION_CLOSED_METHOD: &str = "codex/subscriptionClosed";
+
+/// How long archiving waits for a running conversation to shut down.
+const ARCHIVE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Page size for `getConversationItems` when the client does not pass one.
+const DEFAULT_ITEMS_PAGE_SIZE: usize = 100;
+
 // Removed deprecated ChatGPT login support scaffolding
 
 /// Handles JSON-RPC messages for Beacon Code conversations.
@@ -72,6 +110,12 @@ pub struct CodexMessageProcessor {
     code_linux_sandbox_exe: Option<PathBuf>,
     _config: Arc<Config>,
     conversation_listeners: HashMap<Uuid, oneshot::Sender<()>>,
+    conversation_hub: Arc<ConversationHub>,
+    /// Where the hub sends approval requests this client has to answer.
+    approval_sink: ApprovalSink,
+    /// Answers approval requests; aborted when the connection closes so the
+    /// hub can route later requests to another client.
+    approval_task: JoinHandle<()>,
     // Queue of pending interrupt requests per conversation. We reply when TurnAborted arrives.
     pending_interrupts: Arc<Mutex<HashMap<Uuid, Vec<RequestId>>>>,
     #[allow(dead_code)]
@@ -86,6 +130,14 @@ impl CodexMessageProcessor {
         code_linux_sandbox_exe: Option<PathBuf>,
         config: Arc<Config>,
     ) -> Self {
+        let pending_interrupts = Arc::new(Mutex::new(HashMap::new()));
+        let (approval_tx, approval_rx) = mpsc::unbounded_channel();
+        let approval_task = tokio::spawn(answer_client_requests(
+            approval_rx,
+            conversation_manager.clone(),
+            outgoing.clone(),
+            pending_interrupts.clone(),
+        ));
         Self {
             _auth_manager: auth_manager,
             conversation_manager,
@@ -93,11 +145,20 @@ impl CodexMessageProcessor {
             code_linux_sandbox_exe,
             _config: config,
             conversation_listeners: HashMap::new(),
-            pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
+            conversation_hub: Arc::new(ConversationHub::default()),
+            approval_sink: ApprovalSink::new(approval_tx),
+            approval_task,
+            pending_interrupts,
             pending_fuzzy_searches: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Shares conversation subscriptions with other client connections.
+    pub(crate) fn with_conversation_hub(mut self, conversation_hub: Arc<ConversationHub>) -> Self {
+        self.conversation_hub = conversation_hub;
+        self
+    }
+
     pub async fn process_request(&mut self, request: ClientRequest) {
         match request {
             ClientRequest::Initialize { .. } => {
@@ -161,6 +222,18 @@ impl CodexMessageProcessor {
             ClientRequest::GitDiffToRemote { request_id, params } => {
                 self.git_diff_to_origin(request_id, params.cwd).await;
             }
+            ClientRequest::ForkConversation { request_id, params } => {
+                self.fork_conversation(request_id, params).await;
+            }
+            ClientRequest::RollbackConversation { request_id, params } => {
+                self.rollback_conversation(request_id, params).await;
+            }
+            ClientRequest::GetConversationItems { request_id, params } => {
+                self.get_conversation_items(request_id, params).await;
+            }
+            ClientRequest::ArchiveConversation { request_id, params } => {
+                self.archive_conversation(request_id, params).await;
+            }
             _ => { /* ignore unsupported methods */ }
         }
     }
@@ -208,6 +281,340 @@ impl CodexMessageProcessor {
         }
     }
 
+    async fn fork_conversation(&self, request_id: RequestId, params: ForkConversationParams) {
+        let ForkConversationParams {
+            conversation_id,
+            at_turn,
+            overrides,
+        } = params;
+        let (path, config) = match self
+            .prepare_history_rewrite(conversation_id, overrides)
+            .await
+        {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        match self
+            .conversation_manager
+            .fork_conversation_at_turn(at_turn, config, path)
+            .await
+        {
+            Ok(NewConversation {
+                conversation_id,
+                session_configured,
+                ..
+            }) => {
+                let response = ForkConversationResponse {
+                    conversation_id,
+                    model: session_configured.model,
+                    rollout_path: self.rollout_path_or_sessions_root(conversation_id).await,
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("error forking conversation: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    async fn rollback_conversation(
+        &self,
+        request_id: RequestId,
+        params: RollbackConversationParams,
+    ) {
+        let RollbackConversationParams {
+            conversation_id,
+            turns,
+            overrides,
+        } = params;
+        let (path, config) = match self
+            .prepare_history_rewrite(conversation_id, overrides)
+            .await
+        {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let new_conversation = match self
+            .conversation_manager
+            .fork_conversation(turns, config, path)
+            .await
+        {
+            Ok(new_conversation) => new_conversation,
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("error rolling back conversation: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        // The rolled-back copy replaces the original, which keeps its rollout
+        // on disk. Its subscribers learn the new id when it shuts down.
+        self.conversation_hub
+            .mark_replaced(conversation_id, new_conversation.conversation_id);
+        if let Some(previous) = self
+            .conversation_manager
+            .remove_conversation(&conversation_id)
+            .await
+        {
+            let _ = previous.submit(Op::Shutdown).await;
+        }
+
+        let NewConversation {
+            conversation_id,
+            session_configured,
+            ..
+        } = new_conversation;
+        let response = RollbackConversationResponse {
+            conversation_id,
+            model: session_configured.model,
+            rollout_path: self.rollout_path_or_sessions_root(conversation_id).await,
+        };
+        self.outgoing.send_response(request_id, response).await;
+    }
+
+    async fn get_conversation_items(
+        &self,
+        request_id: RequestId,
+        params: GetConversationItemsParams,
+    ) {
+        let GetConversationItemsParams {
+            conversation_id,
+            cursor,
+            limit,
+        } = params;
+        let path = match self.find_rollout_path(conversation_id).await {
+            Ok(path) => path,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        let items = match RolloutRecorder::get_rollout_response_items(&path).await {
+            Ok(items) => items,
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("error reading conversation items: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        match page_items(items, cursor.as_deref(), limit) {
+            Ok((items, next_cursor)) => {
+                let response = GetConversationItemsResponse { items, next_cursor };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(message) => {
+                let error = JSONRPCErrorError {
+                    code: INVALID_REQUEST_ERROR_CODE,
+                    message,
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    async fn archive_conversation(&self, request_id: RequestId, params: ArchiveConversationParams) {
+        let ArchiveConversationParams {
+            conversation_id,
+            rollout_path,
+        } = params;
+        let rollout_path = match self
+            .validate_rollout_path(conversation_id, &rollout_path)
+            .await
+        {
+            Ok(path) => path,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        if let Some(conversation) = self
+            .conversation_manager
+            .remove_conversation(&conversation_id)
+            .await
+        {
+            self.shut_down_conversation(conversation_id, conversation)
+                .await;
+        }
+
+        match archive_rollout(&self._config.code_home, &rollout_path).await {
+            Ok(_) => {
+                self.outgoing
+                    .send_response(request_id, ArchiveConversationResponse {})
+                    .await;
+            }
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("failed to archive conversation: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    /// Checks that `rollout_path` is the rollout of `conversation_id` inside
+    /// the sessions directory and returns its canonical path.
+    async fn validate_rollout_path(
+        &self,
+        conversation_id: ConversationId,
+        rollout_path: &Path,
+    ) -> Result<PathBuf, JSONRPCErrorError> {
+        let sessions_dir = tokio::fs::canonicalize(self._config.code_home.join(SESSIONS_SUBDIR))
+            .await
+            .map_err(|err| JSONRPCErrorError {
+                code: INTERNAL_ERROR_CODE,
+                message: format!("unable to resolve sessions directory: {err}"),
+                data: None,
+            })?;
+        let invalid = |message: String| JSONRPCErrorError {
+            code: INVALID_REQUEST_ERROR_CODE,
+            message,
+            data: None,
+        };
+        let canonical = match tokio::fs::canonicalize(rollout_path).await {
+            Ok(path) if path.starts_with(&sessions_dir) => path,
+            _ => {
+                return Err(invalid(format!(
+                    "rollout path `{}` must be in the sessions directory",
+                    rollout_path.display()
+                )));
+            }
+        };
+        let expected_suffix = format!("{conversation_id}.jsonl");
+        if !canonical
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(&expected_suffix))
+        {
+            return Err(invalid(format!(
+                "rollout path `{}` does not match conversation id {conversation_id}",
+                rollout_path.display()
+            )));
+        }
+        Ok(canonical)
+    }
+
+    /// Submits `Op::Shutdown` and waits until the conversation reports
+    /// `ShutdownComplete`, its event stream ends, or the timeout elapses.
+    async fn shut_down_conversation(
+        &self,
+        conversation_id: ConversationId,
+        conversation: Arc<BeaconConversation>,
+    ) {
+        let watcher_id = Uuid::new_v4();
+        let mut events = self.conversation_hub.subscribe(
+            conversation_id,
+            conversation.clone(),
+            watcher_id,
+            self.approval_sink.clone(),
+        );
+        if let Err(err) = conversation.submit(Op::Shutdown).await {
+            error!("failed to submit Shutdown to conversation {conversation_id}: {err}");
+        } else {
+            let completed = timeout(ARCHIVE_SHUTDOWN_TIMEOUT, async {
+                loop {
+                    match events.recv().await {
+                        Ok(event) if matches!(event.msg, EventMsg::ShutdownComplete) => break,
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            })
+            .await;
+            if completed.is_err() {
+                warn!("conversation {conversation_id} did not shut down in time; archiving anyway");
+            }
+        }
+        self.conversation_hub
+            .unsubscribe(conversation_id, watcher_id);
+    }
+
+    /// Resolves the rollout of `conversation_id` and the config for the
+    /// conversation that will replay it: the settings recorded in the
+    /// rollout, with `overrides` applied on top.
+    async fn prepare_history_rewrite(
+        &self,
+        conversation_id: ConversationId,
+        overrides: Option<NewConversationParams>,
+    ) -> Result<(PathBuf, Config), JSONRPCErrorError> {
+        let path = self.find_rollout_path(conversation_id).await?;
+        let settings = RolloutRecorder::get_rollout_settings(&path)
+            .await
+            .map_err(|err| JSONRPCErrorError {
+                code: INTERNAL_ERROR_CODE,
+                message: format!("error reading rollout for {conversation_id}: {err}"),
+                data: None,
+            })?;
+        let config = derive_config_for_rollout(
+            overrides.unwrap_or_default(),
+            settings,
+            &self._config.code_home,
+            self.code_linux_sandbox_exe.clone(),
+        )
+        .map_err(|err| JSONRPCErrorError {
+            code: INVALID_REQUEST_ERROR_CODE,
+            message: format!("error deriving config: {err}"),
+            data: None,
+        })?;
+        Ok((path, config))
+    }
+
+    async fn find_rollout_path(
+        &self,
+        conversation_id: ConversationId,
+    ) -> Result<PathBuf, JSONRPCErrorError> {
+        match find_conversation_path_by_id_str(
+            &self._config.code_home,
+            &conversation_id.to_string(),
+        )
+        .await
+        {
+            Ok(Some(path)) => Ok(path),
+            Ok(None) => Err(JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: format!("no rollout recorded for conversation: {conversation_id}"),
+                data: None,
+            }),
+            Err(err) => Err(JSONRPCErrorError {
+                code: INTERNAL_ERROR_CODE,
+                message: format!("error locating rollout for {conversation_id}: {err}"),
+                data: None,
+            }),
+        }
+    }
+
+    async fn rollout_path_or_sessions_root(&self, conversation_id: ConversationId) -> PathBuf {
+        match self.find_rollout_path(conversation_id).await {
+            Ok(path) => path,
+            Err(_) => self._config.code_home.join("sessions"),
+        }
+    }
+
     async fn send_user_message(&self, request_id: RequestId, params: SendUserMessageParams) {
         let SendUserMessageParams {
             conversation_id,
@@ -233,9 +640,16 @@ impl CodexMessageProcessor {
                 WireInputItem::Text { text } => CoreInputItem::Text { text },
                 WireInputItem::Image { image_url } => CoreInputItem::Image { image_url },
                 WireInputItem::LocalImage { path } => CoreInputItem::LocalImage { path },
+                WireInputItem::McpResource { server, uri } => {
+                    CoreInputItem::McpResource { server, uri }
+                }
             })
             .collect();
 
+        // Approval requests for this turn go back to this client.
+        self.conversation_hub
+            .claim_approvals(conversation_id, self.approval_sink.clone());
+
         // Submit user input to the conversation.
         let _ = conversation
             .submit(Op::UserInput {
@@ -282,9 +696,15 @@ impl CodexMessageProcessor {
                 WireInputItem::Text { text } => CoreInputItem::Text { text },
                 WireInputItem::Image { image_url } => CoreInputItem::Image { image_url },
                 WireInputItem::LocalImage { path } => CoreInputItem::LocalImage { path },
+                WireInputItem::McpResource { server, uri } => {
+                    CoreInputItem::McpResource { server, uri }
+                }
             })
             .collect();
 
+        self.conversation_hub
+            .claim_approvals(conversation_id, self.approval_sink.clone());
+
         // Core protocol compatibility: older cores do not support per-turn overrides.
         // Submit only the user input items.
         let _ = conversation
@@ -350,20 +770,50 @@ impl CodexMessageProcessor {
         let (cancel_tx, mut cancel_rx) = oneshot::channel();
         self.conversation_listeners
             .insert(subscription_id, cancel_tx);
+        let hub = self.conversation_hub.clone();
+        let mut events = hub.subscribe(
+            conversation_id,
+            conversation,
+            subscription_id,
+            self.approval_sink.clone(),
+        );
         let outgoing_for_task = self.outgoing.clone();
-        let pending_interrupts = self.pending_interrupts.clone();
         tokio::spawn(async move {
             loop {
                 tokio::select! {
                     _ = &mut cancel_rx => {
-                        // User has unsubscribed, so exit this task.
+                        // User has unsubscribed (or disconnected), so exit this task.
                         break;
                     }
-                    event = conversation.next_event() => {
+                    event = events.recv() => {
                         let event = match event {
                             Ok(event) => event,
-                            Err(err) => {
-                                tracing::warn!("conversation.next_event() failed with: {err}");
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                // Dropping events would leave the client with a
+                                // silently inconsistent transcript, so end the
+                                // subscription and let it re-sync instead.
+                                tracing::warn!("conversation listener {subscription_id} fell {skipped} events behind; closing it");
+                                outgoing_for_task.send_notification(subscription_closed(
+                                    subscription_id,
+                                    conversation_id,
+                                    "lagged",
+                                    None,
+                                ))
+                                .await;
+                                break;
+                            }
+                            Err(broadcast::error::RecvError::Closed) => {
+                                // The conversation ended. After a rollback, point
+                                // the client at the conversation that replaced it.
+                                let replacement = hub.replacement(conversation_id);
+                                let reason = if replacement.is_some() { "rolledBack" } else { "ended" };
+                                outgoing_for_task.send_notification(subscription_closed(
+                                    subscription_id,
+                                    conversation_id,
+                                    reason,
+                                    replacement,
+                                ))
+                                .await;
                                 break;
                             }
                         };
@@ -373,7 +823,7 @@ impl CodexMessageProcessor {
                         // to creating a special enum for notifications with a
                         // stable wire format.
                         let method = format!("codex/event/{}", event.msg);
-                        let mut params = match serde_json::to_value(event.clone()) {
+                        let mut params = match serde_json::to_value(&event) {
                             Ok(serde_json::Value::Object(map)) => map,
                             Ok(_) => {
                                 tracing::error!("event did not serialize to an object");
@@ -391,11 +841,10 @@ impl CodexMessageProcessor {
                             params: Some(params.into()),
                         })
                         .await;
-
-                        apply_bespoke_event_handling(event.clone(), conversation_id, conversation.clone(), outgoing_for_task.clone(), pending_interrupts.clone()).await;
                     }
                 }
             }
+            hub.unsubscribe(conversation_id, subscription_id);
         });
         let response = AddConversationSubscriptionResponse { subscription_id };
         self.outgoing.send_response(request_id, response).await;
@@ -522,9 +971,16 @@ impl CodexMessageProcessor {
                 WireInputItem::Text { text } => CoreInputItem::Text { text },
                 WireInputItem::Image { image_url } => CoreInputItem::Image { image_url },
                 WireInputItem::LocalImage { path } => CoreInputItem::LocalImage { path },
+                WireInputItem::McpResource { server, uri } => {
+                    CoreInputItem::McpResource { server, uri }
+                }
             })
             .collect();
 
+        // Approval requests for this turn go back to this client.
+        self.conversation_hub
+            .claim_approvals(conversation_id, self.approval_sink.clone());
+
         // Submit user input to the conversation.
         let _ = conversation
             .submit(Op::UserInput {
@@ -539,6 +995,43 @@ impl CodexMessageProcessor {
     }
 }
 
+impl Drop for CodexMessageProcessor {
+    fn drop(&mut self) {
+        self.approval_task.abort();
+        self.conversation_hub.disconnect(&self.approval_sink);
+    }
+}
+
+/// Forwards the approval requests the hub routes to this connection.
+async fn answer_client_requests(
+    mut requests: mpsc::UnboundedReceiver<ClientRequestEvent>,
+    conversation_manager: Arc<ConversationManager>,
+    outgoing: Arc<OutgoingMessageSender>,
+    pending_interrupts: Arc<Mutex<HashMap<Uuid, Vec<RequestId>>>>,
+) {
+    while let Some(ClientRequestEvent {
+        conversation_id,
+        event,
+    }) = requests.recv().await
+    {
+        let Ok(conversation) = conversation_manager.get_conversation(conversation_id).await else {
+            warn!(
+                "dropping {} for unknown conversation {conversation_id}",
+                event.msg
+            );
+            continue;
+        };
+        apply_bespoke_event_handling(
+            event,
+            conversation_id,
+            conversation,
+            outgoing.clone(),
+            pending_interrupts.clone(),
+        )
+        .await;
+    }
+}
+
 async fn apply_bespoke_event_handling(
     event: Event,
     conversation_id: ConversationId,
@@ -561,8 +1054,8 @@ async fn apply_bespoke_event_handling(
                 .into_iter()
                 .map(|(p, c)| {
                     let mapped = match c {
-                        code_core::protocol::FileChange::Add { content } => {
-                            code_protocol::protocol::FileChange::Add { content }
+                        code_core::protocol::FileChange::Add { content, mode } => {
+                            code_protocol::protocol::FileChange::Add { content, mode }
                         }
                         code_core::protocol::FileChange::Delete => {
                             code_protocol::protocol::FileChange::Delete {
@@ -574,11 +1067,13 @@ async fn apply_bespoke_event_handling(
                             move_path,
                             original_content,
                             new_content,
+                            mode,
                         } => code_protocol::protocol::FileChange::Update {
                             unified_diff,
                             move_path,
                             original_content,
                             new_content,
+                            mode,
                         },
                     };
                     (p, mapped)
@@ -626,15 +1121,171 @@ async fn apply_bespoke_event_handling(
                 on_exec_approval_response(approval_id, rx, conversation).await;
             });
         }
+        EventMsg::McpSamplingRequest(core_protocol::McpSamplingRequestEvent {
+            id,
+            server,
+            messages,
+            system_prompt,
+            max_tokens,
+        }) => {
+            let params = McpSamplingApprovalParams {
+                conversation_id,
+                server,
+                messages,
+                system_prompt,
+                max_tokens,
+            };
+            let value = serde_json::to_value(&params).unwrap_or_default();
+            let rx = outgoing
+                .send_request(MCP_SAMPLING_APPROVAL_METHOD, Some(value))
+                .await;
+            tokio::spawn(async move {
+                on_mcp_sampling_approval_response(id, rx, conversation).await;
+            });
+        }
+        EventMsg::McpElicitationRequest(core_protocol::McpElicitationRequestEvent {
+            id,
+            server,
+            message,
+            requested_schema,
+        }) => {
+            let params = McpElicitationParams {
+                conversation_id,
+                server,
+                message,
+                requested_schema,
+            };
+            let value = serde_json::to_value(&params).unwrap_or_default();
+            let rx = outgoing
+                .send_request(MCP_ELICITATION_METHOD, Some(value))
+                .await;
+            tokio::spawn(async move {
+                on_mcp_elicitation_response(id, rx, conversation).await;
+            });
+        }
         // No special handling needed for interrupts; responses are sent immediately.
         _ => {}
     }
 }
 
+/// Returns the page of `items` starting at the offset encoded in `cursor`
+/// together with the cursor for the following page.
+fn page_items(
+    items: Vec<ResponseItem>,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Result<(Vec<ResponseItem>, Option<String>), String> {
+    let start = match cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| format!("invalid cursor: {cursor}"))?,
+        None => 0,
+    };
+    let limit = limit.unwrap_or(DEFAULT_ITEMS_PAGE_SIZE).max(1);
+    let end = start.saturating_add(limit).min(items.len());
+    let next_cursor = (end < items.len()).then(|| end.to_string());
+    let page = items
+        .into_iter()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect();
+    Ok((page, next_cursor))
+}
+
+/// Tells a client that the server ended one of its subscriptions.
+fn subscription_closed(
+    subscription_id: Uuid,
+    conversation_id: ConversationId,
+    reason: &str,
+    replacement: Option<ConversationId>,
+) -> OutgoingNotification {
+    let mut params = serde_json::json!({
+        "subscriptionId": subscription_id,
+        "conversationId": conversation_id.to_string(),
+        "reason": reason,
+    });
+    if let Some(replacement) = replacement {
+        params["replacementConversationId"] = replacement.to_string().into();
+    }
+    OutgoingNotification {
+        method: SUBSCRIPTION_CLOSED_METHOD.to_string(),
+        params: Some(params),
+    }
+}
+
 fn derive_config_from_params(
     params: NewConversationParams,
     code_linux_sandbox_exe: Option<PathBuf>,
 ) -> std::io::Result<Config> {
+    let (cli_overrides, overrides) = config_overrides_from_params(params, code_linux_sandbox_exe);
+    Config::load_with_cli_overrides(cli_overrides, overrides)
+}
+
+/// Config for a conversation that replays a recorded rollout. The settings
+/// recorded in the rollout take the place of the defaults from `config.toml`
+/// in `code_home`; anything set in `params` still wins.
+fn derive_config_for_rollout(
+    params: NewConversationParams,
+    settings: RolloutSettings,
+    code_home: &Path,
+    code_linux_sandbox_exe: Option<PathBuf>,
+) -> std::io::Result<Config> {
+    let model_overridden = params.model.is_some();
+    let sandbox_overridden = params.sandbox.is_some();
+    let (cli_overrides, mut overrides) =
+        config_overrides_from_params(params, code_linux_sandbox_exe);
+    let set_by_cli = |key: &str| cli_overrides.iter().any(|(path, _)| path == key);
+    let keep_effort = !model_overridden && !set_by_cli("model_reasoning_effort");
+    let keep_summary = !set_by_cli("model_reasoning_summary");
+    let RolloutSettings {
+        session_meta,
+        turn_context,
+    } = settings;
+
+    if let Some(turn_context) = &turn_context {
+        overrides.cwd = overrides.cwd.or_else(|| Some(turn_context.cwd.clone()));
+        overrides.model = overrides.model.or_else(|| Some(turn_context.model.clone()));
+        overrides.approval_policy = overrides
+            .approval_policy
+            .or_else(|| Some(map_ask_for_approval_from_wire(turn_context.approval_policy)));
+    }
+    let recorded_instructions = match session_meta {
+        Some(meta) => {
+            if overrides.cwd.is_none() && !meta.cwd.as_os_str().is_empty() {
+                overrides.cwd = Some(meta.cwd);
+            }
+            meta.instructions
+        }
+        None => None,
+    };
+
+    let cfg = load_config_as_toml_with_cli_overrides(code_home, cli_overrides)?;
+    let mut config =
+        Config::load_from_base_config_with_overrides(cfg, overrides, code_home.to_path_buf())?;
+    if recorded_instructions.is_some() {
+        config.user_instructions = recorded_instructions;
+    }
+    if let Some(turn_context) = turn_context {
+        if !sandbox_overridden {
+            match map_sandbox_policy_from_wire(turn_context.sandbox_policy) {
+                Ok(policy) => config.sandbox_policy = policy,
+                Err(err) => warn!("ignoring recorded sandbox policy: {err}"),
+            }
+        }
+        if keep_effort && let Some(effort) = turn_context.effort {
+            config.model_reasoning_effort = effort.into();
+        }
+        if keep_summary {
+            config.model_reasoning_summary = turn_context.summary.into();
+        }
+    }
+    Ok(config)
+}
+
+fn config_overrides_from_params(
+    params: NewConversationParams,
+    code_linux_sandbox_exe: Option<PathBuf>,
+) -> (Vec<(String, toml::Value)>, ConfigOverrides) {
     let NewConversationParams {
         model,
         profile,
@@ -676,7 +1327,7 @@ fn derive_config_from_params(
         .map(|(k, v)| (k, json_to_toml(v)))
         .collect();
 
-    Config::load_with_cli_overrides(cli_overrides, overrides)
+    (cli_overrides, overrides)
 }
 
 async fn on_patch_approval_response(
@@ -787,6 +1438,83 @@ async fn on_exec_approval_response(
     }
 }
 
+async fn on_mcp_sampling_approval_response(
+    approval_id: String,
+    receiver: tokio::sync::oneshot::Receiver<mcp_types::Result>,
+    conversation: Arc<BeaconConversation>,
+) {
+    let decision = match timeout(APPROVAL_TIMEOUT, receiver).await {
+        Ok(Ok(value)) => ReviewDecision::from_value(&value).unwrap_or_else(|| {
+            error!("failed to deserialize sampling approval response (value={value:?}); denying");
+            ReviewDecision::Denied
+        }),
+        Ok(Err(err)) => {
+            error!("sampling approval request failed: {err:?}");
+            ReviewDecision::Denied
+        }
+        Err(_) => {
+            warn!(
+                "sampling approval request timed out after {:?} (id={})",
+                APPROVAL_TIMEOUT, approval_id
+            );
+            ReviewDecision::Denied
+        }
+    };
+
+    if let Err(err) = conversation
+        .submit(Op::McpSamplingApproval {
+            id: approval_id,
+            decision: decision.into(),
+        })
+        .await
+    {
+        error!("failed to submit McpSamplingApproval: {err}");
+    }
+}
+
+async fn on_mcp_elicitation_response(
+    elicitation_id: String,
+    receiver: tokio::sync::oneshot::Receiver<mcp_types::Result>,
+    conversation: Arc<BeaconConversation>,
+) {
+    let (action, content) = match timeout(APPROVAL_TIMEOUT, receiver).await {
+        Ok(Ok(value)) => match serde_json::from_value::<McpElicitationResponse>(value) {
+            Ok(McpElicitationResponse { action, content }) => (action, content),
+            Err(err) => {
+                error!("failed to deserialize elicitation response: {err}; cancelling");
+                (WireElicitationAction::Cancel, None)
+            }
+        },
+        Ok(Err(err)) => {
+            error!("elicitation request failed: {err:?}");
+            (WireElicitationAction::Cancel, None)
+        }
+        Err(_) => {
+            warn!(
+                "elicitation request timed out after {:?} (id={})",
+                APPROVAL_TIMEOUT, elicitation_id
+            );
+            (WireElicitationAction::Cancel, None)
+        }
+    };
+    let action = match action {
+        WireElicitationAction::Accept => core_protocol::McpElicitationAction::Accept,
+        WireElicitationAction::Decline => core_protocol::McpElicitationAction::Decline,
+        WireElicitationAction::Cancel => core_protocol::McpElicitationAction::Cancel,
+    };
+
+    if let Err(err) = conversation
+        .submit(Op::ResolveMcpElicitation {
+            id: elicitation_id,
+            action,
+            content,
+        })
+        .await
+    {
+        error!("failed to submit ResolveMcpElicitation: {err}");
+    }
+}
+
 fn map_ask_for_approval_from_wire(
     a: code_protocol::protocol::AskForApproval,
 ) -> core_protocol::AskForApproval {
@@ -804,4 +1532,334 @@ fn map_ask_for_approval_from_wire(
     }
 }
 
+/// The wire and core sandbox policies share their serialized form.
+fn map_sandbox_policy_from_wire(
+    policy: code_protocol::protocol::SandboxPolicy,
+) -> serde_json::Result<core_protocol::SandboxPolicy> {
+    serde_json::from_value(serde_json::to_value(policy)?)
+}
+
 // Unused legacy mappers removed to avoid warnings.
+
+#[cfg(test)]
+mod tests {
+    use code_core::AuthManager;
+    use code_core::CodeAuth;
+    use code_core::config::ConfigToml;
+    use code_protocol::config_types::ReasoningSummary;
+    use code_protocol::models::ContentItem;
+    use code_protocol::protocol::AskForApproval;
+    use code_protocol::protocol::RolloutItem;
+    use code_protocol::protocol::RolloutLine;
+    use code_protocol::protocol::SandboxPolicy;
+    use code_protocol::protocol::SessionMeta;
+    use code_protocol::protocol::SessionMetaLine;
+    use code_protocol::protocol::SessionSource;
+    use code_protocol::protocol::TurnContextItem;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::outgoing_message::OutgoingMessage;
+
+    fn message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn texts(items: &[ResponseItem]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| match item {
+                ResponseItem::Message { content, .. } => match &content[0] {
+                    ContentItem::InputText { text } => text.clone(),
+                    other => panic!("unexpected content: {other:?}"),
+                },
+                other => panic!("unexpected item: {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pages_items_with_offset_cursor() {
+        let items: Vec<ResponseItem> = ["a", "b", "c", "d", "e"].into_iter().map(message).collect();
+
+        let (page, next) = page_items(items.clone(), None, Some(2)).unwrap();
+        assert_eq!(texts(&page), vec!["a", "b"]);
+        assert_eq!(next.as_deref(), Some("2"));
+
+        let (page, next) = page_items(items.clone(), next.as_deref(), Some(2)).unwrap();
+        assert_eq!(texts(&page), vec!["c", "d"]);
+
+        let (page, next) = page_items(items.clone(), next.as_deref(), Some(2)).unwrap();
+        assert_eq!(texts(&page), vec!["e"]);
+        assert_eq!(next, None);
+
+        let (page, next) = page_items(items.clone(), Some("9"), None).unwrap();
+        assert!(page.is_empty());
+        assert_eq!(next, None);
+
+        assert!(page_items(items, Some("nope"), None).is_err());
+    }
+
+    /// Drives a processor like a connected client, keeping notifications
+    /// that arrive while it waits for responses.
+    struct TestClient {
+        processor: CodexMessageProcessor,
+        outgoing: mpsc::Receiver<OutgoingMessage>,
+        notifications: Vec<OutgoingNotification>,
+        next_id: i64,
+    }
+
+    impl TestClient {
+        fn new(code_home: &TempDir) -> Self {
+            let config = Config::load_from_base_config_with_overrides(
+                ConfigToml::default(),
+                ConfigOverrides::default(),
+                code_home.path().to_path_buf(),
+            )
+            .expect("config");
+            let auth_manager = AuthManager::from_auth_for_testing(CodeAuth::from_api_key("test"));
+            let conversation_manager = Arc::new(ConversationManager::new(
+                auth_manager.clone(),
+                SessionSource::Mcp,
+            ));
+            let (tx, outgoing) = mpsc::channel(64);
+            let processor = CodexMessageProcessor::new(
+                auth_manager,
+                conversation_manager,
+                Arc::new(OutgoingMessageSender::new(tx)),
+                None,
+                Arc::new(config),
+            );
+            Self {
+                processor,
+                outgoing,
+                notifications: Vec::new(),
+                next_id: 0,
+            }
+        }
+
+        async fn request(
+            &mut self,
+            request: impl FnOnce(RequestId) -> ClientRequest,
+        ) -> serde_json::Value {
+            self.next_id += 1;
+            let request_id = RequestId::Integer(self.next_id);
+            self.processor
+                .process_request(request(request_id.clone()))
+                .await;
+            loop {
+                match self.next_message().await {
+                    OutgoingMessage::Response(response) if response.id == request_id => {
+                        return response.result;
+                    }
+                    OutgoingMessage::Error(error) if error.id == request_id => {
+                        panic!("request failed: {:?}", error.error);
+                    }
+                    OutgoingMessage::Notification(notification) => {
+                        self.notifications.push(notification);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        async fn notification(&mut self, method: &str) -> serde_json::Value {
+            loop {
+                if let Some(index) = self
+                    .notifications
+                    .iter()
+                    .position(|notification| notification.method == method)
+                {
+                    let notification = self.notifications.remove(index);
+                    return notification.params.expect("params");
+                }
+                if let OutgoingMessage::Notification(notification) = self.next_message().await {
+                    self.notifications.push(notification);
+                }
+            }
+        }
+
+        async fn next_message(&mut self) -> OutgoingMessage {
+            timeout(Duration::from_secs(10), self.outgoing.recv())
+                .await
+                .expect("message within timeout")
+                .expect("outgoing channel open")
+        }
+
+        async fn items(&mut self, conversation_id: ConversationId) -> Vec<String> {
+            let result = self
+                .request(|request_id| ClientRequest::GetConversationItems {
+                    request_id,
+                    params: GetConversationItemsParams {
+                        conversation_id,
+                        cursor: None,
+                        limit: None,
+                    },
+                })
+                .await;
+            let response: GetConversationItemsResponse =
+                serde_json::from_value(result).expect("items response");
+            response
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    ResponseItem::Message { content, .. } => match content.first() {
+                        Some(
+                            ContentItem::InputText { text } | ContentItem::OutputText { text },
+                        ) => Some(text.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect()
+        }
+    }
+
+    /// Writes a two-turn rollout recorded with model `o3` in `cwd`.
+    fn write_rollout(code_home: &TempDir, cwd: &Path) -> ConversationId {
+        let conversation_id = ConversationId::new();
+        let dir = code_home.path().join("sessions/2025/01/01");
+        std::fs::create_dir_all(&dir).expect("sessions dir");
+        let items = [
+            RolloutItem::SessionMeta(SessionMetaLine {
+                meta: SessionMeta {
+                    id: conversation_id,
+                    timestamp: "2025-01-01T00:00:00Z".to_string(),
+                    cwd: cwd.to_path_buf(),
+                    originator: "test".to_string(),
+                    cli_version: "0.0.0".to_string(),
+                    instructions: None,
+                    source: SessionSource::Mcp,
+                },
+                git: None,
+            }),
+            RolloutItem::TurnContext(TurnContextItem {
+                cwd: cwd.to_path_buf(),
+                approval_policy: AskForApproval::Never,
+                sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                model: "o3".to_string(),
+                effort: None,
+                summary: ReasoningSummary::Auto,
+            }),
+            RolloutItem::ResponseItem(message("first")),
+            RolloutItem::ResponseItem(reply("one")),
+            RolloutItem::ResponseItem(message("second")),
+            RolloutItem::ResponseItem(reply("two")),
+        ];
+        let lines: Vec<String> = items
+            .into_iter()
+            .map(|item| {
+                serde_json::to_string(&RolloutLine {
+                    timestamp: "2025-01-01T00:00:00Z".to_string(),
+                    item,
+                })
+                .expect("rollout line")
+            })
+            .collect();
+        std::fs::write(
+            dir.join(format!(
+                "rollout-2025-01-01T00-00-00-{conversation_id}.jsonl"
+            )),
+            lines.join("\n") + "\n",
+        )
+        .expect("write rollout");
+        conversation_id
+    }
+
+    fn reply(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fork_and_rollback_replay_the_recorded_conversation() {
+        let code_home = TempDir::new().expect("code home");
+        let cwd = TempDir::new().expect("cwd");
+        let original = write_rollout(&code_home, cwd.path());
+        let mut client = TestClient::new(&code_home);
+
+        // Forking keeps the recorded model rather than the server default.
+        let result = client
+            .request(|request_id| ClientRequest::ForkConversation {
+                request_id,
+                params: ForkConversationParams {
+                    conversation_id: original,
+                    at_turn: 2,
+                    overrides: None,
+                },
+            })
+            .await;
+        let fork: ForkConversationResponse = serde_json::from_value(result).expect("fork response");
+        assert_ne!(fork.conversation_id, original);
+        assert_eq!(fork.model, "o3");
+        let fork_items = client.items(fork.conversation_id).await;
+        assert!(
+            ["first", "one", "second", "two"]
+                .iter()
+                .all(|text| fork_items.iter().any(|item| item == text)),
+            "{fork_items:?}"
+        );
+
+        let result = client
+            .request(|request_id| ClientRequest::AddConversationListener {
+                request_id,
+                params: AddConversationListenerParams {
+                    conversation_id: fork.conversation_id,
+                },
+            })
+            .await;
+        let subscription: AddConversationSubscriptionResponse =
+            serde_json::from_value(result).expect("subscription response");
+
+        // Overrides still win over the recorded settings.
+        let result = client
+            .request(|request_id| ClientRequest::RollbackConversation {
+                request_id,
+                params: RollbackConversationParams {
+                    conversation_id: fork.conversation_id,
+                    turns: 1,
+                    overrides: Some(NewConversationParams {
+                        model: Some("gpt-5".to_string()),
+                        ..Default::default()
+                    }),
+                },
+            })
+            .await;
+        let rollback: RollbackConversationResponse =
+            serde_json::from_value(result).expect("rollback response");
+        assert_eq!(rollback.model, "gpt-5");
+        let rollback_items = client.items(rollback.conversation_id).await;
+        assert!(
+            rollback_items.iter().any(|item| item == "first"),
+            "{rollback_items:?}"
+        );
+        assert!(
+            !rollback_items.iter().any(|item| item == "second"),
+            "{rollback_items:?}"
+        );
+
+        // The listener on the replaced conversation learns where to go.
+        let closed = client.notification(SUBSCRIPTION_CLOSED_METHOD).await;
+        assert_eq!(
+            closed,
+            serde_json::json!({
+                "subscriptionId": subscription.subscription_id,
+                "conversationId": fork.conversation_id.to_string(),
+                "reason": "rolledBack",
+                "replacementConversationId": rollback.conversation_id.to_string(),
+            })
+        );
+    }
+}
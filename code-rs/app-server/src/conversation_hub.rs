@@ -0,0 +1,474 @@
+//! Fan-out of conversation events to every subscribed listener.
+//!
+//! A conversation has a single event receiver, so two listeners calling
+//! `next_event()` would each see only part of the stream. The hub runs one
+//! pump task per conversation that re-broadcasts events to all subscriptions,
+//! across every connected client. A subscriber that falls too far behind the
+//! broadcast is disconnected rather than silently skipping events.
+//!
+//! Requests the client must answer (approvals, elicitations) do not go
+//! through the broadcast. The pump hands each one to exactly one connection
+//! over an unbounded channel: the connection that submitted the current turn,
+//! or, once that connection is gone, the one with the oldest subscription.
+//!
+//! When a conversation is rolled back, its replacement is recorded here so the
+//! subscribers of the old conversation can be told where to go once it ends.
+//!
+//! An entry lives while the conversation has subscribers or a connected turn
+//! owner: it goes away with the last subscription, when its event stream ends
+//! with nobody subscribed, or when the turn owner disconnects.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use code_core::BeaconConversation;
+use code_core::protocol::Event;
+use code_core::protocol::EventMsg;
+use code_protocol::mcp_protocol::ConversationId;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+/// Events buffered per subscriber before a slow subscriber starts to lag.
+const EVENT_BUFFER: usize = 1024;
+
+/// A request from the conversation that one client has to answer.
+pub(crate) struct ClientRequestEvent {
+    pub conversation_id: ConversationId,
+    pub event: Event,
+}
+
+/// Where a connection receives the [`ClientRequestEvent`]s it should answer.
+#[derive(Clone)]
+pub(crate) struct ApprovalSink {
+    connection_id: Uuid,
+    requests: mpsc::UnboundedSender<ClientRequestEvent>,
+}
+
+impl ApprovalSink {
+    pub(crate) fn new(requests: mpsc::UnboundedSender<ClientRequestEvent>) -> Self {
+        Self {
+            connection_id: Uuid::new_v4(),
+            requests,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ConversationHub {
+    /// Shared with the pump tasks so they can drop their entry when the
+    /// conversation's event stream ends.
+    conversations: Arc<Mutex<HashMap<ConversationId, HubEntry>>>,
+}
+
+#[derive(Default)]
+struct HubEntry {
+    routes: Arc<Mutex<Routes>>,
+    pump: Option<Pump>,
+    /// The conversation that took over from this one.
+    replaced_by: Option<ConversationId>,
+}
+
+struct Pump {
+    /// Only used to hand out new receivers. The pump owns the sender, so
+    /// subscribers see the channel close when the conversation ends.
+    events: broadcast::Receiver<Event>,
+    /// Dropping this stops the pump task.
+    _stop: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct Routes {
+    /// The connection that submitted the most recent turn.
+    turn_owner: Option<ApprovalSink>,
+    /// Subscriptions in the order they were added, with their connection.
+    subscribers: Vec<(Uuid, ApprovalSink)>,
+}
+
+impl ConversationHub {
+    /// Registers `subscription_id` for the connection behind `sink` and
+    /// returns its event receiver, starting the pump for the conversation if
+    /// this is its first subscription.
+    pub(crate) fn subscribe(
+        &self,
+        conversation_id: ConversationId,
+        conversation: Arc<BeaconConversation>,
+        subscription_id: Uuid,
+        sink: ApprovalSink,
+    ) -> broadcast::Receiver<Event> {
+        let mut conversations = self.lock();
+        let entry = conversations.entry(conversation_id).or_default();
+        lock(&entry.routes)
+            .subscribers
+            .push((subscription_id, sink));
+        let pump = entry.pump.get_or_insert_with(|| {
+            let (sender, events) = broadcast::channel(EVENT_BUFFER);
+            let (stop, stop_rx) = oneshot::channel();
+            tokio::spawn(pump_events(
+                conversation_id,
+                conversation,
+                sender,
+                entry.routes.clone(),
+                self.conversations.clone(),
+                stop_rx,
+            ));
+            Pump {
+                events,
+                _stop: stop,
+            }
+        });
+        pump.events.resubscribe()
+    }
+
+    /// Removes a subscription. The pump stops once the last one is gone so
+    /// later events stay queued for the next subscriber.
+    pub(crate) fn unsubscribe(&self, conversation_id: ConversationId, subscription_id: Uuid) {
+        let mut conversations = self.lock();
+        if let Some(entry) = conversations.get_mut(&conversation_id) {
+            let mut routes = lock(&entry.routes);
+            routes.subscribers.retain(|(id, _)| *id != subscription_id);
+            let idle = routes.subscribers.is_empty();
+            drop(routes);
+            if idle {
+                conversations.remove(&conversation_id);
+            }
+        }
+    }
+
+    /// Sends the conversation's client requests to the connection behind
+    /// `sink` until another connection submits a turn.
+    pub(crate) fn claim_approvals(&self, conversation_id: ConversationId, sink: ApprovalSink) {
+        let mut conversations = self.lock();
+        let entry = conversations.entry(conversation_id).or_default();
+        lock(&entry.routes).turn_owner = Some(sink);
+    }
+
+    /// Stops routing client requests to the connection behind `sink` and
+    /// drops the entries that only its turn ownership kept alive.
+    pub(crate) fn disconnect(&self, sink: &ApprovalSink) {
+        self.lock().retain(|_, entry| {
+            let mut routes = lock(&entry.routes);
+            if routes
+                .turn_owner
+                .as_ref()
+                .is_some_and(|owner| owner.connection_id == sink.connection_id)
+            {
+                routes.turn_owner = None;
+            }
+            routes.turn_owner.is_some() || !routes.subscribers.is_empty()
+        });
+    }
+
+    /// Records that `replacement` took over from `conversation_id`. Only
+    /// kept while the old conversation still has subscribers.
+    pub(crate) fn mark_replaced(
+        &self,
+        conversation_id: ConversationId,
+        replacement: ConversationId,
+    ) {
+        if let Some(entry) = self.lock().get_mut(&conversation_id) {
+            entry.replaced_by = Some(replacement);
+        }
+    }
+
+    /// The conversation that took over from `conversation_id`, if any.
+    pub(crate) fn replacement(&self, conversation_id: ConversationId) -> Option<ConversationId> {
+        self.lock()
+            .get(&conversation_id)
+            .and_then(|entry| entry.replaced_by)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<ConversationId, HubEntry>> {
+        lock(&self.conversations)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Whether `msg` is a request that a client has to answer.
+fn needs_client_answer(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::ExecApprovalRequest(_)
+            | EventMsg::ApplyPatchApprovalRequest(_)
+            | EventMsg::McpSamplingRequest(_)
+            | EventMsg::McpElicitationRequest(_)
+    )
+}
+
+/// Forgets the pump of a conversation whose event stream ended, and the whole
+/// entry if nobody is subscribed. Subscribers that are still around keep the
+/// entry so they can look up the replacement once their receiver closes.
+fn pump_finished(
+    conversations: &Mutex<HashMap<ConversationId, HubEntry>>,
+    conversation_id: ConversationId,
+    routes: &Arc<Mutex<Routes>>,
+) {
+    let mut conversations = lock(conversations);
+    let Some(entry) = conversations.get_mut(&conversation_id) else {
+        return;
+    };
+    // A later subscription may have started a new entry with its own pump.
+    if !Arc::ptr_eq(&entry.routes, routes) {
+        return;
+    }
+    entry.pump = None;
+    let idle = lock(&entry.routes).subscribers.is_empty();
+    if idle {
+        conversations.remove(&conversation_id);
+    }
+}
+
+/// Hands `request` to the turn owner, falling back to subscribers in the
+/// order they subscribed when a connection has gone away.
+fn route_client_request(routes: &Mutex<Routes>, mut request: ClientRequestEvent) {
+    let routes = lock(routes);
+    let candidates = routes
+        .turn_owner
+        .iter()
+        .chain(routes.subscribers.iter().map(|(_, sink)| sink));
+    for sink in candidates {
+        match sink.requests.send(request) {
+            Ok(()) => return,
+            Err(mpsc::error::SendError(returned)) => {
+                tracing::debug!("connection {} is gone", sink.connection_id);
+                request = returned;
+            }
+        }
+    }
+    tracing::warn!(
+        "no connected client can answer {} for conversation {}",
+        request.event.msg,
+        request.conversation_id
+    );
+}
+
+async fn pump_events(
+    conversation_id: ConversationId,
+    conversation: Arc<BeaconConversation>,
+    events: broadcast::Sender<Event>,
+    routes: Arc<Mutex<Routes>>,
+    conversations: Arc<Mutex<HashMap<ConversationId, HubEntry>>>,
+    mut stop: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            event = conversation.next_event() => match event {
+                Ok(event) => {
+                    if needs_client_answer(&event.msg) {
+                        route_client_request(
+                            &routes,
+                            ClientRequestEvent {
+                                conversation_id,
+                                event: event.clone(),
+                            },
+                        );
+                    }
+                    let _ = events.send(event);
+                }
+                Err(err) => {
+                    tracing::warn!("conversation.next_event() failed with: {err}");
+                    break;
+                }
+            },
+        }
+    }
+    // Runs before `events` is dropped, so subscribers find the entry (and any
+    // replacement) when they see the channel close.
+    pump_finished(&conversations, conversation_id, &routes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::CodeAuth;
+    use code_core::ConversationManager;
+    use code_core::config::Config;
+    use code_core::config::ConfigOverrides;
+    use code_core::config::ConfigToml;
+    use code_core::protocol::ExecApprovalRequestEvent;
+    use code_core::protocol::Op;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn sink() -> (ApprovalSink, mpsc::UnboundedReceiver<ClientRequestEvent>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (ApprovalSink::new(tx), rx)
+    }
+
+    fn approval(call_id: &str) -> ClientRequestEvent {
+        ClientRequestEvent {
+            conversation_id: ConversationId::new(),
+            event: Event {
+                id: "sub".to_string(),
+                event_seq: 0,
+                msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+                    call_id: call_id.to_string(),
+                    command: vec!["true".to_string()],
+                    cwd: std::path::PathBuf::from("/"),
+                    reason: None,
+                }),
+                order: None,
+            },
+        }
+    }
+
+    fn call_id(request: ClientRequestEvent) -> String {
+        match request.event.msg {
+            EventMsg::ExecApprovalRequest(event) => event.call_id,
+            other => panic!("unexpected event {other}"),
+        }
+    }
+
+    #[test]
+    fn client_requests_go_to_the_turn_owner() {
+        let (first, mut first_rx) = sink();
+        let (owner, mut owner_rx) = sink();
+        let routes = Mutex::new(Routes {
+            turn_owner: Some(owner),
+            subscribers: vec![(Uuid::new_v4(), first)],
+        });
+
+        route_client_request(&routes, approval("call-1"));
+        route_client_request(&routes, approval("call-2"));
+
+        assert_eq!(call_id(owner_rx.try_recv().expect("owner")), "call-1");
+        assert_eq!(call_id(owner_rx.try_recv().expect("owner")), "call-2");
+        assert!(first_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn client_requests_fall_back_to_oldest_live_subscriber() {
+        let (gone, gone_rx) = sink();
+        let (first, first_rx) = sink();
+        let (second, mut second_rx) = sink();
+        let (owner, owner_rx) = sink();
+        drop(gone_rx);
+        drop(owner_rx);
+        drop(first_rx);
+        let routes = Mutex::new(Routes {
+            turn_owner: Some(owner),
+            subscribers: vec![
+                (Uuid::new_v4(), gone),
+                (Uuid::new_v4(), first),
+                (Uuid::new_v4(), second),
+            ],
+        });
+
+        route_client_request(&routes, approval("call-1"));
+
+        assert_eq!(call_id(second_rx.try_recv().expect("fallback")), "call-1");
+    }
+
+    #[test]
+    fn disconnecting_the_turn_owner_drops_entries_it_kept_alive() {
+        let hub = ConversationHub::default();
+        let (owner, _owner_rx) = sink();
+        let (other, _other_rx) = sink();
+        let owned = ConversationId::new();
+        let subscribed = ConversationId::new();
+        hub.claim_approvals(owned, owner.clone());
+        hub.claim_approvals(subscribed, owner.clone());
+        lock(&hub.lock()[&subscribed].routes)
+            .subscribers
+            .push((Uuid::new_v4(), other.clone()));
+
+        hub.disconnect(&other);
+        assert_eq!(hub.lock().len(), 2);
+
+        hub.disconnect(&owner);
+        let conversations = hub.lock();
+        assert_eq!(conversations.keys().collect::<Vec<_>>(), vec![&subscribed]);
+        assert!(
+            lock(&conversations[&subscribed].routes)
+                .turn_owner
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn finished_pump_drops_the_entry_once_nobody_is_subscribed() {
+        let hub = ConversationHub::default();
+        let (owner, _owner_rx) = sink();
+        let (subscriber, _subscriber_rx) = sink();
+        let ended = ConversationId::new();
+        let watched = ConversationId::new();
+        let replacement = ConversationId::new();
+        hub.claim_approvals(ended, owner.clone());
+        hub.claim_approvals(watched, owner);
+        lock(&hub.lock()[&watched].routes)
+            .subscribers
+            .push((Uuid::new_v4(), subscriber));
+        hub.mark_replaced(watched, replacement);
+
+        let routes = |id: ConversationId| hub.lock()[&id].routes.clone();
+        let (ended_routes, watched_routes) = (routes(ended), routes(watched));
+        pump_finished(&hub.conversations, ended, &ended_routes);
+        pump_finished(&hub.conversations, watched, &watched_routes);
+
+        assert!(!hub.lock().contains_key(&ended));
+        assert_eq!(hub.replacement(watched), Some(replacement));
+    }
+
+    #[tokio::test]
+    async fn events_fan_out_to_every_subscriber() {
+        let code_home = tempfile::TempDir::new().expect("tempdir");
+        let config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            code_home.path().to_path_buf(),
+        )
+        .expect("config");
+        let manager = ConversationManager::with_auth(CodeAuth::from_api_key("test"));
+        let new_conversation = manager
+            .new_conversation(config)
+            .await
+            .expect("conversation");
+        let conversation_id = new_conversation.conversation_id;
+        let conversation = new_conversation.conversation;
+
+        let hub = ConversationHub::default();
+        let (first_sink, _first_requests) = sink();
+        let (second_sink, _second_requests) = sink();
+        let mut first = hub.subscribe(
+            conversation_id,
+            conversation.clone(),
+            Uuid::new_v4(),
+            first_sink,
+        );
+        let mut second = hub.subscribe(
+            conversation_id,
+            conversation.clone(),
+            Uuid::new_v4(),
+            second_sink,
+        );
+
+        let submission = conversation
+            .submit(Op::ListCustomPrompts)
+            .await
+            .expect("submit");
+        for events in [&mut first, &mut second] {
+            let event = tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    let event = events.recv().await.expect("event");
+                    if event.id == submission {
+                        return event;
+                    }
+                }
+            })
+            .await
+            .expect("event within timeout");
+            assert!(
+                matches!(event.msg, EventMsg::ListCustomPromptsResponse(_)),
+                "{event:?}"
+            );
+        }
+    }
+}
@@ -1,6 +1,7 @@
+use clap::Parser;
+use code_app_server::AppServerCli;
 use code_app_server::run_main;
 use code_arg0::arg0_dispatch_or_else;
-use code_common::CliConfigOverrides;
 
 const CODE_SECURE_MODE_ENV_VAR: &str = "CODE_SECURE_MODE";
 const CODEX_SECURE_MODE_ENV_VAR: &str = "CODEX_SECURE_MODE";
@@ -23,7 +24,7 @@ fn main() -> anyhow::Result<()> {
     }));
 
     arg0_dispatch_or_else(|code_linux_sandbox_exe| async move {
-        run_main(code_linux_sandbox_exe, CliConfigOverrides::default()).await?;
+        run_main(code_linux_sandbox_exe, AppServerCli::parse()).await?;
         Ok(())
     })
 }
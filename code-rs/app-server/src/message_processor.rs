@@ -1,7 +1,9 @@
 use std::path::PathBuf;
 
 use crate::code_message_processor::CodexMessageProcessor;
+use crate::conversation_hub::ConversationHub;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
+use crate::outgoing_message::OutgoingMessage;
 use crate::outgoing_message::OutgoingMessageSender;
 use code_protocol::mcp_protocol::AuthMode;
 use code_protocol::mcp_protocol::ClientInfo;
@@ -16,27 +18,31 @@ use code_core::default_client::USER_AGENT_SUFFIX;
 use code_core::default_client::get_code_user_agent;
 use mcp_types::JSONRPCError;
 use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
 use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::info;
 
-pub(crate) struct MessageProcessor {
-    outgoing: Arc<OutgoingMessageSender>,
-    code_message_processor: CodexMessageProcessor,
-    base_config: Arc<Config>,
-    initialized: bool,
+/// State shared by every client connection.
+pub(crate) struct ServerContext {
+    auth_manager: Arc<AuthManager>,
+    conversation_manager: Arc<ConversationManager>,
+    conversation_hub: Arc<ConversationHub>,
+    code_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    /// Token clients must present unless their transport is trusted.
+    pub(crate) auth_token: Option<String>,
 }
 
-impl MessageProcessor {
-    /// Create a new `MessageProcessor`, retaining a handle to the outgoing
-    /// `Sender` so handlers can enqueue messages to be written to stdout.
+impl ServerContext {
     pub(crate) fn new(
-        outgoing: OutgoingMessageSender,
         code_linux_sandbox_exe: Option<PathBuf>,
         config: Arc<Config>,
+        auth_token: Option<String>,
     ) -> Self {
-        let outgoing = Arc::new(outgoing);
         let auth_manager = AuthManager::shared_with_mode_and_originator(
             config.code_home.clone(),
             AuthMode::ApiKey,
@@ -46,20 +52,89 @@ impl MessageProcessor {
             auth_manager.clone(),
             SessionSource::Mcp,
         ));
-        let config_for_processor = config;
-        let code_message_processor = CodexMessageProcessor::new(
+        Self {
             auth_manager,
             conversation_manager,
-            outgoing.clone(),
+            conversation_hub: Arc::new(ConversationHub::default()),
             code_linux_sandbox_exe,
-            config_for_processor.clone(),
-        );
+            config,
+            auth_token,
+        }
+    }
+}
+
+/// Processes one client connection until its incoming channel closes.
+/// `authenticated` is true when the transport already verified the client.
+pub(crate) async fn serve_connection(
+    context: Arc<ServerContext>,
+    mut incoming_rx: mpsc::Receiver<JSONRPCMessage>,
+    outgoing_tx: mpsc::Sender<OutgoingMessage>,
+    authenticated: bool,
+) {
+    let mut processor = MessageProcessor::new(
+        OutgoingMessageSender::new(outgoing_tx),
+        &context,
+        authenticated,
+    );
+    while let Some(msg) = incoming_rx.recv().await {
+        match msg {
+            JSONRPCMessage::Request(r) => processor.process_request(r).await,
+            JSONRPCMessage::Response(r) => processor.process_response(r).await,
+            JSONRPCMessage::Notification(n) => processor.process_notification(n).await,
+            JSONRPCMessage::Error(e) => processor.process_error(e),
+        }
+    }
+
+    info!("processor task exited (channel closed)");
+}
+
+/// Compares auth tokens without short-circuiting on the first mismatch.
+pub(crate) fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+pub(crate) struct MessageProcessor {
+    outgoing: Arc<OutgoingMessageSender>,
+    code_message_processor: CodexMessageProcessor,
+    base_config: Arc<Config>,
+    initialized: bool,
+    /// Token `initialize` must carry; `None` once the client is trusted.
+    required_token: Option<String>,
+}
+
+impl MessageProcessor {
+    /// Create a new `MessageProcessor`, retaining a handle to the outgoing
+    /// `Sender` so handlers can enqueue messages for the client.
+    pub(crate) fn new(
+        outgoing: OutgoingMessageSender,
+        context: &ServerContext,
+        authenticated: bool,
+    ) -> Self {
+        let outgoing = Arc::new(outgoing);
+        let code_message_processor = CodexMessageProcessor::new(
+            context.auth_manager.clone(),
+            context.conversation_manager.clone(),
+            outgoing.clone(),
+            context.code_linux_sandbox_exe.clone(),
+            context.config.clone(),
+        )
+        .with_conversation_hub(context.conversation_hub.clone());
 
         Self {
             outgoing,
             code_message_processor,
-            base_config: config_for_processor,
+            base_config: context.config.clone(),
             initialized: false,
+            required_token: if authenticated {
+                None
+            } else {
+                context.auth_token.clone()
+            },
         }
     }
 
@@ -80,6 +155,19 @@ impl MessageProcessor {
                         };
                         self.outgoing.send_error(request_id, error).await;
                         return;
+                    } else if let Some(expected) = self.required_token.as_deref()
+                        && !params
+                            .auth_token
+                            .as_deref()
+                            .is_some_and(|token| tokens_match(token, expected))
+                    {
+                        let error = JSONRPCErrorError {
+                            code: INVALID_REQUEST_ERROR_CODE,
+                            message: "Unauthorized".to_string(),
+                            data: None,
+                        };
+                        self.outgoing.send_error(request_id, error).await;
+                        return;
                     } else {
                         let ClientInfo {
                             name,
@@ -145,3 +233,16 @@ impl MessageProcessor {
         tracing::error!("<- error: {:?}", err);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_match_requires_exact_token() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secreT", "secret"));
+        assert!(!tokens_match("secret2", "secret"));
+        assert!(!tokens_match("", "secret"));
+    }
+}
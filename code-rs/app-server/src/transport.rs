@@ -0,0 +1,582 @@
+//! Client transports for the app server.
+//!
+//! Every transport turns a client connection into a pair of channels:
+//! decoded [`JSONRPCMessage`]s in and [`OutgoingMessage`]s out. Stdio and
+//! Unix sockets use newline-delimited JSON; WebSocket clients send one
+//! JSON-RPC message per text frame.
+
+use std::fmt;
+use std::io::Result as IoResult;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use futures::SinkExt;
+use futures::StreamExt;
+use mcp_types::JSONRPCMessage;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::server::ErrorResponse;
+use tokio_tungstenite::tungstenite::handshake::server::Request;
+use tokio_tungstenite::tungstenite::handshake::server::Response;
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::CHANNEL_CAPACITY;
+use crate::message_processor::ServerContext;
+use crate::message_processor::serve_connection;
+use crate::message_processor::tokens_match;
+use crate::outgoing_message::OutgoingMessage;
+
+/// Where the app server accepts clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddress {
+    /// A single client on stdin/stdout.
+    Stdio,
+    /// WebSocket clients on `HOST:PORT`.
+    WebSocket(String),
+    /// Clients on a Unix domain socket at this path.
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "stdio" {
+            return Ok(Self::Stdio);
+        }
+        if let Some(rest) = s.strip_prefix("ws://") {
+            let addr = rest.strip_suffix('/').unwrap_or(rest);
+            let valid = addr
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+            if !valid || addr.contains('/') {
+                return Err(format!("expected ws://HOST:PORT, got `{s}`"));
+            }
+            return Ok(Self::WebSocket(addr.to_string()));
+        }
+        if let Some(path) = s.strip_prefix("unix:") {
+            let path = path.strip_prefix("//").unwrap_or(path);
+            if path.is_empty() {
+                return Err(format!("expected unix:PATH, got `{s}`"));
+            }
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        Err(format!(
+            "unsupported listen address `{s}`; use stdio, ws://HOST:PORT, or unix:PATH"
+        ))
+    }
+}
+
+impl fmt::Display for ListenAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdio => write!(f, "stdio"),
+            Self::WebSocket(addr) => write!(f, "ws://{addr}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Serves the single stdio client until stdin reaches EOF.
+pub(crate) async fn serve_stdio(context: Arc<ServerContext>) {
+    let (incoming_tx, incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+    let (outgoing_tx, outgoing_rx) = mpsc::channel::<OutgoingMessage>(CHANNEL_CAPACITY);
+
+    let reader_handle = tokio::spawn(read_lines(tokio::io::stdin(), incoming_tx));
+    let processor_handle = tokio::spawn(serve_connection(context, incoming_rx, outgoing_tx, true));
+    let writer_handle = tokio::spawn(write_lines(tokio::io::stdout(), outgoing_rx));
+
+    // The typical exit path is the reader hitting EOF which, once it drops
+    // `incoming_tx`, propagates shutdown to the processor and then to the
+    // writer.
+    let _ = tokio::join!(reader_handle, processor_handle, writer_handle);
+}
+
+/// Accepts WebSocket clients until Ctrl-C or a listener error.
+pub(crate) async fn serve_websocket(context: Arc<ServerContext>, addr: &str) -> IoResult<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("app server listening on ws://{}", listener.local_addr()?);
+    tokio::select! {
+        result = accept_websocket_clients(listener, context) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+async fn accept_websocket_clients(
+    listener: TcpListener,
+    context: Arc<ServerContext>,
+) -> IoResult<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let context = Arc::clone(&context);
+        tokio::spawn(async move {
+            debug!("websocket client connected from {peer}");
+            if let Err(err) = serve_websocket_client(context, stream).await {
+                warn!("websocket client {peer}: {err}");
+            }
+            debug!("websocket client {peer} disconnected");
+        });
+    }
+}
+
+/// Accepts Unix socket clients until Ctrl-C or a listener error. The socket
+/// is only accessible to the current user and is removed on exit.
+#[cfg(unix)]
+pub(crate) async fn serve_unix(
+    context: Arc<ServerContext>,
+    path: &std::path::Path,
+) -> IoResult<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // Replace a socket left behind by a previous run, but never another file.
+    if let Ok(metadata) = std::fs::symlink_metadata(path)
+        && metadata.file_type().is_socket()
+    {
+        std::fs::remove_file(path)?;
+    }
+    let listener = bind_private_unix_socket(path)?;
+    info!("app server listening on unix:{}", path.display());
+    let result = tokio::select! {
+        result = accept_unix_clients(listener, context) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
+    let _ = std::fs::remove_file(path);
+    result
+}
+
+/// Binds a Unix socket at `path` that only the current user can connect to.
+///
+/// `bind` creates the socket with the process umask, so chmod-ing it
+/// afterwards would leave a window in which anyone could connect. Instead the
+/// socket is bound and restricted inside a fresh 0700 directory next to
+/// `path`, then hard-linked into place, which also refuses to replace an
+/// existing file.
+#[cfg(unix)]
+fn bind_private_unix_socket(path: &std::path::Path) -> IoResult<tokio::net::UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let staging = tempfile::Builder::new()
+        .prefix(".code-app-server-")
+        .tempdir_in(parent)?;
+    let staged = staging.path().join("socket");
+    let listener = tokio::net::UnixListener::bind(&staged)?;
+    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+    std::fs::hard_link(&staged, path)?;
+    Ok(listener)
+}
+
+#[cfg(unix)]
+async fn accept_unix_clients(
+    listener: tokio::net::UnixListener,
+    context: Arc<ServerContext>,
+) -> IoResult<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let context = Arc::clone(&context);
+        tokio::spawn(async move {
+            debug!("unix socket client connected");
+            let authenticated = context.auth_token.is_none();
+            let (reader, writer) = stream.into_split();
+            serve_stream(context, reader, writer, authenticated).await;
+            debug!("unix socket client disconnected");
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) async fn serve_unix(
+    _context: Arc<ServerContext>,
+    _path: &std::path::Path,
+) -> IoResult<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "unix sockets are not supported on this platform",
+    ))
+}
+
+/// Serves one newline-delimited JSON client.
+#[cfg(unix)]
+async fn serve_stream<R, W>(context: Arc<ServerContext>, reader: R, writer: W, authenticated: bool)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (incoming_tx, incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+    let (outgoing_tx, outgoing_rx) = mpsc::channel::<OutgoingMessage>(CHANNEL_CAPACITY);
+    let reader_handle = tokio::spawn(read_lines(reader, incoming_tx));
+    let writer_handle = tokio::spawn(write_lines(writer, outgoing_rx));
+    serve_connection(context, incoming_rx, outgoing_tx, authenticated).await;
+    let _ = tokio::join!(reader_handle, writer_handle);
+}
+
+async fn serve_websocket_client(context: Arc<ServerContext>, stream: TcpStream) -> IoResult<()> {
+    // A correct `Authorization: Bearer` header authenticates the connection
+    // up front; without one the client must pass the token to `initialize`.
+    let mut authenticated = context.auth_token.is_none();
+    let check_header = |request: &Request, response: Response| {
+        let Some(expected) = context.auth_token.as_deref() else {
+            return Ok(response);
+        };
+        let Some(header) = request.headers().get("authorization") else {
+            return Ok(response);
+        };
+        let presented = header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if tokens_match(presented, expected) {
+            authenticated = true;
+            Ok(response)
+        } else {
+            let mut rejection = ErrorResponse::new(Some("invalid auth token".to_string()));
+            *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(rejection)
+        }
+    };
+    let ws = tokio_tungstenite::accept_hdr_async(stream, check_header)
+        .await
+        .map_err(std::io::Error::other)?;
+    let (mut sink, mut source) = ws.split();
+
+    let (incoming_tx, incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+    let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<OutgoingMessage>(CHANNEL_CAPACITY);
+
+    let reader_handle = tokio::spawn(async move {
+        while let Some(frame) = source.next().await {
+            let text = match frame {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(err) => {
+                    warn!("websocket read failed: {err}");
+                    break;
+                }
+            };
+            match serde_json::from_str::<JSONRPCMessage>(&text) {
+                Ok(msg) => {
+                    if incoming_tx.send(msg).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => error!("Failed to deserialize JSONRPCMessage: {e}"),
+            }
+        }
+    });
+
+    let writer_handle = tokio::spawn(async move {
+        while let Some(outgoing_message) = outgoing_rx.recv().await {
+            let msg: JSONRPCMessage = outgoing_message.into();
+            match serde_json::to_string(&msg) {
+                Ok(json) => {
+                    if let Err(e) = sink.send(Message::Text(json)).await {
+                        error!("Failed to write to websocket: {e}");
+                        break;
+                    }
+                }
+                Err(e) => error!("Failed to serialize JSONRPCMessage: {e}"),
+            }
+        }
+        let _ = sink.close().await;
+    });
+
+    serve_connection(context, incoming_rx, outgoing_tx, authenticated).await;
+    let _ = tokio::join!(reader_handle, writer_handle);
+    Ok(())
+}
+
+/// Reads newline-delimited JSON-RPC messages into `incoming_tx`.
+async fn read_lines<R: AsyncRead + Unpin>(reader: R, incoming_tx: mpsc::Sender<JSONRPCMessage>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await.unwrap_or_default() {
+        match serde_json::from_str::<JSONRPCMessage>(&line) {
+            Ok(msg) => {
+                if incoming_tx.send(msg).await.is_err() {
+                    // Receiver gone – nothing left to do.
+                    break;
+                }
+            }
+            Err(e) => error!("Failed to deserialize JSONRPCMessage: {e}"),
+        }
+    }
+
+    debug!("reader finished (EOF)");
+}
+
+/// Writes outgoing messages as newline-delimited JSON.
+async fn write_lines<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    mut outgoing_rx: mpsc::Receiver<OutgoingMessage>,
+) {
+    while let Some(outgoing_message) = outgoing_rx.recv().await {
+        let msg: JSONRPCMessage = outgoing_message.into();
+        match serde_json::to_string(&msg) {
+            Ok(mut json) => {
+                json.push('\n');
+                if let Err(e) = writer.write_all(json.as_bytes()).await {
+                    error!("Failed to write message: {e}");
+                    break;
+                }
+                if let Err(e) = writer.flush().await {
+                    error!("Failed to flush message: {e}");
+                    break;
+                }
+            }
+            Err(e) => error!("Failed to serialize JSONRPCMessage: {e}"),
+        }
+    }
+
+    info!("writer exited (channel closed)");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_listen_addresses() {
+        assert_eq!("stdio".parse(), Ok(ListenAddress::Stdio));
+        assert_eq!(
+            "ws://127.0.0.1:4500".parse(),
+            Ok(ListenAddress::WebSocket("127.0.0.1:4500".to_string()))
+        );
+        assert_eq!(
+            "ws://localhost:4500/".parse(),
+            Ok(ListenAddress::WebSocket("localhost:4500".to_string()))
+        );
+        assert_eq!(
+            "unix:/tmp/code.sock".parse(),
+            Ok(ListenAddress::Unix(PathBuf::from("/tmp/code.sock")))
+        );
+        assert_eq!(
+            "unix:///tmp/code.sock".parse(),
+            Ok(ListenAddress::Unix(PathBuf::from("/tmp/code.sock")))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_listen_addresses() {
+        for addr in [
+            "ws://127.0.0.1",
+            "ws://:4500",
+            "ws://host:port",
+            "ws://host:4500/path",
+            "unix:",
+            "tcp://127.0.0.1:4500",
+        ] {
+            assert!(
+                addr.parse::<ListenAddress>().is_err(),
+                "{addr} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn listen_address_round_trips_through_display() {
+        for addr in ["stdio", "ws://127.0.0.1:4500", "unix:/tmp/code.sock"] {
+            let parsed: ListenAddress = addr.parse().expect("parse");
+            assert_eq!(parsed.to_string(), addr);
+        }
+    }
+
+    fn initialize_request(id: i64, auth_token: Option<&str>) -> serde_json::Value {
+        let mut params = serde_json::json!({
+            "clientInfo": {"name": "test", "version": "0.0.0"},
+        });
+        if let Some(token) = auth_token {
+            params["authToken"] = token.into();
+        }
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "initialize",
+            "params": params,
+        })
+    }
+
+    fn test_context(code_home: &tempfile::TempDir, auth_token: &str) -> Arc<ServerContext> {
+        use code_core::config::Config;
+        use code_core::config::ConfigOverrides;
+        use code_core::config::ConfigToml;
+
+        let config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            code_home.path().to_path_buf(),
+        )
+        .expect("config");
+        Arc::new(ServerContext::new(
+            None,
+            Arc::new(config),
+            Some(auth_token.to_string()),
+        ))
+    }
+
+    #[cfg(unix)]
+    struct LineClient {
+        lines: tokio::io::Lines<BufReader<tokio::net::unix::OwnedReadHalf>>,
+        writer: tokio::net::unix::OwnedWriteHalf,
+        next_id: i64,
+    }
+
+    #[cfg(unix)]
+    impl LineClient {
+        async fn connect(path: &std::path::Path) -> Self {
+            let stream = tokio::net::UnixStream::connect(path)
+                .await
+                .expect("connect");
+            let (reader, writer) = stream.into_split();
+            Self {
+                lines: BufReader::new(reader).lines(),
+                writer,
+                next_id: 0,
+            }
+        }
+
+        async fn initialize(&mut self, auth_token: Option<&str>) -> serde_json::Value {
+            self.next_id += 1;
+            let mut line = initialize_request(self.next_id, auth_token).to_string();
+            line.push('\n');
+            self.writer
+                .write_all(line.as_bytes())
+                .await
+                .expect("write request");
+            let reply = self
+                .lines
+                .next_line()
+                .await
+                .expect("read reply")
+                .expect("reply line");
+            serde_json::from_str(&reply).expect("reply json")
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_clients_authenticate_independently() {
+        let code_home = tempfile::TempDir::new().expect("tempdir");
+        let context = test_context(&code_home, "secret");
+        let socket = code_home.path().join("app-server.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).expect("bind");
+        tokio::spawn(accept_unix_clients(listener, context));
+
+        let mut first = LineClient::connect(&socket).await;
+        let mut second = LineClient::connect(&socket).await;
+
+        let rejected = first.initialize(None).await;
+        assert_eq!(rejected["error"]["message"], "Unauthorized");
+        let rejected = first.initialize(Some("wrong")).await;
+        assert_eq!(rejected["error"]["message"], "Unauthorized");
+        let accepted = first.initialize(Some("secret")).await;
+        assert!(accepted["result"]["userAgent"].is_string(), "{accepted}");
+
+        // The second connection has its own session state.
+        let accepted = second.initialize(Some("secret")).await;
+        assert!(accepted["result"]["userAgent"].is_string(), "{accepted}");
+        let again = first.initialize(Some("secret")).await;
+        assert_eq!(again["error"]["message"], "Already initialized");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_is_private_from_the_start() {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let socket = dir.path().join("app-server.sock");
+        let _listener = bind_private_unix_socket(&socket).expect("bind");
+
+        let metadata = std::fs::symlink_metadata(&socket).expect("socket metadata");
+        assert!(metadata.file_type().is_socket());
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        // The staging directory is gone once the socket is in place.
+        let entries: Vec<_> = std::fs::read_dir(dir.path())
+            .expect("read dir")
+            .map(|entry| entry.expect("entry").file_name())
+            .collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("app-server.sock")]);
+
+        // An existing file is never replaced.
+        let other = dir.path().join("notes.txt");
+        std::fs::write(&other, "keep").expect("write");
+        let err = bind_private_unix_socket(&other).expect_err("must not replace a file");
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&other).expect("read"), "keep");
+    }
+
+    async fn websocket_initialize(
+        url: &str,
+        bearer: Option<&str>,
+        auth_token: Option<&str>,
+    ) -> Result<serde_json::Value, tokio_tungstenite::tungstenite::Error> {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let mut request = url.into_client_request()?;
+        if let Some(bearer) = bearer {
+            request.headers_mut().insert(
+                "authorization",
+                format!("Bearer {bearer}").parse().expect("header value"),
+            );
+        }
+        let (mut ws, _) = tokio_tungstenite::connect_async(request).await?;
+        ws.send(Message::Text(initialize_request(1, auth_token).to_string()))
+            .await?;
+        loop {
+            match ws.next().await.expect("reply frame")? {
+                Message::Text(text) => return Ok(serde_json::from_str(&text).expect("reply json")),
+                _ => continue,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn websocket_clients_authenticate_by_header_or_initialize() {
+        let code_home = tempfile::TempDir::new().expect("tempdir");
+        let context = test_context(&code_home, "secret");
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let url = format!("ws://{}", listener.local_addr().expect("local addr"));
+        tokio::spawn(accept_websocket_clients(listener, context));
+
+        // A wrong bearer token is rejected during the handshake.
+        match websocket_initialize(&url, Some("wrong"), None).await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            }
+            other => panic!("expected 401, got {other:?}"),
+        }
+
+        // The right bearer token authenticates the connection up front.
+        let accepted = websocket_initialize(&url, Some("secret"), None)
+            .await
+            .expect("handshake");
+        assert!(accepted["result"]["userAgent"].is_string(), "{accepted}");
+
+        // Without a header the token has to come with `initialize`.
+        let rejected = websocket_initialize(&url, None, None)
+            .await
+            .expect("handshake");
+        assert_eq!(rejected["error"]["message"], "Unauthorized");
+        let accepted = websocket_initialize(&url, None, Some("secret"))
+            .await
+            .expect("handshake");
+        assert!(accepted["result"]["userAgent"].is_string(), "{accepted}");
+    }
+}
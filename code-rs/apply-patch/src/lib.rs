@@ -1,6 +1,7 @@
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod transaction;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -25,6 +26,7 @@ use tree_sitter::StreamingIterator;
 use tree_sitter_bash::LANGUAGE as BASH;
 
 pub use standalone_executable::main;
+use transaction::StagedChanges;
 
 // Back-compat shim for code-core callers
 // The core crate expects a simple async FileSystem abstraction and a default
@@ -36,6 +38,30 @@ pub use standalone_executable::main;
 pub trait FileSystem {
     async fn read_text_file(&self, path: &Path) -> std::io::Result<String>;
     async fn write_text_file(&self, path: &Path, contents: String) -> std::io::Result<()>;
+
+    /// Apply every change in `action` all-or-nothing. By default new contents
+    /// are written through this file system, and the files already written
+    /// are restored if a later change fails.
+    async fn apply_action(
+        &self,
+        action: &ApplyPatchAction,
+    ) -> std::result::Result<AffectedPaths, ApplyPatchError>
+    where
+        Self: Sized,
+    {
+        apply_action_through(self, action).await
+    }
+}
+
+/// Apply `action` by writing new contents through `file_system`. Files
+/// written before a failing change are restored from what they held before.
+pub async fn apply_action_through(
+    file_system: &impl FileSystem,
+    action: &ApplyPatchAction,
+) -> std::result::Result<AffectedPaths, ApplyPatchError> {
+    let (affected, staged) = stage_action(action);
+    staged.commit_through(file_system).await?;
+    Ok(affected)
 }
 
 pub struct StdFileSystem;
@@ -48,6 +74,17 @@ impl FileSystem for StdFileSystem {
     async fn write_text_file(&self, path: &Path, contents: String) -> std::io::Result<()> {
         std::fs::write(path, contents)
     }
+
+    /// Stages every file next to its target and renames them into place,
+    /// like the `apply_patch` command itself.
+    async fn apply_action(
+        &self,
+        action: &ApplyPatchAction,
+    ) -> std::result::Result<AffectedPaths, ApplyPatchError> {
+        let (affected, staged) = stage_action(action);
+        staged.commit()?;
+        Ok(affected)
+    }
 }
 
 /// Detailed instructions for gpt-4.1 on how to use the `apply_patch` tool.
@@ -55,6 +92,8 @@ pub const APPLY_PATCH_TOOL_INSTRUCTIONS: &str = include_str!("../apply_patch_too
 
 const APPLY_PATCH_COMMANDS: [&str; 2] = ["apply_patch", "applypatch"];
 
+const UTF8_BOM: &str = "\u{feff}";
+
 fn looks_like_bash(cmd: &str) -> bool {
     let trimmed = cmd.trim_matches('"').trim_matches('\'');
     if trimmed.eq_ignore_ascii_case("bash") || trimmed.eq_ignore_ascii_case("bash.exe") {
@@ -81,6 +120,15 @@ pub enum ApplyPatchError {
         "patch detected without explicit call to apply_patch. Rerun as [\"apply_patch\", \"<patch>\"]"
     )]
     ImplicitInvocation,
+    /// A hunk could not be applied. The patch is all-or-nothing, so no files
+    /// were changed.
+    #[error("hunk {hunk} ({}) failed, no files were changed: {source}", .path.display())]
+    HunkFailed {
+        /// 1-based index of the hunk within the patch.
+        hunk: usize,
+        path: PathBuf,
+        source: Box<ApplyPatchError>,
+    },
 }
 
 impl From<std::io::Error> for ApplyPatchError {
@@ -196,6 +244,14 @@ pub enum MaybeApplyPatchVerified {
 pub struct ApplyPatchAction {
     changes: HashMap<PathBuf, ApplyPatchFileChange>,
 
+    /// Unix permission bits requested with `*** Set Mode:`, keyed by the path
+    /// the file ends up at.
+    modes: HashMap<PathBuf, u32>,
+
+    /// 1-based position in the patch of the hunk behind each change, keyed
+    /// like `changes`, so errors name the hunk the model wrote.
+    hunk_numbers: HashMap<PathBuf, usize>,
+
     /// The raw patch argument that can be used with `apply_patch` as an exec
     /// call. i.e., if the original arg was parsed in "lenient" mode with a
     /// heredoc, this should be the value without the heredoc wrapper.
@@ -215,6 +271,11 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    /// Returns the permission bits the patch sets, keyed by final path.
+    pub fn modes(&self) -> &HashMap<PathBuf, u32> {
+        &self.modes
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -238,6 +299,8 @@ impl ApplyPatchAction {
         #[expect(clippy::expect_used)]
         Self {
             changes,
+            modes: HashMap::new(),
+            hunk_numbers: HashMap::from([(path.to_path_buf(), 1)]),
             cwd: path
                 .parent()
                 .expect("path should have parent")
@@ -288,10 +351,16 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                 })
                 .unwrap_or_else(|| cwd.to_path_buf());
             let mut changes = HashMap::new();
-            for hunk in hunks {
+            let mut modes = HashMap::new();
+            let mut hunk_numbers = HashMap::new();
+            for (index, hunk) in hunks.into_iter().enumerate() {
                 let path = hunk.resolve_path(&effective_cwd);
+                hunk_numbers.insert(path.clone(), index + 1);
                 match hunk {
-                    Hunk::AddFile { contents, .. } => {
+                    Hunk::AddFile { contents, mode, .. } => {
+                        if let Some(mode) = mode {
+                            modes.insert(path.clone(), mode);
+                        }
                         changes.insert(path, ApplyPatchFileChange::Add { content: contents });
                     }
                     Hunk::DeleteFile { .. } => {
@@ -309,7 +378,10 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         changes.insert(path, ApplyPatchFileChange::Delete { content });
                     }
                     Hunk::UpdateFile {
-                        move_path, chunks, ..
+                        move_path,
+                        mode,
+                        chunks,
+                        ..
                     } => {
                         let ApplyPatchFileUpdate {
                             unified_diff,
@@ -320,11 +392,15 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
                             }
                         };
+                        let move_path = move_path.map(|p| cwd.join(p));
+                        if let Some(mode) = mode {
+                            modes.insert(move_path.clone().unwrap_or_else(|| path.clone()), mode);
+                        }
                         changes.insert(
                             path,
                             ApplyPatchFileChange::Update {
                                 unified_diff,
-                                move_path: move_path.map(|p| cwd.join(p)),
+                                move_path,
                                 new_content: contents,
                             },
                         );
@@ -333,6 +409,8 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
             }
             MaybeApplyPatchVerified::Body(ApplyPatchAction {
                 changes,
+                modes,
+                hunk_numbers,
                 patch,
                 cwd: effective_cwd,
             })
@@ -603,67 +681,165 @@ pub struct AffectedPaths {
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
-/// Returns an error if the patch could not be applied.
+/// Returns an error if the patch could not be applied. Every hunk is staged
+/// before anything is written, and the writes are committed all-or-nothing,
+/// so a failing hunk never leaves the tree half-patched.
 fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     if hunks.is_empty() {
         anyhow::bail!("No files were modified.");
     }
 
+    let (affected, staged) = stage_hunks(hunks)?;
+    staged.commit()?;
+    Ok(affected)
+}
+
+/// Compute the final contents of every path touched by `hunks` without
+/// writing anything. Later hunks see the effect of earlier ones, so a patch
+/// may add a file and then update it.
+fn stage_hunks(
+    hunks: &[Hunk],
+) -> std::result::Result<(AffectedPaths, StagedChanges), ApplyPatchError> {
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
-    for hunk in hunks {
+    let mut staged = StagedChanges::default();
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let number = index + 1;
+        let failed = |path: &Path, err: ApplyPatchError| ApplyPatchError::HunkFailed {
+            hunk: number,
+            path: path.to_path_buf(),
+            source: Box::new(err),
+        };
         match hunk {
-            Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+            Hunk::AddFile {
+                path,
+                contents,
+                mode,
+            } => {
+                staged.stage(number, path, Some(contents.clone()), *mode);
                 added.push(path.clone());
             }
             Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
+                let exists = match staged.get(path) {
+                    Some(contents) => contents.is_some(),
+                    None => path.exists(),
+                };
+                if !exists {
+                    return Err(failed(
+                        path,
+                        ApplyPatchError::IoError(IoError {
+                            context: format!("Failed to delete file {}", path.display()),
+                            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+                        }),
+                    ));
+                }
+                staged.stage(number, path, None, None);
                 deleted.push(path.clone());
             }
             Hunk::UpdateFile {
                 path,
                 move_path,
+                mode,
                 chunks,
             } => {
+                let original_contents = match staged.get(path) {
+                    Some(Some(contents)) => contents.to_string(),
+                    Some(None) => {
+                        return Err(failed(
+                            path,
+                            ApplyPatchError::IoError(IoError {
+                                context: format!(
+                                    "Failed to read file to update {}",
+                                    path.display()
+                                ),
+                                source: std::io::Error::from(std::io::ErrorKind::NotFound),
+                            }),
+                        ));
+                    }
+                    None => read_file_to_update(path).map_err(|err| failed(path, err))?,
+                };
                 let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
+                    apply_chunks_to_contents(original_contents, path, chunks)
+                        .map_err(|err| failed(path, err))?;
                 if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
-                        })?;
-                    }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
+                    // A moved file keeps its permissions unless the hunk sets new ones.
+                    let mode = mode.or_else(|| staged.mode(path));
+                    staged.stage(number, dest, Some(new_contents), mode);
+                    staged.stage(number, path, None, None);
                     modified.push(dest.clone());
                 } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    staged.stage(number, path, Some(new_contents), *mode);
                     modified.push(path.clone());
                 }
             }
         }
     }
-    Ok(AffectedPaths {
-        added,
-        modified,
-        deleted,
-    })
+
+    Ok((
+        AffectedPaths {
+            added,
+            modified,
+            deleted,
+        },
+        staged,
+    ))
+}
+
+/// Stage the already computed contents of `action` in patch order. Errors
+/// report the position of each change's hunk in the patch.
+fn stage_action(action: &ApplyPatchAction) -> (AffectedPaths, StagedChanges) {
+    let mut added: Vec<PathBuf> = Vec::new();
+    let mut modified: Vec<PathBuf> = Vec::new();
+    let mut deleted: Vec<PathBuf> = Vec::new();
+    let mut staged = StagedChanges::default();
+
+    let hunk_number = |path: &Path| action.hunk_numbers.get(path).copied().unwrap_or_default();
+    let mut paths: Vec<&PathBuf> = action.changes.keys().collect();
+    paths.sort_by_key(|path| (hunk_number(path), *path));
+    for path in paths {
+        let number = hunk_number(path);
+        let mode = |path: &Path| action.modes.get(path).copied();
+        match &action.changes[path] {
+            ApplyPatchFileChange::Add { content } => {
+                staged.stage(number, path, Some(content.clone()), mode(path));
+                added.push(path.clone());
+            }
+            ApplyPatchFileChange::Delete { .. } => {
+                staged.stage(number, path, None, None);
+                deleted.push(path.clone());
+            }
+            ApplyPatchFileChange::Update {
+                move_path: Some(dest),
+                new_content,
+                ..
+            } => {
+                let dest_mode = mode(dest).or_else(|| staged.mode(path));
+                staged.stage(number, dest, Some(new_content.clone()), dest_mode);
+                staged.stage(number, path, None, None);
+                modified.push(dest.clone());
+                deleted.push(path.clone());
+            }
+            ApplyPatchFileChange::Update {
+                move_path: None,
+                new_content,
+                ..
+            } => {
+                staged.stage(number, path, Some(new_content.clone()), mode(path));
+                modified.push(path.clone());
+            }
+        }
+    }
+
+    (
+        AffectedPaths {
+            added,
+            modified,
+            deleted,
+        },
+        staged,
+    )
 }
 
 struct AppliedPatch {
@@ -677,17 +853,48 @@ fn derive_new_contents_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
 ) -> std::result::Result<AppliedPatch, ApplyPatchError> {
-    let original_contents = match std::fs::read_to_string(path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            return Err(ApplyPatchError::IoError(IoError {
-                context: format!("Failed to read file to update {}", path.display()),
-                source: err,
-            }));
-        }
+    let original_contents = read_file_to_update(path)?;
+    apply_chunks_to_contents(original_contents, path, chunks)
+}
+
+fn read_file_to_update(path: &Path) -> std::result::Result<String, ApplyPatchError> {
+    std::fs::read_to_string(path).map_err(|err| {
+        ApplyPatchError::IoError(IoError {
+            context: format!("Failed to read file to update {}", path.display()),
+            source: err,
+        })
+    })
+}
+
+/// Apply the chunks to `original_contents`, the current contents of `path`.
+/// Chunks are matched against the text with any UTF-8 BOM removed and CRLF
+/// line endings normalised to LF; both are restored in the new contents so the
+/// file keeps its original encoding.
+fn apply_chunks_to_contents(
+    original_contents: String,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<AppliedPatch, ApplyPatchError> {
+    if chunks.is_empty() {
+        // A hunk that only changes the file mode leaves the contents alone.
+        return Ok(AppliedPatch {
+            new_contents: original_contents.clone(),
+            original_contents,
+        });
+    }
+
+    let (bom, body) = match original_contents.strip_prefix(UTF8_BOM) {
+        Some(body) => (UTF8_BOM, body),
+        None => ("", original_contents.as_str()),
+    };
+    let crlf = uses_crlf(body);
+    let body = if crlf {
+        body.replace("\r\n", "\n")
+    } else {
+        body.to_string()
     };
 
-    let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
+    let mut original_lines: Vec<String> = body.split('\n').map(String::from).collect();
 
     // Drop the trailing empty element that results from the final newline so
     // that line counts match the behaviour of standard `diff`.
@@ -701,13 +908,21 @@ fn derive_new_contents_from_chunks(
     if !new_lines.last().is_some_and(String::is_empty) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
+    let line_ending = if crlf { "\r\n" } else { "\n" };
+    let new_contents = format!("{bom}{}", new_lines.join(line_ending));
     Ok(AppliedPatch {
         original_contents,
         new_contents,
     })
 }
 
+/// Whether most line breaks in `text` are CRLF rather than bare LF.
+fn uses_crlf(text: &str) -> bool {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count();
+    crlf > 0 && crlf * 2 >= lf
+}
+
 /// Compute a list of replacements needed to transform `original_lines` into the
 /// new lines, given the patch `chunks`. Each replacement is returned as
 /// `(start_index, old_len, new_lines)`.
@@ -921,6 +1136,7 @@ mod tests {
         vec![Hunk::AddFile {
             path: PathBuf::from("foo"),
             contents: "hi\n".to_string(),
+            mode: None,
         }]
     }
 
@@ -991,7 +1207,8 @@ mod tests {
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        mode: None,
                     }]
                 );
             }
@@ -1016,7 +1233,8 @@ mod tests {
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        mode: None,
                     }]
                 );
             }
@@ -1060,7 +1278,8 @@ PATCH"#,
                         hunks,
                         vec![Hunk::AddFile {
                             path: PathBuf::from("foo"),
-                            contents: "hi\n".to_string()
+                            contents: "hi\n".to_string(),
+                            mode: None,
                         }]
                     );
                 }
@@ -1660,6 +1879,8 @@ g
                         new_content: "updated session directory content\n".to_string(),
                     },
                 )]),
+                modes: HashMap::new(),
+                hunk_numbers: HashMap::from([(session_dir.path().join(relative_path), 1)]),
                 patch: argv[1].clone(),
                 cwd: session_dir.path().to_path_buf(),
             })
@@ -1685,4 +1906,267 @@ g
         let result = apply_patch(&patch, &mut stdout, &mut stderr);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_failed_hunk_leaves_tree_untouched() {
+        let dir = tempdir().unwrap();
+        let added = dir.path().join("added.txt");
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        fs::write(&first, "one\n").unwrap();
+        fs::write(&second, "two\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Add File: {}
++new
+*** Update File: {}
+@@
+-one
++uno
+*** Update File: {}
+@@
+-missing
++dos"#,
+            added.display(),
+            first.display(),
+            second.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let err = apply_patch(&patch, &mut stdout, &mut stderr).unwrap_err();
+
+        let ApplyPatchError::HunkFailed { hunk, path, .. } = &err else {
+            panic!("expected HunkFailed, got {err:?}");
+        };
+        assert_eq!(*hunk, 3);
+        assert_eq!(path, &second);
+        assert!(stdout.is_empty());
+        assert!(!added.exists());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "one\n");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "two\n");
+    }
+
+    #[test]
+    fn test_unwritable_target_rolls_back_earlier_files() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let blocker = dir.path().join("blocker");
+        fs::write(&first, "one\n").unwrap();
+        fs::write(&blocker, "not a directory\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-one
++uno
+*** Add File: {}
++new"#,
+            first.display(),
+            blocker.join("new.txt").display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let err = apply_patch(&patch, &mut stdout, &mut stderr).unwrap_err();
+
+        assert!(matches!(err, ApplyPatchError::HunkFailed { hunk: 2, .. }));
+        assert_eq!(fs::read_to_string(&first).unwrap(), "one\n");
+        let leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(
+            leftovers.len(),
+            2,
+            "temporary files left behind: {leftovers:?}"
+        );
+    }
+
+    #[test]
+    fn test_verified_action_reports_the_hunk_position_in_the_patch() {
+        let dir = tempdir().unwrap();
+        let last = dir.path().join("z.txt");
+        let blocker = dir.path().join("a_blocker");
+        fs::write(&last, "one\n").unwrap();
+        fs::write(&blocker, "not a directory\n").unwrap();
+        // The failing path sorts first but is the second hunk of the patch.
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-one
++uno
+*** Add File: {}
++new"#,
+            last.display(),
+            blocker.join("new.txt").display()
+        ));
+        let argv = vec!["apply_patch".to_string(), patch];
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified(&argv, dir.path())
+        else {
+            panic!("expected a verified patch");
+        };
+
+        let (_, staged) = stage_action(&action);
+        let err = staged.commit().unwrap_err();
+
+        let ApplyPatchError::HunkFailed { hunk, path, .. } = &err else {
+            panic!("expected HunkFailed, got {err:?}");
+        };
+        assert_eq!(*hunk, 2);
+        assert_eq!(path, &blocker.join("new.txt"));
+        assert_eq!(fs::read_to_string(&last).unwrap(), "one\n");
+    }
+
+    #[test]
+    fn test_failed_swap_restores_originals_and_removes_created_dirs() {
+        let dir = tempdir().unwrap();
+        let keep = dir.path().join("keep.txt");
+        let nested = dir.path().join("nested");
+        let victim = dir.path().join("victim.txt");
+        fs::write(&keep, "old\n").unwrap();
+        fs::write(&victim, "doomed\n").unwrap();
+        // A non-empty directory where the deleted file would be moved aside
+        // makes the last rename fail, after the first two were swapped in.
+        let blocked_backup = transaction::sibling_path(&victim, "orig");
+        fs::create_dir(&blocked_backup).unwrap();
+        fs::write(blocked_backup.join("occupied"), "").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-old
++new
+*** Add File: {}
++added
+*** Delete File: {}"#,
+            keep.display(),
+            nested.join("deeper/added.txt").display(),
+            victim.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let err = apply_patch(&patch, &mut stdout, &mut stderr).unwrap_err();
+
+        assert!(matches!(err, ApplyPatchError::HunkFailed { hunk: 3, .. }));
+        assert_eq!(fs::read_to_string(&keep).unwrap(), "old\n");
+        assert_eq!(fs::read_to_string(&victim).unwrap(), "doomed\n");
+        assert!(!nested.exists());
+        let mut leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        leftovers.sort();
+        let mut expected = vec![blocked_backup, keep, victim];
+        expected.sort();
+        assert_eq!(leftovers, expected);
+    }
+
+    #[test]
+    fn test_later_hunks_see_earlier_hunks() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("new.txt");
+        let patch = wrap_patch(&format!(
+            r#"*** Add File: {0}
++first
+*** Update File: {0}
+@@
+-first
++second"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+    }
+
+    #[test]
+    fn test_update_preserves_crlf_and_bom() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("windows.txt");
+        fs::write(&path, "\u{feff}alpha\r\nbeta\r\ngamma\r\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ alpha
+-beta
++BETA
++delta"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{feff}alpha\r\nBETA\r\ndelta\r\ngamma\r\n"
+        );
+    }
+
+    #[test]
+    fn test_update_keeps_lf_files_lf() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("unix.txt");
+        fs::write(&path, "alpha\nbeta\r\ngamma\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-alpha
++ALPHA"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ALPHA\nbeta\r\ngamma\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_move_keeps_permissions_and_set_mode_changes_them() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("run.sh");
+        let dest = dir.path().join("bin/run.sh");
+        let added = dir.path().join("build.sh");
+        fs::write(&src, "echo hi\n").unwrap();
+        fs::set_permissions(&src, fs::Permissions::from_mode(0o755)).unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+*** Move to: {}
+@@
+-echo hi
++echo hello
+*** Add File: {}
+*** Set Mode: 750
++make"#,
+            src.display(),
+            dest.display(),
+            added.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(&dest), 0o755);
+        assert_eq!(mode(&added), 0o750);
+
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n*** Set Mode: 644",
+            dest.display()
+        ));
+        let mut stdout = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(mode(&dest), 0o644);
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "echo hello\n");
+    }
 }
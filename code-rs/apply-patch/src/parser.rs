@@ -8,13 +8,14 @@
 //! end_patch: "*** End Patch" LF?
 //!
 //! hunk: add_hunk | delete_hunk | update_hunk
-//! add_hunk: "*** Add File: " filename LF add_line+
+//! add_hunk: "*** Add File: " filename LF set_mode? add_line+
 //! delete_hunk: "*** Delete File: " filename LF
-//! update_hunk: "*** Update File: " filename LF change_move? change?
+//! update_hunk: "*** Update File: " filename LF change_move? set_mode? change?
 //! filename: /(.+)/
 //! add_line: "+" /(.+)/ LF -> line
 //!
 //! change_move: "*** Move to: " filename LF
+//! set_mode: "*** Set Mode: " /0?[0-7]{3}/ LF
 //! change: (change_context | change_line)+ eof_line?
 //! change_context: ("@@" | "@@ " /(.+)/) LF
 //! change_line: ("+" | "-" | " ") /(.+)/ LF
@@ -34,6 +35,7 @@ const ADD_FILE_MARKER: &str = "*** Add File: ";
 const DELETE_FILE_MARKER: &str = "*** Delete File: ";
 const UPDATE_FILE_MARKER: &str = "*** Update File: ";
 const MOVE_TO_MARKER: &str = "*** Move to: ";
+const SET_MODE_MARKER: &str = "*** Set Mode: ";
 const EOF_MARKER: &str = "*** End of File";
 const CHANGE_CONTEXT_MARKER: &str = "@@ ";
 const EMPTY_CHANGE_CONTEXT_MARKER: &str = "@@";
@@ -61,6 +63,8 @@ pub enum Hunk {
     AddFile {
         path: PathBuf,
         contents: String,
+        /// Unix permission bits from `*** Set Mode:`, e.g. `0o755`.
+        mode: Option<u32>,
     },
     DeleteFile {
         path: PathBuf,
@@ -68,6 +72,8 @@ pub enum Hunk {
     UpdateFile {
         path: PathBuf,
         move_path: Option<PathBuf>,
+        /// Unix permission bits from `*** Set Mode:`, e.g. `0o755`.
+        mode: Option<u32>,
 
         /// Chunks should be in order, i.e. the `change_context` of one chunk
         /// should occur later in the file than the previous chunk.
@@ -249,7 +255,11 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
         // Add File
         let mut contents = String::new();
         let mut parsed_lines = 1;
-        for add_line in &lines[1..] {
+        let mode = parse_set_mode(lines.get(1), line_number + 1)?;
+        if mode.is_some() {
+            parsed_lines += 1;
+        }
+        for add_line in &lines[parsed_lines..] {
             if let Some(line_to_add) = add_line.strip_prefix('+') {
                 contents.push_str(line_to_add);
                 contents.push('\n');
@@ -262,6 +272,7 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             AddFile {
                 path: PathBuf::from(path),
                 contents,
+                mode,
             },
             parsed_lines,
         ));
@@ -288,6 +299,13 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             parsed_lines += 1;
         }
 
+        // Optional: set mode line
+        let mode = parse_set_mode(remaining_lines.first(), line_number + parsed_lines)?;
+        if mode.is_some() {
+            remaining_lines = &remaining_lines[1..];
+            parsed_lines += 1;
+        }
+
         let mut chunks = Vec::new();
         // NOTE: we need to know to stop once we reach the next special marker header.
         while !remaining_lines.is_empty() {
@@ -312,7 +330,8 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             remaining_lines = &remaining_lines[chunk_lines..]
         }
 
-        if chunks.is_empty() {
+        // A mode change on its own is a valid update.
+        if chunks.is_empty() && mode.is_none() {
             return Err(InvalidHunkError {
                 message: format!("Update file hunk for path '{path}' is empty"),
                 line_number,
@@ -323,6 +342,7 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             UpdateFile {
                 path: PathBuf::from(path),
                 move_path: move_path.map(PathBuf::from),
+                mode,
                 chunks,
             },
             parsed_lines,
@@ -337,6 +357,26 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
     })
 }
 
+/// Parse an optional `*** Set Mode: 755` line into permission bits. Only
+/// the rwx bits are accepted; setuid, setgid and sticky bits are rejected.
+fn parse_set_mode(line: Option<&&str>, line_number: usize) -> Result<Option<u32>, ParseError> {
+    let Some(value) = line.and_then(|line| line.trim().strip_prefix(SET_MODE_MARKER)) else {
+        return Ok(None);
+    };
+    let value = value.trim();
+    let is_octal =
+        (3..=4).contains(&value.len()) && value.chars().all(|c| ('0'..='7').contains(&c));
+    match u32::from_str_radix(value, 8) {
+        Ok(mode) if is_octal && mode <= 0o777 => Ok(Some(mode)),
+        _ => Err(InvalidHunkError {
+            message: format!(
+                "Invalid mode '{value}': expected octal permissions such as 755 or 644"
+            ),
+            line_number,
+        }),
+    }
+}
+
 fn parse_update_file_chunk(
     lines: &[&str],
     line_number: usize,
@@ -486,7 +526,8 @@ fn test_parse_patch() {
         vec![
             AddFile {
                 path: PathBuf::from("path/add.py"),
-                contents: "abc\ndef\n".to_string()
+                contents: "abc\ndef\n".to_string(),
+                mode: None,
             },
             DeleteFile {
                 path: PathBuf::from("path/delete.py")
@@ -494,6 +535,7 @@ fn test_parse_patch() {
             UpdateFile {
                 path: PathBuf::from("path/update.py"),
                 move_path: Some(PathBuf::from("path/update2.py")),
+                mode: None,
                 chunks: vec![UpdateFileChunk {
                     change_context: Some("def f():".to_string()),
                     old_lines: vec!["    pass".to_string()],
@@ -521,6 +563,7 @@ fn test_parse_patch() {
             UpdateFile {
                 path: PathBuf::from("file.py"),
                 move_path: None,
+                mode: None,
                 chunks: vec![UpdateFileChunk {
                     change_context: None,
                     old_lines: vec![],
@@ -530,7 +573,8 @@ fn test_parse_patch() {
             },
             AddFile {
                 path: PathBuf::from("other.py"),
-                contents: "content\n".to_string()
+                contents: "content\n".to_string(),
+                mode: None,
             }
         ]
     );
@@ -551,6 +595,7 @@ fn test_parse_patch() {
         vec![UpdateFile {
             path: PathBuf::from("file2.py"),
             move_path: None,
+            mode: None,
             chunks: vec![UpdateFileChunk {
                 change_context: None,
                 old_lines: vec!["import foo".to_string()],
@@ -571,6 +616,7 @@ fn test_parse_patch_lenient() {
     let expected_patch = vec![UpdateFile {
         path: PathBuf::from("file2.py"),
         move_path: None,
+        mode: None,
         chunks: vec![UpdateFileChunk {
             change_context: None,
             old_lines: vec!["import foo".to_string()],
@@ -660,6 +706,68 @@ fn test_parse_one_hunk() {
     // Other edge cases are already covered by tests above/below.
 }
 
+#[test]
+fn test_parse_set_mode() {
+    assert_eq!(
+        parse_one_hunk(
+            &["*** Add File: run.sh", "*** Set Mode: 755", "+#!/bin/sh"],
+            2
+        ),
+        Ok((
+            AddFile {
+                path: PathBuf::from("run.sh"),
+                contents: "#!/bin/sh\n".to_string(),
+                mode: Some(0o755),
+            },
+            3
+        ))
+    );
+    assert_eq!(
+        parse_one_hunk(
+            &[
+                "*** Update File: run.sh",
+                "*** Move to: bin/run.sh",
+                "*** Set Mode: 0700",
+            ],
+            2
+        ),
+        Ok((
+            UpdateFile {
+                path: PathBuf::from("run.sh"),
+                move_path: Some(PathBuf::from("bin/run.sh")),
+                mode: Some(0o700),
+                chunks: Vec::new(),
+            },
+            3
+        ))
+    );
+    assert_eq!(
+        parse_one_hunk(&["*** Update File: run.sh", "*** Set Mode: 9755"], 2),
+        Err(InvalidHunkError {
+            message: "Invalid mode '9755': expected octal permissions such as 755 or 644"
+                .to_string(),
+            line_number: 3
+        })
+    );
+    for special in ["4755", "2755", "1777"] {
+        assert_eq!(
+            parse_one_hunk(
+                &[
+                    "*** Update File: run.sh",
+                    &format!("*** Set Mode: {special}")
+                ],
+                2
+            ),
+            Err(InvalidHunkError {
+                message: format!(
+                    "Invalid mode '{special}': expected octal permissions such as 755 or 644"
+                ),
+                line_number: 3
+            })
+        );
+    }
+}
+
 #[test]
 fn test_update_file_chunk() {
     assert_eq!(
@@ -0,0 +1,520 @@
+//! All-or-nothing application of the file changes produced by a patch.
+//!
+//! Every new file body is first written to a temporary file next to its
+//! target. Only once all of them are on disk are the originals moved aside and
+//! the temporary files renamed into place. If any of those renames fails, the
+//! targets that were already replaced are restored from the moved-aside
+//! originals and any directories created for new files are removed again, so
+//! the tree is left exactly as it was before the patch.
+//!
+//! [`StagedChanges::commit_through`] offers the same guarantee when file
+//! contents have to be written through a [`FileSystem`] that cannot rename:
+//! it remembers what each write replaced and writes it back on failure.
+
+use std::collections::HashMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::ApplyPatchError;
+use crate::FileSystem;
+use crate::IoError;
+
+/// The final state of one path after all hunks have been staged.
+#[derive(Debug)]
+struct StagedChange {
+    /// 1-based index of the last hunk that touched `path`.
+    hunk: usize,
+    path: PathBuf,
+    /// New contents, or `None` when the file is deleted.
+    contents: Option<String>,
+    /// Unix permission bits to give the file; `None` keeps the existing ones.
+    mode: Option<u32>,
+}
+
+/// In-memory result of applying a patch's hunks, in the order paths were
+/// first touched.
+#[derive(Debug, Default)]
+pub(crate) struct StagedChanges {
+    changes: Vec<StagedChange>,
+    by_path: HashMap<PathBuf, usize>,
+}
+
+impl StagedChanges {
+    /// Record that hunk number `hunk` leaves `path` with `contents`
+    /// (`None` to delete it) and, if given, permission bits `mode`, replacing
+    /// whatever an earlier hunk staged. A mode staged earlier is kept when
+    /// `mode` is `None`.
+    pub(crate) fn stage(
+        &mut self,
+        hunk: usize,
+        path: &Path,
+        contents: Option<String>,
+        mode: Option<u32>,
+    ) {
+        if let Some(&index) = self.by_path.get(path) {
+            let change = &mut self.changes[index];
+            change.hunk = hunk;
+            change.contents = contents;
+            change.mode = mode.or(change.mode);
+            return;
+        }
+        self.by_path.insert(path.to_path_buf(), self.changes.len());
+        self.changes.push(StagedChange {
+            hunk,
+            path: path.to_path_buf(),
+            contents,
+            mode,
+        });
+    }
+
+    /// The staged state of `path`: `None` if no hunk touched it yet,
+    /// `Some(None)` if an earlier hunk deleted it.
+    pub(crate) fn get(&self, path: &Path) -> Option<Option<&str>> {
+        self.by_path
+            .get(path)
+            .map(|&index| self.changes[index].contents.as_deref())
+    }
+
+    /// The permission bits `path` will end up with: the staged mode if a hunk
+    /// set one, otherwise the mode of the file on disk.
+    pub(crate) fn mode(&self, path: &Path) -> Option<u32> {
+        match self.by_path.get(path) {
+            Some(&index) => self.changes[index].mode.or_else(|| file_mode(path)),
+            None => file_mode(path),
+        }
+    }
+
+    /// Apply the staged changes so that either all of them take effect or
+    /// none do.
+    pub(crate) fn commit(mut self) -> Result<(), ApplyPatchError> {
+        self.drop_transient_files();
+        commit(&self.changes)
+    }
+
+    /// Like [`StagedChanges::commit`], but new contents are written with
+    /// `file_system`. Deletions still happen on the local file system, by
+    /// moving the file aside until every write has succeeded.
+    pub(crate) async fn commit_through(
+        mut self,
+        file_system: &impl FileSystem,
+    ) -> Result<(), ApplyPatchError> {
+        self.drop_transient_files();
+        let mut journal = Vec::with_capacity(self.changes.len());
+        for change in &self.changes {
+            if let Err(err) = write_through(file_system, change, &mut journal).await {
+                let restore_errors = undo_writes(file_system, journal).await;
+                return Err(partly_rolled_back(change, err, restore_errors));
+            }
+        }
+        for entry in journal {
+            if let Undo::Delete { backup, .. } = entry {
+                let _ = fs::remove_file(backup);
+            }
+        }
+        Ok(())
+    }
+
+    /// A file that is both added and deleted by the patch never touches disk.
+    fn drop_transient_files(&mut self) {
+        self.changes.retain(|change| {
+            change.contents.is_some() || fs::symlink_metadata(&change.path).is_ok()
+        });
+    }
+}
+
+#[derive(Debug)]
+struct PreparedChange<'a> {
+    change: &'a StagedChange,
+    /// Where the change lands; symlinks to existing files are written through.
+    target: PathBuf,
+    /// Temporary file holding the new contents.
+    temp: Option<PathBuf>,
+    /// Where the original is moved while the change is committed.
+    backup: Option<PathBuf>,
+    /// Parent directories created for a new file, outermost first.
+    created_dirs: Vec<PathBuf>,
+    backed_up: bool,
+    placed: bool,
+}
+
+fn commit(changes: &[StagedChange]) -> Result<(), ApplyPatchError> {
+    let mut prepared: Vec<PreparedChange> = Vec::with_capacity(changes.len());
+    for change in changes {
+        match prepare(change) {
+            Ok(entry) => prepared.push(entry),
+            Err(err) => {
+                discard_temp_files(&prepared);
+                return Err(hunk_failed(change, err));
+            }
+        }
+    }
+
+    for index in 0..prepared.len() {
+        if let Err(err) = swap_in(&mut prepared[index]) {
+            let restore_errors = roll_back(&prepared[..=index]);
+            discard_temp_files(&prepared);
+            return Err(partly_rolled_back(
+                prepared[index].change,
+                err,
+                restore_errors,
+            ));
+        }
+    }
+
+    for entry in &prepared {
+        if let Some(backup) = &entry.backup {
+            let _ = fs::remove_file(backup);
+        }
+    }
+    Ok(())
+}
+
+/// The error for a failed `change`, noting every path that could not be
+/// restored while undoing the earlier ones.
+fn partly_rolled_back(
+    change: &StagedChange,
+    err: ApplyPatchError,
+    restore_errors: Vec<String>,
+) -> ApplyPatchError {
+    if restore_errors.is_empty() {
+        return hunk_failed(change, err);
+    }
+    ApplyPatchError::IoError(IoError {
+        context: format!(
+            "hunk {} ({}) failed and the patch was only partly rolled back: {}",
+            change.hunk,
+            change.path.display(),
+            restore_errors.join("; ")
+        ),
+        source: match err {
+            ApplyPatchError::IoError(IoError { source, .. }) => source,
+            other => std::io::Error::other(other.to_string()),
+        },
+    })
+}
+
+fn hunk_failed(change: &StagedChange, err: ApplyPatchError) -> ApplyPatchError {
+    ApplyPatchError::HunkFailed {
+        hunk: change.hunk,
+        path: change.path.clone(),
+        source: Box::new(err),
+    }
+}
+
+fn io_error(context: String, source: std::io::Error) -> ApplyPatchError {
+    ApplyPatchError::IoError(IoError { context, source })
+}
+
+/// Write the new contents to a temporary file and check that the target can
+/// be replaced, without touching the target itself.
+fn prepare(change: &StagedChange) -> Result<PreparedChange<'_>, ApplyPatchError> {
+    let path = &change.path;
+    let exists = fs::symlink_metadata(path).is_ok();
+
+    let Some(contents) = &change.contents else {
+        if !exists {
+            return Err(io_error(
+                format!("Failed to delete file {}", path.display()),
+                std::io::Error::from(std::io::ErrorKind::NotFound),
+            ));
+        }
+        return Ok(PreparedChange {
+            change,
+            target: path.clone(),
+            temp: None,
+            backup: Some(sibling_path(path, "orig")),
+            created_dirs: Vec::new(),
+            backed_up: false,
+            placed: false,
+        });
+    };
+
+    let target = if exists {
+        fs::canonicalize(path).unwrap_or_else(|_| path.clone())
+    } else {
+        path.clone()
+    };
+    let created_dirs = create_parent_dirs(&target).map_err(|e| {
+        io_error(
+            format!("Failed to create parent directories for {}", path.display()),
+            e,
+        )
+    })?;
+
+    let existing_permissions = if exists {
+        // Opening for writing surfaces read-only files now rather than
+        // silently replacing them with a rename later.
+        OpenOptions::new()
+            .append(true)
+            .open(&target)
+            .map_err(|e| io_error(format!("Failed to write file {}", path.display()), e))?;
+        fs::metadata(&target).ok().map(|meta| meta.permissions())
+    } else {
+        None
+    };
+    let permissions = mode_permissions(change.mode).or(existing_permissions);
+
+    let temp = sibling_path(&target, "tmp");
+    let write_temp = || -> std::io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)?;
+        file.write_all(contents.as_bytes())?;
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        Ok(())
+    };
+    if let Err(e) = write_temp() {
+        let _ = fs::remove_file(&temp);
+        remove_created_dirs(&created_dirs);
+        return Err(io_error(
+            format!("Failed to write file {}", path.display()),
+            e,
+        ));
+    }
+
+    Ok(PreparedChange {
+        change,
+        backup: exists.then(|| sibling_path(&target, "orig")),
+        target,
+        temp: Some(temp),
+        created_dirs,
+        backed_up: false,
+        placed: false,
+    })
+}
+
+/// Create the missing parent directories of `path` and return them,
+/// outermost first, so a rollback can remove exactly those.
+fn create_parent_dirs(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut missing = Vec::new();
+    let mut current = path.parent();
+    while let Some(dir) = current
+        && !dir.as_os_str().is_empty()
+        && fs::symlink_metadata(dir).is_err()
+    {
+        missing.push(dir.to_path_buf());
+        current = dir.parent();
+    }
+    missing.reverse();
+    if let Some(innermost) = missing.last() {
+        fs::create_dir_all(innermost)?;
+    }
+    Ok(missing)
+}
+
+/// Remove directories returned by [`create_parent_dirs`], innermost first.
+/// Directories that are no longer empty are left alone.
+fn remove_created_dirs(dirs: &[PathBuf]) {
+    for dir in dirs.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// Move the original aside and rename the new contents into place.
+fn swap_in(entry: &mut PreparedChange) -> Result<(), ApplyPatchError> {
+    let display = entry.change.path.display();
+    if let Some(backup) = &entry.backup {
+        fs::rename(&entry.target, backup)
+            .map_err(|e| io_error(format!("Failed to replace file {display}"), e))?;
+        entry.backed_up = true;
+    }
+    if let Some(temp) = &entry.temp {
+        fs::rename(temp, &entry.target)
+            .map_err(|e| io_error(format!("Failed to write file {display}"), e))?;
+        entry.placed = true;
+    }
+    Ok(())
+}
+
+/// Undo `entries` in reverse order, returning a description of every path
+/// that could not be restored.
+fn roll_back(entries: &[PreparedChange]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for entry in entries.iter().rev() {
+        if entry.placed
+            && let Err(e) = fs::remove_file(&entry.target)
+        {
+            errors.push(format!("could not remove {}: {e}", entry.target.display()));
+            continue;
+        }
+        if entry.backed_up
+            && let Some(backup) = &entry.backup
+            && let Err(e) = fs::rename(backup, &entry.target)
+        {
+            errors.push(format!(
+                "could not restore {} (original kept at {}): {e}",
+                entry.target.display(),
+                backup.display()
+            ));
+        }
+    }
+    errors
+}
+
+/// Remove the temporary files of `entries` that were not renamed into place,
+/// then the directories created for them.
+fn discard_temp_files(entries: &[PreparedChange]) {
+    for entry in entries {
+        if !entry.placed
+            && let Some(temp) = &entry.temp
+        {
+            let _ = fs::remove_file(temp);
+        }
+    }
+    for entry in entries.iter().rev() {
+        remove_created_dirs(&entry.created_dirs);
+    }
+}
+
+/// How to undo one step of [`StagedChanges::commit_through`].
+enum Undo {
+    /// `path` was written; put `original` back, or remove the file and the
+    /// directories created for it when it did not exist.
+    Write {
+        path: PathBuf,
+        original: Option<String>,
+        original_mode: Option<u32>,
+        created_dirs: Vec<PathBuf>,
+    },
+    /// `path` was moved to `backup` in place of deleting it.
+    Delete { path: PathBuf, backup: PathBuf },
+}
+
+async fn write_through(
+    file_system: &impl FileSystem,
+    change: &StagedChange,
+    journal: &mut Vec<Undo>,
+) -> Result<(), ApplyPatchError> {
+    let path = &change.path;
+    let exists = fs::symlink_metadata(path).is_ok();
+    let Some(contents) = &change.contents else {
+        let backup = sibling_path(path, "orig");
+        fs::rename(path, &backup)
+            .map_err(|e| io_error(format!("Failed to delete file {}", path.display()), e))?;
+        journal.push(Undo::Delete {
+            path: path.clone(),
+            backup,
+        });
+        return Ok(());
+    };
+
+    let original = if exists {
+        Some(
+            file_system
+                .read_text_file(path)
+                .await
+                .map_err(|e| io_error(format!("Failed to read file {}", path.display()), e))?,
+        )
+    } else {
+        None
+    };
+    let created_dirs = create_parent_dirs(path).map_err(|e| {
+        io_error(
+            format!("Failed to create parent directories for {}", path.display()),
+            e,
+        )
+    })?;
+    journal.push(Undo::Write {
+        path: path.clone(),
+        original,
+        original_mode: file_mode(path),
+        created_dirs,
+    });
+    file_system
+        .write_text_file(path, contents.clone())
+        .await
+        .map_err(|e| io_error(format!("Failed to write file {}", path.display()), e))?;
+    if let Some(permissions) = mode_permissions(change.mode) {
+        fs::set_permissions(path, permissions)
+            .map_err(|e| io_error(format!("Failed to set mode of {}", path.display()), e))?;
+    }
+    Ok(())
+}
+
+/// Undo `journal` in reverse order, returning a description of every path
+/// that could not be restored.
+async fn undo_writes(file_system: &impl FileSystem, journal: Vec<Undo>) -> Vec<String> {
+    let mut errors = Vec::new();
+    for entry in journal.into_iter().rev() {
+        match entry {
+            Undo::Write {
+                path,
+                original: Some(original),
+                original_mode,
+                ..
+            } => {
+                if let Err(e) = file_system.write_text_file(&path, original).await {
+                    errors.push(format!("could not restore {}: {e}", path.display()));
+                } else if let Some(permissions) = mode_permissions(original_mode) {
+                    let _ = fs::set_permissions(&path, permissions);
+                }
+            }
+            Undo::Write {
+                path,
+                original: None,
+                created_dirs,
+                ..
+            } => {
+                if let Err(e) = fs::remove_file(&path)
+                    && e.kind() != std::io::ErrorKind::NotFound
+                {
+                    errors.push(format!("could not remove {}: {e}", path.display()));
+                }
+                remove_created_dirs(&created_dirs);
+            }
+            Undo::Delete { path, backup } => {
+                if let Err(e) = fs::rename(&backup, &path) {
+                    errors.push(format!(
+                        "could not restore {} (original kept at {}): {e}",
+                        path.display(),
+                        backup.display()
+                    ));
+                }
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .ok()
+        .map(|meta| meta.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn mode_permissions(mode: Option<u32>) -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    mode.map(fs::Permissions::from_mode)
+}
+
+/// Permission bits have no equivalent outside Unix and are ignored there.
+#[cfg(not(unix))]
+fn mode_permissions(_mode: Option<u32>) -> Option<fs::Permissions> {
+    None
+}
+
+/// A hidden file next to `path`, e.g. `dir/.name.1234.apply_patch.tmp`.
+pub(crate) fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{name}.{}.apply_patch.{suffix}",
+        std::process::id()
+    ))
+}
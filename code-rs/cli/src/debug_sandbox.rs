@@ -95,6 +95,7 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                config.exec_limits,
                 stdio_policy,
                 env,
             )
@@ -111,6 +112,7 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                config.exec_limits,
                 stdio_policy,
                 env,
             )
@@ -4,6 +4,7 @@ use clap::CommandFactory;
 use clap::Parser;
 use clap_complete::Shell;
 use clap_complete::generate;
+use code_app_server::AppServerCli;
 use code_arg0::arg0_dispatch_or_else;
 use code_chatgpt::apply_command::ApplyCommand;
 use code_chatgpt::apply_command::run_apply_command;
@@ -36,8 +37,14 @@ use tokio::runtime::Builder as TokioRuntimeBuilder;
 use tokio::runtime::Handle as TokioHandle;
 
 mod mcp_cmd;
+mod review_cmd;
+mod sessions_cmd;
+mod worktree_cmd;
 
 use crate::mcp_cmd::McpCli;
+use crate::review_cmd::ReviewCli;
+use crate::sessions_cmd::SessionsCli;
+use crate::worktree_cmd::WorktreeCli;
 
 const CLI_COMMAND_NAME: &str = "code";
 pub(crate) const CODE_SECURE_MODE_ENV_VAR: &str = "CODE_SECURE_MODE";
@@ -124,8 +131,8 @@ enum Subcommand {
     /// [experimental] Run the Beacon Code MCP server (stdio transport).
     McpServer,
 
-    /// [experimental] Run the app server.
-    AppServer,
+    /// [experimental] Run the app server (stdio, WebSocket, or Unix socket).
+    AppServer(AppServerCli),
 
     /// Generate shell completion scripts.
     Completion(CompletionCommand),
@@ -144,6 +151,15 @@ enum Subcommand {
     /// Resume a previous interactive session (picker by default; use --last to continue the most recent).
     Resume(ResumeCommand),
 
+    /// Review code changes headlessly and print the findings (markdown, JSON or SARIF).
+    Review(ReviewCli),
+
+    /// Search recorded sessions.
+    Sessions(SessionsCli),
+
+    /// List, inspect, diff, merge or remove branch worktrees.
+    Worktree(WorktreeCli),
+
     /// Internal: generate TypeScript protocol bindings.
     #[clap(hide = true)]
     GenerateTs(GenerateTsCommand),
@@ -354,8 +370,33 @@ async fn cli_main(code_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()>
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run().await?;
         }
-        Some(Subcommand::AppServer) => {
-            code_app_server::run_main(code_linux_sandbox_exe, root_config_overrides).await?;
+        Some(Subcommand::Review(mut review_cli)) => {
+            prepend_config_flags(
+                &mut review_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            review_cli.run(code_linux_sandbox_exe).await?;
+        }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(
+                &mut sessions_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            sessions_cli.run().await?;
+        }
+        Some(Subcommand::Worktree(mut worktree_cli)) => {
+            prepend_config_flags(
+                &mut worktree_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            worktree_cli.run().await?;
+        }
+        Some(Subcommand::AppServer(mut app_server_cli)) => {
+            prepend_config_flags(
+                &mut app_server_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            code_app_server::run_main(code_linux_sandbox_exe, app_server_cli).await?;
         }
         Some(Subcommand::Resume(ResumeCommand {
             session_id,
@@ -635,6 +676,7 @@ fn resolve_resume_path(session_id: Option<&str>, last: bool) -> anyhow::Result<O
                 include_archived: false,
                 include_deleted: false,
                 limit: Some(1),
+                text: None,
             };
             let entry = catalog
                 .get_latest(&query)
@@ -0,0 +1,522 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use code_common::CliConfigOverrides;
+use code_core::AuthManager;
+use code_core::ConversationManager;
+use code_core::NewConversation;
+use code_core::config::Config;
+use code_core::config::ConfigOverrides;
+use code_core::git_info::current_branch_name;
+use code_core::git_info::get_git_repo_root;
+use code_core::protocol::AskForApproval;
+use code_core::protocol::ErrorEvent;
+use code_core::protocol::EventMsg;
+use code_core::protocol::Op;
+use code_core::protocol::ReviewContextMetadata;
+use code_core::protocol::ReviewFinding;
+use code_core::protocol::ReviewOutputEvent;
+use code_core::protocol::ReviewRequest;
+use code_core::review_prompts::branch_review_prompt;
+use code_core::review_prompts::commit_review_prompt;
+use code_core::review_prompts::workspace_review_prompt;
+use code_protocol::config_types::SandboxMode;
+use code_protocol::protocol::SessionSource;
+use serde_json::json;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_ROOT_BASE_ID: &str = "%SRCROOT%";
+
+/// Review code changes without the TUI, for CI.
+///
+/// Reviews the uncommitted changes by default; use `--base` or `--commit` to
+/// pick a different scope. Exits with status 1 when a finding is at or above
+/// `--fail-on-priority` (P0 is the most severe).
+#[derive(Debug, clap::Parser)]
+pub struct ReviewCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Review the changes between the current branch and REF.
+    #[arg(long, value_name = "REF", group = "scope")]
+    pub base: Option<String>,
+
+    /// Review the changes introduced by a single commit.
+    #[arg(long, value_name = "SHA", group = "scope")]
+    pub commit: Option<String>,
+
+    /// Review staged, unstaged and untracked changes (the default).
+    #[arg(long, group = "scope")]
+    pub uncommitted: bool,
+
+    /// Output format for the findings.
+    #[arg(long, value_enum, default_value_t = ReviewFormat::Markdown)]
+    pub format: ReviewFormat,
+
+    /// Exit with status 1 if any finding has priority N or more severe (0-3).
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(i32).range(0..=3))]
+    pub fail_on_priority: Option<i32>,
+
+    /// Model to review with (defaults to `review_model` from config).
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    /// Directory to review (defaults to the current directory).
+    #[arg(long, short = 'C', value_name = "DIR")]
+    pub cd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReviewFormat {
+    Markdown,
+    Json,
+    Sarif,
+}
+
+impl ReviewCli {
+    pub async fn run(self, code_linux_sandbox_exe: Option<PathBuf>) -> Result<()> {
+        let ReviewCli {
+            config_overrides,
+            base,
+            commit,
+            uncommitted: _,
+            format,
+            fail_on_priority,
+            model,
+            cd,
+        } = self;
+        let cli_kv_overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+        // Reviews only read the tree and run headless, so never ask for
+        // approval and never allow writes.
+        let overrides = ConfigOverrides {
+            review_model: model,
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_mode: Some(SandboxMode::ReadOnly),
+            cwd: cd.map(|p| p.canonicalize().unwrap_or(p)),
+            code_linux_sandbox_exe,
+            ..ConfigOverrides::default()
+        };
+        let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+        let repo_root = get_git_repo_root(&config.cwd)
+            .ok_or_else(|| anyhow!("{} is not inside a git repository", config.cwd.display()))?;
+
+        for rev in base.iter().chain(commit.iter()) {
+            verify_revision(&repo_root, rev)?;
+        }
+
+        let request = match (base, commit) {
+            (Some(base), _) => base_request(&base, current_branch_name(&config.cwd).await),
+            (None, Some(commit)) => commit_request(&commit),
+            (None, None) => uncommitted_request(),
+        };
+        let hint = request.user_facing_hint.clone();
+        let output = run_review(&config, request).await?;
+        ensure_structured(&output)?;
+
+        let rendered = match format {
+            ReviewFormat::Markdown => render_markdown(&output, &hint, &repo_root),
+            ReviewFormat::Json => serde_json::to_string_pretty(&output)?,
+            ReviewFormat::Sarif => serde_json::to_string_pretty(&to_sarif(&output, &repo_root))?,
+        };
+        println!("{rendered}");
+
+        if let Some(threshold) = fail_on_priority {
+            let blocking = count_at_or_above(&output.findings, threshold);
+            if blocking > 0 {
+                eprintln!("{blocking} finding(s) at P{threshold} or above.");
+                std::process::exit(1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fail early, before a session is started, when `rev` does not name a commit.
+fn verify_revision(repo_root: &Path, rev: &str) -> Result<()> {
+    let output = std::process::Command::new("git")
+        .current_dir(repo_root)
+        .args(["rev-parse", "--verify", "--quiet", "--end-of-options"])
+        .arg(format!("{rev}^{{commit}}"))
+        .output()
+        .map_err(|e| anyhow!("failed to run git rev-parse: {e}"))?;
+    if !output.status.success() {
+        bail!("`{rev}` is not a commit in {}", repo_root.display());
+    }
+    Ok(())
+}
+
+/// When the reviewer's reply is not the expected JSON, core keeps the raw
+/// text as the explanation and reports no verdict and no findings. Treat
+/// that as a failure rather than a clean review.
+fn ensure_structured(output: &ReviewOutputEvent) -> Result<()> {
+    if output.overall_correctness.trim().is_empty()
+        && output.findings.is_empty()
+        && !output.overall_explanation.trim().is_empty()
+    {
+        bail!(
+            "the reviewer did not return structured findings:\n{}",
+            output.overall_explanation.trim()
+        );
+    }
+    Ok(())
+}
+
+fn uncommitted_request() -> ReviewRequest {
+    ReviewRequest {
+        prompt: workspace_review_prompt(),
+        user_facing_hint: "current workspace changes".to_string(),
+        metadata: Some(ReviewContextMetadata {
+            scope: Some("workspace".to_string()),
+            ..Default::default()
+        }),
+    }
+}
+
+fn commit_request(commit: &str) -> ReviewRequest {
+    let short_sha: String = commit.chars().take(7).collect();
+    ReviewRequest {
+        prompt: commit_review_prompt(commit, None),
+        user_facing_hint: format!("commit {short_sha}"),
+        metadata: Some(ReviewContextMetadata {
+            scope: Some("commit".to_string()),
+            commit: Some(commit.to_string()),
+            ..Default::default()
+        }),
+    }
+}
+
+fn base_request(base: &str, current: Option<String>) -> ReviewRequest {
+    ReviewRequest {
+        prompt: branch_review_prompt(base, current.as_deref()),
+        user_facing_hint: format!("against {base}"),
+        metadata: Some(ReviewContextMetadata {
+            scope: Some("branch_diff".to_string()),
+            base_branch: Some(base.to_string()),
+            current_branch: current,
+            ..Default::default()
+        }),
+    }
+}
+
+/// Start a session, submit the review and wait for the reviewer's findings.
+async fn run_review(config: &Config, request: ReviewRequest) -> Result<ReviewOutputEvent> {
+    let auth_manager = AuthManager::shared_with_mode_and_originator(
+        config.code_home.clone(),
+        code_login::AuthMode::ApiKey,
+        config.responses_originator_header.clone(),
+    );
+    let conversation_manager = ConversationManager::new(auth_manager, SessionSource::Cli);
+    let NewConversation { conversation, .. } = conversation_manager
+        .new_conversation(config.clone())
+        .await?;
+
+    eprintln!("Reviewing {}...", request.user_facing_hint);
+    conversation
+        .submit(Op::Review {
+            review_request: request,
+        })
+        .await?;
+
+    let mut last_error = None;
+    let output = loop {
+        let event = tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                conversation.submit(Op::Interrupt).await.ok();
+                bail!("review interrupted");
+            }
+            event = conversation.next_event() => event?,
+        };
+        match event.msg {
+            EventMsg::ExitedReviewMode(output) => break output,
+            EventMsg::Error(ErrorEvent { message }) => last_error = Some(message),
+            EventMsg::ShutdownComplete => break None,
+            _ => {}
+        }
+    };
+    conversation.submit(Op::Shutdown).await.ok();
+
+    match (output, last_error) {
+        (Some(output), _) => Ok(output),
+        (None, Some(error)) => bail!("review failed: {error}"),
+        (None, None) => bail!("the reviewer did not return any output"),
+    }
+}
+
+/// Number of findings whose priority is `threshold` or more severe.
+fn count_at_or_above(findings: &[ReviewFinding], threshold: i32) -> usize {
+    findings
+        .iter()
+        .filter(|finding| finding.priority <= threshold)
+        .count()
+}
+
+/// `path` relative to `repo_root` with `/` separators, or `None` if it lies
+/// outside the repository.
+fn repo_relative_path(path: &Path, repo_root: &Path) -> Option<String> {
+    let relative = path.strip_prefix(repo_root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn display_path(path: &Path, repo_root: &Path) -> String {
+    repo_relative_path(path, repo_root).unwrap_or_else(|| path.display().to_string())
+}
+
+fn sarif_level(priority: i32) -> &'static str {
+    match priority {
+        i32::MIN..=1 => "error",
+        2 => "warning",
+        _ => "note",
+    }
+}
+
+/// Convert the review into a SARIF 2.1.0 log with one result per finding.
+/// Paths inside the repository are reported relative to `%SRCROOT%` so code
+/// scanning tools can match them to the checkout.
+fn to_sarif(output: &ReviewOutputEvent, repo_root: &Path) -> serde_json::Value {
+    let rules: Vec<serde_json::Value> = (0..=3)
+        .map(|priority| {
+            json!({
+                "id": format!("P{priority}"),
+                "shortDescription": { "text": priority_description(priority) },
+                "defaultConfiguration": { "level": sarif_level(priority) },
+            })
+        })
+        .collect();
+
+    let results: Vec<serde_json::Value> = output
+        .findings
+        .iter()
+        .map(|finding| {
+            let location = &finding.code_location;
+            let artifact = match repo_relative_path(&location.absolute_file_path, repo_root) {
+                Some(uri) => json!({ "uri": uri, "uriBaseId": SARIF_ROOT_BASE_ID }),
+                None => json!({
+                    "uri": format!("file://{}", location.absolute_file_path.display()),
+                }),
+            };
+            // SARIF lines are 1-based and regions may not end before they start.
+            let start_line = location.line_range.start.max(1);
+            let end_line = location.line_range.end.max(start_line);
+            let message = if finding.body.trim().is_empty() {
+                finding.title.clone()
+            } else {
+                format!("{}\n\n{}", finding.title, finding.body)
+            };
+            json!({
+                "ruleId": format!("P{}", finding.priority.clamp(0, 3)),
+                "level": sarif_level(finding.priority),
+                "message": { "text": message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": artifact,
+                        "region": { "startLine": start_line, "endLine": end_line },
+                    },
+                }],
+                "properties": {
+                    "priority": finding.priority,
+                    "confidence": finding.confidence_score,
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "code review",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+            "properties": {
+                "overallCorrectness": output.overall_correctness,
+                "overallExplanation": output.overall_explanation,
+                "overallConfidence": output.overall_confidence_score,
+            },
+        }],
+    })
+}
+
+fn priority_description(priority: i32) -> &'static str {
+    match priority {
+        0 => "Blocking: drop everything to fix",
+        1 => "Urgent: address in the next cycle",
+        2 => "Normal: fix eventually",
+        _ => "Low: nice to have",
+    }
+}
+
+fn render_markdown(output: &ReviewOutputEvent, hint: &str, repo_root: &Path) -> String {
+    let mut lines = vec![format!("# Code review: {hint}")];
+    if !output.overall_explanation.trim().is_empty() {
+        lines.push(String::new());
+        lines.push(output.overall_explanation.trim().to_string());
+    }
+    if !output.overall_correctness.trim().is_empty() {
+        lines.push(String::new());
+        lines.push(format!(
+            "**Overall correctness:** {} (confidence {:.1})",
+            output.overall_correctness.trim(),
+            output.overall_confidence_score
+        ));
+    }
+
+    lines.push(String::new());
+    if output.findings.is_empty() {
+        lines.push("No findings.".to_string());
+        return lines.join("\n");
+    }
+
+    let mut findings: Vec<&ReviewFinding> = output.findings.iter().collect();
+    findings.sort_by_key(|finding| finding.priority);
+    lines.push(format!("## Findings ({})", findings.len()));
+    for finding in findings {
+        let location = &finding.code_location;
+        let range = &location.line_range;
+        let lines_label = if range.end > range.start {
+            format!("{}-{}", range.start, range.end)
+        } else {
+            range.start.to_string()
+        };
+        lines.push(String::new());
+        lines.push(format!("### {}", finding.title.trim()));
+        lines.push(format!(
+            "`{}:{lines_label}` · P{} · confidence {:.1}",
+            display_path(&location.absolute_file_path, repo_root),
+            finding.priority,
+            finding.confidence_score
+        ));
+        if !finding.body.trim().is_empty() {
+            lines.push(String::new());
+            lines.push(finding.body.trim().to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::protocol::ReviewCodeLocation;
+    use code_core::protocol::ReviewLineRange;
+
+    fn finding(path: &str, priority: i32, start: u32, end: u32) -> ReviewFinding {
+        ReviewFinding {
+            title: format!("[P{priority}] Something is off"),
+            body: "Details.".to_string(),
+            confidence_score: 0.8,
+            priority,
+            code_location: ReviewCodeLocation {
+                absolute_file_path: PathBuf::from(path),
+                line_range: ReviewLineRange { start, end },
+            },
+        }
+    }
+
+    fn output(findings: Vec<ReviewFinding>) -> ReviewOutputEvent {
+        ReviewOutputEvent {
+            findings,
+            overall_correctness: "patch is incorrect".to_string(),
+            overall_explanation: "One blocking bug.".to_string(),
+            overall_confidence_score: 0.7,
+        }
+    }
+
+    #[test]
+    fn sarif_maps_locations_relative_to_repo_root() {
+        let review = output(vec![
+            finding("/repo/src/lib.rs", 1, 10, 12),
+            finding("/elsewhere/gen.rs", 3, 0, 0),
+        ]);
+        let sarif = to_sarif(&review, Path::new("/repo"));
+        let results = &sarif["runs"][0]["results"];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(results[0]["ruleId"], "P1");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": "src/lib.rs", "uriBaseId": "%SRCROOT%" },
+                "region": { "startLine": 10, "endLine": 12 },
+            })
+        );
+        assert_eq!(results[1]["level"], "note");
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": "file:///elsewhere/gen.rs" },
+                "region": { "startLine": 1, "endLine": 1 },
+            })
+        );
+    }
+
+    #[test]
+    fn fail_on_priority_counts_findings_at_or_above_threshold() {
+        let findings = vec![
+            finding("/repo/a.rs", 0, 1, 1),
+            finding("/repo/b.rs", 2, 1, 1),
+            finding("/repo/c.rs", 3, 1, 1),
+        ];
+        assert_eq!(count_at_or_above(&findings, 0), 1);
+        assert_eq!(count_at_or_above(&findings, 2), 2);
+        assert_eq!(count_at_or_above(&[], 3), 0);
+    }
+
+    #[test]
+    fn unstructured_reviewer_reply_is_an_error() {
+        let mut review = output(Vec::new());
+        review.overall_correctness = String::new();
+        review.overall_explanation = "Looks fine to me, but I could not format it.".to_string();
+        let err = ensure_structured(&review).expect_err("unstructured reply");
+        assert!(err.to_string().contains("could not format it"), "{err}");
+
+        assert!(ensure_structured(&output(Vec::new())).is_ok());
+        review.overall_explanation = String::new();
+        assert!(ensure_structured(&review).is_ok());
+    }
+
+    #[test]
+    fn unknown_revisions_are_rejected_before_reviewing() {
+        let repo = tempfile::TempDir::new().expect("tempdir");
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .env("GIT_CONFIG_NOSYSTEM", "1")
+                .current_dir(repo.path())
+                .args(args)
+                .status()
+                .expect("run git");
+            assert!(status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q"]);
+        git(&[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "init",
+        ]);
+
+        assert!(verify_revision(repo.path(), "HEAD").is_ok());
+        let err = verify_revision(repo.path(), "no-such-branch").expect_err("unknown ref");
+        assert!(err.to_string().contains("no-such-branch"), "{err}");
+    }
+}
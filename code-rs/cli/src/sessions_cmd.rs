@@ -0,0 +1,172 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use code_common::CliConfigOverrides;
+use code_core::SessionCatalog;
+use code_core::SessionIndexEntry;
+use code_core::SessionQuery;
+use code_core::config::find_code_home;
+use code_core::entry_to_rollout_path;
+
+/// Inspect recorded sessions.
+///
+/// Subcommands:
+/// - `search` — full-text search over messages and commands (with `--json`)
+#[derive(Debug, clap::Parser)]
+pub struct SessionsCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsSubcommand {
+    /// Search user/assistant messages and exec commands across all sessions.
+    Search(SearchArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SearchArgs {
+    /// Words to search for; every word must appear in the session.
+    #[arg(required = true, num_args = 1..)]
+    pub query: Vec<String>,
+
+    /// Only search sessions started in the current directory.
+    #[arg(long)]
+    pub here: bool,
+
+    /// Maximum number of sessions to show.
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Output the matching sessions as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl SessionsCli {
+    pub async fn run(self) -> Result<()> {
+        let SessionsCli {
+            config_overrides,
+            subcommand,
+        } = self;
+        // Validate any provided overrides even though they are not currently applied.
+        config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+        match subcommand {
+            SessionsSubcommand::Search(args) => run_search(args).await,
+        }
+    }
+}
+
+async fn run_search(args: SearchArgs) -> Result<()> {
+    let code_home = find_code_home().context("failed to resolve CODE_HOME")?;
+    let cwd = if args.here {
+        Some(std::env::current_dir()?)
+    } else {
+        None
+    };
+    let text = args.query.join(" ");
+
+    let catalog = SessionCatalog::new(code_home.clone());
+    let query = SessionQuery {
+        cwd,
+        min_user_messages: 1,
+        limit: Some(args.limit),
+        text: Some(text.clone()),
+        ..SessionQuery::default()
+    };
+    let entries = catalog
+        .query(&query)
+        .await
+        .context("failed to search sessions")?;
+
+    if args.json {
+        let json_entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "session_id": entry.session_id,
+                    "rollout_path": entry_to_rollout_path(&code_home, entry),
+                    "created_at": entry.created_at,
+                    "last_event_at": entry.last_event_at,
+                    "cwd": entry.cwd_display,
+                    "git_branch": entry.git_branch,
+                    "user_message_count": entry.user_message_count,
+                    "last_user_snippet": entry.last_user_snippet,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json_entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No sessions match \"{text}\".");
+        return Ok(());
+    }
+
+    print_table(&entries);
+    println!();
+    println!("Resume one with `code resume <session>`.");
+    Ok(())
+}
+
+fn print_table(entries: &[SessionIndexEntry]) {
+    let rows: Vec<[String; 4]> = entries
+        .iter()
+        .map(|entry| {
+            [
+                short_timestamp(&entry.last_event_at),
+                entry.session_id.to_string()[..8].to_string(),
+                entry.cwd_display.clone(),
+                entry
+                    .last_user_snippet
+                    .as_deref()
+                    .map(|s| s.lines().next().unwrap_or_default().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    let headers = ["Updated", "Session", "Directory", "Last message"];
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    println!(
+        "{:<time_w$}  {:<id_w$}  {:<cwd_w$}  {}",
+        headers[0],
+        headers[1],
+        headers[2],
+        headers[3],
+        time_w = widths[0],
+        id_w = widths[1],
+        cwd_w = widths[2],
+    );
+    for row in &rows {
+        println!(
+            "{:<time_w$}  {:<id_w$}  {:<cwd_w$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            time_w = widths[0],
+            id_w = widths[1],
+            cwd_w = widths[2],
+        );
+    }
+}
+
+/// `2025-11-15T10:00:10.123Z` -> `2025-11-15 10:00`.
+fn short_timestamp(ts: &str) -> String {
+    ts.chars()
+        .take(16)
+        .map(|c| if c == 'T' { ' ' } else { c })
+        .collect()
+}
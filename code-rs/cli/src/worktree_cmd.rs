@@ -0,0 +1,256 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use code_common::CliConfigOverrides;
+use code_core::git_info::resolve_root_git_project_for_trust;
+use code_core::git_worktree::BranchWorktree;
+use code_core::git_worktree::branch_worktree_diff;
+use code_core::git_worktree::find_branch_worktree;
+use code_core::git_worktree::list_branch_worktrees;
+use code_core::git_worktree::merge_branch_worktree;
+use code_core::git_worktree::remove_branch_worktree;
+
+/// Manage the branch worktrees created by `/branch` and agents.
+///
+/// Subcommands:
+/// - `list`  — list worktrees with task, age, ahead/behind and dirty state (with `--json`)
+/// - `show`  — show a single worktree (with `--json`)
+/// - `diff`  — diff a worktree against the point it branched from (with `--stat`)
+/// - `merge` — merge a worktree branch into its base branch (with `--squash`)
+/// - `rm`    — remove a worktree and its branch (with `--force`)
+#[derive(Debug, clap::Parser)]
+pub struct WorktreeCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: WorktreeSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum WorktreeSubcommand {
+    /// List branch worktrees for the current repository.
+    List(ListArgs),
+
+    /// Show details for a branch worktree.
+    Show(ShowArgs),
+
+    /// Show the changes made in a branch worktree.
+    Diff(DiffArgs),
+
+    /// Merge a branch worktree into its base branch.
+    Merge(MergeArgs),
+
+    /// Remove a branch worktree and delete its branch.
+    Rm(RmArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Output the worktrees as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ShowArgs {
+    /// Branch name, directory name or path of the worktree.
+    pub name: String,
+
+    /// Output the worktree as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct DiffArgs {
+    /// Branch name, directory name or path of the worktree.
+    pub name: String,
+
+    /// Show a diffstat instead of the full patch.
+    #[arg(long)]
+    pub stat: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct MergeArgs {
+    /// Branch name, directory name or path of the worktree.
+    pub name: String,
+
+    /// Squash the worktree's commits into a single commit.
+    #[arg(long)]
+    pub squash: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct RmArgs {
+    /// Branch name, directory name or path of the worktree.
+    pub name: String,
+
+    /// Remove even if the worktree has uncommitted or unmerged changes.
+    #[arg(long, short = 'f')]
+    pub force: bool,
+}
+
+impl WorktreeCli {
+    pub async fn run(self) -> Result<()> {
+        let WorktreeCli {
+            config_overrides,
+            subcommand,
+        } = self;
+        // Validate any provided overrides even though they are not currently applied.
+        config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+        let git_root = repo_root()?;
+        match subcommand {
+            WorktreeSubcommand::List(args) => {
+                let worktrees = list_branch_worktrees(&git_root)
+                    .await
+                    .map_err(|e| anyhow!(e))?;
+                if args.json {
+                    let entries: Vec<_> = worktrees.iter().map(worktree_json).collect();
+                    println!("{}", serde_json::to_string_pretty(&entries)?);
+                } else {
+                    print_table(&worktrees);
+                }
+            }
+            WorktreeSubcommand::Show(args) => {
+                let wt = find(&git_root, &args.name).await?;
+                if args.json {
+                    println!("{}", serde_json::to_string_pretty(&worktree_json(&wt))?);
+                } else {
+                    print_details(&wt);
+                }
+            }
+            WorktreeSubcommand::Diff(args) => {
+                let wt = find(&git_root, &args.name).await?;
+                let diff = branch_worktree_diff(&wt, args.stat)
+                    .await
+                    .map_err(|e| anyhow!(e))?;
+                print!("{diff}");
+            }
+            WorktreeSubcommand::Merge(args) => {
+                let wt = find(&git_root, &args.name).await?;
+                let summary = merge_branch_worktree(&git_root, &wt, args.squash)
+                    .await
+                    .map_err(|e| anyhow!(e))?;
+                println!("{summary}");
+            }
+            WorktreeSubcommand::Rm(args) => {
+                let wt = find(&git_root, &args.name).await?;
+                remove_branch_worktree(&git_root, &wt, args.force)
+                    .await
+                    .map_err(|e| anyhow!(e))?;
+                println!("Removed worktree {} ({}).", wt.name(), wt.path.display());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Root of the main checkout, even when run from inside a branch worktree.
+fn repo_root() -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    resolve_root_git_project_for_trust(&cwd)
+        .ok_or_else(|| anyhow!("{} is not inside a git repository", cwd.display()))
+}
+
+async fn find(git_root: &std::path::Path, name: &str) -> Result<BranchWorktree> {
+    find_branch_worktree(git_root, name)
+        .await
+        .map_err(|e| anyhow!("{e}. Run `code worktree list` to see available worktrees."))
+}
+
+fn worktree_json(wt: &BranchWorktree) -> serde_json::Value {
+    serde_json::json!({
+        "name": wt.name(),
+        "path": wt.path,
+        "branch": wt.branch,
+        "task": wt.task(),
+        "base": wt.base_ref,
+        "ahead": wt.ahead,
+        "behind": wt.behind,
+        "dirty_files": wt.dirty_files,
+        "created_at": wt.created_at,
+    })
+}
+
+fn print_table(worktrees: &[BranchWorktree]) {
+    if worktrees.is_empty() {
+        println!("No branch worktrees. Create one with `/branch <task>` in the TUI.");
+        return;
+    }
+
+    let rows: Vec<[String; 5]> = worktrees
+        .iter()
+        .map(|wt| {
+            [
+                wt.name(),
+                wt.age_label().unwrap_or_else(|| "-".to_string()),
+                format!("↑{} ↓{}", wt.ahead, wt.behind),
+                dirty_label(wt),
+                wt.task().unwrap_or("-").to_string(),
+            ]
+        })
+        .collect();
+
+    let headers = ["Name", "Age", "Ahead/Behind", "State", "Task"];
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    println!(
+        "{:<name_w$}  {:<age_w$}  {:<ab_w$}  {:<state_w$}  {}",
+        headers[0],
+        headers[1],
+        headers[2],
+        headers[3],
+        headers[4],
+        name_w = widths[0],
+        age_w = widths[1],
+        ab_w = widths[2],
+        state_w = widths[3],
+    );
+    for row in &rows {
+        println!(
+            "{:<name_w$}  {:<age_w$}  {:<ab_w$}  {:<state_w$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            name_w = widths[0],
+            age_w = widths[1],
+            ab_w = widths[2],
+            state_w = widths[3],
+        );
+    }
+}
+
+fn print_details(wt: &BranchWorktree) {
+    println!("{}", wt.name());
+    println!("  path: {}", wt.path.display());
+    if let Some(task) = wt.task() {
+        println!("  task: {task}");
+    }
+    if let Some(age) = wt.age_label() {
+        println!("  age: {age}");
+    }
+    println!("  base: {}", wt.base_ref.as_deref().unwrap_or("-"));
+    println!("  ahead: {}", wt.ahead);
+    println!("  behind: {}", wt.behind);
+    println!("  state: {}", dirty_label(wt));
+}
+
+fn dirty_label(wt: &BranchWorktree) -> String {
+    if wt.is_dirty() {
+        format!("dirty ({})", wt.dirty_files)
+    } else {
+        "clean".to_string()
+    }
+}
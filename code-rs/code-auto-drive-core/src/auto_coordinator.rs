@@ -233,6 +233,8 @@ pub enum BudgetAlertType {
     TokenExceeded,
     TurnLimitReached,
     DurationExceeded,
+    CostWarning,
+    CostExceeded,
 }
 
 impl AutoCoordinatorEvent {
@@ -1,11 +1,17 @@
 //! Budget controller for resource limit management.
 //!
 //! This module provides tracking and enforcement of resource limits including
-//! token budgets, turn counts, and duration limits.
+//! token budgets, cost budgets, turn counts, and duration limits.
 
+use std::collections::HashMap;
 use std::time::Duration;
 use std::time::Instant;
 
+use code_core::config_types::ModelPricing;
+use code_core::protocol::TokenUsage;
+
+const TOKENS_PER_MILLION: f64 = 1_000_000.0;
+
 /// Configuration for budget limits.
 #[derive(Clone, Debug, Default)]
 pub struct BudgetConfig {
@@ -15,6 +21,10 @@ pub struct BudgetConfig {
     pub turn_limit: Option<u32>,
     /// Maximum duration allowed.
     pub duration_limit: Option<Duration>,
+    /// Maximum spend allowed, in USD.
+    pub cost_budget_usd: Option<f64>,
+    /// Token prices keyed by model slug.
+    pub pricing: HashMap<String, ModelPricing>,
 }
 
 /// Current resource usage statistics.
@@ -26,6 +36,8 @@ pub struct ResourceUsage {
     pub turns_completed: u32,
     /// Time elapsed since start.
     pub elapsed: Duration,
+    /// Spend across every priced spender, in USD.
+    pub cost_usd: f64,
 }
 
 /// Alerts emitted when budget thresholds are reached.
@@ -43,17 +55,102 @@ pub enum BudgetAlert {
     TurnLimitReached { count: u32, limit: u32 },
     /// Duration limit has been exceeded.
     DurationExceeded { elapsed: Duration, limit: Duration },
+    /// Spend has reached warning threshold (80%).
+    CostWarning {
+        used_usd: f64,
+        limit_usd: f64,
+        percentage: f32,
+    },
+    /// Spend has exceeded the cost budget.
+    CostExceeded { used_usd: f64, limit_usd: f64 },
     /// Session pool queue is approaching backpressure threshold.
     BackpressureWarning { queue_size: i32, limit: i32 },
     /// Session pool queue exceeded backpressure threshold.
     BackpressureExceeded { queue_size: i32, limit: i32 },
 }
 
+impl BudgetAlert {
+    /// One-line description for notices and logs.
+    pub fn message(&self) -> String {
+        match self {
+            Self::TokenWarning {
+                used,
+                limit,
+                percentage,
+            } => format!("{percentage:.0}% of token budget used ({used}/{limit})"),
+            Self::TokenExceeded { used, limit } => {
+                format!("token budget exceeded ({used}/{limit})")
+            }
+            Self::TurnLimitReached { count, limit } => {
+                format!("turn limit reached ({count}/{limit})")
+            }
+            Self::DurationExceeded { elapsed, limit } => format!(
+                "duration limit exceeded ({}s/{}s)",
+                elapsed.as_secs(),
+                limit.as_secs()
+            ),
+            Self::CostWarning {
+                used_usd,
+                limit_usd,
+                percentage,
+            } => format!("{percentage:.0}% of cost budget used (${used_usd:.2}/${limit_usd:.2})"),
+            Self::CostExceeded {
+                used_usd,
+                limit_usd,
+            } => format!("cost budget exceeded (${used_usd:.2}/${limit_usd:.2})"),
+            Self::BackpressureWarning { queue_size, limit } => {
+                format!("session queue near capacity ({queue_size}/{limit})")
+            }
+            Self::BackpressureExceeded { queue_size, limit } => {
+                format!("session queue full ({queue_size}/{limit})")
+            }
+        }
+    }
+}
+
+/// Returns the price of `usage` in USD. Input counts include cached tokens
+/// and output counts include reasoning tokens, so each is billed once.
+pub fn usage_cost_usd(pricing: &ModelPricing, usage: &TokenUsage) -> f64 {
+    let cached = usage.cached_input();
+    let reasoning = usage.reasoning_output_tokens.min(usage.output_tokens);
+    let cached_price = pricing
+        .cached_input_per_mtok
+        .unwrap_or(pricing.input_per_mtok);
+    let reasoning_price = pricing
+        .reasoning_output_per_mtok
+        .unwrap_or(pricing.output_per_mtok);
+    (usage.non_cached_input() as f64 * pricing.input_per_mtok
+        + cached as f64 * cached_price
+        + (usage.output_tokens - reasoning) as f64 * pricing.output_per_mtok
+        + reasoning as f64 * reasoning_price)
+        / TOKENS_PER_MILLION
+}
+
+/// Finds the pricing entry for `model`: an exact key, otherwise the longest
+/// key that `model` starts with.
+pub fn pricing_for_model<'a>(
+    pricing: &'a HashMap<String, ModelPricing>,
+    model: &str,
+) -> Option<&'a ModelPricing> {
+    if let Some(entry) = pricing.get(model) {
+        return Some(entry);
+    }
+    pricing
+        .iter()
+        .filter(|(key, _)| model.starts_with(key.as_str()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, entry)| entry)
+}
+
 /// Controller for managing resource budgets.
+#[derive(Clone, Debug)]
 pub struct BudgetController {
     config: BudgetConfig,
     current_usage: ResourceUsage,
     started_at: Option<Instant>,
+    /// Spend per spender (coordinator, CLI session, sub-agent). Spenders
+    /// report cumulative usage, so a new report replaces the previous one.
+    spender_costs: HashMap<String, f64>,
 }
 
 impl BudgetController {
@@ -63,6 +160,7 @@ impl BudgetController {
             config: BudgetConfig::default(),
             current_usage: ResourceUsage::default(),
             started_at: None,
+            spender_costs: HashMap::new(),
         }
     }
 
@@ -87,6 +185,44 @@ impl BudgetController {
         }
     }
 
+    /// Records the cumulative token usage of one spender on `model` and
+    /// returns its cost, or `None` when the model has no pricing entry.
+    pub fn record_model_usage(
+        &mut self,
+        spender: &str,
+        model: &str,
+        usage: &TokenUsage,
+    ) -> Option<f64> {
+        let pricing = pricing_for_model(&self.config.pricing, model)?;
+        let cost = usage_cost_usd(pricing, usage);
+        self.spender_costs.insert(spender.to_string(), cost);
+        self.current_usage.cost_usd = self.spender_costs.values().sum();
+        Some(cost)
+    }
+
+    /// Total spend so far, or `None` if no priced usage was recorded.
+    pub fn cost_usd(&self) -> Option<f64> {
+        (!self.spender_costs.is_empty()).then_some(self.current_usage.cost_usd)
+    }
+
+    /// Checks the cost budget alone.
+    pub fn check_cost(&self) -> Option<BudgetAlert> {
+        let limit_usd = self.config.cost_budget_usd?;
+        let used_usd = self.current_usage.cost_usd;
+        if used_usd >= limit_usd {
+            return Some(BudgetAlert::CostExceeded {
+                used_usd,
+                limit_usd,
+            });
+        }
+        let percentage = (used_usd / limit_usd * 100.0) as f32;
+        (percentage >= 80.0).then_some(BudgetAlert::CostWarning {
+            used_usd,
+            limit_usd,
+            percentage,
+        })
+    }
+
     /// Checks budget status and returns any alerts.
     pub fn check_budget(&self) -> Option<BudgetAlert> {
         // Check token budget
@@ -105,6 +241,10 @@ impl BudgetController {
             }
         }
 
+        if let Some(alert) = self.check_cost() {
+            return Some(alert);
+        }
+
         // Check turn limit
         if let Some(limit) = self.config.turn_limit
             && self.current_usage.turns_completed >= limit
@@ -149,10 +289,17 @@ impl BudgetController {
             })
             .unwrap_or(Duration::MAX);
 
+        let remaining_cost = self
+            .config
+            .cost_budget_usd
+            .map(|limit| (limit - self.current_usage.cost_usd).max(0.0))
+            .unwrap_or(f64::INFINITY);
+
         ResourceUsage {
             total_tokens: remaining_tokens,
             turns_completed: remaining_turns,
             elapsed: remaining_duration,
+            cost_usd: remaining_cost,
         }
     }
 
@@ -161,6 +308,7 @@ impl BudgetController {
         matches!(
             self.check_budget(),
             Some(BudgetAlert::TokenExceeded { .. })
+                | Some(BudgetAlert::CostExceeded { .. })
                 | Some(BudgetAlert::TurnLimitReached { .. })
                 | Some(BudgetAlert::DurationExceeded { .. })
         )
@@ -175,6 +323,7 @@ impl BudgetController {
     pub fn reset(&mut self) {
         self.current_usage = ResourceUsage::default();
         self.started_at = None;
+        self.spender_costs.clear();
     }
 }
 
@@ -188,6 +337,34 @@ impl Default for BudgetController {
 mod tests {
     use super::*;
 
+    fn priced_config(cost_budget_usd: f64) -> BudgetConfig {
+        let mut pricing = HashMap::new();
+        pricing.insert(
+            "gpt-5.1".to_string(),
+            ModelPricing {
+                input_per_mtok: 1.25,
+                cached_input_per_mtok: Some(0.125),
+                output_per_mtok: 10.0,
+                reasoning_output_per_mtok: None,
+            },
+        );
+        BudgetConfig {
+            cost_budget_usd: Some(cost_budget_usd),
+            pricing,
+            ..Default::default()
+        }
+    }
+
+    fn usage(input: u64, cached: u64, output: u64, reasoning: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            cached_input_tokens: cached,
+            output_tokens: output,
+            reasoning_output_tokens: reasoning,
+            total_tokens: input + output,
+        }
+    }
+
     #[test]
     fn test_no_limits() {
         let controller = BudgetController::new();
@@ -274,4 +451,77 @@ mod tests {
         assert_eq!(controller.current_usage().total_tokens, 0);
         assert_eq!(controller.current_usage().turns_completed, 0);
     }
+
+    #[test]
+    fn test_usage_cost_prices_each_token_class() {
+        let pricing = ModelPricing {
+            input_per_mtok: 2.0,
+            cached_input_per_mtok: Some(0.5),
+            output_per_mtok: 8.0,
+            reasoning_output_per_mtok: Some(16.0),
+        };
+        // 1M fresh input, 1M cached, 1M visible output, 1M reasoning.
+        let cost = usage_cost_usd(&pricing, &usage(2_000_000, 1_000_000, 2_000_000, 1_000_000));
+        assert!((cost - 26.5).abs() < 1e-9, "cost was {cost}");
+    }
+
+    #[test]
+    fn test_pricing_prefers_longest_matching_key() {
+        let mut config = priced_config(10.0);
+        config.pricing.insert(
+            "gpt-5.1-codex".to_string(),
+            ModelPricing {
+                input_per_mtok: 3.0,
+                cached_input_per_mtok: None,
+                output_per_mtok: 12.0,
+                reasoning_output_per_mtok: None,
+            },
+        );
+        let codex =
+            pricing_for_model(&config.pricing, "gpt-5.1-codex-max").map(|p| p.input_per_mtok);
+        let base = pricing_for_model(&config.pricing, "gpt-5.1-mini").map(|p| p.input_per_mtok);
+        assert_eq!(codex, Some(3.0));
+        assert_eq!(base, Some(1.25));
+        assert!(pricing_for_model(&config.pricing, "o3").is_none());
+    }
+
+    #[test]
+    fn test_cost_budget_alerts_and_pause() {
+        let mut controller = BudgetController::new();
+        controller.configure(priced_config(1.0));
+
+        // $0.85 from the coordinator.
+        controller.record_model_usage("coordinator", "gpt-5.1", &usage(0, 0, 85_000, 0));
+        assert!(matches!(
+            controller.check_budget(),
+            Some(BudgetAlert::CostWarning { .. })
+        ));
+        assert!(!controller.should_pause());
+
+        // A sub-agent on a prefixed slug pushes the run over the limit.
+        controller.record_model_usage("agent:a1", "gpt-5.1-codex", &usage(0, 0, 20_000, 0));
+        assert!(matches!(
+            controller.check_budget(),
+            Some(BudgetAlert::CostExceeded { .. })
+        ));
+        assert!(controller.should_pause());
+    }
+
+    #[test]
+    fn test_cumulative_reports_replace_previous_spend() {
+        let mut controller = BudgetController::new();
+        controller.configure(priced_config(100.0));
+        assert_eq!(controller.cost_usd(), None);
+
+        controller.record_model_usage("cli", "gpt-5.1", &usage(0, 0, 100_000, 0));
+        controller.record_model_usage("cli", "gpt-5.1", &usage(0, 0, 200_000, 0));
+        assert_eq!(
+            controller.record_model_usage("cli", "unpriced-model", &usage(0, 0, 1, 0)),
+            None
+        );
+
+        let cost = controller.cost_usd().unwrap_or_default();
+        assert!((cost - 2.0).abs() < 1e-9, "cost was {cost}");
+        assert!((controller.remaining().cost_usd - 98.0).abs() < 1e-9);
+    }
 }
@@ -1,13 +1,21 @@
+use std::collections::HashSet;
 use std::time::Duration;
 use std::time::Instant;
 
 use code_common::elapsed::format_duration;
 use code_core::protocol::ReviewContextMetadata;
 use code_core::protocol::ReviewOutputEvent;
+use code_core::protocol::TokenUsage;
 use code_git_tooling::GhostCommit;
 
 use crate::AutoTurnAgentsAction;
 use crate::AutoTurnAgentsTiming;
+use crate::budget::BudgetAlert;
+use crate::budget::BudgetConfig;
+use crate::budget::BudgetController;
+
+const COORDINATOR_SPENDER: &str = "coordinator";
+const CLI_SPENDER: &str = "cli";
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AutoContinueMode {
@@ -190,6 +198,9 @@ pub struct AutoRunSummary {
     pub turns_completed: usize,
     pub message: Option<String>,
     pub goal: Option<String>,
+    /// Spend across the coordinator, CLI turns and sub-agents. `None` when
+    /// none of the models used had a pricing entry.
+    pub total_cost_usd: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -349,6 +360,13 @@ pub struct AutoDriveController {
     pub diagnostic_alert: Option<String>,
     pub intervention_reason: Option<String>,
     pub checkpoint_status: Option<String>,
+    pub budget: BudgetController,
+    /// CLI session usage at launch; the session total includes earlier turns.
+    cli_usage_baseline: TokenUsage,
+    /// Agents that already existed at launch and are not billed to the run.
+    preexisting_agents: HashSet<String>,
+    cost_warning_reported: bool,
+    cost_exceeded_reported: bool,
 }
 
 impl AutoDriveController {
@@ -577,6 +595,80 @@ impl AutoDriveController {
         ]
     }
 
+    /// Applies the run's budget limits and starts its clock. Call after
+    /// `prepare_launch`, which resets the controller. Usage already spent by
+    /// the CLI session and by `existing_agents` is not billed to the run.
+    pub fn configure_budget(
+        &mut self,
+        config: BudgetConfig,
+        cli_usage: &TokenUsage,
+        existing_agents: impl IntoIterator<Item = String>,
+    ) {
+        self.budget.configure(config);
+        self.budget.start();
+        self.cli_usage_baseline = cli_usage.clone();
+        self.preexisting_agents = existing_agents.into_iter().collect();
+    }
+
+    /// Records the coordinator's cumulative usage for this run.
+    pub fn record_coordinator_spend(
+        &mut self,
+        model: &str,
+        usage: &TokenUsage,
+    ) -> Option<BudgetAlert> {
+        self.record_spend(COORDINATOR_SPENDER, model, usage)
+    }
+
+    /// Records the CLI session's cumulative usage; only what was spent since
+    /// launch counts toward the run.
+    pub fn record_cli_spend(
+        &mut self,
+        model: &str,
+        session_total: &TokenUsage,
+    ) -> Option<BudgetAlert> {
+        let usage = usage_since(session_total, &self.cli_usage_baseline);
+        self.record_spend(CLI_SPENDER, model, &usage)
+    }
+
+    /// Records a sub-agent's cumulative usage.
+    pub fn record_agent_spend(
+        &mut self,
+        agent_id: &str,
+        model: &str,
+        usage: &TokenUsage,
+    ) -> Option<BudgetAlert> {
+        if self.preexisting_agents.contains(agent_id) {
+            return None;
+        }
+        self.record_spend(&format!("agent:{agent_id}"), model, usage)
+    }
+
+    /// Returns a cost alert the first time the run crosses the warning
+    /// threshold or the limit.
+    fn record_spend(
+        &mut self,
+        spender: &str,
+        model: &str,
+        usage: &TokenUsage,
+    ) -> Option<BudgetAlert> {
+        if self.started_at.is_none() {
+            return None;
+        }
+        self.budget.record_model_usage(spender, model, usage)?;
+        match self.budget.check_cost()? {
+            alert @ BudgetAlert::CostExceeded { .. } if !self.cost_exceeded_reported => {
+                self.cost_exceeded_reported = true;
+                self.cost_warning_reported = true;
+                Some(alert)
+            }
+            alert @ BudgetAlert::CostWarning { .. } if !self.cost_warning_reported => {
+                self.cost_warning_reported = true;
+                Some(alert)
+            }
+            _ => None,
+        }
+    }
+
     pub fn launch_failed(&mut self, goal: String, error: String) -> Vec<AutoControllerEffect> {
         self.goal = None;
         self.mark_intro_pending();
@@ -601,6 +693,7 @@ impl AutoDriveController {
             turns_completed: self.turns_completed,
             message: message.clone(),
             goal: self.goal.clone(),
+            total_cost_usd: self.budget.cost_usd(),
         };
 
         self.reset();
@@ -651,6 +744,7 @@ impl AutoDriveController {
                     "Auto Drive stopped after {AUTO_RESTART_MAX_ATTEMPTS} reconnect attempts."
                 )),
                 goal: self.goal.clone(),
+                total_cost_usd: self.budget.cost_usd(),
             };
             self.reset();
             self.last_run_summary = Some(summary.clone());
@@ -978,14 +1072,41 @@ impl AutoDriveController {
     }
 }
 
+fn usage_since(total: &TokenUsage, baseline: &TokenUsage) -> TokenUsage {
+    TokenUsage {
+        input_tokens: total.input_tokens.saturating_sub(baseline.input_tokens),
+        cached_input_tokens: total
+            .cached_input_tokens
+            .saturating_sub(baseline.cached_input_tokens),
+        output_tokens: total.output_tokens.saturating_sub(baseline.output_tokens),
+        reasoning_output_tokens: total
+            .reasoning_output_tokens
+            .saturating_sub(baseline.reasoning_output_tokens),
+        total_tokens: total.total_tokens.saturating_sub(baseline.total_tokens),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::AUTO_RESTART_MAX_ATTEMPTS;
     use super::AutoControllerEffect;
     use super::AutoDriveController;
     use super::AutoRunPhase;
+    use crate::budget::BudgetAlert;
+    use crate::budget::BudgetConfig;
+    use code_core::config_types::ModelPricing;
+    use code_core::protocol::TokenUsage;
+    use std::collections::HashMap;
     use std::time::Instant;
 
+    fn output_tokens(output_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            output_tokens,
+            total_tokens: output_tokens,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn bypass_flag_only_applies_once_across_manual_resume() {
         let mut controller = AutoDriveController::default();
@@ -1087,4 +1208,74 @@ mod tests {
         let effects = controller.handle_countdown_tick(countdown_id, 3, 5);
         assert!(effects.is_empty());
     }
+
+    #[test]
+    fn run_cost_covers_all_spenders_and_alerts_once() {
+        let mut controller = AutoDriveController::default();
+        controller.prepare_launch(
+            "Ship it".to_string(),
+            true,
+            true,
+            true,
+            true,
+            super::AutoContinueMode::Immediate,
+            true,
+        );
+        let mut pricing = HashMap::new();
+        pricing.insert(
+            "gpt-5.1".to_string(),
+            ModelPricing {
+                input_per_mtok: 1.0,
+                cached_input_per_mtok: None,
+                output_per_mtok: 10.0,
+                reasoning_output_per_mtok: None,
+            },
+        );
+        controller.configure_budget(
+            BudgetConfig {
+                cost_budget_usd: Some(1.0),
+                pricing,
+                ..Default::default()
+            },
+            &output_tokens(500_000),
+            ["old-agent".to_string()],
+        );
+        controller.launch_succeeded("Ship it".to_string(), None, Instant::now());
+
+        // $0.50 from the coordinator, $0.10 from the CLI since launch.
+        assert!(
+            controller
+                .record_coordinator_spend("gpt-5.1", &output_tokens(50_000))
+                .is_none()
+        );
+        assert!(
+            controller
+                .record_cli_spend("gpt-5.1", &output_tokens(510_000))
+                .is_none()
+        );
+        assert!(
+            controller
+                .record_agent_spend("old-agent", "gpt-5.1", &output_tokens(900_000))
+                .is_none()
+        );
+
+        let warning = controller.record_agent_spend("a1", "gpt-5.1-codex", &output_tokens(25_000));
+        assert!(matches!(warning, Some(BudgetAlert::CostWarning { .. })));
+        assert!(
+            controller
+                .record_agent_spend("a1", "gpt-5.1-codex", &output_tokens(26_000))
+                .is_none()
+        );
+
+        let exceeded = controller.record_agent_spend("a1", "gpt-5.1-codex", &output_tokens(45_000));
+        assert!(matches!(exceeded, Some(BudgetAlert::CostExceeded { .. })));
+
+        controller.stop_run(Instant::now(), None);
+        let cost = controller
+            .last_run_summary
+            .as_ref()
+            .and_then(|summary| summary.total_cost_usd)
+            .unwrap_or_default();
+        assert!((cost - 1.05).abs() < 1e-9, "cost was {cost}");
+    }
 }
@@ -62,6 +62,7 @@ use crate::telemetry::TelemetryCollector;
 use crate::telemetry::TurnOutcome;
 use anyhow::anyhow;
 use code_core::config_types::AutoDriveSettings;
+use code_core::config_types::ModelPricing;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -130,21 +131,28 @@ impl EnhancedConfig {
         token_budget: Option<u64>,
         turn_limit: Option<u32>,
         duration_limit_seconds: Option<u64>,
+        cost_budget_usd: Option<f64>,
+        pricing: HashMap<String, ModelPricing>,
         max_concurrent_agents: usize,
         audit_enabled: bool,
         audit_path: Option<PathBuf>,
         telemetry_enabled: bool,
     ) -> Self {
-        let budget =
-            if token_budget.is_some() || turn_limit.is_some() || duration_limit_seconds.is_some() {
-                Some(BudgetConfig {
-                    token_budget,
-                    turn_limit,
-                    duration_limit: duration_limit_seconds.map(std::time::Duration::from_secs),
-                })
-            } else {
-                None
-            };
+        let budget = if token_budget.is_some()
+            || turn_limit.is_some()
+            || duration_limit_seconds.is_some()
+            || cost_budget_usd.is_some()
+        {
+            Some(BudgetConfig {
+                token_budget,
+                turn_limit,
+                duration_limit: duration_limit_seconds.map(std::time::Duration::from_secs),
+                cost_budget_usd,
+                pricing,
+            })
+        } else {
+            None
+        };
 
         Self {
             checkpoint_enabled,
@@ -29,6 +29,8 @@ pub enum BudgetAlertKind {
     TokenExceeded,
     TurnLimitReached,
     DurationExceeded,
+    CostWarning,
+    CostExceeded,
     BackpressureWarning,
     BackpressureExceeded,
 }
@@ -40,6 +42,8 @@ impl From<&BudgetAlert> for BudgetAlertKind {
             BudgetAlert::TokenExceeded { .. } => Self::TokenExceeded,
             BudgetAlert::TurnLimitReached { .. } => Self::TurnLimitReached,
             BudgetAlert::DurationExceeded { .. } => Self::DurationExceeded,
+            BudgetAlert::CostWarning { .. } => Self::CostWarning,
+            BudgetAlert::CostExceeded { .. } => Self::CostExceeded,
             BudgetAlert::BackpressureWarning { .. } => Self::BackpressureWarning,
             BudgetAlert::BackpressureExceeded { .. } => Self::BackpressureExceeded,
         }
@@ -228,6 +232,8 @@ impl InterventionHandler {
                     BudgetAlertKind::TokenExceeded => "Token budget exceeded".to_string(),
                     BudgetAlertKind::TurnLimitReached => "Turn limit reached".to_string(),
                     BudgetAlertKind::DurationExceeded => "Duration limit exceeded".to_string(),
+                    BudgetAlertKind::CostWarning => "Cost budget warning".to_string(),
+                    BudgetAlertKind::CostExceeded => "Cost budget exceeded".to_string(),
                     BudgetAlertKind::BackpressureWarning => {
                         "Session pool backpressure warning".to_string()
                     }
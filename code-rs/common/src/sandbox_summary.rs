@@ -1,12 +1,22 @@
 use code_core::protocol::SandboxPolicy;
 
 pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
+    let mut summary = summarize_access(sandbox_policy);
+    let deny_read = sandbox_policy.deny_read();
+    if !deny_read.is_empty() {
+        summary.push_str(&format!(" (read denied: {})", deny_read.join(", ")));
+    }
+    summary
+}
+
+fn summarize_access(sandbox_policy: &SandboxPolicy) -> String {
     match sandbox_policy {
         SandboxPolicy::DangerFullAccess => "danger-full-access".to_string(),
-        SandboxPolicy::ReadOnly => "read-only".to_string(),
+        SandboxPolicy::ReadOnly { .. } => "read-only".to_string(),
         SandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
+            network_allowlist,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
             ..
@@ -30,6 +40,11 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             summary.push_str(&format!(" [{}]", writable_entries.join(", ")));
             if *network_access {
                 summary.push_str(" (network access enabled)");
+            } else if !network_allowlist.is_empty() {
+                summary.push_str(&format!(
+                    " (network allowlist: {})",
+                    network_allowlist.join(", ")
+                ));
             }
             summary
         }
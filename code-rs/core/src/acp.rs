@@ -3,8 +3,12 @@
 use agent_client_protocol as acp;
 use anyhow::Context as _;
 use anyhow::Result;
+use code_apply_patch::AffectedPaths;
+use code_apply_patch::ApplyPatchAction;
+use code_apply_patch::ApplyPatchError;
 use code_apply_patch::FileSystem;
 use code_apply_patch::StdFileSystem;
+use code_apply_patch::apply_action_through;
 use mcp_types::CallToolResult;
 use std::collections::HashMap;
 use std::path::Path;
@@ -127,6 +131,17 @@ impl<'a> FileSystem for AcpFileSystem<'a> {
             StdFileSystem.write_text_file(path, contents).await
         }
     }
+
+    async fn apply_action(
+        &self,
+        action: &ApplyPatchAction,
+    ) -> std::result::Result<AffectedPaths, ApplyPatchError> {
+        if self.tools.write_text_file.is_some() {
+            apply_action_through(self, action).await
+        } else {
+            StdFileSystem.apply_action(action).await
+        }
+    }
 }
 
 pub(crate) async fn request_permission(
@@ -273,6 +288,7 @@ pub fn new_patch_tool_call(
         match change {
             FileChange::Add {
                 content: new_content,
+                ..
             } => {
                 content.push(acp::ToolCallContent::Diff {
                     diff: acp::Diff {
@@ -302,7 +318,7 @@ pub fn new_patch_tool_call(
                 move_path,
                 new_content,
                 original_content,
-                unified_diff: _,
+                ..
             } => {
                 if let Some(new_path) = move_path
                     && changes.len() > 1
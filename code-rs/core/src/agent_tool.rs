@@ -94,8 +94,16 @@ use crate::openai_tools::JsonSchema;
 use crate::openai_tools::OpenAiTool;
 use crate::openai_tools::ResponsesApiTool;
 use crate::protocol::AgentInfo;
+use crate::protocol::AgentTokenUsage;
 use shlex::split as shlex_split;
 
+/// Set in the environment of built-in agents so that `code exec` reports the
+/// run's token usage on exit.
+pub const AGENT_USAGE_REPORT_ENV: &str = "CODE_AGENT_USAGE_REPORT";
+
+/// Prefix of the stderr line carrying an [`AgentTokenUsage`] as JSON.
+pub const AGENT_USAGE_REPORT_PREFIX: &str = "code-agent-usage: ";
+
 fn current_code_binary_path() -> Result<std::path::PathBuf, String> {
     if let Ok(path) = std::env::var("CODE_BINARY_PATH") {
         return Ok(std::path::PathBuf::from(path));
@@ -178,6 +186,8 @@ pub struct Agent {
     #[allow(dead_code)]
     pub config: Option<AgentConfig>,
     pub reasoning_effort: code_protocol::config_types::ReasoningEffort,
+    #[serde(default)]
+    pub token_usage: Option<AgentTokenUsage>,
 }
 
 // Global agent manager
@@ -237,7 +247,11 @@ impl AgentManager {
                         result: agent.result.clone(),
                         error: agent.error.clone(),
                         elapsed_ms,
-                        token_count: None,
+                        token_count: agent
+                            .token_usage
+                            .as_ref()
+                            .map(|report| report.usage.blended_total()),
+                        token_usage: agent.token_usage.clone(),
                     }
                 })
                 .collect();
@@ -363,6 +377,7 @@ impl AgentManager {
             branch_name: None,
             config: config.clone(),
             reasoning_effort,
+            token_usage: None,
         };
 
         self.agents.insert(agent_id.clone(), agent.clone());
@@ -496,6 +511,13 @@ impl AgentManager {
         }
     }
 
+    pub async fn update_agent_token_usage(&mut self, agent_id: &str, usage: AgentTokenUsage) {
+        if let Some(agent) = self.agents.get_mut(agent_id) {
+            agent.token_usage = Some(usage);
+            self.send_agent_status_update().await;
+        }
+    }
+
     pub async fn add_progress(&mut self, agent_id: &str, message: String) {
         if let Some(agent) = self.agents.get_mut(agent_id) {
             agent
@@ -572,7 +594,26 @@ fn generate_branch_id(model: &str, agent: &str) -> String {
     format!("code-{model_s}-{suffix_s}")
 }
 
+use crate::git_worktree::BranchMetadata;
+use crate::git_worktree::current_branch;
 use crate::git_worktree::setup_worktree;
+use crate::git_worktree::write_branch_metadata;
+
+/// One-line task summary recorded in the worktree's branch metadata.
+fn worktree_task_summary(prompt: &str) -> String {
+    const MAX_CHARS: usize = 80;
+    let line = prompt
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if line.chars().count() > MAX_CHARS {
+        let truncated: String = line.chars().take(MAX_CHARS - 1).collect();
+        format!("{truncated}…")
+    } else {
+        line.to_string()
+    }
+}
 
 async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
     let mut manager = AGENT_MANAGER.write().await;
@@ -648,8 +689,18 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
                     .await;
                 drop(manager);
 
+                let base_branch = current_branch(&git_root).await;
                 match setup_worktree(&git_root, &branch_id).await {
                     Ok((worktree_path, used_branch)) => {
+                        let metadata = BranchMetadata {
+                            base_branch,
+                            task: Some(worktree_task_summary(&prompt)),
+                            created_at: Some(Utc::now().timestamp()),
+                            ..Default::default()
+                        };
+                        if let Err(err) = write_branch_metadata(&worktree_path, &metadata).await {
+                            tracing::warn!("failed to record branch metadata: {err}");
+                        }
                         let mut manager = AGENT_MANAGER.write().await;
                         manager
                             .add_progress(
@@ -698,6 +749,7 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
                             }
                         } else {
                             execute_model_with_permissions(
+                                Some(&agent_id),
                                 &model,
                                 &full_prompt,
                                 false,
@@ -748,6 +800,7 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
             }
         } else {
             execute_model_with_permissions(
+                Some(&agent_id),
                 &model,
                 &full_prompt,
                 true,
@@ -765,6 +818,7 @@ async fn execute_agent(agent_id: String, config: Option<AgentConfig>) {
 }
 
 async fn execute_model_with_permissions(
+    agent_id: Option<&str>,
     model: &str,
     prompt: &str,
     read_only: bool,
@@ -993,6 +1047,7 @@ async fn execute_model_with_permissions(
     };
 
     let built_in_cloud = family == "cloud" && config.is_none();
+    let report_usage = matches!(family, "codex" | "code");
 
     // Clamp reasoning effort to what the target model supports.
     let clamped_effort = match reasoning_effort {
@@ -1095,6 +1150,9 @@ async fn execute_model_with_permissions(
                 env.insert(k.clone(), v.clone());
             }
         }
+        if report_usage {
+            env.insert(AGENT_USAGE_REPORT_ENV.to_string(), "1".to_string());
+        }
 
         // No OS sandbox.
 
@@ -1118,6 +1176,7 @@ async fn execute_model_with_permissions(
                         std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
                     }),
                     &SandboxPolicy::DangerFullAccess,
+                    crate::exec_limits::ExecResourceLimits::default(),
                     StdioPolicy::RedirectForShellTool,
                     env.clone(),
                 )
@@ -1148,6 +1207,9 @@ async fn execute_model_with_permissions(
         }
 
         cmd.args(final_args.clone());
+        if report_usage {
+            cmd.env(AGENT_USAGE_REPORT_ENV, "1");
+        }
         match cmd.output().await {
             Ok(o) => o,
             Err(e) => {
@@ -1160,10 +1222,17 @@ async fn execute_model_with_permissions(
         }
     };
 
+    let (stderr, usage) = take_usage_report(&String::from_utf8_lossy(&output.stderr));
+    if let Some(agent_id) = agent_id
+        && let Some(usage) = usage
+    {
+        let mut manager = AGENT_MANAGER.write().await;
+        manager.update_agent_token_usage(agent_id, usage).await;
+    }
+
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         let stdout = String::from_utf8_lossy(&output.stdout);
         let combined = if stderr.trim().is_empty() {
             stdout.trim().to_string()
@@ -1176,6 +1245,19 @@ async fn execute_model_with_permissions(
     }
 }
 
+/// Splits the usage report written by `code exec` off an agent's stderr.
+fn take_usage_report(stderr: &str) -> (String, Option<AgentTokenUsage>) {
+    let mut usage = None;
+    let mut rest = Vec::new();
+    for line in stderr.lines() {
+        match line.strip_prefix(AGENT_USAGE_REPORT_PREFIX) {
+            Some(json) => usage = serde_json::from_str(json).ok().or(usage),
+            None => rest.push(line),
+        }
+    }
+    (rest.join("\n"), usage)
+}
+
 pub(crate) fn should_use_current_exe_for_agent(
     family: &str,
     command_missing: bool,
@@ -1285,6 +1367,7 @@ async fn run_agent_smoke_test(cfg: AgentConfig) -> Result<String, String> {
     let read_only = should_validate_in_read_only(&cfg);
     let mut task = tokio::spawn(async move {
         execute_model_with_permissions(
+            None,
             &model_name,
             AGENT_SMOKE_TEST_PROMPT,
             read_only,
@@ -1403,6 +1486,7 @@ async fn execute_cloud_built_in_streaming(
             std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
         }),
         &SandboxPolicy::DangerFullAccess,
+        crate::exec_limits::ExecResourceLimits::default(),
         StdioPolicy::RedirectForShellTool,
         env,
     )
@@ -1952,7 +2036,10 @@ mod tests {
     use super::normalize_agent_name;
     use super::resolve_program_path;
     use super::should_use_current_exe_for_agent;
+    use super::take_usage_report;
     use crate::config_types::AgentConfig;
+    use crate::protocol::AgentTokenUsage;
+    use crate::protocol::TokenUsage;
 
     #[test]
     fn drops_empty_names() {
@@ -2025,6 +2112,30 @@ mod tests {
         let custom = resolve_program_path(false, "custom-coder").expect("resolved custom");
         assert_eq!(custom, std::path::PathBuf::from("custom-coder"));
     }
+
+    #[test]
+    fn usage_report_is_split_off_stderr() {
+        let stderr = "warning: slow disk\ncode-agent-usage: {\"model\":\"gpt-5.1-codex\",\"usage\":{\"input_tokens\":1200,\"cached_input_tokens\":200,\"output_tokens\":300,\"reasoning_output_tokens\":100,\"total_tokens\":1500}}\n";
+        let (rest, usage) = take_usage_report(stderr);
+        assert_eq!(rest, "warning: slow disk");
+        assert_eq!(
+            usage,
+            Some(AgentTokenUsage {
+                model: "gpt-5.1-codex".to_string(),
+                usage: TokenUsage {
+                    input_tokens: 1200,
+                    cached_input_tokens: 200,
+                    output_tokens: 300,
+                    reasoning_output_tokens: 100,
+                    total_tokens: 1500,
+                },
+            })
+        );
+
+        let (rest, usage) = take_usage_report("fatal: no repo");
+        assert_eq!(rest, "fatal: no repo");
+        assert_eq!(usage, None);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,1162 @@
+//! Client for Anthropic's Messages API (`wire_api = "anthropic"`).
+//!
+//! The conversation history is rewritten as Messages API content blocks and
+//! the streamed `content_block_*` events are mapped onto [`ResponseEvent`]s so
+//! the rest of the pipeline stays agnostic of the wire format. Thinking blocks
+//! become reasoning items whose `encrypted_content` carries the block
+//! signature, which lets them be replayed verbatim on the next request.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use bytes::Bytes;
+use code_otel::otel_event_manager::OtelEventManager;
+use code_protocol::models::ContentItem;
+use code_protocol::models::ReasoningItemContent;
+use code_protocol::models::ReasoningItemReasoningSummary;
+use code_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+use crate::ModelProviderInfo;
+use crate::auth::AuthManager;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
+use crate::debug_logger::DebugLogger;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::error::RetryLimitReachedError;
+use crate::error::UnexpectedResponseError;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::FREEFORM_INPUT_PARAM;
+use crate::openai_tools::create_tools_json_for_anthropic_messages_api;
+use crate::openai_tools::freeform_tool_names;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+
+const ANTHROPIC_VERSION_HEADER: &str = "anthropic-version";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// `max_tokens` is required by the Messages API. Used when the config does
+/// not set `model_max_output_tokens`.
+const DEFAULT_MAX_TOKENS: u64 = 32_000;
+
+/// Smallest thinking budget the API accepts.
+const MIN_THINKING_BUDGET: u64 = 1_024;
+
+/// Reasoning items produced by this client carry one of these id prefixes.
+/// Only those are replayed as thinking blocks; reasoning from other providers
+/// cannot be verified by Anthropic and is dropped.
+const THINKING_ID_PREFIX: &str = "thinking_";
+const REDACTED_THINKING_ID_PREFIX: &str = "redacted_thinking_";
+
+/// Implementation for the Anthropic Messages API.
+pub(crate) async fn stream_anthropic_messages(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    effort: ReasoningEffortConfig,
+    max_output_tokens: Option<u64>,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    debug_logger: &Arc<Mutex<DebugLogger>>,
+    auth_manager: Option<Arc<AuthManager>>,
+    otel_event_manager: Option<OtelEventManager>,
+    log_tag: Option<&str>,
+) -> Result<ResponseStream> {
+    let payload = build_messages_request(
+        prompt,
+        model_family,
+        model_slug,
+        effort,
+        max_output_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+    )?;
+
+    let endpoint = provider.get_full_url(&None);
+    debug!(
+        "POST to {}: {}",
+        endpoint,
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let mut attempt = 0;
+    let max_retries = provider.request_max_retries();
+    let mut request_id = String::new();
+    loop {
+        attempt += 1;
+
+        let auth = auth_manager.as_ref().and_then(|m| m.auth());
+        let mut req_builder = provider.create_request_builder(client, &auth).await?;
+        let has_version = req_builder
+            .try_clone()
+            .and_then(|builder| builder.build().ok())
+            .is_some_and(|req| req.headers().contains_key(ANTHROPIC_VERSION_HEADER));
+        if !has_version {
+            req_builder = req_builder.header(ANTHROPIC_VERSION_HEADER, ANTHROPIC_VERSION);
+        }
+        req_builder = req_builder
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload);
+
+        if request_id.is_empty()
+            && let Ok(logger) = debug_logger.lock()
+        {
+            request_id = logger
+                .start_request_log(&endpoint, &payload, None, log_tag)
+                .unwrap_or_default();
+        }
+
+        match req_builder.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.append_response_event(
+                        &request_id,
+                        "stream_initiated",
+                        &json!({
+                            "status": "success",
+                            "status_code": resp.status().as_u16()
+                        }),
+                    );
+                }
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                tokio::spawn(process_anthropic_sse(
+                    stream,
+                    tx_event,
+                    freeform_tool_names(&prompt.tools),
+                    provider.stream_idle_timeout(),
+                    Arc::clone(debug_logger),
+                    request_id.clone(),
+                    otel_event_manager.clone(),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                // 529 is Anthropic's "overloaded" status and is worth retrying.
+                let retryable = status == StatusCode::TOO_MANY_REQUESTS
+                    || status.is_server_error()
+                    || status.as_u16() == 529;
+                if !retryable {
+                    let body = res.text().await.unwrap_or_default();
+                    if let Ok(logger) = debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "error",
+                            &json!({
+                                "status": status.as_u16(),
+                                "body": body
+                            }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    return Err(CodexErr::UnexpectedStatus(UnexpectedResponseError {
+                        status,
+                        body,
+                        request_id: None,
+                    }));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(RetryLimitReachedError {
+                        status,
+                        request_id: None,
+                        retryable,
+                    }));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+                let delay = retry_after_secs
+                    .map(Duration::from_secs)
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    if let Ok(logger) = debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "network_error",
+                            &json!({ "error": e.to_string() }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    if e.is_connect() || e.is_timeout() || e.is_request() {
+                        let req_id = (!request_id.is_empty()).then(|| request_id.clone());
+                        return Err(CodexErr::Stream(
+                            format!("[transport] network unavailable: {e}"),
+                            None,
+                            req_id,
+                        ));
+                    }
+                    return Err(e.into());
+                }
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+        }
+    }
+}
+
+/// Builds the Messages API request body for `prompt`.
+fn build_messages_request(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    effort: ReasoningEffortConfig,
+    max_tokens: u64,
+) -> Result<Value> {
+    if prompt.output_schema.is_some() {
+        return Err(CodexErr::UnsupportedOperation(
+            "output_schema is not supported for the Anthropic Messages API".to_string(),
+        ));
+    }
+
+    let messages = build_messages(&prompt.get_formatted_input());
+    let tools = create_tools_json_for_anthropic_messages_api(&prompt.tools)?;
+    let mut payload = json!({
+        "model": model_slug,
+        "max_tokens": max_tokens,
+        "system": prompt.get_full_instructions(model_family),
+        "messages": messages,
+        "stream": true,
+    });
+    let Some(obj) = payload.as_object_mut() else {
+        return Ok(payload);
+    };
+    if !tools.is_empty() {
+        obj.insert("tools".to_string(), json!(tools));
+    }
+    if let Some(budget) = thinking_budget(effort, max_tokens)
+        && can_enable_thinking(&messages)
+    {
+        obj.insert(
+            "thinking".to_string(),
+            json!({ "type": "enabled", "budget_tokens": budget }),
+        );
+    }
+    Ok(payload)
+}
+
+/// Maps the configured reasoning effort to an extended thinking budget. The
+/// budget must stay below `max_tokens`.
+fn thinking_budget(effort: ReasoningEffortConfig, max_tokens: u64) -> Option<u64> {
+    let budget = match effort {
+        ReasoningEffortConfig::Minimal | ReasoningEffortConfig::None => return None,
+        ReasoningEffortConfig::Low => 4_096,
+        ReasoningEffortConfig::Medium => 8_192,
+        ReasoningEffortConfig::High => 16_384,
+        ReasoningEffortConfig::XHigh => 32_768,
+    };
+    let budget = budget.min(max_tokens / 2);
+    (budget >= MIN_THINKING_BUDGET).then_some(budget)
+}
+
+/// With thinking enabled the API requires an assistant turn that ends in tool
+/// use to start with its thinking block. History recorded without thinking
+/// (or by another provider) cannot satisfy that, so thinking stays off.
+fn can_enable_thinking(messages: &[Value]) -> bool {
+    let Some(last_assistant) = messages
+        .iter()
+        .rev()
+        .find(|message| message["role"] == "assistant")
+    else {
+        return true;
+    };
+    let blocks = last_assistant["content"].as_array();
+    let uses_tools =
+        blocks.is_some_and(|blocks| blocks.iter().any(|block| block["type"] == "tool_use"));
+    let starts_with_thinking = blocks
+        .and_then(|blocks| blocks.first())
+        .is_some_and(|block| block["type"] == "thinking" || block["type"] == "redacted_thinking");
+    !uses_tools || starts_with_thinking
+}
+
+/// Converts the formatted history into alternating user/assistant messages.
+/// Consecutive items with the same role are merged into one message.
+fn build_messages(input: &[ResponseItem]) -> Vec<Value> {
+    let mut messages: Vec<Value> = Vec::new();
+    for item in input {
+        let (role, blocks) = match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                let blocks = content.iter().filter_map(content_block).collect();
+                (role, blocks)
+            }
+            ResponseItem::Reasoning {
+                id,
+                summary,
+                content,
+                encrypted_content: Some(signature),
+            } => {
+                let block = if id.starts_with(REDACTED_THINKING_ID_PREFIX) {
+                    json!({ "type": "redacted_thinking", "data": signature })
+                } else if id.starts_with(THINKING_ID_PREFIX) {
+                    json!({
+                        "type": "thinking",
+                        "thinking": reasoning_text(summary, content.as_deref()),
+                        "signature": signature,
+                    })
+                } else {
+                    continue;
+                };
+                ("assistant", vec![block])
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let input = match serde_json::from_str::<Value>(arguments) {
+                    Ok(value @ Value::Object(_)) => value,
+                    _ => json!({ "arguments": arguments }),
+                };
+                let block = json!({
+                    "type": "tool_use",
+                    "id": call_id,
+                    "name": name,
+                    "input": input,
+                });
+                ("assistant", vec![block])
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                // Freeform tools are declared with a single string parameter.
+                let block = json!({
+                    "type": "tool_use",
+                    "id": call_id,
+                    "name": name,
+                    "input": { FREEFORM_INPUT_PARAM: input },
+                });
+                ("assistant", vec![block])
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let mut block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output.content,
+                });
+                if output.success == Some(false) {
+                    block["is_error"] = json!(true);
+                }
+                ("user", vec![block])
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": call_id,
+                    "content": output,
+                });
+                ("user", vec![block])
+            }
+            // Local shell calls, web searches and compaction summaries are
+            // Responses API items with no Messages API equivalent.
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::CompactionSummary { .. }
+            | ResponseItem::Other => continue,
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+
+        match messages.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(content) = last["content"].as_array_mut() {
+                    content.extend(blocks);
+                }
+            }
+            _ => messages.push(json!({ "role": role, "content": blocks })),
+        }
+    }
+    messages
+}
+
+fn content_block(item: &ContentItem) -> Option<Value> {
+    match item {
+        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+            // The API rejects empty text blocks.
+            (!text.is_empty()).then(|| json!({ "type": "text", "text": text }))
+        }
+        ContentItem::InputImage { image_url } => {
+            let source = match image_url
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"))
+            {
+                Some((media_type, data)) => json!({
+                    "type": "base64",
+                    "media_type": media_type,
+                    "data": data,
+                }),
+                None => json!({ "type": "url", "url": image_url }),
+            };
+            Some(json!({ "type": "image", "source": source }))
+        }
+    }
+}
+
+fn reasoning_text(
+    summary: &[ReasoningItemReasoningSummary],
+    content: Option<&[ReasoningItemContent]>,
+) -> String {
+    let mut text = String::new();
+    for ReasoningItemReasoningSummary::SummaryText { text: part } in summary {
+        text.push_str(part);
+    }
+    for item in content.unwrap_or_default() {
+        match item {
+            ReasoningItemContent::ReasoningText { text: part }
+            | ReasoningItemContent::Text { text: part } => text.push_str(part),
+        }
+    }
+    text
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: MessageStart,
+    },
+    ContentBlockStart {
+        index: u32,
+        content_block: ContentBlockStart,
+    },
+    ContentBlockDelta {
+        index: u32,
+        delta: ContentBlockDelta,
+    },
+    ContentBlockStop {
+        index: u32,
+    },
+    MessageDelta {
+        #[serde(default)]
+        usage: Option<Usage>,
+    },
+    MessageStop,
+    Error {
+        error: ApiError,
+    },
+    /// `ping` and event types added after this client was written.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageStart {
+    id: String,
+    #[serde(default)]
+    usage: Usage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlockStart {
+    Text {
+        #[serde(default)]
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+    },
+    Thinking {
+        #[serde(default)]
+        thinking: String,
+        #[serde(default)]
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlockDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    SignatureDelta {
+        signature: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Usage counters. `message_start` reports the input side and
+/// `message_delta` the running output count, so fields are merged.
+#[derive(Debug, Default, Deserialize)]
+struct Usage {
+    input_tokens: Option<u64>,
+    cache_creation_input_tokens: Option<u64>,
+    cache_read_input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+}
+
+impl Usage {
+    fn merge(&mut self, other: Usage) {
+        self.input_tokens = other.input_tokens.or(self.input_tokens);
+        self.cache_creation_input_tokens = other
+            .cache_creation_input_tokens
+            .or(self.cache_creation_input_tokens);
+        self.cache_read_input_tokens = other
+            .cache_read_input_tokens
+            .or(self.cache_read_input_tokens);
+        self.output_tokens = other.output_tokens.or(self.output_tokens);
+    }
+
+    /// Anthropic reports cached tokens separately from `input_tokens`; the
+    /// rest of the pipeline expects them to be included, as OpenAI does.
+    fn to_token_usage(&self) -> TokenUsage {
+        let cached = self.cache_read_input_tokens.unwrap_or(0);
+        let input =
+            self.input_tokens.unwrap_or(0) + self.cache_creation_input_tokens.unwrap_or(0) + cached;
+        let output = self.output_tokens.unwrap_or(0);
+        TokenUsage {
+            input_tokens: input,
+            cached_input_tokens: cached,
+            output_tokens: output,
+            reasoning_output_tokens: 0,
+            total_tokens: input + output,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+/// A content block being streamed.
+enum Block {
+    Text(String),
+    ToolUse {
+        call_id: String,
+        name: String,
+        input_json: String,
+    },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking(String),
+}
+
+impl Block {
+    /// `freeform_tools` names the tools declared with a single string
+    /// parameter; their calls become custom tool calls again.
+    fn into_item(self, item_id: String, freeform_tools: &HashSet<String>) -> Option<ResponseItem> {
+        match self {
+            Block::Text(text) if text.is_empty() => None,
+            Block::Text(text) => Some(ResponseItem::Message {
+                id: Some(item_id),
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText { text }],
+            }),
+            Block::ToolUse {
+                call_id,
+                name,
+                input_json,
+            } if freeform_tools.contains(&name) => {
+                let input = match serde_json::from_str::<Value>(&input_json) {
+                    Ok(Value::Object(mut fields)) => match fields.remove(FREEFORM_INPUT_PARAM) {
+                        Some(Value::String(input)) => input,
+                        _ => input_json,
+                    },
+                    _ => input_json,
+                };
+                Some(ResponseItem::CustomToolCall {
+                    id: None,
+                    status: None,
+                    call_id,
+                    name,
+                    input,
+                })
+            }
+            Block::ToolUse {
+                call_id,
+                name,
+                input_json,
+            } => Some(ResponseItem::FunctionCall {
+                id: None,
+                name,
+                arguments: if input_json.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    input_json
+                },
+                call_id,
+            }),
+            Block::Thinking {
+                thinking,
+                signature,
+            } => Some(ResponseItem::Reasoning {
+                id: format!("{THINKING_ID_PREFIX}{item_id}"),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText { text: thinking }],
+                content: None,
+                encrypted_content: Some(signature),
+            }),
+            Block::RedactedThinking(data) => Some(ResponseItem::Reasoning {
+                id: format!("{REDACTED_THINKING_ID_PREFIX}{item_id}"),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some(data),
+            }),
+        }
+    }
+}
+
+/// SSE processor for the Messages API streaming format.
+async fn process_anthropic_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    freeform_tools: HashSet<String>,
+    idle_timeout: Duration,
+    debug_logger: Arc<Mutex<DebugLogger>>,
+    request_id: String,
+    otel_event_manager: Option<OtelEventManager>,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+    let mut message_id = String::new();
+    let mut usage = Usage::default();
+    let mut blocks: BTreeMap<u32, Block> = BTreeMap::new();
+
+    loop {
+        let next_event = if let Some(manager) = otel_event_manager.as_ref() {
+            manager
+                .log_sse_event(|| timeout(idle_timeout, stream.next()))
+                .await
+        } else {
+            timeout(idle_timeout, stream.next()).await
+        };
+
+        let sse = match next_event {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        format!("[transport] {e}"),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "[idle] timeout waiting for SSE".into(),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        let event: StreamEvent = match serde_json::from_str(&sse.data) {
+            Ok(event) => event,
+            Err(e) => {
+                debug!("anthropic SSE parse error: {e} | data: {}", sse.data);
+                continue;
+            }
+        };
+        trace!("anthropic_messages received SSE event: {event:?}");
+        if let Ok(logger) = debug_logger.lock()
+            && let Ok(value) = serde_json::from_str::<Value>(&sse.data)
+        {
+            let _ = logger.append_response_event(&request_id, "sse_event", &value);
+        }
+
+        match event {
+            StreamEvent::MessageStart { message } => {
+                message_id = message.id;
+                usage.merge(message.usage);
+                if tx_event.send(Ok(ResponseEvent::Created)).await.is_err() {
+                    return;
+                }
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => {
+                let block = match content_block {
+                    ContentBlockStart::Text { text } => Block::Text(text),
+                    ContentBlockStart::ToolUse { id, name } => Block::ToolUse {
+                        call_id: id,
+                        name,
+                        input_json: String::new(),
+                    },
+                    ContentBlockStart::Thinking {
+                        thinking,
+                        signature,
+                    } => Block::Thinking {
+                        thinking,
+                        signature,
+                    },
+                    ContentBlockStart::RedactedThinking { data } => Block::RedactedThinking(data),
+                    ContentBlockStart::Other => continue,
+                };
+                blocks.insert(index, block);
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                let item_id = format!("{message_id}_{index}");
+                let event = match (blocks.get_mut(&index), delta) {
+                    (Some(Block::Text(text)), ContentBlockDelta::TextDelta { text: delta }) => {
+                        text.push_str(&delta);
+                        ResponseEvent::OutputTextDelta {
+                            delta,
+                            item_id: Some(item_id),
+                            sequence_number: None,
+                            output_index: Some(index),
+                        }
+                    }
+                    (
+                        Some(Block::ToolUse { input_json, .. }),
+                        ContentBlockDelta::InputJsonDelta { partial_json },
+                    ) => {
+                        input_json.push_str(&partial_json);
+                        continue;
+                    }
+                    (
+                        Some(Block::Thinking { thinking, .. }),
+                        ContentBlockDelta::ThinkingDelta { thinking: delta },
+                    ) => {
+                        thinking.push_str(&delta);
+                        ResponseEvent::ReasoningSummaryDelta {
+                            delta,
+                            item_id: Some(format!("{THINKING_ID_PREFIX}{item_id}")),
+                            sequence_number: None,
+                            output_index: Some(index),
+                            summary_index: Some(0),
+                        }
+                    }
+                    (
+                        Some(Block::Thinking { signature, .. }),
+                        ContentBlockDelta::SignatureDelta { signature: delta },
+                    ) => {
+                        signature.push_str(&delta);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                if tx_event.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+            StreamEvent::ContentBlockStop { index } => {
+                let Some(item) = blocks.remove(&index).and_then(|block| {
+                    block.into_item(format!("{message_id}_{index}"), &freeform_tools)
+                }) else {
+                    continue;
+                };
+                let event = ResponseEvent::OutputItemDone {
+                    item,
+                    sequence_number: None,
+                    output_index: Some(index),
+                };
+                if tx_event.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+            StreamEvent::MessageDelta {
+                usage: Some(delta_usage),
+            } => usage.merge(delta_usage),
+            StreamEvent::MessageDelta { usage: None } | StreamEvent::Other => {}
+            StreamEvent::MessageStop => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: std::mem::take(&mut message_id),
+                        token_usage: Some(usage.to_token_usage()),
+                    }))
+                    .await;
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.end_request_log(&request_id);
+                }
+                return;
+            }
+            StreamEvent::Error { error } => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        format!("[anthropic] {}: {}", error.kind, error.message),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.end_request_log(&request_id);
+                }
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_family::derive_default_model_family;
+    use crate::model_provider_info::WireApi;
+    use code_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn user(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn provider(base_url: String) -> ModelProviderInfo {
+        ModelProviderInfo {
+            name: "anthropic".to_string(),
+            base_url: Some(base_url),
+            env_key: None,
+            env_key_instructions: None,
+            wire_api: WireApi::AnthropicMessages,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: Some(0),
+            stream_max_retries: Some(0),
+            stream_idle_timeout_ms: Some(5_000),
+            requires_openai_auth: false,
+            openrouter: None,
+        }
+    }
+
+    #[test]
+    fn history_becomes_alternating_content_blocks() {
+        let input = vec![
+            user("list files"),
+            ResponseItem::Reasoning {
+                id: "thinking_msg_1_0".to_string(),
+                summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: "Use ls.".to_string(),
+                }],
+                content: None,
+                encrypted_content: Some("sig".to_string()),
+            },
+            ResponseItem::Reasoning {
+                id: "rs_openai".to_string(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some("opaque".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "denied".to_string(),
+                    success: Some(false),
+                },
+            },
+            user("why?"),
+        ];
+
+        let messages = build_messages(&input);
+
+        assert_eq!(
+            messages,
+            vec![
+                json!({"role": "user", "content": [{"type": "text", "text": "list files"}]}),
+                json!({"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "Use ls.", "signature": "sig"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {"command": ["ls"]}},
+                ]}),
+                json!({"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "denied", "is_error": true},
+                    {"type": "text", "text": "why?"},
+                ]}),
+            ]
+        );
+        assert!(can_enable_thinking(&messages));
+    }
+
+    #[test]
+    fn thinking_is_disabled_for_tool_turns_without_thinking() {
+        let messages = build_messages(&[
+            user("hi"),
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "call_1".to_string(),
+            },
+        ]);
+        assert!(!can_enable_thinking(&messages));
+
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::High, 32_000),
+            Some(16_000)
+        );
+        assert_eq!(thinking_budget(ReasoningEffortConfig::Low, 1_500), None);
+        assert_eq!(
+            thinking_budget(ReasoningEffortConfig::Minimal, 32_000),
+            None
+        );
+    }
+
+    #[test]
+    fn freeform_tools_round_trip_as_single_string_tool_use() {
+        let tools = create_tools_json_for_anthropic_messages_api(&[
+            crate::tool_apply_patch::create_apply_patch_freeform_tool(),
+        ])
+        .expect("tools");
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "apply_patch");
+        assert_eq!(tools[0]["input_schema"]["required"], json!(["input"]));
+        assert_eq!(
+            tools[0]["input_schema"]["properties"]["input"]["type"],
+            "string"
+        );
+
+        let patch = "*** Begin Patch\n*** End Patch";
+        let freeform_tools = HashSet::from(["apply_patch".to_string()]);
+        let block = Block::ToolUse {
+            call_id: "toolu_2".to_string(),
+            name: "apply_patch".to_string(),
+            input_json: json!({ "input": patch }).to_string(),
+        };
+        let call = block
+            .into_item("msg_1_0".to_string(), &freeform_tools)
+            .expect("item");
+        assert_eq!(
+            call,
+            ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "toolu_2".to_string(),
+                name: "apply_patch".to_string(),
+                input: patch.to_string(),
+            }
+        );
+
+        let messages = build_messages(&[
+            call,
+            ResponseItem::CustomToolCallOutput {
+                call_id: "toolu_2".to_string(),
+                output: "Done!".to_string(),
+            },
+        ]);
+        assert_eq!(
+            messages,
+            vec![
+                json!({"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "toolu_2", "name": "apply_patch", "input": {"input": patch}},
+                ]}),
+                json!({"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_2", "content": "Done!"},
+                ]}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_stream_fails_with_a_retryable_stream_error() {
+        let (tx_event, mut rx_event) = mpsc::channel(4);
+        process_anthropic_sse(
+            futures::stream::pending::<Result<Bytes>>(),
+            tx_event,
+            HashSet::new(),
+            Duration::from_millis(20),
+            Arc::new(Mutex::new(DebugLogger::new(false).expect("logger"))),
+            String::new(),
+            None,
+        )
+        .await;
+
+        match rx_event.recv().await {
+            Some(Err(CodexErr::Stream(message, None, _))) => {
+                assert!(message.starts_with("[idle]"), "{message}");
+            }
+            other => panic!("expected an idle stream error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_thinking_text_and_tool_use_from_mock_server() {
+        let events = [
+            json!({"type": "message_start", "message": {"id": "msg_1", "usage": {"input_tokens": 10, "cache_read_input_tokens": 90, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Look first."}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "ping"}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Checking."}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"command\":"}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "[\"ls\"]}"}}),
+            json!({"type": "content_block_stop", "index": 2}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 25}}),
+            json!({"type": "message_stop"}),
+        ];
+        let body: String = events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {event}\n\n",
+                    event["type"].as_str().unwrap()
+                )
+            })
+            .collect();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header(ANTHROPIC_VERSION_HEADER, ANTHROPIC_VERSION))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_raw(body, "text/event-stream"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let prompt = Prompt {
+            input: vec![user("list files")],
+            ..Default::default()
+        };
+        let debug_logger = Arc::new(Mutex::new(DebugLogger::new(false).unwrap()));
+        let mut stream = stream_anthropic_messages(
+            &prompt,
+            &derive_default_model_family("claude-sonnet-4-5"),
+            "claude-sonnet-4-5",
+            ReasoningEffortConfig::Medium,
+            None,
+            &reqwest::Client::new(),
+            &provider(format!("{}/v1", server.uri())),
+            &debug_logger,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("stream");
+
+        let mut items = Vec::new();
+        let mut deltas = Vec::new();
+        let mut completed = None;
+        while let Some(event) = stream.rx_event.recv().await {
+            match event.expect("event") {
+                ResponseEvent::OutputItemDone { item, .. } => items.push(item),
+                ResponseEvent::OutputTextDelta { delta, .. }
+                | ResponseEvent::ReasoningSummaryDelta { delta, .. } => deltas.push(delta),
+                ResponseEvent::Completed {
+                    response_id,
+                    token_usage,
+                } => completed = Some((response_id, token_usage)),
+                _ => {}
+            }
+        }
+
+        assert_eq!(deltas, vec!["Look first.", "Checking."]);
+        assert_eq!(
+            items,
+            vec![
+                ResponseItem::Reasoning {
+                    id: "thinking_msg_1_0".to_string(),
+                    summary: vec![ReasoningItemReasoningSummary::SummaryText {
+                        text: "Look first.".to_string(),
+                    }],
+                    content: None,
+                    encrypted_content: Some("sig".to_string()),
+                },
+                ResponseItem::Message {
+                    id: Some("msg_1_1".to_string()),
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: "Checking.".to_string(),
+                    }],
+                },
+                ResponseItem::FunctionCall {
+                    id: None,
+                    name: "shell".to_string(),
+                    arguments: r#"{"command":["ls"]}"#.to_string(),
+                    call_id: "toolu_1".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            completed,
+            Some((
+                "msg_1".to_string(),
+                Some(TokenUsage {
+                    input_tokens: 100,
+                    cached_input_tokens: 90,
+                    output_tokens: 25,
+                    reasoning_output_tokens: 0,
+                    total_tokens: 125,
+                })
+            ))
+        );
+    }
+}
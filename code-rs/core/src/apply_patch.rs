@@ -1,13 +1,13 @@
 use crate::acp::AcpFileSystem;
 use crate::codex::Session;
 use crate::patch_harness::run_patch_harness;
+use crate::protocol::AskForApproval;
 use crate::protocol::FileChange;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::WritableRoot;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
-use anyhow::Context as _;
 use anyhow::Result;
 use code_apply_patch::AffectedPaths;
 use code_apply_patch::ApplyPatchAction;
@@ -44,6 +44,7 @@ pub(crate) async fn apply_patch(
     attempt_req: u64,
     output_index: Option<u32>,
     action: ApplyPatchAction,
+    hook_approval_reason: Option<String>,
 ) -> ApplyPatchResult {
     let (harness_summary_json, harness_status_message) = {
         let mut summary_json: Option<String> = None;
@@ -135,12 +136,26 @@ pub(crate) async fn apply_patch(
             .await;
     }
 
-    let auto_approved = match assess_patch_safety(
+    let safety = assess_patch_safety(
         &action,
         sess.get_approval_policy(),
         sess.get_sandbox_policy(),
         sess.get_cwd(),
-    ) {
+    );
+    // A `file.before_write` hook can demand an explicit approval even when
+    // the patch would otherwise be applied without asking.
+    let safety = match (hook_approval_reason.as_ref(), safety) {
+        (Some(_), SafetyCheck::AutoApprove { .. } | SafetyCheck::AskUser)
+            if matches!(sess.get_approval_policy(), AskForApproval::Never) =>
+        {
+            SafetyCheck::Reject {
+                reason: "a project hook requires approval but approval policy is never".to_string(),
+            }
+        }
+        (Some(_), SafetyCheck::AutoApprove { .. }) => SafetyCheck::AskUser,
+        (_, other) => other,
+    };
+    let auto_approved = match safety {
         SafetyCheck::AutoApprove { .. } => {
             if let Err(reason) = check_patch_paths_within_writable_roots(
                 &action,
@@ -174,7 +189,13 @@ pub(crate) async fn apply_patch(
         }
         SafetyCheck::AskUser => {
             let rx = sess
-                .request_patch_approval(sub_id.to_owned(), call_id.to_owned(), &action, None, None)
+                .request_patch_approval(
+                    sub_id.to_owned(),
+                    call_id.to_owned(),
+                    &action,
+                    hook_approval_reason,
+                    None,
+                )
                 .await;
             match rx.await.unwrap_or_default() {
                 ReviewDecision::Approved | ReviewDecision::ApprovedForSession => false,
@@ -230,11 +251,13 @@ pub(crate) fn convert_apply_patch_to_protocol(
     action: &ApplyPatchAction,
 ) -> HashMap<PathBuf, FileChange> {
     let changes = action.changes();
+    let modes = action.modes();
     let mut result = HashMap::with_capacity(changes.len());
     for (path, change) in changes {
         let protocol_change = match change {
             ApplyPatchFileChange::Add { content } => FileChange::Add {
                 content: content.clone(),
+                mode: modes.get(path).copied(),
             },
             ApplyPatchFileChange::Delete { content: _ } => FileChange::Delete,
             ApplyPatchFileChange::Update {
@@ -243,11 +266,13 @@ pub(crate) fn convert_apply_patch_to_protocol(
                 new_content,
             } => {
                 let original_content = std::fs::read_to_string(path).unwrap_or_default();
+                let mode = modes.get(move_path.as_ref().unwrap_or(path)).copied();
                 FileChange::Update {
                     unified_diff: unified_diff.clone(),
                     move_path: move_path.clone(),
                     original_content,
                     new_content: new_content.clone(),
+                    mode,
                 }
             }
         };
@@ -327,7 +352,7 @@ fn writable_roots_for_patch(
         SandboxPolicy::WorkspaceWrite { .. } => {
             Some(sandbox_policy.get_writable_roots_with_cwd(cwd))
         }
-        SandboxPolicy::ReadOnly => Some(vec![WritableRoot {
+        SandboxPolicy::ReadOnly { .. } => Some(vec![WritableRoot {
             root: cwd.to_path_buf(),
             read_only_subpaths: Vec::new(),
         }]),
@@ -422,69 +447,13 @@ async fn apply_changes_from_apply_patch_and_report(
     Ok(())
 }
 
+/// Apply `action` through `fs` all-or-nothing: if any file cannot be
+/// written, the files already changed are restored.
 async fn apply_changes_from_apply_patch(
     action: &ApplyPatchAction,
     fs: &impl FileSystem,
 ) -> Result<AffectedPaths> {
-    let mut added: Vec<PathBuf> = Vec::new();
-    let mut modified: Vec<PathBuf> = Vec::new();
-    let mut deleted: Vec<PathBuf> = Vec::new();
-
-    for (path, change) in action.changes() {
-        match change {
-            ApplyPatchFileChange::Add { content } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
-                fs.write_text_file(path, content.clone())
-                    .await
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
-                added.push(path.clone());
-            }
-            ApplyPatchFileChange::Delete { content: _ } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
-                deleted.push(path.clone());
-            }
-            ApplyPatchFileChange::Update {
-                move_path,
-                new_content,
-                ..
-            } => {
-                if let Some(move_path) = move_path {
-                    if let Some(parent) = move_path.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!(
-                                "Failed to create parent directories for {}",
-                                move_path.display()
-                            )
-                        })?;
-                    }
-
-                    std::fs::rename(path, move_path)
-                        .with_context(|| format!("Failed to rename file {}", path.display()))?;
-                    fs.write_text_file(move_path, new_content.clone()).await?;
-                    modified.push(move_path.clone());
-                    deleted.push(path.clone());
-                } else {
-                    fs.write_text_file(path, new_content.clone()).await?;
-                    modified.push(path.clone());
-                }
-            }
-        }
-    }
-
-    Ok(AffectedPaths {
-        added,
-        modified,
-        deleted,
-    })
+    Ok(fs.apply_action(action).await?)
 }
 
 #[cfg(test)]
@@ -501,6 +470,8 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
@@ -522,6 +493,8 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
@@ -550,6 +523,8 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
@@ -561,4 +536,69 @@ mod tests {
             "expected symlink escape to be rejected: {result:?}"
         );
     }
+
+    /// Writes through to disk but refuses one path, like a client that
+    /// rejects a write halfway through a patch.
+    struct RefusingFileSystem {
+        refused: PathBuf,
+    }
+
+    impl FileSystem for RefusingFileSystem {
+        async fn read_text_file(&self, path: &Path) -> std::io::Result<String> {
+            StdFileSystem.read_text_file(path).await
+        }
+
+        async fn write_text_file(&self, path: &Path, contents: String) -> std::io::Result<()> {
+            if path == self.refused {
+                return Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+            }
+            StdFileSystem.write_text_file(path, contents).await
+        }
+    }
+
+    #[tokio::test]
+    async fn refused_write_restores_files_written_before_it() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path();
+        std::fs::write(cwd.join("a.txt"), "one\n").unwrap();
+        std::fs::write(cwd.join("b.txt"), "gone\n").unwrap();
+        std::fs::write(cwd.join("d.txt"), "four\n").unwrap();
+        let patch = r#"*** Begin Patch
+*** Update File: a.txt
+@@
+-one
++uno
+*** Delete File: b.txt
+*** Add File: c/new.txt
++three
+*** Update File: d.txt
+@@
+-four
++cuatro
+*** End Patch"#;
+        let argv = vec!["apply_patch".to_string(), patch.to_string()];
+        let code_apply_patch::MaybeApplyPatchVerified::Body(action) =
+            code_apply_patch::maybe_parse_apply_patch_verified(&argv, cwd)
+        else {
+            panic!("expected a patch");
+        };
+        let fs = RefusingFileSystem {
+            refused: action.cwd.join("d.txt"),
+        };
+
+        let result = apply_changes_from_apply_patch(&action, &fs).await;
+
+        assert!(result.is_err(), "expected the refused write to fail");
+        assert_eq!(std::fs::read_to_string(cwd.join("a.txt")).unwrap(), "one\n");
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("b.txt")).unwrap(),
+            "gone\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("d.txt")).unwrap(),
+            "four\n"
+        );
+        assert!(!cwd.join("c").exists());
+        assert_eq!(std::fs::read_dir(cwd).unwrap().count(), 3);
+    }
 }
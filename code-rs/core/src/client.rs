@@ -34,6 +34,7 @@ const AUTH_REQUIRED_MESSAGE: &str = "Authentication required. Run `code login` t
 
 use crate::agent_defaults::default_agent_configs;
 use crate::agent_defaults::enabled_agent_model_specs;
+use crate::anthropic_messages::stream_anthropic_messages;
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
 use crate::client_common::Prompt;
@@ -55,6 +56,7 @@ use crate::error::RetryLimitReachedError;
 use crate::error::UnexpectedResponseError;
 use crate::error::UsageLimitReachedError;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
+use crate::gemini::stream_gemini;
 use crate::model_family::ModelFamily;
 use crate::model_family::find_family_for_model;
 use crate::model_provider_info::ModelProviderInfo;
@@ -367,13 +369,12 @@ impl ModelClient {
         );
 
         tools_config.shell_type = match sandbox_policy {
-            SandboxPolicy::ReadOnly => {
+            // Keep the session policy as is so `deny_read` carries over.
+            sp @ SandboxPolicy::ReadOnly { .. } => {
                 if base_uses_native_shell {
                     base_shell_type
                 } else {
-                    ConfigShellToolType::ShellWithRequest {
-                        sandbox_policy: SandboxPolicy::ReadOnly,
-                    }
+                    ConfigShellToolType::ShellWithRequest { sandbox_policy: sp }
                 }
             }
             sp @ SandboxPolicy::WorkspaceWrite { .. } => {
@@ -399,6 +400,11 @@ impl ModelClient {
         })
     }
 
+    /// The `remote_compaction` setting; `None` means "decide from auth mode".
+    pub fn get_remote_compaction(&self) -> Option<bool> {
+        self.config.remote_compaction
+    }
+
     pub fn default_model_slug(&self) -> &str {
         self.config.model.as_str()
     }
@@ -407,13 +413,64 @@ impl ModelClient {
         &self.config.model_family
     }
 
-    /// Dispatches to either the Responses or Chat implementation depending on
-    /// the provider config.  Public callers always invoke `stream()` – the
-    /// specialised helpers are private to avoid accidental misuse.
+    /// Dispatches to the Responses, Chat, Anthropic Messages or Gemini
+    /// implementation depending on the provider config.  Public callers always invoke
+    /// `stream()` – the specialised helpers are private to avoid accidental
+    /// misuse.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
         let log_tag = prompt.log_tag.as_deref();
         match self.provider.wire_api {
             WireApi::Responses => self.stream_responses(prompt, log_tag).await,
+            WireApi::AnthropicMessages => {
+                let effective_family = prompt
+                    .model_family_override
+                    .as_ref()
+                    .unwrap_or(&self.config.model_family);
+                let model_slug = prompt
+                    .model_override
+                    .as_deref()
+                    .unwrap_or(self.config.model.as_str());
+                // The Messages API streams complete content blocks, so no
+                // aggregation adapter is needed.
+                stream_anthropic_messages(
+                    prompt,
+                    effective_family,
+                    model_slug,
+                    self.effort,
+                    self.config.model_max_output_tokens,
+                    &self.client,
+                    &self.provider,
+                    &self.debug_logger,
+                    self.auth_manager.clone(),
+                    self.otel_event_manager.clone(),
+                    log_tag,
+                )
+                .await
+            }
+            WireApi::Gemini => {
+                let effective_family = prompt
+                    .model_family_override
+                    .as_ref()
+                    .unwrap_or(&self.config.model_family);
+                let model_slug = prompt
+                    .model_override
+                    .as_deref()
+                    .unwrap_or(self.config.model.as_str());
+                stream_gemini(
+                    prompt,
+                    effective_family,
+                    model_slug,
+                    self.effort,
+                    self.config.model_max_output_tokens,
+                    &self.client,
+                    &self.provider,
+                    &self.debug_logger,
+                    self.auth_manager.clone(),
+                    self.otel_event_manager.clone(),
+                    log_tag,
+                )
+                .await
+            }
             WireApi::Chat => {
                 let effective_family = prompt
                     .model_family_override
@@ -1811,6 +1868,7 @@ mod tests {
     use chrono::Duration as ChronoDuration;
     use chrono::TimeZone;
     use chrono::Utc;
+    use code_protocol::models::ContentItem;
     use serde_json::json;
     use std::collections::HashMap;
     use tokio::sync::mpsc;
@@ -2572,4 +2630,120 @@ mod tests {
             other => panic!("unexpected quota event: {other:?}"),
         }
     }
+
+    fn compact_test_client(base_url: String, code_home: &std::path::Path) -> ModelClient {
+        let config = Config::load_from_base_config_with_overrides(
+            crate::config::ConfigToml::default(),
+            crate::config::ConfigOverrides::default(),
+            code_home.to_path_buf(),
+        )
+        .expect("config");
+        let provider = ModelProviderInfo {
+            name: "fake".to_string(),
+            base_url: Some(base_url),
+            env_key: None,
+            env_key_instructions: None,
+            wire_api: WireApi::Responses,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: Some(0),
+            stream_max_retries: Some(0),
+            stream_idle_timeout_ms: Some(1000),
+            requires_openai_auth: false,
+            openrouter: None,
+        };
+        ModelClient::new(
+            Arc::new(config),
+            None,
+            None,
+            provider,
+            ReasoningEffortConfig::default(),
+            ReasoningSummaryConfig::default(),
+            TextVerbosityConfig::default(),
+            Uuid::new_v4(),
+            Arc::new(Mutex::new(DebugLogger::new(false).expect("debug logger"))),
+        )
+    }
+
+    fn user_message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn compact_conversation_history_posts_to_compact_endpoint() {
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+        use wiremock::matchers::body_partial_json;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+
+        let server = MockServer::start().await;
+        let summary = ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: "summary".to_string(),
+            }],
+        };
+        Mock::given(method("POST"))
+            .and(path("/v1/responses/compact"))
+            .and(body_partial_json(json!({
+                "input": [{"type": "message", "role": "user", "content": [{"type": "input_text", "text": "hello"}]}],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "output": [summary] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let code_home = tempfile::TempDir::new().expect("tempdir");
+        let client = compact_test_client(format!("{}/v1", server.uri()), code_home.path());
+        let mut prompt = Prompt::default();
+        prompt.input = vec![user_message("hello")];
+
+        let output = client
+            .compact_conversation_history(&prompt)
+            .await
+            .expect("compact succeeds");
+        assert_eq!(
+            serde_json::to_value(&output).expect("serialize"),
+            serde_json::to_value(vec![summary]).expect("serialize")
+        );
+    }
+
+    #[tokio::test]
+    async fn compact_conversation_history_surfaces_error_status() {
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/responses/compact"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+            .mount(&server)
+            .await;
+
+        let code_home = tempfile::TempDir::new().expect("tempdir");
+        let client = compact_test_client(format!("{}/v1", server.uri()), code_home.path());
+        let mut prompt = Prompt::default();
+        prompt.input = vec![user_message("hello")];
+
+        match client.compact_conversation_history(&prompt).await {
+            Err(CodexErr::UnexpectedStatus(err)) => {
+                assert_eq!(err.status, StatusCode::NOT_FOUND);
+                assert_eq!(err.body, "not found");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
 }
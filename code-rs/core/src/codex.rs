@@ -85,6 +85,8 @@ use uuid::Uuid;
 
 pub mod compact;
 pub mod compact_remote;
+mod mcp_client_requests;
+mod mcp_server_events;
 use self::compact::build_compacted_history;
 use self::compact::collect_compaction_snippets;
 use self::compact_remote::run_inline_remote_auto_compact_task;
@@ -94,6 +96,8 @@ pub(crate) const INITIAL_SUBMIT_ID: &str = "";
 const HOOK_OUTPUT_LIMIT: usize = 2048;
 const PENDING_ONLY_SENTINEL: &str = "__code_pending_only__";
 const MIN_SHELL_TIMEOUT_MS: u64 = 30 * 60 * 1000;
+/// Requests from MCP servers queued before the session starts answering them.
+const MCP_CLIENT_REQUEST_BUFFER: usize = 32;
 
 #[derive(Clone, Default)]
 struct ConfirmGuardRuntime {
@@ -181,16 +185,20 @@ fn to_proto_approval_policy(policy: AskForApproval) -> ProtoAskForApproval {
 fn to_proto_sandbox_policy(policy: SandboxPolicy) -> ProtoSandboxPolicy {
     match policy {
         SandboxPolicy::DangerFullAccess => ProtoSandboxPolicy::DangerFullAccess,
-        SandboxPolicy::ReadOnly => ProtoSandboxPolicy::ReadOnly,
+        SandboxPolicy::ReadOnly { deny_read } => ProtoSandboxPolicy::ReadOnly { deny_read },
         SandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
+            network_allowlist,
+            deny_read,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
             allow_git_writes,
         } => ProtoSandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
+            network_allowlist,
+            deny_read,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
             allow_git_writes,
@@ -319,6 +327,18 @@ fn response_input_from_core_items(items: Vec<InputItem>) -> ResponseInputItem {
                     }
                 }
             }
+            // Normally resolved by `mcp_input` before conversion; keep a marker
+            // if one slips through so the model still sees the reference.
+            InputItem::McpResource { server, uri } => {
+                content_items.push(ContentItem::InputText {
+                    text: format!("[MCP resource {server}:{uri}]"),
+                });
+            }
+            InputItem::McpPrompt { server, name, .. } => {
+                content_items.push(ContentItem::InputText {
+                    text: format!("[MCP prompt {server}:{name}]"),
+                });
+            }
         }
     }
 
@@ -844,6 +864,9 @@ use crate::config_types::ProjectHookEvent;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::UiLocale;
 use crate::context_timeline::ContextTimeline;
+use crate::context_timeline::StorageConfig;
+use crate::context_timeline::TimelineStorage;
+use crate::context_timeline::timeline_path_for_rollout;
 use crate::conversation_history::ConversationHistory;
 use crate::dry_run_guard::DryRunAnalysis;
 use crate::dry_run_guard::DryRunDisposition;
@@ -869,6 +892,8 @@ use crate::exec::StreamOutput;
 use crate::exec::process_exec_tool_call;
 use crate::exec_command::ExecSessionManager;
 use crate::exec_env::create_env;
+use crate::exec_limits::ExecResourceLimits;
+use crate::exec_policy::ExecPolicy;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::model_family::derive_default_model_family;
@@ -879,6 +904,7 @@ use crate::openai_tools::get_openai_tools;
 use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
 use crate::project_doc::get_user_instructions;
+use crate::project_features::HookVerdict;
 use crate::project_features::ProjectCommand;
 use crate::project_features::ProjectHook;
 use crate::project_features::ProjectHooks;
@@ -906,6 +932,7 @@ use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::FileChange;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
+use crate::protocol::ListMcpCatalogResponseEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
@@ -933,6 +960,7 @@ use crate::slash_commands::get_enabled_agents;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::user_instructions::UserInstructions;
 use crate::user_notification::UserNotification;
+use crate::user_notification::UserNotifier;
 use crate::util::backoff;
 use crate::util::wait_for_connectivity;
 use code_protocol::models::ContentItem;
@@ -1191,6 +1219,8 @@ struct State {
     approved_commands: HashSet<ApprovedCommandPattern>,
     current_task: Option<AgentTask>,
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
+    /// Elicitation forms sent to the UI on behalf of MCP servers, by request id.
+    pending_elicitations: HashMap<String, mcp_client_requests::PendingElicitation>,
     pending_input: Vec<ResponseInputItem>,
     pending_user_input: Vec<QueuedUserInput>,
     history: ConversationHistory,
@@ -1380,6 +1410,7 @@ pub(crate) struct Session {
     approval_policy: AskForApproval,
     sandbox_policy: SandboxPolicy,
     shell_environment_policy: ShellEnvironmentPolicy,
+    exec_limits: ExecResourceLimits,
     _writable_roots: Vec<PathBuf>,
     disable_response_storage: bool,
     tools_config: ToolsConfig,
@@ -1399,9 +1430,9 @@ pub(crate) struct Session {
     /// Preferred locale for system prompts and assistant replies.
     ui_locale: UiLocale,
 
-    /// External notifier command (will be passed as args to exec()). When
-    /// `None` this feature is disabled.
-    notify: Option<Vec<String>>,
+    /// Delivers end-user notifications to the `notify` command and the
+    /// configured notification sinks.
+    notifier: UserNotifier,
 
     /// Optional rollout recorder for persisting the conversation transcript so
     /// sessions can be replayed or inspected later.
@@ -1418,6 +1449,9 @@ pub(crate) struct Session {
     /// Track the last screenshot path and hash to detect changes
     last_screenshot_info: Mutex<Option<(PathBuf, Vec<u8>, Vec<u8>)>>, // (path, phash, dhash)
     confirm_guard: ConfirmGuardRuntime,
+    /// User (`$CODE_HOME/policy/*.policy`) and project (`.code/exec.policy`)
+    /// exec policies consulted before every shell call.
+    exec_policy: ExecPolicy,
     project_hooks: ProjectHooks,
     project_commands: Vec<ProjectCommand>,
     hook_guard: AtomicBool,
@@ -1428,6 +1462,8 @@ pub(crate) struct Session {
     next_turn_text_format: Mutex<Option<TextFormat>>,
     env_ctx_v2: bool,
     retention_config: crate::config_types::RetentionConfig,
+    /// Persists the context timeline next to the rollout (env_ctx_v2 only).
+    context_timeline_storage: Mutex<Option<TimelineStorage>>,
     model_descriptions: Option<String>,
 }
 
@@ -2173,12 +2209,17 @@ impl Session {
             &sub_id,
             EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
                 call_id: call_id.clone(),
-                command,
+                command: command.clone(),
                 cwd,
                 reason,
             }),
         );
         let _ = self.tx_event.send(event).await;
+        self.maybe_notify(UserNotification::ApprovalRequested {
+            turn_id: sub_id,
+            call_id: call_id.clone(),
+            summary: crate::util::strip_bash_lc_and_escape(&command),
+        });
         {
             let mut state = self.state.lock().unwrap();
             // Track pending approval by call_id (unique per request) rather than sub_id
@@ -2207,6 +2248,15 @@ impl Session {
             }),
         );
         let _ = self.tx_event.send(event).await;
+        let file_count = action.changes().len();
+        self.maybe_notify(UserNotification::ApprovalRequested {
+            turn_id: sub_id,
+            call_id: call_id.clone(),
+            summary: format!(
+                "apply patch to {file_count} file{}",
+                if file_count == 1 { "" } else { "s" }
+            ),
+        });
         {
             let mut state = self.state.lock().unwrap();
             // Track pending approval by call_id to avoid collisions.
@@ -2259,6 +2309,15 @@ impl Session {
 
             crate::telemetry::global_telemetry().record_retention(&retention_stats);
 
+            let mut storage = self.context_timeline_storage.lock().unwrap();
+            let pruned = match storage.as_mut() {
+                Some(storage) => storage.prune(&mut state.context_timeline, &policy),
+                None => Ok(state.context_timeline.prune(&policy)),
+            };
+            if let Err(err) = pruned {
+                warn!("env_ctx_v2: failed to prune persisted context timeline: {err}");
+            }
+
             let legacy_stats = CleanupStats {
                 removed_screenshots: retention_stats.removed_screenshots,
                 removed_status: retention_stats.removed_status,
@@ -2604,23 +2663,9 @@ impl Session {
             None
         };
 
-        if enable_hooks && let Some(params_ref) = params_for_hooks.as_ref() {
-            let before_event = if is_apply_patch {
-                ProjectHookEvent::FileBeforeWrite
-            } else {
-                ProjectHookEvent::ToolBefore
-            };
-            self.run_hooks_for_exec_event(
-                turn_diff_tracker,
-                before_event,
-                &begin_ctx,
-                params_ref,
-                None,
-                attempt_req,
-            )
-            .await;
-        }
-
+        // `tool.before` / `file.before_write` hooks already ran through
+        // `run_pre_exec_hooks` before the call was approved; only the after
+        // hooks are fired from here.
         self.on_exec_command_begin(
             turn_diff_tracker,
             begin_ctx.clone(),
@@ -2643,7 +2688,8 @@ impl Session {
         let output_stderr;
         let borrowed: &ExecToolCallOutput = match &result {
             Ok(output) => output,
-            Err(CodexErr::Sandbox(SandboxErr::Timeout { output })) => output,
+            Err(CodexErr::Sandbox(SandboxErr::Timeout { output }))
+            | Err(CodexErr::Sandbox(SandboxErr::ResourceLimit { output, .. })) => output,
             Err(e) => {
                 output_stderr = ExecToolCallOutput {
                     exit_code: -1,
@@ -2771,6 +2817,85 @@ impl Session {
         }
     }
 
+    /// Runs the blocking `tool.before` / `file.before_write` hooks for a
+    /// model-issued tool call and folds their JSON verdicts together.
+    ///
+    /// Hooks run in configuration order. A `deny` stops evaluation
+    /// immediately, a `rewrite` replaces the argv seen by later hooks, and a
+    /// `require_approval` forces the call through the approval prompt even
+    /// when the safety check would auto-approve it.
+    async fn run_pre_exec_hooks(
+        &self,
+        turn_diff_tracker: &mut TurnDiffTracker,
+        event: ProjectHookEvent,
+        exec_ctx: &ExecCommandContext,
+        params: &ExecParams,
+        attempt_req: u64,
+    ) -> PreExecHookOutcome {
+        let mut outcome = PreExecHookOutcome::default();
+        if self.project_hooks.is_empty() {
+            return outcome;
+        }
+        let hooks: Vec<ProjectHook> = self.project_hooks.hooks_for(event).cloned().collect();
+        if hooks.is_empty() {
+            return outcome;
+        }
+        let Some(_guard) = HookGuard::try_acquire(&self.hook_guard) else {
+            return outcome;
+        };
+        let mut current = params.clone();
+        for (idx, hook) in hooks.into_iter().enumerate() {
+            let payload = build_exec_hook_payload(event, exec_ctx, &current, None);
+            let Some(output) = self
+                .run_hook_command(
+                    turn_diff_tracker,
+                    &hook,
+                    event,
+                    &payload,
+                    Some(exec_ctx),
+                    attempt_req,
+                    idx,
+                )
+                .await
+            else {
+                continue;
+            };
+            match HookVerdict::parse(&output.stdout.text) {
+                None | Some(HookVerdict::Allow) => {}
+                Some(HookVerdict::Deny { reason }) => {
+                    outcome.denied = Some(reason.unwrap_or_else(|| {
+                        format!("blocked by project hook `{}`", hook_label(&hook))
+                    }));
+                    return outcome;
+                }
+                Some(HookVerdict::Rewrite { argv }) => {
+                    if exec_ctx.apply_patch.is_some() {
+                        let order = self.next_background_order(&exec_ctx.sub_id, attempt_req, None);
+                        self.notify_background_event_with_order(
+                            &exec_ctx.sub_id,
+                            order,
+                            format!(
+                                "Hook `{}` returned a rewrite for a patch; ignoring it.",
+                                hook_label(&hook)
+                            ),
+                        )
+                        .await;
+                        continue;
+                    }
+                    current.command = argv.clone();
+                    outcome.command = Some(argv);
+                }
+                Some(HookVerdict::RequireApproval { reason }) => {
+                    outcome.require_approval = true;
+                    if outcome.approval_reason.is_none() {
+                        outcome.approval_reason = reason;
+                    }
+                }
+            }
+        }
+        outcome
+    }
+
     async fn run_session_hooks(&self, event: ProjectHookEvent) {
         if self.project_hooks.is_empty() {
             return;
@@ -2818,7 +2943,7 @@ impl Session {
         base_ctx: Option<&ExecCommandContext>,
         attempt_req: u64,
         index: usize,
-    ) {
+    ) -> Option<ExecToolCallOutput> {
         let sub_id = base_ctx
             .map(|ctx| ctx.sub_id.clone())
             .unwrap_or_else(|| INITIAL_SUBMIT_ID.to_string());
@@ -2854,6 +2979,7 @@ impl Session {
             env,
             with_escalated_permissions: Some(false),
             justification: None,
+            resource_limits: self.exec_limits,
         };
 
         let exec_ctx = ExecCommandContext {
@@ -2874,7 +3000,7 @@ impl Session {
             stdout_stream: None,
         };
 
-        if let Err(err) = Box::pin(self.run_exec_with_events_inner(
+        match Box::pin(self.run_exec_with_events_inner(
             turn_diff_tracker,
             exec_ctx,
             exec_args,
@@ -2885,21 +3011,21 @@ impl Session {
         ))
         .await
         {
-            let hook_label = hook
-                .name
-                .as_deref()
-                .unwrap_or_else(|| hook.command.first().map(String::as_str).unwrap_or("hook"));
-            let order = self.next_background_order(&sub_id, attempt_req, None);
-            self.notify_background_event_with_order(
-                &sub_id,
-                order,
-                format!(
-                    "Hook `{}` failed: {}",
-                    hook_label,
-                    get_error_message_ui(&err)
-                ),
-            )
-            .await;
+            Ok(output) => Some(output),
+            Err(err) => {
+                let order = self.next_background_order(&sub_id, attempt_req, None);
+                self.notify_background_event_with_order(
+                    &sub_id,
+                    order,
+                    format!(
+                        "Hook `{}` failed: {}",
+                        hook_label(hook),
+                        get_error_message_ui(&err)
+                    ),
+                )
+                .await;
+                None
+            }
         }
     }
 
@@ -2945,6 +3071,7 @@ impl Session {
             env,
             with_escalated_permissions: Some(false),
             justification: None,
+            resource_limits: self.exec_limits,
         };
 
         let call_id = format!("project_cmd_{}", sanitize_identifier(&command.name));
@@ -2956,6 +3083,33 @@ impl Session {
             apply_patch: None,
         };
 
+        let mut exec_params = exec_params;
+        let hook_outcome = self
+            .run_pre_exec_hooks(
+                turn_diff_tracker,
+                ProjectHookEvent::ToolBefore,
+                &exec_ctx,
+                &exec_params,
+                attempt_req,
+            )
+            .await;
+        if let Some(reason) = hook_outcome.denied {
+            let order = self.next_background_order(sub_id, attempt_req, None);
+            self.notify_background_event_with_order(
+                sub_id,
+                order,
+                format!(
+                    "Project command `{}` rejected by project hook: {reason}",
+                    command.name
+                ),
+            )
+            .await;
+            return;
+        }
+        if let Some(argv) = hook_outcome.command {
+            exec_params.command = argv;
+        }
+
         let sandbox_type = self.resolve_internal_sandbox(false);
         let exec_args = ExecInvokeArgs {
             params: exec_params,
@@ -3293,6 +3447,7 @@ impl Session {
                             }
                         }
                     }
+                    self.persist_context_timeline(&state.context_timeline);
 
                     Ok(Some((emission, items)))
                 }
@@ -3344,6 +3499,42 @@ impl Session {
         Some(items)
     }
 
+    /// Appends new timeline entries to the on-disk timeline, if enabled.
+    fn persist_context_timeline(&self, timeline: &ContextTimeline) {
+        let mut storage = self.context_timeline_storage.lock().unwrap();
+        if let Some(storage) = storage.as_mut()
+            && storage.config().auto_persist
+            && let Err(err) = storage.save(timeline)
+        {
+            warn!("env_ctx_v2: failed to persist context timeline: {err}");
+        }
+    }
+
+    /// Loads the persisted timeline for a resumed session. Returns `None`
+    /// when there is nothing usable on disk.
+    fn load_persisted_context_timeline(&self) -> Option<ContextTimeline> {
+        let mut storage = self.context_timeline_storage.lock().unwrap();
+        let storage = storage.as_mut()?;
+        match storage.load() {
+            Ok(timeline) if !timeline.is_empty() => Some(timeline),
+            Ok(_) => None,
+            Err(err) => {
+                warn!("env_ctx_v2: ignoring persisted context timeline: {err}");
+                None
+            }
+        }
+    }
+
+    /// Replaces the on-disk timeline with `timeline`.
+    fn rewrite_persisted_context_timeline(&self, timeline: &ContextTimeline) {
+        let mut storage = self.context_timeline_storage.lock().unwrap();
+        if let Some(storage) = storage.as_mut()
+            && let Err(err) = storage.rewrite(timeline)
+        {
+            warn!("env_ctx_v2: failed to rewrite context timeline: {err}");
+        }
+    }
+
     /// Assemble environment context items from the timeline for prompt input.
     fn assemble_from_timeline(&self) -> Option<Vec<ResponseItem>> {
         if !self.env_ctx_v2 {
@@ -3454,12 +3645,17 @@ impl Session {
                     process_rollout_env_item(&mut replay_ctx, response_item);
                 }
                 RolloutItem::Compacted(compacted) => {
-                    let snippets = collect_compaction_snippets(&history);
-                    history = build_compacted_history(
-                        self.build_initial_context(turn_context),
-                        &snippets,
-                        &compacted.message,
-                    );
+                    history = match &compacted.replacement_history {
+                        Some(replacement) => replacement.clone(),
+                        None => {
+                            let snippets = collect_compaction_snippets(&history);
+                            build_compacted_history(
+                                self.build_initial_context(turn_context),
+                                &snippets,
+                                &compacted.message,
+                            )
+                        }
+                    };
                 }
                 RolloutItem::Event(recorded_event) => {
                     if let code_protocol::protocol::EventMsg::UserMessage(user_msg_event) =
@@ -3496,6 +3692,16 @@ impl Session {
             replay_ctx.last_snapshot = Some(snapshot);
         }
 
+        // The persisted timeline survives compaction, which drops the
+        // environment items from the rollout, so prefer it over the replay.
+        if let Some(timeline) = self.load_persisted_context_timeline() {
+            replay_ctx.last_snapshot = timeline.latest_snapshot();
+            replay_ctx.next_sequence = timeline.next_sequence();
+            replay_ctx.timeline = timeline;
+        } else if !replay_ctx.timeline.is_empty() {
+            self.rewrite_persisted_context_timeline(&replay_ctx.timeline);
+        }
+
         let restored_snapshot = replay_ctx.last_snapshot.clone();
         let next_seq_value = replay_ctx.next_sequence;
         {
@@ -3632,33 +3838,11 @@ impl Session {
         // KillOnDrop in exec.rs.
     }
 
-    /// Spawn the configured notifier (if any) with the given JSON payload as
-    /// the last argument. Failures are logged but otherwise ignored so that
-    /// notification issues do not interfere with the main workflow.
+    /// Send the notification to the configured notifier and sinks. Failures
+    /// are logged but otherwise ignored so that notification issues do not
+    /// interfere with the main workflow.
     fn maybe_notify(&self, notification: UserNotification) {
-        let Some(notify_command) = &self.notify else {
-            return;
-        };
-
-        if notify_command.is_empty() {
-            return;
-        }
-
-        let Ok(json) = serde_json::to_string(&notification) else {
-            error!("failed to serialise notification payload");
-            return;
-        };
-
-        let mut command = std::process::Command::new(&notify_command[0]);
-        if notify_command.len() > 1 {
-            command.args(&notify_command[1..]);
-        }
-        command.arg(json);
-
-        // Fire-and-forget – we do not wait for completion.
-        if let Err(e) = command.spawn() {
-            warn!("failed to spawn notifier '{}': {e}", notify_command[0]);
-        }
+        self.notifier.notify(&notification);
     }
 }
 
@@ -4007,6 +4191,23 @@ fn sanitize_identifier(value: &str) -> String {
     slug
 }
 
+fn hook_label(hook: &ProjectHook) -> &str {
+    hook.name
+        .as_deref()
+        .unwrap_or_else(|| hook.command.first().map(String::as_str).unwrap_or("hook"))
+}
+
+/// Combined result of the blocking pre-execution hooks for one tool call.
+#[derive(Debug, Default)]
+struct PreExecHookOutcome {
+    /// Set when a hook denied the call; carries the reason shown to the model.
+    denied: Option<String>,
+    /// Replacement argv requested by a `rewrite` verdict.
+    command: Option<Vec<String>>,
+    require_approval: bool,
+    approval_reason: Option<String>,
+}
+
 fn truncate_payload(text: &str, limit: usize) -> String {
     let mut iter = text.chars();
     let truncated: String = iter.by_ref().take(limit).collect();
@@ -4587,21 +4788,28 @@ async fn submission_loop(
                     drop(old_session_arc);
                 }
 
-                let (mcp_connection_manager, failed_clients) = match McpConnectionManager::new(
-                    config.mcp_servers.clone(),
-                    config.use_experimental_use_rmcp_client,
-                    excluded_tools,
-                )
-                .await
-                {
-                    Ok((mgr, failures)) => (mgr, failures),
-                    Err(e) => {
-                        let message = format!("Failed to create MCP connection manager: {e:#}");
-                        error!("{message}");
-                        mcp_connection_errors.push(message);
-                        (McpConnectionManager::default(), Default::default())
-                    }
-                };
+                let (mcp_client_request_tx, mcp_client_request_rx) =
+                    tokio::sync::mpsc::channel(MCP_CLIENT_REQUEST_BUFFER);
+                let (mcp_server_event_tx, mcp_server_event_rx) =
+                    tokio::sync::mpsc::unbounded_channel();
+                let (mcp_connection_manager, failed_clients) =
+                    match McpConnectionManager::new_with_client_requests(
+                        config.mcp_servers.clone(),
+                        config.use_experimental_use_rmcp_client,
+                        excluded_tools,
+                        Some(mcp_client_request_tx),
+                        Some(mcp_server_event_tx),
+                    )
+                    .await
+                    {
+                        Ok((mgr, failures)) => (mgr, failures),
+                        Err(e) => {
+                            let message = format!("Failed to create MCP connection manager: {e:#}");
+                            error!("{message}");
+                            mcp_connection_errors.push(message);
+                            (McpConnectionManager::default(), Default::default())
+                        }
+                    };
 
                 // Surface individual client start-up failures to the user.
                 if !failed_clients.is_empty() {
@@ -4646,6 +4854,19 @@ async fn submission_loop(
                 tools_config.set_agent_models(agent_models);
 
                 let model_descriptions = model_guide_markdown_with_custom(&config.agents);
+                let context_timeline_storage = if config.env_ctx_v2 {
+                    rollout_recorder.as_ref().map(|recorder| {
+                        TimelineStorage::new(StorageConfig::with_path(timeline_path_for_rollout(
+                            &recorder.rollout_path,
+                        )))
+                    })
+                } else {
+                    None
+                };
+                let exec_policy = ExecPolicy::load(&config.code_home, &cwd, config.project_trusted);
+                if !exec_policy.is_empty() {
+                    info!("Loaded exec policies: {:?}", exec_policy);
+                }
                 let mut new_session = Arc::new(Session {
                     id: session_id,
                     client,
@@ -4657,6 +4878,7 @@ async fn submission_loop(
                     approval_policy,
                     sandbox_policy,
                     shell_environment_policy: config.shell_environment_policy.clone(),
+                    exec_limits: config.exec_limits,
                     cwd,
                     _writable_roots: writable_roots,
                     mcp_connection_manager,
@@ -4665,7 +4887,7 @@ async fn submission_loop(
                     agents: config.agents.clone(),
                     model_reasoning_effort: config.model_reasoning_effort,
                     ui_locale: config.ui_locale.clone(),
-                    notify,
+                    notifier: UserNotifier::new(notify, config.notification_sinks.clone()),
                     state: Mutex::new(state),
                     rollout: Mutex::new(rollout_recorder),
                     code_linux_sandbox_exe: config.code_linux_sandbox_exe.clone(),
@@ -4676,6 +4898,7 @@ async fn submission_loop(
                     last_system_status: Mutex::new(None),
                     last_screenshot_info: Mutex::new(None),
                     confirm_guard: ConfirmGuardRuntime::from_config(&config.confirm_guard),
+                    exec_policy,
                     project_hooks: config.project_hooks.clone(),
                     project_commands: config.project_commands.clone(),
                     hook_guard: AtomicBool::new(false),
@@ -4686,12 +4909,21 @@ async fn submission_loop(
                     next_turn_text_format: Mutex::new(None),
                     env_ctx_v2: config.env_ctx_v2,
                     retention_config: config.retention.clone(),
+                    context_timeline_storage: Mutex::new(context_timeline_storage),
                     model_descriptions,
                 });
                 let weak_handle = Arc::downgrade(&new_session);
                 if let Some(inner) = Arc::get_mut(&mut new_session) {
-                    inner.self_handle = weak_handle;
+                    inner.self_handle = weak_handle.clone();
                 }
+                mcp_client_requests::spawn_mcp_client_request_listener(
+                    weak_handle.clone(),
+                    mcp_client_request_rx,
+                );
+                mcp_server_events::spawn_mcp_server_event_listener(
+                    weak_handle,
+                    mcp_server_event_rx,
+                );
                 sess = Some(new_session);
                 if let Some(sess_arc) = &sess
                     && !config.always_allow_commands.is_empty()
@@ -4815,6 +5047,10 @@ async fn submission_loop(
                     }
                 };
 
+                let items =
+                    crate::mcp_input::resolve_mcp_input_items(sess.mcp_connection_manager(), items)
+                        .await;
+
                 // Clean up old status items when new user input arrives
                 // This prevents token buildup from old screenshots/status messages
                 sess.cleanup_old_status_items().await;
@@ -4838,6 +5074,10 @@ async fn submission_loop(
                     }
                 };
 
+                let items =
+                    crate::mcp_input::resolve_mcp_input_items(sess.mcp_connection_manager(), items)
+                        .await;
+
                 if sess.has_running_task() {
                     let mut response_item = response_input_from_core_items(items.clone());
                     sess.enforce_user_message_limits(&sub.id, &mut response_item);
@@ -4907,6 +5147,24 @@ async fn submission_loop(
                     other => sess.notify_approval(&id, other),
                 }
             }
+            Op::McpSamplingApproval { id, decision } => {
+                if let Some(sess) = sess.as_ref() {
+                    sess.notify_approval(&id, decision);
+                } else {
+                    send_no_session_event(sub.id).await;
+                }
+            }
+            Op::ResolveMcpElicitation {
+                id,
+                action,
+                content,
+            } => {
+                if let Some(sess) = sess.as_ref() {
+                    sess.resolve_mcp_elicitation(&id, action, content);
+                } else {
+                    send_no_session_event(sub.id).await;
+                }
+            }
             Op::UpdateValidationTool { name, enable } => {
                 if let Some(sess) = sess.as_ref() {
                     sess.update_validation_tool(&name, enable);
@@ -4993,6 +5251,57 @@ async fn submission_loop(
                     }
                 });
             }
+
+            Op::SearchHistoryRequest {
+                query,
+                log_id,
+                repo_root,
+                limit,
+            } => {
+                let config = config.clone();
+                let tx_event = tx_event.clone();
+                let sub_id = sub.id.clone();
+
+                tokio::spawn(async move {
+                    let repo_only = repo_root.is_some();
+                    let search_query = query.clone();
+                    // Scans the whole file under a shared lock, so keep it off the runtime.
+                    let matches = tokio::task::spawn_blocking(move || {
+                        crate::message_history::search(
+                            log_id,
+                            &search_query,
+                            repo_root.as_deref(),
+                            limit,
+                            &config,
+                        )
+                    })
+                    .await
+                    .unwrap_or_default();
+
+                    let event = Event {
+                        id: sub_id,
+                        event_seq: 0,
+                        msg: EventMsg::SearchHistoryResponse(
+                            crate::protocol::SearchHistoryResponseEvent {
+                                query,
+                                repo_only,
+                                matches: matches
+                                    .into_iter()
+                                    .map(|(offset, entry)| crate::protocol::HistorySearchMatch {
+                                        offset,
+                                        text: entry.text,
+                                    })
+                                    .collect(),
+                            },
+                        ),
+                        order: None,
+                    };
+
+                    if let Err(e) = tx_event.send(event).await {
+                        warn!("failed to send SearchHistoryResponse event: {e}");
+                    }
+                });
+            }
             // Upstream protocol no longer includes ListMcpTools; skip handling here.
             Op::ListCustomPrompts => {
                 let sess = match sess.as_ref() {
@@ -5021,6 +5330,59 @@ async fn submission_loop(
 
                 sess.send_event(event).await;
             }
+            Op::ListMcpCatalog => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+
+                // Listing waits on every server; keep it off the submission
+                // loop so a slow server cannot hold up later ops.
+                tokio::spawn(async move {
+                    let manager = sess.mcp_connection_manager();
+                    let (resources, resource_templates, prompts) = tokio::join!(
+                        manager.list_all_resources(),
+                        manager.list_all_resource_templates(),
+                        manager.list_all_prompts(),
+                    );
+
+                    let event = Event {
+                        id: sub.id,
+                        event_seq: 0,
+                        msg: EventMsg::ListMcpCatalogResponse(ListMcpCatalogResponseEvent {
+                            resources,
+                            resource_templates,
+                            prompts,
+                        }),
+                        order: None,
+                    };
+
+                    sess.send_event(event).await;
+                });
+            }
+            Op::RestartMcpServer { server } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+
+                let msg = match sess.mcp_connection_manager().restart_server(&server).await {
+                    Ok(tools) => EventMsg::BackgroundEvent(BackgroundEventEvent {
+                        message: format!("Restarted MCP server `{server}` ({tools} tools)"),
+                    }),
+                    Err(err) => EventMsg::Error(ErrorEvent {
+                        message: format!("{err:#}"),
+                    }),
+                };
+                let event = sess.make_event(&sub.id, msg);
+                sess.send_event(event).await;
+            }
             Op::Compact => {
                 let sess = match sess.as_ref() {
                     Some(sess) => sess,
@@ -5105,6 +5467,9 @@ async fn submission_loop(
                     }
                 }
                 if let Some(ref sess_arc) = sess {
+                    // Nothing appends to the timeline after shutdown, so the
+                    // session can be archived once ShutdownComplete is sent.
+                    sess_arc.context_timeline_storage.lock().unwrap().take();
                     sess_arc
                         .run_session_hooks(ProjectHookEvent::SessionEnd)
                         .await;
@@ -5622,8 +5987,7 @@ async fn run_agent(
                     )
                     .await;
 
-                    // Choose between local and remote compact based on auth mode,
-                    // matching upstream codex-rs behavior
+                    // Choose between local and remote compact (see `remote_compaction`).
                     if compact::should_use_remote_compact_task(&sess).await {
                         let _ = run_inline_remote_auto_compact_task(
                             Arc::clone(&sess),
@@ -8201,6 +8565,7 @@ fn to_exec_params(params: ShellToolCallParams, sess: &Session) -> ExecParams {
         env: create_env(&sess.shell_environment_policy),
         with_escalated_permissions: params.with_escalated_permissions,
         justification: params.justification,
+        resource_limits: sess.exec_limits,
     }
 }
 
@@ -10695,6 +11060,39 @@ async fn handle_container_exec_with_params(
                 };
             }
             let changes = convert_apply_patch_to_protocol(&action);
+            let hook_ctx = ExecCommandContext {
+                sub_id: sub_id.clone(),
+                call_id: call_id.clone(),
+                command_for_display: params.command.clone(),
+                cwd: params.cwd.clone(),
+                apply_patch: Some(ApplyPatchCommandContext {
+                    user_explicitly_approved_this_action: false,
+                    changes: changes.clone(),
+                }),
+            };
+            let hook_outcome = sess
+                .run_pre_exec_hooks(
+                    turn_diff_tracker,
+                    ProjectHookEvent::FileBeforeWrite,
+                    &hook_ctx,
+                    &params,
+                    attempt_req,
+                )
+                .await;
+            if let Some(reason) = hook_outcome.denied {
+                return ResponseInputItem::FunctionCallOutput {
+                    call_id,
+                    output: FunctionCallOutputPayload {
+                        content: format!("patch rejected by project hook: {reason}"),
+                        success: Some(false),
+                    },
+                };
+            }
+            let hook_approval_reason = hook_outcome.require_approval.then(|| {
+                hook_outcome
+                    .approval_reason
+                    .unwrap_or_else(|| "approval required by project hook".to_string())
+            });
             turn_diff_tracker.on_patch_begin(&changes);
 
             match apply_patch::apply_patch(
@@ -10704,6 +11102,7 @@ async fn handle_container_exec_with_params(
                 attempt_req,
                 output_index,
                 action,
+                hook_approval_reason,
             )
             .await
             {
@@ -10791,6 +11190,45 @@ async fn handle_container_exec_with_params(
         MaybeApplyPatchVerified::NotApplyPatch => {}
     }
 
+    let hook_ctx = ExecCommandContext {
+        sub_id: sub_id.clone(),
+        call_id: call_id.clone(),
+        command_for_display: params.command.clone(),
+        cwd: params.cwd.clone(),
+        apply_patch: None,
+    };
+    let hook_outcome = sess
+        .run_pre_exec_hooks(
+            turn_diff_tracker,
+            ProjectHookEvent::ToolBefore,
+            &hook_ctx,
+            &params,
+            attempt_req,
+        )
+        .await;
+    if let Some(reason) = hook_outcome.denied {
+        return ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: format!("exec command rejected by project hook: {reason}"),
+                success: Some(false),
+            },
+        };
+    }
+    if let Some(command) = hook_outcome.command {
+        let order = sess.next_background_order(&sub_id, attempt_req, output_index);
+        sess.notify_background_event_with_order(
+            &sub_id,
+            order,
+            format!(
+                "Project hook rewrote command: {}",
+                crate::util::strip_bash_lc_and_escape(&command)
+            ),
+        )
+        .await;
+        params.command = command;
+    }
+
     let safety = {
         let state = sess.state.lock().unwrap();
         assess_command_safety(
@@ -10798,9 +11236,26 @@ async fn handle_container_exec_with_params(
             sess.approval_policy,
             &sess.sandbox_policy,
             &state.approved_commands,
+            &sess.exec_policy,
+            &params.cwd,
             params.with_escalated_permissions.unwrap_or(false),
         )
     };
+    let safety = match safety {
+        SafetyCheck::AutoApprove { .. } | SafetyCheck::AskUser
+            if hook_outcome.require_approval
+                && matches!(sess.approval_policy, AskForApproval::Never) =>
+        {
+            SafetyCheck::Reject {
+                reason: "a project hook requires approval but approval policy is never".to_string(),
+            }
+        }
+        SafetyCheck::AutoApprove { .. } if hook_outcome.require_approval => SafetyCheck::AskUser,
+        other => other,
+    };
+    let approval_reason = hook_outcome
+        .approval_reason
+        .or_else(|| params.justification.clone());
     let command_for_display = params.command.clone();
     let harness_summary_json: Option<String> = None;
 
@@ -10831,7 +11286,7 @@ async fn handle_container_exec_with_params(
                     call_id.clone(),
                     params.command.clone(),
                     params.cwd.clone(),
-                    params.justification.clone(),
+                    approval_reason.clone(),
                 )
                 .await;
 
@@ -10986,6 +11441,10 @@ async fn handle_container_exec_with_params(
             Err(CodexErr::Sandbox(SandboxErr::Timeout { output })) => {
                 (output.as_ref().clone(), 124)
             }
+            Err(CodexErr::Sandbox(SandboxErr::ResourceLimit { output, .. })) => {
+                let exit = output.exit_code;
+                (*output, exit)
+            }
             Err(e) => {
                 let msg = get_error_message_ui(&e);
                 (
@@ -11169,12 +11628,24 @@ async fn handle_sandbox_error(
     let otel_event_manager = sess.client.get_otel_event_manager();
     let tool_name = "local_shell";
 
+    // Resource limits apply with or without the sandbox, so retrying unsandboxed
+    // would not help. The output already ends with a note naming the limit.
+    if let SandboxErr::ResourceLimit { output, .. } = &error {
+        return ResponseInputItem::FunctionCallOutput {
+            output: FunctionCallOutputPayload {
+                content: format_exec_output_with_limit(sess, &sub_id, &call_id, output),
+                success: Some(false),
+            },
+            call_id,
+        };
+    }
+
     // Early out if either the user never wants to be asked for approval, or
     // we're letting the model manage escalation requests. Otherwise, continue
     match sess.approval_policy {
         AskForApproval::Never | AskForApproval::OnRequest => {
             // Clarify when Read Only mode is the reason a command cannot proceed.
-            let content = if matches!(sess.sandbox_policy, SandboxPolicy::ReadOnly) {
+            let content = if matches!(sess.sandbox_policy, SandboxPolicy::ReadOnly { .. }) {
                 format!("command blocked by Read Only mode: {error}")
             } else {
                 format!("failed in sandbox {sandbox_type:?} with execution error: {error}")
@@ -11582,7 +12053,11 @@ async fn send_agent_status_update(sess: &Session) {
                 result: agent.result.clone(),
                 error: agent.error.clone(),
                 elapsed_ms,
-                token_count: None,
+                token_count: agent
+                    .token_usage
+                    .as_ref()
+                    .map(|report| report.usage.blended_total()),
+                token_usage: agent.token_usage.clone(),
             }
         })
         .collect();
@@ -11,6 +11,7 @@ use crate::client_common::ResponseEvent;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
+use crate::model_provider_info::WireApi;
 use crate::protocol::AgentMessageEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::EventMsg;
@@ -41,27 +42,25 @@ const COMPACT_TOOL_OUTPUT_MAX_BYTES: usize = 4 * 1024;
 const COMPACT_IMAGE_URL_MAX_BYTES: usize = 512;
 const MAX_COMPACTION_SNIPPETS: usize = 12;
 
-/// Determine whether to use remote compaction (ChatGPT-based) or local compaction.
-///
-/// Upstream codex-rs checks if auth mode is ChatGPT and RemoteCompaction feature is enabled.
-/// In code-rs, remote compaction infrastructure is not yet implemented, so this always
-/// returns false (always use local compaction).
-///
-/// TODO: Once ChatGPT auth and remote compaction are implemented, update this to:
-/// ```
-/// session
-///     .services
-///     .auth_manager
-///     .auth()
-///     .is_some_and(|auth| auth.mode == AuthMode::ChatGPT)
-///     && session.enabled(Feature::RemoteCompaction).await
-/// ```
+/// Determine whether to compact through the provider's compaction endpoint
+/// (see `compact_remote`) rather than with a local summarisation turn.
 pub(super) async fn should_use_remote_compact_task(session: &Session) -> bool {
-    session
+    let chatgpt_auth = session
         .client
         .get_auth_manager()
         .and_then(|manager| manager.auth())
-        .is_some_and(|auth| auth.mode == AuthMode::ChatGPT)
+        .is_some_and(|auth| auth.mode == AuthMode::ChatGPT);
+    remote_compaction_enabled(
+        session.client.get_remote_compaction(),
+        session.client.get_provider().wire_api,
+        chatgpt_auth,
+    )
+}
+
+/// Only Responses API providers expose `/responses/compact`. Without an
+/// explicit `remote_compaction` setting it is used for ChatGPT sign-in.
+fn remote_compaction_enabled(setting: Option<bool>, wire_api: WireApi, chatgpt_auth: bool) -> bool {
+    wire_api == WireApi::Responses && setting.unwrap_or(chatgpt_auth)
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -349,6 +348,7 @@ pub(super) async fn perform_compaction(
 
     let rollout_item = RolloutItem::Compacted(CompactedItem {
         message: summary_text.clone(),
+        replacement_history: None,
     });
     sess.persist_rollout_items(&[rollout_item]).await;
 
@@ -503,6 +503,7 @@ async fn run_compact_task_inner_inline(
 
     let rollout_item = RolloutItem::Compacted(CompactedItem {
         message: summary_text.clone(),
+        replacement_history: None,
     });
     sess.persist_rollout_items(&[rollout_item]).await;
 
@@ -846,6 +847,16 @@ pub(super) fn response_input_from_core_items(items: Vec<InputItem>) -> ResponseI
                     }
                 }
             }
+            InputItem::McpResource { server, uri } => {
+                content_items.push(ContentItem::InputText {
+                    text: format!("[MCP resource {server}:{uri}]"),
+                });
+            }
+            InputItem::McpPrompt { server, name, .. } => {
+                content_items.push(ContentItem::InputText {
+                    text: format!("[MCP prompt {server}:{name}]"),
+                });
+            }
         }
     }
 
@@ -860,6 +871,23 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn remote_compaction_requires_responses_api() {
+        assert!(remote_compaction_enabled(None, WireApi::Responses, true));
+        assert!(!remote_compaction_enabled(None, WireApi::Responses, false));
+        assert!(remote_compaction_enabled(
+            Some(true),
+            WireApi::Responses,
+            false
+        ));
+        assert!(!remote_compaction_enabled(
+            Some(false),
+            WireApi::Responses,
+            true
+        ));
+        assert!(!remote_compaction_enabled(Some(true), WireApi::Chat, true));
+    }
+
     #[test]
     fn resolve_compact_prompt_text_prefers_override() {
         let text = resolve_compact_prompt_text(Some("custom prompt"));
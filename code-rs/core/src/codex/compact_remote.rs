@@ -2,15 +2,16 @@ use std::sync::Arc;
 
 use super::Session;
 use super::TurnContext;
+use super::compact;
 use super::compact::is_context_overflow_error;
 use super::compact::prune_orphan_tool_outputs;
 use super::compact::response_input_from_core_items;
 use super::compact::sanitize_items_for_compact;
 use super::compact::send_compaction_checkpoint_warning;
 use crate::Prompt;
+use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::protocol::AgentMessageEvent;
-use crate::protocol::ErrorEvent;
 use crate::protocol::EventMsg;
 use crate::protocol::InputItem;
 use crate::util::backoff;
@@ -19,6 +20,8 @@ use code_protocol::models::ResponseItem;
 use code_protocol::protocol::CompactedItem;
 use code_protocol::protocol::RolloutItem;
 
+/// Remote auto-compaction; falls back to local compaction when the endpoint
+/// fails so the turn can still continue with a trimmed history.
 pub(super) async fn run_inline_remote_auto_compact_task(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
@@ -27,15 +30,10 @@ pub(super) async fn run_inline_remote_auto_compact_task(
     let sub_id = sess.next_internal_sub_id();
     match run_remote_compact_task_inner(&sess, &turn_context, &sub_id, extra_input).await {
         Ok(history) => history,
+        Err(CodexErr::Interrupted) => Vec::new(),
         Err(err) => {
-            let event = sess.make_event(
-                &sub_id,
-                EventMsg::Error(ErrorEvent {
-                    message: format!("remote compact failed: {err}"),
-                }),
-            );
-            sess.send_event(event).await;
-            Vec::new()
+            notify_local_fallback(&sess, &sub_id, &err).await;
+            compact::run_inline_auto_compact_task(sess, turn_context).await
         }
     }
 }
@@ -46,26 +44,30 @@ pub(super) async fn run_remote_compact_task(
     sub_id: String,
     extra_input: Vec<InputItem>,
 ) -> CodexResult<()> {
-    match run_remote_compact_task_inner(&sess, &turn_context, &sub_id, extra_input).await {
+    match run_remote_compact_task_inner(&sess, &turn_context, &sub_id, extra_input.clone()).await {
         Ok(_history) => {
             // Mirror local compaction behaviour: clear the running task when the
             // compaction finished successfully so the UI can unblock.
             sess.remove_task(&sub_id);
             Ok(())
         }
+        Err(CodexErr::Interrupted) => Err(CodexErr::Interrupted),
         Err(err) => {
-            let event = sess.make_event(
-                &sub_id,
-                EventMsg::Error(ErrorEvent {
-                    message: err.to_string(),
-                }),
-            );
-            sess.send_event(event).await;
-            Err(err)
+            notify_local_fallback(&sess, &sub_id, &err).await;
+            compact::perform_compaction(sess, turn_context, sub_id, extra_input, true).await
         }
     }
 }
 
+async fn notify_local_fallback(sess: &Arc<Session>, sub_id: &str, err: &CodexErr) {
+    tracing::warn!("remote compact failed; falling back to local compaction: {err}");
+    sess.notify_stream_error(
+        sub_id,
+        format!("remote compact failed: {err}; compacting locally instead…"),
+    )
+    .await;
+}
+
 async fn run_remote_compact_task_inner(
     sess: &Arc<Session>,
     turn_context: &Arc<TurnContext>,
@@ -154,6 +156,7 @@ async fn run_remote_compact_task_inner(
 
     let rollout_item = RolloutItem::Compacted(CompactedItem {
         message: "Conversation history compacted.".to_string(),
+        replacement_history: Some(new_history.clone()),
     });
     sess.persist_rollout_items(&[rollout_item]).await;
 
@@ -0,0 +1,394 @@
+//! Answers requests that MCP servers send to us in our role as their client:
+//! `roots/list`, `sampling/createMessage` and `elicitation/create`.
+//!
+//! The connection manager forwards each request as an [`McpClientRequest`];
+//! the listener spawned here resolves it against the session that owns the
+//! manager. Sampling goes through the session's [`ModelClient`] only after the
+//! user approves it, and elicitations are surfaced to the UI as a form.
+//!
+//! [`ModelClient`]: crate::client::ModelClient
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Weak;
+
+use code_protocol::models::ContentItem;
+use code_protocol::models::ResponseItem;
+use futures::StreamExt;
+use mcp_types::CreateMessageRequest;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::ElicitRequest;
+use mcp_types::ElicitRequestParams;
+use mcp_types::ElicitResult;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::ListRootsRequest;
+use mcp_types::ListRootsResult;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::PingRequest;
+use mcp_types::Role;
+use mcp_types::Root;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::Session;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::mcp_connection_manager::McpClientRequest;
+use crate::protocol::AskForApproval;
+use crate::protocol::EventMsg;
+use crate::protocol::McpElicitationAction;
+use crate::protocol::McpElicitationRequestEvent;
+use crate::protocol::McpSamplingRequestEvent;
+use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxPolicy;
+
+const USER_REJECTED_ERROR_CODE: i64 = -1;
+const INVALID_PARAMS_ERROR_CODE: i64 = -32602;
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+pub(super) type PendingElicitation = oneshot::Sender<(McpElicitationAction, Option<Value>)>;
+
+/// Drain `rx` for as long as the session is alive, answering each request on
+/// its own task so a pending approval never blocks other servers.
+pub(super) fn spawn_mcp_client_request_listener(
+    session: Weak<Session>,
+    mut rx: mpsc::Receiver<McpClientRequest>,
+) {
+    tokio::spawn(async move {
+        while let Some(request) = rx.recv().await {
+            let Some(sess) = session.upgrade() else {
+                break;
+            };
+            tokio::spawn(async move {
+                let McpClientRequest {
+                    server,
+                    method,
+                    params,
+                    respond_to,
+                } = request;
+                let result = handle_request(&sess, &server, &method, params).await;
+                let _ = respond_to.send(result);
+            });
+        }
+    });
+}
+
+async fn handle_request(
+    sess: &Arc<Session>,
+    server: &str,
+    method: &str,
+    params: Option<Value>,
+) -> Result<Value, JSONRPCErrorError> {
+    match method {
+        PingRequest::METHOD => Ok(json!({})),
+        ListRootsRequest::METHOD => to_result(ListRootsResult {
+            roots: session_roots(sess.get_cwd(), sess.get_sandbox_policy()),
+        }),
+        CreateMessageRequest::METHOD => {
+            let params: CreateMessageRequestParams = parse_params(params)?;
+            to_result(sess.create_mcp_message(server, params).await?)
+        }
+        ElicitRequest::METHOD => {
+            let params: ElicitRequestParams = parse_params(params)?;
+            to_result(sess.elicit_from_user(server, params).await)
+        }
+        other => Err(rpc_error(
+            METHOD_NOT_FOUND_ERROR_CODE,
+            format!("method `{other}` is not supported by this client"),
+        )),
+    }
+}
+
+/// Roots exposed to MCP servers: the session cwd followed by any extra
+/// `writable_roots` from a workspace-write sandbox, without duplicates.
+fn session_roots(cwd: &Path, sandbox_policy: &SandboxPolicy) -> Vec<Root> {
+    let mut paths: Vec<PathBuf> = vec![cwd.to_path_buf()];
+    if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = sandbox_policy {
+        for root in writable_roots {
+            let root = if root.is_absolute() {
+                root.clone()
+            } else {
+                cwd.join(root)
+            };
+            if !paths.contains(&root) {
+                paths.push(root);
+            }
+        }
+    }
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let uri = url::Url::from_file_path(&path).ok()?.to_string();
+            Some(Root {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                uri,
+            })
+        })
+        .collect()
+}
+
+/// Flatten the sampling conversation into model input. Audio has no
+/// equivalent in the Responses API and is replaced with a placeholder.
+fn sampling_input(params: &CreateMessageRequestParams) -> Vec<ResponseItem> {
+    params
+        .messages
+        .iter()
+        .map(|message| {
+            let (role, content) = match (&message.role, &message.content) {
+                (Role::User, SamplingMessageContent::TextContent(text)) => (
+                    "user",
+                    ContentItem::InputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (Role::User, SamplingMessageContent::ImageContent(image)) => (
+                    "user",
+                    ContentItem::InputImage {
+                        image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+                    },
+                ),
+                (Role::Assistant, SamplingMessageContent::TextContent(text)) => (
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (role, content) => {
+                    let kind = match content {
+                        SamplingMessageContent::ImageContent(_) => "image",
+                        _ => "audio",
+                    };
+                    let role = match role {
+                        Role::User => "user",
+                        Role::Assistant => "assistant",
+                    };
+                    (
+                        "user",
+                        ContentItem::InputText {
+                            text: format!("[{role} {kind} attachment omitted]"),
+                        },
+                    )
+                }
+            };
+            ResponseItem::Message {
+                id: None,
+                role: role.to_string(),
+                content: vec![content],
+            }
+        })
+        .collect()
+}
+
+fn to_result<T: serde::Serialize>(value: T) -> Result<Value, JSONRPCErrorError> {
+    serde_json::to_value(value).map_err(|err| rpc_error(INTERNAL_ERROR_CODE, err.to_string()))
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(
+    params: Option<Value>,
+) -> Result<T, JSONRPCErrorError> {
+    serde_json::from_value(params.unwrap_or(Value::Null))
+        .map_err(|err| rpc_error(INVALID_PARAMS_ERROR_CODE, err.to_string()))
+}
+
+fn rpc_error(code: i64, message: impl Into<String>) -> JSONRPCErrorError {
+    JSONRPCErrorError {
+        code,
+        data: None,
+        message: message.into(),
+    }
+}
+
+impl Session {
+    /// Ask the user to approve a sampling request and, if approved, run it
+    /// through the session's model client.
+    async fn create_mcp_message(
+        &self,
+        server: &str,
+        params: CreateMessageRequestParams,
+    ) -> Result<CreateMessageResult, JSONRPCErrorError> {
+        if matches!(self.get_approval_policy(), AskForApproval::Never) {
+            return Err(rpc_error(
+                USER_REJECTED_ERROR_CODE,
+                "sampling requires user approval, which is disabled for this session",
+            ));
+        }
+
+        let id = format!("mcp-sampling-{}", Uuid::new_v4());
+        let (tx_approve, rx_approve) = oneshot::channel();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.pending_approvals.insert(id.clone(), tx_approve);
+        }
+        let event = self.make_event(
+            &id,
+            EventMsg::McpSamplingRequest(McpSamplingRequestEvent {
+                id: id.clone(),
+                server: server.to_string(),
+                messages: params.messages.clone(),
+                system_prompt: params.system_prompt.clone(),
+                max_tokens: params.max_tokens,
+            }),
+        );
+        self.send_event(event).await;
+
+        match rx_approve.await.unwrap_or_default() {
+            ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {}
+            ReviewDecision::Denied | ReviewDecision::Abort => {
+                return Err(rpc_error(
+                    USER_REJECTED_ERROR_CODE,
+                    "user rejected sampling request",
+                ));
+            }
+        }
+
+        let mut prompt = Prompt::default();
+        prompt.input = sampling_input(&params);
+        prompt.base_instructions_override = params.system_prompt.clone();
+        prompt.include_additional_instructions = false;
+        prompt.ui_locale = self.ui_locale.clone();
+        prompt.log_tag = Some("codex/mcp-sampling".to_string());
+
+        let mut stream = self
+            .client
+            .clone()
+            .stream(&prompt)
+            .await
+            .map_err(|err| rpc_error(INTERNAL_ERROR_CODE, err.to_string()))?;
+        let mut text = String::new();
+        loop {
+            match stream.next().await {
+                Some(Ok(ResponseEvent::OutputItemDone {
+                    item: ResponseItem::Message { role, content, .. },
+                    ..
+                })) if role == "assistant" => {
+                    for item in content {
+                        if let ContentItem::OutputText { text: chunk } = item {
+                            text.push_str(&chunk);
+                        }
+                    }
+                }
+                Some(Ok(ResponseEvent::Completed { .. })) => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(rpc_error(INTERNAL_ERROR_CODE, err.to_string())),
+                None => {
+                    return Err(rpc_error(
+                        INTERNAL_ERROR_CODE,
+                        "stream closed before response.completed",
+                    ));
+                }
+            }
+        }
+
+        Ok(CreateMessageResult {
+            content: CreateMessageResultContent::TextContent(TextContent {
+                annotations: None,
+                text,
+                r#type: "text".to_string(),
+            }),
+            model: self.client.get_model(),
+            role: Role::Assistant,
+            stop_reason: Some("endTurn".to_string()),
+        })
+    }
+
+    /// Surface an elicitation form to the user and wait for the answer.
+    /// Sessions that never prompt the user decline immediately.
+    async fn elicit_from_user(&self, server: &str, params: ElicitRequestParams) -> ElicitResult {
+        if matches!(self.get_approval_policy(), AskForApproval::Never) {
+            return ElicitResult {
+                action: McpElicitationAction::Decline.as_str().to_string(),
+                content: None,
+            };
+        }
+
+        let id = format!("mcp-elicitation-{}", Uuid::new_v4());
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.pending_elicitations.insert(id.clone(), tx);
+        }
+        let event = self.make_event(
+            &id,
+            EventMsg::McpElicitationRequest(McpElicitationRequestEvent {
+                id: id.clone(),
+                server: server.to_string(),
+                message: params.message,
+                requested_schema: params.requested_schema,
+            }),
+        );
+        self.send_event(event).await;
+
+        let (action, content) = rx.await.unwrap_or((McpElicitationAction::Cancel, None));
+        ElicitResult {
+            action: action.as_str().to_string(),
+            content: match action {
+                McpElicitationAction::Accept => content,
+                McpElicitationAction::Decline | McpElicitationAction::Cancel => None,
+            },
+        }
+    }
+
+    pub(crate) fn resolve_mcp_elicitation(
+        &self,
+        id: &str,
+        action: McpElicitationAction,
+        content: Option<Value>,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(tx) = state.pending_elicitations.remove(id) {
+            let _ = tx.send((action, content));
+        } else {
+            warn!("no pending MCP elicitation found for id={id}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn roots_include_cwd_and_extra_writable_roots_once() {
+        let cwd = if cfg!(windows) {
+            PathBuf::from(r"C:\work\repo")
+        } else {
+            PathBuf::from("/work/repo")
+        };
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![cwd.clone(), PathBuf::from("vendor")],
+            network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            allow_git_writes: true,
+        };
+
+        let roots = session_roots(&cwd, &policy);
+        let names: Vec<Option<&str>> = roots.iter().map(|root| root.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("repo"), Some("vendor")]);
+        assert!(roots.iter().all(|root| root.uri.starts_with("file://")));
+        assert!(roots[1].uri.ends_with("/repo/vendor"));
+    }
+
+    #[test]
+    fn read_only_sandbox_exposes_only_cwd() {
+        let cwd = std::env::temp_dir();
+        let roots = session_roots(&cwd, &SandboxPolicy::new_read_only_policy());
+        assert_eq!(roots.len(), 1);
+    }
+}
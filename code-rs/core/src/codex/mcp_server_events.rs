@@ -0,0 +1,61 @@
+//! Surfaces [`McpServerEvent`]s from the connection manager's supervisor to
+//! the UI. Tool list refreshes need no action here: the next turn reads the
+//! updated tool map when it builds the tools JSON.
+
+use std::sync::Weak;
+
+use tokio::sync::mpsc;
+
+use super::Session;
+use crate::mcp_connection_manager::McpServerEvent;
+use crate::protocol::BackgroundEventEvent;
+use crate::protocol::ErrorEvent;
+use crate::protocol::EventMsg;
+
+const MCP_SUPERVISOR_SUB_ID: &str = "mcp-supervisor";
+
+pub(super) fn spawn_mcp_server_event_listener(
+    session: Weak<Session>,
+    mut rx: mpsc::UnboundedReceiver<McpServerEvent>,
+) {
+    tokio::spawn(async move {
+        while let Some(server_event) = rx.recv().await {
+            let Some(sess) = session.upgrade() else {
+                break;
+            };
+            let event = sess.make_event(MCP_SUPERVISOR_SUB_ID, event_msg(server_event));
+            sess.send_event(event).await;
+        }
+    });
+}
+
+fn event_msg(event: McpServerEvent) -> EventMsg {
+    let message = match event {
+        McpServerEvent::ToolsChanged { server, tools } => {
+            format!("MCP server `{server}` updated its tools ({tools} available)")
+        }
+        McpServerEvent::Restarted { server, tools } => {
+            format!("MCP server `{server}` restarted after its connection dropped ({tools} tools)")
+        }
+        McpServerEvent::RestartFailed {
+            server,
+            error,
+            retry_in: Some(delay),
+        } => format!(
+            "MCP server `{server}` failed to restart, retrying in {}s: {error}",
+            delay.as_secs()
+        ),
+        McpServerEvent::RestartFailed {
+            server,
+            error,
+            retry_in: None,
+        } => {
+            return EventMsg::Error(ErrorEvent {
+                message: format!(
+                    "MCP server `{server}` could not be restarted: {error}. Use `/mcp restart {server}` to try again."
+                ),
+            });
+        }
+    };
+    EventMsg::BackgroundEvent(BackgroundEventEvent { message })
+}
@@ -19,6 +19,7 @@ use crate::config_types::History;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
 use crate::config_types::Notice;
+use crate::config_types::NotificationSinkConfig;
 use crate::config_types::Notifications;
 use crate::config_types::OtelConfig;
 use crate::config_types::OtelConfigToml;
@@ -37,6 +38,7 @@ use crate::config_types::Tui;
 use crate::config_types::UiLocale;
 use crate::config_types::UriBasedFileOpener;
 use crate::config_types::ValidationConfig;
+use crate::exec_limits::ExecResourceLimits;
 use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_family::ModelFamily;
 use crate::model_family::derive_default_model_family;
@@ -221,7 +223,14 @@ pub struct Config {
     /// Project-specific commands available in the active workspace.
     pub project_commands: Vec<ProjectCommand>,
 
+    /// Whether the active workspace is marked `trust_level = "trusted"`.
+    pub project_trusted: bool,
+
     pub shell_environment_policy: ShellEnvironmentPolicy,
+
+    /// Resource limits applied to every command the agent runs.
+    pub exec_limits: ExecResourceLimits,
+
     /// Patterns requiring an explicit confirm prefix before running.
     pub confirm_guard: ConfirmGuardConfig,
 
@@ -283,6 +292,10 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Webhook and Unix-socket destinations for notifications, each with its
+    /// own event filter. See [`NotificationSinkConfig`].
+    pub notification_sinks: Vec<NotificationSinkConfig>,
+
     /// Record of which one-time notices the user has acknowledged.
     pub notices: Notice,
 
@@ -387,6 +400,10 @@ pub struct Config {
     /// Whether we're using ChatGPT authentication (affects feature availability)
     pub using_chatgpt_auth: bool,
 
+    /// Compact history through the provider's `/responses/compact` endpoint.
+    /// `None` enables it only when signed in with ChatGPT.
+    pub remote_compaction: Option<bool>,
+
     /// GitHub integration configuration.
     pub github: GithubConfig,
 
@@ -1846,12 +1863,21 @@ pub struct ConfigToml {
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
+    /// Per-command rlimits (memory, CPU time, processes, file size).
+    #[serde(default)]
+    pub exec_limits: ExecResourceLimits,
+
     /// Sandbox mode to use.
     pub sandbox_mode: Option<SandboxMode>,
 
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Paths or globs that sandboxed commands may not read, applied in both
+    /// `read-only` and `workspace-write` modes.
+    #[serde(default)]
+    pub sandbox_deny_read: Vec<String>,
+
     #[serde(default)]
     pub confirm_guard: Option<ConfirmGuardConfig>,
 
@@ -1877,6 +1903,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub notify: Option<Vec<String>>,
 
+    /// Additional notification destinations (`[[notification_sinks]]`).
+    #[serde(default)]
+    pub notification_sinks: Option<Vec<NotificationSinkConfig>>,
+
     /// Stored acknowledgement flags for in-product notices.
     pub notice: Option<Notice>,
 
@@ -1944,6 +1974,11 @@ pub struct ConfigToml {
     /// Defaults to `false`.
     pub show_raw_agent_reasoning: Option<bool>,
 
+    /// Compact history through the provider's `/responses/compact` endpoint
+    /// instead of a local summarisation turn. Defaults to on for ChatGPT
+    /// sign-in and off otherwise; only Responses API providers support it.
+    pub remote_compaction: Option<bool>,
+
     pub model_reasoning_effort: Option<ReasoningEffort>,
     pub model_reasoning_summary: Option<ReasoningSummary>,
     pub model_text_verbosity: Option<TextVerbosity>,
@@ -2071,51 +2106,42 @@ impl ConfigToml {
             .or(self.sandbox_mode)
             .unwrap_or_default();
         match resolved_sandbox_mode {
-            SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+            SandboxMode::ReadOnly => SandboxPolicy::ReadOnly {
+                deny_read: self.sandbox_deny_read.clone(),
+            },
             SandboxMode::WorkspaceWrite => match self.sandbox_workspace_write.as_ref() {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                     allow_git_writes,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
+                    network_allowlist: network_allowlist.clone(),
+                    deny_read: self.sandbox_deny_read.clone(),
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
                     allow_git_writes: *allow_git_writes,
                 },
-                None => SandboxPolicy::new_workspace_write_policy(),
+                None => SandboxPolicy::WorkspaceWrite {
+                    writable_roots: Vec::new(),
+                    network_access: false,
+                    network_allowlist: Vec::new(),
+                    deny_read: self.sandbox_deny_read.clone(),
+                    exclude_tmpdir_env_var: false,
+                    exclude_slash_tmp: false,
+                    allow_git_writes: true,
+                },
             },
             SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
         }
     }
 
     pub fn is_cwd_trusted(&self, resolved_cwd: &Path) -> bool {
-        let projects = self.projects.clone().unwrap_or_default();
-
-        let is_path_trusted = |path: &Path| {
-            let path_str = path.to_string_lossy().to_string();
-            projects
-                .get(&path_str)
-                .map(|p| p.trust_level.as_deref() == Some("trusted"))
-                .unwrap_or(false)
-        };
-
-        // Fast path: exact cwd match
-        if is_path_trusted(resolved_cwd) {
-            return true;
-        }
-
-        // If cwd lives inside a git worktree, check whether the root git project
-        // (the primary repository working directory) is trusted. This lets
-        // worktrees inherit trust from the main project.
-        if let Some(root_project) = resolve_root_git_project_for_trust(resolved_cwd) {
-            return is_path_trusted(&root_project);
-        }
-
-        false
+        is_cwd_trusted_in(self.projects.as_ref(), resolved_cwd)
     }
 
     pub fn get_config_profile(
@@ -2208,6 +2234,37 @@ fn upgrade_legacy_model_slug(slug: &str) -> Option<String> {
     None
 }
 
+fn is_cwd_trusted_in(
+    projects: Option<&HashMap<String, ProjectConfig>>,
+    resolved_cwd: &Path,
+) -> bool {
+    let Some(projects) = projects else {
+        return false;
+    };
+
+    let is_path_trusted = |path: &Path| {
+        let path_str = path.to_string_lossy().to_string();
+        projects
+            .get(&path_str)
+            .map(|p| p.trust_level.as_deref() == Some("trusted"))
+            .unwrap_or(false)
+    };
+
+    // Fast path: exact cwd match
+    if is_path_trusted(resolved_cwd) {
+        return true;
+    }
+
+    // If cwd lives inside a git worktree, check whether the root git project
+    // (the primary repository working directory) is trusted. This lets
+    // worktrees inherit trust from the main project.
+    if let Some(root_project) = resolve_root_git_project_for_trust(resolved_cwd) {
+        return is_path_trusted(&root_project);
+    }
+
+    false
+}
+
 /// Optional overrides for user configuration (e.g., from CLI flags).
 #[derive(Default, Debug, Clone)]
 pub struct ConfigOverrides {
@@ -2348,6 +2405,8 @@ impl Config {
         // Honor the exact directory the program was started in (or provided via -C/--cd).
         // Any Git-aware features should resolve the repo root on demand.
 
+        let project_trusted = is_cwd_trusted_in(cfg.projects.as_ref(), &resolved_cwd);
+
         // Project-specific overrides based on final resolved cwd (exact match)
         let project_key = resolved_cwd.to_string_lossy().to_string();
         let project_override = cfg.projects.as_ref().and_then(|m| m.get(&project_key));
@@ -2357,23 +2416,35 @@ impl Config {
             .or(project_override.and_then(|p| p.sandbox_mode))
             .or(cfg.sandbox_mode)
             .unwrap_or_default();
+        let deny_read = cfg.sandbox_deny_read.clone();
         let sandbox_policy = match effective_sandbox_mode {
-            SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+            SandboxMode::ReadOnly => SandboxPolicy::ReadOnly { deny_read },
             SandboxMode::WorkspaceWrite => match cfg_workspace {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                     allow_git_writes,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
+                    deny_read,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                     allow_git_writes,
                 },
-                None => SandboxPolicy::new_workspace_write_policy(),
+                None => SandboxPolicy::WorkspaceWrite {
+                    writable_roots: Vec::new(),
+                    network_access: false,
+                    network_allowlist: Vec::new(),
+                    deny_read,
+                    exclude_tmpdir_env_var: false,
+                    exclude_slash_tmp: false,
+                    allow_git_writes: true,
+                },
             },
             SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
         };
@@ -2624,7 +2695,9 @@ impl Config {
             always_allow_commands,
             project_hooks,
             project_commands,
+            project_trusted,
             shell_environment_policy,
+            exec_limits: cfg.exec_limits,
             confirm_guard,
             disable_response_storage: config_profile
                 .disable_response_storage
@@ -2633,6 +2706,7 @@ impl Config {
                 .unwrap_or(false),
             auto_upgrade_enabled: cfg.auto_upgrade_enabled.unwrap_or(false),
             notify: cfg.notify,
+            notification_sinks: cfg.notification_sinks.unwrap_or_default(),
             notices: cfg.notice.unwrap_or_default(),
             user_instructions,
             base_instructions,
@@ -2699,6 +2773,7 @@ impl Config {
             debug: debug.unwrap_or(false),
             // Already computed before moving code_home
             using_chatgpt_auth,
+            remote_compaction: cfg.remote_compaction,
             github: cfg.github.unwrap_or_default(),
             validation: cfg.validation.unwrap_or_default(),
             subagent_commands: cfg.subagents.map(|s| s.commands).unwrap_or_default(),
@@ -3104,7 +3179,7 @@ network_access = true  # This should be ignored.
             .expect("TOML deserialization should succeed");
         let sandbox_mode_override = None;
         assert_eq!(
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             sandbox_read_only_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
 
@@ -3126,6 +3201,8 @@ exclude_slash_tmp = true
             SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![PathBuf::from("/my/workspace")],
                 network_access: false,
+                network_allowlist: Vec::new(),
+                deny_read: Vec::new(),
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
                 allow_git_writes: true,
@@ -637,6 +637,123 @@ impl Default for Notifications {
     }
 }
 
+/// How the TUI raises a notification in the terminal.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TuiNotificationMethod {
+    /// `OSC 9` desktop notification (iTerm2, WezTerm, Windows Terminal, kitty).
+    #[default]
+    Osc9,
+    /// `OSC 777` notification with a separate title (foot, Ghostty, urxvt).
+    Osc777,
+    /// Plain terminal bell.
+    Bell,
+}
+
+/// Key handling style for the composer textarea.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EditingMode {
+    /// Modeless, emacs-style editing keys.
+    #[default]
+    Emacs,
+    /// Modal vi editing with normal, insert and visual modes.
+    Vi,
+}
+
+/// One or more key chords, e.g. `"ctrl-j"` or `["enter", "ctrl-m"]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeyBindings {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBindings {
+    pub fn chords(&self) -> &[String] {
+        match self {
+            Self::One(chord) => std::slice::from_ref(chord),
+            Self::Many(chords) => chords,
+        }
+    }
+}
+
+/// `[tui.keymap]`: remaps composer actions. Unset actions keep their default
+/// keys; a binding replaces every default key for that action.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TuiKeymap {
+    #[serde(default)]
+    pub submit: Option<KeyBindings>,
+    #[serde(default)]
+    pub newline: Option<KeyBindings>,
+    #[serde(default)]
+    pub history_up: Option<KeyBindings>,
+    #[serde(default)]
+    pub history_down: Option<KeyBindings>,
+    #[serde(default)]
+    pub file_search: Option<KeyBindings>,
+    #[serde(default)]
+    pub history_search: Option<KeyBindings>,
+    #[serde(default)]
+    pub toggle_reasoning: Option<KeyBindings>,
+}
+
+/// Event types that notification sinks can subscribe to.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Display)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum NotificationEvent {
+    AgentTurnComplete,
+    ApprovalRequested,
+    AutoDriveFinished,
+    BudgetAlert,
+}
+
+/// Payload shape used when posting to a webhook.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// The notification JSON, as passed to the `notify` command.
+    #[default]
+    Json,
+    /// Slack incoming-webhook message (`{"text": ...}`).
+    Slack,
+    /// Microsoft Teams incoming-webhook `MessageCard`.
+    Teams,
+}
+
+/// A destination for notifications, configured as `[[notification_sinks]]`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum NotificationSinkConfig {
+    /// POST each notification to an HTTP(S) endpoint.
+    Webhook {
+        url: String,
+        #[serde(default)]
+        format: WebhookFormat,
+        /// Events delivered to this sink. Empty means every event.
+        #[serde(default)]
+        events: Vec<NotificationEvent>,
+    },
+    /// Write each notification as one JSON line to a Unix domain socket.
+    UnixSocket {
+        path: PathBuf,
+        /// Events delivered to this sink. Empty means every event.
+        #[serde(default)]
+        events: Vec<NotificationEvent>,
+    },
+}
+
+impl NotificationSinkConfig {
+    pub fn accepts(&self, event: NotificationEvent) -> bool {
+        let events = match self {
+            Self::Webhook { events, .. } | Self::UnixSocket { events, .. } => events,
+        };
+        events.is_empty() || events.contains(&event)
+    }
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct CachedTerminalBackground {
@@ -673,7 +790,7 @@ pub struct Tui {
     #[serde(default)]
     pub highlight: HighlightConfig,
 
-    /// Whether to show reasoning content expanded by default (can be toggled with Ctrl+R/T)
+    /// Whether to show reasoning content expanded by default (can be toggled with Alt+R)
     #[serde(default)]
     pub show_reasoning: bool,
 
@@ -690,6 +807,18 @@ pub struct Tui {
     #[serde(default)]
     pub notifications: Notifications,
 
+    /// How notifications are raised in the terminal. Defaults to `osc9`.
+    #[serde(default)]
+    pub notification_method: TuiNotificationMethod,
+
+    /// Remapped keys for composer actions.
+    #[serde(default)]
+    pub keymap: TuiKeymap,
+
+    /// Composer editing style: `emacs` (default) or modal `vi`.
+    #[serde(default)]
+    pub editing_mode: EditingMode,
+
     /// Whether to use the terminal's Alternate Screen (full-screen) mode.
     /// When false, Beacon Code renders nothing and leaves the standard terminal
     /// buffer visible; users can toggle back to Alternate Screen at runtime
@@ -718,6 +847,9 @@ impl Default for Tui {
             stream: StreamConfig::default(),
             spinner: SpinnerSelection::default(),
             notifications: Notifications::default(),
+            notification_method: TuiNotificationMethod::default(),
+            keymap: TuiKeymap::default(),
+            editing_mode: EditingMode::default(),
             alternate_screen: true,
             review_auto_resolve: false,
         }
@@ -850,6 +982,16 @@ pub struct AutoDriveSettings {
     #[serde(default)]
     pub duration_limit_seconds: Option<u64>,
 
+    /// Spending limit in USD, priced with `pricing`. None means unlimited.
+    #[serde(default)]
+    pub cost_budget_usd: Option<f64>,
+
+    /// Per-model token prices used for cost accounting, keyed by model slug.
+    /// A key also matches slugs it is a prefix of (`gpt-5.1` prices
+    /// `gpt-5.1-codex` unless that has its own entry).
+    #[serde(default)]
+    pub pricing: HashMap<String, ModelPricing>,
+
     /// Maximum concurrent agents for parallel execution.
     #[serde(default = "default_max_concurrent_agents")]
     pub max_concurrent_agents: usize,
@@ -894,6 +1036,8 @@ impl Default for AutoDriveSettings {
             token_budget: None,
             turn_limit: None,
             duration_limit_seconds: None,
+            cost_budget_usd: None,
+            pricing: HashMap::new(),
             max_concurrent_agents: default_max_concurrent_agents(),
             audit_enabled: false,
             audit_path: None,
@@ -933,6 +1077,19 @@ const fn default_max_concurrent_agents() -> usize {
     8
 }
 
+/// USD prices per million tokens for one model.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input_per_mtok: f64,
+    /// Price of cached input tokens. Defaults to `input_per_mtok`.
+    #[serde(default)]
+    pub cached_input_per_mtok: Option<f64>,
+    pub output_per_mtok: f64,
+    /// Price of reasoning output tokens. Defaults to `output_per_mtok`.
+    #[serde(default)]
+    pub reasoning_output_per_mtok: Option<f64>,
+}
+
 /// High throughput pool/session defaults.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct HighThroughputSettings {
@@ -1263,6 +1420,10 @@ pub struct SandboxWorkspaceWrite {
     pub writable_roots: Vec<PathBuf>,
     #[serde(default)]
     pub network_access: bool,
+    /// Hosts reachable through the filtering proxy when `network_access` is
+    /// false, e.g. `crates.io` or `*.npmjs.org:443`.
+    #[serde(default)]
+    pub network_allowlist: Vec<String>,
     #[serde(default)]
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
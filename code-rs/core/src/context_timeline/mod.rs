@@ -6,9 +6,7 @@
 //! - Sequence-aware delta append
 //! - Hash-deduplicated snapshots
 //! - Retention/pruning hooks
-//!
-//! Phase 2A: Core implementation with feature flag gating.
-//! Not yet integrated into SessionState.
+//! - Append-only persistence next to the session rollout
 //!
 //! # Feature Flags
 //!
@@ -21,6 +19,10 @@ use crate::flags;
 mod storage;
 mod timeline;
 
+pub use storage::StorageConfig;
+pub use storage::TimelineStorage;
+pub use storage::is_timeline_path;
+pub use storage::timeline_path_for_rollout;
 pub use timeline::ContextTimeline;
 pub use timeline::DeltaEntry;
 pub use timeline::SnapshotEntry;
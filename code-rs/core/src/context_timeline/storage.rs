@@ -1,19 +1,52 @@
 //! Storage utilities for timeline persistence.
 //!
-//! Phase 2A: Placeholder for future persistence implementation.
-//! Current implementation is in-memory only.
+//! A timeline is stored as JSONL next to the session rollout. Each line holds
+//! one record (header, baseline, delta, or snapshot) together with a SHA-1
+//! checksum of the record, so [`TimelineStorage::load`] can reject corrupted
+//! files. Saving only appends records that are not on disk yet, and snapshots
+//! are written once per fingerprint. A torn final line left by a crash is cut
+//! off before the next append. Pruning rewrites the file from the pruned
+//! timeline.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 use serde::Serialize;
+use sha1::Digest;
+use sha1::Sha1;
 
 use super::timeline::ContextTimeline;
+use super::timeline::DeltaEntry;
+use super::timeline::SnapshotEntry;
+use crate::EnvironmentContextSnapshot;
+use crate::retention::RetentionPolicy;
+
+const FORMAT_VERSION: u32 = 1;
+const TIMELINE_EXTENSION: &str = "timeline.jsonl";
+
+/// Returns the timeline path that belongs to the rollout at `rollout_path`.
+pub fn timeline_path_for_rollout(rollout_path: &Path) -> PathBuf {
+    rollout_path.with_extension(TIMELINE_EXTENSION)
+}
+
+/// Whether `path` is a timeline file rather than a rollout. Both end in
+/// `.jsonl`, so rollout discovery must skip timelines explicitly.
+pub fn is_timeline_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(&format!(".{TIMELINE_EXTENSION}")))
+}
 
 /// Storage configuration for timeline persistence.
-///
-/// Phase 2A: Not yet implemented, placeholder for future phases.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct StorageConfig {
-    /// Path to the storage directory.
+    /// Path to the timeline file. `None` keeps the timeline in memory only.
     pub storage_path: Option<std::path::PathBuf>,
 
     /// Whether to enable automatic persistence on updates.
@@ -23,7 +56,6 @@ pub struct StorageConfig {
     pub compress: bool,
 }
 
-#[allow(dead_code)]
 impl StorageConfig {
     /// Creates a storage config for in-memory only (no persistence).
     pub fn memory_only() -> Self {
@@ -40,37 +72,302 @@ impl StorageConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TimelineRecord {
+    Header {
+        version: u32,
+        next_sequence: u64,
+    },
+    Baseline {
+        snapshot: EnvironmentContextSnapshot,
+    },
+    Delta(DeltaEntry),
+    Snapshot(SnapshotEntry),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRecord {
+    checksum: String,
+    record: serde_json::Value,
+}
+
+/// What has already been written to the timeline file.
+#[derive(Debug, Default)]
+struct PersistedState {
+    baseline: bool,
+    last_delta_sequence: u64,
+    snapshots: HashSet<String>,
+    /// Length of the intact prefix when the file ends in a torn record. The
+    /// next append truncates the file to it first.
+    truncate_to: Option<u64>,
+}
+
 /// Storage operations for timeline.
-///
-/// Phase 2A: Placeholder implementations that do nothing.
-/// Future phases will implement actual persistence.
-#[allow(dead_code)]
+#[derive(Debug)]
 pub struct TimelineStorage {
     config: StorageConfig,
+    persisted: PersistedState,
 }
 
-#[allow(dead_code)]
 impl TimelineStorage {
     pub fn new(config: StorageConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            persisted: PersistedState::default(),
+        }
     }
 
     /// Loads a timeline from storage.
     ///
-    /// Phase 2A: Returns empty timeline, persistence not yet implemented.
-    pub fn load(&self) -> Result<ContextTimeline, std::io::Error> {
-        Ok(ContextTimeline::new())
+    /// A missing file yields an empty timeline. A final line without a
+    /// trailing newline (from a crash mid-append) is ignored; any other
+    /// unreadable record, checksum mismatch, or out-of-order delta fails with
+    /// `InvalidData`.
+    pub fn load(&mut self) -> Result<ContextTimeline, io::Error> {
+        self.persisted = PersistedState::default();
+        let Some(path) = self.config.storage_path.as_ref() else {
+            return Ok(ContextTimeline::new());
+        };
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(ContextTimeline::new());
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut timeline = ContextTimeline::new();
+        let mut persisted = PersistedState::default();
+        let complete_len = contents.rfind('\n').map_or(0, |index| index + 1);
+        let (complete, torn_tail) = contents.split_at(complete_len);
+        if !torn_tail.is_empty() {
+            tracing::warn!(
+                "context timeline {}: ignoring incomplete final record",
+                path.display()
+            );
+            persisted.truncate_to = Some(complete_len as u64);
+        }
+        for (index, line) in complete.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = decode_record(line).map_err(|err| {
+                invalid_data(format!(
+                    "context timeline {} line {}: {err}",
+                    path.display(),
+                    index + 1
+                ))
+            })?;
+            apply_record(&mut timeline, &mut persisted, record).map_err(|err| {
+                invalid_data(format!(
+                    "context timeline {} line {}: {err}",
+                    path.display(),
+                    index + 1
+                ))
+            })?;
+        }
+
+        self.persisted = persisted;
+        Ok(timeline)
     }
 
-    /// Saves a timeline to storage.
-    ///
-    /// Phase 2A: Does nothing, persistence not yet implemented.
-    pub fn save(&self, _timeline: &ContextTimeline) -> Result<(), std::io::Error> {
+    /// Appends the parts of `timeline` that are not on disk yet.
+    pub fn save(&mut self, timeline: &ContextTimeline) -> Result<(), io::Error> {
+        let Some(path) = self.config.storage_path.as_ref() else {
+            return Ok(());
+        };
+
+        let mut records = Vec::new();
+        if !self.persisted.baseline
+            && let Some(snapshot) = timeline.baseline()
+        {
+            records.push(TimelineRecord::Baseline {
+                snapshot: snapshot.clone(),
+            });
+        }
+        records.extend(
+            timeline
+                .delta_entries()
+                .filter(|entry| entry.sequence > self.persisted.last_delta_sequence)
+                .cloned()
+                .map(TimelineRecord::Delta),
+        );
+        records.extend(
+            timeline
+                .snapshot_entries()
+                .into_iter()
+                .filter(|entry| !self.persisted.snapshots.contains(&entry.fingerprint))
+                .cloned()
+                .map(TimelineRecord::Snapshot),
+        );
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let mut buf = Vec::new();
+        for record in &records {
+            encode_record(&mut buf, record)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if let Some(len) = self.persisted.truncate_to {
+            file.set_len(len)?;
+            self.persisted.truncate_to = None;
+        }
+        file.write_all(&buf)?;
+        file.flush()?;
+
+        for record in records {
+            self.persisted.note(&record);
+        }
+        Ok(())
+    }
+
+    /// Replaces the file with exactly the contents of `timeline`.
+    pub fn rewrite(&mut self, timeline: &ContextTimeline) -> Result<(), io::Error> {
+        let Some(path) = self.config.storage_path.as_ref() else {
+            return Ok(());
+        };
+
+        let mut records = vec![TimelineRecord::Header {
+            version: FORMAT_VERSION,
+            next_sequence: timeline.next_sequence(),
+        }];
+        if let Some(snapshot) = timeline.baseline() {
+            records.push(TimelineRecord::Baseline {
+                snapshot: snapshot.clone(),
+            });
+        }
+        records.extend(timeline.delta_entries().cloned().map(TimelineRecord::Delta));
+        records.extend(
+            timeline
+                .snapshot_entries()
+                .into_iter()
+                .cloned()
+                .map(TimelineRecord::Snapshot),
+        );
+
+        let mut buf = Vec::new();
+        for record in &records {
+            encode_record(&mut buf, record)?;
+        }
+        let tmp_path = path.with_extension("jsonl.tmp");
+        {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(&buf)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, path)?;
+
+        let mut persisted = PersistedState::default();
+        for record in &records {
+            persisted.note(record);
+        }
+        self.persisted = persisted;
         Ok(())
     }
 
+    /// Prunes `timeline` with `policy` and rewrites the file when anything
+    /// was removed. Returns the number of removed entries.
+    pub fn prune(
+        &mut self,
+        timeline: &mut ContextTimeline,
+        policy: &RetentionPolicy,
+    ) -> Result<usize, io::Error> {
+        let removed = timeline.prune(policy);
+        if removed > 0 {
+            self.rewrite(timeline)?;
+        }
+        Ok(removed)
+    }
+
     /// Returns the storage config.
     pub fn config(&self) -> &StorageConfig {
         &self.config
     }
 }
+
+impl PersistedState {
+    fn note(&mut self, record: &TimelineRecord) {
+        match record {
+            TimelineRecord::Header { .. } => {}
+            TimelineRecord::Baseline { .. } => self.baseline = true,
+            TimelineRecord::Delta(entry) => {
+                self.last_delta_sequence = self.last_delta_sequence.max(entry.sequence);
+            }
+            TimelineRecord::Snapshot(entry) => {
+                self.snapshots.insert(entry.fingerprint.clone());
+            }
+        }
+    }
+}
+
+fn apply_record(
+    timeline: &mut ContextTimeline,
+    persisted: &mut PersistedState,
+    record: TimelineRecord,
+) -> Result<(), String> {
+    persisted.note(&record);
+    match record {
+        TimelineRecord::Header {
+            version,
+            next_sequence,
+        } => {
+            if version > FORMAT_VERSION {
+                return Err(format!("unsupported format version {version}"));
+            }
+            timeline.advance_sequence_to(next_sequence);
+        }
+        TimelineRecord::Baseline { snapshot } => {
+            timeline
+                .add_baseline_once(snapshot)
+                .map_err(|err| err.to_string())?;
+        }
+        TimelineRecord::Delta(entry) => {
+            timeline
+                .restore_delta(entry)
+                .map_err(|err| err.to_string())?;
+        }
+        TimelineRecord::Snapshot(entry) => {
+            if entry.snapshot.fingerprint() != entry.fingerprint {
+                return Err(format!(
+                    "snapshot does not match fingerprint {}",
+                    entry.fingerprint
+                ));
+            }
+            timeline.restore_snapshot(entry);
+        }
+    }
+    Ok(())
+}
+
+fn encode_record(buf: &mut Vec<u8>, record: &TimelineRecord) -> Result<(), io::Error> {
+    let record = serde_json::to_value(record)?;
+    let stored = StoredRecord {
+        checksum: checksum(&record)?,
+        record,
+    };
+    serde_json::to_writer(&mut *buf, &stored)?;
+    buf.push(b'\n');
+    Ok(())
+}
+
+fn decode_record(line: &str) -> Result<TimelineRecord, String> {
+    let stored: StoredRecord = serde_json::from_str(line).map_err(|err| err.to_string())?;
+    let expected = checksum(&stored.record).map_err(|err| err.to_string())?;
+    if expected != stored.checksum {
+        return Err("checksum mismatch".to_string());
+    }
+    serde_json::from_value(stored.record).map_err(|err| err.to_string())
+}
+
+fn checksum(record: &serde_json::Value) -> Result<String, io::Error> {
+    let encoded = serde_json::to_vec(record)?;
+    let mut sha = Sha1::new();
+    sha.update(encoded);
+    Ok(format!("{:x}", sha.finalize()))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -388,3 +388,199 @@ fn test_snapshot_entry_serialization() {
     assert_eq!(deserialized.fingerprint, fingerprint);
     assert_eq!(deserialized.snapshot, snapshot);
 }
+
+// Persistence tests
+
+/// Builds a timeline with a baseline and `deltas` chained cwd changes,
+/// recording every resulting snapshot.
+fn build_chained_timeline(deltas: usize) -> ContextTimeline {
+    let mut timeline = ContextTimeline::new();
+    let mut current = create_test_snapshot("/repo", Some("main"));
+    timeline.add_baseline_once(current.clone()).unwrap();
+    timeline.record_snapshot(current.clone()).unwrap();
+    for i in 0..deltas {
+        let delta = create_test_delta(&current.fingerprint(), &format!("/repo-{i}"));
+        let seq = timeline.next_sequence();
+        timeline.apply_delta(seq, delta.clone()).unwrap();
+        current = current.apply_delta(&delta);
+        timeline.record_snapshot(current.clone()).unwrap();
+    }
+    timeline
+}
+
+fn storage_at(path: &std::path::Path) -> TimelineStorage {
+    TimelineStorage::new(StorageConfig::with_path(path.to_path_buf()))
+}
+
+fn assert_same_timeline(actual: &ContextTimeline, expected: &ContextTimeline) {
+    assert_eq!(actual.baseline(), expected.baseline());
+    assert_eq!(actual.delta_sequences(), expected.delta_sequences());
+    for sequence in expected.delta_sequences() {
+        assert_eq!(actual.get_delta(sequence), expected.get_delta(sequence));
+    }
+    let mut fingerprints = actual.snapshot_fingerprints();
+    fingerprints.sort();
+    let mut expected_fingerprints = expected.snapshot_fingerprints();
+    expected_fingerprints.sort();
+    assert_eq!(fingerprints, expected_fingerprints);
+    assert_eq!(actual.next_sequence(), expected.next_sequence());
+    assert_eq!(actual.latest_snapshot(), expected.latest_snapshot());
+}
+
+#[test]
+fn storage_round_trips_timeline() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = timeline_path_for_rollout(&dir.path().join("rollout-1.jsonl"));
+    assert_eq!(path, dir.path().join("rollout-1.timeline.jsonl"));
+    assert!(is_timeline_path(&path));
+    assert!(!is_timeline_path(&dir.path().join("rollout-1.jsonl")));
+
+    let timeline = build_chained_timeline(3);
+    storage_at(&path).save(&timeline).unwrap();
+
+    let loaded = storage_at(&path).load().unwrap();
+    assert_same_timeline(&loaded, &timeline);
+    assert_eq!(
+        loaded.latest_snapshot().and_then(|snapshot| snapshot.cwd),
+        Some("/repo-2".to_string())
+    );
+}
+
+#[test]
+fn storage_load_of_missing_file_is_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let loaded = storage_at(&dir.path().join("missing.timeline.jsonl"))
+        .load()
+        .unwrap();
+    assert!(loaded.is_empty());
+
+    let loaded = TimelineStorage::new(StorageConfig::memory_only())
+        .load()
+        .unwrap();
+    assert!(loaded.is_empty());
+}
+
+#[test]
+fn storage_appends_only_new_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rollout.timeline.jsonl");
+    let mut storage = storage_at(&path);
+
+    let mut timeline = build_chained_timeline(1);
+    storage.save(&timeline).unwrap();
+    let first = std::fs::read_to_string(&path).unwrap();
+    // baseline + delta + two snapshots
+    assert_eq!(first.lines().count(), 4);
+
+    storage.save(&timeline).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), first);
+
+    // Returning to the baseline environment adds a delta but no snapshot.
+    let latest = timeline.latest_snapshot().unwrap();
+    let baseline = timeline.baseline().cloned().unwrap();
+    let delta = baseline.diff_from(&latest);
+    let seq = timeline.next_sequence();
+    timeline.apply_delta(seq, delta).unwrap();
+    assert!(!timeline.record_snapshot(baseline).unwrap());
+    storage.save(&timeline).unwrap();
+
+    let second = std::fs::read_to_string(&path).unwrap();
+    assert!(second.starts_with(&first));
+    assert_eq!(second.lines().count(), 5);
+    assert_same_timeline(&storage_at(&path).load().unwrap(), &timeline);
+}
+
+#[test]
+fn storage_rejects_tampered_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rollout.timeline.jsonl");
+    storage_at(&path).save(&build_chained_timeline(2)).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, contents.replacen("/repo-0", "/evil", 1)).unwrap();
+
+    let err = storage_at(&path).load().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("checksum mismatch"), "{err}");
+}
+
+#[test]
+fn storage_ignores_torn_final_record() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rollout.timeline.jsonl");
+    let timeline = build_chained_timeline(2);
+    storage_at(&path).save(&timeline).unwrap();
+
+    let mut contents = std::fs::read_to_string(&path).unwrap();
+    contents.push_str("{\"checksum\":\"abc\",\"record\":{\"type\":\"de");
+    std::fs::write(&path, contents).unwrap();
+
+    assert_same_timeline(&storage_at(&path).load().unwrap(), &timeline);
+}
+
+#[test]
+fn storage_truncates_torn_final_record_before_appending() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rollout.timeline.jsonl");
+    storage_at(&path).save(&build_chained_timeline(1)).unwrap();
+    let intact = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, format!("{intact}{{\"checksum\":\"abc\",\"rec")).unwrap();
+
+    let mut storage = storage_at(&path);
+    let mut timeline = storage.load().unwrap();
+    let latest = timeline.latest_snapshot().unwrap();
+    let delta = create_test_delta(&latest.fingerprint(), "/repo-resumed");
+    let seq = timeline.next_sequence();
+    timeline.apply_delta(seq, delta).unwrap();
+    storage.save(&timeline).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.starts_with(&intact));
+    assert!(!contents.contains("\"rec{"));
+    assert_same_timeline(&storage_at(&path).load().unwrap(), &timeline);
+}
+
+#[test]
+fn storage_prune_rewrites_file_and_keeps_sequence() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rollout.timeline.jsonl");
+    let mut storage = storage_at(&path);
+    let mut timeline = build_chained_timeline(5);
+    storage.save(&timeline).unwrap();
+
+    let policy = crate::retention::RetentionPolicy {
+        max_env_deltas: 2,
+        ..Default::default()
+    };
+    // Three deltas plus the two snapshots only they referenced.
+    assert_eq!(storage.prune(&mut timeline, &policy).unwrap(), 5);
+    assert_eq!(timeline.delta_sequences(), vec![4, 5]);
+    assert_eq!(timeline.snapshot_count(), 4);
+    assert_eq!(timeline.next_sequence(), 6);
+
+    let loaded = storage_at(&path).load().unwrap();
+    assert_same_timeline(&loaded, &timeline);
+
+    // The pruned timeline keeps accepting deltas where it left off.
+    let mut loaded = loaded;
+    let latest = loaded.latest_snapshot().unwrap();
+    let delta = create_test_delta(&latest.fingerprint(), "/repo-next");
+    loaded.apply_delta(6, delta).unwrap();
+    assert_eq!(loaded.next_sequence(), 7);
+}
+
+#[test]
+fn prune_enforces_byte_budget() {
+    let mut timeline = build_chained_timeline(4);
+    let policy = crate::retention::RetentionPolicy {
+        max_env_deltas: 10,
+        max_total_bytes: 0,
+        ..Default::default()
+    };
+
+    timeline.prune(&policy);
+    assert_eq!(timeline.delta_count(), 0);
+    assert!(timeline.baseline().is_some());
+    assert_eq!(timeline.latest_snapshot().as_ref(), timeline.baseline());
+    assert_eq!(timeline.next_sequence(), 5);
+}
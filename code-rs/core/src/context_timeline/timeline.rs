@@ -2,6 +2,7 @@
 
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use code_protocol::models::ResponseItem;
 use serde::Deserialize;
@@ -10,6 +11,7 @@ use thiserror::Error;
 
 use crate::EnvironmentContextDelta;
 use crate::EnvironmentContextSnapshot;
+use crate::retention::RetentionPolicy;
 
 #[derive(Debug, Error)]
 pub enum TimelineError {
@@ -230,8 +232,111 @@ impl ContextTimeline {
         Ok(items)
     }
 
+    /// Returns the environment the model saw last: the result of the newest
+    /// delta applied to its base snapshot, or the baseline when there are no
+    /// deltas.
+    pub fn latest_snapshot(&self) -> Option<EnvironmentContextSnapshot> {
+        match self.deltas.values().next_back() {
+            Some(entry) => self
+                .snapshots
+                .get(&entry.delta.base_fingerprint)
+                .map(|base| base.snapshot.apply_delta(&entry.delta)),
+            None => self.baseline.clone(),
+        }
+    }
+
+    /// Delta entries in sequence order.
+    pub(crate) fn delta_entries(&self) -> impl Iterator<Item = &DeltaEntry> {
+        self.deltas.values()
+    }
+
+    /// Snapshot entries, oldest first.
+    pub(crate) fn snapshot_entries(&self) -> Vec<&SnapshotEntry> {
+        let mut entries: Vec<&SnapshotEntry> = self.snapshots.values().collect();
+        entries.sort_by(|a, b| {
+            a.recorded_at
+                .cmp(&b.recorded_at)
+                .then_with(|| a.fingerprint.cmp(&b.fingerprint))
+        });
+        entries
+    }
+
+    /// Re-inserts a persisted delta, keeping its sequence and timestamp.
+    /// Gaps are allowed because older deltas may have been pruned.
+    pub(crate) fn restore_delta(&mut self, entry: DeltaEntry) -> Result<(), TimelineError> {
+        if self.baseline.is_none() {
+            return Err(TimelineError::BaselineNotSet);
+        }
+        if entry.sequence < self.next_sequence {
+            return Err(TimelineError::DeltaSequenceOutOfOrder {
+                expected: self.next_sequence,
+                actual: entry.sequence,
+            });
+        }
+        self.next_sequence = entry.sequence + 1;
+        self.deltas.insert(entry.sequence, entry);
+        Ok(())
+    }
+
+    /// Re-inserts a persisted snapshot. Returns `false` for duplicates.
+    pub(crate) fn restore_snapshot(&mut self, entry: SnapshotEntry) -> bool {
+        if self.snapshots.contains_key(&entry.fingerprint) {
+            return false;
+        }
+        self.snapshots.insert(entry.fingerprint.clone(), entry);
+        true
+    }
+
+    /// Raises the next expected sequence, e.g. after the deltas before it
+    /// were pruned.
+    pub(crate) fn advance_sequence_to(&mut self, next_sequence: u64) {
+        self.next_sequence = self.next_sequence.max(next_sequence);
+    }
+
     // Pruning entry points
 
+    /// Drops old deltas and unreferenced snapshots according to `policy`.
+    ///
+    /// Keeps the baseline, the newest `max_env_deltas` deltas and the
+    /// snapshots those deltas are based on, then drops the oldest deltas
+    /// until the timeline fits in `max_total_bytes`. The sequence counter is
+    /// left untouched. Returns the number of entries removed.
+    pub fn prune(&mut self, policy: &RetentionPolicy) -> usize {
+        let mut removed = 0;
+
+        while self.deltas.len() > policy.max_env_deltas {
+            self.deltas.pop_first();
+            removed += 1;
+        }
+        removed += self.prune_unreferenced_snapshots();
+
+        while self.estimated_bytes() > policy.max_total_bytes && !self.deltas.is_empty() {
+            self.deltas.pop_first();
+            removed += 1 + self.prune_unreferenced_snapshots();
+        }
+
+        removed
+    }
+
+    fn prune_unreferenced_snapshots(&mut self) -> usize {
+        let mut referenced: HashSet<String> = self
+            .deltas
+            .values()
+            .map(|entry| entry.delta.base_fingerprint.clone())
+            .collect();
+        if let Some(baseline) = &self.baseline {
+            referenced.insert(baseline.fingerprint());
+        }
+        if let Some(latest) = self.latest_snapshot() {
+            referenced.insert(latest.fingerprint());
+        }
+
+        let before = self.snapshots.len();
+        self.snapshots
+            .retain(|fingerprint, _| referenced.contains(fingerprint));
+        before - self.snapshots.len()
+    }
+
     /// Estimates the total memory usage in bytes.
     ///
     /// This is a rough estimate for Phase 2A. Future phases can refine this.
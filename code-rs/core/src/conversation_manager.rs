@@ -12,10 +12,15 @@ use crate::protocol::EventMsg;
 use crate::protocol::SessionConfiguredEvent;
 use crate::rollout::RolloutRecorder;
 use code_protocol::ConversationId;
+use code_protocol::models::ContentItem;
 use code_protocol::models::ResponseItem;
+use code_protocol::protocol::BROWSER_SNAPSHOT_OPEN_TAG;
+use code_protocol::protocol::ENVIRONMENT_CONTEXT_DELTA_OPEN_TAG;
+use code_protocol::protocol::ENVIRONMENT_CONTEXT_OPEN_TAG;
 use code_protocol::protocol::InitialHistory;
 use code_protocol::protocol::RolloutItem;
 use code_protocol::protocol::SessionSource;
+use code_protocol::protocol::USER_INSTRUCTIONS_OPEN_TAG;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -156,13 +161,35 @@ impl ConversationManager {
     pub async fn fork_conversation(
         &self,
         num_messages_to_drop: usize,
-        mut config: Config,
+        config: Config,
         path: PathBuf,
     ) -> CodexResult<NewConversation> {
         // Compute the prefix up to the cut point.
         let history = RolloutRecorder::get_rollout_history(&path).await?;
         let history = truncate_after_dropping_last_messages(history, num_messages_to_drop);
+        self.spawn_from_history(history, config).await
+    }
 
+    /// Fork an existing conversation keeping only its first `turns` user
+    /// turns (and everything recorded before the next one). Forking past the
+    /// last turn copies the whole conversation. The new conversation has a
+    /// fresh id.
+    pub async fn fork_conversation_at_turn(
+        &self,
+        turns: usize,
+        config: Config,
+        path: PathBuf,
+    ) -> CodexResult<NewConversation> {
+        let history = RolloutRecorder::get_rollout_history(&path).await?;
+        let history = truncate_to_first_turns(history, turns);
+        self.spawn_from_history(history, config).await
+    }
+
+    async fn spawn_from_history(
+        &self,
+        history: InitialHistory,
+        mut config: Config,
+    ) -> CodexResult<NewConversation> {
         // If there is no prior history to seed, just start a fresh conversation.
         if matches!(history, InitialHistory::New) {
             return self
@@ -184,8 +211,14 @@ impl ConversationManager {
         .await
         .map_err(CodexErr::Io)?;
 
-        // Persist rollout items to seed the resumed conversation.
-        let rollout_items = history.get_rollout_items();
+        // Persist rollout items to seed the resumed conversation. The new
+        // recorder already wrote its own session meta; copying the source's
+        // would make the resumed conversation take over the source's id.
+        let rollout_items: Vec<RolloutItem> = history
+            .get_rollout_items()
+            .into_iter()
+            .filter(|item| !matches!(item, RolloutItem::SessionMeta(_)))
+            .collect();
         if !rollout_items.is_empty() {
             recorder
                 .record_items(&rollout_items)
@@ -207,6 +240,56 @@ impl ConversationManager {
     }
 }
 
+/// Whether `item` is a message the user sent. The session also records
+/// user-role messages of its own (environment context, system status and
+/// screenshots, browser snapshots, review results); those do not start a turn.
+fn is_user_turn(item: &RolloutItem) -> bool {
+    let RolloutItem::ResponseItem(ResponseItem::Message { role, content, .. }) = item else {
+        return false;
+    };
+    role == "user"
+        && content.iter().any(|content| match content {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                !is_injected_text(text)
+            }
+            ContentItem::InputImage { .. } => false,
+        })
+}
+
+fn is_injected_text(text: &str) -> bool {
+    let text = text.trim_start();
+    text.is_empty()
+        || text.starts_with("== System Status ==")
+        || [
+            ENVIRONMENT_CONTEXT_OPEN_TAG,
+            ENVIRONMENT_CONTEXT_DELTA_OPEN_TAG,
+            BROWSER_SNAPSHOT_OPEN_TAG,
+            USER_INSTRUCTIONS_OPEN_TAG,
+            "<user_action>",
+        ]
+        .iter()
+        .any(|tag| text.starts_with(tag))
+}
+
+/// Return a prefix of `items` that ends right before the `n`th user message
+/// (counting from zero), i.e. the first `n` turns.
+fn truncate_to_first_turns(history: InitialHistory, n: usize) -> InitialHistory {
+    let items: Vec<RolloutItem> = history.get_rollout_items();
+    let cut_idx = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| is_user_turn(item))
+        .nth(n)
+        .map_or(items.len(), |(idx, _)| idx);
+    let rolled: Vec<RolloutItem> = items.into_iter().take(cut_idx).collect();
+
+    if rolled.is_empty() {
+        InitialHistory::New
+    } else {
+        InitialHistory::Forked(rolled)
+    }
+}
+
 /// Return a prefix of `items` obtained by dropping the last `n` user messages
 /// and all items that follow them.
 fn truncate_after_dropping_last_messages(history: InitialHistory, n: usize) -> InitialHistory {
@@ -220,9 +303,7 @@ fn truncate_after_dropping_last_messages(history: InitialHistory, n: usize) -> I
     // Find indices of user message inputs in rollout order.
     let mut user_positions: Vec<usize> = Vec::new();
     for (idx, item) in items.iter().enumerate() {
-        if let RolloutItem::ResponseItem(ResponseItem::Message { role, .. }) = item
-            && role == "user"
-        {
+        if is_user_turn(item) {
             user_positions.push(idx);
         }
     }
@@ -246,9 +327,7 @@ fn truncate_after_dropping_last_messages(history: InitialHistory, n: usize) -> I
 #[cfg(test)]
 mod tests {
     use super::*;
-    use code_protocol::models::ContentItem;
     use code_protocol::models::ReasoningItemReasoningSummary;
-    use code_protocol::models::ResponseItem;
 
     fn user_msg(text: &str) -> ResponseItem {
         ResponseItem::Message {
@@ -320,4 +399,109 @@ mod tests {
         let truncated2 = truncate_after_dropping_last_messages(InitialHistory::Forked(initial2), 2);
         assert!(matches!(truncated2, InitialHistory::New));
     }
+
+    #[test]
+    fn keeps_first_turns() {
+        let items = vec![
+            user_msg("u1"),
+            assistant_msg("a1"),
+            user_msg("u2"),
+            assistant_msg("a2"),
+            user_msg("u3"),
+            assistant_msg("a3"),
+        ];
+        let history = || {
+            InitialHistory::Forked(
+                items
+                    .iter()
+                    .cloned()
+                    .map(RolloutItem::ResponseItem)
+                    .collect(),
+            )
+        };
+
+        let got = truncate_to_first_turns(history(), 2).get_rollout_items();
+        let expected: Vec<RolloutItem> = items[..4]
+            .iter()
+            .cloned()
+            .map(RolloutItem::ResponseItem)
+            .collect();
+        assert_eq!(
+            serde_json::to_value(&got).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+
+        // Forking past the end keeps everything; zero turns keeps nothing.
+        let got = truncate_to_first_turns(history(), 10).get_rollout_items();
+        assert_eq!(got.len(), items.len());
+        assert!(matches!(
+            truncate_to_first_turns(history(), 0),
+            InitialHistory::New
+        ));
+    }
+
+    #[test]
+    fn injected_user_messages_do_not_count_as_turns() {
+        let injected = |content: Vec<ContentItem>| ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content,
+        };
+        let text = |text: &str| ContentItem::InputText {
+            text: text.to_string(),
+        };
+        let items = vec![
+            injected(vec![text(
+                "<user_instructions>\nbe terse\n</user_instructions>",
+            )]),
+            injected(vec![text(
+                "<environment_context>\n<cwd>/repo</cwd>\n</environment_context>",
+            )]),
+            user_msg("u1"),
+            assistant_msg("a1"),
+            injected(vec![
+                text("== System Status ==\n [automatic message added by system]"),
+                ContentItem::InputImage {
+                    image_url: "data:image/png;base64,AAAA".to_string(),
+                },
+            ]),
+            injected(vec![ContentItem::InputImage {
+                image_url: "data:image/png;base64,BBBB".to_string(),
+            }]),
+            user_msg("u2"),
+            assistant_msg("a2"),
+            injected(vec![text(
+                "<user_action>\n  <action>review</action>\n</user_action>",
+            )]),
+            assistant_msg("a3"),
+        ];
+        let history = || {
+            InitialHistory::Forked(
+                items
+                    .iter()
+                    .cloned()
+                    .map(RolloutItem::ResponseItem)
+                    .collect(),
+            )
+        };
+
+        // One turn keeps the context messages, u1 and its status items.
+        assert_eq!(
+            truncate_to_first_turns(history(), 1)
+                .get_rollout_items()
+                .len(),
+            6
+        );
+        // Dropping the last turn cuts at u2, not at the review results.
+        assert_eq!(
+            truncate_after_dropping_last_messages(history(), 1)
+                .get_rollout_items()
+                .len(),
+            6
+        );
+        assert!(matches!(
+            truncate_after_dropping_last_messages(history(), 3),
+            InitialHistory::New
+        ));
+    }
 }
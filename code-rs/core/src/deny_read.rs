@@ -0,0 +1,318 @@
+//! Resolution and matching of the sandbox `deny_read` list.
+//!
+//! Entries are paths or globs. `~` expands to the home directory and relative
+//! entries are resolved against the sandbox cwd. `*` and `?` match within a
+//! single path component, while `**` matches any number of components. A
+//! match denies the path itself and everything beneath it.
+//!
+//! Seatbelt matches the generated regexes directly. The Linux sandbox
+//! expands the globs to existing paths with
+//! [`DenyReadMatcher::existing_paths`] and covers each one with an empty,
+//! unreadable mount in the command's own mount namespace.
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use regex_lite::Regex;
+use walkdir::WalkDir;
+
+/// Error text that tools print when the kernel refuses to open a file.
+const PERMISSION_ERRORS: &[&str] = &["Permission denied", "Operation not permitted"];
+
+/// Directories that glob expansion does not descend into. They are large,
+/// hold version-control or dependency files rather than project secrets, and
+/// walking them would slow down every sandboxed command. Patterns whose
+/// literal prefix already points inside one of them still apply.
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target", ".venv"];
+
+#[derive(Debug, Clone)]
+struct DenyReadPattern {
+    /// Absolute glob, with its wildcard-free prefix canonicalized.
+    glob: PathBuf,
+    regex_source: String,
+    regex: Regex,
+}
+
+/// The `deny_read` entries of a sandbox policy, resolved against a cwd.
+#[derive(Debug, Clone, Default)]
+pub struct DenyReadMatcher {
+    patterns: Vec<DenyReadPattern>,
+}
+
+impl DenyReadMatcher {
+    pub fn new(patterns: &[String], cwd: &Path) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|pattern| resolve_pattern(pattern, cwd))
+            .filter_map(|glob| {
+                let regex_source = glob_to_regex(&glob);
+                let regex = Regex::new(&regex_source).ok()?;
+                Some(DenyReadPattern {
+                    glob,
+                    regex_source,
+                    regex,
+                })
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Anchored regexes matching each denied path and its descendants.
+    pub fn regex_sources(&self) -> impl Iterator<Item = &str> {
+        self.patterns
+            .iter()
+            .map(|pattern| pattern.regex_source.as_str())
+    }
+
+    /// Whether reading `path` (absolute) is denied. Both the path as given
+    /// and its canonical form are checked.
+    pub fn is_denied(&self, path: &Path) -> bool {
+        let lexical: PathBuf = path.components().collect();
+        if self.matches(&lexical) {
+            return true;
+        }
+        path.canonicalize()
+            .is_ok_and(|canonical| self.matches(&canonical))
+    }
+
+    /// Existing files and directories matched by the patterns. Matched
+    /// directories are returned whole rather than walked, and glob expansion
+    /// stays on the prefix's file system and skips [`SKIPPED_DIRS`].
+    pub fn existing_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        for pattern in &self.patterns {
+            let (prefix, rest) = split_literal_prefix(&pattern.glob);
+            if rest.is_none() {
+                if prefix.symlink_metadata().is_ok() {
+                    paths.push(prefix);
+                }
+                continue;
+            }
+
+            let mut entries = WalkDir::new(&prefix)
+                .follow_links(false)
+                .same_file_system(true)
+                .into_iter();
+            while let Some(entry) = entries.next() {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                let is_dir = entry.file_type().is_dir();
+                if pattern.regex.is_match(&entry.path().to_string_lossy()) {
+                    paths.push(entry.path().to_path_buf());
+                    if is_dir {
+                        entries.skip_current_dir();
+                    }
+                } else if is_dir
+                    && entry.depth() > 0
+                    && SKIPPED_DIRS.iter().any(|dir| entry.file_name() == *dir)
+                {
+                    entries.skip_current_dir();
+                }
+            }
+        }
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Looks for a denied path named on a "Permission denied" line of
+    /// `output`, resolving relative paths against `cwd`.
+    pub fn denied_path_in_output(&self, output: &str, cwd: &Path) -> Option<PathBuf> {
+        if self.is_empty() {
+            return None;
+        }
+        output
+            .lines()
+            .filter(|line| PERMISSION_ERRORS.iter().any(|error| line.contains(error)))
+            .flat_map(|line| line.split(": "))
+            .map(|part| part.trim().trim_matches(|c| matches!(c, '\'' | '"' | '`')))
+            .filter(|part| !part.is_empty())
+            .filter_map(|part| expand_path(part, cwd))
+            .find(|path| self.is_denied(path))
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.regex.is_match(&path))
+    }
+}
+
+/// Resolves `pattern` to an absolute glob. The wildcard-free prefix is
+/// canonicalized when it exists so that matches agree with the paths the
+/// kernel reports (e.g. `/private/tmp` rather than `/tmp` on macOS).
+fn resolve_pattern(pattern: &str, cwd: &Path) -> Option<PathBuf> {
+    let path = expand_path(pattern.trim(), cwd)?;
+    let (prefix, rest) = split_literal_prefix(&path);
+    let prefix = prefix.canonicalize().unwrap_or(prefix);
+    Some(match rest {
+        Some(rest) => prefix.join(rest),
+        None => prefix,
+    })
+}
+
+fn expand_path(path: &str, cwd: &Path) -> Option<PathBuf> {
+    if path.is_empty() {
+        return None;
+    }
+    if path == "~" {
+        return dirs::home_dir();
+    }
+    if let Some(rest) = path.strip_prefix("~/") {
+        return dirs::home_dir().map(|home| home.join(rest));
+    }
+    let path = Path::new(path);
+    Some(if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    })
+}
+
+/// Splits `path` before its first component containing a wildcard.
+fn split_literal_prefix(path: &Path) -> (PathBuf, Option<PathBuf>) {
+    let mut prefix = PathBuf::new();
+    let mut components = path.components();
+    while let Some(component) = components.next() {
+        if let Component::Normal(name) = component
+            && name.to_string_lossy().contains(['*', '?'])
+        {
+            let rest: PathBuf = std::iter::once(component).chain(components).collect();
+            return (prefix, Some(rest));
+        }
+        prefix.push(component);
+    }
+    (prefix, None)
+}
+
+/// Translates an absolute glob into an anchored regex that also matches
+/// everything beneath the matched path.
+fn glob_to_regex(glob: &Path) -> String {
+    let glob = glob.to_string_lossy();
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '.' | '+' | '(' | ')' | '|' | '^' | '$' | '[' | ']' | '{' | '}' | '\\' => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            _ => regex.push(c),
+        }
+    }
+    regex.push_str("(/.*)?$");
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn matcher(patterns: &[&str], cwd: &Path) -> DenyReadMatcher {
+        let patterns: Vec<String> = patterns.iter().map(|p| (*p).to_string()).collect();
+        DenyReadMatcher::new(&patterns, cwd)
+    }
+
+    #[test]
+    fn glob_translation() {
+        assert_eq!(
+            glob_to_regex(Path::new("/home/me/.ssh")),
+            r"^/home/me/\.ssh(/.*)?$"
+        );
+        assert_eq!(
+            glob_to_regex(Path::new("/repo/**/.env*")),
+            r"^/repo/(.*/)?\.env[^/]*(/.*)?$"
+        );
+    }
+
+    #[test]
+    fn relative_globs_match_anywhere_below_cwd() {
+        let cwd = Path::new("/nonexistent/repo");
+        let m = matcher(&["**/.env*"], cwd);
+
+        assert!(m.is_denied(Path::new("/nonexistent/repo/.env")));
+        assert!(m.is_denied(Path::new("/nonexistent/repo/app/.env.local")));
+        assert!(m.is_denied(Path::new("/nonexistent/repo/./app/.env")));
+        assert!(!m.is_denied(Path::new("/nonexistent/repo/app/env.rs")));
+        assert!(!m.is_denied(Path::new("/nonexistent/other/.env")));
+    }
+
+    #[test]
+    fn directories_deny_their_contents() {
+        let m = matcher(&["/nonexistent/home/.ssh"], Path::new("/"));
+
+        assert!(m.is_denied(Path::new("/nonexistent/home/.ssh")));
+        assert!(m.is_denied(Path::new("/nonexistent/home/.ssh/id_ed25519")));
+        assert!(!m.is_denied(Path::new("/nonexistent/home/.sshrc")));
+    }
+
+    #[test]
+    fn existing_paths_expands_globs() {
+        let tmp = TempDir::new().expect("tempdir");
+        let root = tmp.path().canonicalize().expect("canonical tempdir");
+        std::fs::create_dir_all(root.join("app/secrets")).expect("mkdir");
+        std::fs::write(root.join(".env"), "A=1").expect("write");
+        std::fs::write(root.join("app/.env.local"), "B=2").expect("write");
+        std::fs::write(root.join("app/main.rs"), "").expect("write");
+        std::fs::write(root.join("app/secrets/key"), "").expect("write");
+        std::fs::create_dir_all(root.join("node_modules/pkg")).expect("mkdir");
+        std::fs::write(root.join("node_modules/pkg/.env"), "C=3").expect("write");
+
+        let m = matcher(&["**/.env*", "app/secrets", "missing"], &root);
+
+        assert_eq!(
+            m.existing_paths(),
+            vec![
+                root.join(".env"),
+                root.join("app/.env.local"),
+                root.join("app/secrets"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_denied_path_in_error_output() {
+        let cwd = Path::new("/nonexistent/repo");
+        let m = matcher(&["**/.env*", "/nonexistent/home/.aws"], cwd);
+
+        assert_eq!(
+            m.denied_path_in_output("cat: .env: Permission denied\n", cwd),
+            Some(PathBuf::from("/nonexistent/repo/.env"))
+        );
+        assert_eq!(
+            m.denied_path_in_output(
+                "PermissionError: [Errno 13] Permission denied: '/nonexistent/home/.aws/credentials'",
+                cwd
+            ),
+            Some(PathBuf::from("/nonexistent/home/.aws/credentials"))
+        );
+        assert_eq!(
+            m.denied_path_in_output("cat: README.md: Permission denied\n", cwd),
+            None
+        );
+        assert_eq!(
+            m.denied_path_in_output("cat: .env: No such file", cwd),
+            None
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::exec::ExecToolCallOutput;
+use crate::exec_limits::ResourceLimitHit;
 use chrono::DateTime;
 use chrono::Duration as ChronoDuration;
 use chrono::Utc;
@@ -39,6 +40,13 @@ pub enum SandboxErr {
     #[error("command was killed by a signal")]
     Signal(i32),
 
+    /// Command ran into one of the configured `[exec_limits]`
+    #[error("command exceeded its {limit}")]
+    ResourceLimit {
+        limit: ResourceLimitHit,
+        output: Box<ExecToolCallOutput>,
+    },
+
     /// Error from linux landlock
     #[error("Landlock was not able to fully enforce all sandbox rules")]
     LandlockRestrict,
@@ -315,7 +323,8 @@ impl CodexErr {
 
 pub fn get_error_message_ui(e: &CodexErr) -> String {
     match e {
-        CodexErr::Sandbox(SandboxErr::Denied { output }) => output.stderr.text.clone(),
+        CodexErr::Sandbox(SandboxErr::Denied { output })
+        | CodexErr::Sandbox(SandboxErr::ResourceLimit { output, .. }) => output.stderr.text.clone(),
         // Timeouts are not sandbox errors from a UX perspective; present them plainly
         CodexErr::Sandbox(SandboxErr::Timeout { output }) => format!(
             "error: command timed out after {} ms",
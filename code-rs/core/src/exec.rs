@@ -17,9 +17,11 @@ use tokio::io::BufReader;
 use tokio::process::Child;
 
 use crate::codex::Session;
+use crate::deny_read::DenyReadMatcher;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::exec_limits::ExecResourceLimits;
 use crate::landlock::spawn_command_under_linux_sandbox;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
@@ -89,6 +91,7 @@ pub struct ExecParams {
     pub env: HashMap<String, String>,
     pub with_escalated_permissions: Option<bool>,
     pub justification: Option<String>,
+    pub resource_limits: ExecResourceLimits,
 }
 
 impl ExecParams {
@@ -135,6 +138,8 @@ pub async fn process_exec_tool_call(
     let start = Instant::now();
 
     let timeout_duration = params.maybe_timeout_duration();
+    let command_cwd = params.cwd.clone();
+    let resource_limits = params.resource_limits;
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
@@ -144,6 +149,7 @@ pub async fn process_exec_tool_call(
                 command,
                 cwd: command_cwd,
                 env,
+                resource_limits,
                 ..
             } = params;
             let child = spawn_command_under_seatbelt(
@@ -151,6 +157,7 @@ pub async fn process_exec_tool_call(
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                resource_limits,
                 StdioPolicy::RedirectForShellTool,
                 env,
             )
@@ -162,6 +169,7 @@ pub async fn process_exec_tool_call(
                 command,
                 cwd: command_cwd,
                 env,
+                resource_limits,
                 ..
             } = params;
 
@@ -174,6 +182,7 @@ pub async fn process_exec_tool_call(
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                resource_limits,
                 StdioPolicy::RedirectForShellTool,
                 env,
             )
@@ -187,6 +196,8 @@ pub async fn process_exec_tool_call(
         Ok(raw_output) => {
             #[allow(unused_mut)]
             let mut timed_out = raw_output.timed_out;
+            #[allow(unused_mut)]
+            let mut killed_by_signal = None;
 
             #[cfg(target_family = "unix")]
             {
@@ -194,7 +205,7 @@ pub async fn process_exec_tool_call(
                     if signal == TIMEOUT_CODE {
                         timed_out = true;
                     } else {
-                        return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
+                        killed_by_signal = Some(signal);
                     }
                 }
             }
@@ -205,8 +216,16 @@ pub async fn process_exec_tool_call(
             }
 
             let stdout = raw_output.stdout.from_utf8_lossy();
-            let stderr = raw_output.stderr.from_utf8_lossy();
-            let aggregated_output = raw_output.aggregated_output.from_utf8_lossy();
+            let mut stderr = raw_output.stderr.from_utf8_lossy();
+            let mut aggregated_output = raw_output.aggregated_output.from_utf8_lossy();
+            if exit_code != 0
+                && sandbox_type != SandboxType::None
+                && let Some(note) =
+                    deny_read_note(sandbox_policy, sandbox_cwd, &command_cwd, &stderr.text)
+            {
+                stderr.text.push_str(&note);
+                aggregated_output.text.push_str(&note);
+            }
             let exec_output = ExecToolCallOutput {
                 exit_code,
                 stdout,
@@ -222,6 +241,21 @@ pub async fn process_exec_tool_call(
                 }));
             }
 
+            if let Some(limit) = resource_limits.exceeded(killed_by_signal, exit_code) {
+                let mut exec_output = exec_output;
+                let note = format!("\n{}\n", limit.output_note());
+                exec_output.stderr.text.push_str(&note);
+                exec_output.aggregated_output.text.push_str(&note);
+                return Err(CodexErr::Sandbox(SandboxErr::ResourceLimit {
+                    limit,
+                    output: Box::new(exec_output),
+                }));
+            }
+
+            if let Some(signal) = killed_by_signal {
+                return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
+            }
+
             if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
                 return Err(CodexErr::Sandbox(SandboxErr::Denied {
                     output: Box::new(exec_output),
@@ -237,6 +271,25 @@ pub async fn process_exec_tool_call(
     }
 }
 
+/// Explains a failed read of a `deny_read` path, which otherwise shows up as
+/// a bare "Permission denied".
+fn deny_read_note(
+    sandbox_policy: &SandboxPolicy,
+    sandbox_cwd: &Path,
+    command_cwd: &Path,
+    stderr: &str,
+) -> Option<String> {
+    if sandbox_policy.has_full_disk_read_access() {
+        return None;
+    }
+    let matcher = DenyReadMatcher::new(sandbox_policy.deny_read(), sandbox_cwd);
+    let path = matcher.denied_path_in_output(stderr, command_cwd)?;
+    Some(format!(
+        "\nsandbox: reading {} is blocked by the sandbox `deny_read` setting\n",
+        path.display()
+    ))
+}
+
 /// We don't have a fully deterministic way to tell if our command failed
 /// because of the sandbox - a command in the user's zshrc file might hit an
 /// error, but the command itself might fail or succeed for other reasons.
@@ -306,7 +359,11 @@ async fn exec(
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.maybe_timeout_duration();
     let ExecParams {
-        command, cwd, env, ..
+        command,
+        cwd,
+        env,
+        resource_limits,
+        ..
     } = params;
 
     let (program, args) = command.split_first().ok_or_else(|| {
@@ -322,6 +379,7 @@ async fn exec(
         arg0,
         cwd,
         sandbox_policy,
+        resource_limits,
         StdioPolicy::RedirectForShellTool,
         env,
     )
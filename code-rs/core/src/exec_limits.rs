@@ -0,0 +1,232 @@
+//! Per-command resource limits (`[exec_limits]` in config.toml).
+//!
+//! The limits are applied as rlimits in the spawned child (and again by
+//! `code-linux-sandbox` before it execs the command), so they are inherited by
+//! everything the command starts. [`ExecResourceLimits::exceeded`] maps the
+//! signal that killed a command back to the limit that sent it; only the CPU
+//! and file size limits are reported this way.
+
+use std::fmt;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Start of the line appended to a command's output when it hits a limit.
+/// The TUI uses it to title the failed exec cell.
+pub const LIMIT_EXCEEDED_PREFIX: &str = "command exceeded its ";
+
+/// Lowers the soft limit of `$resource` to `$soft` and the hard limit to
+/// `$soft + $headroom`, capped at the current hard limit. A macro because the
+/// resource parameter type differs between libc targets.
+#[cfg(unix)]
+macro_rules! set_rlimit {
+    ($resource:expr, $soft:expr, $headroom:expr) => {{
+        let mut current = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        if unsafe { libc::getrlimit($resource, &mut current) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let soft: u64 = $soft;
+        let hard = soft.saturating_add($headroom).min(current.rlim_max);
+        let limit = libc::rlimit {
+            rlim_cur: soft.min(hard),
+            rlim_max: hard,
+        };
+        if unsafe { libc::setrlimit($resource, &limit) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }};
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecResourceLimits {
+    /// Maximum virtual address space per process, in MiB (`RLIMIT_AS`).
+    pub memory_mb: Option<u64>,
+    /// CPU time per process, in seconds (`RLIMIT_CPU`).
+    pub cpu_seconds: Option<u64>,
+    /// Maximum number of processes for the user (`RLIMIT_NPROC`). This counts
+    /// every process the user owns, not only those started by the command.
+    pub max_processes: Option<u64>,
+    /// Largest file a command may write, in MiB (`RLIMIT_FSIZE`).
+    pub file_size_mb: Option<u64>,
+}
+
+/// The limits that can be reported. Memory and process limits are absent on
+/// purpose: hitting them leaves no signal to recognise, see
+/// [`ExecResourceLimits::exceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceLimitKind {
+    Cpu,
+    FileSize,
+}
+
+/// A limit that a command ran into, with its configured value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimitHit {
+    pub kind: ResourceLimitKind,
+    pub value: u64,
+}
+
+impl ResourceLimitHit {
+    /// The line appended to the command's stderr and aggregated output.
+    pub fn output_note(&self) -> String {
+        format!("{LIMIT_EXCEEDED_PREFIX}{self} (see `[exec_limits]`)")
+    }
+}
+
+impl fmt::Display for ResourceLimitHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.value;
+        match self.kind {
+            ResourceLimitKind::Cpu => write!(f, "CPU time limit ({value}s)"),
+            ResourceLimitKind::FileSize => write!(f, "file size limit ({value} MiB)"),
+        }
+    }
+}
+
+impl ExecResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Applies the limits to the calling process. Only calls `getrlimit` and
+    /// `setrlimit`, so it is safe to use between `fork` and `exec`. Limits are
+    /// never raised above the current hard limit.
+    #[cfg(unix)]
+    pub fn apply_to_current_process(&self) -> std::io::Result<()> {
+        if let Some(mb) = self.memory_mb {
+            set_rlimit!(libc::RLIMIT_AS, mb.saturating_mul(MIB), 0);
+        }
+        if let Some(seconds) = self.cpu_seconds {
+            // The soft limit delivers SIGXCPU; the hard limit one second later
+            // kills a process that ignores it.
+            set_rlimit!(libc::RLIMIT_CPU, seconds, 1);
+        }
+        if let Some(processes) = self.max_processes {
+            set_rlimit!(libc::RLIMIT_NPROC, processes, 0);
+        }
+        if let Some(mb) = self.file_size_mb {
+            set_rlimit!(libc::RLIMIT_FSIZE, mb.saturating_mul(MIB), 0);
+        }
+        Ok(())
+    }
+
+    /// Flags passing the limits to `code-linux-sandbox`.
+    pub fn to_cli_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for (flag, value) in [
+            ("--memory-mb", self.memory_mb),
+            ("--cpu-seconds", self.cpu_seconds),
+            ("--max-processes", self.max_processes),
+            ("--file-size-mb", self.file_size_mb),
+        ] {
+            if let Some(value) = value {
+                args.push(flag.to_string());
+                args.push(value.to_string());
+            }
+        }
+        args
+    }
+
+    /// Returns the limit that killed a command. `signal` is the signal that
+    /// killed the command, if any; commands run through a shell report it as
+    /// exit code `128 + signal` instead.
+    ///
+    /// Only CPU and file size limit hits are reported. Those limits announce
+    /// themselves with `SIGXCPU` and `SIGXFSZ`; hitting the memory or process
+    /// limit just makes an allocation or `fork` fail, which the command
+    /// reports like any other error, so this never returns a hit for them.
+    pub fn exceeded(&self, signal: Option<i32>, exit_code: i32) -> Option<ResourceLimitHit> {
+        #[cfg(unix)]
+        {
+            let died_from = |sig: i32| signal == Some(sig) || exit_code == 128 + sig;
+            if let Some(value) = self.cpu_seconds
+                && died_from(libc::SIGXCPU)
+            {
+                return Some(ResourceLimitHit {
+                    kind: ResourceLimitKind::Cpu,
+                    value,
+                });
+            }
+            if let Some(value) = self.file_size_mb
+                && died_from(libc::SIGXFSZ)
+            {
+                return Some(ResourceLimitHit {
+                    kind: ResourceLimitKind::FileSize,
+                    value,
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = (signal, exit_code);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn cli_args_include_only_configured_limits() {
+        let limits = ExecResourceLimits {
+            memory_mb: Some(2048),
+            file_size_mb: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(
+            limits.to_cli_args(),
+            vec!["--memory-mb", "2048", "--file-size-mb", "100"]
+        );
+        assert!(ExecResourceLimits::default().to_cli_args().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn classifies_limit_signals() {
+        let limits = ExecResourceLimits {
+            cpu_seconds: Some(30),
+            file_size_mb: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            limits.exceeded(Some(libc::SIGXCPU), -1),
+            Some(ResourceLimitHit {
+                kind: ResourceLimitKind::Cpu,
+                value: 30,
+            })
+        );
+        assert_eq!(
+            limits
+                .exceeded(None, 128 + libc::SIGXFSZ)
+                .map(|hit| hit.to_string()),
+            Some("file size limit (10 MiB)".to_string())
+        );
+        assert_eq!(limits.exceeded(Some(libc::SIGKILL), -1), None);
+        assert_eq!(limits.exceeded(None, 0), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ignores_signals_of_unconfigured_limits_and_memory_failures() {
+        let limits = ExecResourceLimits {
+            memory_mb: Some(512),
+            max_processes: Some(64),
+            ..Default::default()
+        };
+        // A command that prints an allocation error is not blamed on the
+        // limit: only the exit status counts.
+        assert_eq!(limits.exceeded(None, 134), None);
+        assert_eq!(limits.exceeded(Some(libc::SIGABRT), -1), None);
+        assert_eq!(limits.exceeded(Some(libc::SIGXCPU), -1), None);
+        assert_eq!(
+            ExecResourceLimits::default().exceeded(None, 128 + libc::SIGXFSZ),
+            None
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! User and project Starlark exec policies.
+//!
+//! Policies use the `define_program` / `forbid_program_regex` /
+//! `forbid_substrings` language of the `execpolicy` crate and are read from
+//! `$CODE_HOME/policy/*.policy` (user) and `.code/exec.policy` at the project
+//! root. Every shell call is evaluated against them before falling back to the
+//! approval and sandbox policies.
+//!
+//! A project policy is checked into the repository, so unless the project is
+//! trusted it may only forbid commands; its `define_program` matches are
+//! ignored and never let a command skip the approval prompt.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::ExecvChecker;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::PolicyParser;
+use tracing::warn;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::git_info::get_git_repo_root;
+
+const USER_POLICY_DIR: &str = "policy";
+const POLICY_EXTENSION: &str = "policy";
+const PROJECT_POLICY_FILE: &str = "exec.policy";
+
+/// Outcome of evaluating a command against the loaded policies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecPolicyDecision {
+    /// At least one part of the command is not described by any policy.
+    Unmatched,
+    /// Every part of the command matched and none of them write files.
+    ReadOnly,
+    /// Every part of the command matched and some of them write files.
+    /// `within_writable_roots` is `true` when every written path resolves
+    /// inside the sandbox's writable roots.
+    Writes { within_writable_roots: bool },
+    /// A policy forbids the command.
+    Forbidden { reason: String },
+}
+
+struct LoadedPolicy {
+    path: PathBuf,
+    checker: ExecvChecker,
+    /// `false` for untrusted project policies: they can forbid commands but
+    /// their matches are ignored.
+    may_approve: bool,
+}
+
+/// The set of exec policies that apply to a session.
+#[derive(Default)]
+pub struct ExecPolicy {
+    policies: Vec<LoadedPolicy>,
+}
+
+impl std::fmt::Debug for ExecPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.policies.iter().map(|p| &p.path))
+            .finish()
+    }
+}
+
+impl ExecPolicy {
+    /// Load the project policy for `cwd` followed by the user policies in
+    /// `code_home`. The project policy only counts towards forbidding
+    /// commands unless `project_trusted` is set. Files that fail to parse are
+    /// logged and skipped so a broken policy never blocks a session from
+    /// starting.
+    pub fn load(code_home: &Path, cwd: &Path, project_trusted: bool) -> Self {
+        let mut paths = Vec::new();
+        let project_root = get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+        let project_policy = project_root.join(".code").join(PROJECT_POLICY_FILE);
+        if project_policy.is_file() {
+            paths.push((project_policy, project_trusted));
+        }
+        paths.extend(
+            user_policy_paths(code_home)
+                .into_iter()
+                .map(|path| (path, true)),
+        );
+
+        let mut policy = Self::default();
+        for (path, may_approve) in paths {
+            if let Err(e) = policy.add_file(&path, may_approve) {
+                warn!("Ignoring exec policy {}: {e}", path.display());
+            }
+        }
+        policy
+    }
+
+    /// Parse `source` and add it to the set, attributing errors to `path`.
+    pub fn add_source(&mut self, path: &Path, source: &str) -> Result<(), String> {
+        self.add_parsed(path, source, true)
+    }
+
+    fn add_parsed(&mut self, path: &Path, source: &str, may_approve: bool) -> Result<(), String> {
+        let parsed = PolicyParser::new(&path.to_string_lossy(), source)
+            .parse()
+            .map_err(|e| e.to_string())?;
+        self.policies.push(LoadedPolicy {
+            path: path.to_path_buf(),
+            checker: ExecvChecker::new(parsed),
+            may_approve,
+        });
+        Ok(())
+    }
+
+    fn add_file(&mut self, path: &Path, may_approve: bool) -> Result<(), String> {
+        let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        self.add_parsed(path, &source, may_approve)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Paths of the policies that were loaded, in evaluation order.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.policies.iter().map(|p| p.path.as_path())
+    }
+
+    /// Evaluate `command` (including each command of a `bash -lc` script).
+    /// A `Forbidden` verdict from any policy for any part wins; otherwise the
+    /// command only counts as matched when every part matched some policy.
+    pub fn evaluate(
+        &self,
+        command: &[String],
+        cwd: &Path,
+        writable_roots: &[PathBuf],
+    ) -> ExecPolicyDecision {
+        if self.policies.is_empty() || command.is_empty() {
+            return ExecPolicyDecision::Unmatched;
+        }
+
+        let mut calls = vec![command.to_vec()];
+        if let Some(script_commands) = bash_script_commands(command) {
+            calls.extend(script_commands);
+        }
+
+        let writable_roots: Vec<PathBuf> = writable_roots
+            .iter()
+            .map(|root| fs::canonicalize(root).unwrap_or_else(|_| root.clone()))
+            .collect();
+        let readable_roots = [PathBuf::from("/")];
+        let cwd = Some(cwd.as_os_str().to_os_string());
+
+        // The outer `bash -lc` argv only counts towards forbidding; its
+        // sub-commands decide whether the script as a whole matched.
+        let match_from = usize::from(calls.len() > 1);
+        let mut all_matched = true;
+        let mut writes = false;
+        let mut within_writable_roots = true;
+        for (index, call) in calls.iter().enumerate() {
+            match self.check_call(call) {
+                Some((_, MatchedExec::Forbidden { reason, .. })) => {
+                    return ExecPolicyDecision::Forbidden { reason };
+                }
+                Some((checker, MatchedExec::Match { exec })) if index >= match_from => {
+                    if exec.might_write_files() {
+                        writes = true;
+                        if checker
+                            .check(exec, &cwd, &readable_roots, &writable_roots)
+                            .is_err()
+                        {
+                            within_writable_roots = false;
+                        }
+                    }
+                }
+                Some(_) => {}
+                None if index >= match_from => all_matched = false,
+                None => {}
+            }
+        }
+
+        if !all_matched {
+            ExecPolicyDecision::Unmatched
+        } else if writes {
+            ExecPolicyDecision::Writes {
+                within_writable_roots,
+            }
+        } else {
+            ExecPolicyDecision::ReadOnly
+        }
+    }
+
+    /// Check a single argv against every policy. A `Forbidden` result from
+    /// any policy takes precedence over a `Match` from an earlier one, and
+    /// matches from forbid-only policies are ignored.
+    fn check_call(&self, argv: &[String]) -> Option<(&ExecvChecker, MatchedExec)> {
+        let (program, args) = argv.split_first()?;
+        let call = ExecCall {
+            program: program.clone(),
+            args: args.to_vec(),
+        };
+        let mut matched = None;
+        for policy in &self.policies {
+            match policy.checker.r#match(&call) {
+                Ok(forbidden @ MatchedExec::Forbidden { .. }) => {
+                    return Some((&policy.checker, forbidden));
+                }
+                Ok(exec @ MatchedExec::Match { .. }) => {
+                    if policy.may_approve && matched.is_none() {
+                        matched = Some((&policy.checker, exec));
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+        matched
+    }
+}
+
+fn user_policy_paths(code_home: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(code_home.join(USER_POLICY_DIR)) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file() && path.extension().is_some_and(|ext| ext == POLICY_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn is_bash(program: &str) -> bool {
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name == "bash" || name == "bash.exe")
+}
+
+/// Split `bash -lc "<script>"` into its simple commands. Returns `None` for
+/// anything else, including scripts with redirections or substitutions.
+fn bash_script_commands(command: &[String]) -> Option<Vec<Vec<String>>> {
+    let [bash, flag, script] = command else {
+        return None;
+    };
+    if !is_bash(bash) || flag != "-lc" {
+        return None;
+    }
+    let tree = try_parse_bash(script)?;
+    try_parse_word_only_commands_sequence(&tree, script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const POLICY: &str = r#"
+define_program(
+    program="ls",
+    options=[flag("-l")],
+    args=[ARG_RFILES_OR_CWD],
+)
+
+define_program(
+    program="touch",
+    args=[ARG_WFILE],
+)
+
+forbid_program_regex(
+    regex="^rm$",
+    reason="use git clean instead",
+)
+"#;
+
+    fn policy() -> ExecPolicy {
+        let mut policy = ExecPolicy::default();
+        policy
+            .add_source(Path::new("test.policy"), POLICY)
+            .expect("policy should parse");
+        policy
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn forbidden_anywhere_in_a_script_forbids_the_command() {
+        let decision = policy().evaluate(
+            &argv(&["bash", "-lc", "ls -l && rm -rf target"]),
+            Path::new("/"),
+            &[],
+        );
+        assert_eq!(
+            decision,
+            ExecPolicyDecision::Forbidden {
+                reason: "use git clean instead".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn read_only_and_unmatched_commands() {
+        let policy = policy();
+        assert_eq!(
+            policy.evaluate(&argv(&["bash", "-lc", "ls -l"]), Path::new("/"), &[]),
+            ExecPolicyDecision::ReadOnly
+        );
+        assert_eq!(
+            policy.evaluate(&argv(&["make"]), Path::new("/"), &[]),
+            ExecPolicyDecision::Unmatched
+        );
+        assert_eq!(
+            policy.evaluate(&argv(&["bash", "-lc", "ls && make"]), Path::new("/"), &[]),
+            ExecPolicyDecision::Unmatched
+        );
+    }
+
+    #[test]
+    fn writes_are_checked_against_writable_roots() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let elsewhere = tempfile::tempdir().expect("tempdir");
+        let policy = policy();
+        let roots = vec![workspace.path().to_path_buf()];
+
+        assert_eq!(
+            policy.evaluate(&argv(&["touch", "out.txt"]), workspace.path(), &roots),
+            ExecPolicyDecision::Writes {
+                within_writable_roots: true
+            }
+        );
+        assert_eq!(
+            policy.evaluate(&argv(&["touch", "out.txt"]), elsewhere.path(), &roots),
+            ExecPolicyDecision::Writes {
+                within_writable_roots: false
+            }
+        );
+    }
+
+    #[test]
+    fn load_reads_project_and_user_policies() {
+        let code_home = tempfile::tempdir().expect("tempdir");
+        let project = tempfile::tempdir().expect("tempdir");
+        fs::create_dir_all(code_home.path().join(USER_POLICY_DIR)).expect("mkdir");
+        fs::create_dir_all(project.path().join(".code")).expect("mkdir");
+        fs::write(code_home.path().join("policy/tools.policy"), POLICY).expect("write");
+        fs::write(
+            code_home.path().join("policy/broken.policy"),
+            "define_program(",
+        )
+        .expect("write");
+        fs::write(code_home.path().join("policy/notes.txt"), POLICY).expect("write");
+        fs::write(project.path().join(".code/exec.policy"), POLICY).expect("write");
+
+        let policy = ExecPolicy::load(code_home.path(), project.path(), true);
+        let names: Vec<String> = policy
+            .paths()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["exec.policy", "tools.policy"]);
+    }
+
+    #[test]
+    fn untrusted_project_policy_only_forbids() {
+        let code_home = tempfile::tempdir().expect("tempdir");
+        let project = tempfile::tempdir().expect("tempdir");
+        fs::create_dir_all(project.path().join(".code")).expect("mkdir");
+        fs::write(project.path().join(".code/exec.policy"), POLICY).expect("write");
+
+        let policy = ExecPolicy::load(code_home.path(), project.path(), false);
+        assert_eq!(
+            policy.evaluate(&argv(&["ls", "-l"]), project.path(), &[]),
+            ExecPolicyDecision::Unmatched
+        );
+        assert_eq!(
+            policy.evaluate(&argv(&["rm", "-rf", "target"]), project.path(), &[]),
+            ExecPolicyDecision::Forbidden {
+                reason: "use git clean instead".to_string()
+            }
+        );
+
+        let trusted = ExecPolicy::load(code_home.path(), project.path(), true);
+        assert_eq!(
+            trusted.evaluate(&argv(&["ls", "-l"]), project.path(), &[]),
+            ExecPolicyDecision::ReadOnly
+        );
+    }
+}
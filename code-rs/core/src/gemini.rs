@@ -0,0 +1,1005 @@
+//! Client for Google's Gemini API (`wire_api = "gemini"`).
+//!
+//! Requests go to `models/{model}:streamGenerateContent?alt=sse`. The history
+//! is sent as Gemini `contents`, tools as `functionDeclarations`, and each
+//! streamed `GenerateContentResponse` chunk is mapped onto [`ResponseEvent`]s.
+//! Thought summaries become a reasoning item whose `encrypted_content` holds
+//! the thought signature, which is attached to the following model part when
+//! the history is replayed.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use bytes::Bytes;
+use code_otel::otel_event_manager::OtelEventManager;
+use code_protocol::models::ContentItem;
+use code_protocol::models::ReasoningItemReasoningSummary;
+use code_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+use uuid::Uuid;
+
+use crate::ModelProviderInfo;
+use crate::auth::AuthManager;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
+use crate::debug_logger::DebugLogger;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::error::RetryLimitReachedError;
+use crate::error::UnexpectedResponseError;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::FREEFORM_INPUT_PARAM;
+use crate::openai_tools::create_function_declarations_for_gemini_api;
+use crate::openai_tools::freeform_tool_names;
+use crate::protocol::TokenUsage;
+use crate::util::backoff;
+
+/// Prefix of the reasoning items produced by this client. Only those are
+/// replayed, since thought signatures from other providers mean nothing to
+/// Gemini.
+const THOUGHTS_ID_PREFIX: &str = "gemini_thoughts_";
+
+/// Gemini does not always return ids for function calls. Generated ids carry
+/// this prefix and are not sent back.
+const GENERATED_CALL_ID_PREFIX: &str = "gemini_call_";
+
+/// Implementation for the Gemini `streamGenerateContent` API.
+pub(crate) async fn stream_gemini(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    model_slug: &str,
+    effort: ReasoningEffortConfig,
+    max_output_tokens: Option<u64>,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    debug_logger: &Arc<Mutex<DebugLogger>>,
+    auth_manager: Option<Arc<AuthManager>>,
+    otel_event_manager: Option<OtelEventManager>,
+    log_tag: Option<&str>,
+) -> Result<ResponseStream> {
+    let payload = build_generate_content_request(prompt, model_family, effort, max_output_tokens);
+
+    let endpoint = provider.get_gemini_stream_url(model_slug);
+    debug!(
+        "POST to {}: {}",
+        endpoint,
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let mut attempt = 0;
+    let max_retries = provider.request_max_retries();
+    let mut request_id = String::new();
+    loop {
+        attempt += 1;
+
+        let auth = auth_manager.as_ref().and_then(|m| m.auth());
+        let req_builder = provider
+            .create_gemini_request_builder(client, &auth, model_slug)
+            .await?
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&payload);
+
+        if request_id.is_empty()
+            && let Ok(logger) = debug_logger.lock()
+        {
+            request_id = logger
+                .start_request_log(&endpoint, &payload, None, log_tag)
+                .unwrap_or_default();
+        }
+
+        match req_builder.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.append_response_event(
+                        &request_id,
+                        "stream_initiated",
+                        &json!({
+                            "status": "success",
+                            "status_code": resp.status().as_u16()
+                        }),
+                    );
+                }
+                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+                tokio::spawn(process_gemini_sse(
+                    stream,
+                    tx_event,
+                    freeform_tool_names(&prompt.tools),
+                    provider.stream_idle_timeout(),
+                    Arc::clone(debug_logger),
+                    request_id.clone(),
+                    otel_event_manager.clone(),
+                ));
+                return Ok(ResponseStream { rx_event });
+            }
+            Ok(res) => {
+                let status = res.status();
+                let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                if !retryable {
+                    let body = res.text().await.unwrap_or_default();
+                    if let Ok(logger) = debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "error",
+                            &json!({
+                                "status": status.as_u16(),
+                                "body": body
+                            }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    return Err(CodexErr::UnexpectedStatus(UnexpectedResponseError {
+                        status,
+                        body,
+                        request_id: None,
+                    }));
+                }
+
+                if attempt > max_retries {
+                    return Err(CodexErr::RetryLimit(RetryLimitReachedError {
+                        status,
+                        request_id: None,
+                        retryable,
+                    }));
+                }
+
+                let retry_after_secs = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok());
+                let delay = retry_after_secs
+                    .map(Duration::from_secs)
+                    .unwrap_or_else(|| backoff(attempt));
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                if attempt > max_retries {
+                    if let Ok(logger) = debug_logger.lock() {
+                        let _ = logger.append_response_event(
+                            &request_id,
+                            "network_error",
+                            &json!({ "error": e.to_string() }),
+                        );
+                        let _ = logger.end_request_log(&request_id);
+                    }
+                    if e.is_connect() || e.is_timeout() || e.is_request() {
+                        let req_id = (!request_id.is_empty()).then(|| request_id.clone());
+                        return Err(CodexErr::Stream(
+                            format!("[transport] network unavailable: {e}"),
+                            None,
+                            req_id,
+                        ));
+                    }
+                    return Err(e.into());
+                }
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+        }
+    }
+}
+
+/// Builds the `GenerateContentRequest` body for `prompt`.
+fn build_generate_content_request(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    effort: ReasoningEffortConfig,
+    max_output_tokens: Option<u64>,
+) -> Value {
+    let mut generation_config = json!({});
+    if let Some(schema) = &prompt.output_schema {
+        generation_config["responseMimeType"] = json!("application/json");
+        generation_config["responseJsonSchema"] = schema.clone();
+    }
+    if let Some(max_output_tokens) = max_output_tokens {
+        generation_config["maxOutputTokens"] = json!(max_output_tokens);
+    }
+    generation_config["thinkingConfig"] = match thinking_budget(effort) {
+        Some(budget) => json!({ "includeThoughts": true, "thinkingBudget": budget }),
+        None => json!({ "includeThoughts": true }),
+    };
+
+    let mut payload = json!({
+        "systemInstruction": {
+            "parts": [{ "text": prompt.get_full_instructions(model_family) }],
+        },
+        "contents": build_contents(&prompt.get_formatted_input()),
+        "generationConfig": generation_config,
+    });
+    let declarations = create_function_declarations_for_gemini_api(&prompt.tools);
+    if !declarations.is_empty() {
+        payload["tools"] = json!([{ "functionDeclarations": declarations }]);
+        payload["toolConfig"] = json!({ "functionCallingConfig": { "mode": "AUTO" } });
+    }
+    payload
+}
+
+/// Maps the configured reasoning effort to a thinking token budget. Minimal
+/// effort leaves the model's default in place, since some Gemini models
+/// cannot turn thinking off.
+fn thinking_budget(effort: ReasoningEffortConfig) -> Option<u64> {
+    match effort {
+        ReasoningEffortConfig::Minimal | ReasoningEffortConfig::None => None,
+        ReasoningEffortConfig::Low => Some(1_024),
+        ReasoningEffortConfig::Medium => Some(8_192),
+        ReasoningEffortConfig::High => Some(24_576),
+        ReasoningEffortConfig::XHigh => Some(32_768),
+    }
+}
+
+/// Converts the formatted history into Gemini `contents`. Consecutive items
+/// with the same role are merged into one content entry.
+fn build_contents(input: &[ResponseItem]) -> Vec<Value> {
+    // `functionResponse` parts must name the function they answer.
+    let call_names: HashMap<&str, &str> = input
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCall { name, call_id, .. }
+            | ResponseItem::CustomToolCall { name, call_id, .. } => {
+                Some((call_id.as_str(), name.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut contents: Vec<Value> = Vec::new();
+    let mut pending_signature: Option<&str> = None;
+    for item in input {
+        let (role, mut parts) = match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" { "model" } else { "user" };
+                (role, content.iter().filter_map(content_part).collect())
+            }
+            ResponseItem::Reasoning {
+                id,
+                encrypted_content: Some(signature),
+                ..
+            } if id.starts_with(THOUGHTS_ID_PREFIX) => {
+                pending_signature = Some(signature.as_str());
+                continue;
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let args = match serde_json::from_str::<Value>(arguments) {
+                    Ok(value @ Value::Object(_)) => value,
+                    _ => json!({ "arguments": arguments }),
+                };
+                let mut call = json!({ "name": name, "args": args });
+                if !call_id.starts_with(GENERATED_CALL_ID_PREFIX) {
+                    call["id"] = json!(call_id);
+                }
+                ("model", vec![json!({ "functionCall": call })])
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                // Freeform tools are declared with a single string parameter.
+                let mut call = json!({ "name": name, "args": { FREEFORM_INPUT_PARAM: input } });
+                if !call_id.starts_with(GENERATED_CALL_ID_PREFIX) {
+                    call["id"] = json!(call_id);
+                }
+                ("model", vec![json!({ "functionCall": call })])
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let key = if output.success == Some(false) {
+                    "error"
+                } else {
+                    "output"
+                };
+                let Some(part) =
+                    function_response(&call_names, call_id, json!({ key: output.content }))
+                else {
+                    continue;
+                };
+                ("user", vec![part])
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let Some(part) =
+                    function_response(&call_names, call_id, json!({ "output": output }))
+                else {
+                    continue;
+                };
+                ("user", vec![part])
+            }
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::CompactionSummary { .. }
+            | ResponseItem::Other => continue,
+        };
+        if parts.is_empty() {
+            continue;
+        }
+        if role == "model"
+            && let Some(signature) = pending_signature.take()
+            && let Some(first) = parts.first_mut()
+        {
+            first["thoughtSignature"] = json!(signature);
+        }
+
+        match contents.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(existing) = last["parts"].as_array_mut() {
+                    existing.extend(parts);
+                }
+            }
+            _ => contents.push(json!({ "role": role, "parts": parts })),
+        }
+    }
+    contents
+}
+
+fn function_response(
+    call_names: &HashMap<&str, &str>,
+    call_id: &str,
+    response: Value,
+) -> Option<Value> {
+    let name = call_names.get(call_id)?;
+    let mut function_response = json!({ "name": name, "response": response });
+    if !call_id.starts_with(GENERATED_CALL_ID_PREFIX) {
+        function_response["id"] = json!(call_id);
+    }
+    Some(json!({ "functionResponse": function_response }))
+}
+
+fn content_part(item: &ContentItem) -> Option<Value> {
+    match item {
+        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+            (!text.is_empty()).then(|| json!({ "text": text }))
+        }
+        ContentItem::InputImage { image_url } => Some(
+            match image_url
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"))
+            {
+                Some((mime_type, data)) => json!({
+                    "inlineData": { "mimeType": mime_type, "data": data },
+                }),
+                None => json!({ "fileData": { "fileUri": image_url } }),
+            },
+        ),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentChunk {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    usage_metadata: Option<UsageMetadata>,
+    response_id: Option<String>,
+    error: Option<ApiError>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    content: Option<CandidateContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CandidateContent {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    text: Option<String>,
+    #[serde(default)]
+    thought: bool,
+    thought_signature: Option<String>,
+    function_call: Option<FunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionCall {
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u64,
+    #[serde(default)]
+    cached_content_token_count: u64,
+    #[serde(default)]
+    candidates_token_count: u64,
+    #[serde(default)]
+    thoughts_token_count: u64,
+    #[serde(default)]
+    total_token_count: u64,
+}
+
+impl UsageMetadata {
+    /// `candidatesTokenCount` excludes thinking tokens, which OpenAI counts
+    /// as part of the output.
+    fn to_token_usage(&self) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.prompt_token_count,
+            cached_input_tokens: self.cached_content_token_count,
+            output_tokens: self.candidates_token_count + self.thoughts_token_count,
+            reasoning_output_tokens: self.thoughts_token_count,
+            total_tokens: self.total_token_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Accumulates the streamed parts of one response. Text and thoughts are
+/// flushed as items whenever a function call arrives and at the end.
+struct GeminiStreamState {
+    item_prefix: String,
+    thoughts: String,
+    signature: Option<String>,
+    reasoning_done: bool,
+    text: String,
+    text_segment: u32,
+}
+
+impl GeminiStreamState {
+    fn reasoning_id(&self) -> String {
+        format!("{THOUGHTS_ID_PREFIX}{}", self.item_prefix)
+    }
+
+    fn message_id(&self) -> String {
+        format!("{}_{}", self.item_prefix, self.text_segment)
+    }
+
+    fn flush(&mut self) -> Vec<ResponseItem> {
+        let mut items = Vec::new();
+        if !self.reasoning_done && (!self.thoughts.is_empty() || self.signature.is_some()) {
+            self.reasoning_done = true;
+            let summary = if self.thoughts.is_empty() {
+                Vec::new()
+            } else {
+                vec![ReasoningItemReasoningSummary::SummaryText {
+                    text: std::mem::take(&mut self.thoughts),
+                }]
+            };
+            items.push(ResponseItem::Reasoning {
+                id: self.reasoning_id(),
+                summary,
+                content: None,
+                encrypted_content: self.signature.take(),
+            });
+        }
+        if !self.text.is_empty() {
+            items.push(ResponseItem::Message {
+                id: Some(self.message_id()),
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText {
+                    text: std::mem::take(&mut self.text),
+                }],
+            });
+            self.text_segment += 1;
+        }
+        items
+    }
+}
+
+/// Turns a streamed `functionCall` into a response item. Calls of freeform
+/// tools become custom tool calls carrying the string `input` argument.
+fn function_call_item(
+    call: FunctionCall,
+    call_id: String,
+    freeform_tools: &HashSet<String>,
+) -> ResponseItem {
+    if freeform_tools.contains(&call.name) {
+        let input = match call.args.get(FREEFORM_INPUT_PARAM) {
+            Some(Value::String(input)) => input.clone(),
+            _ => call.args.to_string(),
+        };
+        return ResponseItem::CustomToolCall {
+            id: None,
+            status: None,
+            call_id,
+            name: call.name,
+            input,
+        };
+    }
+    ResponseItem::FunctionCall {
+        id: None,
+        name: call.name,
+        arguments: if call.args.is_null() {
+            "{}".to_string()
+        } else {
+            call.args.to_string()
+        },
+        call_id,
+    }
+}
+
+/// SSE processor for `streamGenerateContent?alt=sse`. Gemini has no explicit
+/// end event: the stream is complete once a candidate reports a
+/// `finishReason` and the connection closes.
+async fn process_gemini_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    freeform_tools: HashSet<String>,
+    idle_timeout: Duration,
+    debug_logger: Arc<Mutex<DebugLogger>>,
+    request_id: String,
+    otel_event_manager: Option<OtelEventManager>,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+    let mut state = GeminiStreamState {
+        item_prefix: Uuid::new_v4().simple().to_string(),
+        thoughts: String::new(),
+        signature: None,
+        reasoning_done: false,
+        text: String::new(),
+        text_segment: 0,
+    };
+    let mut response_id = String::new();
+    let mut usage: Option<UsageMetadata> = None;
+    let mut finished = false;
+    let mut created = false;
+
+    loop {
+        let next_event = if let Some(manager) = otel_event_manager.as_ref() {
+            manager
+                .log_sse_event(|| timeout(idle_timeout, stream.next()))
+                .await
+        } else {
+            timeout(idle_timeout, stream.next()).await
+        };
+
+        let sse = match next_event {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        format!("[transport] {e}"),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            Ok(None) if finished => {
+                for item in state.flush() {
+                    let _ = tx_event
+                        .send(Ok(ResponseEvent::OutputItemDone {
+                            item,
+                            sequence_number: None,
+                            output_index: None,
+                        }))
+                        .await;
+                }
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage: usage.as_ref().map(UsageMetadata::to_token_usage),
+                    }))
+                    .await;
+                if let Ok(logger) = debug_logger.lock() {
+                    let _ = logger.end_request_log(&request_id);
+                }
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before finishReason".into(),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "[idle] timeout waiting for SSE".into(),
+                        None,
+                        Some(request_id.clone()),
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        let chunk: GenerateContentChunk = match serde_json::from_str(&sse.data) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                debug!("gemini SSE parse error: {e} | data: {}", sse.data);
+                continue;
+            }
+        };
+        trace!("gemini received SSE chunk: {chunk:?}");
+        if let Ok(logger) = debug_logger.lock()
+            && let Ok(value) = serde_json::from_str::<Value>(&sse.data)
+        {
+            let _ = logger.append_response_event(&request_id, "sse_event", &value);
+        }
+
+        if let Some(error) = chunk.error {
+            let _ = tx_event
+                .send(Err(CodexErr::Stream(
+                    format!("[gemini] {}: {}", error.status, error.message),
+                    None,
+                    Some(request_id.clone()),
+                )))
+                .await;
+            return;
+        }
+        if !created {
+            created = true;
+            if tx_event.send(Ok(ResponseEvent::Created)).await.is_err() {
+                return;
+            }
+        }
+        if let Some(id) = chunk.response_id {
+            response_id = id;
+        }
+        if chunk.usage_metadata.is_some() {
+            usage = chunk.usage_metadata;
+        }
+
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            continue;
+        };
+        finished |= candidate.finish_reason.is_some();
+        for part in candidate
+            .content
+            .into_iter()
+            .flat_map(|content| content.parts)
+        {
+            if let Some(signature) = part.thought_signature
+                && state.signature.is_none()
+                && !state.reasoning_done
+            {
+                state.signature = Some(signature);
+            }
+
+            let mut events = Vec::new();
+            if let Some(mut call) = part.function_call {
+                let call_id = call
+                    .id
+                    .take()
+                    .unwrap_or_else(|| format!("{GENERATED_CALL_ID_PREFIX}{}", Uuid::new_v4()));
+                let mut items = state.flush();
+                items.push(function_call_item(call, call_id, &freeform_tools));
+                events.extend(items.into_iter().map(|item| ResponseEvent::OutputItemDone {
+                    item,
+                    sequence_number: None,
+                    output_index: None,
+                }));
+            } else if let Some(text) = part.text.filter(|text| !text.is_empty()) {
+                if part.thought {
+                    state.thoughts.push_str(&text);
+                    events.push(ResponseEvent::ReasoningSummaryDelta {
+                        delta: text,
+                        item_id: Some(state.reasoning_id()),
+                        sequence_number: None,
+                        output_index: None,
+                        summary_index: Some(0),
+                    });
+                } else {
+                    state.text.push_str(&text);
+                    events.push(ResponseEvent::OutputTextDelta {
+                        delta: text,
+                        item_id: Some(state.message_id()),
+                        sequence_number: None,
+                        output_index: None,
+                    });
+                }
+            }
+            for event in events {
+                if tx_event.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_family::derive_default_model_family;
+    use crate::model_provider_info::WireApi;
+    use code_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+
+    fn user(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn provider(base_url: String) -> ModelProviderInfo {
+        ModelProviderInfo {
+            name: "gemini".to_string(),
+            base_url: Some(base_url),
+            env_key: None,
+            env_key_instructions: None,
+            wire_api: WireApi::Gemini,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: Some(0),
+            stream_max_retries: Some(0),
+            stream_idle_timeout_ms: Some(5_000),
+            requires_openai_auth: false,
+            openrouter: None,
+        }
+    }
+
+    #[test]
+    fn history_becomes_contents_with_named_function_responses() {
+        let input = vec![
+            user("list files"),
+            ResponseItem::Reasoning {
+                id: "gemini_thoughts_abc".to_string(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some("sig".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "gemini_call_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "gemini_call_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "README.md".to_string(),
+                    success: Some(true),
+                },
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "unknown".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "orphan".to_string(),
+                    success: None,
+                },
+            },
+        ];
+
+        assert_eq!(
+            build_contents(&input),
+            vec![
+                json!({"role": "user", "parts": [{"text": "list files"}]}),
+                json!({"role": "model", "parts": [{
+                    "functionCall": {"name": "shell", "args": {"command": ["ls"]}},
+                    "thoughtSignature": "sig",
+                }]}),
+                json!({"role": "user", "parts": [{
+                    "functionResponse": {"name": "shell", "response": {"output": "README.md"}},
+                }]}),
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_patch_is_declared_as_a_function_and_round_trips() {
+        let prompt = Prompt {
+            input: vec![user("edit the file")],
+            tools: vec![crate::tool_apply_patch::create_apply_patch_freeform_tool()],
+            ..Default::default()
+        };
+        let payload = build_generate_content_request(
+            &prompt,
+            &derive_default_model_family("gemini-2.5-pro"),
+            ReasoningEffortConfig::Medium,
+            None,
+        );
+        let declaration = &payload["tools"][0]["functionDeclarations"][0];
+        assert_eq!(declaration["name"], "apply_patch");
+        assert_eq!(declaration["parameters"]["required"], json!(["input"]));
+        assert_eq!(
+            declaration["parameters"]["properties"]["input"]["type"],
+            "STRING"
+        );
+
+        let patch = "*** Begin Patch\n*** End Patch";
+        let call = function_call_item(
+            FunctionCall {
+                id: None,
+                name: "apply_patch".to_string(),
+                args: json!({ "input": patch }),
+            },
+            "call_1".to_string(),
+            &freeform_tool_names(&prompt.tools),
+        );
+        assert_eq!(
+            call,
+            ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call_1".to_string(),
+                name: "apply_patch".to_string(),
+                input: patch.to_string(),
+            }
+        );
+
+        let contents = build_contents(&[
+            call,
+            ResponseItem::CustomToolCallOutput {
+                call_id: "call_1".to_string(),
+                output: "Done!".to_string(),
+            },
+        ]);
+        assert_eq!(
+            contents,
+            vec![
+                json!({"role": "model", "parts": [
+                    {"functionCall": {"name": "apply_patch", "args": {"input": patch}, "id": "call_1"}},
+                ]}),
+                json!({"role": "user", "parts": [
+                    {"functionResponse": {"name": "apply_patch", "response": {"output": "Done!"}, "id": "call_1"}},
+                ]}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_thoughts_text_and_function_calls_from_mock_server() {
+        let chunks = [
+            json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Check the tree.", "thought": true}]}}], "responseId": "resp_1"}),
+            json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Listing."}]}}], "responseId": "resp_1"}),
+            json!({"candidates": [{"content": {"role": "model", "parts": [
+                {"functionCall": {"name": "shell", "args": {"command": ["ls"]}}, "thoughtSignature": "sig"},
+            ]}, "finishReason": "STOP"}],
+             "usageMetadata": {"promptTokenCount": 100, "cachedContentTokenCount": 40, "candidatesTokenCount": 20, "thoughtsTokenCount": 30, "totalTokenCount": 150},
+             "responseId": "resp_1"}),
+        ];
+        let body: String = chunks
+            .iter()
+            .map(|chunk| format!("data: {chunk}\r\n\r\n"))
+            .collect();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1beta/models/gemini-2.5-pro:streamGenerateContent"))
+            .and(query_param("alt", "sse"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_raw(body, "text/event-stream"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let prompt = Prompt {
+            input: vec![user("list files")],
+            ..Default::default()
+        };
+        let debug_logger = Arc::new(Mutex::new(DebugLogger::new(false).unwrap()));
+        let mut stream = stream_gemini(
+            &prompt,
+            &derive_default_model_family("gemini-2.5-pro"),
+            "gemini-2.5-pro",
+            ReasoningEffortConfig::Medium,
+            None,
+            &reqwest::Client::new(),
+            &provider(format!("{}/v1beta", server.uri())),
+            &debug_logger,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("stream");
+
+        let mut items = Vec::new();
+        let mut deltas = Vec::new();
+        let mut completed = None;
+        while let Some(event) = stream.rx_event.recv().await {
+            match event.expect("event") {
+                ResponseEvent::OutputItemDone { item, .. } => items.push(item),
+                ResponseEvent::OutputTextDelta { delta, .. }
+                | ResponseEvent::ReasoningSummaryDelta { delta, .. } => deltas.push(delta),
+                ResponseEvent::Completed {
+                    response_id,
+                    token_usage,
+                } => completed = Some((response_id, token_usage)),
+                _ => {}
+            }
+        }
+
+        assert_eq!(deltas, vec!["Check the tree.", "Listing."]);
+        assert_eq!(items.len(), 3);
+        match &items[0] {
+            ResponseItem::Reasoning {
+                id,
+                summary,
+                encrypted_content,
+                ..
+            } => {
+                assert!(id.starts_with(THOUGHTS_ID_PREFIX));
+                assert_eq!(
+                    summary,
+                    &vec![ReasoningItemReasoningSummary::SummaryText {
+                        text: "Check the tree.".to_string(),
+                    }]
+                );
+                assert_eq!(encrypted_content.as_deref(), Some("sig"));
+            }
+            other => panic!("expected reasoning, got {other:?}"),
+        }
+        assert!(matches!(
+            &items[1],
+            ResponseItem::Message { content, .. }
+                if content == &vec![ContentItem::OutputText { text: "Listing.".to_string() }]
+        ));
+        match &items[2] {
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                assert_eq!(name, "shell");
+                assert_eq!(arguments, r#"{"command":["ls"]}"#);
+                assert!(call_id.starts_with(GENERATED_CALL_ID_PREFIX));
+            }
+            other => panic!("expected function call, got {other:?}"),
+        }
+        assert_eq!(
+            completed,
+            Some((
+                "resp_1".to_string(),
+                Some(TokenUsage {
+                    input_tokens: 100,
+                    cached_input_tokens: 40,
+                    output_tokens: 50,
+                    reasoning_output_tokens: 30,
+                    total_tokens: 150,
+                })
+            ))
+        );
+    }
+}
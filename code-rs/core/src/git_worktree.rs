@@ -108,6 +108,12 @@ pub struct BranchMetadata {
     pub remote_ref: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub remote_url: Option<String>,
+    /// Task the worktree was created for (the `/branch` argument or agent prompt).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Creation time in seconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
 }
 
 /// Resolve the git repository root (top-level) for the given cwd.
@@ -302,6 +308,8 @@ pub async fn ensure_local_default_remote(
         remote_name: Some(remote_name.to_string()),
         remote_ref: None,
         remote_url: Some(remote_url),
+        task: None,
+        created_at: Some(Utc::now().timestamp()),
     };
 
     if let Some(base) = base_branch_clean {
@@ -596,3 +604,611 @@ pub async fn detect_default_branch(cwd: &Path) -> Option<String> {
     }
     None
 }
+
+/// A `.code/branches/*` worktree and its state relative to its base branch.
+#[derive(Debug, Clone)]
+pub struct BranchWorktree {
+    pub path: PathBuf,
+    /// Checked-out branch; `None` when HEAD is detached.
+    pub branch: Option<String>,
+    pub metadata: Option<BranchMetadata>,
+    /// Ref that ahead/behind counts, diffs and merges are measured against.
+    pub base_ref: Option<String>,
+    /// Commits on the worktree branch that are not on `base_ref`.
+    pub ahead: usize,
+    /// Commits on `base_ref` that are not on the worktree branch.
+    pub behind: usize,
+    /// Number of uncommitted entries reported by `git status --porcelain`.
+    pub dirty_files: usize,
+    /// Seconds since the Unix epoch, from metadata or the directory's mtime.
+    pub created_at: Option<i64>,
+}
+
+impl BranchWorktree {
+    /// Branch name, or the directory name for a detached worktree.
+    pub fn name(&self) -> String {
+        self.branch.clone().unwrap_or_else(|| {
+            self.path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.path.display().to_string())
+        })
+    }
+
+    pub fn task(&self) -> Option<&str> {
+        self.metadata.as_ref().and_then(|m| m.task.as_deref())
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_files > 0
+    }
+
+    /// Compact age such as `3d`, `5h`, `12m` or `now`.
+    pub fn age_label(&self) -> Option<String> {
+        let secs = (Utc::now().timestamp() - self.created_at?).max(0);
+        Some(match secs {
+            s if s >= 86_400 => format!("{}d", s / 86_400),
+            s if s >= 3_600 => format!("{}h", s / 3_600),
+            s if s >= 60 => format!("{}m", s / 60),
+            _ => "now".to_string(),
+        })
+    }
+
+    /// Base branch name without the remote prefix, for merging. Only the
+    /// recorded remote is stripped, so `feature/x` stays `feature/x`.
+    fn base_branch(&self) -> Option<String> {
+        let metadata = self.metadata.as_ref();
+        if let Some(base) = metadata.and_then(|m| m.base_branch.clone()) {
+            return Some(base);
+        }
+        let base_ref = self.base_ref.as_deref()?;
+        let local = metadata
+            .and_then(|m| m.remote_name.as_deref())
+            .and_then(|remote| base_ref.strip_prefix(remote)?.strip_prefix('/'))
+            .unwrap_or(base_ref);
+        Some(local.to_string())
+    }
+}
+
+async fn run_git(cwd: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .current_dir(cwd)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git {}: {e}", args.join(" ")))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(format!("git {} failed: {stderr}", args.join(" ")))
+    }
+}
+
+/// Name of the branch checked out at `cwd`, or `None` when HEAD is detached.
+pub async fn current_branch(cwd: &Path) -> Option<String> {
+    let name = run_git(cwd, &["branch", "--show-current"]).await.ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Parse `git worktree list --porcelain` into `(path, branch)` pairs.
+fn parse_worktree_list(porcelain: &str) -> Vec<(PathBuf, Option<String>)> {
+    let mut entries = Vec::new();
+    for block in porcelain.split("\n\n") {
+        let mut path = None;
+        let mut branch = None;
+        for line in block.lines() {
+            if let Some(p) = line.strip_prefix("worktree ") {
+                path = Some(PathBuf::from(p));
+            } else if let Some(b) = line.strip_prefix("branch ") {
+                branch = Some(b.strip_prefix("refs/heads/").unwrap_or(b).to_string());
+            }
+        }
+        if let Some(path) = path {
+            entries.push((path, branch));
+        }
+    }
+    entries
+}
+
+/// Parse `git rev-list --left-right --count base...HEAD` into `(ahead, behind)`.
+fn parse_left_right_count(output: &str) -> Option<(usize, usize)> {
+    let mut counts = output.split_whitespace().map(str::parse::<usize>);
+    let behind = counts.next()?.ok()?;
+    let ahead = counts.next()?.ok()?;
+    Some((ahead, behind))
+}
+
+async fn resolve_base_ref(
+    git_root: &Path,
+    worktree: &Path,
+    metadata: Option<&BranchMetadata>,
+) -> Option<String> {
+    let mut candidates: Vec<String> = Vec::new();
+    if let Some(meta) = metadata {
+        candidates.extend(meta.remote_ref.clone());
+        if let (Some(remote), Some(base)) = (&meta.remote_name, &meta.base_branch) {
+            candidates.push(format!("{remote}/{base}"));
+        }
+        candidates.extend(meta.base_branch.clone());
+    }
+    candidates.extend(detect_default_branch(git_root).await);
+    for candidate in candidates {
+        if run_git(worktree, &["rev-parse", "--verify", "--quiet", &candidate])
+            .await
+            .is_ok()
+        {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+async fn inspect_branch_worktree(
+    git_root: &Path,
+    path: PathBuf,
+    branch: Option<String>,
+) -> BranchWorktree {
+    let metadata = load_branch_metadata(&path);
+    let base_ref = resolve_base_ref(git_root, &path, metadata.as_ref()).await;
+    let (ahead, behind) = match &base_ref {
+        Some(base) => run_git(
+            &path,
+            &[
+                "rev-list",
+                "--left-right",
+                "--count",
+                &format!("{base}...HEAD"),
+            ],
+        )
+        .await
+        .ok()
+        .and_then(|out| parse_left_right_count(&out))
+        .unwrap_or((0, 0)),
+        None => (0, 0),
+    };
+    let dirty_files = run_git(&path, &["status", "--porcelain"])
+        .await
+        .map(|out| out.lines().filter(|l| !l.trim().is_empty()).count())
+        .unwrap_or(0);
+    let created_at = metadata.as_ref().and_then(|m| m.created_at).or_else(|| {
+        let modified = stdfs::metadata(&path).ok()?.modified().ok()?;
+        let secs = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        i64::try_from(secs).ok()
+    });
+    BranchWorktree {
+        path,
+        branch,
+        metadata,
+        base_ref,
+        ahead,
+        behind,
+        dirty_files,
+        created_at,
+    }
+}
+
+/// List the branch worktrees (`.code/branches/*`) registered with `git_root`,
+/// newest first.
+pub async fn list_branch_worktrees(git_root: &Path) -> Result<Vec<BranchWorktree>, String> {
+    let porcelain = run_git(git_root, &["worktree", "list", "--porcelain"]).await?;
+    let mut worktrees = Vec::new();
+    for (path, branch) in parse_worktree_list(&porcelain) {
+        if !is_branch_worktree_path(&path) || !path.exists() {
+            continue;
+        }
+        worktrees.push(inspect_branch_worktree(git_root, path, branch).await);
+    }
+    worktrees.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(worktrees)
+}
+
+/// Find a branch worktree by branch name, directory name or path.
+pub async fn find_branch_worktree(git_root: &Path, name: &str) -> Result<BranchWorktree, String> {
+    let worktrees = list_branch_worktrees(git_root).await?;
+    let wanted = Path::new(name);
+    worktrees
+        .into_iter()
+        .find(|wt| {
+            wt.branch.as_deref() == Some(name)
+                || wt.path.file_name() == Some(wanted.as_os_str())
+                || wt.path == wanted
+        })
+        .ok_or_else(|| format!("No branch worktree named `{name}`"))
+}
+
+/// Diff of the worktree (committed and uncommitted changes) against the point
+/// where it forked from its base.
+pub async fn branch_worktree_diff(worktree: &BranchWorktree, stat: bool) -> Result<String, String> {
+    let base = worktree
+        .base_ref
+        .as_deref()
+        .ok_or_else(|| format!("Could not determine the base branch of {}", worktree.name()))?;
+    let merge_base = run_git(&worktree.path, &["merge-base", base, "HEAD"]).await?;
+    let mut args = vec!["diff"];
+    if stat {
+        args.push("--stat");
+    }
+    args.push(merge_base.trim());
+    run_git(&worktree.path, &args).await
+}
+
+/// Merge the worktree branch into its base branch in the main checkout at
+/// `git_root`. Both checkouts must be clean and the main checkout must have
+/// the base branch checked out. Returns a one-line summary.
+pub async fn merge_branch_worktree(
+    git_root: &Path,
+    worktree: &BranchWorktree,
+    squash: bool,
+) -> Result<String, String> {
+    let name = worktree.name();
+    let branch = worktree
+        .branch
+        .as_deref()
+        .ok_or_else(|| format!("{name} has a detached HEAD; check out a branch first"))?;
+    let base = worktree
+        .base_branch()
+        .ok_or_else(|| format!("Could not determine the base branch of {name}"))?;
+    if worktree.is_dirty() {
+        return Err(format!(
+            "{name} has {} uncommitted change(s); commit them in {} first",
+            worktree.dirty_files,
+            worktree.path.display()
+        ));
+    }
+    if worktree.ahead == 0 {
+        return Ok(format!("{name} has no commits to merge into {base}"));
+    }
+
+    let current = current_branch(git_root).await;
+    if current.as_deref() != Some(base.as_str()) {
+        return Err(format!(
+            "{} is on `{}`; check out `{base}` there before merging",
+            git_root.display(),
+            current.as_deref().unwrap_or("a detached HEAD")
+        ));
+    }
+    let root_status = run_git(git_root, &["status", "--porcelain", "--untracked-files=no"]).await?;
+    if !root_status.trim().is_empty() {
+        return Err(format!(
+            "{} has uncommitted changes; commit or stash them first",
+            git_root.display()
+        ));
+    }
+
+    let merged = if squash {
+        let message = worktree
+            .task()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Squash merge {branch}"));
+        match run_git(git_root, &["merge", "--squash", branch]).await {
+            Ok(_) => run_git(git_root, &["commit", "-m", &message]).await,
+            Err(e) => Err(e),
+        }
+    } else {
+        run_git(git_root, &["merge", "--no-ff", "--no-edit", branch]).await
+    };
+    if let Err(err) = merged {
+        let abort: &[&str] = if squash {
+            &["reset", "--merge"]
+        } else {
+            &["merge", "--abort"]
+        };
+        let _ = run_git(git_root, abort).await;
+        return Err(format!(
+            "Merging {branch} into {base} failed and was aborted: {err}"
+        ));
+    }
+    Ok(format!(
+        "Merged {branch} into {base} ({} commit{})",
+        worktree.ahead,
+        if worktree.ahead == 1 { "" } else { "s" }
+    ))
+}
+
+/// Commits on the worktree branch that are on neither `base_ref` nor the
+/// local base branch. [`merge_branch_worktree`] merges into the local branch,
+/// so while `base_ref` is a remote-tracking ref a merged worktree stays
+/// `ahead` of it until the base is pushed.
+async fn unmerged_commits(worktree: &BranchWorktree) -> usize {
+    let mut refs: Vec<String> = worktree.base_ref.iter().cloned().collect();
+    if let Some(base) = worktree.base_branch() {
+        let local = format!("refs/heads/{base}");
+        if run_git(
+            &worktree.path,
+            &["rev-parse", "--verify", "--quiet", &local],
+        )
+        .await
+        .is_ok()
+        {
+            refs.push(local);
+        }
+    }
+    let mut args = vec!["rev-list", "--count", "HEAD", "--not"];
+    args.extend(refs.iter().map(String::as_str));
+    run_git(&worktree.path, &args)
+        .await
+        .ok()
+        .and_then(|out| out.trim().parse().ok())
+        .unwrap_or(worktree.ahead)
+}
+
+/// Remove the worktree, its branch and its metadata. Without `force`, refuses
+/// when the worktree has uncommitted changes or commits missing from both its
+/// base ref and the local base branch.
+pub async fn remove_branch_worktree(
+    git_root: &Path,
+    worktree: &BranchWorktree,
+    force: bool,
+) -> Result<(), String> {
+    let name = worktree.name();
+    if !force {
+        if worktree.is_dirty() {
+            return Err(format!(
+                "{name} has {} uncommitted change(s); use --force to discard them",
+                worktree.dirty_files
+            ));
+        }
+        if worktree.ahead > 0 {
+            let unmerged = unmerged_commits(worktree).await;
+            if unmerged > 0 {
+                return Err(format!(
+                    "{name} has {unmerged} unmerged commit(s); merge it first or use --force"
+                ));
+            }
+        }
+    }
+
+    let path = worktree.path.to_string_lossy().to_string();
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        args.push("--force");
+    }
+    args.push(&path);
+    run_git(git_root, &args).await?;
+    remove_branch_metadata(&worktree.path);
+    if let Some(branch) = &worktree.branch {
+        run_git(git_root, &["branch", "-D", branch]).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .expect("run git");
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit_file(dir: &Path, name: &str, contents: &str) {
+        stdfs::write(dir.join(name), contents).expect("write file");
+        git(dir, &["add", name]);
+        git(dir, &["commit", "-q", "-m", &format!("Add {name}")]);
+    }
+
+    /// A repository on `main` with one commit, plus a `feature` branch
+    /// worktree under `.code/branches`. Returns `(git_root, worktree)`.
+    fn repo_with_worktree(tmp: &TempDir) -> (PathBuf, PathBuf) {
+        let root = tmp.path().join("repo");
+        stdfs::create_dir(&root).expect("create repo dir");
+        git(&root, &["init", "-q"]);
+        git(&root, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+        git(&root, &["config", "user.name", "Test User"]);
+        git(&root, &["config", "user.email", "test@example.com"]);
+        git(&root, &["config", "commit.gpgsign", "false"]);
+        commit_file(&root, "README.md", "hello\n");
+
+        let worktree = tmp.path().join(".code").join("branches").join("feature");
+        let worktree_arg = worktree.to_string_lossy().to_string();
+        git(
+            &root,
+            &["worktree", "add", "-q", "-b", "feature", &worktree_arg],
+        );
+        (root, worktree)
+    }
+
+    #[tokio::test]
+    async fn lists_ahead_and_behind_counts_against_the_base() {
+        let tmp = TempDir::new().expect("tempdir");
+        let (root, worktree) = repo_with_worktree(&tmp);
+        commit_file(&worktree, "a.txt", "a");
+        commit_file(&worktree, "b.txt", "b");
+        commit_file(&root, "c.txt", "c");
+
+        let listed = list_branch_worktrees(&root).await.expect("list");
+        assert_eq!(listed.len(), 1);
+        let wt = &listed[0];
+        assert_eq!(wt.branch.as_deref(), Some("feature"));
+        assert_eq!(wt.base_ref.as_deref(), Some("main"));
+        assert_eq!((wt.ahead, wt.behind), (2, 1));
+        assert!(!wt.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn dirty_worktrees_are_not_merged_or_removed() {
+        let tmp = TempDir::new().expect("tempdir");
+        let (root, worktree) = repo_with_worktree(&tmp);
+        commit_file(&worktree, "a.txt", "a");
+        stdfs::write(worktree.join("scratch.txt"), "wip").expect("write scratch");
+
+        let wt = find_branch_worktree(&root, "feature").await.expect("find");
+        assert_eq!(wt.dirty_files, 1);
+        let merge_err = merge_branch_worktree(&root, &wt, false)
+            .await
+            .expect_err("merge refuses dirty worktree");
+        assert!(merge_err.contains("uncommitted"), "{merge_err}");
+        let remove_err = remove_branch_worktree(&root, &wt, false)
+            .await
+            .expect_err("remove refuses dirty worktree");
+        assert!(remove_err.contains("uncommitted"), "{remove_err}");
+        assert!(worktree.join("scratch.txt").exists());
+        assert_eq!(git(&root, &["rev-list", "--count", "main"]), "1");
+    }
+
+    #[tokio::test]
+    async fn squash_merge_adds_one_commit_to_the_base() {
+        let tmp = TempDir::new().expect("tempdir");
+        let (root, worktree) = repo_with_worktree(&tmp);
+        commit_file(&worktree, "a.txt", "a");
+        commit_file(&worktree, "b.txt", "b");
+
+        let wt = find_branch_worktree(&root, "feature").await.expect("find");
+        let summary = merge_branch_worktree(&root, &wt, true)
+            .await
+            .expect("squash merge");
+        assert_eq!(summary, "Merged feature into main (2 commits)");
+        assert_eq!(git(&root, &["rev-list", "--count", "main"]), "2");
+        assert_eq!(
+            git(&root, &["log", "-1", "--format=%s", "main"]),
+            "Squash merge feature"
+        );
+        assert!(root.join("a.txt").exists());
+        assert!(root.join("b.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn remove_requires_force_for_unmerged_commits() {
+        let tmp = TempDir::new().expect("tempdir");
+        let (root, worktree) = repo_with_worktree(&tmp);
+        commit_file(&worktree, "a.txt", "a");
+
+        let wt = find_branch_worktree(&root, "feature").await.expect("find");
+        let err = remove_branch_worktree(&root, &wt, false)
+            .await
+            .expect_err("remove refuses unmerged commits");
+        assert!(err.contains("unmerged"), "{err}");
+        assert!(worktree.exists());
+
+        remove_branch_worktree(&root, &wt, true)
+            .await
+            .expect("forced remove");
+        assert!(!worktree.exists());
+        assert_eq!(git(&root, &["branch", "--list", "feature"]), "");
+    }
+
+    #[tokio::test]
+    async fn remove_without_force_deletes_a_merged_worktree() {
+        let tmp = TempDir::new().expect("tempdir");
+        let (root, worktree) = repo_with_worktree(&tmp);
+
+        let wt = find_branch_worktree(&root, "feature").await.expect("find");
+        remove_branch_worktree(&root, &wt, false)
+            .await
+            .expect("remove");
+        assert!(!worktree.exists());
+        assert_eq!(git(&root, &["branch", "--list", "feature"]), "");
+        assert!(list_branch_worktrees(&root).await.expect("list").is_empty());
+    }
+
+    #[tokio::test]
+    async fn remove_accepts_a_worktree_merged_into_the_local_base_before_push() {
+        let tmp = TempDir::new().expect("tempdir");
+        let (root, worktree) = repo_with_worktree(&tmp);
+        let remote = tmp.path().join("origin.git");
+        let remote_arg = remote.to_string_lossy().to_string();
+        git(tmp.path(), &["init", "-q", "--bare", &remote_arg]);
+        git(&root, &["remote", "add", "origin", &remote_arg]);
+        git(&root, &["push", "-q", "origin", "main"]);
+        // Legacy in-tree metadata, kept out of `git status`.
+        stdfs::write(
+            worktree.join(".codex-branch.json"),
+            r#"{"base_branch":"main","remote_name":"origin"}"#,
+        )
+        .expect("write metadata");
+        stdfs::create_dir_all(root.join(".git/info")).expect("create info dir");
+        stdfs::write(root.join(".git/info/exclude"), ".codex-branch.json\n")
+            .expect("write exclude");
+        commit_file(&worktree, "a.txt", "a");
+
+        let wt = find_branch_worktree(&root, "feature").await.expect("find");
+        assert_eq!(wt.base_ref.as_deref(), Some("origin/main"));
+        let err = remove_branch_worktree(&root, &wt, false)
+            .await
+            .expect_err("remove refuses unmerged commits");
+        assert!(err.contains("1 unmerged"), "{err}");
+
+        merge_branch_worktree(&root, &wt, false)
+            .await
+            .expect("merge");
+        let wt = find_branch_worktree(&root, "feature").await.expect("find");
+        // Still ahead of the remote ref, but merged into local `main`.
+        assert_eq!(wt.ahead, 1);
+        remove_branch_worktree(&root, &wt, false)
+            .await
+            .expect("remove merged worktree");
+        assert!(!worktree.exists());
+    }
+
+    #[test]
+    fn base_branch_strips_only_the_recorded_remote() {
+        let worktree = |remote_name: Option<&str>, base_ref: &str| BranchWorktree {
+            path: PathBuf::from("/tmp/.code/branches/wt"),
+            branch: Some("wt".to_string()),
+            metadata: Some(BranchMetadata {
+                remote_name: remote_name.map(str::to_string),
+                ..BranchMetadata::default()
+            }),
+            base_ref: Some(base_ref.to_string()),
+            ahead: 0,
+            behind: 0,
+            dirty_files: 0,
+            created_at: None,
+        };
+        assert_eq!(
+            worktree(Some(LOCAL_DEFAULT_REMOTE), "local-default/feature/x").base_branch(),
+            Some("feature/x".to_string())
+        );
+        assert_eq!(
+            worktree(Some(LOCAL_DEFAULT_REMOTE), "release/1.0").base_branch(),
+            Some("release/1.0".to_string())
+        );
+        assert_eq!(
+            worktree(None, "feature/x").base_branch(),
+            Some("feature/x".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_worktree_list_porcelain() {
+        let porcelain = "worktree /repo\nHEAD 1111\nbranch refs/heads/main\n\n\
+            worktree /home/u/.code/working/repo/branches/code-branch-fix\nHEAD 2222\n\
+            branch refs/heads/code-branch-fix\n\n\
+            worktree /tmp/detached\nHEAD 3333\ndetached\n";
+        assert_eq!(
+            parse_worktree_list(porcelain),
+            vec![
+                (PathBuf::from("/repo"), Some("main".to_string())),
+                (
+                    PathBuf::from("/home/u/.code/working/repo/branches/code-branch-fix"),
+                    Some("code-branch-fix".to_string()),
+                ),
+                (PathBuf::from("/tmp/detached"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_left_right_counts_as_ahead_behind() {
+        assert_eq!(parse_left_right_count("2\t5\n"), Some((5, 2)));
+        assert_eq!(parse_left_right_count(""), None);
+    }
+}
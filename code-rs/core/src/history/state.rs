@@ -3012,6 +3012,7 @@ mod tests {
             PathBuf::from("src/main.rs"),
             FileChange::Add {
                 content: "fn main() {}".into(),
+                mode: None,
             },
         );
         records.push(HistoryRecord::Patch(PatchRecord {
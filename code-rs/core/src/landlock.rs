@@ -1,3 +1,5 @@
+use crate::exec_limits::ExecResourceLimits;
+use crate::network_proxy::ProxyEndpoint;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -19,20 +21,34 @@ pub async fn spawn_command_under_linux_sandbox<P>(
     command_cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    resource_limits: ExecResourceLimits,
     stdio_policy: StdioPolicy,
-    env: HashMap<String, String>,
+    mut env: HashMap<String, String>,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    let network_proxy = crate::network_proxy::proxy_for_policy(sandbox_policy).await?;
+    if let Some(proxy) = &network_proxy {
+        crate::network_proxy::apply_proxy_env(&mut env, proxy);
+    }
+    let args = create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        network_proxy.as_ref(),
+        resource_limits,
+    );
     let arg0 = Some("code-linux-sandbox");
+    // The helper applies `resource_limits` to the command itself. Limiting
+    // the helper here would also bind the proxy bridge it forks.
     spawn_child_async(
         code_linux_sandbox_exe.as_ref().to_path_buf(),
         args,
         arg0,
         command_cwd,
         sandbox_policy,
+        ExecResourceLimits::default(),
         stdio_policy,
         env,
     )
@@ -40,10 +56,16 @@ where
 }
 
 /// Converts the sandbox policy into the CLI invocation for `code-linux-sandbox`.
+/// `network_proxy` is the allowlist proxy: the helper runs the command in a
+/// loopback-only network namespace and forwards the proxy port inside it to
+/// the proxy's unix socket. The helper applies `resource_limits` only in the
+/// process that execs the command.
 fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    network_proxy: Option<&ProxyEndpoint>,
+    resource_limits: ExecResourceLimits,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = sandbox_policy_cwd
@@ -55,13 +77,23 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if let Some(proxy) = network_proxy {
+        linux_cmd.push("--network-proxy-port".to_string());
+        linux_cmd.push(proxy.addr.port().to_string());
+        if let Some(socket_path) = &proxy.socket_path {
+            linux_cmd.push("--network-proxy-socket".to_string());
+            linux_cmd.push(socket_path.to_string_lossy().into_owned());
+        }
+    }
+    linux_cmd.extend(resource_limits.to_cli_args());
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
@@ -6,6 +6,7 @@
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
 pub mod account_usage;
+mod anthropic_messages;
 mod apply_patch;
 pub mod auth;
 pub mod auth_accounts;
@@ -32,6 +33,7 @@ pub mod context_timeline;
 mod conversation_history;
 pub mod custom_prompts;
 pub mod debug_logger;
+pub mod deny_read;
 mod environment_context;
 mod reasoning;
 pub mod retention;
@@ -51,7 +53,10 @@ pub mod error;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
+pub mod exec_limits;
+pub mod exec_policy;
 mod flags;
+mod gemini;
 pub mod git_info;
 pub mod git_worktree;
 pub mod history;
@@ -60,9 +65,12 @@ pub mod http_client;
 mod image_comparison;
 pub mod landlock;
 pub mod mcp_connection_manager;
+mod mcp_input;
 mod mcp_tool_call;
 mod message_history;
+pub use message_history::find_ignore_case;
 mod model_provider_info;
+pub mod network_proxy;
 pub mod parse_command;
 pub mod slash_commands;
 mod truncate;
@@ -81,6 +89,7 @@ mod event_mapping;
 mod prompt_assembly_tests;
 pub mod protocol;
 pub mod review_format;
+pub mod review_prompts;
 pub use code_protocol::protocol::InitialHistory;
 pub use conversation_manager::ConversationManager;
 pub use conversation_manager::NewConversation;
@@ -114,8 +123,10 @@ mod workflow_validation;
 pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
 pub use rollout::INTERACTIVE_SESSION_SOURCES;
 pub use rollout::RolloutRecorder;
+pub use rollout::RolloutSettings;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
+pub use rollout::archive::archive_rollout;
 pub use rollout::catalog::SessionIndexEntry;
 pub use rollout::find_conversation_path_by_id_str;
 pub use rollout::list::ConversationItem;
@@ -125,9 +136,11 @@ pub use session_catalog::SessionCatalog;
 pub use session_catalog::SessionQuery;
 pub use session_catalog::entry_to_rollout_path;
 mod function_tool;
-mod user_notification;
+pub mod user_notification;
 pub mod util;
 
+pub use agent_tool::AGENT_USAGE_REPORT_ENV;
+pub use agent_tool::AGENT_USAGE_REPORT_PREFIX;
 pub use agent_tool::smoke_test_agent_blocking;
 pub use agent_tool::split_command_and_args;
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
@@ -4,29 +4,52 @@
 //! configured server (keyed by the *server name*). It offers convenience
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
-//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key. Resources and
+//! prompts are queried on demand and grouped by server name.
+//!
+//! Requests initiated by a server (`roots/list`, `sampling/createMessage`,
+//! `elicitation/create`) are forwarded as [`McpClientRequest`]s over the
+//! channel passed to [`McpConnectionManager::new_with_client_requests`].
+//!
+//! The tool map is kept current for the lifetime of the session: a
+//! `notifications/tools/list_changed` from a server re-lists that server's
+//! tools, and a server whose connection drops is restarted automatically with
+//! exponential backoff. Both are reported as [`McpServerEvent`]s.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use code_mcp_client::McpClient;
+use code_mcp_client::ServerNotificationHandler;
+use code_mcp_client::ServerRequestHandler;
 use code_rmcp_client::RmcpClient;
 use mcp_types::ClientCapabilities;
+use mcp_types::ClientCapabilitiesRoots;
 use mcp_types::Implementation;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::Prompt;
+use mcp_types::Resource;
+use mcp_types::ResourceTemplate;
 use mcp_types::Tool;
 
 use serde_json::json;
 use sha1::Digest;
 use sha1::Sha1;
 use tokio::sync::RwLock;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::task::AbortHandle;
 use tokio::task::JoinSet;
 use tokio::task::yield_now;
+use tracing::debug;
 use tracing::info;
 use tracing::warn;
 
@@ -44,6 +67,29 @@ const MAX_TOOL_NAME_LENGTH: usize = 64;
 /// Default timeout for initializing MCP server & initially listing tools.
 const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Timeout for resource and prompt requests when the server sets no
+/// `tool_timeout_sec`. These requests run before a turn starts, so they must
+/// never wait indefinitely.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delay before the first automatic restart of a server whose connection
+/// dropped; doubled after every failed attempt up to [`MAX_RESTART_BACKOFF`].
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Consecutive failed automatic restarts after which a server is left down
+/// until it is restarted explicitly.
+const MAX_AUTO_RESTART_ATTEMPTS: u32 = 5;
+
+/// How often the supervisor checks for servers whose connection dropped.
+const SUPERVISOR_INTERVAL: Duration = Duration::from_secs(2);
+
+const TOOLS_LIST_CHANGED_METHOD: &str = "notifications/tools/list_changed";
+
+/// Upper bound on paginated `*/list` requests issued per server so a
+/// misbehaving server cannot keep us looping forever.
+const MAX_LIST_PAGES: usize = 20;
+
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
@@ -116,6 +162,128 @@ fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     qualified_tools
 }
 
+/// A request sent by an MCP server to this client. The receiver answers by
+/// sending the JSON `result` (or a JSON-RPC error) on `respond_to`.
+#[derive(Debug)]
+pub struct McpClientRequest {
+    pub server: String,
+    pub method: String,
+    pub params: Option<serde_json::Value>,
+    pub respond_to: oneshot::Sender<Result<serde_json::Value, JSONRPCErrorError>>,
+}
+
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// Build the per-server callback that forwards server-initiated requests to
+/// the session over `tx`.
+fn forwarding_request_handler(
+    server_name: String,
+    tx: mpsc::Sender<McpClientRequest>,
+) -> ServerRequestHandler {
+    Arc::new(move |method, params| {
+        let server = server_name.clone();
+        let tx = tx.clone();
+        Box::pin(async move {
+            let (respond_to, response) = oneshot::channel();
+            let unavailable = || JSONRPCErrorError {
+                code: INTERNAL_ERROR_CODE,
+                data: None,
+                message: "client session is no longer available".to_string(),
+            };
+            tx.send(McpClientRequest {
+                server,
+                method,
+                params,
+                respond_to,
+            })
+            .await
+            .map_err(|_| unavailable())?;
+            response.await.unwrap_or_else(|_| Err(unavailable()))
+        })
+    })
+}
+
+/// Build the per-server callback that asks the supervisor to re-list the
+/// server's tools whenever it reports `notifications/tools/list_changed`.
+fn tools_changed_handler(
+    server_name: String,
+    tx: mpsc::UnboundedSender<String>,
+) -> ServerNotificationHandler {
+    Arc::new(move |method, _params| {
+        if method == TOOLS_LIST_CHANGED_METHOD {
+            let _ = tx.send(server_name.clone());
+        }
+    })
+}
+
+/// Change in the set of running servers or the tools they expose, reported on
+/// the channel passed to [`McpConnectionManager::new_with_client_requests`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpServerEvent {
+    /// The server announced a new tool list and it was re-fetched.
+    ToolsChanged { server: String, tools: usize },
+    /// The server was restarted after its connection dropped.
+    Restarted { server: String, tools: usize },
+    /// An automatic restart failed. `retry_in` is `None` once the supervisor
+    /// has given up on the server.
+    RestartFailed {
+        server: String,
+        error: String,
+        retry_in: Option<Duration>,
+    },
+}
+
+/// Everything needed to (re)start a configured server after the manager has
+/// been created.
+struct ServerLauncher {
+    configs: HashMap<String, McpServerConfig>,
+    use_rmcp_client: bool,
+    excluded_tools: HashSet<(String, String)>,
+    client_requests: Option<mpsc::Sender<McpClientRequest>>,
+    tools_changed: mpsc::UnboundedSender<String>,
+    events: Option<mpsc::UnboundedSender<McpServerEvent>>,
+    /// Servers waiting for an automatic restart.
+    pending_restarts: std::sync::Mutex<HashMap<String, RestartState>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RestartState {
+    /// Failed restart attempts so far.
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+/// Backoff before automatic restart attempt number `attempt` (zero-based).
+fn restart_backoff(attempt: u32) -> Duration {
+    INITIAL_RESTART_BACKOFF
+        .checked_mul(1u32 << attempt.min(16))
+        .map_or(MAX_RESTART_BACKOFF, |delay| delay.min(MAX_RESTART_BACKOFF))
+}
+
+impl ServerLauncher {
+    fn emit(&self, event: McpServerEvent) {
+        if let Some(tx) = &self.events {
+            let _ = tx.send(event);
+        }
+    }
+
+    async fn start(&self, server_name: &str) -> Result<ManagedClient> {
+        let cfg = self
+            .configs
+            .get(server_name)
+            .cloned()
+            .ok_or_else(|| anyhow!("unknown MCP server '{server_name}'"))?;
+        start_server(
+            server_name.to_string(),
+            cfg,
+            self.use_rmcp_client,
+            self.client_requests.clone(),
+            Some(self.tools_changed.clone()),
+        )
+        .await
+    }
+}
+
 struct ToolInfo {
     server_name: String,
     tool_name: String,
@@ -128,6 +296,13 @@ struct ManagedClient {
     tool_timeout: Option<Duration>,
 }
 
+impl ManagedClient {
+    /// Timeout for resource and prompt requests.
+    fn request_timeout(&self) -> Duration {
+        self.tool_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT)
+    }
+}
+
 #[derive(Clone)]
 enum McpClientAdapter {
     Legacy(Arc<McpClient>),
@@ -142,16 +317,30 @@ impl McpClientAdapter {
         env: Option<HashMap<String, String>>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
+        request_handler: Option<ServerRequestHandler>,
+        notification_handler: Option<ServerNotificationHandler>,
     ) -> Result<Self> {
         tracing::debug!(
             "new_stdio_client use_rmcp_client: {use_rmcp_client} program: {program:?} args: {args:?} env: {env:?} params: {params:?} startup_timeout: {startup_timeout:?}"
         );
         if use_rmcp_client {
             let client = Arc::new(RmcpClient::new_stdio_client(program, args, env).await?);
+            if let Some(handler) = request_handler {
+                client.set_server_request_handler(handler);
+            }
+            if let Some(handler) = notification_handler {
+                client.set_server_notification_handler(handler);
+            }
             client.initialize(params, Some(startup_timeout)).await?;
             Ok(McpClientAdapter::Rmcp(client))
         } else {
             let client = Arc::new(McpClient::new_stdio_client(program, args, env).await?);
+            if let Some(handler) = request_handler {
+                client.set_server_request_handler(handler);
+            }
+            if let Some(handler) = notification_handler {
+                client.set_server_notification_handler(handler);
+            }
             client.initialize(params, Some(startup_timeout)).await?;
             Ok(McpClientAdapter::Legacy(client))
         }
@@ -162,8 +351,16 @@ impl McpClientAdapter {
         bearer_token: Option<String>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
+        request_handler: Option<ServerRequestHandler>,
+        notification_handler: Option<ServerNotificationHandler>,
     ) -> Result<Self> {
         let client = Arc::new(RmcpClient::new_streamable_http_client(url, bearer_token)?);
+        if let Some(handler) = request_handler {
+            client.set_server_request_handler(handler);
+        }
+        if let Some(handler) = notification_handler {
+            client.set_server_notification_handler(handler);
+        }
         client.initialize(params, Some(startup_timeout)).await?;
         Ok(McpClientAdapter::Rmcp(client))
     }
@@ -191,6 +388,80 @@ impl McpClientAdapter {
         }
     }
 
+    async fn list_resources(
+        &self,
+        params: Option<mcp_types::ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ListResourcesResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.list_resources(params, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.list_resources(params, timeout).await,
+        }
+    }
+
+    async fn list_resource_templates(
+        &self,
+        params: Option<mcp_types::ListResourceTemplatesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ListResourceTemplatesResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => {
+                client.list_resource_templates(params, timeout).await
+            }
+            McpClientAdapter::Rmcp(client) => client.list_resource_templates(params, timeout).await,
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ReadResourceResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.read_resource(uri, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.read_resource(uri, timeout).await,
+        }
+    }
+
+    async fn list_prompts(
+        &self,
+        params: Option<mcp_types::ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ListPromptsResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.list_prompts(params, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.list_prompts(params, timeout).await,
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::GetPromptResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.get_prompt(name, arguments, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.get_prompt(name, arguments, timeout).await,
+        }
+    }
+
+    /// Whether the connection to the server has dropped.
+    async fn is_closed(&self) -> bool {
+        match self {
+            McpClientAdapter::Legacy(client) => client.is_closed(),
+            McpClientAdapter::Rmcp(client) => client.is_closed().await,
+        }
+    }
+
+    fn is_same(&self, other: &McpClientAdapter) -> bool {
+        match (self, other) {
+            (McpClientAdapter::Legacy(a), McpClientAdapter::Legacy(b)) => Arc::ptr_eq(a, b),
+            (McpClientAdapter::Rmcp(a), McpClientAdapter::Rmcp(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
     async fn into_shutdown(self) {
         match self {
             McpClientAdapter::Legacy(client) => {
@@ -213,10 +484,17 @@ pub struct McpConnectionManager {
     ///
     /// The server name originates from the keys of the `mcp_servers` map in
     /// the user configuration.
-    clients: RwLock<HashMap<String, ManagedClient>>,
+    clients: Arc<RwLock<HashMap<String, ManagedClient>>>,
 
-    /// Fully qualified tool name -> tool instance.
-    tools: HashMap<String, ToolInfo>,
+    /// Fully qualified tool name -> tool instance. Shared with the supervisor
+    /// task, which rewrites a server's entries when its tools change.
+    tools: Arc<std::sync::RwLock<HashMap<String, ToolInfo>>>,
+
+    /// Present whenever at least one server is configured.
+    launcher: Option<Arc<ServerLauncher>>,
+
+    /// Task that refreshes tool lists and restarts dropped servers.
+    supervisor: Option<AbortHandle>,
 }
 
 impl McpConnectionManager {
@@ -232,6 +510,24 @@ impl McpConnectionManager {
         mcp_servers: HashMap<String, McpServerConfig>,
         use_rmcp_client: bool,
         excluded_tools: HashSet<(String, String)>,
+    ) -> Result<(Self, ClientStartErrors)> {
+        Self::new_with_client_requests(mcp_servers, use_rmcp_client, excluded_tools, None, None)
+            .await
+    }
+
+    /// Like [`new`](Self::new), but also advertises the `roots` and
+    /// `sampling` client capabilities and forwards server-initiated requests
+    /// to `client_requests`. Without a channel, servers only see the
+    /// `elicitation` capability and their requests are declined.
+    ///
+    /// Tool list refreshes and automatic restarts are reported on
+    /// `server_events` when provided.
+    pub async fn new_with_client_requests(
+        mcp_servers: HashMap<String, McpServerConfig>,
+        use_rmcp_client: bool,
+        excluded_tools: HashSet<(String, String)>,
+        client_requests: Option<mpsc::Sender<McpClientRequest>>,
+        server_events: Option<mpsc::UnboundedSender<McpServerEvent>>,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
@@ -241,6 +537,8 @@ impl McpConnectionManager {
         // Launch all configured servers concurrently.
         let mut join_set = JoinSet::new();
         let mut errors = ClientStartErrors::new();
+        let mut configs = HashMap::with_capacity(mcp_servers.len());
+        let (tools_changed_tx, tools_changed_rx) = mpsc::unbounded_channel();
 
         for (server_name, cfg) in mcp_servers {
             // Validate server name before spawning
@@ -264,81 +562,26 @@ impl McpConnectionManager {
                 continue;
             }
 
-            let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
-            let tool_timeout = cfg.tool_timeout_sec;
-
-            let use_rmcp_client_flag = use_rmcp_client;
+            configs.insert(server_name.clone(), cfg.clone());
+            let client_requests = client_requests.clone();
+            let tools_changed = tools_changed_tx.clone();
             join_set.spawn(async move {
-                let McpServerConfig { transport, .. } = cfg;
-                let server_name_for_error = server_name.clone();
-                let params = mcp_types::InitializeRequestParams {
-                    capabilities: ClientCapabilities {
-                        experimental: None,
-                        roots: None,
-                        sampling: None,
-                        // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                        // indicates this should be an empty object.
-                        elicitation: Some(json!({})),
-                    },
-                    client_info: Implementation {
-                        name: "beacon-mcp-client".to_owned(),
-                        version: env!("CARGO_PKG_VERSION").to_owned(),
-                        title: Some("Beacon Code".into()),
-                        // This field is used by Beacon Code when it is an MCP
-                        // server: it should not be used when Beacon Code is
-                        // an MCP client.
-                        user_agent: None,
-                    },
-                    protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                };
-
-                let client = match transport {
-                    McpServerTransportConfig::Stdio { command, args, env } => {
-                        let command_for_error = command.clone();
-                        let args_for_error = args.clone();
-                        let command_os: OsString = command.into();
-                        let args_os: Vec<OsString> = args.into_iter().map(Into::into).collect();
-                        McpClientAdapter::new_stdio_client(
-                            use_rmcp_client_flag,
-                            command_os,
-                            args_os,
-                            env,
-                            params.clone(),
-                            startup_timeout,
-                        )
-                        .await
-                        .with_context(|| {
-                            if args_for_error.is_empty() {
-                                format!(
-                                    "failed to spawn MCP server `{server_name_for_error}` using command `{command_for_error}`"
-                                )
-                            } else {
-                                format!(
-                                    "failed to spawn MCP server `{server_name_for_error}` using command `{command_for_error}` with args {args_for_error:?}"
-                                )
-                            }
-                        })
-                    }
-                    McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
-                        McpClientAdapter::new_streamable_http_client(
-                            url,
-                            bearer_token,
-                            params,
-                            startup_timeout,
-                        )
-                        .await
-                    }
-                }
-                .map(|c| (c, startup_timeout));
-
-                ((server_name, tool_timeout), client)
+                let client = start_server(
+                    server_name.clone(),
+                    cfg,
+                    use_rmcp_client,
+                    client_requests,
+                    tools_changed,
+                )
+                .await;
+                (server_name, client)
             });
         }
 
         let mut clients: HashMap<String, ManagedClient> = HashMap::with_capacity(join_set.len());
 
         while let Some(res) = join_set.join_next().await {
-            let ((server_name, tool_timeout), client_res) = match res {
+            let (server_name, client_res) = match res {
                 Ok(result) => result,
                 Err(e) => {
                     warn!("Task panic when starting MCP server: {e:#}");
@@ -347,15 +590,8 @@ impl McpConnectionManager {
             };
 
             match client_res {
-                Ok((client, startup_timeout)) => {
-                    clients.insert(
-                        server_name,
-                        ManagedClient {
-                            client,
-                            startup_timeout,
-                            tool_timeout,
-                        },
-                    );
+                Ok(managed) => {
+                    clients.insert(server_name, managed);
                 }
                 Err(e) => {
                     errors.insert(server_name, e);
@@ -367,10 +603,31 @@ impl McpConnectionManager {
 
         let tools = qualify_tools(all_tools);
 
+        let launcher = Arc::new(ServerLauncher {
+            configs,
+            use_rmcp_client,
+            excluded_tools,
+            client_requests,
+            tools_changed: tools_changed_tx,
+            events: server_events,
+            pending_restarts: std::sync::Mutex::new(HashMap::new()),
+        });
+        let clients = Arc::new(RwLock::new(clients));
+        let tools = Arc::new(std::sync::RwLock::new(tools));
+        let supervisor = tokio::spawn(supervise(
+            Arc::clone(&clients),
+            Arc::clone(&tools),
+            Arc::clone(&launcher),
+            tools_changed_rx,
+        ))
+        .abort_handle();
+
         Ok((
             Self {
-                clients: RwLock::new(clients),
+                clients,
                 tools,
+                launcher: Some(launcher),
+                supervisor: Some(supervisor),
             },
             errors,
         ))
@@ -380,11 +637,48 @@ impl McpConnectionManager {
     /// fully-qualified name for the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
         self.tools
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
             .iter()
             .map(|(name, tool)| (name.clone(), tool.tool.clone()))
             .collect()
     }
 
+    /// Stop `server` (if it is running) and start it again from its
+    /// configuration, re-listing its tools. Returns the number of tools the
+    /// server now exposes.
+    pub async fn restart_server(&self, server: &str) -> Result<usize> {
+        let launcher = self
+            .launcher
+            .as_ref()
+            .filter(|launcher| launcher.configs.contains_key(server))
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+        launcher
+            .pending_restarts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(server);
+
+        let previous = self.clients.write().await.remove(server);
+        if let Some(previous) = previous {
+            previous.shutdown().await;
+        }
+        replace_server_tools(
+            &mut self
+                .tools
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            server,
+            Vec::new(),
+        );
+
+        let managed = launcher
+            .start(server)
+            .await
+            .with_context(|| format!("failed to restart MCP server `{server}`"))?;
+        Ok(install_client(&self.clients, &self.tools, launcher, server, managed).await)
+    }
+
     /// Invoke the tool indicated by the (server, tool) pair.
     pub async fn call_tool(
         &self,
@@ -408,13 +702,175 @@ impl McpConnectionManager {
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// Returns the resources advertised by every server, keyed by server
+    /// name. Servers that do not support `resources/list` are omitted.
+    pub async fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
+        self.collect_from_servers("resources/list", |client, timeout| async move {
+            collect_pages(|cursor| {
+                let client = client.clone();
+                async move {
+                    let params = cursor.map(|cursor| mcp_types::ListResourcesRequestParams {
+                        cursor: Some(cursor),
+                    });
+                    let page = client.list_resources(params, Some(timeout)).await?;
+                    Ok((page.resources, page.next_cursor))
+                }
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Returns the resource templates advertised by every server, keyed by
+    /// server name.
+    pub async fn list_all_resource_templates(&self) -> HashMap<String, Vec<ResourceTemplate>> {
+        self.collect_from_servers("resources/templates/list", |client, timeout| async move {
+            collect_pages(|cursor| {
+                let client = client.clone();
+                async move {
+                    let params =
+                        cursor.map(|cursor| mcp_types::ListResourceTemplatesRequestParams {
+                            cursor: Some(cursor),
+                        });
+                    let page = client
+                        .list_resource_templates(params, Some(timeout))
+                        .await?;
+                    Ok((page.resource_templates, page.next_cursor))
+                }
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Returns the prompts advertised by every server, keyed by server name.
+    pub async fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        self.collect_from_servers("prompts/list", |client, timeout| async move {
+            collect_pages(|cursor| {
+                let client = client.clone();
+                async move {
+                    let params = cursor.map(|cursor| mcp_types::ListPromptsRequestParams {
+                        cursor: Some(cursor),
+                    });
+                    let page = client.list_prompts(params, Some(timeout)).await?;
+                    Ok((page.prompts, page.next_cursor))
+                }
+            })
+            .await
+        })
+        .await
+    }
+
+    /// Read a single resource from `server`.
+    pub async fn read_resource(
+        &self,
+        server: &str,
+        uri: &str,
+    ) -> Result<mcp_types::ReadResourceResult> {
+        let (client, timeout) = self.client_with_timeout(server).await?;
+        client
+            .read_resource(uri.to_string(), timeout)
+            .await
+            .with_context(|| format!("resources/read failed for `{server}` ({uri})"))
+    }
+
+    /// Render a prompt from `server` with the provided arguments.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        prompt: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> Result<mcp_types::GetPromptResult> {
+        let (client, timeout) = self.client_with_timeout(server).await?;
+        client
+            .get_prompt(prompt.to_string(), arguments, timeout)
+            .await
+            .with_context(|| format!("prompts/get failed for `{server}/{prompt}`"))
+    }
+
+    async fn client_with_timeout(
+        &self,
+        server: &str,
+    ) -> Result<(McpClientAdapter, Option<Duration>)> {
+        let clients = self.clients.read().await;
+        let managed = clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+        Ok((managed.client.clone(), Some(managed.request_timeout())))
+    }
+
+    /// Run `fetch` against every connected server concurrently and gather the
+    /// successful results by server name. Each request is bounded by the
+    /// server's request timeout.
+    async fn collect_from_servers<T, F, Fut>(
+        &self,
+        label: &'static str,
+        fetch: F,
+    ) -> HashMap<String, Vec<T>>
+    where
+        T: Send + 'static,
+        F: Fn(McpClientAdapter, Duration) -> Fut,
+        Fut: Future<Output = Result<Vec<T>>> + Send + 'static,
+    {
+        let targets: Vec<(String, McpClientAdapter, Duration)> = {
+            let clients = self.clients.read().await;
+            clients
+                .iter()
+                .map(|(name, managed)| {
+                    (
+                        name.clone(),
+                        managed.client.clone(),
+                        managed.request_timeout(),
+                    )
+                })
+                .collect()
+        };
+
+        let mut join_set = JoinSet::new();
+        for (server_name, client, timeout) in targets {
+            let fut = fetch(client, timeout);
+            join_set.spawn(async move { (server_name, fut.await) });
+        }
+
+        let mut aggregated = HashMap::new();
+        while let Some(join_res) = join_set.join_next().await {
+            match join_res {
+                Ok((server_name, Ok(items))) => {
+                    if !items.is_empty() {
+                        aggregated.insert(server_name, items);
+                    }
+                }
+                Ok((server_name, Err(err))) => {
+                    // Most servers only implement a subset of the spec; a
+                    // missing method is expected and not worth surfacing.
+                    debug!("{label} unavailable for MCP server '{server_name}': {err:#}");
+                }
+                Err(err) => warn!("Task panic during {label}: {err:#}"),
+            }
+        }
+        aggregated
+    }
+
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
         self.tools
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
 
     pub async fn shutdown_all(&self) {
+        if let Some(supervisor) = &self.supervisor {
+            supervisor.abort();
+        }
+        if let Some(launcher) = &self.launcher {
+            launcher
+                .pending_restarts
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .clear();
+        }
+
         let mut clients = self.clients.write().await;
         let drained: Vec<ManagedClient> = clients.drain().map(|(_, managed)| managed).collect();
         drop(clients);
@@ -425,12 +881,356 @@ impl McpConnectionManager {
     }
 }
 
+impl Drop for McpConnectionManager {
+    fn drop(&mut self) {
+        if let Some(supervisor) = &self.supervisor {
+            supervisor.abort();
+        }
+    }
+}
+
 impl ManagedClient {
     async fn shutdown(self) {
         self.client.into_shutdown().await;
     }
 }
 
+/// Spawn and initialize a single configured server.
+async fn start_server(
+    server_name: String,
+    cfg: McpServerConfig,
+    use_rmcp_client: bool,
+    client_requests: Option<mpsc::Sender<McpClientRequest>>,
+    tools_changed: mpsc::UnboundedSender<String>,
+) -> Result<ManagedClient> {
+    let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+    let tool_timeout = cfg.tool_timeout_sec;
+    let request_handler =
+        client_requests.map(|tx| forwarding_request_handler(server_name.clone(), tx));
+    let notification_handler = Some(tools_changed_handler(server_name.clone(), tools_changed));
+    let handles_requests = request_handler.is_some();
+    let params = mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            // The roots we report are fixed for the lifetime of the session,
+            // so we never send list_changed.
+            roots: handles_requests.then_some(ClientCapabilitiesRoots {
+                list_changed: Some(false),
+            }),
+            sampling: handles_requests.then(|| json!({})),
+            // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+            // indicates this should be an empty object.
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "beacon-mcp-client".to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            title: Some("Beacon Code".into()),
+            // This field is used by Beacon Code when it is an MCP server: it
+            // should not be used when Beacon Code is an MCP client.
+            user_agent: None,
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+    };
+
+    let McpServerConfig { transport, .. } = cfg;
+    let client = match transport {
+        McpServerTransportConfig::Stdio { command, args, env } => {
+            let command_for_error = command.clone();
+            let args_for_error = args.clone();
+            let command_os: OsString = command.into();
+            let args_os: Vec<OsString> = args.into_iter().map(Into::into).collect();
+            McpClientAdapter::new_stdio_client(
+                use_rmcp_client,
+                command_os,
+                args_os,
+                env,
+                params,
+                startup_timeout,
+                request_handler,
+                notification_handler,
+            )
+            .await
+            .with_context(|| {
+                if args_for_error.is_empty() {
+                    format!(
+                        "failed to spawn MCP server `{server_name}` using command `{command_for_error}`"
+                    )
+                } else {
+                    format!(
+                        "failed to spawn MCP server `{server_name}` using command `{command_for_error}` with args {args_for_error:?}"
+                    )
+                }
+            })?
+        }
+        McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
+            McpClientAdapter::new_streamable_http_client(
+                url,
+                bearer_token,
+                params,
+                startup_timeout,
+                request_handler,
+                notification_handler,
+            )
+            .await?
+        }
+    };
+
+    Ok(ManagedClient {
+        client,
+        startup_timeout,
+        tool_timeout,
+    })
+}
+
+/// Background task owned by the manager: re-lists the tools of servers that
+/// announce a change and restarts servers whose connection dropped.
+async fn supervise(
+    clients: Arc<RwLock<HashMap<String, ManagedClient>>>,
+    tools: Arc<std::sync::RwLock<HashMap<String, ToolInfo>>>,
+    launcher: Arc<ServerLauncher>,
+    mut tools_changed: mpsc::UnboundedReceiver<String>,
+) {
+    let mut interval = tokio::time::interval(SUPERVISOR_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            Some(server) = tools_changed.recv() => {
+                refresh_server_tools(&clients, &tools, &launcher, &server).await;
+            }
+            _ = interval.tick() => {
+                detect_closed_servers(&clients, &tools, &launcher).await;
+                run_due_restarts(&clients, &tools, &launcher).await;
+            }
+        }
+    }
+}
+
+async fn refresh_server_tools(
+    clients: &RwLock<HashMap<String, ManagedClient>>,
+    tools: &std::sync::RwLock<HashMap<String, ToolInfo>>,
+    launcher: &ServerLauncher,
+    server: &str,
+) {
+    let target = clients
+        .read()
+        .await
+        .get(server)
+        .map(|managed| (managed.client.clone(), managed.startup_timeout));
+    let Some((client, timeout)) = target else {
+        return;
+    };
+    match client.list_tools(None, Some(timeout)).await {
+        Ok(result) => {
+            let server_tools = to_tool_infos(server, result.tools, &launcher.excluded_tools);
+            let count = server_tools.len();
+            replace_server_tools(
+                &mut tools
+                    .write()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner),
+                server,
+                server_tools,
+            );
+            info!("refreshed tools for MCP server '{server}': {count} tools");
+            launcher.emit(McpServerEvent::ToolsChanged {
+                server: server.to_string(),
+                tools: count,
+            });
+        }
+        Err(err) => warn!("Failed to refresh tools for MCP server '{server}': {err:#}"),
+    }
+}
+
+/// Move servers whose connection dropped out of the live set and schedule
+/// their first automatic restart.
+async fn detect_closed_servers(
+    clients: &RwLock<HashMap<String, ManagedClient>>,
+    tools: &std::sync::RwLock<HashMap<String, ToolInfo>>,
+    launcher: &ServerLauncher,
+) {
+    let snapshot: Vec<(String, McpClientAdapter)> = clients
+        .read()
+        .await
+        .iter()
+        .map(|(name, managed)| (name.clone(), managed.client.clone()))
+        .collect();
+
+    for (name, client) in snapshot {
+        if !client.is_closed().await {
+            continue;
+        }
+        let removed = {
+            let mut clients = clients.write().await;
+            // A concurrent explicit restart may already have replaced it.
+            if clients
+                .get(&name)
+                .is_some_and(|managed| managed.client.is_same(&client))
+            {
+                clients.remove(&name)
+            } else {
+                None
+            }
+        };
+        let Some(removed) = removed else {
+            continue;
+        };
+        warn!("MCP server '{name}' connection closed; scheduling restart");
+        removed.shutdown().await;
+        replace_server_tools(
+            &mut tools
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            &name,
+            Vec::new(),
+        );
+        launcher
+            .pending_restarts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(
+                name,
+                RestartState {
+                    attempts: 0,
+                    next_attempt: Instant::now() + restart_backoff(0),
+                },
+            );
+    }
+}
+
+async fn run_due_restarts(
+    clients: &RwLock<HashMap<String, ManagedClient>>,
+    tools: &std::sync::RwLock<HashMap<String, ToolInfo>>,
+    launcher: &ServerLauncher,
+) {
+    let now = Instant::now();
+    let due: Vec<(String, RestartState)> = launcher
+        .pending_restarts
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .iter()
+        .filter(|(_, state)| state.next_attempt <= now)
+        .map(|(name, state)| (name.clone(), *state))
+        .collect();
+
+    for (name, state) in due {
+        match launcher.start(&name).await {
+            Ok(managed) => {
+                launcher
+                    .pending_restarts
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .remove(&name);
+                let count = install_client(clients, tools, launcher, &name, managed).await;
+                info!("restarted MCP server '{name}' ({count} tools)");
+                launcher.emit(McpServerEvent::Restarted {
+                    server: name,
+                    tools: count,
+                });
+            }
+            Err(err) => {
+                let attempts = state.attempts + 1;
+                let retry_in =
+                    (attempts < MAX_AUTO_RESTART_ATTEMPTS).then(|| restart_backoff(attempts));
+                {
+                    let mut pending = launcher
+                        .pending_restarts
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner);
+                    match retry_in {
+                        Some(delay) => {
+                            pending.insert(
+                                name.clone(),
+                                RestartState {
+                                    attempts,
+                                    next_attempt: Instant::now() + delay,
+                                },
+                            );
+                        }
+                        None => {
+                            pending.remove(&name);
+                        }
+                    }
+                }
+                warn!("Failed to restart MCP server '{name}' (attempt {attempts}): {err:#}");
+                launcher.emit(McpServerEvent::RestartFailed {
+                    server: name,
+                    error: format!("{err:#}"),
+                    retry_in,
+                });
+            }
+        }
+    }
+}
+
+/// List the tools of a freshly started server and make it live, replacing
+/// (and shutting down) any client still registered under `name`. Returns the
+/// number of tools registered for the server.
+async fn install_client(
+    clients: &RwLock<HashMap<String, ManagedClient>>,
+    tools: &std::sync::RwLock<HashMap<String, ToolInfo>>,
+    launcher: &ServerLauncher,
+    name: &str,
+    managed: ManagedClient,
+) -> usize {
+    let server_tools = match managed
+        .client
+        .list_tools(None, Some(managed.startup_timeout))
+        .await
+    {
+        Ok(result) => to_tool_infos(name, result.tools, &launcher.excluded_tools),
+        Err(err) => {
+            warn!("Failed to list tools for MCP server '{name}': {err:#}");
+            Vec::new()
+        }
+    };
+    let count = server_tools.len();
+    let previous = clients.write().await.insert(name.to_string(), managed);
+    replace_server_tools(
+        &mut tools
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner),
+        name,
+        server_tools,
+    );
+    if let Some(previous) = previous {
+        previous.shutdown().await;
+    }
+    count
+}
+
+/// Swap the entries belonging to `server` in the qualified tool map for
+/// `server_tools`, re-qualifying names so collisions are resolved as at
+/// startup.
+fn replace_server_tools(
+    tools: &mut HashMap<String, ToolInfo>,
+    server: &str,
+    server_tools: Vec<ToolInfo>,
+) {
+    let mut all: Vec<ToolInfo> = tools
+        .drain()
+        .map(|(_, tool)| tool)
+        .filter(|tool| tool.server_name != server)
+        .collect();
+    all.extend(server_tools);
+    *tools = qualify_tools(all);
+}
+
+fn to_tool_infos(
+    server_name: &str,
+    tools: Vec<Tool>,
+    excluded_tools: &HashSet<(String, String)>,
+) -> Vec<ToolInfo> {
+    tools
+        .into_iter()
+        .filter(|tool| !excluded_tools.contains(&(server_name.to_string(), tool.name.clone())))
+        .map(|tool| ToolInfo {
+            server_name: server_name.to_string(),
+            tool_name: tool.name.clone(),
+            tool,
+        })
+        .collect()
+}
+
 /// Query every server for its available tools and return a single map that
 /// contains **all** tools. Each key is the fully-qualified name for the tool.
 async fn list_all_tools(
@@ -465,17 +1265,7 @@ async fn list_all_tools(
 
         match list_result {
             Ok(result) => {
-                for tool in result.tools {
-                    if excluded_tools.contains(&(server_name.clone(), tool.name.clone())) {
-                        continue;
-                    }
-                    let tool_info = ToolInfo {
-                        server_name: server_name.clone(),
-                        tool_name: tool.name.clone(),
-                        tool,
-                    };
-                    aggregated.push(tool_info);
-                }
+                aggregated.extend(to_tool_infos(&server_name, result.tools, excluded_tools));
             }
             Err(err) => {
                 warn!("Failed to list tools for MCP server '{server_name}': {err:#?}");
@@ -493,6 +1283,26 @@ async fn list_all_tools(
     aggregated
 }
 
+/// Follow `nextCursor` pagination until the server stops returning a cursor
+/// (or [`MAX_LIST_PAGES`] pages have been fetched).
+async fn collect_pages<T, F, Fut>(mut fetch_page: F) -> Result<Vec<T>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>)>>,
+{
+    let mut items = Vec::new();
+    let mut cursor = None;
+    for _ in 0..MAX_LIST_PAGES {
+        let (page, next_cursor) = fetch_page(cursor.take()).await?;
+        items.extend(page);
+        match next_cursor {
+            Some(next) if !next.is_empty() => cursor = Some(next),
+            _ => break,
+        }
+    }
+    Ok(items)
+}
+
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
     !server_name.is_empty()
         && server_name
@@ -621,6 +1431,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn replace_server_tools_only_touches_that_server() {
+        let mut tools = qualify_tools(vec![
+            create_test_tool("server1", "old"),
+            create_test_tool("server2", "kept"),
+        ]);
+
+        replace_server_tools(
+            &mut tools,
+            "server1",
+            vec![
+                create_test_tool("server1", "new_a"),
+                create_test_tool("server1", "new_b"),
+            ],
+        );
+
+        let mut keys: Vec<_> = tools.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec!["server1__new_a", "server1__new_b", "server2__kept"]
+        );
+
+        replace_server_tools(&mut tools, "server2", Vec::new());
+        assert!(!tools.contains_key("server2__kept"));
+    }
+
+    #[test]
+    fn restart_backoff_doubles_up_to_cap() {
+        assert_eq!(restart_backoff(0), Duration::from_secs(1));
+        assert_eq!(restart_backoff(1), Duration::from_secs(2));
+        assert_eq!(restart_backoff(3), Duration::from_secs(8));
+        assert_eq!(restart_backoff(6), MAX_RESTART_BACKOFF);
+        assert_eq!(restart_backoff(u32::MAX), MAX_RESTART_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn restart_unknown_server_is_an_error() {
+        let manager = McpConnectionManager::default();
+        let err = manager
+            .restart_server("missing")
+            .await
+            .expect_err("restarting an unconfigured server should fail");
+        assert!(format!("{err:#}").contains("missing"));
+    }
+
+    #[tokio::test]
+    async fn collect_pages_follows_cursors_until_exhausted() {
+        let pages = collect_pages(|cursor| async move {
+            match cursor.as_deref() {
+                None => Ok((vec![1, 2], Some("a".to_string()))),
+                Some("a") => Ok((vec![3], Some(String::new()))),
+                Some(other) => Err(anyhow!("unexpected cursor {other}")),
+            }
+        })
+        .await
+        .expect("pagination should succeed");
+
+        assert_eq!(pages, vec![1, 2, 3]);
+    }
+
     #[tokio::test]
     async fn stdio_spawn_error_mentions_server_and_command() {
         let mut servers = HashMap::new();
@@ -0,0 +1,228 @@
+//! Resolution of MCP-backed user input.
+//!
+//! The UI can attach `InputItem::McpResource` mentions and
+//! `InputItem::McpPrompt` invocations to a user turn. Before the turn is
+//! converted into model input those items are replaced with the content the
+//! owning server returns, so the rest of the pipeline only ever sees text and
+//! images.
+
+use mcp_types::BlobResourceContents;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::GetPromptResult;
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::Role;
+use mcp_types::TextResourceContents;
+
+use crate::mcp_connection_manager::McpConnectionManager;
+use crate::protocol::InputItem;
+
+/// Replace every MCP resource/prompt item in `items` with the content fetched
+/// from its server. Failures are reported inline as text so the user (and the
+/// model) can see why the attachment is missing instead of the turn failing.
+pub(crate) async fn resolve_mcp_input_items(
+    manager: &McpConnectionManager,
+    items: Vec<InputItem>,
+) -> Vec<InputItem> {
+    if !items.iter().any(is_mcp_item) {
+        return items;
+    }
+
+    let mut resolved = Vec::with_capacity(items.len());
+    for item in items {
+        match item {
+            InputItem::McpResource { server, uri } => {
+                match manager.read_resource(&server, &uri).await {
+                    Ok(result) => resolved.extend(resource_input_items(&server, &uri, result)),
+                    Err(err) => resolved.push(InputItem::Text {
+                        text: format!("[MCP resource {server}:{uri} unavailable: {err:#}]"),
+                    }),
+                }
+            }
+            InputItem::McpPrompt {
+                server,
+                name,
+                arguments,
+            } => {
+                let arguments = if arguments.is_empty() {
+                    None
+                } else {
+                    serde_json::to_value(arguments).ok()
+                };
+                match manager.get_prompt(&server, &name, arguments).await {
+                    Ok(result) => resolved.extend(prompt_input_items(result)),
+                    Err(err) => resolved.push(InputItem::Text {
+                        text: format!("[MCP prompt {server}:{name} unavailable: {err:#}]"),
+                    }),
+                }
+            }
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+fn is_mcp_item(item: &InputItem) -> bool {
+    matches!(
+        item,
+        InputItem::McpResource { .. } | InputItem::McpPrompt { .. }
+    )
+}
+
+/// Convert the result of `resources/read` into input items. Text contents are
+/// wrapped in an `<mcp_resource>` block so the model can tell where the
+/// attachment came from; image blobs are inlined as data URLs.
+fn resource_input_items(server: &str, uri: &str, result: ReadResourceResult) -> Vec<InputItem> {
+    let mut out = Vec::new();
+    for contents in result.contents {
+        match contents {
+            ReadResourceResultContents::TextResourceContents(text) => {
+                out.push(text_resource_item(server, &text));
+            }
+            ReadResourceResultContents::BlobResourceContents(blob) => {
+                out.push(blob_resource_item(server, &blob));
+            }
+        }
+    }
+    if out.is_empty() {
+        out.push(InputItem::Text {
+            text: format!("[MCP resource {server}:{uri} is empty]"),
+        });
+    }
+    out
+}
+
+/// Convert the messages returned by `prompts/get` into input items. Assistant
+/// messages are labelled so a multi-message prompt keeps its shape when it is
+/// flattened into a single user turn.
+fn prompt_input_items(result: GetPromptResult) -> Vec<InputItem> {
+    let mut out = Vec::new();
+    for message in result.messages {
+        let prefix = match message.role {
+            Role::User => "",
+            Role::Assistant => "[assistant] ",
+        };
+        match message.content {
+            ContentBlock::TextContent(text) => out.push(InputItem::Text {
+                text: format!("{prefix}{}", text.text),
+            }),
+            ContentBlock::ImageContent(image) => out.push(InputItem::Image {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+            }),
+            ContentBlock::AudioContent(audio) => out.push(InputItem::Text {
+                text: format!("[audio attachment ({}) omitted]", audio.mime_type),
+            }),
+            ContentBlock::ResourceLink(link) => out.push(InputItem::Text {
+                text: format!("{prefix}{} ({})", link.name, link.uri),
+            }),
+            ContentBlock::EmbeddedResource(embedded) => match embedded.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => {
+                    out.push(InputItem::Text {
+                        text: format!("{prefix}{}", text.text),
+                    });
+                }
+                EmbeddedResourceResource::BlobResourceContents(blob) => {
+                    out.push(blob_resource_item("prompt", &blob));
+                }
+            },
+        }
+    }
+    out
+}
+
+fn text_resource_item(server: &str, contents: &TextResourceContents) -> InputItem {
+    InputItem::Text {
+        text: format!(
+            "<mcp_resource server=\"{server}\" uri=\"{}\">\n{}\n</mcp_resource>",
+            contents.uri, contents.text
+        ),
+    }
+}
+
+fn blob_resource_item(server: &str, contents: &BlobResourceContents) -> InputItem {
+    match contents.mime_type.as_deref() {
+        Some(mime) if mime.starts_with("image/") => InputItem::Image {
+            image_url: format!("data:{mime};base64,{}", contents.blob),
+        },
+        mime => InputItem::Text {
+            text: format!(
+                "[MCP resource {server}:{} is binary ({}) and was not attached]",
+                contents.uri,
+                mime.unwrap_or("unknown type")
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::PromptMessage;
+    use mcp_types::TextContent;
+    use pretty_assertions::assert_eq;
+
+    fn text_of(item: &InputItem) -> &str {
+        match item {
+            InputItem::Text { text } => text,
+            other => panic!("expected text item, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resource_contents_are_tagged_and_images_inlined() {
+        let result = ReadResourceResult {
+            contents: vec![
+                ReadResourceResultContents::TextResourceContents(TextResourceContents {
+                    mime_type: Some("text/plain".to_string()),
+                    text: "hello".to_string(),
+                    uri: "file:///notes.txt".to_string(),
+                }),
+                ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                    blob: "AAAA".to_string(),
+                    mime_type: Some("image/png".to_string()),
+                    uri: "file:///shot.png".to_string(),
+                }),
+            ],
+        };
+
+        let items = resource_input_items("docs", "file:///notes.txt", result);
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            text_of(&items[0]),
+            "<mcp_resource server=\"docs\" uri=\"file:///notes.txt\">\nhello\n</mcp_resource>"
+        );
+        match &items[1] {
+            InputItem::Image { image_url } => assert_eq!(image_url, "data:image/png;base64,AAAA"),
+            other => panic!("expected image item, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn prompt_messages_become_text_items() {
+        let text = |s: &str| {
+            ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: s.to_string(),
+                r#type: "text".to_string(),
+            })
+        };
+        let result = GetPromptResult {
+            description: None,
+            messages: vec![
+                PromptMessage {
+                    content: text("Review this diff"),
+                    role: Role::User,
+                },
+                PromptMessage {
+                    content: text("Sure"),
+                    role: Role::Assistant,
+                },
+            ],
+        };
+
+        let items = prompt_input_items(result);
+        let texts: Vec<&str> = items.iter().map(text_of).collect();
+        assert_eq!(texts, vec!["Review this diff", "[assistant] Sure"]);
+    }
+}
@@ -6,9 +6,11 @@
 //! JSON-Lines tooling. Each record has the following schema:
 //!
 //! ````text
-//! {"session_id":"<uuid>","ts":<unix_seconds>,"text":"<message>"}
+//! {"session_id":"<uuid>","ts":<unix_seconds>,"text":"<message>","cwd":"<dir>"}
 //! ````
 //!
+//! `cwd` was added later; older records omit it.
+//!
 //! To minimise the chance of interleaved writes when multiple processes are
 //! appending concurrently, callers should *prepare the full line* (record +
 //! trailing `\n`) and write it with a **single `write(2)` system call** while
@@ -22,6 +24,8 @@ use std::io::Result;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
 use std::path::PathBuf;
 
 use once_cell::sync::Lazy;
@@ -63,6 +67,9 @@ pub struct HistoryEntry {
     pub session_id: String,
     pub ts: u64,
     pub text: String,
+    /// Working directory of the session that recorded the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 }
 
 fn history_filepath(config: &Config) -> PathBuf {
@@ -104,6 +111,7 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
         session_id: session_id.to_string(),
         ts,
         text,
+        cwd: Some(config.cwd.clone()),
     };
     let mut line = serde_json::to_string(&entry)
         .map_err(|e| std::io::Error::other(format!("failed to serialise history entry: {e}")))?;
@@ -374,8 +382,121 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
     None
 }
 
+/// Byte range in `text` of the first occurrence of `query`, ignoring case.
+/// Both sides are lowercased one character at a time, so a character whose
+/// lowercase form is longer (such as `İ`) still maps back to whole characters
+/// of `text`. History search and the composer's match highlighting both use
+/// this, so they always agree on what matches.
+pub fn find_ignore_case(text: &str, query: &str) -> Option<Range<usize>> {
+    let query: String = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return None;
+    }
+    let mut folded = String::with_capacity(text.len());
+    // Byte offset in `text` of the character each byte of `folded` came from.
+    let mut origin = Vec::with_capacity(text.len());
+    for (offset, ch) in text.char_indices() {
+        folded.extend(ch.to_lowercase());
+        origin.resize(folded.len(), offset);
+    }
+    let found = folded.find(&query)?;
+    let start = *origin.get(found)?;
+    let last = *origin.get(found + query.len() - 1)?;
+    let end = last + text[last..].chars().next().map_or(0, char::len_utf8);
+    Some(start..end)
+}
+
+/// Search the history file identified by `log_id` for entries whose text
+/// contains `query` (see [`find_ignore_case`]), newest first. Repeated texts are
+/// reported once, at their most recent offset. When `repo_root` is given only
+/// entries recorded under that directory match; entries written before `cwd`
+/// was tracked never do. At most `limit` matches are returned.
+#[cfg(unix)]
+pub(crate) fn search(
+    log_id: u64,
+    query: &str,
+    repo_root: Option<&Path>,
+    limit: usize,
+    config: &Config,
+) -> Vec<(usize, HistoryEntry)> {
+    use std::collections::HashSet;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::os::unix::fs::MetadataExt;
+
+    if query.is_empty() || limit == 0 {
+        return Vec::new();
+    }
+
+    let path = history_filepath(config);
+    let file: File = match OpenOptions::new().read(true).open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to open history file");
+            return Vec::new();
+        }
+    };
+    match file.metadata() {
+        Ok(m) if m.ino() == log_id => {}
+        Ok(_) => return Vec::new(),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to stat history file");
+            return Vec::new();
+        }
+    }
+
+    if let Err(e) = acquire_shared_lock_with_retry(&file) {
+        tracing::warn!(error = %e, "failed to acquire shared lock on history file");
+        return Vec::new();
+    }
+    let mut candidates = Vec::new();
+    for (idx, line) in BufReader::new(&file).lines().enumerate() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to read line from history file");
+                break;
+            }
+        };
+        let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line) else {
+            continue;
+        };
+        let in_repo = match repo_root {
+            Some(root) => entry
+                .cwd
+                .as_deref()
+                .is_some_and(|cwd| cwd.starts_with(root)),
+            None => true,
+        };
+        if in_repo && find_ignore_case(&entry.text, query).is_some() {
+            candidates.push((idx, entry));
+        }
+    }
+    let _ = fs2::FileExt::unlock(&file);
+
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .rev()
+        .filter(|(_, entry)| seen.insert(entry.text.clone()))
+        .take(limit)
+        .collect()
+}
+
+/// Fallback stub for non-Unix systems: currently always returns no matches.
+#[cfg(not(unix))]
+pub(crate) fn search(
+    log_id: u64,
+    query: &str,
+    repo_root: Option<&Path>,
+    limit: usize,
+    config: &Config,
+) -> Vec<(usize, HistoryEntry)> {
+    let _ = (log_id, query, repo_root, limit, config);
+    Vec::new()
+}
+
 #[cfg(unix)]
-#[allow(dead_code)]
 fn acquire_shared_lock_with_retry(file: &File) -> Result<()> {
     for _ in 0..MAX_RETRIES {
         match fs2::FileExt::try_lock_shared(file) {
@@ -422,7 +543,10 @@ async fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
 mod tests {
     use super::HistoryEntry;
     use super::append_entry;
+    use super::find_ignore_case;
     use super::history_filepath;
+    use super::history_metadata;
+    use super::search;
     use crate::config::Config;
     use crate::config::ConfigOverrides;
     use crate::config::ConfigToml;
@@ -500,4 +624,67 @@ mod tests {
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].text, "<redacted> and <redacted>");
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn search_returns_newest_unique_matches_scoped_to_repo() {
+        let temp = tempdir().expect("tempdir");
+        let mut config = test_config(temp.path());
+        let session_id = Uuid::new_v4();
+        let repo = temp.path().join("repo");
+        let elsewhere = temp.path().join("elsewhere");
+
+        config.cwd = repo.join("crates");
+        append_entry("Fix the parser", &session_id, &config)
+            .await
+            .expect("append");
+        config.cwd = elsewhere;
+        append_entry("fix the docs", &session_id, &config)
+            .await
+            .expect("append");
+        config.cwd = repo.clone();
+        append_entry("Fix the parser", &session_id, &config)
+            .await
+            .expect("append");
+        append_entry("unrelated", &session_id, &config)
+            .await
+            .expect("append");
+
+        let (log_id, _) = history_metadata(&config).await;
+        let texts = |matches: Vec<(usize, HistoryEntry)>| -> Vec<(usize, String)> {
+            matches
+                .into_iter()
+                .map(|(offset, entry)| (offset, entry.text))
+                .collect()
+        };
+
+        assert_eq!(
+            texts(search(log_id, "FIX", None, 10, &config)),
+            vec![
+                (2, "Fix the parser".to_string()),
+                (1, "fix the docs".to_string()),
+            ]
+        );
+        assert_eq!(
+            texts(search(log_id, "fix", Some(&repo), 10, &config)),
+            vec![(2, "Fix the parser".to_string())]
+        );
+        assert_eq!(
+            texts(search(log_id, "fix", None, 1, &config)),
+            vec![(2, "Fix the parser".to_string())]
+        );
+        assert!(search(log_id + 1, "fix", None, 10, &config).is_empty());
+    }
+
+    #[test]
+    fn find_ignore_case_maps_back_to_whole_characters() {
+        assert_eq!(find_ignore_case("Fix the Bug", "bug"), Some(8..11));
+        assert_eq!(find_ignore_case("naïve café", "CAFÉ"), Some(7..12));
+        // `İ` lowercases to `i` plus a combining dot; a match on either part
+        // covers the whole `İ`.
+        assert_eq!(find_ignore_case("İzmir", "i"), Some(0..2));
+        assert_eq!(find_ignore_case("İzmir", "İZ"), Some(0..3));
+        assert_eq!(find_ignore_case("fix", "fixes"), None);
+        assert_eq!(find_ignore_case("fix", ""), None);
+    }
 }
@@ -24,6 +24,7 @@ const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
 const MAX_REQUEST_MAX_RETRIES: u64 = 100;
+const GEMINI_DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
 
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
@@ -40,6 +41,13 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// Anthropic's Messages API at `/v1/messages`.
+    #[serde(rename = "anthropic")]
+    AnthropicMessages,
+
+    /// Google's Gemini API at `/v1beta/models/{model}:streamGenerateContent`.
+    Gemini,
 }
 
 /// Serializable representation of a provider definition.
@@ -177,7 +185,8 @@ impl ModelProviderInfo {
     /// Construct a `POST` RequestBuilder for the given URL using the provided
     /// reqwest Client applying:
     ///   • provider-specific headers (static + env based)
-    ///   • Bearer auth header when an API key is available.
+    ///   • Bearer auth header when an API key is available (`x-api-key` for
+    ///     the Anthropic Messages API, `x-goog-api-key` for Gemini).
     ///   • Auth token for OAuth.
     ///
     /// If the provider declares an `env_key` but the variable is missing/empty, returns an [`Err`] identical to the
@@ -194,12 +203,41 @@ impl ModelProviderInfo {
         let mut builder = client.post(&url);
 
         if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
+            builder = self.apply_auth_token(builder, auth.get_token().await?);
         }
 
         Ok(self.apply_http_headers(builder))
     }
 
+    /// Like [`ModelProviderInfo::create_request_builder`] for Gemini
+    /// providers, whose endpoint includes the model slug.
+    pub(crate) async fn create_gemini_request_builder<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        auth: &Option<CodeAuth>,
+        model: &str,
+    ) -> crate::error::Result<reqwest::RequestBuilder> {
+        let effective_auth = self.effective_auth(auth)?;
+        let mut builder = client.post(self.get_gemini_stream_url(model));
+        if let Some(auth) = effective_auth.as_ref() {
+            builder = self.apply_auth_token(builder, auth.get_token().await?);
+        }
+
+        Ok(self.apply_http_headers(builder))
+    }
+
+    fn apply_auth_token(
+        &self,
+        builder: reqwest::RequestBuilder,
+        token: String,
+    ) -> reqwest::RequestBuilder {
+        match self.wire_api {
+            WireApi::AnthropicMessages => builder.header("x-api-key", token),
+            WireApi::Gemini => builder.header("x-goog-api-key", token),
+            WireApi::Responses | WireApi::Chat => builder.bearer_auth(token),
+        }
+    }
+
     pub async fn create_compact_request_builder<'a>(
         &'a self,
         client: &'a reqwest::Client,
@@ -258,7 +296,11 @@ impl ModelProviderInfo {
     }
 
     pub(crate) fn get_full_url(&self, auth: &Option<CodeAuth>) -> String {
-        let default_base_url = if matches!(
+        let default_base_url = if self.wire_api == WireApi::AnthropicMessages {
+            "https://api.anthropic.com/v1"
+        } else if self.wire_api == WireApi::Gemini {
+            GEMINI_DEFAULT_BASE_URL
+        } else if matches!(
             auth,
             Some(CodeAuth {
                 mode: AuthMode::ChatGPT,
@@ -278,7 +320,38 @@ impl ModelProviderInfo {
         match self.wire_api {
             WireApi::Responses => format!("{base_url}/responses{query_string}"),
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            WireApi::AnthropicMessages => format!("{base_url}/messages{query_string}"),
+            // The model is part of the Gemini endpoint; see `get_gemini_stream_url`.
+            WireApi::Gemini => format!("{base_url}/models{query_string}"),
+        }
+    }
+
+    /// Streaming endpoint for `model` on a Gemini provider. `query_params`
+    /// are URL-encoded and appended after the `alt=sse` parameter the
+    /// endpoint requires.
+    pub(crate) fn get_gemini_stream_url(&self, model: &str) -> String {
+        let base_url = self
+            .base_url
+            .clone()
+            .unwrap_or_else(|| GEMINI_DEFAULT_BASE_URL.to_string());
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        let endpoint = format!("{base_url}/models/{model}:streamGenerateContent");
+        let Ok(mut url) = reqwest::Url::parse(&endpoint) else {
+            // Let the request itself report the malformed base URL.
+            return format!("{endpoint}?alt=sse");
+        };
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("alt", "sse");
+            if let Some(params) = &self.query_params {
+                let mut params: Vec<_> = params.iter().collect();
+                params.sort();
+                for (key, value) in params {
+                    query.append_pair(key, value);
+                }
+            }
         }
+        url.into()
     }
 
     pub(crate) fn get_compact_url(&self, auth: &Option<CodeAuth>) -> Option<String> {
@@ -640,6 +713,59 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
         assert_eq!(expected_provider, provider);
     }
 
+    #[test]
+    fn test_deserialize_anthropic_model_provider_toml() {
+        let anthropic_provider_toml = r#"
+name = "Anthropic"
+env_key = "ANTHROPIC_API_KEY"
+wire_api = "anthropic"
+        "#;
+
+        let provider: ModelProviderInfo = toml::from_str(anthropic_provider_toml).unwrap();
+        assert_eq!(provider.wire_api, WireApi::AnthropicMessages);
+        assert_eq!(
+            provider.get_full_url(&None),
+            "https://api.anthropic.com/v1/messages"
+        );
+    }
+
+    #[test]
+    fn gemini_stream_url_includes_model_and_query_params() {
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Gemini"
+env_key = "GEMINI_API_KEY"
+wire_api = "gemini"
+query_params = { key2 = "b" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(provider.wire_api, WireApi::Gemini);
+        assert_eq!(
+            provider.get_gemini_stream_url("models/gemini-2.5-pro"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse&key2=b"
+        );
+    }
+
+    #[test]
+    fn gemini_stream_url_encodes_query_params() {
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Gemini"
+wire_api = "gemini"
+base_url = "http://localhost:8080/v1beta"
+query_params = { "x&y" = "a b=c", token = "1/2?3" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            provider.get_gemini_stream_url("gemini-2.5-flash"),
+            "http://localhost:8080/v1beta/models/gemini-2.5-flash:streamGenerateContent?alt=sse&token=1%2F2%3F3&x%26y=a+b%3Dc"
+        );
+    }
+
     #[test]
     fn detects_azure_responses_base_urls() {
         fn provider_for(base_url: &str) -> ModelProviderInfo {
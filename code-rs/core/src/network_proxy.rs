@@ -0,0 +1,565 @@
+//! Filtering HTTP(S) proxy that gives sandboxed commands access to an
+//! allowlist of hosts.
+//!
+//! When a `workspace-write` policy disables `network_access` but lists hosts
+//! in `network_allowlist`, sandboxed commands are spawned with `HTTP_PROXY`,
+//! `HTTPS_PROXY` and `ALL_PROXY` pointing at a loopback proxy started here.
+//! The proxy only forwards `CONNECT` tunnels and absolute-form `http://`
+//! requests whose destination matches the allowlist. The platform sandbox
+//! keeps every other connection blocked: on Linux, the command runs in a
+//! network namespace whose only interface is loopback, and the sandbox helper
+//! bridges the proxy port inside it to the proxy's unix socket; on macOS,
+//! Seatbelt only allows outbound traffic to the proxy.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::copy_bidirectional;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+use crate::protocol::SandboxPolicy;
+
+/// Upper bound on the request line plus headers read from a client before
+/// the request is rejected.
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+
+/// Ports allowed for allowlist entries that do not name one.
+const DEFAULT_ALLOWED_PORTS: [u16; 2] = [80, 443];
+
+/// Variables pointed at the proxy for sandboxed commands.
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Variables removed for sandboxed commands: hosts listed there would be
+/// contacted directly and fail.
+const NO_PROXY_ENV_VARS: [&str; 2] = ["NO_PROXY", "no_proxy"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostPattern {
+    Exact(String),
+    /// `*.example.com`, stored as `.example.com`; matches subdomains only.
+    Subdomains(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AllowRule {
+    host: HostPattern,
+    port: Option<u16>,
+}
+
+/// Parsed form of `sandbox_workspace_write.network_allowlist`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NetworkAllowlist {
+    rules: Vec<AllowRule>,
+}
+
+impl NetworkAllowlist {
+    /// Parse allowlist entries of the form `host`, `*.domain` or
+    /// `host:port`. A leading `http://`/`https://` and any path are ignored.
+    /// Entries that cannot be parsed are skipped with a warning.
+    pub fn parse(entries: &[String]) -> Self {
+        let rules = entries
+            .iter()
+            .filter_map(|entry| {
+                let rule = parse_rule(entry);
+                if rule.is_none() {
+                    warn!("ignoring invalid network_allowlist entry `{entry}`");
+                }
+                rule
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        let host = normalize_host(host);
+        self.rules.iter().any(|rule| {
+            let port_ok = match rule.port {
+                Some(allowed) => allowed == port,
+                None => DEFAULT_ALLOWED_PORTS.contains(&port),
+            };
+            let host_ok = match &rule.host {
+                HostPattern::Exact(allowed) => *allowed == host,
+                HostPattern::Subdomains(suffix) => host.ends_with(suffix.as_str()),
+            };
+            port_ok && host_ok
+        })
+    }
+}
+
+fn parse_rule(entry: &str) -> Option<AllowRule> {
+    let entry = entry.trim();
+    let entry = entry
+        .strip_prefix("https://")
+        .or_else(|| entry.strip_prefix("http://"))
+        .unwrap_or(entry);
+    let authority = entry.split('/').next().unwrap_or_default();
+    let (host, port) = split_host_port(authority, None)?;
+    let host = match host.strip_prefix("*.") {
+        Some(domain) if !domain.is_empty() => HostPattern::Subdomains(format!(".{domain}")),
+        Some(_) => return None,
+        None if host.contains('*') => return None,
+        None => HostPattern::Exact(host),
+    };
+    Some(AllowRule { host, port })
+}
+
+/// Split `host[:port]` (with IPv6 literals in brackets). Returns `None` for
+/// an empty host or an unparseable port.
+fn split_host_port(authority: &str, default_port: Option<u16>) -> Option<(String, Option<u16>)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        match after.strip_prefix(':') {
+            Some(port) => (host, Some(port)),
+            None if after.is_empty() => (host, None),
+            None => return None,
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match port {
+        Some(port) => Some(port.parse::<u16>().ok()?),
+        None => default_port,
+    };
+    let host = normalize_host(host);
+    if host.is_empty() {
+        return None;
+    }
+    Some((host, port))
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+/// Where the filtering proxy for a policy can be reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyEndpoint {
+    /// Loopback address the proxy listens on. Commands are pointed at
+    /// `127.0.0.1` and this port.
+    pub addr: SocketAddr,
+    /// Unix socket serving the same proxy (unix only). The Linux sandbox
+    /// helper bridges the proxy port inside the command's network namespace
+    /// to it.
+    pub socket_path: Option<PathBuf>,
+}
+
+struct RunningProxy {
+    endpoint: ProxyEndpoint,
+    tasks: Vec<JoinHandle<()>>,
+    /// Private (0700) directory holding the unix socket; removed on drop.
+    #[cfg(unix)]
+    _socket_dir: tempfile::TempDir,
+}
+
+impl RunningProxy {
+    fn is_running(&self) -> bool {
+        self.tasks.iter().all(|task| !task.is_finished())
+    }
+}
+
+static PROXIES: OnceLock<Mutex<HashMap<Vec<String>, RunningProxy>>> = OnceLock::new();
+
+/// Endpoint of the filtering proxy serving `sandbox_policy`'s allowlist,
+/// starting it on first use. Returns `None` when the policy either allows
+/// all network access or none at all.
+pub async fn proxy_for_policy(sandbox_policy: &SandboxPolicy) -> io::Result<Option<ProxyEndpoint>> {
+    let allowlist = sandbox_policy.network_allowlist();
+    if allowlist.is_empty() {
+        return Ok(None);
+    }
+
+    let mut proxies = PROXIES.get_or_init(Default::default).lock().await;
+    if let Some(proxy) = proxies.get(allowlist)
+        && proxy.is_running()
+    {
+        return Ok(Some(proxy.endpoint.clone()));
+    }
+
+    let proxy = start_proxy(NetworkAllowlist::parse(allowlist)).await?;
+    let endpoint = proxy.endpoint.clone();
+    info!("started sandbox network proxy at {endpoint:?} for {allowlist:?}");
+    proxies.insert(allowlist.to_vec(), proxy);
+    Ok(Some(endpoint))
+}
+
+/// Point `env` at the proxy so a sandboxed command's HTTP(S) traffic goes
+/// through it. Only the sandbox spawners call this: a command that runs
+/// without a sandbox keeps its own network settings.
+pub(crate) fn apply_proxy_env(env: &mut HashMap<String, String>, endpoint: &ProxyEndpoint) {
+    let url = format!("http://{}", endpoint.addr);
+    for key in PROXY_ENV_VARS {
+        env.insert(key.to_string(), url.clone());
+    }
+    for key in NO_PROXY_ENV_VARS {
+        env.remove(key);
+    }
+}
+
+async fn start_proxy(allowlist: NetworkAllowlist) -> io::Result<RunningProxy> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let addr = listener.local_addr()?;
+    let allowlist = Arc::new(allowlist);
+
+    let mut tasks = Vec::new();
+    let tcp_allowlist = Arc::clone(&allowlist);
+    tasks.push(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => serve_client(stream, peer.to_string(), &tcp_allowlist),
+                Err(err) => warn!("sandbox network proxy failed to accept a connection: {err}"),
+            }
+        }
+    }));
+
+    #[cfg(unix)]
+    {
+        let socket_dir = tempfile::Builder::new().prefix("code-proxy-").tempdir()?;
+        let socket_path = socket_dir.path().join("proxy.sock");
+        let unix_listener = tokio::net::UnixListener::bind(&socket_path)?;
+        tasks.push(tokio::spawn(async move {
+            loop {
+                match unix_listener.accept().await {
+                    Ok((stream, _)) => serve_client(stream, "unix socket".to_string(), &allowlist),
+                    Err(err) => warn!("sandbox network proxy failed to accept a connection: {err}"),
+                }
+            }
+        }));
+        Ok(RunningProxy {
+            endpoint: ProxyEndpoint {
+                addr,
+                socket_path: Some(socket_path),
+            },
+            tasks,
+            _socket_dir: socket_dir,
+        })
+    }
+
+    #[cfg(not(unix))]
+    {
+        drop(allowlist);
+        Ok(RunningProxy {
+            endpoint: ProxyEndpoint {
+                addr,
+                socket_path: None,
+            },
+            tasks,
+        })
+    }
+}
+
+fn serve_client<S>(stream: S, peer: String, allowlist: &Arc<NetworkAllowlist>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let allowlist = Arc::clone(allowlist);
+    tokio::spawn(async move {
+        if let Err(err) = handle_client(stream, &allowlist).await {
+            debug!("sandbox network proxy connection from {peer} ended: {err}");
+        }
+    });
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ProxyRequest {
+    host: String,
+    port: u16,
+    /// Request head to send upstream for plain HTTP; `None` for `CONNECT`.
+    forward_head: Option<String>,
+}
+
+async fn handle_client<S>(mut client: S, allowlist: &NetworkAllowlist) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let Some((head, body_prefix)) = read_request_head(&mut client).await? else {
+        return respond(&mut client, "400 Bad Request", "request head too large").await;
+    };
+    let Some(request) = parse_request_head(&head) else {
+        return respond(&mut client, "400 Bad Request", "malformed proxy request").await;
+    };
+
+    if !allowlist.allows(&request.host, request.port) {
+        info!(
+            "sandbox network proxy blocked {}:{}",
+            request.host, request.port
+        );
+        let message = format!(
+            "{}:{} is not in the sandbox network allowlist",
+            request.host, request.port
+        );
+        return respond(&mut client, "403 Forbidden", &message).await;
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)).await {
+        Ok(stream) => stream,
+        Err(err) => {
+            let message = format!(
+                "failed to connect to {}:{}: {err}",
+                request.host, request.port
+            );
+            return respond(&mut client, "502 Bad Gateway", &message).await;
+        }
+    };
+
+    match &request.forward_head {
+        None => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+        }
+        Some(head) => upstream.write_all(head.as_bytes()).await?,
+    }
+    if !body_prefix.is_empty() {
+        upstream.write_all(&body_prefix).await?;
+    }
+    copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Read up to and including the blank line that ends the request head.
+/// Returns the head and any bytes already read past it, or `None` when the
+/// head exceeds [`MAX_REQUEST_HEAD_BYTES`].
+async fn read_request_head<S>(client: &mut S) -> io::Result<Option<(String, Vec<u8>)>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    loop {
+        if let Some(end) = find_head_end(&buf) {
+            let rest = buf.split_off(end);
+            return Ok(Some((String::from_utf8_lossy(&buf).into_owned(), rest)));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Ok(None);
+        }
+        let read = client.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "client closed before sending a request",
+            ));
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+}
+
+fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+/// Parse a proxy request head: either `CONNECT host:port` or an
+/// absolute-form `http://` request, which is rewritten to origin form with
+/// `Connection: close` so every request on the connection is checked.
+fn parse_request_head(head: &str) -> Option<ProxyRequest> {
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    let version = parts.next()?;
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(target, Some(443))?;
+        return Some(ProxyRequest {
+            host,
+            port: port?,
+            forward_head: None,
+        });
+    }
+
+    let rest = target
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("http://"))
+        .map(|_| &target[7..])?;
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+    let (host, port) = split_host_port(authority, Some(80))?;
+
+    let mut forward_head = format!("{method} {path} {version}\r\n");
+    for line in lines.filter(|line| !line.is_empty()) {
+        let name = line.split(':').next().unwrap_or_default().trim();
+        if [
+            "connection",
+            "proxy-connection",
+            "proxy-authorization",
+            "keep-alive",
+        ]
+        .iter()
+        .any(|hop| name.eq_ignore_ascii_case(hop))
+        {
+            continue;
+        }
+        forward_head.push_str(line);
+        forward_head.push_str("\r\n");
+    }
+    forward_head.push_str("Connection: close\r\n\r\n");
+
+    Some(ProxyRequest {
+        host,
+        port: port?,
+        forward_head: Some(forward_head),
+    })
+}
+
+async fn respond<S>(client: &mut S, status: &str, message: &str) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{message}",
+        message.len()
+    );
+    client.write_all(response.as_bytes()).await?;
+    client.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn allowlist(entries: &[&str]) -> NetworkAllowlist {
+        let entries: Vec<String> = entries.iter().map(|entry| entry.to_string()).collect();
+        NetworkAllowlist::parse(&entries)
+    }
+
+    #[test]
+    fn allowlist_matches_hosts_wildcards_and_ports() {
+        let list = allowlist(&[
+            "crates.io",
+            "*.npmjs.org",
+            "https://Static.Rust-Lang.org/dist",
+            "localhost:8080",
+            "bad:port",
+        ]);
+
+        assert!(list.allows("crates.io", 443));
+        assert!(list.allows("CRATES.IO.", 80));
+        assert!(!list.allows("crates.io", 22));
+        assert!(!list.allows("index.crates.io", 443));
+        assert!(list.allows("registry.npmjs.org", 443));
+        assert!(!list.allows("npmjs.org", 443));
+        assert!(list.allows("static.rust-lang.org", 443));
+        assert!(list.allows("localhost", 8080));
+        assert!(!list.allows("localhost", 443));
+        assert!(!list.allows("evil.example", 443));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        assert_eq!(
+            parse_request_head(
+                "CONNECT index.crates.io:443 HTTP/1.1\r\nHost: index.crates.io\r\n\r\n"
+            ),
+            Some(ProxyRequest {
+                host: "index.crates.io".to_string(),
+                port: 443,
+                forward_head: None,
+            })
+        );
+
+        assert_eq!(
+            parse_request_head(
+                "GET http://example.com/a?b=1 HTTP/1.1\r\nHost: example.com\r\nProxy-Connection: keep-alive\r\n\r\n"
+            ),
+            Some(ProxyRequest {
+                host: "example.com".to_string(),
+                port: 80,
+                forward_head: Some(
+                    "GET /a?b=1 HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                ),
+            })
+        );
+
+        assert_eq!(parse_request_head("GET /relative HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[tokio::test]
+    async fn proxy_tunnels_allowed_hosts_and_rejects_others() {
+        let upstream = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            stream.write_all(&buf).await.unwrap();
+        });
+
+        let proxy = start_proxy(allowlist(&[&format!("127.0.0.1:{upstream_port}")]))
+            .await
+            .unwrap();
+
+        let mut client = TcpStream::connect(proxy.endpoint.addr).await.unwrap();
+        client
+            .write_all(format!("CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\nping").as_bytes())
+            .await
+            .unwrap();
+        let mut response = vec![0u8; "HTTP/1.1 200 Connection Established\r\n\r\nping".len()];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(
+            String::from_utf8(response).unwrap(),
+            "HTTP/1.1 200 Connection Established\r\n\r\nping"
+        );
+
+        let mut blocked = TcpStream::connect(proxy.endpoint.addr).await.unwrap();
+        blocked
+            .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        blocked.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{response}");
+
+        #[cfg(unix)]
+        {
+            let socket_path = proxy.endpoint.socket_path.clone().unwrap();
+            let mut over_socket = tokio::net::UnixStream::connect(socket_path).await.unwrap();
+            over_socket
+                .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = String::new();
+            over_socket.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{response}");
+        }
+
+        for task in &proxy.tasks {
+            task.abort();
+        }
+    }
+}
@@ -6,6 +6,7 @@ use serde_json::Value as JsonValue;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use crate::agent_tool::create_agent_tool;
 use crate::model_family::ModelFamily;
@@ -450,7 +451,7 @@ Long-running commands may be backgrounded after an initial window. Use `wait` to
         SandboxPolicy::DangerFullAccess => {
             "Runs a shell command and returns its output. Output streams live to the UI. Long-running commands may be backgrounded after an initial window. Use `wait` to await background tasks.".to_string()
         }
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             "Runs a shell command and returns its output. Output streams live to the UI. Long-running commands may be backgrounded after an initial window. Use `wait` to await background tasks.".to_string()
         }
     };
@@ -513,6 +514,151 @@ pub(crate) fn create_tools_json_for_chat_completions_api(
     Ok(tools_json)
 }
 
+/// Name of the single string parameter that carries a freeform tool's input
+/// on wire APIs that only have function tools.
+pub(crate) const FREEFORM_INPUT_PARAM: &str = "input";
+
+/// Describes a freeform tool as a function whose only parameter is the raw
+/// input string. The grammar goes into the description so the model still
+/// knows the expected format.
+fn freeform_tool_as_function(tool: &FreeformTool) -> ResponsesApiTool {
+    let FreeformTool {
+        name,
+        description,
+        format,
+    } = tool;
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        FREEFORM_INPUT_PARAM.to_string(),
+        JsonSchema::String {
+            description: Some(format!(
+                "The raw tool input. It must match this {} {}:\n{}",
+                format.syntax, format.r#type, format.definition
+            )),
+            allowed_values: None,
+        },
+    );
+    ResponsesApiTool {
+        name: name.clone(),
+        description: description.clone(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![FREEFORM_INPUT_PARAM.to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    }
+}
+
+/// The function and freeform tools as plain function tools. Freeform tools
+/// take their input as one string parameter (see [`FREEFORM_INPUT_PARAM`]).
+fn tools_as_functions(tools: &[OpenAiTool]) -> impl Iterator<Item = ResponsesApiTool> + '_ {
+    tools.iter().filter_map(|tool| match tool {
+        OpenAiTool::Function(function) => Some(function.clone()),
+        OpenAiTool::Freeform(freeform) => Some(freeform_tool_as_function(freeform)),
+        OpenAiTool::LocalShell {} | OpenAiTool::WebSearch(_) => None,
+    })
+}
+
+/// Names of the freeform tools, whose calls come back as function calls on
+/// wire APIs that only have function tools and are turned back into custom
+/// tool calls.
+pub(crate) fn freeform_tool_names(tools: &[OpenAiTool]) -> HashSet<String> {
+    tools
+        .iter()
+        .filter_map(|tool| match tool {
+            OpenAiTool::Freeform(FreeformTool { name, .. }) => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rewrites the function and freeform tools as Anthropic Messages API tool
+/// definitions (`name`, `description`, `input_schema`). Other tool types have
+/// no Messages API equivalent and are dropped.
+pub(crate) fn create_tools_json_for_anthropic_messages_api(
+    tools: &[OpenAiTool],
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    tools_as_functions(tools)
+        .map(|tool| -> crate::error::Result<serde_json::Value> {
+            Ok(json!({
+                "name": tool.name,
+                "description": tool.description,
+                "input_schema": serde_json::to_value(&tool.parameters)?,
+            }))
+        })
+        .collect()
+}
+
+/// Converts the function and freeform tools to Gemini `functionDeclarations`.
+/// Gemini takes an OpenAPI-style schema with upper-case type names and
+/// rejects objects with empty `properties`, so tools without parameters omit
+/// them entirely.
+pub(crate) fn create_function_declarations_for_gemini_api(tools: &[OpenAiTool]) -> Vec<JsonValue> {
+    tools_as_functions(tools)
+        .map(|tool| {
+            let mut declaration = json!({
+                "name": tool.name,
+                "description": tool.description,
+            });
+            if !matches!(&tool.parameters, JsonSchema::Object { properties, .. } if properties.is_empty())
+            {
+                declaration["parameters"] = gemini_schema(&tool.parameters);
+            }
+            declaration
+        })
+        .collect()
+}
+
+fn gemini_schema(schema: &JsonSchema) -> JsonValue {
+    let (mut value, description) = match schema {
+        JsonSchema::Boolean { description } => (json!({ "type": "BOOLEAN" }), description),
+        JsonSchema::String {
+            description,
+            allowed_values,
+        } => {
+            let mut value = json!({ "type": "STRING" });
+            if let Some(allowed_values) = allowed_values {
+                value["format"] = json!("enum");
+                value["enum"] = json!(allowed_values);
+            }
+            (value, description)
+        }
+        JsonSchema::Number { description } => (json!({ "type": "NUMBER" }), description),
+        JsonSchema::Array { items, description } => (
+            json!({ "type": "ARRAY", "items": gemini_schema(items) }),
+            description,
+        ),
+        JsonSchema::Object {
+            properties,
+            required,
+            ..
+        } => {
+            let mut value = json!({ "type": "OBJECT" });
+            if !properties.is_empty() {
+                let properties: serde_json::Map<String, JsonValue> = properties
+                    .iter()
+                    .map(|(name, schema)| (name.clone(), gemini_schema(schema)))
+                    .collect();
+                value["properties"] = JsonValue::Object(properties);
+            }
+            let required: Vec<&String> = required
+                .iter()
+                .flatten()
+                .filter(|name| properties.contains_key(*name))
+                .collect();
+            if !required.is_empty() {
+                value["required"] = json!(required);
+            }
+            (value, &None)
+        }
+    };
+    if let Some(description) = description {
+        value["description"] = json!(description);
+    }
+    value
+}
+
 pub(crate) fn mcp_tool_to_openai_tool(
     fully_qualified_name: String,
     tool: mcp_types::Tool,
@@ -1040,6 +1186,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn gemini_function_declarations_use_openapi_schema() {
+        let tools = vec![
+            OpenAiTool::Function(ResponsesApiTool {
+                name: "read".to_string(),
+                description: "Read a file".to_string(),
+                strict: false,
+                parameters: JsonSchema::Object {
+                    properties: BTreeMap::from([
+                        (
+                            "path".to_string(),
+                            JsonSchema::String {
+                                description: Some("File path".to_string()),
+                                allowed_values: None,
+                            },
+                        ),
+                        (
+                            "lines".to_string(),
+                            JsonSchema::Array {
+                                items: Box::new(JsonSchema::Number { description: None }),
+                                description: None,
+                            },
+                        ),
+                    ]),
+                    required: Some(vec!["path".to_string(), "missing".to_string()]),
+                    additional_properties: Some(false.into()),
+                },
+            }),
+            OpenAiTool::Function(ResponsesApiTool {
+                name: "now".to_string(),
+                description: "Current time".to_string(),
+                strict: false,
+                parameters: JsonSchema::Object {
+                    properties: BTreeMap::new(),
+                    required: None,
+                    additional_properties: None,
+                },
+            }),
+            OpenAiTool::LocalShell {},
+        ];
+
+        assert_eq!(
+            create_function_declarations_for_gemini_api(&tools),
+            vec![
+                json!({
+                    "name": "read",
+                    "description": "Read a file",
+                    "parameters": {
+                        "type": "OBJECT",
+                        "properties": {
+                            "lines": {"type": "ARRAY", "items": {"type": "NUMBER"}},
+                            "path": {"type": "STRING", "description": "File path"},
+                        },
+                        "required": ["path"],
+                    },
+                }),
+                json!({"name": "now", "description": "Current time"}),
+            ]
+        );
+    }
+
     #[test]
     fn test_get_openai_tools() {
         let model_family = find_family_for_model("codex-mini-latest")
@@ -1047,7 +1254,7 @@ mod tests {
         let mut config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             true,
             false,
             true,
@@ -1078,7 +1285,7 @@ mod tests {
         let mut config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             true,
             false,
             true,
@@ -1108,7 +1315,7 @@ mod tests {
         let mut config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             true,
             false,
             true,
@@ -1138,7 +1345,7 @@ mod tests {
         let mut config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             false,
             false,
             true,
@@ -1255,7 +1462,7 @@ mod tests {
         let mut config = ToolsConfig::new_from_params(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
@@ -1394,7 +1601,7 @@ mod tests {
         let _config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             false,
             false,
             true,
@@ -1409,7 +1616,7 @@ mod tests {
         let mut config = ToolsConfig::new_from_params(&ToolsConfigParams {
             model_family: &model_family,
             approval_policy: AskForApproval::Never,
-            sandbox_policy: SandboxPolicy::ReadOnly,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
             include_plan_tool: false,
             include_apply_patch_tool: false,
             include_web_search_request: true,
@@ -1483,7 +1690,7 @@ mod tests {
         let mut config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             false,
             false,
             true,
@@ -1556,7 +1763,7 @@ mod tests {
         let mut config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             false,
             false,
             true,
@@ -1630,7 +1837,7 @@ mod tests {
         let mut config = ToolsConfig::new(
             &model_family,
             AskForApproval::Never,
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             false,
             false,
             true,
@@ -1700,6 +1907,8 @@ mod tests {
         let sandbox_policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec!["workspace".into()],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
             allow_git_writes: true,
@@ -1736,7 +1945,7 @@ mod tests {
 
     #[test]
     fn test_shell_tool_for_sandbox_readonly() {
-        let tool = super::create_shell_tool_for_sandbox(&SandboxPolicy::ReadOnly);
+        let tool = super::create_shell_tool_for_sandbox(&SandboxPolicy::new_read_only_policy());
         let OpenAiTool::Function(ResponsesApiTool {
             description, name, ..
         }) = &tool
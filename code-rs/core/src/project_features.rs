@@ -129,3 +129,113 @@ fn resolve_optional_path(raw: &Option<String>, project_root: &Path) -> Option<Pa
         Some(project_root.join(path))
     }
 }
+
+/// Verdict a blocking `tool.before` / `file.before_write` hook can print on
+/// stdout to gate the pending command or patch.
+///
+/// Hooks emit a single JSON object (either as the whole stdout or as its last
+/// non-empty line), for example
+/// `{"decision": "deny", "reason": "migrations/ is read-only"}` or
+/// `{"decision": "rewrite", "argv": ["cargo", "test", "--offline"]}`.
+/// Hooks that print nothing parseable are treated as `Allow` so existing
+/// fire-and-forget hooks keep working unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookVerdict {
+    Allow,
+    Deny { reason: Option<String> },
+    Rewrite { argv: Vec<String> },
+    RequireApproval { reason: Option<String> },
+}
+
+#[derive(serde::Deserialize)]
+struct RawHookVerdict {
+    decision: String,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default, alias = "command")]
+    argv: Option<Vec<String>>,
+}
+
+impl HookVerdict {
+    /// Parse the verdict printed by a hook. Returns `None` when the output
+    /// does not contain a recognizable verdict object.
+    pub fn parse(stdout: &str) -> Option<Self> {
+        let trimmed = stdout.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let raw = serde_json::from_str::<RawHookVerdict>(trimmed)
+            .ok()
+            .or_else(|| {
+                trimmed
+                    .lines()
+                    .rev()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .filter(|line| line.starts_with('{'))
+                    .and_then(|line| serde_json::from_str::<RawHookVerdict>(line).ok())
+            })?;
+        let reason = raw
+            .reason
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty());
+        match raw.decision.trim().to_ascii_lowercase().as_str() {
+            "allow" | "approve" => Some(HookVerdict::Allow),
+            "deny" | "block" | "reject" => Some(HookVerdict::Deny { reason }),
+            "ask" | "require_approval" | "require-approval" => {
+                Some(HookVerdict::RequireApproval { reason })
+            }
+            "rewrite" => match raw.argv {
+                Some(argv) if !argv.is_empty() => Some(HookVerdict::Rewrite { argv }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_deny_with_reason() {
+        let verdict =
+            HookVerdict::parse(r#"{"decision":"deny","reason":"never touch migrations/"}"#);
+        assert_eq!(
+            verdict,
+            Some(HookVerdict::Deny {
+                reason: Some("never touch migrations/".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn parses_verdict_from_last_line() {
+        let stdout =
+            "checking policy...\n{\"decision\":\"rewrite\",\"argv\":[\"cargo\",\"test\"]}\n";
+        assert_eq!(
+            HookVerdict::parse(stdout),
+            Some(HookVerdict::Rewrite {
+                argv: vec!["cargo".to_string(), "test".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn parses_require_approval() {
+        assert_eq!(
+            HookVerdict::parse(r#"{"decision":"ask"}"#),
+            Some(HookVerdict::RequireApproval { reason: None })
+        );
+    }
+
+    #[test]
+    fn ignores_unstructured_output() {
+        assert_eq!(HookVerdict::parse(""), None);
+        assert_eq!(HookVerdict::parse("all good"), None);
+        assert_eq!(HookVerdict::parse(r#"{"decision":"rewrite"}"#), None);
+        assert_eq!(HookVerdict::parse(r#"{"decision":"maybe"}"#), None);
+    }
+}
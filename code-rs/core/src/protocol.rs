@@ -168,6 +168,24 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Approve or deny an MCP server's `sampling/createMessage` request.
+    McpSamplingApproval {
+        /// The id from the corresponding `McpSamplingRequestEvent`.
+        id: String,
+        /// The user's decision in response to the request.
+        decision: ReviewDecision,
+    },
+
+    /// Answer an MCP server's `elicitation/create` request.
+    ResolveMcpElicitation {
+        /// The id from the corresponding `McpElicitationRequestEvent`.
+        id: String,
+        action: McpElicitationAction,
+        /// Form values keyed by property name; only meaningful for `Accept`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<serde_json::Value>,
+    },
+
     /// Update a specific validation tool toggle for the session.
     UpdateValidationTool { name: String, enable: bool },
 
@@ -201,10 +219,31 @@ pub enum Op {
     /// Request a single history entry identified by `log_id` + `offset`.
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
 
+    /// Search the history log identified by `log_id` for entries containing
+    /// `query` (case-insensitive). When `repo_root` is set only entries
+    /// recorded under that directory match.
+    /// Reply is delivered via `EventMsg::SearchHistoryResponse`.
+    SearchHistoryRequest {
+        query: String,
+        log_id: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        repo_root: Option<PathBuf>,
+        limit: usize,
+    },
+
     /// Request the list of available custom prompts.
     /// Reply is delivered via `EventMsg::ListCustomPromptsResponse`.
     ListCustomPrompts,
 
+    /// Request the resources and prompts exposed by connected MCP servers.
+    /// Reply is delivered via `EventMsg::ListMcpCatalogResponse`.
+    ListMcpCatalog,
+
+    /// Stop and start the named MCP server again, re-listing its tools for
+    /// the next turn. The outcome is reported as a `BackgroundEvent` (or an
+    /// `Error` when the restart fails).
+    RestartMcpServer { server: String },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
@@ -261,7 +300,13 @@ pub enum SandboxPolicy {
 
     /// Read-only access to the entire file-system.
     #[serde(rename = "read-only")]
-    ReadOnly,
+    ReadOnly {
+        /// Paths or globs (`~/.ssh`, `**/.env*`) that must not be read from
+        /// within the sandbox. Relative entries are resolved against the
+        /// sandbox cwd.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<String>,
+    },
 
     /// Same as `ReadOnly` but additionally grants write access to the current
     /// working directory ("workspace").
@@ -277,6 +322,18 @@ pub enum SandboxPolicy {
         #[serde(default)]
         network_access: bool,
 
+        /// Hosts reachable through the sandbox's filtering proxy when
+        /// `network_access` is `false`. Entries are `host`, `*.domain` or
+        /// `host:port`; without a port only 80 and 443 are allowed. Empty by
+        /// default, which blocks all network access.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        network_allowlist: Vec<String>,
+
+        /// Paths or globs that must not be read from within the sandbox, even
+        /// beneath writable roots. See `ReadOnly::deny_read`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<String>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -335,7 +392,9 @@ impl FromStr for SandboxPolicy {
 impl SandboxPolicy {
     /// Returns a policy with read-only disk access and no network.
     pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
+        SandboxPolicy::ReadOnly {
+            deny_read: Vec::new(),
+        }
     }
 
     /// Returns a policy that can read the entire disk, but can only write to
@@ -345,21 +404,46 @@ impl SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
             allow_git_writes: true,
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
+    /// Returns `false` when the policy lists `deny_read` paths.
     pub fn has_full_disk_read_access(&self) -> bool {
-        true
+        self.deny_read().is_empty()
+    }
+
+    /// Paths or globs that must not be read from within the sandbox, as
+    /// configured (unresolved).
+    pub fn deny_read(&self) -> &[String] {
+        match self {
+            SandboxPolicy::DangerFullAccess => &[],
+            SandboxPolicy::ReadOnly { deny_read } => deny_read,
+            SandboxPolicy::WorkspaceWrite { deny_read, .. } => deny_read,
+        }
+    }
+
+    /// Returns this policy with its `deny_read` list replaced. Has no effect
+    /// on `DangerFullAccess`, which has no sandbox to enforce it.
+    pub fn with_deny_read(mut self, paths: Vec<String>) -> Self {
+        match &mut self {
+            SandboxPolicy::DangerFullAccess => {}
+            SandboxPolicy::ReadOnly { deny_read }
+            | SandboxPolicy::WorkspaceWrite { deny_read, .. } => {
+                *deny_read = paths;
+            }
+        }
+        self
     }
 
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { .. } => false,
         }
     }
@@ -367,24 +451,42 @@ impl SandboxPolicy {
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
         }
     }
 
+    /// Hosts that commands may reach through the filtering network proxy.
+    /// Empty unless this is a `WorkspaceWrite` policy with `network_access`
+    /// disabled.
+    pub fn network_allowlist(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                network_allowlist,
+                ..
+            } => network_allowlist,
+            SandboxPolicy::DangerFullAccess
+            | SandboxPolicy::ReadOnly { .. }
+            | SandboxPolicy::WorkspaceWrite { .. } => &[],
+        }
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
         match self {
             SandboxPolicy::DangerFullAccess => Vec::new(),
-            SandboxPolicy::ReadOnly => Vec::new(),
+            SandboxPolicy::ReadOnly { .. } => Vec::new(),
             SandboxPolicy::WorkspaceWrite {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 allow_git_writes,
                 network_access: _,
+                network_allowlist: _,
+                deny_read: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -464,6 +566,23 @@ pub enum InputItem {
         /// Optional metadata to help identify the image (e.g., "screenshot:1234567890:https://example.com")
         metadata: Option<String>,
     },
+
+    /// Resource exposed by an MCP server (an `@server:uri` mention). The
+    /// session reads it via `resources/read` and inlines the contents before
+    /// the turn is sent to the model.
+    McpResource {
+        server: String,
+        uri: String,
+    },
+
+    /// Prompt exposed by an MCP server. The session renders it via
+    /// `prompts/get` and replaces this item with the resulting messages.
+    McpPrompt {
+        server: String,
+        name: String,
+        #[serde(default)]
+        arguments: HashMap<String, String>,
+    },
 }
 
 /// Event Queue Entry - events from agent
@@ -741,6 +860,14 @@ pub enum EventMsg {
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// An MCP server asked to run a completion through the session's model.
+    /// Answer with `Op::McpSamplingApproval`.
+    McpSamplingRequest(McpSamplingRequestEvent),
+
+    /// An MCP server asked the user to fill in a form. Answer with
+    /// `Op::ResolveMcpElicitation`.
+    McpElicitationRequest(McpElicitationRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that the agent is about to apply a code patch. Mirrors
@@ -755,9 +882,15 @@ pub enum EventMsg {
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
+    /// Response to SearchHistoryRequest.
+    SearchHistoryResponse(SearchHistoryResponseEvent),
+
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// Resources and prompts exposed by connected MCP servers.
+    ListMcpCatalogResponse(ListMcpCatalogResponseEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     /// Browser screenshot has been captured and is ready for display
@@ -1154,6 +1287,53 @@ pub struct ApplyPatchApprovalRequestEvent {
     pub grant_root: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpSamplingRequestEvent {
+    /// Identifier to echo back in `Op::McpSamplingApproval`.
+    pub id: String,
+    /// Name of the MCP server making the request.
+    pub server: String,
+    /// Conversation the server wants completed.
+    pub messages: Vec<mcp_types::SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub max_tokens: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpElicitationRequestEvent {
+    /// Identifier to echo back in `Op::ResolveMcpElicitation`.
+    pub id: String,
+    /// Name of the MCP server making the request.
+    pub server: String,
+    /// Message to show the user above the form.
+    pub message: String,
+    /// Flat JSON schema describing the requested fields.
+    pub requested_schema: mcp_types::ElicitRequestParamsRequestedSchema,
+}
+
+/// How the user answered an MCP elicitation request.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum McpElicitationAction {
+    /// The user submitted the form.
+    Accept,
+    /// The user explicitly declined to provide the information.
+    Decline,
+    /// The user dismissed the request without choosing.
+    Cancel,
+}
+
+impl McpElicitationAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            McpElicitationAction::Accept => "accept",
+            McpElicitationAction::Decline => "decline",
+            McpElicitationAction::Cancel => "cancel",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BackgroundEventEvent {
     pub message: String,
@@ -1195,6 +1375,32 @@ pub struct GetHistoryEntryResponseEvent {
     pub entry: Option<HistoryEntry>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchHistoryResponseEvent {
+    /// The query this response answers, so stale responses can be dropped.
+    pub query: String,
+    /// Whether the search was restricted to the repository root.
+    pub repo_only: bool,
+    /// Matching entries, newest first, without repeated texts.
+    pub matches: Vec<HistorySearchMatch>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistorySearchMatch {
+    pub offset: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ListMcpCatalogResponseEvent {
+    /// Resources keyed by MCP server name.
+    pub resources: HashMap<String, Vec<mcp_types::Resource>>,
+    /// Resource templates keyed by MCP server name.
+    pub resource_templates: HashMap<String, Vec<mcp_types::ResourceTemplate>>,
+    /// Prompts keyed by MCP server name.
+    pub prompts: HashMap<String, Vec<mcp_types::Prompt>>,
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct SessionConfiguredEvent {
     /// Unique id for this session.
@@ -1262,6 +1468,17 @@ pub struct AgentInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub token_count: Option<u64>,
+    /// Token usage breakdown reported by the agent, for cost accounting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub token_usage: Option<AgentTokenUsage>,
+}
+
+/// Cumulative token usage of an agent run and the model that spent it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AgentTokenUsage {
+    pub model: String,
+    pub usage: TokenUsage,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
@@ -1304,6 +1521,9 @@ impl From<code_protocol::protocol::ReviewDecision> for ReviewDecision {
 pub enum FileChange {
     Add {
         content: String,
+        /// Unix permission bits the patch sets on the new file.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<u32>,
     },
     Delete,
     Update {
@@ -1311,6 +1531,9 @@ pub enum FileChange {
         move_path: Option<PathBuf>,
         original_content: String,
         new_content: String,
+        /// Unix permission bits the patch sets on the resulting file.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<u32>,
     },
 }
 
@@ -1360,6 +1583,21 @@ mod tests {
     #![allow(clippy::unwrap_used)]
     use super::*;
 
+    /// `ReadOnly` used to be a unit variant; configs and clients that predate
+    /// `deny_read` must keep working.
+    #[test]
+    fn read_only_policy_accepts_legacy_json() {
+        let legacy = serde_json::json!({ "mode": "read-only" });
+        assert_eq!(
+            serde_json::from_value::<SandboxPolicy>(legacy.clone()).unwrap(),
+            SandboxPolicy::new_read_only_policy()
+        );
+        assert_eq!(
+            serde_json::to_value(SandboxPolicy::new_read_only_policy()).unwrap(),
+            legacy
+        );
+    }
+
     /// Serialize Event to verify that its JSON representation has the expected
     /// amount of nesting.
     #[test]
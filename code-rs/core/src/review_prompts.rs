@@ -0,0 +1,32 @@
+//! Prompts for the review scopes offered by `/review` and `code review`, so
+//! both front ends ask the reviewer the same thing.
+
+/// Review the staged, unstaged and untracked changes.
+pub fn workspace_review_prompt() -> String {
+    "Review the current workspace changes (staged, unstaged, and untracked files) and highlight bugs, regressions, risky patterns, and missing tests before merge.".to_string()
+}
+
+/// Review a single commit; `subject` is quoted when known.
+pub fn commit_review_prompt(sha: &str, subject: Option<&str>) -> String {
+    match subject {
+        Some(subject) => format!(
+            "Review the code changes introduced by commit {sha} (\"{subject}\"). Provide prioritized, actionable findings."
+        ),
+        None => format!(
+            "Review the code changes introduced by commit {sha}. Provide prioritized, actionable findings."
+        ),
+    }
+}
+
+/// Review what the current branch would merge into `base`. Without a
+/// current branch (detached HEAD) the prompt only names the base.
+pub fn branch_review_prompt(base: &str, current: Option<&str>) -> String {
+    match current {
+        Some(current) => format!(
+            "Review the code changes between the current branch '{current}' and '{base}'. Identify the intent of the changes in '{current}' and ensure no obvious gaps remain. Find all genuine bugs or regressions which need to be addressed before merging. Return ALL issues which need to be addressed, not just the first one you find."
+        ),
+        None => format!(
+            "Review the code changes that would merge into '{base}'. Identify bugs, regressions, risky patterns, and missing tests before merge."
+        ),
+    }
+}
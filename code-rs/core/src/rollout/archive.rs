@@ -0,0 +1,76 @@
+//! Archiving moves a session out of the sessions tree.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::ARCHIVED_SESSIONS_SUBDIR;
+use crate::context_timeline::timeline_path_for_rollout;
+
+/// Moves the rollout at `rollout_path` into `<code_home>/archived_sessions`
+/// and deletes the context timeline stored next to it, which is only used to
+/// resume the session. Returns the archived rollout path.
+///
+/// The session must be shut down first so nothing writes either file again.
+pub async fn archive_rollout(code_home: &Path, rollout_path: &Path) -> io::Result<PathBuf> {
+    let file_name = rollout_path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("rollout path `{}` has no file name", rollout_path.display()),
+        )
+    })?;
+    let archive_dir = code_home.join(ARCHIVED_SESSIONS_SUBDIR);
+    tokio::fs::create_dir_all(&archive_dir).await?;
+    let archived = archive_dir.join(file_name);
+    tokio::fs::rename(rollout_path, &archived).await?;
+
+    match tokio::fs::remove_file(timeline_path_for_rollout(rollout_path)).await {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    Ok(archived)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::rollout::SESSIONS_SUBDIR;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn archive_moves_rollout_and_removes_timeline() {
+        let code_home = tempfile::tempdir().unwrap();
+        let day_dir = code_home.path().join(SESSIONS_SUBDIR).join("2025/10/06");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        let rollout = day_dir.join("rollout-2025-10-06T12-00-00-id.jsonl");
+        let timeline = timeline_path_for_rollout(&rollout);
+        std::fs::write(&rollout, "{}\n").unwrap();
+        std::fs::write(&timeline, "{}\n").unwrap();
+
+        let archived = archive_rollout(code_home.path(), &rollout).await.unwrap();
+
+        assert_eq!(
+            archived,
+            code_home
+                .path()
+                .join(ARCHIVED_SESSIONS_SUBDIR)
+                .join("rollout-2025-10-06T12-00-00-id.jsonl")
+        );
+        assert_eq!(std::fs::read_to_string(&archived).unwrap(), "{}\n");
+        assert!(!rollout.exists());
+        assert!(!timeline.exists());
+    }
+
+    #[tokio::test]
+    async fn archive_without_timeline_succeeds() {
+        let code_home = tempfile::tempdir().unwrap();
+        let rollout = code_home.path().join("rollout-id.jsonl");
+        std::fs::write(&rollout, "{}\n").unwrap();
+
+        let archived = archive_rollout(code_home.path(), &rollout).await.unwrap();
+        assert!(archived.exists());
+    }
+}
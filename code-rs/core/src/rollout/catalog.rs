@@ -21,6 +21,7 @@ use tracing::warn;
 use uuid::Uuid;
 
 use super::SESSIONS_SUBDIR;
+use super::text_index;
 
 const INDEX_SUBDIR: &str = "sessions/index";
 const CATALOG_FILENAME: &str = "catalog.jsonl";
@@ -336,6 +337,9 @@ impl SessionCatalog {
         if changed {
             self.save()?;
         }
+        if let Err(err) = text_index::update_from_catalog(code_home, self).await {
+            warn!("failed to update session text index: {err}");
+        }
 
         Ok(result)
     }
@@ -588,6 +592,7 @@ pub async fn update_catalog_entry(
         }
     }
 
+    text_index::schedule_update(code_home);
     Ok(())
 }
 
@@ -16,6 +16,7 @@ use uuid::Uuid;
 
 use super::SESSIONS_SUBDIR;
 use crate::config::resolve_code_path_for_read;
+use crate::context_timeline::is_timeline_path;
 use crate::protocol::EventMsg;
 use crate::protocol::event_msg_from_protocol;
 use code_protocol::protocol::RolloutItem;
@@ -469,6 +470,9 @@ pub async fn find_conversation_path_by_id_str(
             .and_then(|ext| ext.to_str())
             .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
         {
+            if is_timeline_path(&candidate) {
+                continue;
+            }
             return Ok(Some(candidate));
         }
 
@@ -533,6 +537,30 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn skips_context_timeline_next_to_rollout() -> anyhow::Result<()> {
+        let temp = tempdir()?;
+        let code_home = temp.path();
+        let uuid_str = Uuid::new_v4().to_string();
+
+        let sessions_dir = code_home
+            .join(SESSIONS_SUBDIR)
+            .join("2025")
+            .join("10")
+            .join("06");
+        fs::create_dir_all(&sessions_dir)?;
+
+        let base = format!("rollout-2025-10-06T12-00-00-{uuid_str}");
+        let rollout_path = sessions_dir.join(format!("{base}.jsonl"));
+        fs::write(&rollout_path, "{\"item\":\"dummy\"}\n")?;
+        fs::write(sessions_dir.join(format!("{base}.timeline.jsonl")), "")?;
+
+        let resolved = find_conversation_path_by_id_str(code_home, &uuid_str).await?;
+        assert_eq!(resolved, Some(rollout_path));
+
+        Ok(())
+    }
+
     #[test]
     fn snapshot_conversion_rewrites_extension() {
         let snapshot = PathBuf::from(
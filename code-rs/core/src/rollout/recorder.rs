@@ -43,10 +43,19 @@ use code_protocol::protocol::RolloutItem;
 use code_protocol::protocol::RolloutLine;
 use code_protocol::protocol::SessionMeta;
 use code_protocol::protocol::SessionMetaLine;
+use code_protocol::protocol::TurnContextItem;
 
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SessionStateSnapshot {}
 
+/// The settings a rollout was recorded with.
+#[derive(Debug, Default, Clone)]
+pub struct RolloutSettings {
+    pub session_meta: Option<SessionMeta>,
+    /// The most recent turn context, if the rollout recorded any.
+    pub turn_context: Option<TurnContextItem>,
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SavedSession {
     pub session: SessionMeta,
@@ -420,6 +429,62 @@ impl RolloutRecorder {
         }))
     }
 
+    /// Returns the response items recorded in the rollout at `path`, in
+    /// order. Compaction summaries are returned as the message that replaced
+    /// the compacted history; events and session metadata are skipped.
+    pub async fn get_rollout_response_items(path: &Path) -> std::io::Result<Vec<ResponseItem>> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let mut items = Vec::new();
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<RolloutLine>(line) {
+                Ok(rollout_line) => match rollout_line.item {
+                    RolloutItem::ResponseItem(item) => items.push(item),
+                    RolloutItem::Compacted(compacted) => items.push(ResponseItem::from(compacted)),
+                    RolloutItem::SessionMeta(_)
+                    | RolloutItem::TurnContext(_)
+                    | RolloutItem::Event(_) => {}
+                },
+                Err(e) => {
+                    warn!("failed to parse rollout line: {line:?}, error: {e}");
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// Returns the session metadata and the latest turn context recorded in
+    /// the rollout at `path`, so a conversation replaying it can start with
+    /// the same settings.
+    pub async fn get_rollout_settings(path: &Path) -> std::io::Result<RolloutSettings> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let mut settings = RolloutSettings::default();
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<RolloutLine>(line) {
+                Ok(rollout_line) => match rollout_line.item {
+                    RolloutItem::SessionMeta(meta_line) => {
+                        settings.session_meta.get_or_insert(meta_line.meta);
+                    }
+                    RolloutItem::TurnContext(turn_context) => {
+                        settings.turn_context = Some(turn_context);
+                    }
+                    RolloutItem::ResponseItem(_)
+                    | RolloutItem::Compacted(_)
+                    | RolloutItem::Event(_) => {}
+                },
+                Err(e) => {
+                    warn!("failed to parse rollout line: {line:?}, error: {e}");
+                }
+            }
+        }
+        Ok(settings)
+    }
+
     pub async fn shutdown(&self) -> std::io::Result<()> {
         let (tx_done, rx_done) = oneshot::channel();
         match self.tx.send(RolloutCmd::Shutdown { ack: tx_done }).await {
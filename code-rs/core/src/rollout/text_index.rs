@@ -0,0 +1,674 @@
+//! Full-text search index over session rollouts.
+//!
+//! The index lives next to `catalog.jsonl` and maps terms to the sessions
+//! whose user/assistant messages or exec commands contain them. Sessions
+//! whose `last_event_at` differs from the indexed value are re-read from disk
+//! and re-indexed when the catalog reconciles, and in a debounced background
+//! task after the recorder updates the catalog, so neither a text query nor
+//! the rollout writer waits for indexing.
+//!
+//! `text_index.json` is a snapshot. Later changes are appended to
+//! `text_index.journal.jsonl` and replayed on load; the snapshot is only
+//! rewritten once the journal grows long.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use code_protocol::models::ContentItem;
+use code_protocol::models::LocalShellAction;
+use code_protocol::models::ResponseItem;
+use code_protocol::models::ShellToolCallParams;
+use code_protocol::protocol::RolloutItem;
+use code_protocol::protocol::RolloutLine;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::catalog::SessionCatalog;
+
+const INDEX_SUBDIR: &str = "sessions/index";
+const TEXT_INDEX_FILENAME: &str = "text_index.json";
+const TEXT_INDEX_JOURNAL_FILENAME: &str = "text_index.journal.jsonl";
+const TEXT_INDEX_VERSION: u32 = 2;
+/// Journal entries after which the snapshot is rewritten and the journal
+/// cleared.
+const MAX_JOURNAL_ENTRIES: usize = 256;
+/// How long recorder updates are collected before the index catches up.
+const BACKGROUND_UPDATE_DELAY: Duration = Duration::from_secs(2);
+const MIN_TERM_CHARS: usize = 2;
+const MAX_TERM_CHARS: usize = 64;
+
+/// Term counts extracted from a single session.
+pub type TermCounts = HashMap<String, u32>;
+
+/// The index shared by everything in this process that uses one code home.
+/// `None` until first loaded from disk.
+pub(crate) type SharedTextIndex = Arc<AsyncMutex<Option<SessionTextIndex>>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedSession {
+    /// `last_event_at` of the rollout when it was indexed.
+    last_event_at: String,
+    /// Total number of terms in the session (used for length normalisation).
+    term_count: u32,
+    /// Distinct terms of the session, so removing it only touches their
+    /// postings.
+    #[serde(default)]
+    terms: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posting {
+    session_id: Uuid,
+    tf: u32,
+}
+
+/// On-disk inverted index: term -> sessions containing it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionTextIndex {
+    version: u32,
+    sessions: HashMap<Uuid, IndexedSession>,
+    postings: BTreeMap<String, Vec<Posting>>,
+    #[serde(skip)]
+    index_path: PathBuf,
+    /// Entries in the journal that are not part of the snapshot yet.
+    #[serde(skip)]
+    journal_entries: usize,
+}
+
+/// One change appended to the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    Index {
+        session_id: Uuid,
+        last_event_at: String,
+        terms: TermCounts,
+    },
+    Remove {
+        session_id: Uuid,
+    },
+}
+
+impl SessionTextIndex {
+    /// Load the index from disk, or start empty if it is missing, unreadable
+    /// or written by a different index version. Journal entries are replayed
+    /// on top of the snapshot.
+    pub fn load(code_home: &Path) -> io::Result<Self> {
+        let index_path = code_home.join(INDEX_SUBDIR).join(TEXT_INDEX_FILENAME);
+        let empty = Self {
+            version: TEXT_INDEX_VERSION,
+            index_path: index_path.clone(),
+            ..Self::default()
+        };
+        if !index_path.exists() {
+            return Ok(empty);
+        }
+
+        let bytes = fs::read(&index_path)?;
+        let mut index = match serde_json::from_slice::<Self>(&bytes) {
+            Ok(mut index) if index.version == TEXT_INDEX_VERSION => {
+                index.index_path = index_path;
+                index
+            }
+            Ok(_) => return Ok(empty),
+            Err(e) => {
+                warn!("Failed to parse session text index, rebuilding: {}", e);
+                return Ok(empty);
+            }
+        };
+
+        let journal = match fs::read_to_string(index.journal_path()) {
+            Ok(journal) => journal,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        // A torn last line from an interrupted append is skipped; the
+        // session is simply re-indexed on the next update.
+        for entry in journal
+            .lines()
+            .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+        {
+            index.apply(entry);
+            index.journal_entries += 1;
+        }
+        Ok(index)
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.index_path.with_file_name(TEXT_INDEX_JOURNAL_FILENAME)
+    }
+
+    /// Write the index to disk, replacing the previous file. The contents go
+    /// to a uniquely named temporary file first, so concurrent writers never
+    /// interleave and readers never see a partial index.
+    pub fn save(&self) -> io::Result<()> {
+        let parent = match self.index_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(parent)?;
+        let json = serde_json::to_vec(self).map_err(io::Error::other)?;
+        let mut tmp = tempfile::Builder::new()
+            .prefix(".text_index.")
+            .suffix(".tmp")
+            .tempfile_in(parent)?;
+        io::Write::write_all(&mut tmp, &json)?;
+        tmp.persist(&self.index_path).map_err(|err| err.error)?;
+        match fs::remove_file(self.journal_path()) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Apply `entries` and persist them: appended to the journal, or as a
+    /// new snapshot when there is none yet or the journal has grown long.
+    fn record(&mut self, entries: Vec<JournalEntry>) -> io::Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        for entry in &entries {
+            serde_json::to_writer(&mut lines, entry).map_err(io::Error::other)?;
+            lines.push(b'\n');
+        }
+        let count = entries.len();
+        for entry in entries {
+            self.apply(entry);
+        }
+
+        self.journal_entries += count;
+        if self.journal_entries > MAX_JOURNAL_ENTRIES || !self.index_path.exists() {
+            self.save()?;
+            self.journal_entries = 0;
+            return Ok(());
+        }
+        // One append per batch, so concurrent writers never interleave lines.
+        let mut journal = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.journal_path())?;
+        io::Write::write_all(&mut journal, &lines)
+    }
+
+    fn apply(&mut self, entry: JournalEntry) {
+        match entry {
+            JournalEntry::Index {
+                session_id,
+                last_event_at,
+                terms,
+            } => self.index_session(session_id, &last_event_at, terms),
+            JournalEntry::Remove { session_id } => self.remove_session(&session_id),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Replace the postings for `session_id` with `terms`.
+    pub fn index_session(&mut self, session_id: Uuid, last_event_at: &str, terms: TermCounts) {
+        self.remove_session(&session_id);
+        let term_count = terms.values().sum();
+        let mut indexed_terms = Vec::with_capacity(terms.len());
+        for (term, tf) in terms {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .push(Posting { session_id, tf });
+            indexed_terms.push(term);
+        }
+        self.sessions.insert(
+            session_id,
+            IndexedSession {
+                last_event_at: last_event_at.to_string(),
+                term_count,
+                terms: indexed_terms,
+            },
+        );
+    }
+
+    /// Drop a session from the index.
+    pub fn remove_session(&mut self, session_id: &Uuid) {
+        let Some(indexed) = self.sessions.remove(session_id) else {
+            return;
+        };
+        for term in indexed.terms {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.retain(|p| &p.session_id != session_id);
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Sessions that left `catalog`, and catalog entries that are missing
+    /// from the index or were indexed at a different `last_event_at`.
+    fn outdated(&self, catalog: &SessionCatalog) -> (Vec<Uuid>, Vec<PendingSession>) {
+        let removed = self
+            .sessions
+            .keys()
+            .filter(|id| !catalog.entries.contains_key(id))
+            .copied()
+            .collect();
+        let changed = catalog
+            .entries
+            .values()
+            .filter(|entry| {
+                self.sessions
+                    .get(&entry.session_id)
+                    .is_none_or(|indexed| indexed.last_event_at != entry.last_event_at)
+            })
+            .map(|entry| PendingSession {
+                session_id: entry.session_id,
+                last_event_at: entry.last_event_at.clone(),
+                rollout_path: entry.rollout_path.clone(),
+            })
+            .collect();
+        (removed, changed)
+    }
+
+    /// Score sessions against `query`. Every query term must match; the last
+    /// term also matches as a prefix so results update while typing. Results
+    /// are ordered by descending relevance.
+    pub fn search(&self, query: &str) -> Vec<(Uuid, f64)> {
+        let terms: Vec<String> = tokenize(query).collect();
+        if terms.is_empty() || self.sessions.is_empty() {
+            return Vec::new();
+        }
+
+        let total = self.sessions.len() as f64;
+        let mut scores: HashMap<Uuid, f64> = HashMap::new();
+        let mut matched_terms: HashMap<Uuid, usize> = HashMap::new();
+
+        for (i, term) in terms.iter().enumerate() {
+            let is_last = i + 1 == terms.len();
+            let mut per_session: HashMap<Uuid, f64> = HashMap::new();
+            let matching = self
+                .postings
+                .range(term.clone()..)
+                .take_while(|(candidate, _)| {
+                    if is_last {
+                        candidate.starts_with(term.as_str())
+                    } else {
+                        *candidate == term
+                    }
+                });
+            for (_, postings) in matching {
+                let idf = (1.0 + total / postings.len() as f64).ln();
+                for posting in postings {
+                    let length = self
+                        .sessions
+                        .get(&posting.session_id)
+                        .map_or(1.0, |s| f64::from(s.term_count.max(1)));
+                    let tf = f64::from(posting.tf) / length.sqrt();
+                    *per_session.entry(posting.session_id).or_default() += tf * idf;
+                }
+            }
+            for (session_id, score) in per_session {
+                *scores.entry(session_id).or_default() += score;
+                *matched_terms.entry(session_id).or_default() += 1;
+            }
+        }
+
+        let mut results: Vec<(Uuid, f64)> = scores
+            .into_iter()
+            .filter(|(id, _)| matched_terms.get(id) == Some(&terms.len()))
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        results
+    }
+}
+
+/// A catalog entry that has to be (re-)indexed.
+struct PendingSession {
+    session_id: Uuid,
+    last_event_at: String,
+    rollout_path: PathBuf,
+}
+
+/// The shared index for `code_home`.
+pub(crate) fn shared_index(code_home: &Path) -> SharedTextIndex {
+    static CACHE: OnceCell<Mutex<HashMap<PathBuf, SharedTextIndex>>> = OnceCell::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut guard = cache
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    guard
+        .entry(code_home.to_path_buf())
+        .or_insert_with(|| Arc::new(AsyncMutex::new(None)))
+        .clone()
+}
+
+/// Loads the index into `slot` unless it is already there.
+pub(crate) async fn ensure_loaded<'a>(
+    slot: &'a mut Option<SessionTextIndex>,
+    code_home: &Path,
+) -> io::Result<&'a mut SessionTextIndex> {
+    if slot.is_none() {
+        let code_home = code_home.to_path_buf();
+        let loaded = tokio::task::spawn_blocking(move || SessionTextIndex::load(&code_home))
+            .await
+            .map_err(io::Error::other)??;
+        *slot = Some(loaded);
+    }
+    slot.as_mut()
+        .ok_or_else(|| io::Error::other("session text index not loaded"))
+}
+
+/// Bring the index for `code_home` up to date with the catalog on disk a
+/// little later, in the background. Calls made while an update is pending
+/// are folded into it, so a recorder flush never waits for indexing.
+pub(crate) fn schedule_update(code_home: &Path) {
+    static PENDING: OnceCell<Mutex<HashSet<PathBuf>>> = OnceCell::new();
+    let pending = PENDING.get_or_init(|| Mutex::new(HashSet::new()));
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        // The next catalog reconcile indexes the session instead.
+        return;
+    };
+    let code_home = code_home.to_path_buf();
+    {
+        let mut pending = pending
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if !pending.insert(code_home.clone()) {
+            return;
+        }
+    }
+    runtime.spawn(async move {
+        tokio::time::sleep(BACKGROUND_UPDATE_DELAY).await;
+        // Cleared first, so updates recorded from here on schedule a new run.
+        pending
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&code_home);
+        let catalog_home = code_home.clone();
+        let catalog =
+            match tokio::task::spawn_blocking(move || SessionCatalog::load(&catalog_home)).await {
+                Ok(Ok(catalog)) => catalog,
+                Ok(Err(err)) => {
+                    warn!("failed to load session catalog for text indexing: {err}");
+                    return;
+                }
+                Err(err) => {
+                    warn!("session text indexing task failed: {err}");
+                    return;
+                }
+            };
+        if let Err(err) = update_from_catalog(&code_home, &catalog).await {
+            warn!("failed to update session text index: {err}");
+        }
+    });
+}
+
+/// Bring the shared index for `code_home` in line with `catalog` and
+/// persist the changes. Only sessions that are new or changed since they
+/// were indexed are read, and the rollouts are read without holding the
+/// index lock so text queries are not blocked meanwhile.
+pub(crate) async fn update_from_catalog(
+    code_home: &Path,
+    catalog: &SessionCatalog,
+) -> io::Result<()> {
+    let shared = shared_index(code_home);
+    let (removed, changed) = {
+        let mut guard = shared.lock().await;
+        ensure_loaded(&mut guard, code_home)
+            .await?
+            .outdated(catalog)
+    };
+    if removed.is_empty() && changed.is_empty() {
+        return Ok(());
+    }
+
+    let mut indexed = Vec::with_capacity(changed.len());
+    for pending in changed {
+        let terms = collect_rollout_terms(&code_home.join(&pending.rollout_path))
+            .await
+            .unwrap_or_default();
+        indexed.push((pending, terms));
+    }
+
+    let mut guard = shared.lock().await;
+    let index = ensure_loaded(&mut guard, code_home).await?;
+    let mut entries: Vec<JournalEntry> = removed
+        .into_iter()
+        .filter(|session_id| index.sessions.contains_key(session_id))
+        .map(|session_id| JournalEntry::Remove { session_id })
+        .collect();
+    for (pending, terms) in indexed {
+        // Another update may have indexed this state or a newer one meanwhile.
+        let current = index
+            .sessions
+            .get(&pending.session_id)
+            .is_some_and(|indexed| indexed.last_event_at >= pending.last_event_at);
+        if !current {
+            entries.push(JournalEntry::Index {
+                session_id: pending.session_id,
+                last_event_at: pending.last_event_at,
+                terms,
+            });
+        }
+    }
+    index.record(entries)
+}
+
+/// Split text into lowercase alphanumeric terms.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| {
+            let len = word.chars().count();
+            (MIN_TERM_CHARS..=MAX_TERM_CHARS).contains(&len)
+        })
+        .map(str::to_lowercase)
+}
+
+fn add_terms(counts: &mut TermCounts, text: &str) {
+    for term in tokenize(text) {
+        *counts.entry(term).or_default() += 1;
+    }
+}
+
+/// Whether a message is injected context rather than something a person or
+/// the assistant actually wrote.
+fn is_injected_message(text: &str) -> bool {
+    let trimmed = text.trim_start();
+    trimmed.starts_with("== System Status ==")
+        || trimmed.starts_with("<environment_context>")
+        || trimmed.starts_with("<user_instructions>")
+        || trimmed.starts_with("<user_action>")
+}
+
+/// Extract searchable terms from user/assistant messages and exec commands.
+pub async fn collect_rollout_terms(path: &Path) -> Option<TermCounts> {
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::BufReader;
+
+    let file = match tokio::fs::File::open(path).await {
+        Ok(f) => f,
+        Err(e) => {
+            warn!("Failed to open rollout file {:?}: {}", path, e);
+            return None;
+        }
+    };
+    let mut reader = BufReader::new(file).lines();
+    let mut counts = TermCounts::new();
+
+    while let Some(line) = reader.next_line().await.ok().flatten() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(&line) else {
+            continue;
+        };
+        let RolloutItem::ResponseItem(item) = rollout_line.item else {
+            continue;
+        };
+        match item {
+            ResponseItem::Message { role, content, .. }
+                if role.eq_ignore_ascii_case("user") || role.eq_ignore_ascii_case("assistant") =>
+            {
+                for item in content {
+                    if let ContentItem::InputText { text } | ContentItem::OutputText { text } = item
+                        && !is_injected_message(&text)
+                    {
+                        add_terms(&mut counts, &text);
+                    }
+                }
+            }
+            ResponseItem::LocalShellCall {
+                action: LocalShellAction::Exec(exec),
+                ..
+            } => add_terms(&mut counts, &exec.command.join(" ")),
+            ResponseItem::FunctionCall {
+                name, arguments, ..
+            } if name == "shell" || name == "container.exec" => {
+                if let Ok(params) = serde_json::from_str::<ShellToolCallParams>(&arguments) {
+                    add_terms(&mut counts, &params.command.join(" "));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn terms(text: &str) -> TermCounts {
+        let mut counts = TermCounts::new();
+        add_terms(&mut counts, text);
+        counts
+    }
+
+    #[test]
+    fn tokenize_lowercases_and_drops_short_words() {
+        let tokens: Vec<String> = tokenize("Fix the Auth-retry bug in a/b.rs").collect();
+        assert_eq!(
+            tokens,
+            vec!["fix", "the", "auth", "retry", "bug", "in", "rs"]
+        );
+    }
+
+    #[test]
+    fn search_requires_all_terms_and_prefix_matches_last() {
+        let auth = Uuid::from_u128(1);
+        let retry_only = Uuid::from_u128(2);
+        let mut index = SessionTextIndex::default();
+        index.index_session(auth, "t1", terms("we fixed the auth retry bug"));
+        index.index_session(retry_only, "t1", terms("retry the flaky upload"));
+
+        let hits: Vec<Uuid> = index
+            .search("auth ret")
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(hits, vec![auth]);
+
+        let hits: Vec<Uuid> = index
+            .search("retry")
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[test]
+    fn reindexing_a_session_replaces_its_postings() {
+        let id = Uuid::from_u128(7);
+        let mut index = SessionTextIndex::default();
+        index.index_session(id, "t1", terms("cargo build"));
+        index.index_session(id, "t2", terms("cargo test"));
+
+        assert!(index.search("build").is_empty());
+        assert_eq!(index.search("test").len(), 1);
+
+        index.remove_session(&id);
+        assert!(index.is_empty());
+        assert!(index.postings.is_empty());
+    }
+
+    #[test]
+    fn save_replaces_the_index_without_leaving_temp_files() {
+        let code_home = tempfile::TempDir::new().expect("tempdir");
+        let mut index = SessionTextIndex::load(code_home.path()).expect("load");
+        index.index_session(Uuid::from_u128(1), "t1", terms("cargo build"));
+        index.save().expect("first save");
+        index.index_session(Uuid::from_u128(2), "t1", terms("cargo test"));
+        index.save().expect("second save");
+
+        let dir = code_home.path().join(INDEX_SUBDIR);
+        let names: Vec<String> = fs::read_dir(&dir)
+            .expect("read index dir")
+            .map(|entry| {
+                entry
+                    .expect("entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(names, vec![TEXT_INDEX_FILENAME.to_string()]);
+
+        let reloaded = SessionTextIndex::load(code_home.path()).expect("reload");
+        assert_eq!(reloaded.search("test").len(), 1);
+    }
+
+    #[test]
+    fn recorded_changes_are_appended_to_the_journal_and_replayed() {
+        let code_home = tempfile::TempDir::new().expect("tempdir");
+        let first = Uuid::from_u128(1);
+        let second = Uuid::from_u128(2);
+        let mut index = SessionTextIndex::load(code_home.path()).expect("load");
+        index
+            .record(vec![JournalEntry::Index {
+                session_id: first,
+                last_event_at: "t1".to_string(),
+                terms: terms("cargo build"),
+            }])
+            .expect("first record");
+        let snapshot = index.index_path.clone();
+        let snapshot_bytes = fs::read(&snapshot).expect("snapshot written");
+        assert!(!index.journal_path().exists());
+
+        index
+            .record(vec![JournalEntry::Index {
+                session_id: second,
+                last_event_at: "t1".to_string(),
+                terms: terms("cargo test"),
+            }])
+            .expect("second record");
+        assert_eq!(fs::read(&snapshot).expect("snapshot"), snapshot_bytes);
+        assert!(index.journal_path().exists());
+
+        let ids = |index: &SessionTextIndex, query: &str| -> Vec<Uuid> {
+            index.search(query).into_iter().map(|(id, _)| id).collect()
+        };
+        let mut reloaded = SessionTextIndex::load(code_home.path()).expect("reload");
+        assert_eq!(ids(&reloaded, "build"), vec![first]);
+        assert_eq!(ids(&reloaded, "test"), vec![second]);
+
+        reloaded
+            .record(vec![JournalEntry::Remove { session_id: first }])
+            .expect("remove");
+        let reloaded = SessionTextIndex::load(code_home.path()).expect("reload after remove");
+        assert!(ids(&reloaded, "build").is_empty());
+        assert_eq!(ids(&reloaded, "cargo"), vec![second]);
+    }
+}
@@ -8,6 +8,8 @@ use code_apply_patch::ApplyPatchFileChange;
 
 use crate::codex::ApprovedCommandPattern;
 use crate::exec::SandboxType;
+use crate::exec_policy::ExecPolicy;
+use crate::exec_policy::ExecPolicyDecision;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
@@ -37,7 +39,7 @@ pub fn assess_patch_safety(
     }
 
     // In Read Only mode, we need explicit user approval before writing.
-    if matches!(sandbox_policy, SandboxPolicy::ReadOnly) {
+    if matches!(sandbox_policy, SandboxPolicy::ReadOnly { .. }) {
         return match policy {
             AskForApproval::Never => SafetyCheck::Reject {
                 reason: "write operations require approval but approval policy is set to never"
@@ -56,6 +58,18 @@ pub fn assess_patch_safety(
         }
     }
 
+    // A new exec bit is easy to miss in a diff, so it always goes past the
+    // user even when every path is writable.
+    if patch_adds_exec_bit(action) {
+        return match policy {
+            AskForApproval::Never => SafetyCheck::Reject {
+                reason: "patch makes a file executable but approval policy is set to never"
+                    .to_string(),
+            },
+            _ => SafetyCheck::AskUser,
+        };
+    }
+
     // Even though the patch *appears* to be constrained to writable paths, it
     // is possible that paths in the patch are hard links to files outside the
     // writable roots, so we should still run `apply_patch` in a sandbox in that
@@ -91,6 +105,35 @@ pub fn assess_patch_safety(
     }
 }
 
+/// Whether any `*** Set Mode:` in the patch grants an execute bit the file
+/// does not already have.
+fn patch_adds_exec_bit(action: &ApplyPatchAction) -> bool {
+    action.changes().iter().any(|(path, change)| {
+        let target = match change {
+            ApplyPatchFileChange::Add { .. } => path,
+            ApplyPatchFileChange::Delete { .. } => return false,
+            ApplyPatchFileChange::Update { move_path, .. } => move_path.as_ref().unwrap_or(path),
+        };
+        let Some(mode) = action.modes().get(target) else {
+            return false;
+        };
+        mode & 0o111 & !current_mode(path).unwrap_or(0) != 0
+    })
+}
+
+#[cfg(unix)]
+fn current_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| metadata.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn current_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
 /// For a command to be run _without_ a sandbox, one of the following must be
 /// true:
 ///
@@ -102,29 +145,71 @@ pub fn assess_command_safety(
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<ApprovedCommandPattern>,
+    exec_policy: &ExecPolicy,
+    cwd: &Path,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
+    // User and project exec policies get the first word: a forbidden match
+    // rejects the command even if it was approved for the session.
+    let writable_roots: Vec<PathBuf> = sandbox_policy
+        .get_writable_roots_with_cwd(cwd)
+        .into_iter()
+        .map(|root| root.root)
+        .collect();
+    let policy_decision = exec_policy.evaluate(command, cwd, &writable_roots);
+    if let ExecPolicyDecision::Forbidden { reason } = policy_decision {
+        return SafetyCheck::Reject {
+            reason: format!("forbidden by exec policy: {reason}"),
+        };
+    }
+
     // A command is "trusted" because either:
     // - it belongs to a set of commands we consider "safe" by default, or
     // - the user has explicitly approved the command for this session
     //
-    // Currently, whether a command is "trusted" is a simple boolean, but we
-    // should include more metadata on this command test to indicate whether it
-    // should be run inside a sandbox or not. (This could be something the user
-    // defines as part of `execpolicy`.)
-    //
     // For example, when `is_known_safe_command(command)` returns `true`, it
     // would probably be fine to run the command in a sandbox, but when
     // `approved.contains(command)` is `true`, the user may have approved it for
     // the session _because_ they know it needs to run outside a sandbox.
-    if is_known_safe_command(command) || approved.iter().any(|pattern| pattern.matches(command)) {
-        let user_explicitly_approved = approved.iter().any(|pattern| pattern.matches(command));
+    let user_explicitly_approved = approved.iter().any(|pattern| pattern.matches(command));
+    if is_known_safe_command(command) || user_explicitly_approved {
+        // Known-safe commands such as `cat` still read files, so they stay
+        // sandboxed while the policy denies reads.
+        let sandbox_type = if user_explicitly_approved || sandbox_policy.has_full_disk_read_access()
+        {
+            SandboxType::None
+        } else {
+            get_platform_sandbox().unwrap_or(SandboxType::None)
+        };
         return SafetyCheck::AutoApprove {
-            sandbox_type: SandboxType::None,
+            sandbox_type,
             user_explicitly_approved,
         };
     }
 
+    // A policy-matched command that writes nothing, or only writes inside the
+    // workspace, can run without asking. Policies can describe programs with
+    // arbitrary arguments (`ARG_UNVERIFIED_VARARGS`), so the sandbox is what
+    // holds the command to that and it is never skipped.
+    let policy_allows = match policy_decision {
+        ExecPolicyDecision::ReadOnly => true,
+        ExecPolicyDecision::Writes {
+            within_writable_roots,
+        } => {
+            within_writable_roots && matches!(sandbox_policy, SandboxPolicy::WorkspaceWrite { .. })
+        }
+        ExecPolicyDecision::Unmatched | ExecPolicyDecision::Forbidden { .. } => false,
+    };
+    if policy_allows
+        && !with_escalated_permissions
+        && let Some(sandbox_type) = get_platform_sandbox()
+    {
+        return SafetyCheck::AutoApprove {
+            sandbox_type,
+            user_explicitly_approved: false,
+        };
+    }
+
     assess_safety_for_untrusted_command(approval_policy, sandbox_policy, with_escalated_permissions)
 }
 
@@ -149,7 +234,7 @@ pub(crate) fn assess_safety_for_untrusted_command(
             sandbox_type: SandboxType::None,
             user_explicitly_approved: false,
         },
-        (OnRequest, ReadOnly) | (OnRequest, WorkspaceWrite { .. }) => {
+        (OnRequest, ReadOnly { .. }) | (OnRequest, WorkspaceWrite { .. }) => {
             if with_escalated_permissions {
                 SafetyCheck::AskUser
             } else {
@@ -164,9 +249,9 @@ pub(crate) fn assess_safety_for_untrusted_command(
                 }
             }
         }
-        (Never, ReadOnly)
+        (Never, ReadOnly { .. })
         | (Never, WorkspaceWrite { .. })
-        | (OnFailure, ReadOnly)
+        | (OnFailure, ReadOnly { .. })
         | (OnFailure, WorkspaceWrite { .. }) => {
             match get_platform_sandbox() {
                 Some(sandbox_type) => SafetyCheck::AutoApprove {
@@ -211,7 +296,7 @@ fn is_write_patch_constrained_to_writable_paths(
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
     let writable_roots = match sandbox_policy {
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             return false;
         }
         SandboxPolicy::DangerFullAccess => {
@@ -281,6 +366,7 @@ fn is_write_patch_constrained_to_writable_paths(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::ApprovedCommandMatchKind;
     use tempfile::TempDir;
 
     #[test]
@@ -302,6 +388,8 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
@@ -324,6 +412,8 @@ mod tests {
         let policy_with_parent = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![parent],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
@@ -344,7 +434,7 @@ mod tests {
         let result = assess_patch_safety(
             &action,
             AskForApproval::OnRequest,
-            &SandboxPolicy::ReadOnly,
+            &SandboxPolicy::new_read_only_policy(),
             &cwd,
         );
 
@@ -360,7 +450,7 @@ mod tests {
         let result = assess_patch_safety(
             &action,
             AskForApproval::Never,
-            &SandboxPolicy::ReadOnly,
+            &SandboxPolicy::new_read_only_policy(),
             &cwd,
         );
 
@@ -377,12 +467,69 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_patch_adding_exec_bit_requires_approval() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().to_path_buf();
+        let script = cwd.join("run.sh");
+        std::fs::write(&script, "echo hi\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let parse = |patch: String| {
+            let argv = vec!["apply_patch".to_string(), patch];
+            match code_apply_patch::maybe_parse_apply_patch_verified(&argv, &cwd) {
+                code_apply_patch::MaybeApplyPatchVerified::Body(action) => action,
+                other => panic!("expected patch body, got {other:?}"),
+            }
+        };
+        let assess = |action: &ApplyPatchAction, policy| {
+            assess_patch_safety(action, policy, &SandboxPolicy::DangerFullAccess, &cwd)
+        };
+
+        let chmod_x = parse(format!(
+            "*** Begin Patch\n*** Update File: {}\n*** Set Mode: 755\n*** End Patch",
+            script.display()
+        ));
+        assert_eq!(
+            assess(&chmod_x, AskForApproval::OnRequest),
+            SafetyCheck::AskUser
+        );
+        assert_eq!(
+            assess(&chmod_x, AskForApproval::Never),
+            SafetyCheck::Reject {
+                reason: "patch makes a file executable but approval policy is set to never"
+                    .to_string(),
+            }
+        );
+
+        let add_x = parse(format!(
+            "*** Begin Patch\n*** Add File: {}\n*** Set Mode: 700\n+make\n*** End Patch",
+            cwd.join("build.sh").display()
+        ));
+        assert_eq!(
+            assess(&add_x, AskForApproval::OnRequest),
+            SafetyCheck::AskUser
+        );
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let keep_x = parse(format!(
+            "*** Begin Patch\n*** Update File: {}\n*** Set Mode: 750\n*** End Patch",
+            script.display()
+        ));
+        assert!(matches!(
+            assess(&keep_x, AskForApproval::OnRequest),
+            SafetyCheck::AutoApprove { .. }
+        ));
+    }
+
     #[test]
     fn test_request_escalated_privileges() {
         // Should not be a trusted command
         let command = vec!["git commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<ApprovedCommandPattern> = HashSet::new();
         let request_escalated_privileges = true;
 
@@ -391,17 +538,46 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
         assert_eq!(safety_check, SafetyCheck::AskUser);
     }
 
+    #[test]
+    fn test_known_safe_command_stays_sandboxed_with_deny_read() {
+        let command = vec!["cat".to_string(), ".env".to_string()];
+        let sandbox_policy = SandboxPolicy::ReadOnly {
+            deny_read: vec!["**/.env*".to_string()],
+        };
+        let approved: HashSet<ApprovedCommandPattern> = HashSet::new();
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &sandbox_policy,
+            &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
+            false,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: get_platform_sandbox().unwrap_or(SandboxType::None),
+                user_explicitly_approved: false,
+            }
+        );
+    }
+
     #[test]
     fn test_request_escalated_privileges_no_sandbox_fallback() {
         let command = vec!["git".to_string(), "commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<ApprovedCommandPattern> = HashSet::new();
         let request_escalated_privileges = false;
 
@@ -410,6 +586,8 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
@@ -433,6 +611,8 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
@@ -468,6 +648,8 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: true,
@@ -483,4 +665,89 @@ mod tests {
         // Patch is outside writable paths, so it should ask the user.
         assert_eq!(result, SafetyCheck::AskUser);
     }
+
+    fn exec_policy(source: &str) -> ExecPolicy {
+        let mut policy = ExecPolicy::default();
+        policy
+            .add_source(Path::new("test.policy"), source)
+            .expect("policy should parse");
+        policy
+    }
+
+    #[test]
+    fn test_exec_policy_forbidden_rejects_even_approved_commands() {
+        let command = vec!["rm".to_string(), "-rf".to_string(), "build".to_string()];
+        let policy = exec_policy(r#"forbid_program_regex(regex="^rm$", reason="use git clean")"#);
+        let approved: HashSet<ApprovedCommandPattern> = [ApprovedCommandPattern::new(
+            command.clone(),
+            ApprovedCommandMatchKind::Exact,
+            None,
+        )]
+        .into_iter()
+        .collect();
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::Never,
+            &SandboxPolicy::DangerFullAccess,
+            &approved,
+            &policy,
+            Path::new("/"),
+            false,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::Reject {
+                reason: "forbidden by exec policy: use git clean".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_exec_policy_read_only_match_stays_sandboxed() {
+        let command = vec!["cargo".to_string(), "check".to_string()];
+        let policy =
+            exec_policy(r#"define_program(program="cargo", args=[ARG_UNVERIFIED_VARARGS])"#);
+        let approved: HashSet<ApprovedCommandPattern> = HashSet::new();
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::new_read_only_policy(),
+            &approved,
+            &policy,
+            Path::new("/"),
+            false,
+        );
+
+        let expected = match get_platform_sandbox() {
+            Some(sandbox_type) => SafetyCheck::AutoApprove {
+                sandbox_type,
+                user_explicitly_approved: false,
+            },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(safety_check, expected);
+    }
+
+    #[test]
+    fn test_exec_policy_match_does_not_approve_escalation() {
+        let command = vec!["cargo".to_string(), "check".to_string()];
+        let policy =
+            exec_policy(r#"define_program(program="cargo", args=[ARG_UNVERIFIED_VARARGS])"#);
+        let approved: HashSet<ApprovedCommandPattern> = HashSet::new();
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::new_read_only_policy(),
+            &approved,
+            &policy,
+            Path::new("/"),
+            true,
+        );
+
+        assert_eq!(safety_check, SafetyCheck::AskUser);
+    }
 }
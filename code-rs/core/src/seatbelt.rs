@@ -3,6 +3,8 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+use crate::deny_read::DenyReadMatcher;
+use crate::exec_limits::ExecResourceLimits;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::StdioPolicy;
@@ -21,10 +23,20 @@ pub async fn spawn_command_under_seatbelt(
     command_cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    resource_limits: ExecResourceLimits,
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
-    let args = create_seatbelt_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    let network_proxy = crate::network_proxy::proxy_for_policy(sandbox_policy).await?;
+    if let Some(proxy) = &network_proxy {
+        crate::network_proxy::apply_proxy_env(&mut env, proxy);
+    }
+    let args = create_seatbelt_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        network_proxy.map(|proxy| proxy.addr.port()),
+    );
     let arg0 = None;
     env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
     spawn_child_async(
@@ -33,6 +45,7 @@ pub async fn spawn_command_under_seatbelt(
         arg0,
         command_cwd,
         sandbox_policy,
+        resource_limits,
         stdio_policy,
         env,
     )
@@ -43,6 +56,7 @@ fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    network_proxy_port: Option<u16>,
 ) -> Vec<String> {
     let (file_write_policy, extra_cli_args) = {
         if sandbox_policy.has_full_disk_write_access() {
@@ -100,17 +114,25 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
-    } else {
-        ""
-    };
+    // Reads are allowed everywhere except for `deny_read` matches, which are
+    // denied afterwards so they take precedence.
+    let mut file_read_policy = "; allow read-only file operations\n(allow file-read*)".to_string();
+    let deny_read = DenyReadMatcher::new(sandbox_policy.deny_read(), sandbox_policy_cwd);
+    for regex in deny_read.regex_sources() {
+        // `#"..."` literals cannot contain a double quote.
+        if !regex.contains('"') {
+            file_read_policy.push_str(&format!("\n(deny file-read* (regex #\"{regex}\"))"));
+        }
+    }
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
-        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)"
+        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)".to_string()
+    } else if let Some(port) = network_proxy_port {
+        // Only the allowlist proxy is reachable; it filters destinations.
+        format!("(allow network-outbound (remote ip \"localhost:{port}\"))")
     } else {
-        ""
+        String::new()
     };
 
     let full_policy = format!(
@@ -165,6 +187,8 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![root_with_git, root_without_git],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
             allow_git_writes: false,
@@ -174,6 +198,7 @@ mod tests {
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
             &cwd,
+            None,
         );
 
         // Build the expected policy text using a raw string for readability.
@@ -241,6 +266,8 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
             allow_git_writes: false,
@@ -250,6 +277,7 @@ mod tests {
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
             root_with_git.as_path(),
+            None,
         );
 
         let tmpdir_env_var = std::env::var("TMPDIR")
@@ -312,6 +340,59 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn network_allowlist_only_allows_the_proxy() {
+        let tmp = TempDir::new().expect("tempdir");
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            network_allowlist: vec!["crates.io".to_string()],
+            deny_read: Vec::new(),
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            allow_git_writes: true,
+        };
+
+        let args = create_seatbelt_command_args(
+            vec!["/usr/bin/true".to_string()],
+            &policy,
+            tmp.path(),
+            Some(4321),
+        );
+
+        assert!(
+            args[1].ends_with("\n(allow network-outbound (remote ip \"localhost:4321\"))"),
+            "unexpected policy: {}",
+            args[1]
+        );
+    }
+
+    #[test]
+    fn deny_read_entries_become_deny_rules() {
+        let tmp = TempDir::new().expect("tempdir");
+        let cwd = tmp.path().canonicalize().expect("canonical tempdir");
+        let policy = SandboxPolicy::ReadOnly {
+            deny_read: vec!["**/.env*".to_string()],
+        };
+
+        let args = create_seatbelt_command_args(
+            vec!["/bin/cat".to_string(), ".env".to_string()],
+            &policy,
+            &cwd,
+            None,
+        );
+
+        let cwd_regex = cwd.to_string_lossy().replace('.', "\\.");
+        let expected = format!(
+            "(allow file-read*)\n(deny file-read* (regex #\"^{cwd_regex}/(.*/)?\\.env[^/]*(/.*)?$\"))\n"
+        );
+        assert!(
+            args[1].contains(&expected),
+            "unexpected policy: {}",
+            args[1]
+        );
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
@@ -12,9 +12,12 @@ use code_protocol::protocol::SessionSource;
 use once_cell::sync::OnceCell;
 use tokio::sync::Mutex as AsyncMutex;
 use tokio::task;
+use uuid::Uuid;
 
 use crate::rollout::catalog::SessionIndexEntry;
 use crate::rollout::catalog::{self as rollout_catalog};
+use crate::rollout::text_index;
+use crate::rollout::text_index::SharedTextIndex;
 
 /// Query parameters for catalog lookups.
 #[derive(Debug, Clone, Default)]
@@ -33,19 +36,28 @@ pub struct SessionQuery {
     pub include_deleted: bool,
     /// Maximum number of rows to return.
     pub limit: Option<usize>,
+    /// Full-text filter over user/assistant messages and exec commands. All
+    /// words must match; results are ordered by relevance instead of recency.
+    pub text: Option<String>,
 }
 
 /// Public catalog facade used by TUI/CLI/Exec entrypoints.
 pub struct SessionCatalog {
     code_home: PathBuf,
     cache: Arc<AsyncMutex<Option<rollout_catalog::SessionCatalog>>>,
+    text_index: SharedTextIndex,
 }
 
 impl SessionCatalog {
     /// Create a catalog facade for the provided code home directory.
     pub fn new(code_home: PathBuf) -> Self {
         let cache = catalog_cache_handle(&code_home);
-        Self { code_home, cache }
+        let text_index = text_index::shared_index(&code_home);
+        Self {
+            code_home,
+            cache,
+            text_index,
+        }
     }
 
     /// Query the catalog with the provided filters, returning ordered entries.
@@ -53,7 +65,7 @@ impl SessionCatalog {
         let catalog = self.load_inner().await?;
         let mut rows = Vec::new();
 
-        let candidates: Vec<&SessionIndexEntry> = if let Some(cwd) = &query.cwd {
+        let mut candidates: Vec<&SessionIndexEntry> = if let Some(cwd) = &query.cwd {
             catalog.by_cwd(cwd)
         } else if let Some(git_root) = &query.git_root {
             catalog.by_git_root(git_root)
@@ -61,6 +73,20 @@ impl SessionCatalog {
             catalog.all_ordered()
         };
 
+        if let Some(text) = query.text.as_deref().map(str::trim)
+            && !text.is_empty()
+        {
+            let scores = self.search_text(text).await?;
+            candidates.retain(|entry| scores.contains_key(&entry.session_id));
+            // Stable sort keeps recency order between equally relevant sessions.
+            candidates.sort_by(|a, b| {
+                let score = |entry: &SessionIndexEntry| {
+                    scores.get(&entry.session_id).copied().unwrap_or_default()
+                };
+                score(b).total_cmp(&score(a))
+            });
+        }
+
         for entry in candidates {
             if !query.include_archived && entry.archived {
                 continue;
@@ -130,6 +156,16 @@ impl SessionCatalog {
         entry_to_rollout_path(&self.code_home, entry)
     }
 
+    /// Relevance scores for sessions matching `text`. The catalog keeps the
+    /// text index current while reconciling, so this only looks terms up.
+    async fn search_text(&self, text: &str) -> Result<HashMap<Uuid, f64>> {
+        let mut guard = self.text_index.lock().await;
+        let index = text_index::ensure_loaded(&mut guard, &self.code_home)
+            .await
+            .context("failed to load session text index")?;
+        Ok(index.search(text).into_iter().collect())
+    }
+
     async fn load_inner(&self) -> Result<rollout_catalog::SessionCatalog> {
         {
             let mut guard = self.cache.lock().await;
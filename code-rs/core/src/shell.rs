@@ -316,6 +316,7 @@ mod tests {
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
+            use crate::exec_limits::ExecResourceLimits;
             use crate::protocol::SandboxPolicy;
 
             let temp_home = tempfile::tempdir().unwrap();
@@ -355,6 +356,7 @@ mod tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    resource_limits: ExecResourceLimits::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
@@ -423,6 +425,7 @@ mod macos_tests {
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
+            use crate::exec_limits::ExecResourceLimits;
             use crate::protocol::SandboxPolicy;
 
             // create a temp directory with a zshrc file in it
@@ -463,6 +466,7 @@ mod macos_tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    resource_limits: ExecResourceLimits::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
@@ -515,6 +515,7 @@ mod tests {
             file.clone(),
             FileChange::Add {
                 content: "foo\n".to_string(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&add_changes);
@@ -547,6 +548,7 @@ index {ZERO_OID}..{right_oid}
                 move_path: None,
                 original_content: String::new(),
                 new_content: String::new(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -619,6 +621,7 @@ index {left_oid}..{ZERO_OID}
                 move_path: Some(dest.clone()),
                 original_content: String::new(),
                 new_content: String::new(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&mv_changes);
@@ -661,6 +664,7 @@ index {left_oid}..{right_oid}
                 move_path: Some(dest.clone()),
                 original_content: String::new(),
                 new_content: String::new(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&mv_changes);
@@ -685,6 +689,7 @@ index {left_oid}..{right_oid}
                 move_path: Some(dest.clone()),
                 original_content: String::new(),
                 new_content: String::new(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&mv);
@@ -727,6 +732,7 @@ index {ZERO_OID}..{right_oid}
                 move_path: None,
                 original_content: String::new(),
                 new_content: String::new(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_a);
@@ -804,6 +810,7 @@ index {left_oid_b}..{ZERO_OID}
                 move_path: None,
                 original_content: String::new(),
                 new_content: String::new(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -840,6 +847,7 @@ Binary files differ
             file.clone(),
             FileChange::Add {
                 content: "foo\n".to_string(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&add_changes);
@@ -872,6 +880,7 @@ index {ZERO_OID}..{right_oid}
                 move_path: None,
                 original_content: String::new(),
                 new_content: String::new(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -1,21 +1,79 @@
+//! End-user notifications.
+//!
+//! The legacy `notify` command receives `agent-turn-complete` notifications as
+//! a JSON argument. `[[notification_sinks]]` entries add webhook and
+//! Unix-socket destinations, each filtered by [`NotificationEvent`]. Delivery
+//! is fire-and-forget: failures are logged and never interrupt the session.
+
+use std::path::Path;
+use std::time::Duration;
+
 use serde::Serialize;
+use serde_json::json;
 use tracing::error;
 use tracing::warn;
 
-#[derive(Debug, Default)]
-#[allow(dead_code)]
-pub(crate) struct UserNotifier {
+use crate::config_types::NotificationEvent;
+use crate::config_types::NotificationSinkConfig;
+use crate::config_types::WebhookFormat;
+
+/// Time allowed to connect to a webhook sink.
+const WEBHOOK_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time allowed for a whole webhook request, so a stalled endpoint does not
+/// keep delivery tasks around for the rest of the session.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone)]
+pub struct UserNotifier {
     notify_command: Option<Vec<String>>,
+    sinks: Vec<NotificationSinkConfig>,
+    http: reqwest::Client,
 }
 
-#[allow(dead_code)]
 impl UserNotifier {
-    pub(crate) fn notify(&self, notification: &UserNotification) {
+    pub fn new(notify: Option<Vec<String>>, sinks: Vec<NotificationSinkConfig>) -> Self {
+        Self {
+            notify_command: notify,
+            sinks,
+            http: reqwest::Client::builder()
+                .connect_timeout(WEBHOOK_CONNECT_TIMEOUT)
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        }
+    }
+
+    pub fn notify(&self, notification: &UserNotification) {
         if let Some(notify_command) = &self.notify_command
             && !notify_command.is_empty()
+            && notification.event() == NotificationEvent::AgentTurnComplete
         {
             self.invoke_notify(notify_command, notification)
         }
+
+        let event = notification.event();
+        let sinks: Vec<NotificationSinkConfig> = self
+            .sinks
+            .iter()
+            .filter(|sink| sink.accepts(event))
+            .cloned()
+            .collect();
+        if sinks.is_empty() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("no async runtime available to deliver {event} notification");
+            return;
+        };
+        for sink in sinks {
+            let http = self.http.clone();
+            let notification = notification.clone();
+            runtime.spawn(async move {
+                if let Err(err) = deliver(&http, &sink, &notification).await {
+                    warn!("failed to deliver {event} notification: {err}");
+                }
+            });
+        }
     }
 
     fn invoke_notify(&self, notify_command: &[String], notification: &UserNotification) {
@@ -35,11 +93,11 @@ impl UserNotifier {
             warn!("failed to spawn notifier '{}': {e}", notify_command[0]);
         }
     }
+}
 
-    pub(crate) fn new(notify: Option<Vec<String>>) -> Self {
-        Self {
-            notify_command: notify,
-        }
+impl Default for UserNotifier {
+    fn default() -> Self {
+        Self::new(None, Vec::new())
     }
 }
 
@@ -48,7 +106,7 @@ impl UserNotifier {
 /// program.
 #[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
-pub(crate) enum UserNotification {
+pub enum UserNotification {
     #[serde(rename_all = "kebab-case")]
     AgentTurnComplete {
         turn_id: String,
@@ -59,12 +117,159 @@ pub(crate) enum UserNotification {
         /// The last message sent by the assistant in the turn.
         last_assistant_message: Option<String>,
     },
+
+    /// The agent is waiting for the user to approve a command or patch.
+    #[serde(rename_all = "kebab-case")]
+    ApprovalRequested {
+        turn_id: String,
+        call_id: String,
+        /// What needs approval, e.g. the command line.
+        summary: String,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    AutoDriveFinished {
+        goal: Option<String>,
+        turns_completed: usize,
+        message: Option<String>,
+    },
+
+    /// An Auto Drive spending threshold was crossed.
+    #[serde(rename_all = "kebab-case")]
+    BudgetAlert { message: String, exceeded: bool },
+}
+
+impl UserNotification {
+    pub fn event(&self) -> NotificationEvent {
+        match self {
+            Self::AgentTurnComplete { .. } => NotificationEvent::AgentTurnComplete,
+            Self::ApprovalRequested { .. } => NotificationEvent::ApprovalRequested,
+            Self::AutoDriveFinished { .. } => NotificationEvent::AutoDriveFinished,
+            Self::BudgetAlert { .. } => NotificationEvent::BudgetAlert,
+        }
+    }
+
+    /// Short human-readable headline.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::AgentTurnComplete { .. } => "Turn complete",
+            Self::ApprovalRequested { .. } => "Approval requested",
+            Self::AutoDriveFinished { .. } => "Auto Drive finished",
+            Self::BudgetAlert { exceeded: true, .. } => "Budget exceeded",
+            Self::BudgetAlert {
+                exceeded: false, ..
+            } => "Budget warning",
+        }
+    }
+
+    /// Human-readable details, if any.
+    pub fn body(&self) -> Option<String> {
+        match self {
+            Self::AgentTurnComplete {
+                last_assistant_message,
+                ..
+            } => last_assistant_message.clone(),
+            Self::ApprovalRequested { summary, .. } => Some(summary.clone()),
+            Self::AutoDriveFinished {
+                goal,
+                turns_completed,
+                message,
+            } => message.clone().or_else(|| {
+                goal.as_ref()
+                    .map(|goal| format!("{goal} ({turns_completed} turns)"))
+            }),
+            Self::BudgetAlert { message, .. } => Some(message.clone()),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self.body() {
+            Some(body) if !body.is_empty() => format!("{}: {body}", self.title()),
+            _ => self.title().to_string(),
+        }
+    }
+}
+
+async fn deliver(
+    http: &reqwest::Client,
+    sink: &NotificationSinkConfig,
+    notification: &UserNotification,
+) -> std::io::Result<()> {
+    match sink {
+        NotificationSinkConfig::Webhook { url, format, .. } => {
+            let payload = webhook_payload(*format, notification)?;
+            let response = http
+                .post(url)
+                .timeout(WEBHOOK_TIMEOUT)
+                .json(&payload)
+                .send()
+                .await
+                .map_err(std::io::Error::other)?;
+            if !response.status().is_success() {
+                return Err(std::io::Error::other(format!(
+                    "webhook {url} returned {}",
+                    response.status()
+                )));
+            }
+            Ok(())
+        }
+        NotificationSinkConfig::UnixSocket { path, .. } => {
+            write_to_socket(path, notification).await
+        }
+    }
+}
+
+fn webhook_payload(
+    format: WebhookFormat,
+    notification: &UserNotification,
+) -> std::io::Result<serde_json::Value> {
+    Ok(match format {
+        WebhookFormat::Json => serde_json::to_value(notification)?,
+        WebhookFormat::Slack => json!({ "text": notification.text() }),
+        WebhookFormat::Teams => json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "summary": notification.title(),
+            "title": notification.title(),
+            "text": notification.body().unwrap_or_default(),
+        }),
+    })
+}
+
+#[cfg(unix)]
+async fn write_to_socket(path: &Path, notification: &UserNotification) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut line = serde_json::to_vec(notification)?;
+    line.push(b'\n');
+    let mut stream = tokio::net::UnixStream::connect(path).await?;
+    stream.write_all(&line).await?;
+    stream.shutdown().await
+}
+
+#[cfg(not(unix))]
+async fn write_to_socket(path: &Path, _notification: &UserNotification) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!(
+            "unix socket notification sink {} is not supported on this platform",
+            path.display()
+        ),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn budget_alert() -> UserNotification {
+        UserNotification::BudgetAlert {
+            message: "Spent $4.10 of $5.00".to_string(),
+            exceeded: false,
+        }
+    }
 
     #[test]
     fn test_user_notification() -> Result<()> {
@@ -82,4 +287,106 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn sinks_filter_by_event() -> Result<()> {
+        #[derive(serde::Deserialize)]
+        struct Sinks {
+            notification_sinks: Vec<NotificationSinkConfig>,
+        }
+        let Sinks { notification_sinks } = toml::from_str(
+            r#"
+            [[notification_sinks]]
+            type = "webhook"
+            url = "https://hooks.example.com/T000"
+            format = "slack"
+            events = ["approval-requested", "budget-alert"]
+
+            [[notification_sinks]]
+            type = "unix-socket"
+            path = "/tmp/code-notify.sock"
+            "#,
+        )?;
+
+        assert!(notification_sinks[0].accepts(NotificationEvent::BudgetAlert));
+        assert!(!notification_sinks[0].accepts(NotificationEvent::AgentTurnComplete));
+        assert!(notification_sinks[1].accepts(NotificationEvent::AutoDriveFinished));
+        Ok(())
+    }
+
+    #[test]
+    fn webhook_payloads_match_format() -> Result<()> {
+        let notification = budget_alert();
+        assert_eq!(
+            webhook_payload(WebhookFormat::Slack, &notification)?,
+            json!({ "text": "Budget warning: Spent $4.10 of $5.00" })
+        );
+        assert_eq!(
+            webhook_payload(WebhookFormat::Teams, &notification)?,
+            json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "summary": "Budget warning",
+                "title": "Budget warning",
+                "text": "Spent $4.10 of $5.00",
+            })
+        );
+        assert_eq!(
+            webhook_payload(WebhookFormat::Json, &notification)?,
+            json!({ "type": "budget-alert", "message": "Spent $4.10 of $5.00", "exceeded": false })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn webhook_sink_posts_payload() -> Result<()> {
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+        use wiremock::matchers::body_json;
+        use wiremock::matchers::method;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_json(
+                json!({ "text": "Budget warning: Spent $4.10 of $5.00" }),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sink = NotificationSinkConfig::Webhook {
+            url: server.uri(),
+            format: WebhookFormat::Slack,
+            events: Vec::new(),
+        };
+        deliver(&reqwest::Client::new(), &sink, &budget_alert()).await?;
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_sink_writes_json_line() -> Result<()> {
+        use tokio::io::AsyncReadExt;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("notify.sock");
+        let listener = tokio::net::UnixListener::bind(&path)?;
+
+        let sink = NotificationSinkConfig::UnixSocket {
+            path,
+            events: vec![NotificationEvent::BudgetAlert],
+        };
+        deliver(&reqwest::Client::new(), &sink, &budget_alert()).await?;
+
+        let (mut stream, _) = listener.accept().await?;
+        let mut received = String::new();
+        stream.read_to_string(&mut received).await?;
+        assert_eq!(
+            received,
+            "{\"type\":\"budget-alert\",\"message\":\"Spent $4.10 of $5.00\",\"exceeded\":false}\n"
+        );
+        Ok(())
+    }
 }
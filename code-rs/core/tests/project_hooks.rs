@@ -0,0 +1,277 @@
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+//! Drives blocking `tool.before` hooks end to end: the model issues a shell
+//! call, a real hook process prints a verdict, and the tool output sent back
+//! to the model reflects it.
+
+mod common;
+
+use std::path::Path;
+
+use common::load_default_config_for_test;
+use common::skip_if_no_network;
+use common::wait_for_event;
+
+use code_core::BeaconConversation;
+use code_core::CodeAuth;
+use code_core::ConversationManager;
+use code_core::ModelProviderInfo;
+use code_core::built_in_model_providers;
+use code_core::config_types::ProjectHookConfig;
+use code_core::config_types::ProjectHookEvent;
+use code_core::model_family::find_family_for_model;
+use code_core::project_features::ProjectHooks;
+use code_core::protocol::AskForApproval;
+use code_core::protocol::EventMsg;
+use code_core::protocol::InputItem;
+use code_core::protocol::Op;
+use code_core::protocol::ReviewDecision;
+use code_core::protocol::SandboxPolicy;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path_regex;
+
+const CALL_ID: &str = "call-hook-1";
+
+fn sse(events: &[Value]) -> String {
+    events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {event}\n\n",
+                event["type"].as_str().unwrap()
+            )
+        })
+        .collect()
+}
+
+fn completed(id: &str) -> Value {
+    json!({
+        "type": "response.completed",
+        "response": {
+            "id": id,
+            "usage": {
+                "input_tokens": 0,
+                "input_tokens_details": null,
+                "output_tokens": 0,
+                "output_tokens_details": null,
+                "total_tokens": 0
+            },
+            "output": []
+        }
+    })
+}
+
+fn shell_call(command: &[&str]) -> Value {
+    json!({
+        "type": "response.output_item.done",
+        "item": {
+            "type": "function_call",
+            "name": "shell",
+            "arguments": json!({ "command": command }).to_string(),
+            "call_id": CALL_ID,
+        }
+    })
+}
+
+async fn mount_once(server: &MockServer, body: String) {
+    Mock::given(method("POST"))
+        .and(path_regex(".*/responses$"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(body),
+        )
+        .up_to_n_times(1)
+        .mount(server)
+        .await;
+}
+
+/// A hook that records each invocation in `hook.log` and prints `verdict`.
+fn hook(cwd: &Path, verdict: Value) -> ProjectHooks {
+    let script = format!("echo \"$CODE_HOOK_EVENT\" >> hook.log; printf '%s\\n' '{verdict}'");
+    ProjectHooks::from_configs(
+        &[ProjectHookConfig {
+            event: ProjectHookEvent::ToolBefore,
+            name: Some("gate".to_string()),
+            command: vec!["bash".to_string(), "-c".to_string(), script],
+            cwd: None,
+            env: None,
+            timeout_ms: Some(10_000),
+            run_in_background: None,
+        }],
+        cwd,
+    )
+}
+
+struct HookRun {
+    server: MockServer,
+    cwd: TempDir,
+    _code_home: TempDir,
+    conversation: std::sync::Arc<BeaconConversation>,
+}
+
+async fn start(verdict: Value, approval_policy: AskForApproval) -> HookRun {
+    let server = MockServer::start().await;
+    mount_once(
+        &server,
+        sse(&[shell_call(&["echo", "original"]), completed("resp-1")]),
+    )
+    .await;
+    mount_once(&server, sse(&[completed("resp-2")])).await;
+
+    let cwd = TempDir::new().unwrap();
+    let code_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&code_home);
+    config.cwd = cwd.path().to_path_buf();
+    config.model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    config.model = "o3".to_string();
+    config.model_family = find_family_for_model("o3").unwrap();
+    config.approval_policy = approval_policy;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+    config.project_hooks = hook(cwd.path(), verdict);
+
+    let conversation = ConversationManager::with_auth(CodeAuth::from_api_key("Test API Key"))
+        .new_conversation(config)
+        .await
+        .expect("create new conversation")
+        .conversation;
+    conversation
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "run it".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    HookRun {
+        server,
+        cwd,
+        _code_home: code_home,
+        conversation,
+    }
+}
+
+impl HookRun {
+    /// The tool output that was sent back to the model for the shell call.
+    async fn tool_output(&self) -> String {
+        let requests = self.server.received_requests().await.unwrap();
+        let body: Value = requests.last().unwrap().body_json().unwrap();
+        body["input"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["type"] == "function_call_output" && item["call_id"] == CALL_ID)
+            .map(|item| item["output"].as_str().unwrap_or_default().to_string())
+            .expect("function_call_output for the shell call")
+    }
+
+    fn hook_invocations(&self) -> usize {
+        std::fs::read_to_string(self.cwd.path().join("hook.log"))
+            .unwrap_or_default()
+            .lines()
+            .count()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn deny_verdict_blocks_the_command() {
+    if skip_if_no_network() {
+        return;
+    }
+    let run = start(
+        json!({ "decision": "deny", "reason": "no echo today" }),
+        AskForApproval::Never,
+    )
+    .await;
+    wait_for_event(&run.conversation, |ev| {
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    assert_eq!(
+        run.tool_output().await,
+        "exec command rejected by project hook: no echo today"
+    );
+    assert_eq!(
+        run.hook_invocations(),
+        1,
+        "before hook must run exactly once"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rewrite_verdict_replaces_the_command() {
+    if skip_if_no_network() {
+        return;
+    }
+    let run = start(
+        json!({ "decision": "rewrite", "argv": ["echo", "rewritten"] }),
+        AskForApproval::Never,
+    )
+    .await;
+    wait_for_event(&run.conversation, |ev| {
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let output = run.tool_output().await;
+    assert!(output.contains("rewritten"), "unexpected output: {output}");
+    assert!(!output.contains("original"), "unexpected output: {output}");
+    assert_eq!(
+        run.hook_invocations(),
+        1,
+        "before hook must run exactly once"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn require_approval_verdict_prompts_for_a_safe_command() {
+    if skip_if_no_network() {
+        return;
+    }
+    let run = start(
+        json!({ "decision": "ask", "reason": "hook wants a human" }),
+        AskForApproval::OnRequest,
+    )
+    .await;
+
+    let EventMsg::ExecApprovalRequest(request) = wait_for_event(&run.conversation, |ev| {
+        matches!(ev, EventMsg::ExecApprovalRequest(_))
+    })
+    .await
+    else {
+        unreachable!();
+    };
+    assert_eq!(request.command, vec!["echo", "original"]);
+    assert_eq!(request.reason.as_deref(), Some("hook wants a human"));
+
+    run.conversation
+        .submit(Op::ExecApproval {
+            id: request.call_id,
+            decision: ReviewDecision::Denied,
+        })
+        .await
+        .unwrap();
+    wait_for_event(&run.conversation, |ev| {
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    assert_eq!(run.tool_output().await, "exec command rejected by user");
+    assert_eq!(
+        run.hook_invocations(),
+        1,
+        "before hook must run exactly once"
+    );
+}
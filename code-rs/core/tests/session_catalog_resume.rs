@@ -330,3 +330,51 @@ async fn reconcile_prefers_last_event_over_mtime() {
 
     assert_eq!(latest.session_id, newer_id);
 }
+
+#[tokio::test]
+async fn text_query_matches_message_content() {
+    let temp = TempDir::new().unwrap();
+    let cwd = PathBuf::from("/workspace/project");
+    let auth_id = Uuid::parse_str("f1f1f1f1-f1f1-4f1f-8f1f-f1f1f1f1f1f1").unwrap();
+    let docs_id = Uuid::parse_str("f2f2f2f2-f2f2-4f2f-8f2f-f2f2f2f2f2f2").unwrap();
+    write_rollout_transcript(
+        temp.path(),
+        auth_id,
+        "2025-11-15T10:00:00Z",
+        "2025-11-15T10:00:10Z",
+        &cwd,
+        SessionSource::Cli,
+        "Fix the auth retry bug in the login client",
+    );
+    write_rollout_transcript(
+        temp.path(),
+        docs_id,
+        "2025-11-16T10:00:00Z",
+        "2025-11-16T10:00:10Z",
+        &cwd,
+        SessionSource::Cli,
+        "Document the retry settings",
+    );
+
+    let catalog = SessionCatalog::new(temp.path().to_path_buf());
+    let search = |text: &str| SessionQuery {
+        text: Some(text.to_string()),
+        ..SessionQuery::default()
+    };
+
+    let results = catalog.query(&search("AUTH retry")).await.unwrap();
+    let ids: Vec<_> = results.iter().map(|e| e.session_id).collect();
+    assert_eq!(ids, vec![auth_id]);
+
+    let results = catalog.query(&search("retry")).await.unwrap();
+    assert_eq!(results.len(), 2);
+
+    assert!(
+        catalog
+            .query(&search("kubernetes"))
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(temp.path().join("sessions/index/text_index.json").exists());
+}
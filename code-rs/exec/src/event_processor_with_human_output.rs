@@ -184,7 +184,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::EnvironmentContextFull(_)
             | EventMsg::EnvironmentContextDelta(_)
             | EventMsg::BrowserSnapshot(_)
-            | EventMsg::ListCustomPromptsResponse(_) => {
+            | EventMsg::ListCustomPromptsResponse(_)
+            | EventMsg::ListMcpCatalogResponse(_) => {
                 // Environment context events are consumed by the TUI; the CLI runner
                 // does not surface them alongside the human-readable transcript.
             }
@@ -431,7 +432,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 // it's easy to scan in the terminal output.
                 for (path, change) in changes.iter() {
                     match change {
-                        FileChange::Add { content } => {
+                        FileChange::Add { content, .. } => {
                             let header = format!(
                                 "{} {}",
                                 format_file_change(change),
@@ -530,6 +531,10 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ApplyPatchApprovalRequest(_) => {
                 // Should we exit?
             }
+            EventMsg::McpSamplingRequest(_) | EventMsg::McpElicitationRequest(_) => {
+                // Exec runs with approvals disabled, so core declines these
+                // before they are ever emitted.
+            }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
                     if !self.reasoning_started {
@@ -567,7 +572,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "name: {name:?}");
                 ts_println!(self, "plan: {plan:?}");
             }
-            EventMsg::GetHistoryEntryResponse(_) => {
+            EventMsg::GetHistoryEntryResponse(_) | EventMsg::SearchHistoryResponse(_) => {
                 // Currently ignored in exec output.
             }
             EventMsg::ReplayHistory(_) => {
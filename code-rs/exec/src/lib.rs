@@ -14,6 +14,8 @@ use code_auto_drive_core::AutoTurnAgentsTiming;
 use code_auto_drive_core::AutoTurnCliAction;
 use code_auto_drive_core::MODEL_SLUG;
 use code_auto_drive_core::start_auto_coordinator;
+use code_core::AGENT_USAGE_REPORT_ENV;
+use code_core::AGENT_USAGE_REPORT_PREFIX;
 use code_core::AuthManager;
 use code_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use code_core::BeaconConversation;
@@ -23,12 +25,15 @@ use code_core::config::Config;
 use code_core::config::ConfigOverrides;
 use code_core::config::set_default_originator;
 use code_core::git_info::get_git_repo_root;
+use code_core::protocol::AgentTokenUsage;
 use code_core::protocol::AskForApproval;
 use code_core::protocol::Event;
 use code_core::protocol::EventMsg;
 use code_core::protocol::InputItem;
 use code_core::protocol::Op;
 use code_core::protocol::TaskCompleteEvent;
+use code_core::protocol::TokenCountEvent;
+use code_core::protocol::TokenUsage;
 use code_ollama::DEFAULT_OSS_MODEL;
 use code_protocol::config_types::SandboxMode;
 use code_protocol::models::ContentItem;
@@ -329,7 +334,7 @@ pub async fn run_main(mut cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) ->
     let default_cwd = config.cwd.to_path_buf();
     let _default_approval_policy = config.approval_policy;
     let _default_sandbox_policy = config.sandbox_policy.clone();
-    let _default_model = config.model.clone();
+    let default_model = config.model.clone();
     let _default_effort = config.model_reasoning_effort;
     let _default_summary = config.model_reasoning_summary;
 
@@ -547,10 +552,17 @@ pub async fn run_main(mut cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) ->
     // Track whether a fatal error was reported by the server so we can
     // exit with a non-zero status for automation-friendly signaling.
     let mut error_seen = false;
+    let mut total_usage: Option<TokenUsage> = None;
     while let Some(event) = rx.recv().await {
         if matches!(event.msg, EventMsg::Error(_)) {
             error_seen = true;
         }
+        if let EventMsg::TokenCount(TokenCountEvent {
+            info: Some(info), ..
+        }) = &event.msg
+        {
+            total_usage = Some(info.total_token_usage.clone());
+        }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
@@ -562,6 +574,11 @@ pub async fn run_main(mut cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) ->
             }
         }
     }
+    if std::env::var_os(AGENT_USAGE_REPORT_ENV).is_some()
+        && let Some(usage) = total_usage
+    {
+        report_agent_usage(default_model, usage);
+    }
     if error_seen {
         std::process::exit(1);
     }
@@ -569,6 +586,15 @@ pub async fn run_main(mut cli: Cli, code_linux_sandbox_exe: Option<PathBuf>) ->
     Ok(())
 }
 
+/// Writes the run's token usage for the parent session that launched this
+/// process as an agent.
+fn report_agent_usage(model: String, usage: TokenUsage) {
+    match serde_json::to_string(&AgentTokenUsage { model, usage }) {
+        Ok(json) => eprintln!("{AGENT_USAGE_REPORT_PREFIX}{json}"),
+        Err(err) => warn!("failed to serialize agent usage report: {err}"),
+    }
+}
+
 async fn resolve_resume_path(
     config: &Config,
     args: &crate::cli::ResumeArgs,
@@ -598,6 +624,7 @@ async fn resolve_resume_path(
             include_archived: false,
             include_deleted: false,
             limit: Some(1),
+            text: None,
         };
         let entry = catalog
             .get_latest(&query)
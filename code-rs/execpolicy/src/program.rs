@@ -118,23 +118,29 @@ impl ProgramSpec {
                     program: self.program.clone(),
                 });
             } else if arg.starts_with("-") {
-                match self.allowed_options.get(arg) {
-                    Some(opt) => {
-                        match &opt.meta {
-                            OptMeta::Flag => {
-                                matched_flags.push(MatchedFlag { name: arg.clone() });
-                                // A flag does not expect an argument: continue.
-                                continue;
-                            }
-                            OptMeta::Value(arg_type) => {
-                                expecting_option_value = Some((arg.clone(), arg_type.clone()));
-                                continue;
-                            }
+                if let Some(opt) = self.allowed_options.get(arg) {
+                    match &opt.meta {
+                        OptMeta::Flag => {
+                            matched_flags.push(MatchedFlag { name: arg.clone() });
+                            // A flag does not expect an argument: continue.
+                            continue;
+                        }
+                        OptMeta::Value(arg_type) => {
+                            expecting_option_value = Some((arg.clone(), arg_type.clone()));
+                            continue;
                         }
                     }
-                    None => {
-                        // It could be an --option=value style flag...
-                    }
+                }
+
+                if let Some((name, value, arg_type)) = self.split_combined_option(arg) {
+                    matched_opts.push(MatchedOpt::new(name, value, arg_type)?);
+                    continue;
+                }
+
+                if self.is_option_bundle(arg) {
+                    expecting_option_value =
+                        self.expand_option_bundle(arg, &mut matched_flags, &mut matched_opts)?;
+                    continue;
                 }
 
                 return Err(Error::UnknownOption {
@@ -194,6 +200,58 @@ impl ProgramSpec {
         }
     }
 
+    /// With `combined_format`, split `--name=value` into the option and its
+    /// value. Only options declared with `opt()` accept a value this way.
+    fn split_combined_option<'a>(&self, arg: &'a str) -> Option<(&'a str, &'a str, ArgType)> {
+        if !self.combined_format || !arg.starts_with("--") {
+            return None;
+        }
+        let (name, value) = arg.split_once('=')?;
+        match &self.allowed_options.get(name)?.meta {
+            OptMeta::Value(arg_type) => Some((name, value, arg_type.clone())),
+            OptMeta::Flag => None,
+        }
+    }
+
+    /// With `option_bundling`, `-abc` is treated as `-a -b -c`.
+    fn is_option_bundle(&self, arg: &str) -> bool {
+        self.option_bundling && !arg.starts_with("--") && arg.chars().count() > 2
+    }
+
+    /// Expand a bundle of single-character options. Flags may be combined
+    /// freely; an option that takes a value ends the bundle and consumes the
+    /// rest of it (`-n5`) or, if nothing is left, the next argument (`-ln 5`).
+    /// Returns the option still waiting for its value, if any.
+    fn expand_option_bundle(
+        &self,
+        arg: &str,
+        matched_flags: &mut Vec<MatchedFlag>,
+        matched_opts: &mut Vec<MatchedOpt>,
+    ) -> Result<Option<(String, ArgType)>> {
+        let bundle = &arg[1..];
+        for (offset, c) in bundle.char_indices() {
+            let name = format!("-{c}");
+            match self.allowed_options.get(&name).map(|opt| &opt.meta) {
+                Some(OptMeta::Flag) => matched_flags.push(MatchedFlag { name }),
+                Some(OptMeta::Value(arg_type)) => {
+                    let value = &bundle[offset + c.len_utf8()..];
+                    if value.is_empty() {
+                        return Ok(Some((name, arg_type.clone())));
+                    }
+                    matched_opts.push(MatchedOpt::new(&name, value, arg_type.clone())?);
+                    return Ok(None);
+                }
+                None => {
+                    return Err(Error::UnknownOption {
+                        program: self.program.clone(),
+                        option: name,
+                    });
+                }
+            }
+        }
+        Ok(None)
+    }
+
     pub fn verify_should_match_list(&self) -> Vec<PositiveExampleFailedCheck> {
         let mut violations = Vec::new();
         for good in &self.should_match {
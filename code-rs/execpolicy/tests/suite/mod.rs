@@ -7,4 +7,5 @@ mod literal;
 mod ls;
 mod parse_sed_command;
 mod pwd;
+mod rg;
 mod sed;
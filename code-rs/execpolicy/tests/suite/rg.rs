@@ -0,0 +1,69 @@
+extern crate codex_execpolicy;
+
+use codex_execpolicy::ArgType;
+use codex_execpolicy::Error;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedArg;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::MatchedFlag;
+use codex_execpolicy::MatchedOpt;
+use codex_execpolicy::Policy;
+use codex_execpolicy::Result;
+use codex_execpolicy::ValidExec;
+use codex_execpolicy::get_default_policy;
+
+#[expect(clippy::expect_used)]
+fn setup() -> Policy {
+    get_default_policy().expect("failed to load default policy")
+}
+
+#[test]
+fn test_rg_combined_format() -> Result<()> {
+    let policy = setup();
+    let rg = ExecCall::new("rg", &["--max-count=5", "-in", "init", "src"]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "rg".to_string(),
+                flags: vec![MatchedFlag::new("-i"), MatchedFlag::new("-n")],
+                opts: vec![MatchedOpt::new(
+                    "--max-count",
+                    "5",
+                    ArgType::PositiveInteger
+                )?],
+                args: vec![
+                    MatchedArg::new(2, ArgType::OpaqueNonFile, "init")?,
+                    MatchedArg::new(3, ArgType::ReadableFile, "src")?,
+                ],
+                system_path: vec![],
+            }
+        }),
+        policy.check(&rg)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_rg_combined_format_validates_value() {
+    let policy = setup();
+    let rg = ExecCall::new("rg", &["--max-count=0", "init"]);
+    assert_eq!(
+        Err(Error::InvalidPositiveInteger {
+            value: "0".to_string(),
+        }),
+        policy.check(&rg)
+    );
+}
+
+#[test]
+fn test_rg_flag_does_not_take_combined_value() {
+    let policy = setup();
+    let rg = ExecCall::new("rg", &["--files=yes"]);
+    assert_eq!(
+        Err(Error::UnknownOption {
+            program: "rg".to_string(),
+            option: "--files=yes".to_string(),
+        }),
+        policy.check(&rg)
+    );
+}
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 use std::path::Path;
 use std::path::PathBuf;
 
+use code_core::deny_read::DenyReadMatcher;
 use code_core::error::CodexErr;
 use code_core::error::Result;
 use code_core::error::SandboxErr;
@@ -25,14 +26,72 @@ use seccompiler::SeccompRule;
 use seccompiler::TargetArch;
 use seccompiler::apply_filter;
 
+use crate::namespace::enter_loopback_only_network_namespace;
+use crate::namespace::enter_user_namespace;
+use crate::namespace::hide_paths;
+use crate::proxy_bridge::ProxyBridge;
+
 /// Apply sandbox policies inside this thread so only the child inherits
 /// them, not the entire CLI process.
+///
+/// `deny_read` paths are covered with empty, unreadable mounts in a private
+/// mount namespace, so Landlock can keep granting reads per directory and
+/// files created later stay readable. When `network_proxy` is set, the
+/// command runs in a network namespace with only a loopback interface, where
+/// the proxy port is bridged to the allowlist proxy. Both need unprivileged
+/// user namespaces: without them `deny_read` refuses to run the command and
+/// the allowlist falls back to blocking all network access.
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    network_proxy: Option<ProxyBridge>,
 ) -> Result<()> {
+    let deny_read = if sandbox_policy.has_full_disk_write_access() {
+        Vec::new()
+    } else {
+        DenyReadMatcher::new(sandbox_policy.deny_read(), cwd).existing_paths()
+    };
+    let network_proxy = network_proxy.filter(|_| !sandbox_policy.has_full_network_access());
+
+    // Namespaces have to be set up while the process is still
+    // single-threaded, and before Landlock forbids changing mounts.
+    let user_namespace = if deny_read.is_empty() && network_proxy.is_none() {
+        Ok(())
+    } else {
+        enter_user_namespace().map_err(|err| err.to_string())
+    };
+
+    if !deny_read.is_empty() {
+        user_namespace
+            .clone()
+            .and_then(|()| hide_paths(&deny_read).map_err(|err| err.to_string()))
+            .map_err(|err| {
+                CodexErr::Io(std::io::Error::other(format!(
+                    "cannot enforce the sandbox `deny_read` setting: {err}"
+                )))
+            })?;
+    }
+
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        let proxied = match network_proxy {
+            Some(bridge) => match user_namespace
+                .and_then(|()| route_network_through_proxy(bridge).map_err(|err| err.to_string()))
+            {
+                Ok(()) => true,
+                Err(err) => {
+                    eprintln!(
+                        "code-linux-sandbox: network namespaces are unavailable ({err}); blocking all network access"
+                    );
+                    false
+                }
+            },
+            None => false,
+        };
+        if proxied {
+            install_proxy_only_seccomp_filter_on_current_thread()?;
+        } else {
+            install_network_seccomp_filter_on_current_thread()?;
+        }
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
@@ -44,9 +103,6 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
         install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
     }
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
-
     Ok(())
 }
 
@@ -82,6 +138,72 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
+/// Move into a loopback-only network namespace and fork off the bridge to
+/// the proxy. Returns in the process that goes on to exec the command.
+fn route_network_through_proxy(bridge: ProxyBridge) -> std::io::Result<()> {
+    enter_loopback_only_network_namespace()?;
+    bridge.fork()
+}
+
+/// Installs a seccomp filter for commands inside the loopback-only network
+/// namespace: AF_INET/AF_INET6 sockets are limited to `SOCK_STREAM` so
+/// connections can reach the bridged proxy port, while UDP, raw sockets,
+/// listening sockets and other socket families stay blocked.
+fn install_proxy_only_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+    let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+
+    let mut deny_syscall = |nr: i64| {
+        rules.insert(nr, vec![]);
+    };
+
+    deny_syscall(libc::SYS_accept);
+    deny_syscall(libc::SYS_accept4);
+    deny_syscall(libc::SYS_bind);
+    deny_syscall(libc::SYS_listen);
+    deny_syscall(libc::SYS_sendmmsg);
+    deny_syscall(libc::SYS_recvmmsg);
+    deny_syscall(libc::SYS_ptrace);
+
+    // Deny every socket domain other than AF_UNIX, AF_INET and AF_INET6.
+    let mut socket_rules = vec![SeccompRule::new(
+        [libc::AF_UNIX, libc::AF_INET, libc::AF_INET6]
+            .into_iter()
+            .map(|domain| {
+                SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Ne, domain as u64)
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?,
+    )?];
+
+    // For IP sockets, deny every socket type except SOCK_STREAM. The low four
+    // bits of the type argument hold the type; the rest are flags such as
+    // SOCK_NONBLOCK and SOCK_CLOEXEC.
+    const SOCK_TYPE_MASK: u64 = 0xf;
+    for domain in [libc::AF_INET, libc::AF_INET6] {
+        for ty in (0..=SOCK_TYPE_MASK).filter(|ty| *ty != libc::SOCK_STREAM as u64) {
+            socket_rules.push(SeccompRule::new(vec![
+                SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, domain as u64)?,
+                SeccompCondition::new(
+                    1,
+                    SeccompCmpArgLen::Dword,
+                    SeccompCmpOp::MaskedEq(SOCK_TYPE_MASK),
+                    ty,
+                )?,
+            ])?);
+        }
+    }
+    rules.insert(libc::SYS_socket, socket_rules);
+
+    let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
+        0,
+        SeccompCmpArgLen::Dword,
+        SeccompCmpOp::Ne,
+        libc::AF_UNIX as u64,
+    )?])?;
+    rules.insert(libc::SYS_socketpair, vec![unix_only_rule]);
+
+    apply_deny_filter(rules)
+}
+
 /// Installs a seccomp filter that blocks outbound network access except for
 /// AF_UNIX domain sockets.
 fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
@@ -124,6 +246,14 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
     rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
     rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
 
+    apply_deny_filter(rules)
+}
+
+/// Compiles `rules` into a filter that returns `EPERM` for matching syscalls
+/// and applies it to the current thread.
+fn apply_deny_filter(
+    rules: BTreeMap<i64, Vec<SeccompRule>>,
+) -> std::result::Result<(), SandboxErr> {
     let filter = SeccompFilter::new(
         rules,
         SeccompAction::Allow,                     // default – allow
@@ -2,7 +2,10 @@ use clap::Parser;
 use std::ffi::CString;
 use std::path::PathBuf;
 
+use code_core::exec_limits::ExecResourceLimits;
+
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::proxy_bridge::ProxyBridge;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
@@ -12,6 +15,32 @@ pub struct LandlockCommand {
 
     pub sandbox_policy: code_core::protocol::SandboxPolicy,
 
+    /// Loopback port the command uses to reach the network allowlist proxy.
+    #[arg(long, requires = "network_proxy_socket")]
+    pub network_proxy_port: Option<u16>,
+
+    /// Unix socket of the network allowlist proxy. The command runs in a
+    /// loopback-only network namespace where `network_proxy_port` is bridged
+    /// to this socket.
+    #[arg(long, requires = "network_proxy_port")]
+    pub network_proxy_socket: Option<PathBuf>,
+
+    /// Address space limit for the command, in MiB.
+    #[arg(long)]
+    pub memory_mb: Option<u64>,
+
+    /// CPU time limit for the command, in seconds.
+    #[arg(long)]
+    pub cpu_seconds: Option<u64>,
+
+    /// Limit on the number of processes owned by the user.
+    #[arg(long)]
+    pub max_processes: Option<u64>,
+
+    /// Largest file the command may write, in MiB.
+    #[arg(long)]
+    pub file_size_mb: Option<u64>,
+
     /// Full command args to run under landlock.
     #[arg(trailing_var_arg = true)]
     pub command: Vec<String>,
@@ -21,13 +50,37 @@ pub fn run_main() -> ! {
     let LandlockCommand {
         sandbox_policy_cwd,
         sandbox_policy,
+        network_proxy_port,
+        network_proxy_socket,
+        memory_mb,
+        cpu_seconds,
+        max_processes,
+        file_size_mb,
         command,
     } = LandlockCommand::parse();
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
+    let network_proxy = network_proxy_port
+        .zip(network_proxy_socket)
+        .map(|(port, socket_path)| ProxyBridge { port, socket_path });
+    if let Err(e) =
+        apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd, network_proxy)
+    {
         panic!("error running landlock: {e:?}");
     }
 
+    // Only the command is limited. Applying the limits before the sandbox
+    // would also bind the proxy bridge that the sandbox setup forks, which
+    // needs a thread per connection.
+    let resource_limits = ExecResourceLimits {
+        memory_mb,
+        cpu_seconds,
+        max_processes,
+        file_size_mb,
+    };
+    if let Err(e) = resource_limits.apply_to_current_process() {
+        panic!("error applying resource limits: {e}");
+    }
+
     if command.is_empty() {
         panic!("No command specified to execute.");
     }
@@ -0,0 +1,178 @@
+//! Unprivileged Linux namespaces for the sandboxed command.
+//!
+//! The helper first moves into a new user namespace that maps the caller's
+//! uid and gid to themselves, which gives it the capabilities it needs to
+//! create further namespaces without any real privilege. Files keep their
+//! usual owners and permission checks still use the caller's credentials.
+
+use std::ffi::CString;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Unshare into a new user namespace mapping the current uid and gid to
+/// themselves. Must be called while the process is still single-threaded.
+pub(crate) fn enter_user_namespace() -> io::Result<()> {
+    let uid = unsafe { libc::geteuid() };
+    let gid = unsafe { libc::getegid() };
+    unshare(libc::CLONE_NEWUSER)?;
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    Ok(())
+}
+
+/// Unshare into a new network namespace whose only interface is an active
+/// loopback device. Requires [`enter_user_namespace`] first.
+pub(crate) fn enter_loopback_only_network_namespace() -> io::Result<()> {
+    unshare(libc::CLONE_NEWNET)?;
+    bring_up_loopback()
+}
+
+/// Unshare into a private mount namespace and cover each of `paths` with an
+/// empty, read-only file or directory that nobody may read. Requires
+/// [`enter_user_namespace`] first; the mounts never reach the host.
+pub(crate) fn hide_paths(paths: &[PathBuf]) -> io::Result<()> {
+    unshare(libc::CLONE_NEWNS)?;
+    mount(
+        None,
+        Path::new("/"),
+        None,
+        libc::MS_REC | libc::MS_PRIVATE,
+        None,
+    )?;
+
+    // Pin the targets before the staging mount below can shadow any of them.
+    let mut targets = Vec::new();
+    for path in paths {
+        let target = match OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
+            .open(path)
+        {
+            Ok(target) => target,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        let is_dir = target.metadata()?.is_dir();
+        targets.push((target, is_dir));
+    }
+
+    // Stage the covers on a private tmpfs. Bind mounts keep the tmpfs alive
+    // after the staging mount itself is detached again.
+    let staging = std::env::temp_dir();
+    let flags = libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC;
+    mount(
+        Some(Path::new("tmpfs")),
+        &staging,
+        Some("tmpfs"),
+        flags,
+        Some("mode=0700"),
+    )?;
+    let cover_file = staging.join("hidden-file");
+    let cover_dir = staging.join("hidden-dir");
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o000)
+        .open(&cover_file)?;
+    std::fs::DirBuilder::new().mode(0o000).create(&cover_dir)?;
+    mount(
+        None,
+        &staging,
+        None,
+        libc::MS_REMOUNT | libc::MS_RDONLY | flags,
+        None,
+    )?;
+
+    for (target, is_dir) in &targets {
+        let cover = if *is_dir { &cover_dir } else { &cover_file };
+        mount(Some(cover), &fd_path(target), None, libc::MS_BIND, None)?;
+    }
+
+    let staging = cstring(&staging)?;
+    if unsafe { libc::umount2(staging.as_ptr(), libc::MNT_DETACH) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn fd_path(file: &File) -> PathBuf {
+    PathBuf::from(format!("/proc/self/fd/{}", file.as_raw_fd()))
+}
+
+fn cstring(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes()).map_err(io::Error::other)
+}
+
+fn mount(
+    source: Option<&Path>,
+    target: &Path,
+    fstype: Option<&str>,
+    flags: libc::c_ulong,
+    data: Option<&str>,
+) -> io::Result<()> {
+    let source = source.map(cstring).transpose()?;
+    let target = cstring(target)?;
+    let fstype = fstype
+        .map(CString::new)
+        .transpose()
+        .map_err(io::Error::other)?;
+    let data = data
+        .map(CString::new)
+        .transpose()
+        .map_err(io::Error::other)?;
+    let rc = unsafe {
+        libc::mount(
+            source.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            target.as_ptr(),
+            fstype.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            flags,
+            data.as_ref()
+                .map_or(std::ptr::null(), |s| s.as_ptr().cast::<libc::c_void>()),
+        )
+    };
+    if rc != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn unshare(flags: libc::c_int) -> io::Result<()> {
+    if unsafe { libc::unshare(flags) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// A fresh network namespace starts with `lo` down; set `IFF_UP` on it.
+fn bring_up_loopback() -> io::Result<()> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let mut request: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in request.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    if unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCGIFFLAGS as _, &mut request) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    unsafe {
+        request.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short;
+    }
+    if unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCSIFFLAGS as _, &request) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
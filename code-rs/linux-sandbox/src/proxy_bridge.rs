@@ -0,0 +1,114 @@
+//! Bridge from the command's loopback-only network namespace to the network
+//! allowlist proxy.
+//!
+//! The command is pointed at `127.0.0.1:<port>`, but inside its network
+//! namespace nothing outside loopback is reachable. The helper therefore
+//! forks before applying the sandbox: the child goes on to exec the command
+//! and the parent stays behind, accepting connections on that port and
+//! forwarding each one to the proxy's unix socket. Pathname unix sockets are
+//! resolved through the file system, so they work across network namespaces.
+
+use std::io;
+use std::net::Ipv4Addr;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+/// The allowlist proxy the command may reach.
+#[derive(Debug, Clone)]
+pub(crate) struct ProxyBridge {
+    /// Port the command connects to on `127.0.0.1`.
+    pub port: u16,
+    /// Unix socket of the proxy, outside the namespace.
+    pub socket_path: PathBuf,
+}
+
+impl ProxyBridge {
+    /// Listen on `127.0.0.1:<port>` in the current network namespace, then
+    /// fork. Only returns in the child, which should go on to exec the
+    /// command. The parent forwards connections until the child exits and
+    /// then exits the same way.
+    pub(crate) fn fork(self) -> io::Result<()> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, self.port))?;
+        let bridge_pid = unsafe { libc::getpid() };
+        match unsafe { libc::fork() } {
+            -1 => Err(io::Error::last_os_error()),
+            0 => {
+                drop(listener);
+                // The command must not outlive the bridge that owns its
+                // network access.
+                if unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) } == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                if unsafe { libc::getppid() } != bridge_pid {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            child => {
+                let socket_path = self.socket_path;
+                std::thread::spawn(move || accept_connections(listener, socket_path));
+                match wait_for(child) {
+                    Some(status) => exit_like(status),
+                    None => std::process::exit(1),
+                }
+            }
+        }
+    }
+}
+
+fn accept_connections(listener: TcpListener, socket_path: PathBuf) {
+    for client in listener.incoming().flatten() {
+        let socket_path = socket_path.clone();
+        std::thread::spawn(move || {
+            if let Ok(upstream) = UnixStream::connect(&socket_path) {
+                let _ = forward(client, upstream);
+            }
+        });
+    }
+}
+
+/// Copy bytes both ways until each side has closed its half.
+fn forward(client: TcpStream, upstream: UnixStream) -> io::Result<()> {
+    let mut client_read = client.try_clone()?;
+    let mut upstream_write = upstream.try_clone()?;
+    let to_proxy = std::thread::spawn(move || {
+        let _ = io::copy(&mut client_read, &mut upstream_write);
+        let _ = upstream_write.shutdown(Shutdown::Write);
+    });
+
+    let mut upstream_read = upstream;
+    let mut client_write = client;
+    let _ = io::copy(&mut upstream_read, &mut client_write);
+    let _ = client_write.shutdown(Shutdown::Write);
+    let _ = to_proxy.join();
+    Ok(())
+}
+
+fn wait_for(child: libc::pid_t) -> Option<libc::c_int> {
+    let mut status: libc::c_int = 0;
+    loop {
+        if unsafe { libc::waitpid(child, &mut status, 0) } == child {
+            return Some(status);
+        }
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return None;
+        }
+    }
+}
+
+/// Exit with the command's exit code, or die from the signal that killed it
+/// so callers see the same wait status as for an unbridged command.
+fn exit_like(status: libc::c_int) -> ! {
+    if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        std::process::exit(128 + signal);
+    }
+    std::process::exit(libc::WEXITSTATUS(status));
+}
@@ -6,6 +6,7 @@ use code_core::exec::ExecParams;
 use code_core::exec::SandboxType;
 use code_core::exec::process_exec_tool_call;
 use code_core::exec_env::create_env;
+use code_core::exec_limits::ExecResourceLimits;
 use code_core::protocol::SandboxPolicy;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -44,11 +45,14 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ExecResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: writable_roots.to_vec(),
         network_access: false,
+        network_allowlist: Vec::new(),
+        deny_read: Vec::new(),
         // Exclude tmp-related folders from writable roots because we need a
         // folder that is writable by tests but that we intentionally disallow
         // writing to in the sandbox.
@@ -147,6 +151,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ExecResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -235,3 +240,312 @@ async fn sandbox_blocks_dev_tcp_redirection() {
     // all images ship bash, so we guard against 127 as well.
     assert_network_blocked(&["bash", "-c", "echo hi > /dev/tcp/127.0.0.1/80"]).await;
 }
+
+#[tokio::test]
+#[expect(clippy::expect_used)]
+async fn sandbox_denies_reading_deny_read_paths() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let cwd = tmpdir.path().canonicalize().expect("canonical tempdir");
+    std::fs::write(cwd.join(".env"), "SECRET=1").expect("write .env");
+    std::fs::write(cwd.join("README"), "hello").expect("write README");
+
+    let sandbox_policy = SandboxPolicy::ReadOnly {
+        deny_read: vec!["**/.env*".to_string()],
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_code-linux-sandbox");
+    let code_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let params = ExecParams {
+        command: vec!["cat".to_string(), "README".to_string(), ".env".to_string()],
+        cwd: cwd.clone(),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        resource_limits: ExecResourceLimits::default(),
+    };
+
+    let output = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        cwd.as_path(),
+        &code_linux_sandbox_exe,
+        None,
+    )
+    .await
+    .expect("cat should run");
+
+    if user_namespaces_unavailable(&output.stderr.text) {
+        return;
+    }
+    assert_ne!(output.exit_code, 0);
+    assert!(output.stdout.text.contains("hello"));
+    assert!(!output.stdout.text.contains("SECRET"));
+    assert!(
+        output
+            .stderr
+            .text
+            .contains("blocked by the sandbox `deny_read` setting"),
+        "stderr: {}",
+        output.stderr.text
+    );
+}
+
+/// `deny_read` needs unprivileged user namespaces; the helper refuses to run
+/// commands without them.
+fn user_namespaces_unavailable(stderr: &str) -> bool {
+    let unavailable = stderr.contains("cannot enforce the sandbox `deny_read` setting");
+    if unavailable {
+        eprintln!("skipping: unprivileged user namespaces are disabled");
+    }
+    unavailable
+}
+
+#[tokio::test]
+#[expect(clippy::expect_used)]
+async fn deny_read_keeps_new_files_in_the_same_directory_readable() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let cwd = tmpdir.path().canonicalize().expect("canonical tempdir");
+    std::fs::write(cwd.join(".env"), "SECRET=1").expect("write .env");
+
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: Vec::new(),
+        network_access: false,
+        network_allowlist: Vec::new(),
+        deny_read: vec!["**/.env*".to_string()],
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        allow_git_writes: true,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_code-linux-sandbox");
+    let code_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let params = ExecParams {
+        command: vec![
+            "bash".to_string(),
+            "-c".to_string(),
+            "echo fresh > new.txt && cat new.txt && ls -a && ! cat .env".to_string(),
+        ],
+        cwd: cwd.clone(),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        resource_limits: ExecResourceLimits::default(),
+    };
+
+    let output = match process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        cwd.as_path(),
+        &code_linux_sandbox_exe,
+        None,
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => *output,
+        Err(err) => panic!("unexpected error: {err:?}"),
+    };
+
+    if user_namespaces_unavailable(&output.stderr.text) {
+        return;
+    }
+    assert_eq!(
+        output.exit_code, 0,
+        "stdout:\n{}\nstderr:\n{}",
+        output.stdout.text, output.stderr.text
+    );
+    assert!(
+        output.stdout.text.starts_with("fresh\n"),
+        "{}",
+        output.stdout.text
+    );
+    assert!(
+        output.stdout.text.contains(".env"),
+        "{}",
+        output.stdout.text
+    );
+    assert!(
+        !output.stdout.text.contains("SECRET"),
+        "{}",
+        output.stdout.text
+    );
+    assert_eq!(
+        std::fs::read_to_string(cwd.join(".env")).expect("read .env"),
+        "SECRET=1",
+        "the real file must be untouched"
+    );
+}
+
+#[tokio::test]
+#[expect(clippy::expect_used)]
+async fn network_allowlist_only_reaches_the_proxy() {
+    use std::io::Read;
+    use std::io::Write;
+
+    // An allowlisted "upstream" on the host's loopback interface.
+    let upstream = std::net::TcpListener::bind("127.0.0.1:0").expect("bind upstream");
+    let upstream_port = upstream.local_addr().expect("upstream addr").port();
+    std::thread::spawn(move || {
+        for mut stream in upstream.incoming().flatten() {
+            let mut buf = [0u8; 4];
+            if stream.read_exact(&mut buf).is_ok() {
+                let _ = stream.write_all(b"pong");
+            }
+        }
+    });
+
+    let cwd = std::env::current_dir().expect("cwd should exist");
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: Vec::new(),
+        network_access: false,
+        network_allowlist: vec![format!("127.0.0.1:{upstream_port}")],
+        deny_read: Vec::new(),
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        allow_git_writes: true,
+    };
+    // Tunnel through the proxy named in `HTTP_PROXY`, then try the same
+    // upstream directly; only the first may succeed.
+    let script = format!(
+        r#"hp=${{HTTP_PROXY#http://}}
+exec 3<>/dev/tcp/${{hp%:*}}/${{hp##*:}} || exit 10
+printf 'CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\nping' >&3
+head -c 43 <&3 || exit 11
+echo
+if (echo ping > /dev/tcp/127.0.0.1/{upstream_port}) 2>/dev/null; then exit 12; fi"#
+    );
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script],
+        cwd: cwd.clone(),
+        timeout_ms: Some(NETWORK_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        resource_limits: ExecResourceLimits::default(),
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_code-linux-sandbox");
+    let code_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let output = match process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        cwd.as_path(),
+        &code_linux_sandbox_exe,
+        None,
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => *output,
+        Err(err) => panic!("unexpected error: {err:?}"),
+    };
+
+    if output
+        .stderr
+        .text
+        .contains("network namespaces are unavailable")
+    {
+        eprintln!("skipping: unprivileged user namespaces are disabled");
+        return;
+    }
+    assert_eq!(
+        output.exit_code, 0,
+        "stdout:\n{}\nstderr:\n{}",
+        output.stdout.text, output.stderr.text
+    );
+    assert!(
+        output
+            .stdout
+            .text
+            .starts_with("HTTP/1.1 200 Connection Established\r\n\r\npong"),
+        "stdout: {}",
+        output.stdout.text
+    );
+}
+
+#[tokio::test]
+#[expect(clippy::expect_used)]
+async fn process_limit_does_not_apply_to_the_proxy_bridge() {
+    use std::io::Read;
+    use std::io::Write;
+
+    let upstream = std::net::TcpListener::bind("127.0.0.1:0").expect("bind upstream");
+    let upstream_port = upstream.local_addr().expect("upstream addr").port();
+    std::thread::spawn(move || {
+        for mut stream in upstream.incoming().flatten() {
+            let mut buf = [0u8; 4];
+            if stream.read_exact(&mut buf).is_ok() {
+                let _ = stream.write_all(b"pong");
+            }
+        }
+    });
+
+    let cwd = std::env::current_dir().expect("cwd should exist");
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: Vec::new(),
+        network_access: false,
+        network_allowlist: vec![format!("127.0.0.1:{upstream_port}")],
+        deny_read: Vec::new(),
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        allow_git_writes: true,
+    };
+    // Builtins only: the command itself cannot fork under a limit of one
+    // process, but the bridge still has to start a thread per connection.
+    let script = format!(
+        r#"hp=${{HTTP_PROXY#http://}}
+exec 3<>/dev/tcp/${{hp%:*}}/${{hp##*:}} || exit 10
+printf 'CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\nping' >&3
+read -r -N 43 reply <&3 || exit 11
+printf '%s' "$reply""#
+    );
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script],
+        cwd: cwd.clone(),
+        timeout_ms: Some(NETWORK_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        resource_limits: ExecResourceLimits {
+            max_processes: Some(1),
+            ..ExecResourceLimits::default()
+        },
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_code-linux-sandbox");
+    let code_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let output = match process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        cwd.as_path(),
+        &code_linux_sandbox_exe,
+        None,
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => *output,
+        Err(err) => panic!("unexpected error: {err:?}"),
+    };
+
+    if output
+        .stderr
+        .text
+        .contains("network namespaces are unavailable")
+    {
+        eprintln!("skipping: unprivileged user namespaces are disabled");
+        return;
+    }
+    assert_eq!(
+        output.exit_code, 0,
+        "stdout:\n{}\nstderr:\n{}",
+        output.stdout.text, output.stderr.text
+    );
+    assert!(
+        output.stdout.text.ends_with("pong"),
+        "stdout: {}",
+        output.stdout.text
+    );
+}
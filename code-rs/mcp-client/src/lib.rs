@@ -1,3 +1,5 @@
 mod mcp_client;
 
 pub use mcp_client::McpClient;
+pub use mcp_client::ServerNotificationHandler;
+pub use mcp_client::ServerRequestHandler;
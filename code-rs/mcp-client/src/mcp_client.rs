@@ -6,6 +6,10 @@
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
 //!   3. Offering a convenience helper for the common `tools/list` request.
+//!   4. Answering server-initiated requests (`roots/list`, `sampling/...`,
+//!      `elicitation/create`) through an optional [`ServerRequestHandler`].
+//!   5. Reporting server notifications (e.g. `notifications/tools/list_changed`)
+//!      through an optional [`ServerNotificationHandler`].
 //!
 //! The crate hides all JSON‐RPC framing details behind a typed API. Users
 //! interact with the [`ModelContextProtocolRequest`] trait from `mcp-types` to
@@ -13,7 +17,11 @@
 
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -23,19 +31,36 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
 use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
 use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourceTemplatesRequest;
+use mcp_types::ListResourceTemplatesRequestParams;
+use mcp_types::ListResourceTemplatesResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
@@ -60,6 +85,26 @@ const CHANNEL_CAPACITY: usize = 128;
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// JSON-RPC error code returned for server requests nobody handles.
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+
+/// Callback that answers a request initiated by the server. It receives the
+/// method name and raw params and resolves to the JSON `result` (or a JSON-RPC
+/// error) that is sent back to the server.
+pub type ServerRequestHandler = Arc<
+    dyn Fn(
+            String,
+            Option<serde_json::Value>,
+        )
+            -> Pin<Box<dyn Future<Output = Result<serde_json::Value, JSONRPCErrorError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Callback invoked for every notification sent by the server, with the
+/// method name and raw params.
+pub type ServerNotificationHandler = Arc<dyn Fn(String, Option<serde_json::Value>) + Send + Sync>;
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
@@ -77,6 +122,18 @@ pub struct McpClient {
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Handler for requests initiated by the server, shared with the reader
+    /// task. When unset, such requests are answered with "method not found".
+    server_request_handler: Arc<RwLock<Option<ServerRequestHandler>>>,
+
+    /// Handler for notifications sent by the server. When unset they are only
+    /// logged.
+    server_notification_handler: Arc<RwLock<Option<ServerNotificationHandler>>>,
+
+    /// Set once the reader task stops, i.e. the server closed its stdout or
+    /// exited.
+    closed: Arc<AtomicBool>,
 }
 
 impl McpClient {
@@ -118,6 +175,11 @@ impl McpClient {
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
         let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let server_request_handler: Arc<RwLock<Option<ServerRequestHandler>>> =
+            Arc::new(RwLock::new(None));
+        let server_notification_handler: Arc<RwLock<Option<ServerNotificationHandler>>> =
+            Arc::new(RwLock::new(None));
+        let closed = Arc::new(AtomicBool::new(false));
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -148,6 +210,10 @@ impl McpClient {
         // STDOUT and dispatches responses to the pending map.
         let reader_handle = {
             let pending = pending.clone();
+            let handler = server_request_handler.clone();
+            let notification_handler = server_notification_handler.clone();
+            let closed = closed.clone();
+            let outgoing_tx = outgoing_tx.clone();
             let mut lines = BufReader::new(stdout).lines();
 
             tokio::spawn(async move {
@@ -160,14 +226,22 @@ impl McpClient {
                         Ok(JSONRPCMessage::Error(err)) => {
                             Self::dispatch_error(err, &pending).await;
                         }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
+                        Ok(JSONRPCMessage::Notification(JSONRPCNotification {
+                            method,
+                            params,
+                            ..
+                        })) => {
                             info!("<- notification: {}", line);
+                            let handler = notification_handler
+                                .read()
+                                .ok()
+                                .and_then(|guard| guard.clone());
+                            if let Some(handler) = handler {
+                                handler(method, params);
+                            }
                         }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
+                        Ok(JSONRPCMessage::Request(request)) => {
+                            Self::dispatch_server_request(request, &handler, &outgoing_tx);
                         }
                         Err(e) => {
                             error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
@@ -178,6 +252,7 @@ impl McpClient {
                 // The child process stdout closed (or the read loop errored). Any pending
                 // requests would otherwise wait forever (especially when `timeout=None`),
                 // so fail them all explicitly.
+                closed.store(true, Ordering::SeqCst);
                 Self::fail_all_pending_connection_closed(&pending).await;
             })
         };
@@ -193,9 +268,34 @@ impl McpClient {
             outgoing_tx,
             pending,
             id_counter: AtomicI64::new(1),
+            server_request_handler,
+            server_notification_handler,
+            closed,
         })
     }
 
+    /// Install the handler used to answer requests initiated by the server.
+    /// Should be called before [`initialize`](Self::initialize) so no request
+    /// can arrive unanswered.
+    pub fn set_server_request_handler(&self, handler: ServerRequestHandler) {
+        if let Ok(mut guard) = self.server_request_handler.write() {
+            *guard = Some(handler);
+        }
+    }
+
+    /// Install the handler that receives notifications sent by the server.
+    pub fn set_server_notification_handler(&self, handler: ServerNotificationHandler) {
+        if let Ok(mut guard) = self.server_notification_handler.write() {
+            *guard = Some(handler);
+        }
+    }
+
+    /// Returns `true` once the server has closed its stdout (typically because
+    /// the process exited). Every subsequent request fails.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
     /// Send an arbitrary MCP request and await the typed result.
     ///
     /// If `timeout` is `None` the call waits indefinitely. If `Some(duration)`
@@ -360,6 +460,98 @@ impl McpClient {
         self.send_request::<CallToolRequest>(params, timeout).await
     }
 
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/templates/list`.
+    pub async fn list_resource_templates(
+        &self,
+        params: Option<ListResourceTemplatesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourceTemplatesResult> {
+        self.send_request::<ListResourceTemplatesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
+    /// Internal helper: answer a request initiated by the server. The handler
+    /// runs on its own task so a slow answer (e.g. waiting on the user) does
+    /// not stall the reader loop.
+    fn dispatch_server_request(
+        request: JSONRPCRequest,
+        handler: &Arc<RwLock<Option<ServerRequestHandler>>>,
+        outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
+    ) {
+        let JSONRPCRequest {
+            id, method, params, ..
+        } = request;
+        let handler = handler.read().ok().and_then(|guard| guard.clone());
+        let outgoing_tx = outgoing_tx.clone();
+        tokio::spawn(async move {
+            let result = match handler {
+                Some(handler) => handler(method.clone(), params).await,
+                None => Err(JSONRPCErrorError {
+                    code: METHOD_NOT_FOUND_ERROR_CODE,
+                    message: format!("client does not handle `{method}`"),
+                    data: None,
+                }),
+            };
+            let message = match result {
+                Ok(result) => JSONRPCMessage::Response(JSONRPCResponse {
+                    id,
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                    result,
+                }),
+                Err(error) => JSONRPCMessage::Error(JSONRPCError {
+                    id,
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                    error,
+                }),
+            };
+            if outgoing_tx.send(message).await.is_err() {
+                warn!("failed to answer server request `{method}`: writer task closed");
+            }
+        });
+    }
+
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
     async fn dispatch_response(
         resp: JSONRPCResponse,
@@ -57,6 +57,11 @@ pub struct CodexToolCallParam {
     /// Whether to include the plan tool in the conversation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub include_plan_tool: Option<bool>,
+
+    /// Stream agent message and reasoning deltas while the turn runs, as
+    /// `notifications/progress` keyed to the request's progress token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
 }
 
 /// Custom enum mirroring [`AskForApproval`], but has an extra dependency on
@@ -269,6 +274,7 @@ impl CodexToolCallParam {
             config: cli_overrides,
             base_instructions,
             include_plan_tool,
+            stream: _,
         } = self;
 
         // Build the `ConfigOverrides` recognized by code-core.
@@ -316,6 +322,11 @@ pub struct CodexToolCallReplyParam {
 
     /// The *next user prompt* to continue the Beacon Code conversation.
     pub prompt: String,
+
+    /// Stream agent message and reasoning deltas while the turn runs, as
+    /// `notifications/progress` keyed to the request's progress token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
 }
 
 fn build_code_tool_call_reply_tool(name: &str, title: &str) -> Tool {
@@ -434,6 +445,10 @@ mod tests {
                 "description": "The set of instructions to use instead of the default ones.",
                 "type": "string"
               },
+              "stream": {
+                "description": "Stream agent message and reasoning deltas while the turn runs, as `notifications/progress` keyed to the request's progress token.",
+                "type": "boolean"
+              },
             },
             "required": [
               "prompt"
@@ -483,6 +498,10 @@ mod tests {
                 "description": "The *session id* for this conversation.",
                 "type": "string"
               },
+              "stream": {
+                "description": "Stream agent message and reasoning deltas while the turn runs, as `notifications/progress` keyed to the request's progress token.",
+                "type": "boolean"
+              },
             },
             "required": [
               "prompt",
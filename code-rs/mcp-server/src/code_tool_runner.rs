@@ -29,10 +29,13 @@ use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::exec_approval::handle_exec_approval_request;
+use crate::mcp_client_request::handle_mcp_elicitation_request;
+use crate::mcp_client_request::handle_mcp_sampling_request;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingMessageSenderExt;
 use crate::outgoing_message::OutgoingNotificationMeta;
 use crate::patch_approval::handle_patch_approval_request;
+use crate::progress_stream::ProgressStream;
 use crate::session_store::SessionEntry;
 use crate::session_store::SessionMap;
 
@@ -62,6 +65,7 @@ pub async fn run_code_tool_session(
     session_map: SessionMap,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_code_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    progress: ProgressStream,
 ) {
     let config_for_session = config.clone();
     let NewConversation {
@@ -155,6 +159,7 @@ pub async fn run_code_tool_session(
         session_map,
         conversation_manager,
         running_requests_id_to_code_uuid,
+        progress,
     )
     .await;
 }
@@ -168,6 +173,7 @@ pub async fn run_code_tool_session_reply(
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_code_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
     session_id: Uuid,
+    progress: ProgressStream,
 ) {
     running_requests_id_to_code_uuid
         .lock()
@@ -210,6 +216,7 @@ pub async fn run_code_tool_session_reply(
         session_map,
         conversation_manager,
         running_requests_id_to_code_uuid,
+        progress,
     )
     .await;
 }
@@ -222,6 +229,7 @@ async fn run_code_tool_session_inner(
     session_map: SessionMap,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_code_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    mut progress: ProgressStream,
 ) {
     let request_id_str = match &request_id {
         RequestId::String(s) => s.clone(),
@@ -234,12 +242,14 @@ async fn run_code_tool_session_inner(
     loop {
         match codex.next_event().await {
             Ok(event) => {
+                let meta = progress.event_meta(
+                    OutgoingNotificationMeta::new(Some(request_id.clone())),
+                    &event.msg,
+                );
                 outgoing
-                    .send_event_as_notification(
-                        &event,
-                        Some(OutgoingNotificationMeta::new(Some(request_id.clone()))),
-                    )
+                    .send_event_as_notification(&event, Some(meta))
                     .await;
+                progress.forward(&outgoing, &event.msg).await;
 
                 match event.msg {
                     EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
@@ -314,6 +324,30 @@ async fn run_code_tool_session_inner(
                         .await;
                         continue;
                     }
+                    EventMsg::McpSamplingRequest(ev) => {
+                        handle_mcp_sampling_request(
+                            ev,
+                            outgoing.clone(),
+                            codex.clone(),
+                            request_id.clone(),
+                            request_id_str.clone(),
+                            event.id.clone(),
+                        )
+                        .await;
+                        continue;
+                    }
+                    EventMsg::McpElicitationRequest(ev) => {
+                        handle_mcp_elicitation_request(
+                            ev,
+                            outgoing.clone(),
+                            codex.clone(),
+                            request_id.clone(),
+                            request_id_str.clone(),
+                            event.id.clone(),
+                        )
+                        .await;
+                        continue;
+                    }
                     EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
                         let mut structured_content = json!({
                             "status": "complete",
@@ -351,14 +385,11 @@ async fn run_code_tool_session_inner(
                     EventMsg::SessionConfigured(_) => {
                         tracing::error!("unexpected SessionConfigured event");
                     }
-                    EventMsg::AgentMessageDelta(_) => {
-                        // TODO: think how we want to support this in the MCP
-                    }
-                    EventMsg::AgentReasoningDelta(_) => {
-                        // TODO: think how we want to support this in the MCP
-                    }
-                    EventMsg::AgentMessage(AgentMessageEvent { .. }) => {
-                        // TODO: think how we want to support this in the MCP
+                    EventMsg::AgentMessageDelta(_)
+                    | EventMsg::AgentReasoningDelta(_)
+                    | EventMsg::AgentMessage(AgentMessageEvent { .. }) => {
+                        // Streamed by `progress.forward` above when the
+                        // tool-call opted in.
                     }
                     EventMsg::TokenCount(ev) => {
                         last_token_count = Some(ev);
@@ -381,6 +412,7 @@ async fn run_code_tool_session_inner(
                     | EventMsg::WebSearchBegin(_)
                     | EventMsg::WebSearchComplete(_)
                     | EventMsg::GetHistoryEntryResponse(_)
+                    | EventMsg::SearchHistoryResponse(_)
                     | EventMsg::ReplayHistory(_)
                     | EventMsg::PlanUpdate(_)
                     | EventMsg::BrowserScreenshotUpdate(_)
@@ -388,6 +420,7 @@ async fn run_code_tool_session_inner(
                     | EventMsg::EnvironmentContextFull(_)
                     | EventMsg::EnvironmentContextDelta(_)
                     | EventMsg::ListCustomPromptsResponse(_)
+                    | EventMsg::ListMcpCatalogResponse(_)
                     | EventMsg::AgentStatusUpdate(_)
                     | EventMsg::CompactionCheckpointWarning(_)
                     | EventMsg::TurnAborted(_)
@@ -26,9 +26,11 @@ mod code_tool_config;
 mod code_tool_runner;
 mod error_code;
 mod exec_approval;
+mod mcp_client_request;
 pub(crate) mod message_processor;
 mod outgoing_message;
 mod patch_approval;
+mod progress_stream;
 mod session_store;
 
 use crate::message_processor::MessageProcessor;
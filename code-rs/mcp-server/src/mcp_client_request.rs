@@ -0,0 +1,227 @@
+//! Forwarding of requests that downstream MCP servers make of the Beacon Code
+//! session (sampling and elicitation) to the client driving this MCP server.
+//! Both are sent as `elicitation/create` requests, mirroring exec and patch
+//! approvals, and the client's answer is submitted back to the session.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use code_core::BeaconConversation;
+use code_core::protocol::McpElicitationAction;
+use code_core::protocol::McpElicitationRequestEvent;
+use code_core::protocol::McpSamplingRequestEvent;
+use code_core::protocol::Op;
+use code_core::protocol::ReviewDecision;
+use mcp_types::ElicitRequest;
+use mcp_types::ElicitRequestParamsRequestedSchema;
+use mcp_types::ElicitResult;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::RequestId;
+use mcp_types::SamplingMessageContent;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+use tokio::time::timeout;
+use tracing::error;
+use tracing::warn;
+
+use crate::code_tool_runner::INVALID_PARAMS_ERROR_CODE;
+use crate::outgoing_message::OutgoingMessageSender;
+
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Longest excerpt of the sampling conversation included in the approval
+/// message.
+const SAMPLING_PREVIEW_MAX_CHARS: usize = 400;
+
+/// Conforms to [`mcp_types::ElicitRequestParams`] so that it can be used as the
+/// `params` field of an [`ElicitRequest`].
+#[derive(Debug, Deserialize, Serialize)]
+pub struct McpClientElicitRequestParams {
+    pub message: String,
+
+    #[serde(rename = "requestedSchema")]
+    pub requested_schema: ElicitRequestParamsRequestedSchema,
+
+    // Correlation fields for the client, as for exec/patch approvals.
+    pub code_elicitation: String,
+    pub code_mcp_tool_call_id: String,
+    pub code_event_id: String,
+    pub code_mcp_server: String,
+}
+
+pub(crate) async fn handle_mcp_sampling_request(
+    event: McpSamplingRequestEvent,
+    outgoing: Arc<OutgoingMessageSender>,
+    codex: Arc<BeaconConversation>,
+    request_id: RequestId,
+    tool_call_id: String,
+    event_id: String,
+) {
+    let mut message = format!(
+        "Allow MCP server `{}` to run a model completion (up to {} tokens)?",
+        event.server, event.max_tokens
+    );
+    let preview = sampling_preview(&event);
+    if !preview.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(&preview);
+    }
+
+    let params = McpClientElicitRequestParams {
+        message,
+        requested_schema: ElicitRequestParamsRequestedSchema {
+            r#type: "object".to_string(),
+            properties: json!({}),
+            required: None,
+        },
+        code_elicitation: "mcp-sampling".to_string(),
+        code_mcp_tool_call_id: tool_call_id,
+        code_event_id: event_id,
+        code_mcp_server: event.server,
+    };
+    let Some(on_response) = send_elicitation(&outgoing, &request_id, &params).await else {
+        return;
+    };
+
+    let approval_id = event.id;
+    tokio::spawn(async move {
+        let decision = match timeout(RESPONSE_TIMEOUT, on_response).await {
+            Ok(Ok(value)) => code_protocol::protocol::ReviewDecision::from_value(&value)
+                .map(ReviewDecision::from)
+                .unwrap_or_else(|| {
+                    error!("failed to deserialize sampling approval (value={value:?}); denying");
+                    ReviewDecision::Denied
+                }),
+            Ok(Err(err)) => {
+                error!("sampling approval request failed: {err:?}");
+                ReviewDecision::Denied
+            }
+            Err(_) => {
+                warn!("sampling approval timed out after {RESPONSE_TIMEOUT:?} (id={approval_id})");
+                ReviewDecision::Denied
+            }
+        };
+        if let Err(err) = codex
+            .submit(Op::McpSamplingApproval {
+                id: approval_id,
+                decision,
+            })
+            .await
+        {
+            error!("failed to submit McpSamplingApproval: {err}");
+        }
+    });
+}
+
+pub(crate) async fn handle_mcp_elicitation_request(
+    event: McpElicitationRequestEvent,
+    outgoing: Arc<OutgoingMessageSender>,
+    codex: Arc<BeaconConversation>,
+    request_id: RequestId,
+    tool_call_id: String,
+    event_id: String,
+) {
+    let params = McpClientElicitRequestParams {
+        message: format!("[{}] {}", event.server, event.message),
+        requested_schema: event.requested_schema,
+        code_elicitation: "mcp-elicitation".to_string(),
+        code_mcp_tool_call_id: tool_call_id,
+        code_event_id: event_id,
+        code_mcp_server: event.server,
+    };
+    let Some(on_response) = send_elicitation(&outgoing, &request_id, &params).await else {
+        return;
+    };
+
+    let elicitation_id = event.id;
+    tokio::spawn(async move {
+        let (action, content) = match timeout(RESPONSE_TIMEOUT, on_response).await {
+            Ok(Ok(value)) => match serde_json::from_value::<ElicitResult>(value) {
+                Ok(result) => (parse_action(&result.action), result.content),
+                Err(err) => {
+                    error!("failed to deserialize elicitation result: {err}; cancelling");
+                    (McpElicitationAction::Cancel, None)
+                }
+            },
+            Ok(Err(err)) => {
+                error!("elicitation request failed: {err:?}");
+                (McpElicitationAction::Cancel, None)
+            }
+            Err(_) => {
+                warn!("elicitation timed out after {RESPONSE_TIMEOUT:?} (id={elicitation_id})");
+                (McpElicitationAction::Cancel, None)
+            }
+        };
+        if let Err(err) = codex
+            .submit(Op::ResolveMcpElicitation {
+                id: elicitation_id,
+                action,
+                content,
+            })
+            .await
+        {
+            error!("failed to submit ResolveMcpElicitation: {err}");
+        }
+    });
+}
+
+async fn send_elicitation(
+    outgoing: &OutgoingMessageSender,
+    request_id: &RequestId,
+    params: &McpClientElicitRequestParams,
+) -> Option<tokio::sync::oneshot::Receiver<mcp_types::Result>> {
+    let params_json = match serde_json::to_value(params) {
+        Ok(value) => value,
+        Err(err) => {
+            let message = format!("Failed to serialize McpClientElicitRequestParams: {err}");
+            error!("{message}");
+            outgoing
+                .send_error(
+                    request_id.clone(),
+                    JSONRPCErrorError {
+                        code: INVALID_PARAMS_ERROR_CODE,
+                        message,
+                        data: None,
+                    },
+                )
+                .await;
+            return None;
+        }
+    };
+    Some(
+        outgoing
+            .send_request(ElicitRequest::METHOD, Some(params_json))
+            .await,
+    )
+}
+
+fn parse_action(action: &str) -> McpElicitationAction {
+    match action {
+        "accept" => McpElicitationAction::Accept,
+        "decline" => McpElicitationAction::Decline,
+        _ => McpElicitationAction::Cancel,
+    }
+}
+
+/// Plain-text excerpt of the sampling request for the approval message.
+fn sampling_preview(event: &McpSamplingRequestEvent) -> String {
+    let text: Vec<&str> = event
+        .messages
+        .iter()
+        .filter_map(|message| match &message.content {
+            SamplingMessageContent::TextContent(text) => Some(text.text.as_str()),
+            SamplingMessageContent::ImageContent(_) | SamplingMessageContent::AudioContent(_) => {
+                None
+            }
+        })
+        .collect();
+    let joined = text.join("\n");
+    if joined.chars().count() <= SAMPLING_PREVIEW_MAX_CHARS {
+        return joined;
+    }
+    let mut preview: String = joined.chars().take(SAMPLING_PREVIEW_MAX_CHARS).collect();
+    preview.push('…');
+    preview
+}
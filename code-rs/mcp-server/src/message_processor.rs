@@ -17,6 +17,8 @@ use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotification;
+use crate::progress_stream::ProgressStream;
+use crate::progress_stream::progress_token_from_params;
 use crate::session_store::SessionMap;
 use agent_client_protocol as acp;
 use anyhow::Context as _;
@@ -54,6 +56,7 @@ use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressToken;
 use mcp_types::RequestId;
 use mcp_types::ServerNotification;
 use mcp_types::TextContent;
@@ -258,6 +261,7 @@ impl MessageProcessor {
             }
         }
 
+        let progress_token = progress_token_from_params(request.params.as_ref());
         let client_request = match McpClientRequest::try_from(request) {
             Ok(client_request) => client_request,
             Err(e) => {
@@ -299,7 +303,8 @@ impl MessageProcessor {
                 self.handle_list_tools(request_id, params).await;
             }
             McpClientRequest::CallToolRequest(params) => {
-                self.handle_call_tool(request_id, params).await;
+                self.handle_call_tool(request_id, params, progress_token)
+                    .await;
             }
             McpClientRequest::SetLevelRequest(params) => {
                 self.handle_set_level(params);
@@ -540,14 +545,18 @@ impl MessageProcessor {
         &self,
         id: RequestId,
         params: <mcp_types::CallToolRequest as mcp_types::ModelContextProtocolRequest>::Params,
+        progress_token: Option<ProgressToken>,
     ) {
         tracing::info!("tools/call -> params: {:?}", params);
         let CallToolRequestParams { name, arguments } = params;
 
         match name.as_str() {
-            "code" | "codex" => self.handle_tool_call_codex(id, arguments).await,
+            "code" | "codex" => {
+                self.handle_tool_call_codex(id, arguments, progress_token)
+                    .await
+            }
             "code-reply" | "codex-reply" => {
-                self.handle_tool_call_code_session_reply(id, arguments)
+                self.handle_tool_call_code_session_reply(id, arguments, progress_token)
                     .await
             }
             _ if name == acp::AGENT_METHOD_NAMES.session_new => {
@@ -574,31 +583,41 @@ impl MessageProcessor {
             }
         }
     }
-    async fn handle_tool_call_codex(&self, id: RequestId, arguments: Option<serde_json::Value>) {
+    async fn handle_tool_call_codex(
+        &self,
+        id: RequestId,
+        arguments: Option<serde_json::Value>,
+        progress_token: Option<ProgressToken>,
+    ) {
+        let mut stream = false;
         let (initial_prompt, config): (String, Config) = match arguments {
             Some(json_val) => match serde_json::from_value::<CodexToolCallParam>(json_val) {
-                Ok(tool_cfg) => match tool_cfg.into_config(self.code_linux_sandbox_exe.clone()) {
-                    Ok(cfg) => cfg,
-                    Err(e) => {
-                        let message =
-                            format!("Failed to load Beacon Code configuration from overrides: {e}");
-                        let result = CallToolResult {
-                            content: vec![ContentBlock::TextContent(TextContent {
-                                r#type: "text".to_owned(),
-                                text: message.clone(),
-                                annotations: None,
-                            })],
-                            is_error: Some(true),
-                            structured_content: Some(json!({
-                                "status": "error",
-                                "error": message,
-                            })),
-                        };
-                        self.send_response::<mcp_types::CallToolRequest>(id, result)
-                            .await;
-                        return;
+                Ok(tool_cfg) => {
+                    stream = tool_cfg.stream.unwrap_or(false);
+                    match tool_cfg.into_config(self.code_linux_sandbox_exe.clone()) {
+                        Ok(cfg) => cfg,
+                        Err(e) => {
+                            let message = format!(
+                                "Failed to load Beacon Code configuration from overrides: {e}"
+                            );
+                            let result = CallToolResult {
+                                content: vec![ContentBlock::TextContent(TextContent {
+                                    r#type: "text".to_owned(),
+                                    text: message.clone(),
+                                    annotations: None,
+                                })],
+                                is_error: Some(true),
+                                structured_content: Some(json!({
+                                    "status": "error",
+                                    "error": message,
+                                })),
+                            };
+                            self.send_response::<mcp_types::CallToolRequest>(id, result)
+                                .await;
+                            return;
+                        }
                     }
-                },
+                }
                 Err(e) => {
                     let message =
                         format!("Failed to parse configuration for Beacon Code tool: {e}");
@@ -659,6 +678,7 @@ impl MessageProcessor {
                 session_map,
                 conversation_manager,
                 running_requests_id_to_code_uuid,
+                ProgressStream::new(stream, progress_token),
             )
             .await;
         });
@@ -668,11 +688,16 @@ impl MessageProcessor {
         &self,
         request_id: RequestId,
         arguments: Option<serde_json::Value>,
+        progress_token: Option<ProgressToken>,
     ) {
         tracing::info!("tools/call -> params: {:?}", arguments);
 
         // parse arguments
-        let CodexToolCallReplyParam { session_id, prompt } = match arguments {
+        let CodexToolCallReplyParam {
+            session_id,
+            prompt,
+            stream,
+        } = match arguments {
             Some(json_val) => match serde_json::from_value::<CodexToolCallReplyParam>(json_val) {
                 Ok(params) => params,
                 Err(e) => {
@@ -780,6 +805,7 @@ impl MessageProcessor {
                 conversation_manager,
                 running_requests_id_to_code_uuid,
                 session_id,
+                ProgressStream::new(stream.unwrap_or(false), progress_token),
             )
             .await;
         });
@@ -2,6 +2,7 @@ use std::future::Future;
 use std::pin::Pin;
 
 use code_core::protocol::Event;
+use mcp_types::ProgressToken;
 use mcp_types::RequestId;
 use serde::Serialize;
 use tracing::warn;
@@ -26,11 +27,24 @@ pub struct OutgoingNotificationParams {
 #[serde(rename_all = "camelCase")]
 pub struct OutgoingNotificationMeta {
     pub request_id: Option<RequestId>,
+
+    /// Progress token of the originating `tools/call`, set on events that
+    /// are also streamed as `notifications/progress`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_token: Option<ProgressToken>,
 }
 
 impl OutgoingNotificationMeta {
     pub fn new(request_id: Option<RequestId>) -> Self {
-        Self { request_id }
+        Self {
+            request_id,
+            progress_token: None,
+        }
+    }
+
+    pub fn with_progress_token(mut self, progress_token: ProgressToken) -> Self {
+        self.progress_token = Some(progress_token);
+        self
     }
 }
 
@@ -137,9 +151,7 @@ mod tests {
             order: None,
             msg: EventMsg::SessionConfigured(session_configured_event.clone()),
         };
-        let meta = OutgoingNotificationMeta {
-            request_id: Some(RequestId::String("123".to_string())),
-        };
+        let meta = OutgoingNotificationMeta::new(Some(RequestId::String("123".to_string())));
 
         outgoing_message_sender
             .send_event_as_notification(&event, Some(meta))
@@ -0,0 +1,227 @@
+//! Opt-in streaming of agent output while a `code` tool-call is running.
+//!
+//! When a tool-call sets `stream`, agent message and reasoning text is
+//! forwarded as MCP `notifications/progress` keyed to the progress token the
+//! client put in the `tools/call` request's `_meta`, and the matching
+//! `codex/event` notifications carry that token in their `_meta` as well.
+
+use code_core::protocol::EventMsg;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::ProgressToken;
+use tracing::warn;
+
+use crate::outgoing_message::OutgoingMessageSender;
+use crate::outgoing_message::OutgoingNotification;
+use crate::outgoing_message::OutgoingNotificationMeta;
+
+const PROGRESS_METHOD: &str = "notifications/progress";
+
+/// Extracts `_meta.progressToken` from raw `tools/call` params.
+pub(crate) fn progress_token_from_params(
+    params: Option<&serde_json::Value>,
+) -> Option<ProgressToken> {
+    let token = params?.get("_meta")?.get("progressToken")?;
+    serde_json::from_value(token.clone()).ok()
+}
+
+/// Per-call streaming state.
+#[derive(Debug, Clone)]
+pub(crate) struct ProgressStream {
+    enabled: bool,
+    token: Option<ProgressToken>,
+    progress: u64,
+    saw_message_delta: bool,
+}
+
+impl ProgressStream {
+    pub(crate) fn new(enabled: bool, token: Option<ProgressToken>) -> Self {
+        Self {
+            enabled,
+            token,
+            progress: 0,
+            saw_message_delta: false,
+        }
+    }
+
+    /// Adds the progress token to the `_meta` of streamed events.
+    pub(crate) fn event_meta(
+        &self,
+        meta: OutgoingNotificationMeta,
+        msg: &EventMsg,
+    ) -> OutgoingNotificationMeta {
+        match &self.token {
+            Some(token) if self.enabled && is_streamed(msg) => {
+                meta.with_progress_token(token.clone())
+            }
+            _ => meta,
+        }
+    }
+
+    /// Sends a progress notification for agent output events. A completed
+    /// agent message is only forwarded when its deltas were not streamed.
+    pub(crate) async fn forward(&mut self, outgoing: &OutgoingMessageSender, msg: &EventMsg) {
+        if !self.enabled {
+            return;
+        }
+        let text = match msg {
+            EventMsg::AgentMessageDelta(ev) => {
+                self.saw_message_delta = true;
+                ev.delta.clone()
+            }
+            EventMsg::AgentReasoningDelta(ev) => ev.delta.clone(),
+            EventMsg::AgentMessage(ev) => {
+                if std::mem::take(&mut self.saw_message_delta) {
+                    return;
+                }
+                ev.message.clone()
+            }
+            _ => return,
+        };
+        let Some(token) = self.token.clone() else {
+            return;
+        };
+
+        self.progress += 1;
+        let params = ProgressNotificationParams {
+            message: Some(text),
+            progress: self.progress as f64,
+            progress_token: token,
+            total: None,
+        };
+        match serde_json::to_value(params) {
+            Ok(params) => {
+                outgoing
+                    .send_notification(OutgoingNotification {
+                        method: PROGRESS_METHOD.to_string(),
+                        params: Some(params),
+                    })
+                    .await;
+            }
+            Err(err) => warn!("failed to serialize progress notification: {err}"),
+        }
+    }
+}
+
+fn is_streamed(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::AgentMessageDelta(_)
+            | EventMsg::AgentReasoningDelta(_)
+            | EventMsg::AgentMessage(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use code_core::protocol::AgentMessageDeltaEvent;
+    use code_core::protocol::AgentMessageEvent;
+    use code_core::protocol::AgentReasoningDeltaEvent;
+    use mcp_types::RequestId;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::outgoing_message::OutgoingMessage;
+
+    fn message_delta(delta: &str) -> EventMsg {
+        EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+            delta: delta.to_string(),
+        })
+    }
+
+    fn agent_message(message: &str) -> EventMsg {
+        EventMsg::AgentMessage(AgentMessageEvent {
+            message: message.to_string(),
+        })
+    }
+
+    #[test]
+    fn reads_progress_token_from_meta() {
+        let params = json!({"name": "code", "_meta": {"progressToken": "tok"}});
+        assert_eq!(
+            progress_token_from_params(Some(&params)),
+            Some(ProgressToken::String("tok".to_string()))
+        );
+        let params = json!({"name": "code", "_meta": {"progressToken": 7}});
+        assert_eq!(
+            progress_token_from_params(Some(&params)),
+            Some(ProgressToken::Integer(7))
+        );
+        assert_eq!(
+            progress_token_from_params(Some(&json!({"name": "code"}))),
+            None
+        );
+        assert_eq!(progress_token_from_params(None), None);
+    }
+
+    #[tokio::test]
+    async fn forwards_deltas_as_progress() {
+        let (tx, mut rx) = mpsc::channel::<OutgoingMessage>(8);
+        let outgoing = OutgoingMessageSender::new(tx);
+        let mut stream = ProgressStream::new(true, Some(ProgressToken::Integer(3)));
+
+        stream.forward(&outgoing, &message_delta("Hel")).await;
+        stream
+            .forward(
+                &outgoing,
+                &EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
+                    delta: "thinking".to_string(),
+                }),
+            )
+            .await;
+        // Already streamed through deltas.
+        stream.forward(&outgoing, &agent_message("Hello")).await;
+        // A message without deltas is forwarded whole.
+        stream.forward(&outgoing, &agent_message("Done")).await;
+        drop(outgoing);
+
+        let mut notifications = Vec::new();
+        while let Some(message) = rx.recv().await {
+            let OutgoingMessage::Notification(OutgoingNotification { method, params }) = message
+            else {
+                panic!("expected notification");
+            };
+            assert_eq!(method, "notifications/progress");
+            notifications.push(params.unwrap());
+        }
+        assert_eq!(
+            notifications,
+            vec![
+                json!({"progressToken": 3, "progress": 1.0, "message": "Hel"}),
+                json!({"progressToken": 3, "progress": 2.0, "message": "thinking"}),
+                json!({"progressToken": 3, "progress": 3.0, "message": "Done"}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn disabled_stream_sends_nothing() {
+        let (tx, mut rx) = mpsc::channel::<OutgoingMessage>(8);
+        let outgoing = OutgoingMessageSender::new(tx);
+        let token = Some(ProgressToken::Integer(3));
+
+        ProgressStream::new(false, token.clone())
+            .forward(&outgoing, &message_delta("Hel"))
+            .await;
+        ProgressStream::new(true, None)
+            .forward(&outgoing, &message_delta("Hel"))
+            .await;
+        drop(outgoing);
+        assert!(rx.recv().await.is_none());
+
+        let meta = OutgoingNotificationMeta::new(Some(RequestId::Integer(1)));
+        assert_eq!(
+            ProgressStream::new(false, token.clone()).event_meta(meta.clone(), &message_delta("x")),
+            meta
+        );
+        assert_eq!(
+            ProgressStream::new(true, token.clone()).event_meta(meta.clone(), &message_delta("x")),
+            meta.clone().with_progress_token(ProgressToken::Integer(3))
+        );
+        assert_eq!(
+            ProgressStream::new(true, token).event_meta(meta.clone(), &EventMsg::TaskStarted),
+            meta
+        );
+    }
+}
@@ -23,6 +23,12 @@ pub fn generate_ts(out_dir: &Path, prettier: Option<&Path>) -> Result<()> {
     code_app_server_protocol::ServerRequest::export_all_to(out_dir)?;
     code_app_server_protocol::NewConversationParams::export_all_to(out_dir)?;
     code_app_server_protocol::NewConversationResponse::export_all_to(out_dir)?;
+    code_app_server_protocol::ForkConversationParams::export_all_to(out_dir)?;
+    code_app_server_protocol::ForkConversationResponse::export_all_to(out_dir)?;
+    code_app_server_protocol::RollbackConversationParams::export_all_to(out_dir)?;
+    code_app_server_protocol::RollbackConversationResponse::export_all_to(out_dir)?;
+    code_app_server_protocol::GetConversationItemsParams::export_all_to(out_dir)?;
+    code_app_server_protocol::GetConversationItemsResponse::export_all_to(out_dir)?;
     code_app_server_protocol::AddConversationListenerParams::export_all_to(out_dir)?;
     code_app_server_protocol::AddConversationSubscriptionResponse::export_all_to(out_dir)?;
     code_app_server_protocol::RemoveConversationListenerParams::export_all_to(out_dir)?;
@@ -47,6 +53,10 @@ pub fn generate_ts(out_dir: &Path, prettier: Option<&Path>) -> Result<()> {
     code_app_server_protocol::ApplyPatchApprovalResponse::export_all_to(out_dir)?;
     code_app_server_protocol::ExecCommandApprovalParams::export_all_to(out_dir)?;
     code_app_server_protocol::ExecCommandApprovalResponse::export_all_to(out_dir)?;
+    code_app_server_protocol::McpSamplingApprovalParams::export_all_to(out_dir)?;
+    code_app_server_protocol::McpSamplingApprovalResponse::export_all_to(out_dir)?;
+    code_app_server_protocol::McpElicitationParams::export_all_to(out_dir)?;
+    code_app_server_protocol::McpElicitationResponse::export_all_to(out_dir)?;
     code_app_server_protocol::ServerNotification::export_all_to(out_dir)?;
     code_app_server_protocol::ClientNotification::export_all_to(out_dir)?;
 
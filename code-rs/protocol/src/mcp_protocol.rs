@@ -6,6 +6,7 @@ use crate::config_types::ReasoningEffort;
 use crate::config_types::ReasoningSummary;
 use crate::config_types::SandboxMode;
 use crate::config_types::Verbosity;
+use crate::models::ResponseItem;
 use crate::protocol::AskForApproval;
 use crate::protocol::EventMsg;
 use crate::protocol::FileChange;
@@ -132,6 +133,24 @@ pub enum ClientRequest {
         request_id: RequestId,
         params: ArchiveConversationParams,
     },
+    /// Start a new conversation from the first turns of a recorded one.
+    ForkConversation {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: ForkConversationParams,
+    },
+    /// Replace a conversation with a copy that drops its last turns.
+    RollbackConversation {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: RollbackConversationParams,
+    },
+    /// Page through the response items recorded for a conversation.
+    GetConversationItems {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: GetConversationItemsParams,
+    },
     SendUserMessage {
         #[serde(rename = "id")]
         request_id: RequestId,
@@ -239,6 +258,11 @@ pub enum ClientRequest {
 #[serde(rename_all = "camelCase")]
 pub struct InitializeParams {
     pub client_info: ClientInfo,
+
+    /// Shared secret for app servers listening on a socket
+    /// (`code app-server --listen`). Ignored on stdio.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_token: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
@@ -379,6 +403,69 @@ pub struct ArchiveConversationParams {
 #[serde(rename_all = "camelCase")]
 pub struct ArchiveConversationResponse {}
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkConversationParams {
+    pub conversation_id: ConversationId,
+    /// Number of user turns to keep. Turns are counted from the start of the
+    /// conversation; a value past the last turn copies the whole conversation.
+    pub at_turn: usize,
+    /// Optional overrides to apply when spawning the forked session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<NewConversationParams>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkConversationResponse {
+    pub conversation_id: ConversationId,
+    pub model: String,
+    pub rollout_path: PathBuf,
+}
+
+/// The original conversation is shut down and replaced by the returned one;
+/// its rollout file is kept on disk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackConversationParams {
+    pub conversation_id: ConversationId,
+    /// Number of user turns to drop from the end of the conversation.
+    pub turns: usize,
+    /// Optional overrides to apply when spawning the rolled-back session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<NewConversationParams>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackConversationResponse {
+    pub conversation_id: ConversationId,
+    pub model: String,
+    pub rollout_path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetConversationItemsParams {
+    pub conversation_id: ConversationId,
+    /// Opaque pagination cursor returned by a previous call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    /// Optional page size; defaults to a reasonable server-side value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetConversationItemsResponse {
+    pub items: Vec<ResponseItem>,
+    /// Opaque cursor to pass to the next call to continue after the last item.
+    /// if None, there are no more items to return.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveConversationSubscriptionResponse {}
@@ -587,6 +674,8 @@ pub struct SandboxSettings {
     pub writable_roots: Vec<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network_access: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network_allowlist: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude_tmpdir_env_var: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -663,12 +752,21 @@ pub enum InputItem {
     LocalImage {
         path: PathBuf,
     },
+
+    /// Resource exposed by a connected MCP server; read and inlined by the
+    /// session before the turn is sent to the model.
+    McpResource {
+        server: String,
+        uri: String,
+    },
 }
 
 // TODO(mbolin): Need test to ensure these constants match the enum variants.
 
 pub const APPLY_PATCH_APPROVAL_METHOD: &str = "applyPatchApproval";
 pub const EXEC_COMMAND_APPROVAL_METHOD: &str = "execCommandApproval";
+pub const MCP_SAMPLING_APPROVAL_METHOD: &str = "mcpSamplingApproval";
+pub const MCP_ELICITATION_METHOD: &str = "mcpElicitation";
 
 /// Request initiated from the server and sent to the client.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -686,6 +784,18 @@ pub enum ServerRequest {
         request_id: RequestId,
         params: ExecCommandApprovalParams,
     },
+    /// Request to let an MCP server run a completion through the session model.
+    McpSamplingApproval {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: McpSamplingApprovalParams,
+    },
+    /// Request to show a form on behalf of an MCP server.
+    McpElicitation {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: McpElicitationParams,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -726,6 +836,47 @@ pub struct ApplyPatchApprovalResponse {
     pub decision: ReviewDecision,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpSamplingApprovalParams {
+    pub conversation_id: ConversationId,
+    /// Name of the MCP server asking for the completion.
+    pub server: String,
+    pub messages: Vec<mcp_types::SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub max_tokens: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpSamplingApprovalResponse {
+    pub decision: ReviewDecision,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpElicitationParams {
+    pub conversation_id: ConversationId,
+    /// Name of the MCP server asking for input.
+    pub server: String,
+    pub message: String,
+    pub requested_schema: mcp_types::ElicitRequestParamsRequestedSchema,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum McpElicitationAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct McpElicitationResponse {
+    pub action: McpElicitationAction,
+    /// Form values keyed by property name; only read when `action` is `accept`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<serde_json::Value>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(rename_all = "camelCase")]
@@ -875,6 +1026,91 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn serialize_conversation_history_requests() -> Result<()> {
+        let conversation_id = ConversationId::default();
+        let request = ClientRequest::ForkConversation {
+            request_id: RequestId::Integer(1),
+            params: ForkConversationParams {
+                conversation_id,
+                at_turn: 2,
+                overrides: None,
+            },
+        };
+        assert_eq!(
+            json!({
+                "method": "forkConversation",
+                "id": 1,
+                "params": {"conversationId": conversation_id, "atTurn": 2}
+            }),
+            serde_json::to_value(&request)?,
+        );
+
+        let request = ClientRequest::RollbackConversation {
+            request_id: RequestId::Integer(2),
+            params: RollbackConversationParams {
+                conversation_id,
+                turns: 1,
+                overrides: None,
+            },
+        };
+        assert_eq!(
+            json!({
+                "method": "rollbackConversation",
+                "id": 2,
+                "params": {"conversationId": conversation_id, "turns": 1}
+            }),
+            serde_json::to_value(&request)?,
+        );
+
+        let request: ClientRequest = serde_json::from_value(json!({
+            "method": "getConversationItems",
+            "id": 3,
+            "params": {"conversationId": conversation_id, "cursor": "20", "limit": 10}
+        }))?;
+        assert_eq!(
+            request,
+            ClientRequest::GetConversationItems {
+                request_id: RequestId::Integer(3),
+                params: GetConversationItemsParams {
+                    conversation_id,
+                    cursor: Some("20".to_string()),
+                    limit: Some(10),
+                },
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_mcp_elicitation_request() -> Result<()> {
+        let conversation_id = ConversationId::default();
+        let request = ServerRequest::McpElicitation {
+            request_id: RequestId::Integer(7),
+            params: McpElicitationParams {
+                conversation_id,
+                server: "github".to_string(),
+                message: "Which repository?".to_string(),
+                requested_schema: mcp_types::ElicitRequestParamsRequestedSchema {
+                    properties: json!({ "repo": { "type": "string" } }),
+                    required: Some(vec!["repo".to_string()]),
+                    r#type: "object".to_string(),
+                },
+            },
+        };
+        let value = serde_json::to_value(&request)?;
+        assert_eq!(value["method"], json!(MCP_ELICITATION_METHOD));
+        assert_eq!(
+            value["params"]["requested_schema"]["required"],
+            json!(["repo"])
+        );
+
+        let response: McpElicitationResponse =
+            serde_json::from_value(json!({ "action": "accept", "content": { "repo": "x" } }))?;
+        assert_eq!(response.action, McpElicitationAction::Accept);
+        Ok(())
+    }
+
     #[test]
     fn test_conversation_id_default_is_not_zeroes() {
         let id = ConversationId::default();
@@ -247,7 +247,17 @@ pub enum SandboxPolicy {
 
     /// Read-only access to the entire file-system.
     #[serde(rename = "read-only")]
-    ReadOnly,
+    ReadOnly {
+        /// Paths or globs (`~/.ssh`, `**/.env*`) that must not be read from
+        /// within the sandbox. Relative entries are resolved against the
+        /// sandbox cwd.
+        ///
+        /// Omitted when empty, so the policy keeps the `{"mode": "read-only"}`
+        /// shape it had before this field existed, and clients may leave it out.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[ts(as = "Option<Vec<String>>", optional)]
+        deny_read: Vec<String>,
+    },
 
     /// Same as `ReadOnly` but additionally grants write access to the current
     /// working directory ("workspace").
@@ -263,6 +273,19 @@ pub enum SandboxPolicy {
         #[serde(default)]
         network_access: bool,
 
+        /// Hosts reachable through the sandbox's filtering proxy when
+        /// `network_access` is `false`. Entries are `host`, `*.domain` or
+        /// `host:port`; without a port only 80 and 443 are allowed. Empty by
+        /// default, which blocks all network access.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        network_allowlist: Vec<String>,
+
+        /// Paths or globs that must not be read from within the sandbox, even
+        /// beneath writable roots. See `ReadOnly::deny_read`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[ts(as = "Option<Vec<String>>", optional)]
+        deny_read: Vec<String>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -327,7 +350,9 @@ impl FromStr for SandboxPolicy {
 impl SandboxPolicy {
     /// Returns a policy with read-only disk access and no network.
     pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
+        SandboxPolicy::ReadOnly {
+            deny_read: Vec::new(),
+        }
     }
 
     /// Returns a policy that can read the entire disk, but can only write to
@@ -337,21 +362,33 @@ impl SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: Vec::new(),
+            deny_read: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
             allow_git_writes: true,
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
+    /// Returns `false` when the policy lists `deny_read` paths.
     pub fn has_full_disk_read_access(&self) -> bool {
-        true
+        self.deny_read().is_empty()
+    }
+
+    /// Paths or globs that must not be read from within the sandbox, as
+    /// configured (unresolved).
+    pub fn deny_read(&self) -> &[String] {
+        match self {
+            SandboxPolicy::DangerFullAccess => &[],
+            SandboxPolicy::ReadOnly { deny_read } => deny_read,
+            SandboxPolicy::WorkspaceWrite { deny_read, .. } => deny_read,
+        }
     }
 
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { .. } => false,
         }
     }
@@ -359,7 +396,7 @@ impl SandboxPolicy {
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
         }
     }
@@ -370,13 +407,15 @@ impl SandboxPolicy {
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
         match self {
             SandboxPolicy::DangerFullAccess => Vec::new(),
-            SandboxPolicy::ReadOnly => Vec::new(),
+            SandboxPolicy::ReadOnly { .. } => Vec::new(),
             SandboxPolicy::WorkspaceWrite {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 allow_git_writes,
                 network_access: _,
+                network_allowlist: _,
+                deny_read: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -1075,6 +1114,11 @@ pub enum RolloutItem {
 #[derive(Serialize, Deserialize, Clone, Debug, TS)]
 pub struct CompactedItem {
     pub message: String,
+    /// History returned by remote compaction. When present it replaces the
+    /// conversation verbatim on resume instead of being rebuilt locally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub replacement_history: Option<Vec<ResponseItem>>,
 }
 
 impl From<CompactedItem> for ResponseItem {
@@ -1452,6 +1496,9 @@ impl ReviewDecision {
 pub enum FileChange {
     Add {
         content: String,
+        /// Unix permission bits the patch sets on the new file.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<u32>,
     },
     Delete {
         content: String,
@@ -1461,6 +1508,9 @@ pub enum FileChange {
         move_path: Option<PathBuf>,
         original_content: String,
         new_content: String,
+        /// Unix permission bits the patch sets on the resulting file.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<u32>,
     },
 }
 
@@ -1525,6 +1575,28 @@ mod tests {
     use serde_json::json;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn read_only_policy_keeps_its_unit_wire_shape() -> Result<()> {
+        let legacy = json!({ "mode": "read-only" });
+        assert_eq!(
+            serde_json::from_value::<SandboxPolicy>(legacy.clone())?,
+            SandboxPolicy::new_read_only_policy()
+        );
+        assert_eq!(
+            serde_json::to_value(SandboxPolicy::new_read_only_policy())?,
+            legacy
+        );
+
+        let with_denials = SandboxPolicy::ReadOnly {
+            deny_read: vec!["~/.ssh".to_string()],
+        };
+        assert_eq!(
+            serde_json::to_value(&with_denials)?,
+            json!({ "mode": "read-only", "deny_read": ["~/.ssh"] })
+        );
+        Ok(())
+    }
+
     /// Serialize Event to verify that its JSON representation has the expected
     /// amount of nesting.
     #[test]
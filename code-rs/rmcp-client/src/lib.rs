@@ -3,3 +3,27 @@ mod rmcp_client;
 mod utils;
 
 pub use rmcp_client::RmcpClient;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Callback that answers a request initiated by the server. It receives the
+/// method name and raw params and resolves to the JSON `result` (or a JSON-RPC
+/// error) that is sent back to the server.
+pub type ServerRequestHandler = Arc<
+    dyn Fn(
+            String,
+            Option<serde_json::Value>,
+        ) -> Pin<
+            Box<
+                dyn Future<Output = Result<serde_json::Value, mcp_types::JSONRPCErrorError>> + Send,
+            >,
+        > + Send
+        + Sync,
+>;
+
+/// Callback invoked for every notification sent by the server that the client
+/// forwards (currently the `notifications/*/list_changed` family), with the
+/// method name and raw params.
+pub type ServerNotificationHandler = Arc<dyn Fn(String, Option<serde_json::Value>) + Send + Sync>;
@@ -1,10 +1,15 @@
+use mcp_types::JSONRPCErrorError;
 use rmcp::ClientHandler;
 use rmcp::RoleClient;
 use rmcp::model::CancelledNotificationParam;
 use rmcp::model::ClientInfo;
 use rmcp::model::CreateElicitationRequestParam;
 use rmcp::model::CreateElicitationResult;
+use rmcp::model::CreateMessageRequestParam;
+use rmcp::model::CreateMessageResult;
 use rmcp::model::ElicitationAction;
+use rmcp::model::ErrorCode;
+use rmcp::model::ListRootsResult;
 use rmcp::model::LoggingLevel;
 use rmcp::model::LoggingMessageNotificationParam;
 use rmcp::model::ProgressNotificationParam;
@@ -16,32 +21,119 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
-#[derive(Debug, Clone)]
+use crate::ServerNotificationHandler;
+use crate::ServerRequestHandler;
+use crate::utils::convert_to_mcp;
+use crate::utils::convert_to_rmcp;
+
+#[derive(Clone)]
 pub(crate) struct LoggingClientHandler {
     client_info: ClientInfo,
+    server_request_handler: Option<ServerRequestHandler>,
+    server_notification_handler: Option<ServerNotificationHandler>,
+}
+
+impl std::fmt::Debug for LoggingClientHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoggingClientHandler")
+            .field("client_info", &self.client_info)
+            .field(
+                "server_request_handler",
+                &self.server_request_handler.is_some(),
+            )
+            .field(
+                "server_notification_handler",
+                &self.server_notification_handler.is_some(),
+            )
+            .finish()
+    }
 }
 
 impl LoggingClientHandler {
-    pub(crate) fn new(client_info: ClientInfo) -> Self {
-        Self { client_info }
+    pub(crate) fn new(
+        client_info: ClientInfo,
+        server_request_handler: Option<ServerRequestHandler>,
+        server_notification_handler: Option<ServerNotificationHandler>,
+    ) -> Self {
+        Self {
+            client_info,
+            server_request_handler,
+            server_notification_handler,
+        }
+    }
+
+    fn notify(&self, method: &str) {
+        if let Some(handler) = &self.server_notification_handler {
+            handler(method.to_string(), None);
+        }
+    }
+
+    /// Forward a server-initiated request to the installed handler, converting
+    /// between rmcp types and the JSON shapes the handler speaks.
+    async fn forward<P, R>(&self, method: &str, params: Option<P>) -> Result<R, rmcp::ErrorData>
+    where
+        P: serde::Serialize,
+        R: serde::de::DeserializeOwned,
+    {
+        let Some(handler) = self.server_request_handler.clone() else {
+            return Err(rmcp::ErrorData::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                format!("client does not handle `{method}`"),
+                None,
+            ));
+        };
+        let params = params
+            .map(convert_to_mcp::<_, serde_json::Value>)
+            .transpose()
+            .map_err(|err| rmcp::ErrorData::invalid_params(err.to_string(), None))?;
+        match handler(method.to_string(), params).await {
+            Ok(result) => convert_to_rmcp(result)
+                .map_err(|err| rmcp::ErrorData::internal_error(err.to_string(), None)),
+            Err(JSONRPCErrorError {
+                code,
+                message,
+                data,
+            }) => Err(rmcp::ErrorData::new(ErrorCode(code as i32), message, data)),
+        }
     }
 }
 
 impl ClientHandler for LoggingClientHandler {
-    // TODO (CODEX-3571): support elicitations.
     async fn create_elicitation(
         &self,
         request: CreateElicitationRequestParam,
         _context: RequestContext<RoleClient>,
     ) -> Result<CreateElicitationResult, rmcp::ErrorData> {
-        info!(
-            "MCP server requested elicitation ({}). Elicitations are not supported yet. Declining.",
-            request.message
-        );
-        Ok(CreateElicitationResult {
-            action: ElicitationAction::Decline,
-            content: None,
-        })
+        if self.server_request_handler.is_none() {
+            info!(
+                "MCP server requested elicitation ({}) but no handler is installed. Declining.",
+                request.message
+            );
+            return Ok(CreateElicitationResult {
+                action: ElicitationAction::Decline,
+                content: None,
+            });
+        }
+        self.forward("elicitation/create", Some(request)).await
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::ErrorData> {
+        self.forward("sampling/createMessage", Some(params)).await
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, rmcp::ErrorData> {
+        if self.server_request_handler.is_none() {
+            return Ok(ListRootsResult::default());
+        }
+        self.forward::<serde_json::Value, _>("roots/list", None)
+            .await
     }
 
     async fn on_cancelled(
@@ -76,14 +168,17 @@ impl ClientHandler for LoggingClientHandler {
 
     async fn on_resource_list_changed(&self, _context: NotificationContext<RoleClient>) {
         info!("MCP server resource list changed");
+        self.notify("notifications/resources/list_changed");
     }
 
     async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
         info!("MCP server tool list changed");
+        self.notify("notifications/tools/list_changed");
     }
 
     async fn on_prompt_list_changed(&self, _context: NotificationContext<RoleClient>) {
         info!("MCP server prompt list changed");
+        self.notify("notifications/prompts/list_changed");
     }
 
     fn get_info(&self) -> ClientInfo {
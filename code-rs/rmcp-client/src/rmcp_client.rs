@@ -10,14 +10,26 @@ use anyhow::anyhow;
 use futures::FutureExt;
 use mcp_types::CallToolRequestParams;
 use mcp_types::CallToolResult;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializeResult;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourceTemplatesRequestParams;
+use mcp_types::ListResourceTemplatesResult;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::MCP_SCHEMA_VERSION;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use rmcp::model::CallToolRequestParam;
+use rmcp::model::GetPromptRequestParam;
 use rmcp::model::InitializeRequestParam;
 use rmcp::model::PaginatedRequestParam;
+use rmcp::model::ReadResourceRequestParam;
 use rmcp::service::RoleClient;
 use rmcp::service::RunningService;
 use rmcp::service::{self};
@@ -32,6 +44,8 @@ use tokio::time;
 use tracing::info;
 use tracing::warn;
 
+use crate::ServerNotificationHandler;
+use crate::ServerRequestHandler;
 use crate::logging_client_handler::LoggingClientHandler;
 use crate::utils::convert_call_tool_result;
 use crate::utils::convert_to_mcp;
@@ -57,6 +71,8 @@ enum ClientState {
 /// https://github.com/modelcontextprotocol/rust-sdk
 pub struct RmcpClient {
     state: Mutex<ClientState>,
+    server_request_handler: std::sync::Mutex<Option<ServerRequestHandler>>,
+    server_notification_handler: std::sync::Mutex<Option<ServerNotificationHandler>>,
 }
 
 impl RmcpClient {
@@ -101,6 +117,8 @@ impl RmcpClient {
             state: Mutex::new(ClientState::Connecting {
                 transport: Some(PendingTransport::ChildProcess(transport)),
             }),
+            server_request_handler: std::sync::Mutex::new(None),
+            server_notification_handler: std::sync::Mutex::new(None),
         })
     }
 
@@ -116,9 +134,29 @@ impl RmcpClient {
             state: Mutex::new(ClientState::Connecting {
                 transport: Some(PendingTransport::StreamableHttp(transport)),
             }),
+            server_request_handler: std::sync::Mutex::new(None),
+            server_notification_handler: std::sync::Mutex::new(None),
         })
     }
 
+    /// Install the handler used to answer requests initiated by the server
+    /// (`roots/list`, `sampling/createMessage`, `elicitation/create`). Must be
+    /// called before [`initialize`](Self::initialize) to take effect.
+    pub fn set_server_request_handler(&self, handler: ServerRequestHandler) {
+        if let Ok(mut guard) = self.server_request_handler.lock() {
+            *guard = Some(handler);
+        }
+    }
+
+    /// Install the handler that receives `list_changed` notifications from the
+    /// server. Must be called before [`initialize`](Self::initialize) to take
+    /// effect.
+    pub fn set_server_notification_handler(&self, handler: ServerNotificationHandler) {
+        if let Ok(mut guard) = self.server_notification_handler.lock() {
+            *guard = Some(handler);
+        }
+    }
+
     /// Perform the initialization handshake with the MCP server.
     /// https://modelcontextprotocol.io/specification/2025-06-18/basic/lifecycle#initialization
     pub async fn initialize(
@@ -139,7 +177,21 @@ impl RmcpClient {
         };
 
         let client_info = convert_to_rmcp::<_, InitializeRequestParam>(params.clone())?;
-        let client_handler = LoggingClientHandler::new(client_info);
+        let server_request_handler = self
+            .server_request_handler
+            .lock()
+            .ok()
+            .and_then(|guard| guard.clone());
+        let server_notification_handler = self
+            .server_notification_handler
+            .lock()
+            .ok()
+            .and_then(|guard| guard.clone());
+        let client_handler = LoggingClientHandler::new(
+            client_info,
+            server_request_handler,
+            server_notification_handler,
+        );
         let service_future = match transport {
             PendingTransport::ChildProcess(transport) => {
                 service::serve_client(client_handler.clone(), transport).boxed()
@@ -213,6 +265,78 @@ impl RmcpClient {
         convert_call_tool_result(rmcp_result)
     }
 
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        let service = self.service().await?;
+        let rmcp_params = params
+            .map(convert_to_rmcp::<_, PaginatedRequestParam>)
+            .transpose()?;
+
+        let fut = service.list_resources(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "resources/list").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn list_resource_templates(
+        &self,
+        params: Option<ListResourceTemplatesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourceTemplatesResult> {
+        let service = self.service().await?;
+        let rmcp_params = params
+            .map(convert_to_rmcp::<_, PaginatedRequestParam>)
+            .transpose()?;
+
+        let fut = service.list_resource_templates(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "resources/templates/list").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let service = self.service().await?;
+        let params = ReadResourceRequestParams { uri };
+        let rmcp_params: ReadResourceRequestParam = convert_to_rmcp(params)?;
+        let fut = service.read_resource(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "resources/read").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        let service = self.service().await?;
+        let rmcp_params = params
+            .map(convert_to_rmcp::<_, PaginatedRequestParam>)
+            .transpose()?;
+
+        let fut = service.list_prompts(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "prompts/list").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let service = self.service().await?;
+        let params = GetPromptRequestParams { arguments, name };
+        let rmcp_params: GetPromptRequestParam = convert_to_rmcp(params)?;
+        let fut = service.get_prompt(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "prompts/get").await?;
+        convert_to_mcp(result)
+    }
+
     async fn service(&self) -> Result<Arc<RunningService<RoleClient, LoggingClientHandler>>> {
         let guard = self.state.lock().await;
         match &*guard {
@@ -221,6 +345,16 @@ impl RmcpClient {
         }
     }
 
+    /// Returns `true` once the transport to an initialized server has closed,
+    /// e.g. because the stdio child exited or the HTTP session ended.
+    pub async fn is_closed(&self) -> bool {
+        let guard = self.state.lock().await;
+        match &*guard {
+            ClientState::Ready { service } => service.is_transport_closed(),
+            ClientState::Connecting { .. } => false,
+        }
+    }
+
     pub async fn shutdown(&self) {
         if let Ok(service) = self.service().await {
             service.cancellation_token().cancel();
@@ -13,6 +13,8 @@ use crate::get_git_diff::get_git_diff;
 use crate::get_login_status;
 use crate::history::state::HistorySnapshot;
 use crate::history_cell;
+use crate::keymap::Keymap;
+use crate::keymap::KeymapAction;
 use crate::onboarding::onboarding_screen::KeyboardHandler;
 use crate::onboarding::onboarding_screen::OnboardingScreen;
 use crate::onboarding::onboarding_screen::OnboardingScreenArgs;
@@ -26,6 +28,7 @@ use code_core::ConversationManager;
 use code_core::config::Config;
 use code_core::config::add_project_allowed_command;
 use code_core::config_types::Notifications;
+use code_core::config_types::TuiNotificationMethod;
 use code_core::protocol::Event;
 use code_core::protocol::Op;
 use code_core::protocol::SandboxPolicy;
@@ -212,6 +215,9 @@ pub(crate) struct App<'a> {
     /// Config is stored here so we can recreate ChatWidgets as needed.
     config: Config,
 
+    /// Global shortcuts from `[tui.keymap]`.
+    keymap: Keymap,
+
     /// Latest available release version (if detected) so new widgets can surface it.
     latest_upgrade_version: Option<String>,
 
@@ -537,6 +543,7 @@ impl App<'_> {
 
         let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.clone());
         let start_in_alt = config.tui.alternate_screen;
+        let keymap = Keymap::from_config(&config.tui.keymap);
         Self {
             _server: conversation_manager,
             app_event_tx,
@@ -544,6 +551,7 @@ impl App<'_> {
             app_event_rx_bulk,
             app_state,
             config,
+            keymap,
             latest_upgrade_version,
             file_search,
             pending_redraw,
@@ -649,6 +657,35 @@ impl App<'_> {
 
     fn emit_osc9_notification(message: &str) {
         let payload = format!("\u{1b}]9;{message}\u{7}");
+        Self::write_terminal_notification(&payload);
+    }
+
+    fn emit_osc777_notification(title: &str, body: Option<&str>) {
+        if let Some(payload) = Self::osc777_notification(title, body) {
+            Self::write_terminal_notification(&payload);
+        }
+    }
+
+    /// `OSC 777 ; notify ; title ; body` keeps the title separate. The title
+    /// cannot contain `;`, which separates the fields.
+    fn osc777_notification(title: &str, body: Option<&str>) -> Option<String> {
+        let title = Self::sanitize_notification_text(title).replace(';', ",");
+        let Some(body) = Self::format_notification_message("", body) else {
+            return (!title.is_empty()).then(|| format!("\u{1b}]777;notify;{title};\u{7}"));
+        };
+        let title = if title.is_empty() {
+            "Code".to_string()
+        } else {
+            title
+        };
+        Some(format!("\u{1b}]777;notify;{title};{body}\u{7}"))
+    }
+
+    fn emit_terminal_bell() {
+        Self::write_terminal_notification("\u{7}");
+    }
+
+    fn write_terminal_notification(payload: &str) {
         let mut stdout = std::io::stdout();
         let _ = stdout.write_all(payload.as_bytes());
         let _ = stdout.flush();
@@ -1536,19 +1573,12 @@ impl App<'_> {
                                 }
                             }
                         }
-                        KeyEvent {
-                            code: KeyCode::Char('r'),
-                            modifiers: crossterm::event::KeyModifiers::CONTROL,
-                            kind: KeyEventKind::Press,
-                            ..
-                        }
-                        | KeyEvent {
-                            code: KeyCode::Char('r'),
-                            modifiers: crossterm::event::KeyModifiers::CONTROL,
-                            kind: KeyEventKind::Repeat,
-                            ..
-                        } => {
-                            // Toggle reasoning/thinking visibility (Ctrl+R)
+                        key_event
+                            if self
+                                .keymap
+                                .matches(KeymapAction::ToggleReasoning, &key_event) =>
+                        {
+                            // Toggle reasoning/thinking visibility (Alt+R by default)
                             match &mut self.app_state {
                                 AppState::Chat { widget } => {
                                     widget.toggle_reasoning_visibility();
@@ -2115,6 +2145,9 @@ impl App<'_> {
                                 widget.handle_merge_command();
                             }
                         }
+                        SlashCommand::Worktrees => {
+                            self.app_event_tx.send(AppEvent::StartWorktreePicker);
+                        }
                         SlashCommand::Push => {
                             if let AppState::Chat { widget } = &mut self.app_state {
                                 widget.handle_push_command();
@@ -2354,6 +2387,7 @@ impl App<'_> {
                                                 PathBuf::from("/tmp/test.txt"),
                                                 FileChange::Add {
                                                     content: "test".to_string(),
+                                                    mode: None,
                                                 },
                                             ),
                                             (
@@ -2363,6 +2397,7 @@ impl App<'_> {
                                                     move_path: None,
                                                     original_content: "test2".to_string(),
                                                     new_content: "test".to_string(),
+                                                    mode: None,
                                                 },
                                             ),
                                         ]),
@@ -2392,6 +2427,16 @@ impl App<'_> {
                         widget.handle_resume_picker_load_failed(message);
                     }
                 }
+                AppEvent::ResumePickerSearch { query } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.search_resume_picker(query);
+                    }
+                }
+                AppEvent::ResumePickerSearchResults { query, candidates } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.present_resume_search_results(query, candidates);
+                    }
+                }
                 AppEvent::ResumeFrom(path) => {
                     // Replace the current chat widget with a new one configured to resume
                     let mut cfg = self.config.clone();
@@ -2494,10 +2539,18 @@ impl App<'_> {
                     self.apply_terminal_title();
                 }
                 AppEvent::EmitTuiNotification { title, body } => {
-                    if let Some(message) =
-                        Self::format_notification_message(&title, body.as_deref())
-                    {
-                        Self::emit_osc9_notification(&message);
+                    match self.config.tui.notification_method {
+                        TuiNotificationMethod::Osc9 => {
+                            if let Some(message) =
+                                Self::format_notification_message(&title, body.as_deref())
+                            {
+                                Self::emit_osc9_notification(&message);
+                            }
+                        }
+                        TuiNotificationMethod::Osc777 => {
+                            Self::emit_osc777_notification(&title, body.as_deref());
+                        }
+                        TuiNotificationMethod::Bell => Self::emit_terminal_bell(),
                     }
                 }
                 AppEvent::UpdateMcpServer { name, enable } => {
@@ -2505,6 +2558,11 @@ impl App<'_> {
                         widget.toggle_mcp_server(&name, enable);
                     }
                 }
+                AppEvent::RestartMcpServer { name } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.restart_mcp_server(&name);
+                    }
+                }
                 AppEvent::UpdateSubagentCommand(cmd) => {
                     if let AppState::Chat { widget } = &mut self.app_state {
                         widget.apply_subagent_update(cmd);
@@ -2830,6 +2888,28 @@ impl App<'_> {
                         widget.present_review_branch_picker(current_branch, branches);
                     }
                 }
+                AppEvent::StartWorktreePicker => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.show_worktree_loading();
+                    }
+                    let cwd = self.config.cwd.clone();
+                    let tx = self.app_event_tx.clone();
+                    tokio::spawn(async move {
+                        let result =
+                            match code_core::git_info::resolve_root_git_project_for_trust(&cwd) {
+                                Some(git_root) => {
+                                    code_core::git_worktree::list_branch_worktrees(&git_root).await
+                                }
+                                None => Err("not a git repository".to_string()),
+                            };
+                        tx.send(AppEvent::PresentWorktreePicker { result });
+                    });
+                }
+                AppEvent::PresentWorktreePicker { result } => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.present_worktree_picker(result);
+                    }
+                }
                 AppEvent::DiffResult(text) => {
                     if let AppState::Chat { widget } = &mut self.app_state {
                         widget.add_diff_output(text);
@@ -3828,3 +3908,26 @@ impl TimingStats {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn osc777_notification_separates_title_and_body() {
+        assert_eq!(
+            App::osc777_notification("Turn complete", Some("All tests\npass")),
+            Some("\u{1b}]777;notify;Turn complete;All tests pass\u{7}".to_string())
+        );
+        assert_eq!(
+            App::osc777_notification("a;b", None),
+            Some("\u{1b}]777;notify;a,b;\u{7}".to_string())
+        );
+        assert_eq!(
+            App::osc777_notification("", Some("body")),
+            Some("\u{1b}]777;notify;Code;body\u{7}".to_string())
+        );
+        assert_eq!(App::osc777_notification("\u{7}", Some("")), None);
+    }
+}
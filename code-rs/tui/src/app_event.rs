@@ -256,6 +256,17 @@ pub(crate) enum AppEvent {
         message: String,
     },
 
+    /// Full-text search typed into the resume picker
+    ResumePickerSearch {
+        query: String,
+    },
+
+    /// Results for a resume picker search
+    ResumePickerSearchResults {
+        query: String,
+        candidates: Vec<ResumeCandidate>,
+    },
+
     /// Signal that agents are about to start (triggered when /plan, /solve, /code commands are entered)
     PrepareAgents,
 
@@ -332,6 +343,11 @@ pub(crate) enum AppEvent {
         enable: bool,
     },
 
+    /// Restart a running MCP server and re-list its tools
+    RestartMcpServer {
+        name: String,
+    },
+
     /// Prefill the composer input with the given text
     #[allow(dead_code)]
     PrefillComposer(String),
@@ -374,6 +390,13 @@ pub(crate) enum AppEvent {
         branches: Vec<String>,
     },
 
+    /// Open the `/worktrees` picker and load branch worktrees in the background.
+    StartWorktreePicker,
+    /// Populate the `/worktrees` picker once worktree state has been loaded.
+    PresentWorktreePicker {
+        result: Result<Vec<code_core::git_worktree::BranchWorktree>, String>,
+    },
+
     /// Show the multi-line prompt input to collect custom review instructions.
     OpenReviewCustomPrompt,
 
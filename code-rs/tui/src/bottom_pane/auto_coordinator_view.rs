@@ -76,6 +76,7 @@ pub(crate) struct AutoActiveViewModel {
     pub status_sent_to_user: Option<String>,
     pub status_title: Option<String>,
     pub session_tokens: Option<u64>,
+    pub session_cost_usd: Option<f64>,
     pub intro_started_at: Option<Instant>,
     pub intro_reduced_motion: bool,
     // Enhanced Auto Drive fields
@@ -467,6 +468,9 @@ impl AutoCoordinatorView {
         if let Some(tokens) = model.session_tokens {
             details.push(Self::format_tokens(tokens));
         }
+        if let Some(cost) = model.session_cost_usd {
+            details.push(Self::format_cost(cost));
+        }
         if model.turns_completed > 0 {
             details.push(Self::format_turns(model.turns_completed));
         }
@@ -1545,6 +1549,10 @@ impl AutoCoordinatorView {
         }
     }
 
+    fn format_cost(cost_usd: f64) -> String {
+        format!("${cost_usd:.2}")
+    }
+
     fn status_labels(model: &AutoActiveViewModel) -> (Option<String>, Option<String>) {
         let sent_to_user = model.status_sent_to_user.as_ref().and_then(|value| {
             let trimmed = value.trim();
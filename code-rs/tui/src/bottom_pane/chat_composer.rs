@@ -1,3 +1,4 @@
+use code_core::config_types::EditingMode;
 use code_core::protocol::TokenUsage;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -21,9 +22,12 @@ use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::WidgetRef;
 
 use super::chat_composer_history::ChatComposerHistory;
+use super::chat_composer_history::HistorySearch;
 use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
 use super::file_search_popup::FileSearchPopup;
+use super::mcp_catalog::McpCatalog;
+use super::mcp_catalog::McpPromptEntry;
 use super::paste_burst::PasteBurst;
 use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
@@ -35,12 +39,16 @@ use crate::auto_drive_style::BorderGradient;
 use crate::auto_drive_style::ComposerStyle;
 use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
+use crate::bottom_pane::textarea::ViMode;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::paste_image_to_temp_png;
 use crate::clipboard_paste::try_decode_base64_image_to_temp_png;
+use crate::keymap::Keymap;
+use crate::keymap::KeymapAction;
 use crate::thread_spawner;
 use code_file_search::FileMatch;
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
@@ -149,6 +157,7 @@ pub(crate) struct ChatComposer {
     animation_running: Option<Arc<AtomicBool>>,
     using_chatgpt_auth: bool,
     custom_prompts: Vec<CustomPrompt>,
+    mcp_catalog: McpCatalog,
     // Ephemeral footer notice and its expiry
     footer_notice: Option<(String, std::time::Instant)>,
     // Persistent hint for specific modes (e.g., standard terminal mode)
@@ -173,6 +182,7 @@ pub(crate) struct ChatComposer {
     render_mode: ComposerRenderMode,
     auto_drive_active: bool,
     auto_drive_style: Option<ComposerStyle>,
+    keymap: Keymap,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -219,6 +229,7 @@ impl ChatComposer {
             animation_running: None,
             using_chatgpt_auth,
             custom_prompts: Vec::new(),
+            mcp_catalog: McpCatalog::default(),
             footer_notice: None,
             standard_terminal_hint: None,
             access_mode_label: None,
@@ -235,9 +246,28 @@ impl ChatComposer {
             render_mode: ComposerRenderMode::Full,
             auto_drive_active: false,
             auto_drive_style: None,
+            keymap: Keymap::default(),
         }
     }
 
+    pub(crate) fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    pub(crate) fn set_editing_mode(&mut self, mode: EditingMode) {
+        self.textarea.set_editing_mode(mode);
+    }
+
+    /// True when Esc should return the vi-mode textarea to normal mode
+    /// rather than fall through to the global Esc policy.
+    pub(crate) fn vi_wants_escape(&self) -> bool {
+        self.has_focus && self.textarea.vi_wants_escape()
+    }
+
+    pub(crate) fn vi_escape(&mut self) -> bool {
+        self.textarea.vi_escape()
+    }
+
     pub fn set_using_chatgpt_auth(&mut self, using: bool) {
         self.using_chatgpt_auth = using;
     }
@@ -631,6 +661,11 @@ impl ChatComposer {
         self.history.set_metadata(log_id, entry_count);
     }
 
+    /// Repository root that Ctrl+R search can be restricted to.
+    pub(crate) fn set_history_repo_root(&mut self, repo_root: Option<PathBuf>) {
+        self.history.set_repo_root(repo_root);
+    }
+
     /// Integrate an asynchronous response to an on-demand history lookup. If
     /// the entry is present and the offset matches the current cursor we
     /// immediately populate the textarea.
@@ -650,6 +685,7 @@ impl ChatComposer {
     }
 
     pub fn handle_paste(&mut self, pasted: String) -> bool {
+        self.accept_history_search();
         self.post_paste_space_guard = None;
         let char_count = pasted.chars().count();
         // If the pasted text looks like a base64/data-URI image, decode it and insert as a path.
@@ -817,6 +853,16 @@ impl ChatComposer {
 
     /// Integrate results from an asynchronous file search.
     pub(crate) fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
+        // MCP resources share the `@` popup; list them ahead of file hits.
+        let mut matches = matches;
+        if !query.is_empty() {
+            let mut resources = self.mcp_catalog.resource_matches(&query);
+            if !resources.is_empty() {
+                resources.append(&mut matches);
+                matches = resources;
+            }
+        }
+
         // Handle one-off Tab-triggered case first: only open if matches exist.
         if self.pending_tab_file_query.as_ref() == Some(&query) {
             // If the user kept typing while the search was in-flight, resync to the
@@ -950,6 +996,12 @@ impl ChatComposer {
 
     /// Handle a key event coming from the main UI.
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        if self.history.search().is_some()
+            && let Some(result) = self.handle_history_search_key(key_event)
+        {
+            return result;
+        }
+
         let now = Instant::now();
 
         // Track rapid plain-character bursts (common when bracketed paste is
@@ -1133,6 +1185,15 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            if let Some(entry) = popup.mcp_prompt(idx) {
+                                let command = format!("/{}", entry.command_name());
+                                if !first_line.trim_start().starts_with(&command) {
+                                    self.textarea
+                                        .set_text(&Self::mcp_prompt_command_text(entry));
+                                }
+                            }
+                        }
                     }
                     // After completing, place the cursor at the end of the
                     // slash command so the user can immediately type args.
@@ -1193,6 +1254,35 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            let Some(entry) = popup.mcp_prompt(idx) else {
+                                return (InputResult::None, true);
+                            };
+                            let command = format!("/{}", entry.command_name());
+                            let typed = command_text
+                                .lines()
+                                .next()
+                                .unwrap_or("")
+                                .trim_start()
+                                .starts_with(&command);
+                            if typed || entry.required_arguments().is_empty() {
+                                if !typed {
+                                    self.textarea.set_text(&command);
+                                }
+                                self.active_popup = ActivePopup::None;
+                                return self.handle_key_event_without_popup(KeyEvent::new(
+                                    KeyCode::Enter,
+                                    KeyModifiers::NONE,
+                                ));
+                            }
+                            // Required arguments: fill in `name=""` slots and
+                            // leave the cursor in the first one.
+                            let text = Self::mcp_prompt_command_text(entry);
+                            let cursor = text.find("=\"").map_or(text.len(), |i| i + 2);
+                            self.textarea.set_text(&text);
+                            self.textarea.set_cursor(cursor);
+                            return (InputResult::None, true);
+                        }
                     }
                 }
                 self.handle_key_event_without_popup(key_event)
@@ -1586,42 +1676,6 @@ impl ChatComposer {
                 (InputResult::None, true)
             }
             // -------------------------------------------------------------
-            // Tab-press file search when not using @ or ./ and not in slash cmd
-            // -------------------------------------------------------------
-            KeyEvent {
-                code: KeyCode::Tab, ..
-            } => {
-                // Suppress Tab completion only while the cursor is within the
-                // slash command head (before the first space). Allow Tab-based
-                // file search in the arguments of /plan, /solve, etc.
-                if self.is_cursor_in_slash_command_head() {
-                    return (InputResult::None, false);
-                }
-
-                // If already showing a file popup, let the dedicated handler manage Tab.
-                if matches!(self.active_popup, ActivePopup::File(_)) {
-                    return (InputResult::None, false);
-                }
-
-                // If an @ token is present or token starts with ./, rely on auto-popup.
-                if Self::current_completion_token(&self.textarea).is_some() {
-                    return (InputResult::None, false);
-                }
-
-                // Use the generic token under cursor for a one-off search.
-                if let Some(tok) = Self::current_generic_token(&self.textarea)
-                    && !tok.is_empty()
-                {
-                    self.pending_tab_file_query = Some(tok.clone());
-                    self.app_event_tx
-                        .send(crate::app_event::AppEvent::StartFileSearch(tok));
-                    // Do not show a popup yet; wait for results and only
-                    // show if there are matches to avoid flicker.
-                    return (InputResult::None, true);
-                }
-                (InputResult::None, false)
-            }
-            // -------------------------------------------------------------
             // Handle Esc key — leave to App-level policy (clear/stop/backtrack)
             // -------------------------------------------------------------
             KeyEvent {
@@ -1630,143 +1684,309 @@ impl ChatComposer {
                 // Do nothing here so App can implement global Esc ordering.
                 (InputResult::None, false)
             }
-            // -------------------------------------------------------------
-            // Up/Down key handling - check modifiers to determine action
-            // -------------------------------------------------------------
-            KeyEvent {
-                code: KeyCode::Up | KeyCode::Down,
-                modifiers,
-                ..
-            } => {
-                // Check if Shift is held for history navigation
-                if modifiers.contains(KeyModifiers::SHIFT) {
-                    // History navigation with Shift+Up/Down
-                    if self
-                        .history
-                        .should_handle_navigation(self.textarea.text(), self.textarea.cursor())
-                    {
-                        let replace_text = match key_event.code {
-                            KeyCode::Up => self
-                                .history
-                                .navigate_up(self.textarea.text(), &self.app_event_tx),
-                            KeyCode::Down => self.history.navigate_down(&self.app_event_tx),
-                            _ => None,
-                        };
-                        if let Some(text) = replace_text {
-                            self.textarea.set_text(&text);
-                            self.textarea.set_cursor(0);
-                            return (InputResult::None, true);
-                        }
-                    }
-                    // If history navigation didn't happen, just ignore the key
-                    (InputResult::None, false)
-                } else {
-                    // No Shift modifier — move cursor within the input first.
-                    // Only when already at the top-left/bottom-right should Up/Down scroll chat.
-                    if self.textarea.is_empty() {
-                        return match key_event.code {
-                            KeyCode::Up => (InputResult::ScrollUp, false),
-                            KeyCode::Down => (InputResult::ScrollDown, false),
-                            _ => (InputResult::None, false),
-                        };
-                    }
+            input => match self.keymap.action_for(&input) {
+                Some(action) => self.handle_keymap_action(action, input),
+                None => match input.code {
+                    KeyCode::Up | KeyCode::Down => self.handle_vertical_arrow(input),
+                    _ => self.handle_input_basic(input),
+                },
+            },
+        }
+    }
 
-                    let before = self.textarea.cursor();
-                    let len = self.textarea.text().len();
-                    match key_event.code {
-                        KeyCode::Up => {
-                            if before == 0 {
-                                (InputResult::ScrollUp, false)
-                            } else {
-                                // Move up a visual/logical line; if already on first line, TextArea moves to start.
-                                self.textarea
-                                    .input(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
-                                (InputResult::None, true)
-                            }
-                        }
-                        KeyCode::Down => {
-                            // If sticky is set, prefer chat ScrollDown once
-                            if self.next_down_scrolls_history {
-                                self.next_down_scrolls_history = false;
-                                return (InputResult::ScrollDown, false);
-                            }
-                            if before == len {
-                                (InputResult::ScrollDown, false)
-                            } else {
-                                // Move down a visual/logical line; if already on last line, TextArea moves to end.
-                                self.textarea
-                                    .input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
-                                (InputResult::None, true)
-                            }
-                        }
-                        _ => (InputResult::None, false),
-                    }
+    /// Runs a composer action from `[tui.keymap]`. Actions handled elsewhere
+    /// (reasoning toggle) fall back to plain input.
+    fn handle_keymap_action(
+        &mut self,
+        action: KeymapAction,
+        key_event: KeyEvent,
+    ) -> (InputResult, bool) {
+        match action {
+            KeymapAction::Submit => self.submit_input(),
+            KeymapAction::Newline => {
+                self.textarea.insert_str("\n");
+                self.history.reset_navigation();
+                self.typed_anything = true;
+                (InputResult::None, true)
+            }
+            KeymapAction::HistoryUp => self.navigate_history(true),
+            KeymapAction::HistoryDown => self.navigate_history(false),
+            KeymapAction::FileSearch => self.start_tab_file_search(),
+            KeymapAction::HistorySearch => {
+                self.history.start_search(self.textarea.text());
+                (InputResult::None, true)
+            }
+            KeymapAction::ToggleReasoning => self.handle_input_basic(key_event),
+        }
+    }
+
+    /// Tab-press file search when not using @ or ./ and not in slash cmd.
+    fn start_tab_file_search(&mut self) -> (InputResult, bool) {
+        // Suppress Tab completion only while the cursor is within the
+        // slash command head (before the first space). Allow Tab-based
+        // file search in the arguments of /plan, /solve, etc.
+        if self.is_cursor_in_slash_command_head() {
+            return (InputResult::None, false);
+        }
+
+        // If already showing a file popup, let the dedicated handler manage Tab.
+        if matches!(self.active_popup, ActivePopup::File(_)) {
+            return (InputResult::None, false);
+        }
+
+        // If an @ token is present or token starts with ./, rely on auto-popup.
+        if Self::current_completion_token(&self.textarea).is_some() {
+            return (InputResult::None, false);
+        }
+
+        // Use the generic token under cursor for a one-off search.
+        if let Some(tok) = Self::current_generic_token(&self.textarea)
+            && !tok.is_empty()
+        {
+            self.pending_tab_file_query = Some(tok.clone());
+            self.app_event_tx
+                .send(crate::app_event::AppEvent::StartFileSearch(tok));
+            // Do not show a popup yet; wait for results and only
+            // show if there are matches to avoid flicker.
+            return (InputResult::None, true);
+        }
+        (InputResult::None, false)
+    }
+
+    /// Keys typed while a reverse history search is active. Returns `None`
+    /// when the key accepts the search and should then be handled normally.
+    fn handle_history_search_key(&mut self, key_event: KeyEvent) -> Option<(InputResult, bool)> {
+        if key_event.kind == KeyEventKind::Release {
+            return Some((InputResult::None, false));
+        }
+        if self.keymap.matches(KeymapAction::HistorySearch, &key_event) {
+            let changed = self.history.search_older();
+            self.show_history_search_match();
+            return Some((InputResult::None, changed));
+        }
+        match (key_event.code, key_event.modifiers) {
+            (KeyCode::Up, _) => {
+                let changed = self.history.search_older();
+                self.show_history_search_match();
+                Some((InputResult::None, changed))
+            }
+            (KeyCode::Down, _) => {
+                let changed = self.history.search_newer();
+                self.show_history_search_match();
+                Some((InputResult::None, changed))
+            }
+            (KeyCode::Tab, KeyModifiers::NONE) => {
+                let changed = self.history.search_toggle_repo_only(&self.app_event_tx);
+                self.show_history_search_match();
+                Some((InputResult::None, changed))
+            }
+            (KeyCode::Enter, KeyModifiers::NONE) => {
+                self.accept_history_search();
+                Some((InputResult::None, true))
+            }
+            (KeyCode::Esc, _) | (KeyCode::Char('g'), KeyModifiers::CONTROL) => {
+                self.cancel_history_search();
+                Some((InputResult::None, true))
+            }
+            (KeyCode::Backspace, _) => {
+                self.history.search_pop_char(&self.app_event_tx);
+                self.show_history_search_match();
+                Some((InputResult::None, true))
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.history.search_push_char(c, &self.app_event_tx);
+                self.show_history_search_match();
+                Some((InputResult::None, true))
+            }
+            _ => {
+                self.accept_history_search();
+                None
+            }
+        }
+    }
+
+    /// Mirror the selected search match into the textarea. A failing search
+    /// keeps the last match on screen, like readline.
+    fn show_history_search_match(&mut self) {
+        let Some(search) = self.history.search() else {
+            return;
+        };
+        if let Some((text, range)) = search.current() {
+            let text = text.to_string();
+            self.textarea.set_text(&text);
+            self.textarea.set_cursor(range.start);
+            self.textarea.set_highlight(Some(range));
+        } else if search.query().is_empty() {
+            let original = search.original_text().to_string();
+            self.textarea.set_text(&original);
+            self.textarea.set_cursor(original.len());
+        } else {
+            self.textarea.set_highlight(None);
+        }
+    }
+
+    pub(crate) fn is_history_searching(&self) -> bool {
+        self.history.search().is_some()
+    }
+
+    /// Keep the selected match in the composer for editing.
+    pub(crate) fn accept_history_search(&mut self) {
+        if self.history.finish_search().is_none() {
+            return;
+        }
+        self.textarea.set_highlight(None);
+        self.textarea.set_cursor(self.textarea.text().len());
+        self.resync_popups();
+    }
+
+    /// Restore the text that was in the composer before the search.
+    pub(crate) fn cancel_history_search(&mut self) {
+        let Some(search) = self.history.finish_search() else {
+            return;
+        };
+        let original = search.original_text().to_string();
+        self.textarea.set_text(&original);
+        self.textarea.set_cursor(original.len());
+        self.resync_popups();
+    }
+
+    /// Integrate persistent matches for a reverse history search.
+    pub(crate) fn on_history_search_response(
+        &mut self,
+        query: &str,
+        repo_only: bool,
+        matches: Vec<String>,
+    ) -> bool {
+        if !self.history.on_search_response(query, repo_only, matches) {
+            return false;
+        }
+        self.show_history_search_match();
+        true
+    }
+
+    /// History navigation (Shift+Up/Down by default).
+    fn navigate_history(&mut self, up: bool) -> (InputResult, bool) {
+        if self
+            .history
+            .should_handle_navigation(self.textarea.text(), self.textarea.cursor())
+        {
+            let replace_text = if up {
+                self.history
+                    .navigate_up(self.textarea.text(), &self.app_event_tx)
+            } else {
+                self.history.navigate_down(&self.app_event_tx)
+            };
+            if let Some(text) = replace_text {
+                self.textarea.set_text(&text);
+                self.textarea.set_cursor(0);
+                return (InputResult::None, true);
+            }
+        }
+        // If history navigation didn't happen, just ignore the key
+        (InputResult::None, false)
+    }
+
+    /// Unbound Up/Down: move the cursor within the input first. Only when
+    /// already at the top-left/bottom-right should Up/Down scroll chat.
+    fn handle_vertical_arrow(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        if self.textarea.is_empty() {
+            return match key_event.code {
+                KeyCode::Up => (InputResult::ScrollUp, false),
+                KeyCode::Down => (InputResult::ScrollDown, false),
+                _ => (InputResult::None, false),
+            };
+        }
+
+        let before = self.textarea.cursor();
+        let len = self.textarea.text().len();
+        match key_event.code {
+            KeyCode::Up => {
+                if before == 0 {
+                    (InputResult::ScrollUp, false)
+                } else {
+                    // Move up a visual/logical line; if already on first line, TextArea moves to start.
+                    self.textarea
+                        .input(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+                    (InputResult::None, true)
                 }
             }
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
-                if self.handle_backslash_continuation() {
-                    return (InputResult::None, true);
+            KeyCode::Down => {
+                // If sticky is set, prefer chat ScrollDown once
+                if self.next_down_scrolls_history {
+                    self.next_down_scrolls_history = false;
+                    return (InputResult::ScrollDown, false);
                 }
-                let command_text = self.textarea.text().to_string();
-                let first_line = command_text.lines().next().unwrap_or("");
-                if let Some((name, rest)) = parse_slash_name(first_line)
-                    && rest.is_empty()
-                    && let Some((_label, cmd)) = built_in_slash_commands()
-                        .into_iter()
-                        .find(|(n, _)| *n == name)
-                {
-                    if cmd.is_prompt_expanding() {
-                        self.app_event_tx
-                            .send(crate::app_event::AppEvent::PrepareAgents);
-                    }
-                    self.history.record_local_submission(&command_text);
-                    self.app_event_tx
-                        .send(crate::app_event::AppEvent::DispatchCommand(
-                            cmd,
-                            command_text,
-                        ));
-                    self.textarea.set_text("");
-                    self.active_popup = ActivePopup::None;
-                    return (InputResult::Command(cmd), true);
+                if before == len {
+                    (InputResult::ScrollDown, false)
+                } else {
+                    // Move down a visual/logical line; if already on last line, TextArea moves to end.
+                    self.textarea
+                        .input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+                    (InputResult::None, true)
                 }
+            }
+            _ => (InputResult::None, false),
+        }
+    }
 
-                // Record the exact text that was typed (before replacement)
-                let original_text = self.textarea.text().to_string();
+    fn submit_input(&mut self) -> (InputResult, bool) {
+        if self.handle_backslash_continuation() {
+            return (InputResult::None, true);
+        }
+        let command_text = self.textarea.text().to_string();
+        let first_line = command_text.lines().next().unwrap_or("");
+        if let Some((name, rest)) = parse_slash_name(first_line)
+            && rest.is_empty()
+            && let Some((_label, cmd)) = built_in_slash_commands()
+                .into_iter()
+                .find(|(n, _)| *n == name)
+        {
+            if cmd.is_prompt_expanding() {
+                self.app_event_tx
+                    .send(crate::app_event::AppEvent::PrepareAgents);
+            }
+            self.history.record_local_submission(&command_text);
+            self.app_event_tx
+                .send(crate::app_event::AppEvent::DispatchCommand(
+                    cmd,
+                    command_text,
+                ));
+            self.textarea.set_text("");
+            self.textarea.vi_reset();
+            self.active_popup = ActivePopup::None;
+            return (InputResult::Command(cmd), true);
+        }
 
-                let mut text = self.textarea.text().to_string();
-                self.textarea.set_text("");
+        // Record the exact text that was typed (before replacement)
+        let original_text = self.textarea.text().to_string();
 
-                // Replace all pending pastes in the text
-                for (placeholder, actual) in &self.pending_pastes {
-                    if text.contains(placeholder) {
-                        text = text.replace(placeholder, actual);
-                    }
-                }
-                self.pending_pastes.clear();
+        let mut text = self.textarea.text().to_string();
+        self.textarea.set_text("");
+        self.textarea.vi_reset();
 
-                if text.is_empty() {
-                    (InputResult::None, true)
-                } else {
-                    // Check if this is a prompt-expanding command that will trigger agents
-                    let trimmed = original_text.trim();
-                    if trimmed.starts_with("/plan ")
-                        || trimmed.starts_with("/solve ")
-                        || trimmed.starts_with("/code ")
-                    {
-                        self.app_event_tx
-                            .send(crate::app_event::AppEvent::PrepareAgents);
-                    }
+        // Replace all pending pastes in the text
+        for (placeholder, actual) in &self.pending_pastes {
+            if text.contains(placeholder) {
+                text = text.replace(placeholder, actual);
+            }
+        }
+        self.pending_pastes.clear();
 
-                    self.history.record_local_submission(&original_text);
-                    (InputResult::Submitted(text), true)
-                }
+        if text.is_empty() {
+            (InputResult::None, true)
+        } else {
+            // Check if this is a prompt-expanding command that will trigger agents
+            let trimmed = original_text.trim();
+            if trimmed.starts_with("/plan ")
+                || trimmed.starts_with("/solve ")
+                || trimmed.starts_with("/code ")
+            {
+                self.app_event_tx
+                    .send(crate::app_event::AppEvent::PrepareAgents);
             }
-            input => self.handle_input_basic(input),
+
+            self.history.record_local_submission(&original_text);
+            (InputResult::Submitted(text), true)
         }
     }
 
@@ -1922,6 +2142,9 @@ impl ChatComposer {
                     if !self.custom_prompts.is_empty() {
                         command_popup.set_prompts(self.custom_prompts.clone());
                     }
+                    if !self.mcp_catalog.prompts().is_empty() {
+                        command_popup.set_mcp_prompts(self.mcp_catalog.prompts().to_vec());
+                    }
                     // Load saved subagent commands to include in autocomplete (exclude built-ins)
                     if let Ok(cfg) = code_core::config::Config::load_with_cli_overrides(
                         vec![],
@@ -1958,6 +2181,28 @@ impl ChatComposer {
         }
     }
 
+    /// Command text for an MCP prompt with an empty `name=""` slot for each
+    /// required argument.
+    fn mcp_prompt_command_text(entry: &McpPromptEntry) -> String {
+        let mut text = format!("/{}", entry.command_name());
+        for arg in entry.required_arguments() {
+            text.push_str(&format!(" {arg}=\"\""));
+        }
+        text.push(' ');
+        text
+    }
+
+    pub(crate) fn set_mcp_catalog(&mut self, catalog: McpCatalog) {
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(catalog.prompts().to_vec());
+        }
+        self.mcp_catalog = catalog;
+    }
+
+    pub(crate) fn mcp_catalog(&self) -> &McpCatalog {
+        &self.mcp_catalog
+    }
+
     /// Synchronize `self.file_search_popup` with the current text in the textarea.
     /// Note this is only called when self.active_popup is NOT Command.
     fn sync_file_search_popup(&mut self) {
@@ -2192,6 +2437,70 @@ impl ChatComposer {
         }
     }
 
+    /// Footer shown while a reverse history search is active, e.g.
+    /// `reverse-i-search: fix  2/5 · this repo` plus the search keys.
+    fn render_history_search_footer(
+        &self,
+        search: &HistorySearch,
+        area: Rect,
+        buf: &mut Buffer,
+        key_hint_style: Style,
+        label_style: Style,
+    ) {
+        let (position, total) = search.position();
+        let failing = !search.query().is_empty() && total == 0;
+        let mut left_spans: Vec<Span<'static>> = vec![
+            Span::from("  "),
+            Span::from(if failing {
+                "failing reverse-i-search: "
+            } else {
+                "reverse-i-search: "
+            })
+            .style(key_hint_style.add_modifier(Modifier::BOLD)),
+            Span::from(search.query().to_string())
+                .style(Style::default().fg(crate::colors::text())),
+        ];
+        if total > 0 {
+            left_spans.push(Span::from(format!("  {position}/{total}")).style(label_style));
+        }
+        let scope = if search.repo_only() {
+            "this repo"
+        } else {
+            "all history"
+        };
+        left_spans.push(Span::from(format!("  ·  {scope}")).style(label_style));
+
+        let mut hints: Vec<(String, &str)> = Vec::new();
+        if let Some(key) = self.keymap.hint(KeymapAction::HistorySearch) {
+            hints.push((key, "older"));
+        }
+        hints.push(("↓".to_string(), "newer"));
+        if self.history.can_search_repo_only() {
+            hints.push(("Tab".to_string(), "scope"));
+        }
+        hints.push(("Enter".to_string(), "accept"));
+        hints.push(("Esc".to_string(), "cancel"));
+        let mut right_spans: Vec<Span<'static>> = Vec::new();
+        for (key, label) in hints {
+            if !right_spans.is_empty() {
+                right_spans.push(Span::from("  •  ").style(label_style));
+            }
+            right_spans.push(Span::from(key).style(key_hint_style));
+            right_spans.push(Span::from(format!(" {label}")).style(label_style));
+        }
+
+        let measure = |spans: &[Span<'static>]| -> usize { spans.iter().map(|s| s.width()).sum() };
+        let total_width = area.width as usize;
+        let used = measure(&left_spans) + measure(&right_spans) + 1;
+        let mut line_spans = left_spans;
+        if total_width > used {
+            line_spans.push(Span::from(" ".repeat(total_width - used)));
+            line_spans.extend(right_spans);
+            line_spans.push(Span::from(" "));
+        }
+        Line::from(line_spans).render_ref(area, buf);
+    }
+
     fn render_footer(&self, area: Rect, buf: &mut Buffer) {
         if area.width == 0 || area.height == 0 {
             return;
@@ -2212,6 +2521,17 @@ impl ChatComposer {
                 let key_hint_style = Style::default().fg(crate::colors::function());
                 let label_style = Style::default().fg(crate::colors::text_dim());
 
+                if let Some(search) = self.history.search() {
+                    self.render_history_search_footer(
+                        search,
+                        area,
+                        buf,
+                        key_hint_style,
+                        label_style,
+                    );
+                    return;
+                }
+
                 if let Some(hints) = &self.footer_hint_override {
                     let mut left_spans: Vec<Span<'static>> = vec![Span::from("  ")];
                     for (idx, (key, label)) in hints.iter().enumerate() {
@@ -2263,6 +2583,15 @@ impl ChatComposer {
                 let mut left_spans: Vec<Span> = vec![Span::from("  ")];
                 let mut right_spans: Vec<Span<'static>> = Vec::new();
 
+                if let Some(mode) = self.textarea.vi_mode()
+                    && mode != ViMode::Insert
+                {
+                    left_spans.push(
+                        Span::from(mode.label()).style(key_hint_style.add_modifier(Modifier::BOLD)),
+                    );
+                    left_spans.push(Span::from("   "));
+                }
+
                 let show_access_label = if let Some(until) = self.access_mode_label_expiry {
                     std::time::Instant::now() <= until
                 } else {
@@ -2364,11 +2693,14 @@ impl ChatComposer {
                             return spans;
                         }
                         if !self.ctrl_c_quit_hint {
-                            if self.show_reasoning_hint && include_reasoning {
+                            if self.show_reasoning_hint
+                                && include_reasoning
+                                && let Some(key) = self.keymap.hint(KeymapAction::ToggleReasoning)
+                            {
                                 if !spans.is_empty() {
                                     spans.push(Span::from("  •  ").style(label_style));
                                 }
-                                spans.push(Span::from("Ctrl+R").style(key_hint_style));
+                                spans.push(Span::from(key).style(key_hint_style));
                                 let label = if self.reasoning_shown {
                                     " hide reasoning"
                                 } else {
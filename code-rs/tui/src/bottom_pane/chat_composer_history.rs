@@ -1,7 +1,10 @@
 use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use code_core::find_ignore_case;
 use code_core::protocol::Op;
 
 /// State machine that manages shell-style history navigation (Up/Down) inside
@@ -32,6 +35,63 @@ pub(crate) struct ChatComposerHistory {
     /// The original text that was in the composer before starting history navigation.
     /// This allows us to restore it when pressing down past the newest entry.
     original_text: Option<String>,
+
+    /// Repository root used when reverse search is restricted to this repo.
+    repo_root: Option<PathBuf>,
+
+    /// Whether the last reverse search was restricted to this repo; new
+    /// searches start with the same scope.
+    search_repo_only: bool,
+
+    /// Active Ctrl+R reverse incremental search, if any.
+    search: Option<HistorySearch>,
+}
+
+/// Maximum number of persistent entries requested per reverse-search query.
+const SEARCH_RESULT_LIMIT: usize = 200;
+
+/// State of a Ctrl+R reverse incremental search across the session's own
+/// submissions and the persistent cross-session history.
+pub(crate) struct HistorySearch {
+    query: String,
+    repo_only: bool,
+    /// Composer text before the search started; restored on cancel.
+    original_text: String,
+    /// Persistent matches for the current query and scope, newest first.
+    persistent: Vec<String>,
+    /// Local matches (newest first) followed by persistent ones not already listed.
+    matches: Vec<String>,
+    /// Index into `matches`; larger is older.
+    selected: usize,
+}
+
+impl HistorySearch {
+    pub(crate) fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub(crate) fn repo_only(&self) -> bool {
+        self.repo_only
+    }
+
+    pub(crate) fn original_text(&self) -> &str {
+        &self.original_text
+    }
+
+    /// The selected match and the byte range of the query within it.
+    pub(crate) fn current(&self) -> Option<(&str, Range<usize>)> {
+        let text = self.matches.get(self.selected)?;
+        let range = find_ignore_case(text, &self.query)?;
+        Some((text.as_str(), range))
+    }
+
+    /// 1-based position of the selected match and the number of matches.
+    pub(crate) fn position(&self) -> (usize, usize) {
+        (
+            (self.selected + 1).min(self.matches.len()),
+            self.matches.len(),
+        )
+    }
 }
 
 impl ChatComposerHistory {
@@ -44,9 +104,17 @@ impl ChatComposerHistory {
             history_cursor: None,
             last_history_text: None,
             original_text: None,
+            repo_root: None,
+            search_repo_only: false,
+            search: None,
         }
     }
 
+    /// Set the repository root used by repo-scoped reverse search.
+    pub fn set_repo_root(&mut self, repo_root: Option<PathBuf>) {
+        self.repo_root = repo_root;
+    }
+
     /// Update metadata when a new session is configured.
     pub fn set_metadata(&mut self, log_id: u64, entry_count: usize) {
         self.history_log_id = Some(log_id);
@@ -56,6 +124,7 @@ impl ChatComposerHistory {
         self.history_cursor = None;
         self.last_history_text = None;
         self.original_text = None;
+        self.search = None;
     }
 
     /// Record a message submitted by the user in the current session so it can
@@ -202,6 +271,163 @@ impl ChatComposerHistory {
         None
     }
 
+    // ---------------------------------------------------------------------
+    // Reverse incremental search (Ctrl+R)
+    // ---------------------------------------------------------------------
+
+    pub fn search(&self) -> Option<&HistorySearch> {
+        self.search.as_ref()
+    }
+
+    /// Begin a reverse search, remembering `current_text` for cancellation.
+    pub fn start_search(&mut self, current_text: &str) {
+        self.reset_navigation();
+        self.search = Some(HistorySearch {
+            query: String::new(),
+            repo_only: self.search_repo_only && self.repo_root.is_some(),
+            original_text: current_text.to_string(),
+            persistent: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+        });
+    }
+
+    /// End the search, returning its final state.
+    pub fn finish_search(&mut self) -> Option<HistorySearch> {
+        self.search.take()
+    }
+
+    pub fn search_push_char(&mut self, c: char, app_event_tx: &AppEventSender) {
+        if let Some(search) = self.search.as_mut() {
+            search.query.push(c);
+            self.refresh_search(app_event_tx);
+        }
+    }
+
+    pub fn search_pop_char(&mut self, app_event_tx: &AppEventSender) {
+        if let Some(search) = self.search.as_mut()
+            && search.query.pop().is_some()
+        {
+            self.refresh_search(app_event_tx);
+        }
+    }
+
+    /// Whether reverse search can be restricted to the current repository.
+    pub fn can_search_repo_only(&self) -> bool {
+        self.repo_root.is_some()
+    }
+
+    /// Toggle between searching all history and only this repository's.
+    /// Returns false when no repository root is known.
+    pub fn search_toggle_repo_only(&mut self, app_event_tx: &AppEventSender) -> bool {
+        if self.repo_root.is_none() {
+            return false;
+        }
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+        search.repo_only = !search.repo_only;
+        self.search_repo_only = search.repo_only;
+        self.refresh_search(app_event_tx);
+        true
+    }
+
+    /// Move to the next older match. Returns true if the selection changed.
+    pub fn search_older(&mut self) -> bool {
+        match self.search.as_mut() {
+            Some(search) if search.selected + 1 < search.matches.len() => {
+                search.selected += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Move to the next newer match. Returns true if the selection changed.
+    pub fn search_newer(&mut self) -> bool {
+        match self.search.as_mut() {
+            Some(search) if search.selected > 0 => {
+                search.selected -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Integrate a SearchHistoryResponse event. Responses for an outdated
+    /// query or scope are ignored. Returns true if the matches changed.
+    pub fn on_search_response(
+        &mut self,
+        query: &str,
+        repo_only: bool,
+        matches: Vec<String>,
+    ) -> bool {
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+        if search.query != query || search.repo_only != repo_only {
+            return false;
+        }
+        let selected = search.matches.get(search.selected).cloned();
+        search.persistent = matches;
+        self.rebuild_search_matches();
+        if let (Some(search), Some(selected)) = (self.search.as_mut(), selected) {
+            search.selected = search
+                .matches
+                .iter()
+                .position(|text| *text == selected)
+                .unwrap_or(0);
+        }
+        true
+    }
+
+    /// Recompute matches for a changed query or scope, restarting from the
+    /// newest match, and ask core for persistent matches.
+    fn refresh_search(&mut self, app_event_tx: &AppEventSender) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.persistent.clear();
+        search.selected = 0;
+        let query = search.query.clone();
+        let repo_only = search.repo_only;
+        self.rebuild_search_matches();
+
+        if query.is_empty() || self.history_entry_count == 0 {
+            return;
+        }
+        if let Some(log_id) = self.history_log_id {
+            let op = Op::SearchHistoryRequest {
+                query,
+                log_id,
+                repo_root: self.repo_root.clone().filter(|_| repo_only),
+                limit: SEARCH_RESULT_LIMIT,
+            };
+            app_event_tx.send(AppEvent::BeaconOp(op));
+        }
+    }
+
+    fn rebuild_search_matches(&mut self) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let mut matches: Vec<String> = Vec::new();
+        // Everything submitted this session belongs to the current repo, so
+        // local entries match regardless of scope.
+        let local = self
+            .local_history
+            .iter()
+            .rev()
+            .filter(|text| find_ignore_case(text, &search.query).is_some());
+        for text in local.chain(search.persistent.iter()) {
+            if !matches.contains(text) {
+                matches.push(text.clone());
+            }
+        }
+        search.matches = matches;
+        search.selected = search.selected.min(search.matches.len().saturating_sub(1));
+    }
+
     // ---------------------------------------------------------------------
     // Internal helpers
     // ---------------------------------------------------------------------
@@ -233,3 +459,122 @@ impl ChatComposerHistory {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::mpsc::Receiver;
+    use std::sync::mpsc::channel;
+
+    fn history_with(entries: &[&str]) -> (ChatComposerHistory, AppEventSender, Receiver<AppEvent>) {
+        let (tx, rx) = channel();
+        let mut history = ChatComposerHistory::new();
+        history.set_metadata(7, 3);
+        for entry in entries {
+            history.record_local_submission(entry);
+        }
+        (history, AppEventSender::new(tx), rx)
+    }
+
+    fn type_query(history: &mut ChatComposerHistory, query: &str, tx: &AppEventSender) {
+        for c in query.chars() {
+            history.search_push_char(c, tx);
+        }
+    }
+
+    fn current(history: &ChatComposerHistory) -> Option<String> {
+        history
+            .search()
+            .and_then(HistorySearch::current)
+            .map(|(text, _)| text.to_string())
+    }
+
+    fn search_requests(rx: &Receiver<AppEvent>) -> Vec<(String, Option<PathBuf>)> {
+        rx.try_iter()
+            .filter_map(|event| match event {
+                AppEvent::BeaconOp(Op::SearchHistoryRequest {
+                    query, repo_root, ..
+                }) => Some((query, repo_root)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn search_highlights_case_insensitive_matches() {
+        let (mut history, tx, _rx) = history_with(&["Fix the Bug", "İzmir trip"]);
+        history.start_search("");
+        type_query(&mut history, "İZ", &tx);
+        let matched = history.search().and_then(HistorySearch::current);
+        assert_eq!(matched, Some(("İzmir trip", 0..3)));
+
+        let (mut history, tx, _rx) = history_with(&["Fix the Bug"]);
+        history.start_search("");
+        type_query(&mut history, "bug", &tx);
+        let matched = history.search().and_then(HistorySearch::current);
+        assert_eq!(matched, Some(("Fix the Bug", 8..11)));
+    }
+
+    #[test]
+    fn search_cycles_local_matches_newest_first() {
+        let (mut history, tx, _rx) = history_with(&[
+            "fix the parser",
+            "write docs",
+            "Fix the lexer",
+            "fix the parser",
+        ]);
+        history.start_search("draft");
+        type_query(&mut history, "fix", &tx);
+
+        assert_eq!(current(&history).as_deref(), Some("fix the parser"));
+        assert!(history.search_older());
+        assert_eq!(current(&history).as_deref(), Some("Fix the lexer"));
+        // The repeated "fix the parser" is listed once, so this is the oldest.
+        assert!(!history.search_older());
+        assert!(history.search_newer());
+        assert_eq!(current(&history).as_deref(), Some("fix the parser"));
+
+        type_query(&mut history, "zz", &tx);
+        assert_eq!(current(&history), None);
+        let search = history.finish_search().expect("search active");
+        assert_eq!(search.original_text(), "draft");
+        assert!(history.search().is_none());
+    }
+
+    #[test]
+    fn persistent_matches_follow_local_and_stale_responses_are_dropped() {
+        let (mut history, tx, rx) = history_with(&["fix the parser"]);
+        history.set_repo_root(Some(PathBuf::from("/repo")));
+        history.start_search("");
+        type_query(&mut history, "fi", &tx);
+        assert_eq!(
+            search_requests(&rx),
+            vec![("f".to_string(), None), ("fi".to_string(), None)]
+        );
+
+        assert!(!history.on_search_response("f", false, vec!["fold".to_string()]));
+        assert!(history.on_search_response(
+            "fi",
+            false,
+            vec!["fix the parser".to_string(), "find the leak".to_string()],
+        ));
+        assert_eq!(history.search().map(HistorySearch::position), Some((1, 2)));
+        assert!(history.search_older());
+        assert_eq!(current(&history).as_deref(), Some("find the leak"));
+
+        assert!(history.search_toggle_repo_only(&tx));
+        assert_eq!(
+            search_requests(&rx),
+            vec![("fi".to_string(), Some(PathBuf::from("/repo")))]
+        );
+        // Results for the old scope no longer apply.
+        assert!(!history.on_search_response("fi", false, vec!["fig".to_string()]));
+        assert_eq!(current(&history).as_deref(), Some("fix the parser"));
+
+        // The scope sticks for the next search.
+        history.finish_search();
+        history.start_search("");
+        assert_eq!(history.search().map(HistorySearch::repo_only), Some(true));
+    }
+}
@@ -3,6 +3,7 @@ use ratatui::layout::Margin;
 use ratatui::layout::Rect;
 use ratatui::widgets::WidgetRef;
 
+use super::mcp_catalog::McpPromptEntry;
 use super::popup_consts::MAX_POPUP_ROWS;
 use super::scroll_state::ScrollState;
 use super::selection_popup_common::GenericDisplayRow;
@@ -22,6 +23,8 @@ pub(crate) enum CommandItem {
     UserPrompt(usize),
     // Index into `subagents`
     Subagent(usize),
+    // Index into `mcp_prompts`
+    McpPrompt(usize),
 }
 
 pub(crate) struct CommandPopup {
@@ -30,6 +33,7 @@ pub(crate) struct CommandPopup {
     prompts: Vec<CustomPrompt>,
     state: ScrollState,
     subagents: Vec<String>,
+    mcp_prompts: Vec<McpPromptEntry>,
 }
 
 impl CommandPopup {
@@ -45,6 +49,7 @@ impl CommandPopup {
             prompts: Vec::new(),
             state: ScrollState::new(),
             subagents: Vec::new(),
+            mcp_prompts: Vec::new(),
         }
     }
 
@@ -68,6 +73,17 @@ impl CommandPopup {
         self.subagents.get(idx).map(std::string::String::as_str)
     }
 
+    pub(crate) fn mcp_prompt(&self, idx: usize) -> Option<&McpPromptEntry> {
+        self.mcp_prompts.get(idx)
+    }
+
+    /// Supply prompts advertised by connected MCP servers. They are shown as
+    /// `/mcp:<server>:<prompt>` after custom prompts.
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptEntry>) {
+        self.mcp_prompts = prompts;
+        self.state.clamp_selection(self.filtered_items().len());
+    }
+
     /// Supply custom subagent command names (e.g., ["demo", "ship"]) to include in the
     /// slash popup. Built-ins should already be excluded by the caller.
     pub(crate) fn set_subagent_commands(&mut self, mut names: Vec<String>) {
@@ -135,6 +151,10 @@ impl CommandPopup {
             for idx in 0..self.prompts.len() {
                 out.push((CommandItem::UserPrompt(idx), None, 0));
             }
+            // Then MCP prompts, sorted by server and name.
+            for idx in 0..self.mcp_prompts.len() {
+                out.push((CommandItem::McpPrompt(idx), None, 0));
+            }
             return out;
         }
 
@@ -166,6 +186,11 @@ impl CommandPopup {
                 out.push((CommandItem::UserPrompt(idx), Some(indices), score));
             }
         }
+        for (idx, entry) in self.mcp_prompts.iter().enumerate() {
+            if let Some((indices, score)) = fuzzy_match(&entry.command_name(), filter) {
+                out.push((CommandItem::McpPrompt(idx), Some(indices), score));
+            }
+        }
         // When filtering, sort by ascending score, then prefer built-ins over
         // subagents over prompts to keep core commands easy to reach on exact
         // matches (e.g., `/prompts` should not auto-select a custom prompt),
@@ -182,23 +207,20 @@ impl CommandPopup {
                 CommandItem::Builtin(_) => 0,
                 CommandItem::Subagent(_) => 1,
                 CommandItem::UserPrompt(_) => 2,
+                CommandItem::McpPrompt(_) => 3,
             };
             let rank_cmp = rank(&a.0).cmp(&rank(&b.0));
             if rank_cmp != Ordering::Equal {
                 return rank_cmp;
             }
 
-            let an = match a.0 {
-                CommandItem::Builtin(c) => c.command(),
-                CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                CommandItem::Subagent(i) => &self.subagents[i],
+            let name = |item: &CommandItem| match *item {
+                CommandItem::Builtin(c) => c.command().to_string(),
+                CommandItem::UserPrompt(i) => self.prompts[i].name.clone(),
+                CommandItem::Subagent(i) => self.subagents[i].clone(),
+                CommandItem::McpPrompt(i) => self.mcp_prompts[i].command_name(),
             };
-            let bn = match b.0 {
-                CommandItem::Builtin(c) => c.command(),
-                CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                CommandItem::Subagent(i) => &self.subagents[i],
-            };
-            an.cmp(bn)
+            name(&a.0).cmp(&name(&b.0))
         });
         out
     }
@@ -269,6 +291,17 @@ impl WidgetRef for CommandPopup {
                             format!("/{}", self.subagents[i]),
                             Some("custom subagent".to_string()),
                         ),
+                        CommandItem::McpPrompt(i) => {
+                            let entry = &self.mcp_prompts[i];
+                            let desc = entry
+                                .prompt
+                                .description
+                                .as_deref()
+                                .or(entry.prompt.title.as_deref())
+                                .map(|d| format!("[mcp] {d}"))
+                                .unwrap_or_else(|| "[mcp] prompt".to_string());
+                            (format!("/{}", entry.command_name()), Some(desc))
+                        }
                     };
                     GenericDisplayRow {
                         name,
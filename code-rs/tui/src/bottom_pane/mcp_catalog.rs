@@ -0,0 +1,210 @@
+//! Resources and prompts advertised by connected MCP servers, as surfaced in
+//! the composer (`@server:uri` mentions and `/mcp:server:prompt` commands).
+
+use code_common::fuzzy_match::fuzzy_match;
+use code_core::protocol::InputItem;
+use code_core::protocol::ListMcpCatalogResponseEvent;
+use code_file_search::FileMatch;
+use mcp_types::Prompt;
+use mcp_types::Resource;
+
+/// Slash-command prefix for MCP prompts: `/mcp:<server>:<prompt>`.
+pub(crate) const MCP_PROMPT_CMD_PREFIX: &str = "mcp";
+
+#[derive(Clone, Debug)]
+pub(crate) struct McpPromptEntry {
+    pub server: String,
+    pub prompt: Prompt,
+}
+
+impl McpPromptEntry {
+    /// Command name without the leading slash, e.g. `mcp:github:review`.
+    pub(crate) fn command_name(&self) -> String {
+        format!(
+            "{MCP_PROMPT_CMD_PREFIX}:{}:{}",
+            self.server, self.prompt.name
+        )
+    }
+
+    pub(crate) fn required_arguments(&self) -> Vec<String> {
+        self.prompt
+            .arguments
+            .iter()
+            .flatten()
+            .filter(|arg| arg.required.unwrap_or(false))
+            .map(|arg| arg.name.clone())
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct McpResourceEntry {
+    pub server: String,
+    pub resource: Resource,
+}
+
+impl McpResourceEntry {
+    /// Mention text inserted into the composer, e.g. `@docs:file:///README.md`.
+    pub(crate) fn mention(&self) -> String {
+        format!("@{}:{}", self.server, self.resource.uri)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct McpCatalog {
+    prompts: Vec<McpPromptEntry>,
+    resources: Vec<McpResourceEntry>,
+}
+
+impl McpCatalog {
+    pub(crate) fn from_event(event: ListMcpCatalogResponseEvent) -> Self {
+        let mut prompts: Vec<McpPromptEntry> = event
+            .prompts
+            .into_iter()
+            .flat_map(|(server, prompts)| {
+                prompts.into_iter().map(move |prompt| McpPromptEntry {
+                    server: server.clone(),
+                    prompt,
+                })
+            })
+            .collect();
+        prompts.sort_by(|a, b| {
+            (a.server.as_str(), a.prompt.name.as_str())
+                .cmp(&(b.server.as_str(), b.prompt.name.as_str()))
+        });
+
+        let mut resources: Vec<McpResourceEntry> = event
+            .resources
+            .into_iter()
+            .flat_map(|(server, resources)| {
+                resources.into_iter().map(move |resource| McpResourceEntry {
+                    server: server.clone(),
+                    resource,
+                })
+            })
+            .collect();
+        resources.sort_by(|a, b| {
+            (a.server.as_str(), a.resource.uri.as_str())
+                .cmp(&(b.server.as_str(), b.resource.uri.as_str()))
+        });
+
+        Self { prompts, resources }
+    }
+
+    pub(crate) fn prompts(&self) -> &[McpPromptEntry] {
+        &self.prompts
+    }
+
+    pub(crate) fn find_prompt(&self, server: &str, name: &str) -> Option<&McpPromptEntry> {
+        self.prompts
+            .iter()
+            .find(|entry| entry.server == server && entry.prompt.name == name)
+    }
+
+    /// Resources whose `server:uri` or display name fuzzy-match `query`,
+    /// shaped as file-search results so they can share the `@` popup.
+    pub(crate) fn resource_matches(&self, query: &str) -> Vec<FileMatch> {
+        let mut out: Vec<(i32, FileMatch)> = Vec::new();
+        for entry in &self.resources {
+            let mention = entry.mention();
+            let by_mention = fuzzy_match(&mention[1..], query);
+            let by_name = fuzzy_match(&entry.resource.name, query).map(|(_, score)| score);
+            let score = match (&by_mention, by_name) {
+                (Some((_, a)), Some(b)) => (*a).min(b),
+                (Some((_, a)), None) => *a,
+                (None, Some(b)) => b,
+                (None, None) => continue,
+            };
+            let indices = by_mention.map(|(indices, _)| {
+                // Offset by one for the leading '@' in the displayed path.
+                indices.into_iter().map(|i| (i + 1) as u32).collect()
+            });
+            out.push((
+                score,
+                FileMatch {
+                    score: 0,
+                    path: mention,
+                    indices,
+                },
+            ));
+        }
+        out.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.path.cmp(&b.1.path)));
+        out.into_iter().map(|(_, m)| m).collect()
+    }
+
+    /// Collect `InputItem::McpResource` attachments for every `@server:uri`
+    /// token in `text` that names a known resource.
+    pub(crate) fn resource_mentions(&self, text: &str) -> Vec<InputItem> {
+        let mut out: Vec<InputItem> = Vec::new();
+        for token in text.split_whitespace() {
+            let Some(entry) = self.resources.iter().find(|entry| entry.mention() == token) else {
+                continue;
+            };
+            let already = out.iter().any(|item| {
+                matches!(item, InputItem::McpResource { server, uri }
+                    if *server == entry.server && *uri == entry.resource.uri)
+            });
+            if !already {
+                out.push(InputItem::McpResource {
+                    server: entry.server.clone(),
+                    uri: entry.resource.uri.clone(),
+                });
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn resource(uri: &str, name: &str) -> Resource {
+        Resource {
+            annotations: None,
+            description: None,
+            mime_type: None,
+            name: name.to_string(),
+            size: None,
+            title: None,
+            uri: uri.to_string(),
+        }
+    }
+
+    fn catalog() -> McpCatalog {
+        let mut resources = HashMap::new();
+        resources.insert(
+            "docs".to_string(),
+            vec![
+                resource("file:///README.md", "readme"),
+                resource("file:///CHANGELOG.md", "changelog"),
+            ],
+        );
+        McpCatalog::from_event(ListMcpCatalogResponseEvent {
+            resources,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn resource_matches_use_mention_paths() {
+        let matches = catalog().resource_matches("readme");
+        let paths: Vec<&str> = matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, vec!["@docs:file:///README.md"]);
+    }
+
+    #[test]
+    fn resource_mentions_only_attach_known_resources_once() {
+        let text = "compare @docs:file:///README.md with @docs:file:///missing.md \
+                    and @docs:file:///README.md again";
+        let items = catalog().resource_mentions(text);
+        assert_eq!(items.len(), 1);
+        assert!(matches!(
+            &items[0],
+            InputItem::McpResource { server, uri }
+                if server == "docs" && uri == "file:///README.md"
+        ));
+    }
+}
@@ -0,0 +1,468 @@
+//! Form shown when an MCP server asks the user for structured input
+//! (`elicitation/create`). Fields are derived from the flat JSON schema the
+//! server sends; values are coerced back to the declared types on submit.
+
+use std::any::Any;
+use std::collections::VecDeque;
+
+use code_core::protocol::McpElicitationAction;
+use code_core::protocol::McpElicitationRequestEvent;
+use code_core::protocol::Op;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use ratatui::widgets::Wrap;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::colors;
+
+use super::BottomPane;
+use super::CancellationEvent;
+use super::bottom_pane_view::BottomPaneView;
+use super::bottom_pane_view::ConditionalUpdate;
+use super::form_text_field::FormTextField;
+
+#[derive(Clone, Debug, PartialEq)]
+enum FieldKind {
+    String,
+    Number { integer: bool },
+    Boolean,
+    Enum(Vec<String>),
+}
+
+struct ElicitationField {
+    name: String,
+    label: String,
+    description: Option<String>,
+    kind: FieldKind,
+    required: bool,
+    input: FormTextField,
+}
+
+impl ElicitationField {
+    fn type_hint(&self) -> String {
+        match &self.kind {
+            FieldKind::String => "text".to_string(),
+            FieldKind::Number { integer: true } => "integer".to_string(),
+            FieldKind::Number { integer: false } => "number".to_string(),
+            FieldKind::Boolean => "yes/no".to_string(),
+            FieldKind::Enum(options) => options.join(" | "),
+        }
+    }
+}
+
+pub(crate) struct McpElicitationView {
+    request: McpElicitationRequestEvent,
+    fields: Vec<ElicitationField>,
+    focused: usize,
+    error: Option<String>,
+    queue: VecDeque<McpElicitationRequestEvent>,
+    app_event_tx: AppEventSender,
+    complete: bool,
+}
+
+impl McpElicitationView {
+    pub(crate) fn new(request: McpElicitationRequestEvent, app_event_tx: AppEventSender) -> Self {
+        let fields = fields_from_schema(&request);
+        Self {
+            request,
+            fields,
+            focused: 0,
+            error: None,
+            queue: VecDeque::new(),
+            app_event_tx,
+            complete: false,
+        }
+    }
+
+    /// Queue another request behind the one currently shown.
+    pub(crate) fn enqueue(&mut self, request: McpElicitationRequestEvent) {
+        self.queue.push_back(request);
+    }
+
+    fn resolve(&mut self, action: McpElicitationAction, content: Option<Value>) {
+        self.app_event_tx
+            .send(AppEvent::BeaconOp(Op::ResolveMcpElicitation {
+                id: self.request.id.clone(),
+                action,
+                content,
+            }));
+        match self.queue.pop_front() {
+            Some(next) => {
+                self.fields = fields_from_schema(&next);
+                self.request = next;
+                self.focused = 0;
+                self.error = None;
+            }
+            None => self.complete = true,
+        }
+    }
+
+    fn submit(&mut self) {
+        match collect_values(&self.fields) {
+            Ok(content) => self.resolve(McpElicitationAction::Accept, Some(content)),
+            Err(message) => self.error = Some(message),
+        }
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let len = self.fields.len();
+        if len == 0 {
+            return;
+        }
+        self.focused = if forward {
+            (self.focused + 1) % len
+        } else {
+            (self.focused + len - 1) % len
+        };
+    }
+
+    fn header(&self) -> Paragraph<'static> {
+        let lines = vec![
+            Line::from(vec![
+                "? ".fg(colors::info()),
+                "MCP server ".bold(),
+                self.request.server.clone().bold(),
+                " requests input".bold(),
+            ]),
+            Line::from(self.request.message.clone()),
+        ];
+        Paragraph::new(lines).wrap(Wrap { trim: false })
+    }
+}
+
+impl BottomPaneView<'_> for McpElicitationView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'_>, key_event: KeyEvent) {
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            } => self.resolve(McpElicitationAction::Decline, None),
+            KeyEvent {
+                code: KeyCode::Tab | KeyCode::Down,
+                ..
+            } => self.move_focus(true),
+            KeyEvent {
+                code: KeyCode::BackTab | KeyCode::Up,
+                ..
+            } => self.move_focus(false),
+            KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => {
+                if self.focused + 1 >= self.fields.len() {
+                    self.submit();
+                } else {
+                    self.move_focus(true);
+                }
+            }
+            other => {
+                if let Some(field) = self.fields.get_mut(self.focused) {
+                    field.input.handle_key(other);
+                    self.error = None;
+                }
+            }
+        }
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'_>) -> CancellationEvent {
+        self.resolve(McpElicitationAction::Cancel, None);
+        CancellationEvent::Handled
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        let header = self.header().line_count(width) as u16;
+        let fields = (self.fields.len() as u16).saturating_mul(2);
+        // blank line before fields, then status/hint line
+        header + 1 + fields + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let bottom = area.y.saturating_add(area.height);
+        let header = self.header();
+        let header_height = (header.line_count(area.width) as u16).min(area.height);
+        header.render(
+            Rect {
+                height: header_height,
+                ..area
+            },
+            buf,
+        );
+
+        let mut y = area.y + header_height + 1;
+        for (idx, field) in self.fields.iter().enumerate() {
+            if y >= bottom {
+                return;
+            }
+            let focused = idx == self.focused;
+            let marker = if focused { "› " } else { "  " };
+            let label_style = if focused {
+                Style::default().fg(colors::primary()).bold()
+            } else {
+                Style::default().bold()
+            };
+            let mut spans = vec![
+                Span::styled(marker, label_style),
+                Span::styled(field.label.clone(), label_style),
+            ];
+            if field.required {
+                spans.push("*".fg(colors::error()));
+            }
+            spans.push(format!(" ({})", field.type_hint()).fg(colors::text_dim()));
+            if let Some(description) = &field.description {
+                spans.push(format!(" {description}").fg(colors::text_dim()).italic());
+            }
+            Paragraph::new(Line::from(spans)).render(
+                Rect {
+                    y,
+                    height: 1,
+                    ..area
+                },
+                buf,
+            );
+            y += 1;
+            if y >= bottom {
+                return;
+            }
+            if area.width > 4 {
+                field.input.render(
+                    Rect {
+                        x: area.x + 4,
+                        y,
+                        width: area.width - 4,
+                        height: 1,
+                    },
+                    buf,
+                    focused,
+                );
+            }
+            y += 1;
+        }
+
+        y += 1;
+        if y < bottom {
+            let line = match &self.error {
+                Some(message) => Line::from(message.clone().fg(colors::error())),
+                None => Line::from(
+                    "Tab next field • Enter submit • Esc decline • Ctrl+C cancel"
+                        .fg(colors::text_dim()),
+                ),
+            };
+            Paragraph::new(line).render(
+                Rect {
+                    y,
+                    height: 1,
+                    ..area
+                },
+                buf,
+            );
+        }
+    }
+
+    fn handle_paste(&mut self, pasted: String) -> ConditionalUpdate {
+        match self.fields.get_mut(self.focused) {
+            Some(field) if !pasted.is_empty() => {
+                field.input.handle_paste(pasted);
+                ConditionalUpdate::NeedsRedraw
+            }
+            _ => ConditionalUpdate::NoRedraw,
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(self)
+    }
+}
+
+fn fields_from_schema(request: &McpElicitationRequestEvent) -> Vec<ElicitationField> {
+    let schema = &request.requested_schema;
+    let required = schema.required.clone().unwrap_or_default();
+    let Some(properties) = schema.properties.as_object() else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let kind = match property.get("enum").and_then(Value::as_array) {
+                Some(options) => FieldKind::Enum(
+                    options
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect(),
+                ),
+                None => match property.get("type").and_then(Value::as_str) {
+                    Some("number") => FieldKind::Number { integer: false },
+                    Some("integer") => FieldKind::Number { integer: true },
+                    Some("boolean") => FieldKind::Boolean,
+                    _ => FieldKind::String,
+                },
+            };
+            let mut input = FormTextField::new_single_line();
+            if let Some(default) = property.get("default") {
+                match default {
+                    Value::String(text) => input.set_text(text),
+                    other => input.set_text(&other.to_string()),
+                }
+            }
+            ElicitationField {
+                name: name.clone(),
+                label: property
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .unwrap_or(name)
+                    .to_string(),
+                description: property
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                kind,
+                required: required.contains(name),
+                input,
+            }
+        })
+        .collect()
+}
+
+/// Build the `content` object for an accepted elicitation. Empty optional
+/// fields are omitted; anything that does not match its declared type is
+/// reported back to the user instead of being sent.
+fn collect_values(fields: &[ElicitationField]) -> Result<Value, String> {
+    let mut content = Map::new();
+    for field in fields {
+        let raw = field.input.text().trim();
+        if raw.is_empty() {
+            if field.required {
+                return Err(format!("{} is required", field.label));
+            }
+            continue;
+        }
+        let value = match &field.kind {
+            FieldKind::String => Value::String(raw.to_string()),
+            FieldKind::Number { integer: true } => raw
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("{} must be an integer", field.label))?,
+            FieldKind::Number { integer: false } => raw
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(|| format!("{} must be a number", field.label))?,
+            FieldKind::Boolean => match raw.to_ascii_lowercase().as_str() {
+                "y" | "yes" | "true" => Value::Bool(true),
+                "n" | "no" | "false" => Value::Bool(false),
+                _ => return Err(format!("{} must be yes or no", field.label)),
+            },
+            FieldKind::Enum(options) => {
+                if !options.iter().any(|option| option == raw) {
+                    return Err(format!(
+                        "{} must be one of: {}",
+                        field.label,
+                        options.join(", ")
+                    ));
+                }
+                Value::String(raw.to_string())
+            }
+        };
+        content.insert(field.name.clone(), value);
+    }
+    Ok(Value::Object(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ElicitRequestParamsRequestedSchema;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn request(properties: Value, required: &[&str]) -> McpElicitationRequestEvent {
+        McpElicitationRequestEvent {
+            id: "e1".to_string(),
+            server: "github".to_string(),
+            message: "Configure the release".to_string(),
+            requested_schema: ElicitRequestParamsRequestedSchema {
+                properties,
+                required: Some(required.iter().map(|s| s.to_string()).collect()),
+                r#type: "object".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn values_are_coerced_to_schema_types() {
+        let req = request(
+            json!({
+                "count": { "type": "integer" },
+                "draft": { "type": "boolean" },
+                "channel": { "type": "string", "enum": ["stable", "beta"] },
+                "notes": { "type": "string" },
+            }),
+            &["count"],
+        );
+        let mut fields = fields_from_schema(&req);
+        for field in &mut fields {
+            match field.name.as_str() {
+                "count" => field.input.set_text("3"),
+                "draft" => field.input.set_text("yes"),
+                "channel" => field.input.set_text("beta"),
+                _ => {}
+            }
+        }
+
+        assert_eq!(
+            collect_values(&fields),
+            Ok(json!({ "count": 3, "draft": true, "channel": "beta" }))
+        );
+    }
+
+    #[test]
+    fn missing_required_and_bad_enum_values_are_rejected() {
+        let req = request(
+            json!({
+                "repo": { "type": "string", "title": "Repository" },
+                "channel": { "type": "string", "enum": ["stable", "beta"] },
+            }),
+            &["repo"],
+        );
+        let mut fields = fields_from_schema(&req);
+        assert_eq!(
+            collect_values(&fields),
+            Err("Repository is required".to_string())
+        );
+
+        for field in &mut fields {
+            match field.name.as_str() {
+                "repo" => field.input.set_text("beacon"),
+                _ => field.input.set_text("nightly"),
+            }
+        }
+        assert_eq!(
+            collect_values(&fields),
+            Err("channel must be one of: stable, beta".to_string())
+        );
+    }
+}
@@ -64,6 +64,16 @@ impl McpSettingsView {
         }
     }
 
+    fn on_restart(&mut self) {
+        if let Some(row) = self.rows.get(self.selected)
+            && row.enabled
+        {
+            self.app_event_tx.send(AppEvent::RestartMcpServer {
+                name: row.name.clone(),
+            });
+        }
+    }
+
     fn on_enter(&mut self) {
         match self.selected {
             idx if idx < self.rows.len() => self.on_toggle(),
@@ -107,6 +117,11 @@ impl McpSettingsView {
             } => {
                 self.on_toggle();
             }
+            KeyEvent {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => self.on_restart(),
             KeyEvent {
                 code: KeyCode::Enter,
                 ..
@@ -132,7 +147,7 @@ impl McpSettingsView {
                     | KeyCode::Esc,
                 ..
             } | KeyEvent {
-                code: KeyCode::Char(' '),
+                code: KeyCode::Char(' ' | 'r'),
                 modifiers: KeyModifiers::NONE,
                 ..
             }
@@ -263,6 +278,8 @@ impl<'a> BottomPaneView<'a> for McpSettingsView {
                 " Toggle/Open  ",
                 Style::default().fg(crate::colors::text_dim()),
             ),
+            Span::styled("r", Style::default().fg(crate::colors::function())),
+            Span::styled(" Restart  ", Style::default().fg(crate::colors::text_dim())),
             Span::styled("Esc", Style::default().fg(crate::colors::error())),
             Span::styled(" Close", Style::default().fg(crate::colors::text_dim())),
         ]));
@@ -5,11 +5,13 @@ use crate::app_event_sender::AppEventSender;
 use crate::auto_drive_style::AutoDriveVariant;
 use crate::bottom_pane::chat_composer::ComposerRenderMode;
 use crate::chatwidget::BackgroundOrderTicket;
+use crate::keymap::Keymap;
 use crate::user_approval_widget::ApprovalRequest;
 use crate::user_approval_widget::UserApprovalWidget;
 use crate::util::buffer::fill_rect;
 pub(crate) use bottom_pane_view::BottomPaneView;
 pub(crate) use bottom_pane_view::ConditionalUpdate;
+use code_core::config_types::EditingMode;
 use code_core::protocol::TokenUsage;
 use code_file_search::FileMatch;
 use code_protocol::custom_prompts::CustomPrompt;
@@ -19,6 +21,7 @@ use crossterm::event::KeyEventKind;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::widgets::WidgetRef;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering;
@@ -40,6 +43,8 @@ mod custom_prompt_view;
 mod diff_popup;
 mod file_search_popup;
 pub mod list_selection_view;
+pub(crate) mod mcp_catalog;
+mod mcp_elicitation_view;
 pub(crate) mod model_selection_view;
 mod paste_burst;
 mod popup_consts;
@@ -48,6 +53,7 @@ mod scroll_state;
 mod selection_popup_common;
 pub(crate) use custom_prompt_view::CustomPromptView;
 pub(crate) use list_selection_view::SelectionAction;
+use mcp_elicitation_view::McpElicitationView;
 mod cloud_tasks_view;
 pub(crate) use cloud_tasks_view::CloudTasksView;
 pub mod agents_settings_view;
@@ -166,6 +172,8 @@ pub(crate) struct BottomPaneParams {
     pub(crate) enhanced_keys_supported: bool,
     pub(crate) using_chatgpt_auth: bool,
     pub(crate) auto_drive_variant: AutoDriveVariant,
+    pub(crate) keymap: Keymap,
+    pub(crate) editing_mode: EditingMode,
 }
 
 fn schedule_access_hint_redraw(dur: Duration, tx: AppEventSender) {
@@ -236,12 +244,14 @@ impl BottomPane<'_> {
     const BOTTOM_PAD_LINES: u16 = 1;
     pub fn new(params: BottomPaneParams) -> Self {
         let enhanced_keys_supported = params.enhanced_keys_supported;
-        let composer = ChatComposer::new(
+        let mut composer = ChatComposer::new(
             params.has_input_focus,
             params.app_event_tx.clone(),
             enhanced_keys_supported,
             params.using_chatgpt_auth,
         );
+        composer.set_keymap(params.keymap);
+        composer.set_editing_mode(params.editing_mode);
 
         Self {
             composer,
@@ -617,6 +627,27 @@ impl BottomPane<'_> {
         self.composer.file_popup_visible()
     }
 
+    pub(crate) fn composer_vi_wants_escape(&self) -> bool {
+        self.composer.vi_wants_escape()
+    }
+
+    pub(crate) fn composer_is_history_searching(&self) -> bool {
+        self.composer.is_history_searching()
+    }
+
+    pub(crate) fn composer_cancel_history_search(&mut self) {
+        self.composer.cancel_history_search();
+        self.request_redraw();
+    }
+
+    pub(crate) fn composer_vi_escape(&mut self) -> bool {
+        let handled = self.composer.vi_escape();
+        if handled {
+            self.request_redraw();
+        }
+        handled
+    }
+
     /// True if a modal/overlay view is currently displayed (not the composer popup).
     pub(crate) fn has_active_modal_view(&self) -> bool {
         // Consider a modal inactive once it has completed to avoid blocking
@@ -628,6 +659,11 @@ impl BottomPane<'_> {
         }
     }
 
+    pub(crate) fn set_editing_mode(&mut self, mode: EditingMode) {
+        self.composer.set_editing_mode(mode);
+        self.request_redraw();
+    }
+
     pub(crate) fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
         self.custom_prompts = prompts.clone();
         self.composer.set_custom_prompts(prompts);
@@ -637,6 +673,14 @@ impl BottomPane<'_> {
         &self.custom_prompts
     }
 
+    pub(crate) fn set_mcp_catalog(&mut self, catalog: mcp_catalog::McpCatalog) {
+        self.composer.set_mcp_catalog(catalog);
+    }
+
+    pub(crate) fn mcp_catalog(&self) -> &mcp_catalog::McpCatalog {
+        self.composer.mcp_catalog()
+    }
+
     /// Enable or disable compact compose mode. When enabled, the spacer line
     /// above the input composer is removed so the history can scroll into that
     /// row. This is typically toggled when the user scrolls up.
@@ -852,6 +896,28 @@ impl BottomPane<'_> {
         self.request_redraw();
     }
 
+    /// Show the form for an MCP elicitation request. If one is already on
+    /// screen the new request waits behind it.
+    pub(crate) fn show_mcp_elicitation(
+        &mut self,
+        request: code_core::protocol::McpElicitationRequestEvent,
+    ) {
+        if let Some(view) = self
+            .active_view
+            .as_mut()
+            .and_then(|view| view.as_any_mut())
+            .and_then(|any| any.downcast_mut::<McpElicitationView>())
+        {
+            view.enqueue(request);
+            return;
+        }
+        let view = McpElicitationView::new(request, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.active_view_kind = ActiveViewKind::Other;
+        self.status_view_active = false;
+        self.request_redraw();
+    }
+
     /// Show a generic list selection popup with items and actions.
     pub fn show_list_selection(
         &mut self,
@@ -894,6 +960,23 @@ impl BottomPane<'_> {
         self.request_redraw()
     }
 
+    /// Deliver full-text search results to the open resume picker, if any.
+    pub(crate) fn apply_resume_search_results(
+        &mut self,
+        query: &str,
+        rows: Vec<resume_selection_view::ResumeRow>,
+    ) {
+        if let Some(view) = self
+            .active_view
+            .as_mut()
+            .and_then(|view| view.as_any_mut())
+            .and_then(|any| any.downcast_mut::<resume_selection_view::ResumeSelectionView>())
+        {
+            view.apply_search_results(query, rows);
+            self.request_redraw();
+        }
+    }
+
     pub fn show_undo_timeline_view(&mut self, view: UndoTimelineView) {
         self.active_view = Some(Box::new(view));
         self.active_view_kind = ActiveViewKind::Other;
@@ -1014,7 +1097,7 @@ impl BottomPane<'_> {
         self.request_redraw();
     }
 
-    /// Control footer hint visibility: whether to show Ctrl+R (reasoning) and Ctrl+D (diffs)
+    /// Control footer hint visibility: whether to show the reasoning toggle and Ctrl+D (diffs)
     #[allow(dead_code)]
     pub(crate) fn set_footer_hints(&mut self, show_reasoning: bool, show_diffs: bool) {
         self.composer.set_show_reasoning_hint(show_reasoning);
@@ -1048,6 +1131,10 @@ impl BottomPane<'_> {
         self.composer.set_history_metadata(log_id, entry_count);
     }
 
+    pub(crate) fn set_history_repo_root(&mut self, repo_root: Option<PathBuf>) {
+        self.composer.set_history_repo_root(repo_root);
+    }
+
     #[allow(dead_code)]
     pub(crate) fn flush_paste_burst_if_due(&mut self) -> bool {
         self.composer.flush_paste_burst_if_due()
@@ -1080,6 +1167,20 @@ impl BottomPane<'_> {
         }
     }
 
+    pub(crate) fn on_history_search_response(
+        &mut self,
+        query: &str,
+        repo_only: bool,
+        matches: Vec<String>,
+    ) {
+        if self
+            .composer
+            .on_history_search_response(query, repo_only, matches)
+        {
+            self.request_redraw();
+        }
+    }
+
     pub(crate) fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
         self.composer.on_file_search_result(query, matches);
         self.request_redraw();
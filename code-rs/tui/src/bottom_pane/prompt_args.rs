@@ -1,4 +1,7 @@
+use super::mcp_catalog::MCP_PROMPT_CMD_PREFIX;
+use super::mcp_catalog::McpCatalog;
 use crate::slash_command::built_in_slash_commands;
+use code_core::protocol::InputItem;
 use code_protocol::custom_prompts::CustomPrompt;
 use code_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
 use lazy_static::lazy_static;
@@ -224,6 +227,51 @@ pub fn expand_custom_prompt(
     Ok(Some(prompt.content.clone()))
 }
 
+/// Resolves a message of the form `/mcp:server:prompt key=value …` into an
+/// `InputItem::McpPrompt` for a prompt advertised by a connected MCP server.
+///
+/// Returns `Ok(None)` when the text is not an MCP prompt invocation or the
+/// prompt is unknown, and an error when required arguments are missing.
+pub fn expand_mcp_prompt(
+    text: &str,
+    catalog: &McpCatalog,
+) -> Result<Option<InputItem>, PromptExpansionError> {
+    let Some((name, rest)) = parse_slash_name(text) else {
+        return Ok(None);
+    };
+    let Some(after_prefix) = name.strip_prefix(&format!("{MCP_PROMPT_CMD_PREFIX}:")) else {
+        return Ok(None);
+    };
+    let Some((server, prompt_name)) = after_prefix.split_once(':') else {
+        return Ok(None);
+    };
+    let Some(entry) = catalog.find_prompt(server, prompt_name) else {
+        return Ok(None);
+    };
+
+    let arguments = parse_prompt_inputs(rest).map_err(|error| PromptExpansionError::Args {
+        command: format!("/{name}"),
+        error,
+    })?;
+    let missing: Vec<String> = entry
+        .required_arguments()
+        .into_iter()
+        .filter(|k| !arguments.contains_key(k))
+        .collect();
+    if !missing.is_empty() {
+        return Err(PromptExpansionError::MissingArgs {
+            command: format!("/{name}"),
+            missing,
+        });
+    }
+
+    Ok(Some(InputItem::McpPrompt {
+        server: server.to_string(),
+        name: prompt_name.to_string(),
+        arguments,
+    }))
+}
+
 /// Detect whether `content` contains numeric placeholders ($1..$9) or `$ARGUMENTS`.
 pub fn prompt_has_numeric_placeholders(content: &str) -> bool {
     if content.contains("$ARGUMENTS") {
@@ -378,6 +426,55 @@ mod tests {
         assert_eq!(expanded, "Fix one two");
     }
 
+    #[test]
+    fn mcp_prompt_requires_declared_arguments() {
+        use crate::bottom_pane::mcp_catalog::McpCatalog;
+        use code_core::protocol::ListMcpCatalogResponseEvent;
+        use mcp_types::Prompt;
+        use mcp_types::PromptArgument;
+
+        let prompt = Prompt {
+            arguments: Some(vec![PromptArgument {
+                description: None,
+                name: "pr".to_string(),
+                required: Some(true),
+                title: None,
+            }]),
+            description: None,
+            name: "review".to_string(),
+            title: None,
+        };
+        let catalog = McpCatalog::from_event(ListMcpCatalogResponseEvent {
+            prompts: HashMap::from([("github".to_string(), vec![prompt])]),
+            ..Default::default()
+        });
+
+        let err = expand_mcp_prompt("/mcp:github:review", &catalog).unwrap_err();
+        assert!(matches!(err, PromptExpansionError::MissingArgs { .. }));
+
+        let item = expand_mcp_prompt("/mcp:github:review pr=42", &catalog)
+            .unwrap()
+            .unwrap();
+        match item {
+            InputItem::McpPrompt {
+                server,
+                name,
+                arguments,
+            } => {
+                assert_eq!(server, "github");
+                assert_eq!(name, "review");
+                assert_eq!(arguments.get("pr").map(String::as_str), Some("42"));
+            }
+            other => panic!("expected MCP prompt item, got {other:?}"),
+        }
+
+        assert!(
+            expand_mcp_prompt("/mcp:github:unknown", &catalog)
+                .unwrap()
+                .is_none()
+        );
+    }
+
     #[test]
     fn skips_builtin_conflicts() {
         let prompts = vec![make_prompt("plan", "custom")];
@@ -1,5 +1,6 @@
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Alignment;
 use ratatui::layout::Constraint;
@@ -15,7 +16,13 @@ use ratatui::widgets::Paragraph;
 use ratatui::widgets::Row;
 use ratatui::widgets::Table;
 use ratatui::widgets::Widget;
+use std::any::Any;
 use std::cell::Cell;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
@@ -27,6 +34,10 @@ use super::popup_consts::MAX_POPUP_ROWS;
 
 const RESUME_POPUP_ROWS: usize = 14;
 
+/// How long the query has to stay unchanged before a search is sent, so a
+/// burst of keystrokes results in a single search.
+const RESUME_SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
 pub struct ResumeRow {
     pub modified: String,
     pub created: String,
@@ -46,6 +57,15 @@ pub struct ResumeSelectionView {
     viewport_rows: Cell<usize>,
     complete: bool,
     app_event_tx: AppEventSender,
+    /// Full-text query typed into the picker.
+    query: String,
+    /// Unfiltered rows, stashed while a search is active.
+    all_rows: Option<Vec<ResumeRow>>,
+    /// A search request is in flight for the current query.
+    searching: bool,
+    /// Bumped on every query edit; a scheduled search only fires if it is
+    /// still the latest one when its debounce timer expires.
+    query_generation: Arc<AtomicU64>,
 }
 
 impl ResumeSelectionView {
@@ -64,9 +84,79 @@ impl ResumeSelectionView {
             viewport_rows: Cell::new(RESUME_POPUP_ROWS),
             complete: false,
             app_event_tx,
+            query: String::new(),
+            all_rows: None,
+            searching: false,
+            query_generation: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Replace the rows with search results, ignoring results for a query
+    /// that has since been edited.
+    pub fn apply_search_results(&mut self, query: &str, rows: Vec<ResumeRow>) {
+        if query != self.query {
+            return;
+        }
+        self.rows = rows;
+        self.searching = false;
+        self.selected = 0;
+        self.top = 0;
+    }
+
+    fn on_query_changed(&mut self) {
+        let generation = self.query_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.query.trim().is_empty() {
+            if let Some(rows) = self.all_rows.take() {
+                self.rows = rows;
+            }
+            self.searching = false;
+        } else {
+            if self.all_rows.is_none() {
+                self.all_rows = Some(std::mem::take(&mut self.rows));
+            }
+            self.searching = true;
+            let latest = self.query_generation.clone();
+            let tx = self.app_event_tx.clone();
+            let query = self.query.clone();
+            thread::spawn(move || {
+                thread::sleep(RESUME_SEARCH_DEBOUNCE);
+                if latest.load(Ordering::Relaxed) == generation {
+                    tx.send(AppEvent::ResumePickerSearch { query });
+                }
+            });
+        }
+        self.selected = 0;
+        self.top = 0;
+    }
+
+    fn search_line(&self) -> Line<'_> {
+        let dim = Style::default().fg(crate::colors::text_dim());
+        if self.query.is_empty() {
+            let hint = if self.subtitle.is_empty() {
+                "Type to search messages and commands"
+            } else {
+                self.subtitle.as_str()
+            };
+            return Line::from(Span::styled(hint, dim));
+        }
+        let status = if self.searching {
+            "  searching…".to_string()
+        } else {
+            match self.rows.len() {
+                1 => "  1 match".to_string(),
+                n => format!("  {n} matches"),
+            }
+        };
+        Line::from(vec![
+            Span::styled("Search: ", dim),
+            Span::styled(
+                self.query.as_str(),
+                Style::default().fg(crate::colors::text_bright()),
+            ),
+            Span::styled(status, dim),
+        ])
+    }
+
     fn move_up(&mut self) {
         if self.rows.is_empty() {
             return;
@@ -157,7 +247,24 @@ impl BottomPaneView<'_> for ResumeSelectionView {
                     self.complete = true;
                 }
             }
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.on_query_changed();
+            }
             KeyCode::Esc => self.complete = true,
+            KeyCode::Backspace => {
+                if self.query.pop().is_some() {
+                    self.on_query_changed();
+                }
+            }
+            KeyCode::Char(c)
+                if !key_event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.query.push(c);
+                self.on_query_changed();
+            }
             _ => {}
         }
     }
@@ -175,12 +282,17 @@ impl BottomPaneView<'_> for ResumeSelectionView {
         ConditionalUpdate::NeedsRedraw
     }
 
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(self)
+    }
+
     fn desired_height(&self, _width: u16) -> u16 {
-        // Include block borders (+2), optional subtitle (+1), table header (+1),
-        // clamped rows, spacer (+1), footer (+1)
-        let rows = self.rows.len().min(RESUME_POPUP_ROWS).max(1) as u16;
-        let subtitle = if self.subtitle.is_empty() { 0 } else { 1 };
-        2 + subtitle + 1 + rows + 1 + 1
+        // Include block borders (+2), search/subtitle line (+1), table header (+1),
+        // clamped rows, spacer (+1), footer (+1). Size for the unfiltered list so
+        // the popup does not jump while searching.
+        let total = self.all_rows.as_ref().map_or(self.rows.len(), Vec::len);
+        let rows = total.min(RESUME_POPUP_ROWS).max(1) as u16;
+        2 + 1 + 1 + rows + 1 + 1
     }
 
     fn render(&self, area: Rect, buf: &mut Buffer) {
@@ -205,24 +317,18 @@ impl BottomPaneView<'_> for ResumeSelectionView {
         let inner = block.inner(area);
         block.render(area, buf);
 
-        // Optional subtitle (path, etc.)
+        // Search query, or the subtitle (path, etc.) while no query is typed
         let mut next_y = inner.y;
-        if !self.subtitle.is_empty() {
-            Paragraph::new(Line::from(Span::styled(
-                &self.subtitle,
-                Style::default().fg(crate::colors::text_dim()),
-            )))
-            .render(
-                Rect {
-                    x: inner.x.saturating_add(1),
-                    y: next_y,
-                    width: inner.width.saturating_sub(1),
-                    height: 1,
-                },
-                buf,
-            );
-            next_y = next_y.saturating_add(1);
-        }
+        Paragraph::new(self.search_line()).render(
+            Rect {
+                x: inner.x.saturating_add(1),
+                y: next_y,
+                width: inner.width.saturating_sub(1),
+                height: 1,
+            },
+            buf,
+        );
+        next_y = next_y.saturating_add(1);
 
         // Reserve one blank spacer line above the footer
         let footer_reserved: u16 = 2;
@@ -290,6 +396,21 @@ impl BottomPaneView<'_> for ResumeSelectionView {
             .column_spacing(1);
         table.render(table_area, buf);
 
+        if self.rows.is_empty() && !self.query.is_empty() && !self.searching {
+            Paragraph::new(Line::from(Span::styled(
+                "No sessions match this search",
+                Style::default().fg(crate::colors::text_dim()),
+            )))
+            .render(
+                Rect {
+                    y: table_area.y.saturating_add(header_rows),
+                    height: 1,
+                    ..table_area
+                },
+                buf,
+            );
+        }
+
         // Footer hints
         // Draw a spacer line above footer (implicit by not drawing into that row)
         let footer = Rect {
@@ -304,6 +425,8 @@ impl BottomPaneView<'_> for ResumeSelectionView {
                 Style::default().fg(crate::colors::light_blue()),
             ),
             Span::raw(" Navigate  "),
+            Span::styled("Type", Style::default().fg(crate::colors::light_blue())),
+            Span::raw(" Search  "),
             Span::styled("Enter", Style::default().fg(crate::colors::success())),
             Span::raw(" Select  "),
             Span::styled("Esc", Style::default().fg(crate::colors::error())),
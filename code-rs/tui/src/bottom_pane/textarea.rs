@@ -1,4 +1,5 @@
 use crate::util::buffer::fill_rect;
+use code_core::config_types::EditingMode;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
@@ -15,6 +16,10 @@ use textwrap::Options;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+mod vi;
+
+pub(crate) use vi::ViMode;
+
 #[derive(Debug)]
 pub(crate) struct TextArea {
     text: String,
@@ -24,6 +29,10 @@ pub(crate) struct TextArea {
     // Simple undo stack capturing full snapshots of text and cursor before edits.
     // This is intentionally simple to reliably undo paste and bulk edits across terminals.
     undo_stack: Vec<UndoSnapshot>,
+    /// Modal vi state; `None` in the default emacs-style editing mode.
+    vi: Option<vi::ViState>,
+    /// Range painted like a selection, e.g. the reverse-search match.
+    highlight: Option<Range<usize>>,
 }
 
 #[derive(Debug, Clone)]
@@ -52,14 +61,29 @@ impl TextArea {
             wrap_cache: RefCell::new(None),
             preferred_col: None,
             undo_stack: Vec::new(),
+            vi: None,
+            highlight: None,
         }
     }
 
+    pub fn set_editing_mode(&mut self, mode: EditingMode) {
+        self.vi = match mode {
+            EditingMode::Emacs => None,
+            EditingMode::Vi => Some(vi::ViState::default()),
+        };
+        self.vi_reset();
+    }
+
     pub fn set_text(&mut self, text: &str) {
         self.text = text.to_string();
         self.cursor_pos = self.cursor_pos.clamp(0, self.text.len());
         self.wrap_cache.replace(None);
         self.preferred_col = None;
+        self.highlight = None;
+    }
+
+    pub fn set_highlight(&mut self, range: Option<Range<usize>>) {
+        self.highlight = range.filter(|r| r.end <= self.text.len() && r.start < r.end);
     }
 
     pub fn text(&self) -> &str {
@@ -231,6 +255,9 @@ impl TextArea {
             },
             KeyEventKind::Press => { /* handle below */ }
         }
+        if self.vi_input(event) {
+            return;
+        }
         match event {
             // Some terminals (or configurations) send Control key chords as
             // C0 control characters without reporting the CONTROL modifier.
@@ -952,6 +979,8 @@ impl TextArea {
         let bg = crate::colors::background();
         let fg = crate::colors::text();
         let line_style = Style::default().bg(bg).fg(fg);
+        let selection = self.vi_selection().or_else(|| self.highlight.clone());
+        let selection_style = Style::default().bg(crate::colors::selection()).fg(fg);
         for (row, idx) in range.enumerate() {
             let r = &lines[idx];
             let y = area.y + row as u16;
@@ -961,7 +990,15 @@ impl TextArea {
             // Draw the text on top using theme foreground + background to preserve consistent look.
             if r.end > r.start {
                 let line_range = r.start..r.end - 1;
-                buf.set_string(area.x, y, &self.text[line_range], line_style);
+                buf.set_string(area.x, y, &self.text[line_range.clone()], line_style);
+                if let Some(sel) = &selection {
+                    let start = sel.start.clamp(line_range.start, line_range.end);
+                    let end = sel.end.clamp(line_range.start, line_range.end);
+                    if start < end {
+                        let x = area.x + self.text[line_range.start..start].width() as u16;
+                        buf.set_string(x, y, &self.text[start..end], selection_style);
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,655 @@
+//! Modal vi editing for the composer textarea.
+//!
+//! Insert mode reuses the regular (emacs-style) key handling; normal and
+//! visual mode interpret plain characters as commands. Supported: `h j k l`,
+//! `w b e`, `0 ^ $`, `gg G`, `i a I A o O`, `x D C`, `d`/`c`/`y` with a motion
+//! or `iw`, `dd cc yy`, `p P`, `u`, and `v` with `d x c y`.
+
+use super::TextArea;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum ViMode {
+    #[default]
+    Insert,
+    Normal,
+    Visual,
+}
+
+impl ViMode {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Insert => "INSERT",
+            Self::Normal => "NORMAL",
+            Self::Visual => "VISUAL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    /// First `g` of `gg`.
+    G,
+    Operator(Operator),
+    /// `i` after an operator, waiting for the text object.
+    InnerObject(Operator),
+}
+
+#[derive(Debug, Default)]
+pub(super) struct ViState {
+    mode: ViMode,
+    pending: Option<Pending>,
+    visual_anchor: usize,
+    register: String,
+    register_linewise: bool,
+    /// Undo depth when insert mode began, so leaving insert mode can fold the
+    /// whole insertion into a single undo step.
+    insert_undo_mark: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Word,
+    Punct,
+}
+
+fn class_of(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+impl TextArea {
+    /// Returns true when the key was consumed by vi normal/visual handling.
+    pub(super) fn vi_input(&mut self, event: KeyEvent) -> bool {
+        let Some(mode) = self.vi.as_ref().map(|vi| vi.mode) else {
+            return false;
+        };
+        if mode == ViMode::Insert {
+            return false;
+        }
+        let c = match event {
+            KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                ..
+            } => c,
+            KeyEvent {
+                code: KeyCode::Backspace,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => 'h',
+            // Arrows, Home/End and modifier chords keep their usual meaning.
+            _ => return false,
+        };
+        let pending = self.vi.as_mut().and_then(|vi| vi.pending.take());
+        match pending {
+            Some(Pending::G) => {
+                if c == 'g' {
+                    self.set_cursor(0);
+                }
+            }
+            Some(Pending::Operator(op)) => self.vi_operator(op, c),
+            Some(Pending::InnerObject(op)) => {
+                if c == 'w' {
+                    let range = self.vi_inner_word();
+                    self.vi_apply(op, range);
+                }
+            }
+            None if mode == ViMode::Visual => self.vi_visual_command(c),
+            None => self.vi_normal_command(c),
+        }
+        if self.vi_mode() != Some(ViMode::Insert) {
+            self.vi_clamp_cursor();
+        }
+        true
+    }
+
+    /// Handles Esc. Returns false when there was nothing to leave, so the
+    /// caller can apply its own Esc behaviour.
+    pub(crate) fn vi_escape(&mut self) -> bool {
+        let Some(vi) = self.vi.as_mut() else {
+            return false;
+        };
+        if vi.pending.take().is_some() {
+            return true;
+        }
+        match vi.mode {
+            ViMode::Normal => false,
+            ViMode::Visual => {
+                vi.mode = ViMode::Normal;
+                true
+            }
+            ViMode::Insert => {
+                self.vi_enter_normal();
+                // Like vi, the cursor steps back onto the last inserted char.
+                if self.cursor_pos > self.beginning_of_current_line() {
+                    self.move_cursor_left();
+                }
+                true
+            }
+        }
+    }
+
+    /// Whether Esc would be consumed by [`TextArea::vi_escape`].
+    pub(crate) fn vi_wants_escape(&self) -> bool {
+        self.vi
+            .as_ref()
+            .is_some_and(|vi| vi.pending.is_some() || vi.mode != ViMode::Normal)
+    }
+
+    pub(crate) fn vi_mode(&self) -> Option<ViMode> {
+        self.vi.as_ref().map(|vi| vi.mode)
+    }
+
+    /// Starts the next prompt in insert mode, like shell vi modes do.
+    pub(crate) fn vi_reset(&mut self) {
+        if let Some(vi) = self.vi.as_mut() {
+            vi.mode = ViMode::Insert;
+            vi.pending = None;
+            vi.insert_undo_mark = Some(self.undo_stack.len());
+        }
+    }
+
+    /// Byte range highlighted in visual mode.
+    pub(super) fn vi_selection(&self) -> Option<Range<usize>> {
+        let vi = self.vi.as_ref()?;
+        if vi.mode != ViMode::Visual {
+            return None;
+        }
+        let start = vi.visual_anchor.min(self.cursor_pos);
+        let end = vi.visual_anchor.max(self.cursor_pos);
+        Some(start..self.next_atomic_boundary(end))
+    }
+
+    fn vi_normal_command(&mut self, c: char) {
+        match c {
+            'i' => self.vi_enter_insert(),
+            'a' => {
+                if self.cursor_pos < self.end_of_current_line() {
+                    self.move_cursor_right();
+                }
+                self.vi_enter_insert();
+            }
+            'I' => {
+                self.set_cursor(self.first_non_blank_of_line());
+                self.vi_enter_insert();
+            }
+            'A' => {
+                self.set_cursor(self.end_of_current_line());
+                self.vi_enter_insert();
+            }
+            'o' => {
+                let eol = self.end_of_current_line();
+                self.insert_str_at(eol, "\n");
+                self.set_cursor(eol + 1);
+                self.vi_enter_insert();
+            }
+            'O' => {
+                let bol = self.beginning_of_current_line();
+                self.insert_str_at(bol, "\n");
+                self.set_cursor(bol);
+                self.vi_enter_insert();
+            }
+            'x' => {
+                let end = self.next_char_on_line(self.cursor_pos);
+                self.vi_apply(Operator::Delete, self.cursor_pos..end);
+            }
+            'D' => {
+                let eol = self.end_of_current_line();
+                self.vi_apply(Operator::Delete, self.cursor_pos..eol);
+            }
+            'C' => {
+                let eol = self.end_of_current_line();
+                self.vi_apply(Operator::Change, self.cursor_pos..eol);
+            }
+            'd' => self.vi_set_pending(Pending::Operator(Operator::Delete)),
+            'c' => self.vi_set_pending(Pending::Operator(Operator::Change)),
+            'y' => self.vi_set_pending(Pending::Operator(Operator::Yank)),
+            'g' => self.vi_set_pending(Pending::G),
+            'p' => self.vi_put(true),
+            'P' => self.vi_put(false),
+            'u' => self.undo(),
+            'v' => {
+                if let Some(vi) = self.vi.as_mut() {
+                    vi.mode = ViMode::Visual;
+                    vi.visual_anchor = self.cursor_pos;
+                }
+            }
+            c => {
+                if let Some(pos) = self.vi_motion(c) {
+                    self.set_cursor(pos);
+                }
+            }
+        }
+    }
+
+    fn vi_visual_command(&mut self, c: char) {
+        let op = match c {
+            'd' | 'x' => Operator::Delete,
+            'c' => Operator::Change,
+            'y' => Operator::Yank,
+            'g' => {
+                self.vi_set_pending(Pending::G);
+                return;
+            }
+            c => {
+                if let Some(pos) = self.vi_motion(c) {
+                    self.set_cursor(pos);
+                }
+                return;
+            }
+        };
+        let Some(range) = self.vi_selection() else {
+            return;
+        };
+        if let Some(vi) = self.vi.as_mut() {
+            vi.mode = ViMode::Normal;
+        }
+        self.vi_apply(op, range);
+    }
+
+    fn vi_operator(&mut self, op: Operator, c: char) {
+        let doubled = matches!(
+            (op, c),
+            (Operator::Delete, 'd') | (Operator::Change, 'c') | (Operator::Yank, 'y')
+        );
+        if doubled {
+            self.vi_apply_line(op);
+            return;
+        }
+        if c == 'i' {
+            self.vi_set_pending(Pending::InnerObject(op));
+            return;
+        }
+        let from = self.cursor_pos;
+        let range = match c {
+            // `cw` changes to the end of the word, like `ce`.
+            'w' if op == Operator::Change
+                && !self.char_at(from).is_some_and(char::is_whitespace) =>
+            {
+                from..self.next_atomic_boundary(self.word_end(from))
+            }
+            // `dw` on the last word of a line stops at the line end.
+            'w' => from..self.next_word_start(from).min(self.end_of_current_line()),
+            'e' => from..self.next_atomic_boundary(self.word_end(from)),
+            '$' => from..self.end_of_current_line(),
+            'l' => from..self.next_char_on_line(from),
+            c => match self.vi_motion(c) {
+                Some(to) => to.min(from)..to.max(from),
+                None => return,
+            },
+        };
+        self.vi_apply(op, range);
+    }
+
+    /// Cursor target for a motion key, if `c` is one.
+    fn vi_motion(&mut self, c: char) -> Option<usize> {
+        let pos = self.cursor_pos;
+        Some(match c {
+            'h' => {
+                if pos > self.beginning_of_current_line() {
+                    self.prev_atomic_boundary(pos)
+                } else {
+                    pos
+                }
+            }
+            'l' | ' ' => self.next_char_on_line(pos),
+            'j' => {
+                self.move_cursor_down();
+                return Some(self.cursor_pos);
+            }
+            'k' => {
+                self.move_cursor_up();
+                return Some(self.cursor_pos);
+            }
+            'w' => self.next_word_start(pos),
+            'b' => self.prev_word_start(pos),
+            'e' => self.word_end(pos),
+            '0' => self.beginning_of_current_line(),
+            '^' => self.first_non_blank_of_line(),
+            '$' => self.end_of_current_line(),
+            'G' => self.beginning_of_line(self.text.len()),
+            _ => return None,
+        })
+    }
+
+    fn vi_apply(&mut self, op: Operator, range: Range<usize>) {
+        if let Some(vi) = self.vi.as_mut() {
+            vi.register = self.text[range.clone()].to_string();
+            vi.register_linewise = false;
+        }
+        match op {
+            Operator::Yank => self.set_cursor(range.start),
+            Operator::Delete => {
+                if !range.is_empty() {
+                    self.replace_range(range.clone(), "");
+                }
+                self.set_cursor(range.start);
+            }
+            Operator::Change => {
+                if !range.is_empty() {
+                    self.replace_range(range.clone(), "");
+                }
+                self.set_cursor(range.start);
+                self.vi_enter_insert();
+            }
+        }
+    }
+
+    /// `dd`, `cc` and `yy`.
+    fn vi_apply_line(&mut self, op: Operator) {
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        if let Some(vi) = self.vi.as_mut() {
+            vi.register = self.text[bol..eol].to_string();
+            vi.register_linewise = true;
+        }
+        match op {
+            Operator::Yank => {}
+            Operator::Change => {
+                let start = self.first_non_blank_of_line();
+                if start < eol {
+                    self.replace_range(start..eol, "");
+                }
+                self.set_cursor(start);
+                self.vi_enter_insert();
+            }
+            Operator::Delete => {
+                // Take one adjoining newline with the line so no blank line is left.
+                let range = if eol < self.text.len() {
+                    bol..eol + 1
+                } else {
+                    bol.saturating_sub(1)..eol
+                };
+                if !range.is_empty() {
+                    self.replace_range(range.clone(), "");
+                }
+                self.set_cursor(self.beginning_of_line(range.start.min(self.text.len())));
+                self.set_cursor(self.first_non_blank_of_line());
+            }
+        }
+    }
+
+    fn vi_put(&mut self, after: bool) {
+        let Some((text, linewise)) = self
+            .vi
+            .as_ref()
+            .map(|vi| (vi.register.clone(), vi.register_linewise))
+        else {
+            return;
+        };
+        if text.is_empty() {
+            return;
+        }
+        if linewise {
+            let at = if after {
+                let eol = self.end_of_current_line();
+                self.insert_str_at(eol, &format!("\n{text}"));
+                eol + 1
+            } else {
+                let bol = self.beginning_of_current_line();
+                self.insert_str_at(bol, &format!("{text}\n"));
+                bol
+            };
+            self.set_cursor(at);
+        } else {
+            let at = if after {
+                self.next_char_on_line(self.cursor_pos)
+            } else {
+                self.cursor_pos
+            };
+            self.insert_str_at(at, &text);
+            self.set_cursor(self.prev_atomic_boundary(at + text.len()));
+        }
+    }
+
+    fn vi_set_pending(&mut self, pending: Pending) {
+        if let Some(vi) = self.vi.as_mut() {
+            vi.pending = Some(pending);
+        }
+    }
+
+    fn vi_enter_insert(&mut self) {
+        let depth = self.undo_stack.len();
+        if let Some(vi) = self.vi.as_mut() {
+            vi.mode = ViMode::Insert;
+            vi.insert_undo_mark = Some(depth);
+        }
+    }
+
+    fn vi_enter_normal(&mut self) {
+        let Some(vi) = self.vi.as_mut() else {
+            return;
+        };
+        vi.mode = ViMode::Normal;
+        // Keep only the snapshot taken before the first edit of the insert.
+        if let Some(mark) = vi.insert_undo_mark.take() {
+            self.undo_stack.truncate(mark + 1);
+        }
+    }
+
+    /// In normal mode the cursor rests on a character, never past the line end.
+    fn vi_clamp_cursor(&mut self) {
+        let bol = self.beginning_of_current_line();
+        if self.cursor_pos == self.end_of_current_line() && self.cursor_pos > bol {
+            self.cursor_pos = self.prev_atomic_boundary(self.cursor_pos);
+        }
+    }
+
+    fn char_at(&self, pos: usize) -> Option<char> {
+        self.text[pos..].chars().next()
+    }
+
+    fn char_before(&self, pos: usize) -> Option<char> {
+        self.text[..pos].chars().next_back()
+    }
+
+    fn next_char_on_line(&self, pos: usize) -> usize {
+        self.next_atomic_boundary(pos).min(self.end_of_line(pos))
+    }
+
+    fn first_non_blank_of_line(&self) -> usize {
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        self.text[bol..eol]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(eol, |i| bol + i)
+    }
+
+    fn next_word_start(&self, mut pos: usize) -> usize {
+        if let Some(class) = self.char_at(pos).map(class_of)
+            && class != CharClass::Space
+        {
+            while self.char_at(pos).is_some_and(|c| class_of(c) == class) {
+                pos = self.next_atomic_boundary(pos);
+            }
+        }
+        while self.char_at(pos).is_some_and(char::is_whitespace) {
+            pos = self.next_atomic_boundary(pos);
+        }
+        pos
+    }
+
+    fn prev_word_start(&self, mut pos: usize) -> usize {
+        while self.char_before(pos).is_some_and(char::is_whitespace) {
+            pos = self.prev_atomic_boundary(pos);
+        }
+        let Some(class) = self.char_before(pos).map(class_of) else {
+            return pos;
+        };
+        while self.char_before(pos).is_some_and(|c| class_of(c) == class) {
+            pos = self.prev_atomic_boundary(pos);
+        }
+        pos
+    }
+
+    /// Last character of the word ending after `pos` (vi `e`).
+    fn word_end(&self, pos: usize) -> usize {
+        let mut pos = self.next_atomic_boundary(pos);
+        while self.char_at(pos).is_some_and(char::is_whitespace) {
+            pos = self.next_atomic_boundary(pos);
+        }
+        let Some(class) = self.char_at(pos).map(class_of) else {
+            return self.prev_atomic_boundary(self.text.len());
+        };
+        loop {
+            let next = self.next_atomic_boundary(pos);
+            if !self.char_at(next).is_some_and(|c| class_of(c) == class) {
+                return pos;
+            }
+            pos = next;
+        }
+    }
+
+    /// The run of same-class characters under the cursor (vi `iw`).
+    fn vi_inner_word(&self) -> Range<usize> {
+        let Some(class) = self.char_at(self.cursor_pos).map(class_of) else {
+            return self.cursor_pos..self.cursor_pos;
+        };
+        let same = |c: char| class_of(c) == class && c != '\n';
+        let mut start = self.cursor_pos;
+        while self.char_before(start).is_some_and(same) {
+            start = self.prev_atomic_boundary(start);
+        }
+        let mut end = self.cursor_pos;
+        while self.char_at(end).is_some_and(same) {
+            end = self.next_atomic_boundary(end);
+        }
+        start..end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_core::config_types::EditingMode;
+    use pretty_assertions::assert_eq;
+
+    fn vi_textarea(text: &str, cursor: usize) -> TextArea {
+        let mut textarea = TextArea::new();
+        textarea.set_editing_mode(EditingMode::Vi);
+        textarea.set_text(text);
+        textarea.set_cursor(cursor);
+        assert!(textarea.vi_escape());
+        textarea.set_cursor(cursor);
+        textarea
+    }
+
+    fn keys(textarea: &mut TextArea, keys: &str) {
+        for c in keys.chars() {
+            textarea.input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn escape_leaves_insert_mode() {
+        let mut textarea = TextArea::new();
+        textarea.set_editing_mode(EditingMode::Vi);
+        keys(&mut textarea, "hello");
+        assert_eq!(textarea.vi_mode(), Some(ViMode::Insert));
+        assert!(textarea.vi_wants_escape());
+
+        assert!(textarea.vi_escape());
+        assert_eq!(textarea.vi_mode(), Some(ViMode::Normal));
+        assert_eq!(textarea.cursor(), 4);
+        assert!(!textarea.vi_escape());
+
+        // The whole insertion undoes in one step.
+        keys(&mut textarea, "u");
+        assert_eq!(textarea.text(), "");
+    }
+
+    #[test]
+    fn word_motions() {
+        let mut textarea = vi_textarea("foo.bar baz", 0);
+        keys(&mut textarea, "w");
+        assert_eq!(textarea.cursor(), 3);
+        keys(&mut textarea, "w");
+        assert_eq!(textarea.cursor(), 4);
+        keys(&mut textarea, "e");
+        assert_eq!(textarea.cursor(), 6);
+        keys(&mut textarea, "w$");
+        assert_eq!(textarea.cursor(), 10);
+        keys(&mut textarea, "bb0");
+        assert_eq!(textarea.cursor(), 0);
+    }
+
+    #[test]
+    fn dd_deletes_line_and_p_puts_it_back() {
+        let mut textarea = vi_textarea("one\ntwo\nthree", 5);
+        keys(&mut textarea, "dd");
+        assert_eq!(textarea.text(), "one\nthree");
+        assert_eq!(textarea.cursor(), 4);
+
+        keys(&mut textarea, "p");
+        assert_eq!(textarea.text(), "one\nthree\ntwo");
+
+        keys(&mut textarea, "Gdd");
+        assert_eq!(textarea.text(), "one\nthree");
+    }
+
+    #[test]
+    fn ciw_changes_word_under_cursor() {
+        let mut textarea = vi_textarea("fix the bug now", 9);
+        keys(&mut textarea, "ciw");
+        assert_eq!(textarea.text(), "fix the  now");
+        assert_eq!(textarea.vi_mode(), Some(ViMode::Insert));
+        keys(&mut textarea, "issue");
+        assert_eq!(textarea.text(), "fix the issue now");
+    }
+
+    #[test]
+    fn operators_with_motions() {
+        let mut textarea = vi_textarea("alpha beta gamma", 0);
+        keys(&mut textarea, "dw");
+        assert_eq!(textarea.text(), "beta gamma");
+        keys(&mut textarea, "cwdelta");
+        assert_eq!(textarea.text(), "delta gamma");
+        assert!(textarea.vi_escape());
+        keys(&mut textarea, "0yeP");
+        assert_eq!(textarea.text(), "deltadelta gamma");
+        keys(&mut textarea, "$D");
+        assert_eq!(textarea.text(), "deltadelta gamm");
+    }
+
+    #[test]
+    fn visual_selection_yank_and_delete() {
+        let mut textarea = vi_textarea("select this text", 7);
+        keys(&mut textarea, "ve");
+        assert_eq!(textarea.vi_selection(), Some(7..11));
+        keys(&mut textarea, "y");
+        assert_eq!(textarea.vi_mode(), Some(ViMode::Normal));
+        assert_eq!(textarea.cursor(), 7);
+
+        keys(&mut textarea, "0vd");
+        assert_eq!(textarea.text(), "elect this text");
+        keys(&mut textarea, "$p");
+        assert_eq!(textarea.text(), "elect this texts");
+    }
+
+    #[test]
+    fn pending_operator_cancels_on_escape() {
+        let mut textarea = vi_textarea("keep", 0);
+        keys(&mut textarea, "d");
+        assert!(textarea.vi_wants_escape());
+        assert!(textarea.vi_escape());
+        keys(&mut textarea, "w");
+        assert_eq!(textarea.text(), "keep");
+    }
+}
@@ -28,6 +28,7 @@ use std::time::SystemTime;
 use crate::auto_drive_strings;
 use crate::auto_drive_style::AutoDriveVariant;
 use crate::header_wave::HeaderWaveEffect;
+use crate::keymap::Keymap;
 use crate::spinner;
 use crate::thread_spawner;
 use ratatui::style::Modifier;
@@ -212,10 +213,13 @@ use code_auto_drive_core::AutoTurnAgentsAction;
 use code_auto_drive_core::AutoTurnAgentsTiming;
 use code_auto_drive_core::AutoTurnCliAction;
 use code_auto_drive_core::AutoTurnReviewState;
+use code_auto_drive_core::BudgetAlertType;
 use code_auto_drive_core::CoordinatorContext;
 use code_auto_drive_core::CoordinatorRouterResponse;
 use code_auto_drive_core::TurnConfig;
 use code_auto_drive_core::TurnDescriptor;
+use code_auto_drive_core::budget::BudgetAlert;
+use code_auto_drive_core::budget::BudgetConfig;
 use code_auto_drive_core::route_user_message;
 use code_auto_drive_core::start_auto_coordinator;
 use code_core::TextFormat;
@@ -246,8 +250,11 @@ use code_core::protocol::ExecCommandBeginEvent;
 use code_core::protocol::ExecCommandEndEvent;
 use code_core::protocol::ExecOutputStream;
 use code_core::protocol::InputItem;
+use code_core::protocol::McpSamplingRequestEvent;
 use code_core::protocol::SandboxPolicy;
 use code_core::protocol::SessionConfiguredEvent;
+use code_core::user_notification::UserNotification;
+use code_core::user_notification::UserNotifier;
 // MCP tool call handlers moved into chatwidget::tools
 use crate::bottom_pane::AutoActiveViewModel;
 use crate::bottom_pane::AutoCoordinatorButton;
@@ -266,6 +273,7 @@ use crate::bottom_pane::UpdateSettingsView;
 use crate::bottom_pane::ValidationSettingsView;
 use crate::bottom_pane::agent_editor_view::AgentEditorView;
 use crate::bottom_pane::agents_settings_view::SubagentEditorView;
+use crate::bottom_pane::mcp_catalog::McpCatalog;
 use crate::bottom_pane::mcp_settings_view::McpServerRow;
 use crate::bottom_pane::mcp_settings_view::McpServerRows;
 use crate::bottom_pane::prompt_args;
@@ -301,6 +309,8 @@ pub(crate) enum EscIntent {
     DismissModal,
     CloseSettings,
     CloseFilePopup,
+    CancelHistorySearch,
+    ViNormalMode,
     AutoPauseForEdit,
     AutoStopDuringApproval,
     AutoStopActive,
@@ -1107,6 +1117,9 @@ use code_core::config_types::validation_tool_category;
 use code_core::protocol::RateLimitSnapshotEvent;
 use code_core::protocol::ValidationGroup;
 use code_core::review_format::format_review_findings_block;
+use code_core::review_prompts::branch_review_prompt;
+use code_core::review_prompts::commit_review_prompt;
+use code_core::review_prompts::workspace_review_prompt;
 use code_file_search::FileMatch;
 use code_protocol::models::ContentItem;
 use code_protocol::models::ResponseItem;
@@ -1591,6 +1604,9 @@ pub(crate) struct ChatWidget<'a> {
     replay_history_depth: usize,
     resume_placeholder_visible: bool,
     resume_picker_loading: bool,
+    // Delivers Auto Drive notifications to `[[notification_sinks]]`; built once
+    // so webhook deliveries share one HTTP client.
+    notifier: UserNotifier,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -3631,6 +3647,41 @@ impl ChatWidget<'_> {
         self.request_redraw();
     }
 
+    pub(crate) fn search_resume_picker(&mut self, query: String) {
+        let cwd = self.config.cwd.clone();
+        let code_home = self.config.code_home.clone();
+        let exclude_path = self.config.experimental_resume.clone();
+        let tx = self.app_event_tx.clone();
+
+        tokio::spawn(async move {
+            let search_query = query.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                crate::resume::discovery::search_sessions_for_cwd(
+                    &cwd,
+                    &code_home,
+                    exclude_path.as_deref(),
+                    &search_query,
+                )
+            })
+            .await;
+            let candidates = result.unwrap_or_else(|err| {
+                tracing::warn!("resume picker search failed: {err}");
+                Vec::new()
+            });
+            tx.send(AppEvent::ResumePickerSearchResults { query, candidates });
+        });
+    }
+
+    pub(crate) fn present_resume_search_results(
+        &mut self,
+        query: String,
+        candidates: Vec<crate::resume::discovery::ResumeCandidate>,
+    ) {
+        let rows = Self::resume_rows_from_candidates(candidates);
+        self.bottom_pane.apply_resume_search_results(&query, rows);
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_resume_picker_load_failed(&mut self, message: String) {
         self.resume_picker_loading = false;
         self.bottom_pane.flash_footer_notice(message);
@@ -4179,6 +4230,33 @@ impl ChatWidget<'_> {
         );
     }
 
+    /// Handle an MCP sampling approval request immediately
+    fn handle_mcp_sampling_request_now(&mut self, ev: McpSamplingRequestEvent) {
+        const PREVIEW_MAX_CHARS: usize = 300;
+        let text: Vec<&str> = ev
+            .messages
+            .iter()
+            .filter_map(|message| match &message.content {
+                mcp_types::SamplingMessageContent::TextContent(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        let joined = text.join("\n");
+        let mut preview: String = joined.chars().take(PREVIEW_MAX_CHARS).collect();
+        if joined.chars().count() > PREVIEW_MAX_CHARS {
+            preview.push('…');
+        }
+        let ticket = self.make_background_before_next_output_ticket();
+        self.bottom_pane.push_approval_request(
+            ApprovalRequest::McpSampling {
+                id: ev.id,
+                server: ev.server,
+                preview,
+            },
+            ticket,
+        );
+    }
+
     /// Handle apply patch approval request immediately
     fn handle_apply_patch_approval_now(&mut self, _id: String, ev: ApplyPatchApprovalRequestEvent) {
         let ApplyPatchApprovalRequestEvent {
@@ -5168,6 +5246,8 @@ impl ChatWidget<'_> {
             enhanced_keys_supported,
             using_chatgpt_auth: config.using_chatgpt_auth,
             auto_drive_variant,
+            keymap: Keymap::from_config(&config.tui.keymap),
+            editing_mode: config.tui.editing_mode,
         });
 
         let mut new_widget = Self {
@@ -5370,6 +5450,7 @@ impl ChatWidget<'_> {
             replay_history_depth: 0,
             resume_placeholder_visible: false,
             resume_picker_loading: false,
+            notifier: UserNotifier::new(None, config.notification_sinks.clone()),
         };
         new_widget.spawn_conversation_runtime(config.clone(), auth_manager, code_op_rx);
         if let Ok(Some(active_id)) = auth_accounts::get_active_account_id(&config.code_home)
@@ -5484,6 +5565,8 @@ impl ChatWidget<'_> {
             enhanced_keys_supported,
             using_chatgpt_auth: config.using_chatgpt_auth,
             auto_drive_variant,
+            keymap: Keymap::from_config(&config.tui.keymap),
+            editing_mode: config.tui.editing_mode,
         });
 
         let mut w = Self {
@@ -5699,6 +5782,7 @@ impl ChatWidget<'_> {
             replay_history_depth: 0,
             resume_placeholder_visible: false,
             resume_picker_loading: false,
+            notifier: UserNotifier::new(None, config.notification_sinks.clone()),
         };
         if let Ok(Some(active_id)) = auth_accounts::get_active_account_id(&config.code_home)
             && let Ok(records) = account_usage::list_rate_limit_snapshots(&config.code_home)
@@ -9403,6 +9487,23 @@ impl ChatWidget<'_> {
             }
         }
 
+        // Resolve "/mcp:server:prompt" invocations into prompts rendered by core.
+        match prompt_args::expand_mcp_prompt(&text_only, self.bottom_pane.mcp_catalog()) {
+            Ok(Some(item)) => {
+                message.ordered_items.clear();
+                message.ordered_items.push(item);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                self.history_push_plain_state(history_cell::new_error_event(err.user_message()));
+                return;
+            }
+        }
+
+        // Attach MCP resources mentioned as `@server:uri`.
+        let resource_items = self.bottom_pane.mcp_catalog().resource_mentions(&text_only);
+        message.ordered_items.extend(resource_items);
+
         // Save the prompt if it's a multi-agent command
         let original_trimmed = original_text.trim();
         if original_trimmed.starts_with("/plan ")
@@ -11184,6 +11285,10 @@ impl ChatWidget<'_> {
                 self.session_id = Some(event.session_id);
                 self.bottom_pane
                     .set_history_metadata(event.history_log_id, event.history_entry_count);
+                self.bottom_pane
+                    .set_history_repo_root(code_core::git_info::get_git_repo_root(
+                        &self.config.cwd,
+                    ));
                 // Record session information at the top of the conversation.
                 // If we already showed the startup prelude (Popular commands),
                 // avoid inserting a duplicate. Still surface a notice if the
@@ -11213,6 +11318,8 @@ impl ChatWidget<'_> {
 
                 // Ask core for custom prompts so the slash menu can show them.
                 self.submit_op(Op::ListCustomPrompts);
+                // Likewise for MCP prompts (slash menu) and resources (`@` popup).
+                self.submit_op(Op::ListMcpCatalog);
 
                 if self.resume_placeholder_visible && event.history_entry_count == 0 {
                     self.replace_resume_placeholder_with_notice(RESUME_NO_HISTORY_NOTICE);
@@ -11686,6 +11793,10 @@ impl ChatWidget<'_> {
                 if let Some(info) = &event.info {
                     self.total_token_usage = info.total_token_usage.clone();
                     self.last_token_usage = info.last_token_usage.clone();
+                    let alert = self
+                        .auto_state
+                        .record_cli_spend(&self.config.model, &info.total_token_usage);
+                    self.auto_handle_cost_alert(alert);
                 }
                 if let Some(snapshot) = event.rate_limits {
                     self.update_rate_limit_resets(&snapshot);
@@ -11792,6 +11903,11 @@ impl ChatWidget<'_> {
                 self.apply_plan_terminal_title(desired_title);
             }
             EventMsg::ExecApprovalRequest(ev) => {
+                self.emit_tui_notification(&UserNotification::ApprovalRequested {
+                    turn_id: id.clone(),
+                    call_id: ev.call_id.clone(),
+                    summary: strip_bash_lc_and_escape(&ev.command),
+                });
                 let id2 = id.clone();
                 let ev2 = ev.clone();
                 let seq = event.event_seq;
@@ -11806,6 +11922,15 @@ impl ChatWidget<'_> {
                 );
             }
             EventMsg::ApplyPatchApprovalRequest(ev) => {
+                let file_count = ev.changes.len();
+                self.emit_tui_notification(&UserNotification::ApprovalRequested {
+                    turn_id: id.clone(),
+                    call_id: ev.call_id.clone(),
+                    summary: format!(
+                        "apply patch to {file_count} file{}",
+                        if file_count == 1 { "" } else { "s" }
+                    ),
+                });
                 let id2 = id.clone();
                 let ev2 = ev.clone();
                 self.defer_or_handle(
@@ -12570,11 +12695,30 @@ impl ChatWidget<'_> {
                 self.bottom_pane
                     .on_history_entry_response(log_id, offset, entry.map(|e| e.text));
             }
+            EventMsg::SearchHistoryResponse(ev) => {
+                let matches = ev.matches.into_iter().map(|m| m.text).collect();
+                self.bottom_pane
+                    .on_history_search_response(&ev.query, ev.repo_only, matches);
+            }
             EventMsg::ListCustomPromptsResponse(ev) => {
                 let len = ev.custom_prompts.len();
                 debug!("received {len} custom prompts");
                 self.bottom_pane.set_custom_prompts(ev.custom_prompts);
             }
+            EventMsg::ListMcpCatalogResponse(ev) => {
+                let catalog = McpCatalog::from_event(ev);
+                debug!("received {} MCP prompts", catalog.prompts().len());
+                self.bottom_pane.set_mcp_catalog(catalog);
+            }
+            EventMsg::McpSamplingRequest(ev) => {
+                // Server-initiated; not tied to the active stream, so surface it now.
+                self.handle_mcp_sampling_request_now(ev);
+                self.request_redraw();
+            }
+            EventMsg::McpElicitationRequest(ev) => {
+                self.bottom_pane.show_mcp_elicitation(ev);
+                self.request_redraw();
+            }
             EventMsg::ShutdownComplete => {
                 self.push_background_tail("🟡 ShutdownComplete".to_string());
                 self.app_event_tx.send(AppEvent::ExitRequest);
@@ -12677,6 +12821,17 @@ impl ChatWidget<'_> {
 
                 self.update_agents_terminal_state(&agents, context.clone(), task.clone());
 
+                for agent in &agents {
+                    if let Some(report) = agent.token_usage.as_ref() {
+                        let alert = self.auto_state.record_agent_spend(
+                            &agent.id,
+                            &report.model,
+                            &report.usage,
+                        );
+                        self.auto_handle_cost_alert(alert);
+                    }
+                }
+
                 // Store shared context and task
                 self.agent_context = context;
                 self.agent_task = task;
@@ -13168,6 +13323,7 @@ impl ChatWidget<'_> {
                             PathBuf::from(path),
                             code_core::protocol::FileChange::Add {
                                 content: (*content).to_string(),
+                                mode: None,
                             },
                         );
                         format!("patch: simulated failure while applying {path}")
@@ -13185,6 +13341,7 @@ impl ChatWidget<'_> {
                                 move_path: None,
                                 original_content: (*original).to_string(),
                                 new_content: (*new_content).to_string(),
+                                mode: None,
                             },
                         );
                         format!("patch: simulated failure while applying {path}")
@@ -13391,6 +13548,7 @@ impl ChatWidget<'_> {
             turns_completed: 4,
             message: Some("Auto Drive completed demo run.".to_string()),
             goal: Some(goal),
+            total_cost_usd: Some(1.84),
         });
         let celebration_message = "Diagnostics report: all demo checks passed.".to_string();
         self.auto_state.last_completion_explanation = Some(celebration_message.clone());
@@ -15078,6 +15236,7 @@ fi\n\
             status_sent_to_user: None,
             status_title: None,
             session_tokens: self.auto_session_tokens(),
+            session_cost_usd: None,
             editing_prompt: false,
             intro_started_at: self.auto_state.intro_started_at,
             intro_reduced_motion: self.auto_state.intro_reduced_motion,
@@ -15149,6 +15308,15 @@ fi\n\
             continue_mode,
             reduced_motion,
         );
+        self.auto_state.configure_budget(
+            BudgetConfig {
+                cost_budget_usd: self.config.auto_drive.cost_budget_usd,
+                pricing: self.config.auto_drive.pricing.clone(),
+                ..Default::default()
+            },
+            &self.total_token_usage,
+            self.agent_runtime.keys().cloned(),
+        );
         self.config.auto_drive.cross_check_enabled = cross_check_enabled;
         self.config.auto_drive.qa_automation_enabled = qa_automation_enabled;
         let coordinator_events = {
@@ -15259,10 +15427,7 @@ fi\n\
         };
 
         let mut auto_config = self.config.clone();
-        auto_config.model = self.config.auto_drive.model.trim().to_string();
-        if auto_config.model.is_empty() {
-            auto_config.model = code_auto_drive_core::MODEL_SLUG.to_string();
-        }
+        auto_config.model = self.auto_coordinator_model().to_string();
         auto_config.model_reasoning_effort = self.config.auto_drive.model_reasoning_effort;
 
         match start_auto_coordinator(
@@ -15856,6 +16021,15 @@ Have we met every part of this goal and is there no further work to do?"#
         self.request_redraw();
     }
 
+    fn auto_coordinator_model(&self) -> &str {
+        let model = self.config.auto_drive.model.trim();
+        if model.is_empty() {
+            code_auto_drive_core::MODEL_SLUG
+        } else {
+            model
+        }
+    }
+
     pub(crate) fn auto_handle_token_metrics(
         &mut self,
         total_usage: TokenUsage,
@@ -15864,6 +16038,11 @@ Have we met every part of this goal and is there no further work to do?"#
         duplicate_items: u32,
         replay_updates: u32,
     ) {
+        let model = self.auto_coordinator_model().to_string();
+        let alert = self
+            .auto_state
+            .record_coordinator_spend(&model, &total_usage);
+        self.auto_handle_cost_alert(alert);
         self.auto_history.apply_token_metrics(
             total_usage,
             last_turn_usage,
@@ -15930,6 +16109,10 @@ Have we met every part of this goal and is there no further work to do?"#
 
     pub(crate) fn auto_handle_budget_alert(&mut self, alert_type: &str, message: &str) {
         self.auto_state.budget_alert = Some(format!("{alert_type}: {message}"));
+        self.emit_auto_drive_notification(UserNotification::BudgetAlert {
+            message: message.to_string(),
+            exceeded: !alert_type.ends_with("Warning"),
+        });
         self.history_push_plain_paragraphs(
             PlainMessageKind::Notice,
             [format!("⚠️ Budget: {message}")],
@@ -15938,6 +16121,25 @@ Have we met every part of this goal and is there no further work to do?"#
         self.request_redraw();
     }
 
+    /// Surfaces a cost alert from the run's budget and stops the run once the
+    /// cost budget is spent.
+    fn auto_handle_cost_alert(&mut self, alert: Option<BudgetAlert>) {
+        let Some(alert) = alert else {
+            return;
+        };
+        let exceeded = matches!(alert, BudgetAlert::CostExceeded { .. });
+        let alert_type = if exceeded {
+            BudgetAlertType::CostExceeded
+        } else {
+            BudgetAlertType::CostWarning
+        };
+        let message = alert.message();
+        self.auto_handle_budget_alert(&format!("{alert_type:?}"), &message);
+        if exceeded && self.auto_state.is_active() {
+            self.auto_stop(Some(format!("Auto Drive stopped: {message}.")));
+        }
+    }
+
     pub(crate) fn auto_handle_intervention_required(&mut self, reason: &str) {
         self.auto_state.intervention_reason = Some(reason.to_string());
         self.history_push_plain_paragraphs(
@@ -16055,6 +16257,11 @@ Have we met every part of this goal and is there no further work to do?"#
                         Duration::from_secs(0),
                         self.auto_state.last_completion_explanation.clone(),
                     );
+                    self.emit_auto_drive_notification(UserNotification::AutoDriveFinished {
+                        goal: summary.goal.clone(),
+                        turns_completed: summary.turns_completed,
+                        message: final_message,
+                    });
                     self.auto_turn_review_state = None;
                     if ENABLE_WARP_STRIPES {
                         self.header_wave.set_enabled(false, Instant::now());
@@ -16806,7 +17013,7 @@ Have we met every part of this goal and is there no further work to do?"#
                     (prompt, hint, auto_metadata, review_metadata, preparation)
                 }
                 None => {
-                    let prompt = "Review the current workspace changes and highlight bugs, regressions, risky patterns, and missing tests before merge.".to_string();
+                    let prompt = workspace_review_prompt();
                     let hint = "current workspace changes".to_string();
                     let review_metadata = Some(ReviewContextMetadata {
                         scope: Some("workspace".to_string()),
@@ -16922,6 +17129,7 @@ Have we met every part of this goal and is there no further work to do?"#
                     status_sent_to_user: None,
                     status_title: None,
                     session_tokens: self.auto_session_tokens(),
+                    session_cost_usd: summary.total_cost_usd,
                     editing_prompt: false,
                     intro_started_at: self.auto_state.intro_started_at,
                     intro_reduced_motion: self.auto_state.intro_reduced_motion,
@@ -17150,6 +17358,7 @@ Have we met every part of this goal and is there no further work to do?"#
             status_sent_to_user: status_sent_to_user_for_view,
             status_title: status_title_for_view,
             session_tokens: self.auto_session_tokens(),
+            session_cost_usd: self.auto_state.budget.cost_usd(),
             cli_context,
             show_composer,
             editing_prompt: self.auto_state.is_paused_manual(),
@@ -18709,6 +18918,7 @@ Have we met every part of this goal and is there no further work to do?"#
                     move_path: None,
                     original_content: baseline.clone(),
                     new_content: current.clone(),
+                    mode: None,
                 },
             );
             let detail = create_diff_details_only(&single);
@@ -18829,7 +19039,8 @@ Have we met every part of this goal and is there no further work to do?"#
 
         // Global
         lines.push(kv("Ctrl+H", "Help overlay"));
-        lines.push(kv("Ctrl+R", "Toggle reasoning"));
+        lines.push(kv("Ctrl+R", "Search prompt history"));
+        lines.push(kv("Alt+R", "Toggle reasoning"));
         lines.push(kv("Ctrl+T", "Toggle screen"));
         lines.push(kv("Ctrl+D", "Diff viewer"));
         lines.push(kv(
@@ -19400,7 +19611,7 @@ Have we met every part of this goal and is there no further work to do?"#
         // If we're currently in plan mode, switch the session model immediately.
         if matches!(
             self.config.sandbox_policy,
-            code_core::protocol::SandboxPolicy::ReadOnly
+            code_core::protocol::SandboxPolicy::ReadOnly { .. }
         ) {
             self.apply_planning_session_model();
         }
@@ -19507,7 +19718,7 @@ Have we met every part of this goal and is there no further work to do?"#
 
         if matches!(
             self.config.sandbox_policy,
-            code_core::protocol::SandboxPolicy::ReadOnly
+            code_core::protocol::SandboxPolicy::ReadOnly { .. }
         ) {
             self.apply_planning_session_model();
         }
@@ -20796,7 +21007,7 @@ Have we met every part of this goal and is there no further work to do?"#
         use code_core::protocol::AskForApproval;
         use code_core::protocol::SandboxPolicy;
         let label = match (&self.config.sandbox_policy, self.config.approval_policy) {
-            (SandboxPolicy::ReadOnly, _) => Some("Read Only".to_string()),
+            (SandboxPolicy::ReadOnly { .. }, _) => Some("Read Only".to_string()),
             (
                 SandboxPolicy::WorkspaceWrite {
                     network_access: false,
@@ -20817,7 +21028,7 @@ Have we met every part of this goal and is there no further work to do?"#
 
         // Determine current index
         let idx = match (&self.config.sandbox_policy, self.config.approval_policy) {
-            (SandboxPolicy::ReadOnly, _) => 0,
+            (SandboxPolicy::ReadOnly { .. }, _) => 0,
             (
                 SandboxPolicy::WorkspaceWrite {
                     network_access: false,
@@ -20835,7 +21046,7 @@ Have we met every part of this goal and is there no further work to do?"#
             0 => (
                 "Read Only (Plan Mode)",
                 AskForApproval::OnRequest,
-                SandboxPolicy::ReadOnly,
+                SandboxPolicy::new_read_only_policy(),
             ),
             1 => (
                 "Write with Approval",
@@ -20856,9 +21067,10 @@ Have we met every part of this goal and is there no further work to do?"#
             self.restore_planning_session_model();
         }
 
-        // Update local config
+        // Update local config, keeping any configured read denials.
+        let deny_read = self.config.sandbox_policy.deny_read().to_vec();
         self.config.approval_policy = approval;
-        self.config.sandbox_policy = sandbox;
+        self.config.sandbox_policy = sandbox.with_deny_read(deny_read);
 
         // Send ConfigureSession op to backend
         let op = Op::ConfigureSession {
@@ -20884,7 +21096,9 @@ Have we met every part of this goal and is there no further work to do?"#
             &self.config.cwd,
             self.config.approval_policy,
             match &self.config.sandbox_policy {
-                SandboxPolicy::ReadOnly => code_protocol::config_types::SandboxMode::ReadOnly,
+                SandboxPolicy::ReadOnly { .. } => {
+                    code_protocol::config_types::SandboxMode::ReadOnly
+                }
                 SandboxPolicy::WorkspaceWrite { .. } => {
                     code_protocol::config_types::SandboxMode::WorkspaceWrite
                 }
@@ -21233,6 +21447,14 @@ Have we met every part of this goal and is there no further work to do?"#
             return EscRoute::new(EscIntent::CloseFilePopup, false, false);
         }
 
+        if self.bottom_pane.composer_is_history_searching() {
+            return EscRoute::new(EscIntent::CancelHistorySearch, true, false);
+        }
+
+        if self.bottom_pane.composer_vi_wants_escape() {
+            return EscRoute::new(EscIntent::ViNormalMode, true, false);
+        }
+
         if self.auto_state.is_active() {
             let awaiting_continue_cta = self.auto_should_show_continue_cta();
 
@@ -21308,6 +21530,11 @@ Have we met every part of this goal and is there no further work to do?"#
                 true
             }
             EscIntent::CloseFilePopup => self.close_file_popup_if_active(),
+            EscIntent::CancelHistorySearch => {
+                self.bottom_pane.composer_cancel_history_search();
+                true
+            }
+            EscIntent::ViNormalMode => self.bottom_pane.composer_vi_escape(),
             EscIntent::AutoPauseForEdit => {
                 self.auto_pause_for_manual_edit(false);
                 true
@@ -21548,7 +21775,7 @@ Have we met every part of this goal and is there no further work to do?"#
             StreamKind::Reasoning => {
                 // This reasoning block is the bottom-most; show progress indicator here only
                 self.clear_reasoning_in_progress();
-                // Ensure footer shows Ctrl+R hint when reasoning content is present
+                // Ensure footer shows the reasoning toggle hint when reasoning content is present
                 self.bottom_pane.set_reasoning_hint(true);
                 // Update footer label to reflect current visibility state
                 self.bottom_pane
@@ -24000,7 +24227,7 @@ Have we met every part of this goal and is there no further work to do?"#
         }
     }
 
-    /// Handle `/mcp` command: manage MCP servers (status/on/off/add).
+    /// Handle `/mcp` command: manage MCP servers (status/on/off/restart/add).
     pub(crate) fn handle_mcp_command(&mut self, command_text: String) {
         let trimmed = command_text.trim();
         if trimmed.is_empty() {
@@ -24101,6 +24328,15 @@ Have we met every part of this goal and is there no further work to do?"#
                     }
                 }
             }
+            "restart" => {
+                let name = parts.next().unwrap_or("");
+                if name.is_empty() {
+                    let msg = "Usage: /mcp restart <name>".to_string();
+                    self.history_push_plain_state(history_cell::new_error_event(msg));
+                    return;
+                }
+                self.restart_mcp_server(name);
+            }
             "add" => {
                 // Support two forms:
                 //   1) /mcp add <name> <command> [args…] [ENV=VAL…]
@@ -24243,7 +24479,7 @@ Have we met every part of this goal and is there no further work to do?"#
             }
             _ => {
                 let msg = format!(
-                    "Unknown MCP command: '{sub}'\nUsage:\n  /mcp status\n  /mcp on <name>\n  /mcp off <name>\n  /mcp add <name> <command> [args…] [ENV=VAL…]"
+                    "Unknown MCP command: '{sub}'\nUsage:\n  /mcp status\n  /mcp on <name>\n  /mcp off <name>\n  /mcp restart <name>\n  /mcp add <name> <command> [args…] [ENV=VAL…]"
                 );
                 self.history_push_plain_state(history_cell::new_error_event(msg));
             }
@@ -25024,7 +25260,7 @@ Have we met every part of this goal and is there no further work to do?"#
                 ));
             }
 
-            // Footer already shows the Ctrl+R hint; avoid duplicating it here.
+            // Footer already shows the reasoning toggle hint; avoid duplicating it here.
 
             spans
         };
@@ -26124,6 +26360,7 @@ mod tests {
             turns_completed: 3,
             message: Some("All tasks done.".to_string()),
             goal: Some("Finish feature".to_string()),
+            total_cost_usd: None,
         });
         chat.auto_show_goal_entry_panel();
         chat.handle_paste("Suggested goal".to_string());
@@ -27190,6 +27427,7 @@ mod tests {
                     error: None,
                     elapsed_ms: None,
                     token_count: None,
+                    token_usage: None,
                 }],
                 context: None,
                 task: None,
@@ -27261,6 +27499,7 @@ mod tests {
                     error: None,
                     elapsed_ms: None,
                     token_count: None,
+                    token_usage: None,
                 }],
                 context: None,
                 task: None,
@@ -27302,6 +27541,7 @@ mod tests {
                     error: None,
                     elapsed_ms: None,
                     token_count: None,
+                    token_usage: None,
                 }],
                 context: None,
                 task: None,
@@ -28243,15 +28483,7 @@ impl ChatWidget<'_> {
         {
             let short_sha: String = new_commit.chars().take(7).collect();
             let subject = self.commit_subject_for(&new_commit);
-            base_prompt = if let Some(subject) = subject {
-                format!(
-                    "Review the code changes introduced by commit {new_commit} (\"{subject}\"). Provide prioritized, actionable findings."
-                )
-            } else {
-                format!(
-                    "Review the code changes introduced by commit {new_commit}. Provide prioritized, actionable findings."
-                )
-            };
+            base_prompt = commit_review_prompt(&new_commit, subject.as_deref());
             next_hint = format!("commit {short_sha}");
             if let Some(meta) = next_metadata.as_mut() {
                 meta.commit = Some(new_commit);
@@ -28485,7 +28717,7 @@ impl ChatWidget<'_> {
             })],
         });
 
-        let workspace_prompt = "Review the current workspace changes (staged, unstaged, and untracked files) and highlight bugs, regressions, risky patterns, and missing tests before merge.".to_string();
+        let workspace_prompt = workspace_review_prompt();
         let workspace_hint = "current workspace changes".to_string();
         let workspace_preparation = "Preparing code review for current changes".to_string();
         let workspace_metadata = Some(ReviewContextMetadata {
@@ -28754,15 +28986,8 @@ impl ChatWidget<'_> {
             } else {
                 format!("{short_sha} — {subject}")
             };
-            let prompt = if subject.is_empty() {
-                format!(
-                    "Review the code changes introduced by commit {sha}. Provide prioritized, actionable findings."
-                )
-            } else {
-                format!(
-                    "Review the code changes introduced by commit {sha} (\"{subject}\"). Provide prioritized, actionable findings."
-                )
-            };
+            let prompt =
+                commit_review_prompt(&sha, (!subject.is_empty()).then_some(subject.as_str()));
             let hint = format!("commit {short_sha}");
             let preparation = format!("Preparing code review for commit {short_sha}");
             let prompt_closure = prompt.clone();
@@ -28861,15 +29086,7 @@ impl ChatWidget<'_> {
                 format!("Compare against {branch_trimmed}")
             };
 
-            let prompt = if let Some(current) = current_trimmed.as_ref() {
-                format!(
-                    "Review the code changes between the current branch '{current}' and '{branch_trimmed}'. Identify the intent of the changes in '{current}' and ensure no obvious gaps remain. Find all geniune bugs or regressions which need to be addressed before merging. Return ALL issues which need to be addressed, not just the first one you find."
-                )
-            } else {
-                format!(
-                    "Review the code changes that would merge into '{branch_trimmed}'. Identify bugs, regressions, risky patterns, and missing tests before merge."
-                )
-            };
+            let prompt = branch_review_prompt(branch_trimmed, current_trimmed.as_deref());
             let hint = format!("against {branch_trimmed}");
             let preparation = format!("Preparing code review against {branch_trimmed}");
             let prompt_closure = prompt.clone();
@@ -28928,6 +29145,89 @@ impl ChatWidget<'_> {
         );
     }
 
+    pub(crate) fn show_worktree_loading(&mut self) {
+        let loading_item = SelectionItem {
+            name: "Loading worktrees…".to_string(),
+            description: None,
+            is_current: true,
+            actions: Vec::new(),
+        };
+        let view = ListSelectionView::new(
+            " Branch worktrees ".to_string(),
+            Some("Checking ahead/behind and dirty state".to_string()),
+            Some("Esc cancel".to_string()),
+            vec![loading_item],
+            self.app_event_tx.clone(),
+            6,
+        );
+        self.bottom_pane
+            .show_list_selection("Branch worktrees".to_string(), None, None, view);
+    }
+
+    pub(crate) fn present_worktree_picker(
+        &mut self,
+        result: Result<Vec<code_core::git_worktree::BranchWorktree>, String>,
+    ) {
+        let worktrees = match result {
+            Ok(worktrees) if !worktrees.is_empty() => worktrees,
+            Ok(_) => {
+                self.bottom_pane.flash_footer_notice(
+                    "No branch worktrees — create one with /branch <task>".to_string(),
+                );
+                self.request_redraw();
+                return;
+            }
+            Err(err) => {
+                self.bottom_pane
+                    .flash_footer_notice(format!("`/worktrees` — {err}"));
+                self.request_redraw();
+                return;
+            }
+        };
+
+        let current_root = code_core::git_worktree::branch_worktree_root(&self.config.cwd);
+        let items: Vec<SelectionItem> = worktrees
+            .into_iter()
+            .map(|wt| {
+                let mut details: Vec<String> = Vec::new();
+                if let Some(task) = wt.task() {
+                    details.push(task.to_string());
+                }
+                if let Some(age) = wt.age_label() {
+                    details.push(age);
+                }
+                details.push(format!("↑{} ↓{}", wt.ahead, wt.behind));
+                details.push(if wt.is_dirty() {
+                    format!("dirty ({})", wt.dirty_files)
+                } else {
+                    "clean".to_string()
+                });
+                let path = wt.path.clone();
+                SelectionItem {
+                    name: wt.name(),
+                    description: Some(details.join(" · ")),
+                    is_current: current_root.as_deref() == Some(wt.path.as_path()),
+                    actions: vec![Box::new(
+                        move |tx: &crate::app_event_sender::AppEventSender| {
+                            tx.send(crate::app_event::AppEvent::SwitchCwd(path.clone(), None));
+                        },
+                    )],
+                }
+            })
+            .collect();
+
+        let view = ListSelectionView::new(
+            " Branch worktrees ".to_string(),
+            Some("Enter switches into the worktree; /merge merges it back".to_string()),
+            Some("Enter switch · Esc cancel".to_string()),
+            items,
+            self.app_event_tx.clone(),
+            10,
+        );
+        self.bottom_pane
+            .show_list_selection("Branch worktrees".to_string(), None, None, view);
+    }
+
     /// Handle `/review [focus]` command by starting a dedicated review session.
     pub(crate) fn handle_review_command(&mut self, args: String) {
         if self.is_task_running() {
@@ -28982,9 +29282,7 @@ impl ChatWidget<'_> {
                         (default_branch, current_branch)
                         && base_branch != current_branch
                     {
-                        let prompt = format!(
-                            "Review the code changes between the current branch '{current_branch}' and '{base_branch}'. Identify the intent of the changes in '{current_branch}' and ensure no obvious gaps remain. Find all geniune bugs or regressions which need to be addressed before merging. Return ALL issues which need to be addressed, not just the first one you find."
-                        );
+                        let prompt = branch_review_prompt(&base_branch, Some(&current_branch));
                         let hint = format!("against {base_branch}");
                         let preparation_label =
                             Some(format!("Preparing code review against {base_branch}"));
@@ -29005,15 +29303,15 @@ impl ChatWidget<'_> {
                     }
 
                     tx.send(crate::app_event::AppEvent::RunReviewWithScope {
-                            prompt: "Review the current workspace changes and highlight bugs, regressions, risky patterns, and missing tests before merge.".to_string(),
-                            hint: "current workspace changes".to_string(),
-                            preparation_label: Some("Preparing code review request...".to_string()),
-                            metadata: Some(ReviewContextMetadata {
-                                scope: Some("workspace".to_string()),
-                                ..Default::default()
-                            }),
-                            auto_resolve: auto_flag,
-                        });
+                        prompt: workspace_review_prompt(),
+                        hint: "current workspace changes".to_string(),
+                        preparation_label: Some("Preparing code review request...".to_string()),
+                        metadata: Some(ReviewContextMetadata {
+                            scope: Some("workspace".to_string()),
+                            ..Default::default()
+                        }),
+                        auto_resolve: auto_flag,
+                    });
                 });
                 return;
             }
@@ -29023,7 +29321,7 @@ impl ChatWidget<'_> {
                 ..Default::default()
             };
             self.start_review_with_scope(
-                "Review the current workspace changes and highlight bugs, regressions, risky patterns, and missing tests before merge.".to_string(),
+                workspace_review_prompt(),
                 "current workspace changes".to_string(),
                 Some("Preparing code review request...".to_string()),
                 Some(metadata),
@@ -29758,17 +30056,23 @@ impl ChatWidget<'_> {
             .await
             {
                 Ok(meta_option) => {
-                    if let Some(meta) = meta_option.clone() {
-                        if let Err(e) =
-                            code_core::git_worktree::write_branch_metadata(&worktree, &meta).await
-                        {
-                            tx.send_background_event_with_ticket(
-                                &ticket,
-                                format!("`/branch` — failed to record branch metadata: {e}"),
-                            );
-                        }
-                        branch_metadata = meta_option;
+                    let mut meta =
+                        meta_option.unwrap_or_else(|| code_core::git_worktree::BranchMetadata {
+                            base_branch: current_base_branch.clone(),
+                            ..Default::default()
+                        });
+                    meta.task = task_opt.map(str::to_string);
+                    meta.created_at
+                        .get_or_insert_with(|| chrono::Utc::now().timestamp());
+                    if let Err(e) =
+                        code_core::git_worktree::write_branch_metadata(&worktree, &meta).await
+                    {
+                        tx.send_background_event_with_ticket(
+                            &ticket,
+                            format!("`/branch` — failed to record branch metadata: {e}"),
+                        );
                     }
+                    branch_metadata = Some(meta);
                 }
                 Err(err) => {
                     tx.send_background_event_with_ticket(
@@ -34250,6 +34554,31 @@ impl ChatWidget<'_> {
         });
     }
 
+    /// Raises `notification` in the terminal when `[tui].notifications`
+    /// allows its event type.
+    fn emit_tui_notification(&self, notification: &UserNotification) {
+        if !self.should_emit_tui_notification(&notification.event().to_string()) {
+            return;
+        }
+        self.app_event_tx.send(AppEvent::EmitTuiNotification {
+            title: notification.title().to_string(),
+            body: notification
+                .body()
+                .as_deref()
+                .map(Self::notification_snippet)
+                .filter(|text| !text.is_empty()),
+        });
+    }
+
+    /// Auto Drive runs in the TUI, so its notifications go to the configured
+    /// sinks from here; the core session covers turns and approvals.
+    fn emit_auto_drive_notification(&self, notification: UserNotification) {
+        self.emit_tui_notification(&notification);
+        if self.replay_history_depth == 0 {
+            self.notifier.notify(&notification);
+        }
+    }
+
     fn should_emit_tui_notification(&self, event: &str) -> bool {
         if self.replay_history_depth > 0 {
             return false;
@@ -34320,4 +34649,13 @@ impl ChatWidget<'_> {
             }
         }
     }
+
+    /// Ask the session to restart a running MCP server; the outcome arrives as
+    /// a background or error event.
+    pub(crate) fn restart_mcp_server(&mut self, name: &str) {
+        self.push_background_tail(format!("Restarting MCP server '{name}'…"));
+        self.submit_op(Op::RestartMcpServer {
+            server: name.to_string(),
+        });
+    }
 }
@@ -15,11 +15,13 @@ use chrono::Utc;
 use code_core::config::Config;
 use code_core::config::ConfigOverrides;
 use code_core::config::ConfigToml;
+use code_core::config_types::EditingMode;
 use code_core::history::state::HistoryRecord;
 use code_core::protocol::BackgroundEventEvent;
 use code_core::protocol::Event;
 use code_core::protocol::EventMsg;
 use code_core::protocol::OrderMeta;
+use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use once_cell::sync::Lazy;
 use ratatui::text::Line;
@@ -248,6 +250,23 @@ impl ChatWidgetHarness {
         self.flush_into_widget();
     }
 
+    pub fn paste(&mut self, text: impl Into<String>) {
+        self.chat.handle_paste(text.into());
+        self.flush_into_widget();
+    }
+
+    /// Presses Esc through the same routing the App uses (one step).
+    pub fn send_esc(&mut self) {
+        let route = self.chat.describe_esc_context();
+        self.chat
+            .execute_esc_intent(route.intent, KeyEvent::from(KeyCode::Esc));
+        self.flush_into_widget();
+    }
+
+    pub fn set_editing_mode(&mut self, mode: EditingMode) {
+        self.chat.bottom_pane.set_editing_mode(mode);
+    }
+
     pub(crate) fn drain_events(&self) -> Vec<AppEvent> {
         let mut out = Vec::new();
         while let Ok(ev) = self.events.try_recv() {
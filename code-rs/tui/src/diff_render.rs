@@ -217,6 +217,8 @@ pub(super) fn create_diff_summary_with_width(
         added: usize,
         removed: usize,
         change: FileSummaryKind,
+        /// Permission bits the patch sets, shown so exec-bit changes are visible.
+        mode: Option<u32>,
     }
 
     enum FileSummaryKind {
@@ -295,7 +297,7 @@ pub(super) fn create_diff_summary_with_width(
     let mut files: Vec<FileSummary> = Vec::new();
     for (path, change) in changes.iter() {
         match change {
-            FileChange::Add { content } => {
+            FileChange::Add { content, mode } => {
                 let added = content.lines().count();
                 let empty = added == 0;
                 files.push(FileSummary {
@@ -304,6 +306,7 @@ pub(super) fn create_diff_summary_with_width(
                     added,
                     removed: 0,
                     change: FileSummaryKind::Add { empty },
+                    mode: *mode,
                 });
             }
             FileChange::Delete => {
@@ -317,11 +320,13 @@ pub(super) fn create_diff_summary_with_width(
                     added: 0,
                     removed,
                     change: FileSummaryKind::Delete { removed_unknown },
+                    mode: None,
                 });
             }
             FileChange::Update {
                 unified_diff,
                 move_path,
+                mode,
                 ..
             } => {
                 let tally = count_from_unified(unified_diff);
@@ -351,6 +356,7 @@ pub(super) fn create_diff_summary_with_width(
                         binary: binary_change,
                         metadata_only,
                     },
+                    mode: *mode,
                 });
             }
         }
@@ -406,6 +412,12 @@ pub(super) fn create_diff_summary_with_width(
             Style::default().add_modifier(Modifier::DIM),
         ));
         let dim_style = Style::default().fg(crate::colors::text_dim());
+        let mode_span = f.mode.map(|mode| {
+            RtSpan::styled(
+                format!(" (mode {mode:o})"),
+                Style::default().fg(crate::colors::warning()),
+            )
+        });
 
         if let FileSummaryKind::Update {
             rename_only: true, ..
@@ -422,6 +434,7 @@ pub(super) fn create_diff_summary_with_width(
                 spans.push(RtSpan::styled(f.original_path.clone(), dim_style));
                 spans.push(RtSpan::styled(" (renamed)".to_string(), dim_style));
             }
+            spans.extend(mode_span);
             out.push(RtLine::from(spans));
             continue;
         }
@@ -446,7 +459,9 @@ pub(super) fn create_diff_summary_with_width(
                 no_content_change: true,
                 ..
             } => {
-                annotation = Some(" (no changes)".to_string());
+                if f.mode.is_none() {
+                    annotation = Some(" (no changes)".to_string());
+                }
                 skip_counts = true;
             }
             FileSummaryKind::Update { binary: true, .. } => {
@@ -470,6 +485,7 @@ pub(super) fn create_diff_summary_with_width(
         if let Some(extra) = annotation {
             spans.push(RtSpan::styled(extra, dim_style));
         }
+        spans.extend(mode_span);
 
         if skip_counts {
             out.push(RtLine::from(spans));
@@ -570,7 +586,7 @@ fn render_patch_details_with_width(
             ]));
         }
         match change {
-            FileChange::Add { content } => {
+            FileChange::Add { content, .. } => {
                 for (i, raw) in content.lines().enumerate() {
                     let ln = i + 1;
                     let cleaned = sanitize_diff_text(raw);
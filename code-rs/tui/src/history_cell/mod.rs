@@ -40,6 +40,7 @@ use code_common::create_config_summary_entries;
 use code_common::elapsed::format_duration;
 use code_core::config::Config;
 use code_core::config_types::ReasoningEffort;
+use code_core::exec_limits::LIMIT_EXCEEDED_PREFIX;
 use code_core::parse_command::ParsedCommand;
 use code_core::plan_tool::PlanItemArg;
 use code_core::plan_tool::StepStatus;
@@ -1991,9 +1992,19 @@ fn output_lines(
         if !lines.is_empty() {
             lines.push(Line::from(""));
         }
+        // Resource limit failures carry their own summary line; use it as the
+        // title instead of the bare exit code.
+        let limit_note = stderr
+            .lines()
+            .rev()
+            .find(|line| line.starts_with(LIMIT_EXCEEDED_PREFIX));
         if !is_streaming_preview {
+            let title = match limit_note {
+                Some(note) => format!("Error ({note})"),
+                None => format!("Error (exit code {exit_code})"),
+            };
             lines.push(Line::styled(
-                format!("Error (exit code {exit_code})"),
+                title,
                 Style::default().fg(crate::colors::error()),
             ));
         }
@@ -2006,7 +2017,10 @@ fn output_lines(
                 debug_markers: false,
             },
         );
-        for line in stderr_norm.lines().filter(|line| !line.is_empty()) {
+        for line in stderr_norm
+            .lines()
+            .filter(|line| !line.is_empty() && Some(*line) != limit_note)
+        {
             lines.push(ansi_escape_line(line).style(Style::default().fg(crate::colors::error())));
         }
     }
@@ -5655,6 +5669,7 @@ fn patch_changes_are_noop(changes: &HashMap<PathBuf, FileChange>) -> bool {
             FileChange::Update {
                 move_path: None,
                 unified_diff,
+                mode: None,
                 ..
             } => {
                 !diff_contains_line_edits(unified_diff)
@@ -5670,6 +5685,7 @@ fn file_change_is_rename_only(change: &FileChange) -> bool {
         FileChange::Update {
             move_path: Some(_),
             unified_diff,
+            mode: None,
             ..
         } => {
             !diff_contains_line_edits(unified_diff)
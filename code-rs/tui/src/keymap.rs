@@ -0,0 +1,357 @@
+//! Remappable key bindings for composer actions (`[tui.keymap]`).
+//!
+//! Each action has default chords that match the historical hard-coded keys,
+//! except reasoning toggling, which gave up Ctrl+R to reverse history search.
+//! Configuring an action replaces its defaults, and a chord claimed by a
+//! configured action is removed from every other action's defaults so the
+//! user's choice always wins.
+
+use code_core::config_types::KeyBindings;
+use code_core::config_types::TuiKeymap;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use strum::IntoEnumIterator;
+use strum_macros::Display;
+use strum_macros::EnumIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum KeymapAction {
+    Submit,
+    Newline,
+    HistoryUp,
+    HistoryDown,
+    FileSearch,
+    HistorySearch,
+    ToggleReasoning,
+}
+
+impl KeymapAction {
+    fn default_chords(self) -> &'static [&'static str] {
+        match self {
+            Self::Submit => &["enter"],
+            Self::Newline => &["shift-enter", "alt-enter", "ctrl-j"],
+            Self::HistoryUp => &["shift-up"],
+            Self::HistoryDown => &["shift-down"],
+            Self::FileSearch => &["tab"],
+            Self::HistorySearch => &["ctrl-r"],
+            Self::ToggleReasoning => &["alt-r", "ctrl-shift-r"],
+        }
+    }
+
+    fn configured(self, keymap: &TuiKeymap) -> Option<&KeyBindings> {
+        match self {
+            Self::Submit => keymap.submit.as_ref(),
+            Self::Newline => keymap.newline.as_ref(),
+            Self::HistoryUp => keymap.history_up.as_ref(),
+            Self::HistoryDown => keymap.history_down.as_ref(),
+            Self::FileSearch => keymap.file_search.as_ref(),
+            Self::HistorySearch => keymap.history_search.as_ref(),
+            Self::ToggleReasoning => keymap.toggle_reasoning.as_ref(),
+        }
+    }
+}
+
+/// A single key chord such as `ctrl-r` or `shift-enter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub(crate) fn parse(chord: &str) -> Result<Self, String> {
+        let chord = chord.trim();
+        let mut parts: Vec<&str> = chord.split(['-', '+']).collect();
+        // `ctrl--` / `ctrl-+` bind the separator characters themselves.
+        if parts.len() >= 2 && parts[parts.len() - 1].is_empty() {
+            parts.pop();
+            if let Some(last) = parts.last_mut()
+                && last.is_empty()
+            {
+                *last = if chord.ends_with('+') { "+" } else { "-" };
+            }
+        }
+        let Some((key, modifier_names)) = parts.split_last() else {
+            return Err(format!("empty key binding `{chord}`"));
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                "super" | "cmd" => KeyModifiers::SUPER,
+                other => return Err(format!("unknown modifier `{other}` in `{chord}`")),
+            };
+        }
+
+        let code = match key.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "esc" | "escape" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                        Some(n @ 1..=24) => KeyCode::F(n),
+                        _ => return Err(format!("unknown key `{key}` in `{chord}`")),
+                    },
+                }
+            }
+        };
+        Ok(Self::normalized(code, modifiers))
+    }
+
+    /// Folds the different ways terminals report the same chord: uppercase
+    /// letters vs. Shift, and BackTab vs. Shift+Tab.
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if c.is_ascii_uppercase() => Self {
+                code: KeyCode::Char(c.to_ascii_lowercase()),
+                modifiers: modifiers | KeyModifiers::SHIFT,
+            },
+            KeyCode::BackTab => Self {
+                code: KeyCode::Tab,
+                modifiers: modifiers | KeyModifiers::SHIFT,
+            },
+            code => Self { code, modifiers },
+        }
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        if event.kind == KeyEventKind::Release {
+            return false;
+        }
+        *self == Self::normalized(event.code, event.modifiers)
+    }
+
+    /// Human-readable form for footer hints, e.g. `Ctrl+R`.
+    pub(crate) fn display(&self) -> String {
+        let mut label = String::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SUPER, "Cmd+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(name);
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => label.push_str("Space"),
+            KeyCode::Char(c) => label.push(c.to_ascii_uppercase()),
+            KeyCode::F(n) => label.push_str(&format!("F{n}")),
+            code => label.push_str(&format!("{code:?}")),
+        }
+        label
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Keymap {
+    bindings: Vec<(KeymapAction, Vec<KeyBinding>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&TuiKeymap::default())
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap, skipping (and logging) chords that fail to parse.
+    pub(crate) fn from_config(config: &TuiKeymap) -> Self {
+        let parse_all = |action: KeymapAction, chords: &[String]| -> Vec<KeyBinding> {
+            chords
+                .iter()
+                .filter_map(|chord| match KeyBinding::parse(chord) {
+                    Ok(binding) => Some(binding),
+                    Err(err) => {
+                        tracing::warn!("ignoring [tui.keymap] {action} binding: {err}");
+                        None
+                    }
+                })
+                .collect()
+        };
+
+        let configured: Vec<(KeymapAction, Vec<KeyBinding>)> = KeymapAction::iter()
+            .filter_map(|action| {
+                action
+                    .configured(config)
+                    .map(|bindings| (action, parse_all(action, bindings.chords())))
+            })
+            .collect();
+        let claimed: Vec<KeyBinding> = configured
+            .iter()
+            .flat_map(|(_, bindings)| bindings.iter().copied())
+            .collect();
+
+        let bindings = KeymapAction::iter()
+            .map(|action| {
+                if let Some((_, bindings)) = configured.iter().find(|(a, _)| *a == action) {
+                    return (action, bindings.clone());
+                }
+                let defaults = action
+                    .default_chords()
+                    .iter()
+                    .filter_map(|chord| KeyBinding::parse(chord).ok())
+                    .filter(|binding| !claimed.contains(binding))
+                    .collect();
+                (action, defaults)
+            })
+            .collect();
+        Self { bindings }
+    }
+
+    /// The action bound to `event`, if any.
+    pub(crate) fn action_for(&self, event: &KeyEvent) -> Option<KeymapAction> {
+        self.bindings
+            .iter()
+            .find(|(_, bindings)| bindings.iter().any(|binding| binding.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    pub(crate) fn matches(&self, action: KeymapAction, event: &KeyEvent) -> bool {
+        self.action_for(event) == Some(action)
+    }
+
+    /// Label of the first chord bound to `action`, for footer hints.
+    pub(crate) fn hint(&self, action: KeymapAction) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, bindings)| bindings.first())
+            .map(KeyBinding::display)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn defaults_match_builtin_keys() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(KeymapAction::Submit)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Enter, KeyModifiers::SHIFT)),
+            Some(KeymapAction::Newline)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Up, KeyModifiers::SHIFT)),
+            Some(KeymapAction::HistoryUp)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            Some(KeymapAction::HistorySearch)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('r'), KeyModifiers::ALT)),
+            Some(KeymapAction::ToggleReasoning)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('r'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            keymap.hint(KeymapAction::ToggleReasoning).as_deref(),
+            Some("Alt+R")
+        );
+    }
+
+    #[test]
+    fn configured_bindings_replace_and_claim_defaults() {
+        let config = TuiKeymap {
+            submit: Some(KeyBindings::Many(vec![
+                "ctrl-enter".to_string(),
+                "ctrl-s".to_string(),
+            ])),
+            newline: Some(KeyBindings::One("enter".to_string())),
+            toggle_reasoning: Some(KeyBindings::One("ctrl-shift-r".to_string())),
+            ..TuiKeymap::default()
+        };
+        let keymap = Keymap::from_config(&config);
+
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(KeymapAction::Newline)
+        );
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('s'), KeyModifiers::CONTROL)),
+            Some(KeymapAction::Submit)
+        );
+        // Shift+Enter is no longer a default once newline is configured.
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Enter, KeyModifiers::SHIFT)),
+            None
+        );
+        // Terminals report Ctrl+Shift+R as an uppercase letter.
+        assert!(keymap.matches(
+            KeymapAction::ToggleReasoning,
+            &key(
+                KeyCode::Char('R'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )
+        ));
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            Some(KeymapAction::HistorySearch)
+        );
+    }
+
+    #[test]
+    fn reasoning_toggle_can_reclaim_ctrl_r() {
+        let config = TuiKeymap {
+            toggle_reasoning: Some(KeyBindings::One("ctrl-r".to_string())),
+            ..TuiKeymap::default()
+        };
+        let keymap = Keymap::from_config(&config);
+
+        assert_eq!(
+            keymap.action_for(&key(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            Some(KeymapAction::ToggleReasoning)
+        );
+        assert_eq!(keymap.hint(KeymapAction::HistorySearch), None);
+    }
+
+    #[test]
+    fn parses_chords() {
+        assert_eq!(KeyBinding::parse("Shift+Tab"), KeyBinding::parse("backtab"));
+        assert_eq!(
+            KeyBinding::parse("ctrl--").map(|b| b.display()),
+            Ok("Ctrl+-".to_string())
+        );
+        assert_eq!(
+            KeyBinding::parse("alt-f5").map(|b| b.display()),
+            Ok("Alt+F5".to_string())
+        );
+        assert!(KeyBinding::parse("hyper-x").is_err());
+        assert!(KeyBinding::parse("ctrl-nope").is_err());
+    }
+}
@@ -74,6 +74,7 @@ mod header_wave;
 mod history;
 mod history_cell;
 mod insert_history;
+mod keymap;
 pub mod live_wrap;
 mod markdown;
 mod markdown_render;
@@ -1197,15 +1198,19 @@ fn determine_repo_trust_state(
             if !workspace_write.allow_git_writes {
                 // Maintain the historical networking behaviour from DangerFullAccess: allow outbound
                 // network even when we pivot into WorkspaceWrite solely to protect `.git`.
+                // An allowlist is an explicit request for filtered networking.
                 let network_access = if workspace_write.network_access {
                     true
                 } else {
                     !workspace_write_network_access_explicit
+                        && workspace_write.network_allowlist.is_empty()
                 };
                 config.approval_policy = AskForApproval::Never;
                 config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
                     writable_roots: workspace_write.writable_roots.clone(),
                     network_access,
+                    network_allowlist: workspace_write.network_allowlist.clone(),
+                    deny_read: config_toml.sandbox_deny_read.clone(),
                     exclude_tmpdir_env_var: workspace_write.exclude_tmpdir_env_var,
                     exclude_slash_tmp: workspace_write.exclude_slash_tmp,
                     allow_git_writes: workspace_write.allow_git_writes,
@@ -1215,7 +1220,21 @@ fn determine_repo_trust_state(
         }
 
         config.approval_policy = AskForApproval::Never;
-        config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        if config_toml.sandbox_deny_read.is_empty() {
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        } else {
+            // Read denials protect secrets even in trusted projects, so keep a
+            // sandbox that is otherwise as open as DangerFullAccess.
+            config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
+                writable_roots: Vec::new(),
+                network_access: true,
+                network_allowlist: Vec::new(),
+                deny_read: config_toml.sandbox_deny_read.clone(),
+                exclude_tmpdir_env_var: false,
+                exclude_slash_tmp: false,
+                allow_git_writes: true,
+            };
+        }
         Ok(false)
     } else {
         // if none of the above conditions are met (and no per‑project overrides), show the trust screen
@@ -27,6 +27,26 @@ pub fn list_sessions_for_cwd(
     cwd: &Path,
     code_home: &Path,
     exclude_path: Option<&Path>,
+) -> Vec<ResumeCandidate> {
+    query_sessions_for_cwd(cwd, code_home, exclude_path, None)
+}
+
+/// Return sessions for the cwd whose messages or commands match `text`,
+/// most relevant first.
+pub fn search_sessions_for_cwd(
+    cwd: &Path,
+    code_home: &Path,
+    exclude_path: Option<&Path>,
+    text: &str,
+) -> Vec<ResumeCandidate> {
+    query_sessions_for_cwd(cwd, code_home, exclude_path, Some(text.to_string()))
+}
+
+fn query_sessions_for_cwd(
+    cwd: &Path,
+    code_home: &Path,
+    exclude_path: Option<&Path>,
+    text: Option<String>,
 ) -> Vec<ResumeCandidate> {
     const MAX_RESULTS: usize = 200;
 
@@ -48,6 +68,7 @@ pub fn list_sessions_for_cwd(
             include_archived: false,
             include_deleted: false,
             limit: Some(MAX_RESULTS),
+            text,
         };
 
         match catalog.query(&query).await {
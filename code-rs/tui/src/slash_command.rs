@@ -76,6 +76,7 @@ pub enum SlashCommand {
     Auto,
     Branch,
     Merge,
+    Worktrees,
     Push,
     Validation,
     Mcp,
@@ -127,6 +128,7 @@ impl SlashCommand {
                 "work in an isolated /branch then /merge when done (great for parallel work)"
             }
             SlashCommand::Merge => "merge current worktree branch back to default",
+            SlashCommand::Worktrees => "list branch worktrees (task, age, ahead/behind, dirty)",
             SlashCommand::Push => "commit, push, and monitor workflows",
             SlashCommand::Validation => "control validation harness (status/on/off)",
             SlashCommand::Mcp => "manage MCP servers",
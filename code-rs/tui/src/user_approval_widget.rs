@@ -48,6 +48,12 @@ pub(crate) enum ApprovalRequest {
         id: u64,
         command: String,
     },
+    /// An MCP server wants to run a completion through the session's model.
+    McpSampling {
+        id: String,
+        server: String,
+        preview: String,
+    },
 }
 
 #[derive(Clone)]
@@ -150,12 +156,32 @@ impl UserApprovalWidget<'_> {
                 ];
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
+            ApprovalRequest::McpSampling {
+                server, preview, ..
+            } => {
+                let mut contents = vec![
+                    Line::from(""),
+                    Line::from(vec![
+                        "? ".fg(crate::colors::info()),
+                        "MCP server ".bold(),
+                        server.clone().bold(),
+                        " wants to run a model completion".bold(),
+                    ]),
+                    Line::from(""),
+                ];
+                if !preview.is_empty() {
+                    contents.push(Line::from(preview.clone().dim()));
+                    contents.push(Line::from(""));
+                }
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
         };
 
         let select_options = match &approval_request {
             ApprovalRequest::Exec { command, .. } => build_exec_select_options(command),
             ApprovalRequest::ApplyPatch { .. } => build_patch_select_options(),
             ApprovalRequest::TerminalCommand { .. } => build_terminal_select_options(),
+            ApprovalRequest::McpSampling { .. } => build_sampling_select_options(),
         };
 
         Self {
@@ -280,6 +306,14 @@ impl UserApprovalWidget<'_> {
             ApprovalRequest::ApplyPatch { .. } => {
                 format!("patch approval decision: {decision:?}")
             }
+            ApprovalRequest::McpSampling { server, .. } => match decision {
+                ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+                    format!("approved: model completion for MCP server {server}")
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    format!("not approved: model completion for MCP server {server}")
+                }
+            },
             ApprovalRequest::TerminalCommand { .. } => {
                 unreachable!("terminal approvals handled earlier")
             }
@@ -322,6 +356,14 @@ impl UserApprovalWidget<'_> {
                 id: id.clone(),
                 decision,
             },
+            // Declining a sampling request must not abort the agent's turn.
+            ApprovalRequest::McpSampling { id, .. } => Op::McpSamplingApproval {
+                id: id.clone(),
+                decision: match decision {
+                    ReviewDecision::Abort => ReviewDecision::Denied,
+                    other => other,
+                },
+            },
             ApprovalRequest::TerminalCommand { .. } => {
                 unreachable!("terminal approvals handled earlier")
             }
@@ -491,6 +533,23 @@ fn build_patch_select_options() -> Vec<SelectOption> {
     ]
 }
 
+fn build_sampling_select_options() -> Vec<SelectOption> {
+    vec![
+        SelectOption {
+            label: "Yes".to_string(),
+            description: "Send the request to the model and return the reply".to_string(),
+            hotkey: KeyCode::Char('y'),
+            action: SelectAction::ApproveOnce,
+        },
+        SelectOption {
+            label: "No".to_string(),
+            description: "Reject the request".to_string(),
+            hotkey: KeyCode::Char('n'),
+            action: SelectAction::Abort,
+        },
+    ]
+}
+
 fn build_terminal_select_options() -> Vec<SelectOption> {
     vec![
         SelectOption {
@@ -46,8 +46,8 @@ fn bottom_spacer_collapsed_vs_expanded_reasoning_120x40() {
     assert_viewport_invariants(&expanded_raw, 40);
     let expanded = normalize_output(expanded_raw);
 
-    // Toggle reasoning visibility (Ctrl+R)
-    harness.send_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+    // Toggle reasoning visibility (Alt+R; Ctrl+R now opens history search)
+    harness.send_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT));
     let collapsed_raw = render_chat_widget_to_vt100(&mut harness, 120, 40);
     assert_viewport_invariants(&collapsed_raw, 40);
     let collapsed = normalize_output(collapsed_raw);
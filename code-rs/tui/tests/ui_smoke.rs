@@ -804,6 +804,7 @@ fn smoke_approval_flow() {
         PathBuf::from("sample.txt"),
         FileChange::Add {
             content: "Hello world".into(),
+            mode: None,
         },
     );
 
@@ -841,7 +842,7 @@ fn smoke_approval_flow() {
         .get(&PathBuf::from("sample.txt"))
         .expect("change for sample.txt present");
     match change {
-        FileChange::Add { content } => {
+        FileChange::Add { content, .. } => {
             assert_eq!(
                 content, "Hello world",
                 "patch change should include file contents"
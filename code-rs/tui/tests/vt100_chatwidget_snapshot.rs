@@ -8,6 +8,7 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 
 use code_core::codex::compact::COMPACTION_CHECKPOINT_MESSAGE;
+use code_core::config_types::EditingMode;
 use code_core::protocol::AgentInfo;
 use code_core::protocol::AgentMessageDeltaEvent;
 use code_core::protocol::AgentMessageEvent;
@@ -1257,6 +1258,104 @@ fn final_history_line_visible_across_viewports() {
     }
 }
 
+fn type_keys(harness: &mut ChatWidgetHarness, keys: &str) {
+    for ch in keys.chars() {
+        harness.send_key(make_key(KeyCode::Char(ch), KeyModifiers::NONE));
+    }
+}
+
+#[test]
+fn vi_mode_composer_edits_and_shows_mode() {
+    init_tracing_once();
+    let mut harness = ChatWidgetHarness::new();
+    harness.set_editing_mode(EditingMode::Vi);
+
+    type_keys(&mut harness, "fix the bug now");
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 80, 20));
+    assert!(frame.contains("fix the bug now"), "{frame}");
+    assert!(!frame.contains("NORMAL"), "starts in insert mode:\n{frame}");
+
+    harness.send_esc();
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 80, 20));
+    assert!(frame.contains("NORMAL"), "Esc enters normal mode:\n{frame}");
+    assert!(
+        frame.contains("fix the bug now"),
+        "Esc keeps the draft:\n{frame}"
+    );
+
+    // Back two words onto "bug", change it, and return to normal mode.
+    type_keys(&mut harness, "bbciwissue");
+    harness.send_esc();
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 80, 20));
+    assert!(frame.contains("fix the issue now"), "{frame}");
+
+    type_keys(&mut harness, "0ve");
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 80, 20));
+    assert!(frame.contains("VISUAL"), "{frame}");
+
+    type_keys(&mut harness, "d");
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 80, 20));
+    assert!(frame.contains(" the issue now"), "{frame}");
+    assert!(!frame.contains("fix the issue now"), "{frame}");
+
+    type_keys(&mut harness, "dd");
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 80, 20));
+    assert!(
+        !frame.contains("the issue now"),
+        "dd clears the line:\n{frame}"
+    );
+
+    type_keys(&mut harness, "u");
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 80, 20));
+    assert!(
+        frame.contains(" the issue now"),
+        "u restores the line:\n{frame}"
+    );
+}
+
+#[test]
+fn ctrl_r_searches_prompt_history() {
+    init_tracing_once();
+    let mut harness = ChatWidgetHarness::new();
+    // Paste rather than type so Enter is not swallowed by paste-burst detection.
+    for prompt in ["fix the parser", "write docs"] {
+        harness.paste(prompt);
+        harness.send_key(make_key(KeyCode::Enter, KeyModifiers::NONE));
+    }
+    harness.paste("draft");
+
+    harness.send_key(make_key(KeyCode::Char('r'), KeyModifiers::CONTROL));
+    type_keys(&mut harness, "pars");
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 100, 20));
+    assert!(frame.contains("reverse-i-search: pars"), "{frame}");
+    assert!(frame.contains("1/1"), "{frame}");
+
+    harness.send_esc();
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 100, 20));
+    assert!(!frame.contains("reverse-i-search"), "Esc cancels:\n{frame}");
+    assert!(frame.contains("draft"), "Esc restores the draft:\n{frame}");
+
+    harness.send_key(make_key(KeyCode::Char('r'), KeyModifiers::CONTROL));
+    type_keys(&mut harness, "zzz");
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 100, 20));
+    assert!(frame.contains("failing reverse-i-search: zzz"), "{frame}");
+
+    for _ in 0..3 {
+        harness.send_key(make_key(KeyCode::Backspace, KeyModifiers::NONE));
+    }
+    type_keys(&mut harness, "docs");
+    harness.send_key(make_key(KeyCode::Enter, KeyModifiers::NONE));
+    let frame = normalize_output(render_chat_widget_to_vt100(&mut harness, 100, 20));
+    assert!(
+        !frame.contains("reverse-i-search"),
+        "Enter accepts:\n{frame}"
+    );
+    assert!(
+        !frame.contains("draft"),
+        "the match replaces the draft:\n{frame}"
+    );
+}
+
 #[test]
 fn baseline_multiline_formatting() {
     let mut harness = ChatWidgetHarness::new();
@@ -1764,6 +1863,7 @@ fn agent_run_grouped_desired_layout() {
                     error: None,
                     elapsed_ms: Some(29_000),
                     token_count: Some(12_400),
+                    token_usage: None,
                 },
                 AgentInfo {
                     id: "doc-writer".into(),
@@ -1776,6 +1876,7 @@ fn agent_run_grouped_desired_layout() {
                     error: None,
                     elapsed_ms: Some(4_500),
                     token_count: None,
+                    token_usage: None,
                 },
             ],
             context: Some("regression sweep".into()),
@@ -1861,6 +1962,7 @@ fn agent_run_grouped_plain_tool_name() {
                 error: None,
                 elapsed_ms: Some(18_750),
                 token_count: Some(8_900),
+                token_usage: None,
             }],
             context: Some("regression sweep".into()),
             task: Some("Ship bugfix patch".into()),
@@ -1940,6 +2042,7 @@ fn agents_terminal_overlay_full_details() {
                     error: None,
                     elapsed_ms: Some(4_200),
                     token_count: Some(3_500),
+                    token_usage: None,
                 },
                 AgentInfo {
                     id: "docs-sweep-codex-mini".into(),
@@ -1952,6 +2055,7 @@ fn agents_terminal_overlay_full_details() {
                     error: None,
                     elapsed_ms: Some(1_100),
                     token_count: None,
+                    token_usage: None,
                 },
             ],
             context: Some("Focus on October 2025 product changes".into()),
@@ -1986,6 +2090,7 @@ fn agents_terminal_overlay_full_details() {
                     error: None,
                     elapsed_ms: Some(12_700),
                     token_count: Some(7_200),
+                    token_usage: None,
                 },
                 AgentInfo {
                     id: "docs-sweep-codex-mini".into(),
@@ -1998,6 +2103,7 @@ fn agents_terminal_overlay_full_details() {
                     error: Some("Timed out waiting for GitHub diff".into()),
                     elapsed_ms: Some(9_300),
                     token_count: Some(4_900),
+                    token_usage: None,
                 },
             ],
             context: Some("Focus on October 2025 product changes".into()),
@@ -2056,6 +2162,7 @@ fn plan_agent_keeps_single_aggregate_block() {
                 error: None,
                 elapsed_ms: Some(12_300),
                 token_count: Some(6_100),
+                token_usage: None,
             }],
             context: Some("/plan coordination".into()),
             task: Some("Draft implementation plan".into()),
@@ -2226,6 +2333,7 @@ fn agent_status_missing_batch_displays_error() {
                 error: None,
                 elapsed_ms: Some(8_000),
                 token_count: Some(3_200),
+                token_usage: None,
             }],
             context: Some("debug orphan".into()),
             task: Some("Investigate logs".into()),
@@ -2354,6 +2462,7 @@ fn agent_parallel_batches_do_not_duplicate_cells() {
                     error: None,
                     elapsed_ms: Some(4_000),
                     token_count: Some(2_400),
+                    token_usage: None,
                 },
                 AgentInfo {
                     id: "burger-agent".into(),
@@ -2366,6 +2475,7 @@ fn agent_parallel_batches_do_not_duplicate_cells() {
                     error: None,
                     elapsed_ms: Some(3_200),
                     token_count: Some(1_900),
+                    token_usage: None,
                 },
             ],
             context: Some("Parallel meal planning".into()),
@@ -2397,6 +2507,7 @@ fn agent_parallel_batches_do_not_duplicate_cells() {
                     error: None,
                     elapsed_ms: Some(9_500),
                     token_count: Some(4_200),
+                    token_usage: None,
                 },
                 AgentInfo {
                     id: "burger-agent".into(),
@@ -2409,6 +2520,7 @@ fn agent_parallel_batches_do_not_duplicate_cells() {
                     error: None,
                     elapsed_ms: Some(8_100),
                     token_count: Some(3_600),
+                    token_usage: None,
                 },
             ],
             context: Some("Parallel meal planning".into()),